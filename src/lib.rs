@@ -0,0 +1,46 @@
+//! zshrcman manages dotfiles across multiple devices using Git synchronization.
+//!
+//! The `zshrcman` binary is a thin CLI over this library; everything it does is
+//! available to other Rust programs, e.g. a provisioning tool that wants to
+//! install zshrcman groups without shelling out.
+//!
+//! The main entry points are:
+//!
+//! - [`ConfigStore`]: loads and persists `config.toml` and group definitions
+//! - [`Installer`]: the trait implemented by package backends (brew, npm, ...)
+//! - [`ProfileService`]: create, switch and delete profiles
+//! - [`InstallManager`]: installs the enabled groups of a config
+//!
+//! ```no_run
+//! use zshrcman::{ConfigStore, InstallManager, ProfileService};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let mut profiles = ProfileService::new(ConfigStore::new()?);
+//! if profiles.get("ci").is_none() {
+//!     profiles.create("ci", None)?;
+//! }
+//! profiles.switch("ci")?;
+//!
+//! let mut installer = InstallManager::new(ConfigStore::new()?);
+//! installer.install(true)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Interactive prompts honour [`modules::prompt::set_non_interactive`], which
+//! embedders will usually want to turn on.
+
+pub mod models;
+pub mod modules;
+
+#[cfg(test)]
+mod tests;
+
+pub use modules::config::ConfigManager;
+pub use modules::install::InstallManager;
+pub use modules::installer::{CommandInstaller, Installer, InstallerRegistry};
+pub use modules::profile_service::ProfileService;
+
+/// The configuration store. This is the same type the CLI calls
+/// `ConfigManager`; the alias is the name used by the public API.
+pub type ConfigStore = ConfigManager;
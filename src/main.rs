@@ -1,26 +1,159 @@
-mod models;
-mod modules;
-
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use colored::Colorize;
-use modules::{
+use zshrcman::models::{Alias, Config, ConflictStrategy, InstallScope, OsType, RemovalStrategy, ZshPlugin};
+use zshrcman::modules::{
     alias::AliasManager,
-    config::ConfigManager,
-    git_mgr::GitManager,
-    init::InitManager,
-    install::InstallManager,
+    alias_usage,
+    auto_switch,
+    bench,
+    bootstrap::{BootstrapManager, BootstrapOptions},
+    config::{ConfigLock, ConfigManager},
+    daemon::{self, Daemon},
+    diagnostics::{self, DiagnosticsBundle},
+    disk_usage,
+    editor::Editor,
+    environment::{EnvironmentManager, ShellType},
+    errors::{self, ErrorKind, ResultExt},
+    events,
+    external,
+    facts::Facts,
+    features::{self, Feature},
+    fleet,
+    gen_docs,
+    git_mgr::{self, GitManager, SyncMode},
+    groups,
+    guided,
+    history::{History, Snapshot},
+    hooks::{self, Event},
+    init::{InitManager, InitOptions},
+    install::{self, InstallManager},
+    installer::{Installer, InstallerRegistry},
+    notifications,
+    offline,
+    oplog::{self, Operation, OperationLog},
+    output::{self, Verbosity},
+    packages,
+    plugin,
+    preflight,
+    profile_service::ProfileService,
+    prompt,
+    repos,
+    search,
+    secrets::SecretRef,
+    session,
+    shell_env,
+    sources,
     state_manager::InstallationStateManager,
-    profile_switcher::ProfileSwitcher,
+    stats::RepoStats,
+    suggest,
+    sync_timer::{self, SyncTimer},
+    test_run::{self, Outcome, TestRun},
+    theme,
+    tidy,
+    tour::TourManager,
+    validate::{self, Severity, Validator},
+    verify,
+    which,
+    zsh_plugins::{self, Lockfile},
 };
-use strsim::jaro_winkler;
 
 #[derive(Parser)]
 #[command(name = "zshrcman")]
 #[command(author, version, about = "A Rust-based Zsh/dotfiles manager", long_about = None)]
 struct Cli {
+    #[arg(
+        short = 'y',
+        long = "yes",
+        visible_alias = "non-interactive",
+        global = true,
+        env = "ZSHRCMAN_YES",
+        value_parser = clap::builder::FalseyValueParser::new(),
+        help = "Never prompt; accept the default answer for every question"
+    )]
+    yes: bool,
+    
+    #[arg(
+        long,
+        global = true,
+        env = "ZSHRCMAN_OFFLINE",
+        value_parser = clap::builder::FalseyValueParser::new(),
+        help = "Skip fetching, pushing and webhooks; work from what was fetched last"
+    )]
+    offline: bool,
+    
+    #[arg(
+        long = "enable-feature",
+        value_name = "FEATURE",
+        global = true,
+        env = "ZSHRCMAN_FEATURES",
+        value_delimiter = ',',
+        help = "Enable an experimental feature for this run (repeatable)"
+    )]
+    enable_feature: Vec<String>,
+    
+    #[arg(
+        long,
+        value_name = "DIR",
+        global = true,
+        env = "ZSHRCMAN_HOME",
+        help = "Home directory to deploy into (defaults to $HOME)"
+    )]
+    home: Option<std::path::PathBuf>,
+    
+    #[arg(
+        long,
+        value_name = "SHELL",
+        global = true,
+        env = "ZSHRCMAN_SHELL",
+        help = "Shell whose startup files to manage, e.g. zsh (defaults to $SHELL)"
+    )]
+    shell: Option<String>,
+    
+    #[arg(
+        long,
+        value_name = "PATH",
+        global = true,
+        env = "ZSHRCMAN_CONFIG",
+        help = "config.toml to use, e.g. for a separate work or sandbox context"
+    )]
+    config: Option<std::path::PathBuf>,
+    
+    #[arg(short, long, global = true, conflicts_with = "verbose", help = "Only print errors and results")]
+    quiet: bool,
+    
+    #[arg(short, long, global = true, help = "Show the full output of package managers")]
+    verbose: bool,
+    
+    #[arg(
+        long,
+        global = true,
+        env = "ZSHRCMAN_NO_WAIT",
+        value_parser = clap::builder::FalseyValueParser::new(),
+        help = "Fail instead of waiting when another zshrcman is changing the config"
+    )]
+    no_wait: bool,
+    
+    #[arg(
+        long,
+        value_name = "FD",
+        global = true,
+        conflicts_with = "events_file",
+        help = "Write JSON-lines events to this open file descriptor (experimental: event-log)"
+    )]
+    events_fd: Option<i32>,
+    
+    #[arg(
+        long,
+        value_name = "PATH",
+        global = true,
+        env = "ZSHRCMAN_EVENTS_FILE",
+        help = "Append JSON-lines events to this file (experimental: event-log)"
+    )]
+    events_file: Option<std::path::PathBuf>,
+    
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
@@ -28,19 +161,104 @@ enum Commands {
     Init {
         #[arg(long, help = "Force re-initialization even if already initialized")]
         force: bool,
+        
+        #[arg(long, env = "ZSHRCMAN_REPO", help = "Remote Git repository URL")]
+        repo: Option<String>,
+        
+        #[arg(long, env = "ZSHRCMAN_DEVICE", help = "Device name (uses or creates device/<name>)")]
+        device: Option<String>,
+        
+        #[arg(
+            long,
+            env = "ZSHRCMAN_GROUPS",
+            value_delimiter = ',',
+            help = "Comma-separated groups to enable"
+        )]
+        groups: Option<Vec<String>>,
+    },
+    
+    #[command(about = "Init, sync and install in one non-interactive step")]
+    Bootstrap {
+        #[arg(help = "Remote Git repository URL")]
+        repo: String,
+        
+        #[arg(long, help = "Device name (uses or creates device/<name>)")]
+        device: String,
+        
+        #[arg(long, help = "Profile to create if needed and switch to")]
+        profile: Option<String>,
+        
+        #[arg(long, value_delimiter = ',', help = "Comma-separated groups to enable")]
+        groups: Option<Vec<String>>,
     },
     
     Install {
-        #[arg(long, help = "Install all groups without prompting")]
+        #[arg(long, help = "Install all groups without prompting, including unchanged ones")]
         all: bool,
     },
     
     #[command(name = "remove-all")]
-    RemoveAll,
+    RemoveAll {
+        #[arg(long, help = "Also uninstall pinned packages")]
+        force_pinned: bool,
+    },
+    
+    #[command(about = "Pin a package: never upgrade it, and refuse to remove it unless forced")]
+    Pin {
+        package: String,
+    },
+    
+    #[command(about = "Release a pinned package")]
+    Unpin {
+        package: String,
+    },
     
     Sync {
-        #[arg(long, help = "Force sync even with conflicts")]
+        #[arg(long, help = "Resolve conflicts with repository.force_strategy (default: keep this device's version)")]
         force: bool,
+        
+        #[arg(
+            long,
+            conflicts_with = "force",
+            help = "How to handle files changed on both branches: abort, ours, theirs or interactive"
+        )]
+        strategy: Option<ConflictStrategy>,
+        
+        #[arg(long, conflicts_with = "push_only", help = "Pull and rebase, but don't push")]
+        pull_only: bool,
+        
+        #[arg(long, help = "Push the device branch without pulling first")]
+        push_only: bool,
+        
+        #[arg(long, help = "Show a desktop notification when changes were pulled or the sync failed")]
+        notify: bool,
+        
+        #[arg(long, conflicts_with = "remove_timer", help = "Run `sync --notify` on a schedule with a systemd user timer or launchd agent")]
+        install_timer: bool,
+        
+        #[arg(long, default_value_t = 60, requires = "install_timer", help = "Minutes between scheduled syncs")]
+        every: u64,
+        
+        #[arg(long, help = "Stop and remove the scheduled sync")]
+        remove_timer: bool,
+    },
+    
+    #[command(about = "Show what sync would change: local edits, commits ahead/behind and files")]
+    Diff,
+    
+    #[command(about = "Show every device's last sync, groups and failed installs from their branches")]
+    Fleet,
+    
+    #[command(about = "Fuzzy-find packages, aliases, scripts and groups across all group files")]
+    Search {
+        #[arg(help = "Name or part of one, e.g. kubectx")]
+        term: String,
+    },
+    
+    #[command(about = "Show which groups declare a package, alias or script and whether it's installed and active")]
+    Which {
+        #[arg(help = "Exact package, alias or script name")]
+        name: String,
     },
     
     #[command(subcommand)]
@@ -52,16 +270,180 @@ enum Commands {
     #[command(subcommand)]
     Alias(AliasCommands),
     
+    #[command(subcommand, about = "Manage the zsh plugins of the plugins group")]
+    Plugins(PluginsCommands),
+    
+    #[command(subcommand, about = "Pick the prompt theme, globally or per profile")]
+    Theme(ThemeCommands),
+    
     #[command(subcommand)]
     Profile(ProfileCommands),
     
-    Status,
+    #[command(subcommand)]
+    Config(ConfigCommands),
+    
+    #[command(subcommand, visible_alias = "pkg")]
+    Package(PackageCommands),
+    
+    Status {
+        #[arg(long, help = "Also ask the running daemon what it has done")]
+        live: bool,
+    },
+    
+    #[command(about = "Summarize groups, packages, devices, profiles and install history")]
+    Stats,
+    
+    #[command(about = "Show system facts used by group conditions and templates")]
+    Facts {
+        #[arg(long, help = "Print as JSON")]
+        json: bool,
+    },
+    
+    #[command(about = "Interactive walkthrough in a throwaway sandbox")]
+    Tour,
+    
+    #[command(about = "Check that deployed files, managed blocks, packages, plugins and profile links match the config")]
+    Verify {
+        #[arg(long, help = "Print the drift as JSON")]
+        json: bool,
+    },
+    
+    #[command(about = "Watch the dotfiles repo, auto-commit changes and sync them periodically (experimental)")]
+    Daemon {
+        #[arg(long, default_value_t = 300, help = "Seconds between syncs; 0 only commits")]
+        interval: u64,
+    },
+    
+    #[command(about = "Repair the drift verify finds, changing only what differs from the config")]
+    Apply {
+        #[arg(long, help = "Show what would be repaired without changing anything")]
+        dry_run: bool,
+    },
+    
+    #[command(about = "Bootstrap and install the dotfiles repo in a throwaway Docker container and report each group")]
+    TestRun {
+        #[arg(long, default_value = test_run::DEFAULT_IMAGE, help = "Image to run in")]
+        image: String,
+        
+        #[arg(long, help = "Linux zshrcman binary to run in the container (default: this one)")]
+        binary: Option<std::path::PathBuf>,
+    },
+    
+    #[command(about = "List experimental features and whether they are enabled")]
+    Features,
+    
+    #[command(about = "List installer backends, including discovered plugins")]
+    Installers,
+    
+    #[command(name = "gen-docs", hide = true, about = "Write man pages and a markdown CLI reference")]
+    GenDocs {
+        #[arg(long, default_value = "docs", help = "Directory to write man/*.1 and cli.md into")]
+        out_dir: std::path::PathBuf,
+    },
+    
+    #[command(about = "List external group sources and the groups they provide")]
+    Sources {
+        #[arg(long, help = "Clone or update the sources first")]
+        update: bool,
+    },
+    
+    #[command(about = "Show disk usage of the dotfiles clone, profiles, cache, logs and snapshots")]
+    Du,
+    
+    #[command(about = "Print statements applying the active profile's environment, for eval \"$(zshrcman env)\"")]
+    Env {
+        #[arg(long, value_name = "PROFILE", help = "Profile the shell already has applied; only print what changes")]
+        applied: Option<String>,
+        
+        #[arg(long, help = "Print an rc file snippet that applies profile switches before each prompt")]
+        hook: bool,
+        
+        #[command(subcommand)]
+        command: Option<EnvCommands>,
+    },
+    
+    #[command(subcommand, about = "Switch profiles by directory with .zshrcman-profile files")]
+    AutoSwitch(AutoSwitchCommands),
+    
+    #[command(about = "Start a shell with a profile's environment, leaving the active profile as it is")]
+    Shell {
+        profile: String,
+    },
+    
+    #[command(about = "Time shell startup with and without zshrcman's additions, per fragment and group")]
+    Bench {
+        #[arg(long, default_value_t = 10, help = "Startups to time per shell and fragment")]
+        runs: usize,
+        
+        #[arg(long, default_value_t = 20.0, help = "Flag groups and fragments adding more than this many milliseconds")]
+        slow: f64,
+    },
+    
+    #[command(subcommand, about = "Free disk space used by zshrcman's data")]
+    Clean(CleanCommands),
+    
+    #[command(about = "Recent operations on this machine, oldest first")]
+    Timeline {
+        #[arg(long, help = "Only show operations since an age (12h, 2d, 1w) or a date (2024-05-01)")]
+        since: Option<String>,
+        
+        #[arg(long, short = 'n', default_value_t = 50, help = "Number of operations to show")]
+        limit: usize,
+        
+        #[arg(long, help = "Only show failed operations")]
+        failed: bool,
+    },
+    
+    #[command(about = "Show changes made to config.toml, newest first")]
+    History {
+        #[arg(long, short = 'n', default_value_t = 20, help = "Number of entries to show")]
+        limit: usize,
+        
+        #[arg(long, help = "Show the changed lines of each entry")]
+        diff: bool,
+    },
+    
+    #[command(about = "Revert the last change to config.toml")]
+    Undo {
+        #[arg(long, help = "Undo even if config.toml was edited since")]
+        force: bool,
+    },
+    
+    #[command(about = "Write a redacted diagnostics bundle for bug reports")]
+    Diagnostics {
+        #[arg(long, short, help = "Write the bundle to this file")]
+        output: Option<std::path::PathBuf>,
+        
+        #[arg(long, help = "Print the bundle instead of writing a file")]
+        print: bool,
+    },
+    
+    #[command(about = "Comment out or remove rc file lines that zshrcman now manages")]
+    Tidy {
+        #[arg(long, help = "Startup file to tidy (default: ~/.zshrc)")]
+        file: Option<std::path::PathBuf>,
+        
+        #[arg(long, help = "Only list the redundant lines")]
+        dry_run: bool,
+        
+        #[arg(long, help = "Delete the lines instead of commenting them out")]
+        remove: bool,
+    },
+    
+    /// Any other command runs a `zshrcman-<command>` executable from PATH.
+    #[command(external_subcommand)]
+    External(Vec<String>),
 }
 
 #[derive(Subcommand)]
 enum GroupCommands {
     List,
     
+    #[command(about = "Show a group's packages, aliases, scripts, files and keys")]
+    Show {
+        name: String,
+    },
+    
     Add {
         name: String,
         #[arg(long, help = "Skip typo checking")]
@@ -79,6 +461,30 @@ enum GroupCommands {
     Disable {
         name: String,
     },
+    
+    #[command(about = "Rename a group's files, config entries, aliases and install status, and commit it")]
+    Rename {
+        old: String,
+        new: String,
+    },
+    
+    #[command(about = "Duplicate a group's files and aliases under a new name, and commit it")]
+    Copy {
+        source: String,
+        target: String,
+    },
+    
+    #[command(about = "Undo the last install of a group")]
+    Rollback {
+        name: String,
+    },
+    
+    #[command(about = "Open a group file in $EDITOR and save it only if it validates")]
+    Edit {
+        name: String,
+        #[arg(long, help = "Commit and push the change to the dotfiles repo")]
+        commit: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -100,6 +506,92 @@ enum DeviceCommands {
     Disable {
         name: String,
     },
+    
+    #[command(about = "Tag this device; groups targeting a tag are installed here")]
+    Tag {
+        #[arg(required = true, help = "Tags like laptop, gpu or work")]
+        tags: Vec<String>,
+    },
+    
+    #[command(about = "Remove tags from this device")]
+    Untag {
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    
+    #[command(about = "Delete device branches of machines you no longer use, here and on origin")]
+    Prune {
+        #[arg(
+            long,
+            default_value = "90d",
+            help = "Preselect branches without commits since this age (30d, 8w) or date"
+        )]
+        older_than: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum PluginsCommands {
+    #[command(about = "List plugins with their locked and checked-out commits")]
+    List,
+    
+    #[command(about = "Move plugins to the newest commit of their ref and re-lock them")]
+    Update {
+        #[arg(help = "Only this plugin")]
+        name: Option<String>,
+    },
+    
+    #[command(about = "Delete a plugin's checkout and lock entry")]
+    Remove {
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ThemeCommands {
+    #[command(about = "List the themes from [themes] and which one is active")]
+    List,
+    
+    #[command(about = "Use a theme by default or for a profile, and set up its prompt")]
+    Set {
+        #[arg(help = "Theme name from [themes]; omit with --none")]
+        name: Option<String>,
+        #[arg(long, help = "Set it for this profile instead of by default")]
+        profile: Option<String>,
+        #[arg(long, conflicts_with = "name", help = "Clear the theme instead")]
+        none: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum EnvCommands {
+    #[command(about = "Show which profile or group sets a variable, and what it overrides")]
+    Explain {
+        variable: String,
+    },
+    
+    #[command(about = "Print a secret from its backend; startup files use this for a profile's secrets")]
+    Secret {
+        #[arg(help = "Reference like op://vault/item/field, bw://item, pass://path or age://file")]
+        reference: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AutoSwitchCommands {
+    #[command(about = "Print an rc file snippet that switches profiles on cd")]
+    Hook,
+    
+    #[command(about = "Use a profile in the current directory and below")]
+    Set {
+        profile: String,
+    },
+    
+    #[command(about = "Remove the current directory's .zshrcman-profile")]
+    Unset,
+    
+    #[command(about = "Show which .zshrcman-profile applies here")]
+    Status,
 }
 
 #[derive(Subcommand)]
@@ -111,17 +603,119 @@ enum AliasCommands {
     
     Add {
         group: String,
+        #[arg(help = "Definition like \"alias ll='ls -la'\" or ll='ls -la'")]
         alias_def: String,
+        #[arg(long, help = "What the alias is for")]
+        description: Option<String>,
+        #[arg(long = "for-shell", help = "Only define it in this shell (repeatable)")]
+        shells: Vec<String>,
+        #[arg(long, help = "Define it as a fish alias instead of an abbreviation")]
+        no_abbr: bool,
+        #[arg(long, help = "Add it even if another group defines it differently or it shadows a command")]
+        force: bool,
     },
     
     Remove {
         group: String,
+        #[arg(help = "Alias name or full definition")]
         alias_def: String,
     },
     
     Toggle {
         group: String,
+        #[arg(long, help = "Activate aliases even if they conflict with another group or a command")]
+        force: bool,
+    },
+    
+    #[command(about = "Show how often active aliases were used, and which never were")]
+    Stats {
+        #[arg(help = "Only this group's aliases")]
+        group: Option<String>,
+    },
+    
+    #[command(about = "Print a zsh snippet that logs alias usage; eval it from .zshrc")]
+    Hook,
+}
+
+#[derive(Subcommand)]
+enum CleanCommands {
+    #[command(about = "Empty the download cache")]
+    Cache,
+    
+    #[command(about = "Drop log entries older than the retention period")]
+    Logs {
+        #[arg(long, help = "Keep this many days instead of retention.log_days")]
+        days: Option<u32>,
+    },
+    
+    #[command(about = "Drop old config history entries and leftover install staging dirs")]
+    Snapshots {
+        #[arg(long, help = "Keep this many history entries instead of retention.history_entries")]
+        keep: Option<usize>,
+    },
+    
+    #[command(about = "Clean the cache, logs and snapshots")]
+    All,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    #[command(about = "Check config.toml and group files for unknown keys, bad types and dangling references")]
+    Validate,
+    
+    #[command(about = "Open config.toml in $EDITOR and save it only if it validates")]
+    Edit,
+}
+
+#[derive(Subcommand)]
+enum PackageCommands {
+    #[command(about = "Install a package for the active profile, or activate it if another profile has it")]
+    Install {
+        name: String,
+        #[arg(long, default_value = "profile", help = "Who the package is for: profile, global, or local to install npm/pip/cargo packages into the profile's own prefix")]
+        scope: InstallScope,
+        #[arg(long, help = "Backend to install with (default: the first package manager found)")]
+        installer: Option<String>,
+        #[arg(long, value_delimiter = ',', help = "Comma-separated packages it needs; removed with it once nothing else requires them")]
+        requires: Vec<String>,
+    },
+    
+    #[command(about = "Remove a package installed with `pkg install`")]
+    Remove {
+        name: String,
+        #[arg(long, default_value = "smart", help = "deactivate, smart, force or mark-unused")]
+        strategy: RemovalStrategy,
+        #[arg(long, help = "Also uninstall it if it's pinned")]
+        force_pinned: bool,
+    },
+    
+    #[command(about = "List packages installed with `pkg install`")]
+    List {
+        #[arg(long, help = "Only packages active for this profile")]
+        profile: Option<String>,
+        #[arg(long, help = "Only packages of this scope: profile or global")]
+        scope: Option<InstallScope>,
+        #[arg(long, help = "Only packages installed but active for no profile")]
+        inactive: bool,
+    },
+    
+    #[command(about = "Show a package installed with `pkg install`: version, who installed it and where it's active")]
+    Info {
+        name: String,
+    },
+    
+    #[command(about = "Keep a package at its installed version and out of bulk removals")]
+    Hold {
+        name: String,
+    },
+    
+    #[command(about = "Release a held package")]
+    Unhold {
+        name: String,
     },
+    
+    #[command(about = "List held and pinned packages")]
+    Held,
 }
 
 #[derive(Subcommand)]
@@ -149,17 +743,273 @@ enum ProfileCommands {
     Deactivate,
     
     Current,
+    
+    #[command(name = "enable-group", about = "Enable a group only while this profile is active")]
+    EnableGroup {
+        profile: String,
+        group: String,
+    },
+    
+    #[command(name = "disable-group", about = "Stop enabling a group for this profile")]
+    DisableGroup {
+        profile: String,
+        group: String,
+    },
 }
 
-fn main() -> Result<()> {
+fn main() {
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
     
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let command_path = subcommand_path(&matches);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    prompt::set_non_interactive(cli.yes);
+    offline::set_offline(cli.offline);
+    output::set_verbosity(match (cli.quiet, cli.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (_, true) => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    });
+    diagnostics::install_panic_hook();
+    
+    if let Err(e) = run(cli, &command_path) {
+        eprintln!("Error: {:?}", e);
+        // Only unclassified errors may be bugs worth a report.
+        let kind = errors::classify(&e);
+        if kind == ErrorKind::Other {
+            diagnostics::offer_bundle(&format!("{:?}", e), None);
+        }
+        std::process::exit(kind.exit_code());
+    }
+}
+
+/// `install`, `profile switch`, ...: the subcommand names the user typed.
+fn subcommand_path(matches: &ArgMatches) -> String {
+    let mut names = vec![];
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
+}
+
+/// Commands that never change config or the machine. They don't wait for the
+/// config lock and aren't logged. External commands call back into zshrcman,
+/// which takes the lock and logs itself. The daemon takes it for each commit
+/// and sync instead of holding it while it runs.
+fn is_read_only(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Status { .. }
+            | Commands::Stats
+            | Commands::Facts { .. }
+            | Commands::Tour
+            | Commands::TestRun { .. }
+            | Commands::Verify { .. }
+            | Commands::Daemon { .. }
+            | Commands::Features
+            | Commands::Installers
+            | Commands::GenDocs { .. }
+            | Commands::Sources { update: false }
+            | Commands::Du
+            | Commands::Bench { .. }
+            | Commands::Env { .. }
+            | Commands::Shell { .. }
+            | Commands::AutoSwitch(_)
+            | Commands::Timeline { .. }
+            | Commands::History { .. }
+            | Commands::Diagnostics { .. }
+            | Commands::Diff
+            | Commands::Fleet
+            | Commands::Search { .. }
+            | Commands::Which { .. }
+            | Commands::Tidy { dry_run: true, .. }
+            | Commands::Group(GroupCommands::Show { .. })
+            | Commands::Alias(AliasCommands::Stats { .. } | AliasCommands::Hook)
+            | Commands::Plugins(PluginsCommands::List)
+            | Commands::Theme(ThemeCommands::List)
+            | Commands::Config(ConfigCommands::Validate)
+            | Commands::Package(PackageCommands::Held)
+            | Commands::Package(PackageCommands::Info { .. })
+            | Commands::Package(PackageCommands::List { .. })
+            | Commands::External(_)
+    )
+}
+
+fn run(cli: Cli, command_path: &str) -> Result<()> {
+    preflight::apply(cli.home, cli.shell, cli.config)?;
+    features::enable_from_cli(&cli.enable_feature)?;
+    if cli.events_fd.is_some() || cli.events_file.is_some() {
+        features::require(Feature::EventLog, &ConfigManager::new()?.config)?;
+    }
+    if let Some(fd) = cli.events_fd {
+        events::open_fd(fd)?;
+    } else if let Some(path) = &cli.events_file {
+        events::open_file(path)?;
+    }
+    
+    let Some(command) = cli.command else {
+        return run_guided(cli.no_wait);
+    };
+    let args = oplog::redact_args(&std::env::args().skip(1).collect::<Vec<_>>());
+    execute(command, command_path, &args, cli.no_wait)
+}
+
+/// Runs `command` under the config lock, recording its history entry and
+/// operation log line. `args` is the command line as the user typed it.
+fn execute(command: Commands, command_path: &str, args: &str, no_wait: bool) -> Result<()> {
+    if is_read_only(&command) {
+        return run_command(command);
+    }
+    
+    let config_path = ConfigManager::get_config_path()?;
+    let _lock = ConfigLock::acquire(&config_path, !no_wait)?;
+    
+    // Undo records its own history entry.
+    let records_history = !matches!(command, Commands::Undo { .. });
+    let snapshot = Snapshot::take(&config_path)?;
+    let started_at = chrono::Utc::now();
+    let timer = std::time::Instant::now();
+    let result = run_command(command);
+    
+    let history_id = if records_history {
+        match History::open().and_then(|history| snapshot.record(&history, args)) {
+            Ok(entry) => entry.map(|entry| entry.id),
+            Err(e) => {
+                output::warn(format!("⚠️  Failed to record history: {:#}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+    
+    let operation = Operation {
+        started_at,
+        duration_ms: timer.elapsed().as_millis() as u64,
+        command: command_path.to_string(),
+        args: args.to_string(),
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        history_id,
+    };
+    if let Err(e) = OperationLog::open().and_then(|log| log.append(&operation)) {
+        output::warn(format!("⚠️  Failed to log operation: {:#}", e));
+    }
+    if let (Ok(config_mgr), Ok(data)) = (ConfigManager::new(), serde_json::to_value(&operation)) {
+        hooks::emit(&config_mgr.config, Event::Operation, data);
+    }
+    result
+}
+
+/// Man pages and the markdown reference, generated from the clap
+/// definitions for packagers and the docs.
+fn handle_gen_docs(out_dir: &std::path::Path) -> Result<()> {
+    let pages = gen_docs::man_pages(Cli::command(), &out_dir.join("man"))?;
+    println!("✅ Wrote {} man pages to {}", pages.len(), out_dir.join("man").display());
+    let reference = out_dir.join("cli.md");
+    std::fs::write(&reference, gen_docs::markdown(Cli::command()))
+        .with_context(|| format!("Failed to write {}", reference.display()))?;
+    println!("✅ Wrote {}", reference.display());
+    Ok(())
+}
+
+/// `zshrcman` without a subcommand: a menu of common tasks, each run like
+/// the command it stands for, until the user quits. Without a terminal to
+/// ask on, prints the help instead.
+fn run_guided(no_wait: bool) -> Result<()> {
+    if !guided::available() {
+        Cli::command().print_help()?;
+        return Ok(());
+    }
+    
+    println!("{}", "🐚 zshrcman".bold().cyan());
+    println!("   Pick a task; run `zshrcman --help` for everything else.");
+    loop {
+        println!();
+        let task = guided::choose()?;
+        if task == guided::Task::Quit {
+            return Ok(());
+        }
+        let Some((command, path, values)) = guided_command(task)? else {
+            continue;
+        };
+        let args = oplog::redact_args(&std::iter::once(path.to_string()).chain(values).collect::<Vec<_>>());
+        if let Err(e) = execute(command, path, &args, no_wait) {
+            println!("{} {:#}", "❌".red(), e);
+        }
+    }
+}
+
+/// The command a menu task runs, asking for what it needs, with its
+/// subcommand path and arguments. None if there's nothing to run.
+fn guided_command(task: guided::Task) -> Result<Option<(Commands, &'static str, Vec<String>)>> {
+    use guided::Task;
     
-    match cli.command {
-        Commands::Init { force } => {
+    let command = match task {
+        Task::Status => (Commands::Status { live: false }, "status", vec![]),
+        Task::Install => (Commands::Install { all: false }, "install", vec![]),
+        Task::Sync => (
+            Commands::Sync {
+                force: false,
+                strategy: None,
+                pull_only: false,
+                push_only: false,
+                notify: false,
+                install_timer: false,
+                every: 60,
+                remove_timer: false,
+            },
+            "sync",
+            vec![],
+        ),
+        Task::SwitchProfile => {
+            let config = ConfigManager::new()?.config;
+            let names: Vec<String> = config.profiles.keys().cloned().collect();
+            let Some(name) = guided::pick("Switch to profile", &names, config.active_profile.as_deref())? else {
+                println!("No profiles yet; create one with `zshrcman profile create <name>`");
+                return Ok(None);
+            };
+            (Commands::Profile(ProfileCommands::Switch { name: name.clone() }), "profile switch", vec![name])
+        }
+        Task::ListAliases => (Commands::Alias(AliasCommands::List { group: None }), "alias list", vec![]),
+        Task::AddAlias => {
+            let config = ConfigManager::new()?.config;
+            let Some(group) = guided::pick("Add it to group", &config.groups.global, None)? else {
+                println!("No groups yet; add one with `zshrcman group add <name>`");
+                return Ok(None);
+            };
+            let alias_def = prompt::input("Alias (e.g. ll='ls -la')", None, "a definition")?;
+            let command = AliasCommands::Add {
+                group: group.clone(),
+                alias_def: alias_def.clone(),
+                description: None,
+                shells: vec![],
+                no_abbr: false,
+                force: false,
+            };
+            (Commands::Alias(command), "alias add", vec![group, alias_def])
+        }
+        Task::ToggleAliases => {
+            let config = ConfigManager::new()?.config;
+            let groups: Vec<String> = config.aliases.keys().cloned().collect();
+            let Some(group) = guided::pick("Choose active aliases of group", &groups, None)? else {
+                println!("No aliases yet; add one first");
+                return Ok(None);
+            };
+            (Commands::Alias(AliasCommands::Toggle { group: group.clone(), force: false }), "alias toggle", vec![group])
+        }
+        Task::Quit => return Ok(None),
+    };
+    Ok(Some(command))
+}
+
+fn run_command(command: Commands) -> Result<()> {
+    match command {
+        Commands::Init { force, repo, device, groups } => {
             if !force {
                 if let Ok(config) = ConfigManager::new() {
                     if config.config.repository.url.is_some() {
@@ -168,7 +1018,11 @@ fn main() -> Result<()> {
                     }
                 }
             }
-            InitManager::run()?;
+            InitManager::run(InitOptions { repo, device, groups })?;
+        }
+        
+        Commands::Bootstrap { repo, device, profile, groups } => {
+            BootstrapManager::run(BootstrapOptions { repo, device, profile, groups })?;
         }
         
         Commands::Install { all } => {
@@ -177,37 +1031,108 @@ fn main() -> Result<()> {
             install_mgr.install(all)?;
         }
         
-        Commands::RemoveAll => {
+        Commands::RemoveAll { force_pinned } => {
             let config_mgr = ConfigManager::new()?;
-            let mut install_mgr = InstallManager::new(config_mgr);
+            let mut install_mgr = InstallManager::new(config_mgr).force_pinned(force_pinned);
             install_mgr.remove_all()?;
         }
         
-        Commands::Sync { force: _ } => {
+        Commands::Pin { package } => {
+            let mut state_mgr = InstallationStateManager::new(ConfigManager::new()?);
+            if state_mgr.pin(&package)? {
+                println!("{} {}", "📌 Pinned package:".green(), package);
+            } else {
+                println!("'{}' is already pinned", package);
+            }
+        }
+        
+        Commands::Unpin { package } => {
+            let mut state_mgr = InstallationStateManager::new(ConfigManager::new()?);
+            if state_mgr.unpin(&package)? {
+                println!("{} {}", "✅ Unpinned package:".green(), package);
+            } else {
+                println!("'{}' is not pinned", package);
+            }
+        }
+        
+        Commands::Sync { install_timer: true, every, .. } => handle_install_sync_timer(every)?,
+        
+        Commands::Sync { remove_timer: true, .. } => {
+            if sync_timer::remove(&preflight::home_dir()?, &OsType::detect())? {
+                println!("{}", "✅ Scheduled sync removed".green());
+            } else {
+                println!("No scheduled sync is installed");
+            }
+        }
+        
+        Commands::Sync { force, strategy, pull_only, push_only, notify, .. } => {
+            let mode = match (pull_only, push_only) {
+                (true, _) => SyncMode::PullOnly,
+                (_, true) => SyncMode::PushOnly,
+                _ => SyncMode::Both,
+            };
             let config_mgr = ConfigManager::new()?;
+            let strategy = match strategy {
+                Some(strategy) => strategy,
+                None if force => config_mgr.config.repository.force_strategy.unwrap_or(ConflictStrategy::Ours),
+                None => ConflictStrategy::Abort,
+            };
             let dotfiles_path = ConfigManager::get_dotfiles_path()?;
             let git_mgr = GitManager::init_or_clone(
                 &dotfiles_path,
                 config_mgr.config.repository.url.as_deref(),
-            )?;
+            )?
+            .with_mirrors(&config_mgr.config.repository.remotes)?
+            .with_identity(&config_mgr.config.repository.identity);
             
-            git_mgr.sync(
-                &config_mgr.config.repository.main_branch,
-                &config_mgr.config.device.branch,
-            )?;
+            if let Err(e) = fleet::publish(&git_mgr, &dotfiles_path, &config_mgr) {
+                output::warn(format!("⚠️  Couldn't update this device's status report: {:#}", e));
+            }
+            let main_branch = &config_mgr.config.repository.main_branch;
+            let fetched = git_mgr.tracking_id(main_branch);
+            let result = git_mgr.sync(main_branch, &config_mgr.config.device.branch, strategy, mode);
+            hooks::emit_sync(&config_mgr.config, "dotfiles", &config_mgr.config.device.branch, &result);
+            let result = result.kind(ErrorKind::Git);
+            if notify {
+                let pulled = git_mgr.tracking_id(main_branch) != fetched;
+                if let Some((summary, body)) = notifications::sync_outcome(main_branch, pulled, &result) {
+                    notifications::send(&summary, &body);
+                }
+            }
+            result?;
+            repos::sync_all(&config_mgr, strategy, mode);
+            if mode != SyncMode::PushOnly {
+                sources::update_all(&config_mgr);
+            }
             
             println!("{}", "✅ Repository synced successfully!".green());
         }
         
+        Commands::Diff => handle_diff()?,
+        
+        Commands::Fleet => fleet::show(&ConfigManager::new()?)?,
+        
+        Commands::Search { term } => handle_search(&term)?,
+        
+        Commands::Which { name } => handle_which(&name)?,
+        
         Commands::Group(cmd) => handle_group_command(cmd)?,
         
         Commands::Device(cmd) => handle_device_command(cmd)?,
         
         Commands::Alias(cmd) => handle_alias_command(cmd)?,
         
+        Commands::Plugins(cmd) => handle_plugins_command(cmd)?,
+        
+        Commands::Theme(cmd) => handle_theme_command(cmd)?,
+        
         Commands::Profile(cmd) => handle_profile_command(cmd)?,
         
-        Commands::Status => {
+        Commands::Config(cmd) => handle_config_command(cmd)?,
+        
+        Commands::Package(cmd) => handle_package_command(cmd)?,
+        
+        Commands::Status { live } => {
             let config_mgr = ConfigManager::new()?;
             
             println!("{}", "📊 zshrcman Status".bold().cyan());
@@ -218,11 +1143,23 @@ fn main() -> Result<()> {
             } else {
                 println!("  Repository: {}", "Not configured".yellow());
             }
+            for (name, repository) in &config_mgr.config.repositories {
+                println!(
+                    "  Repository '{}': {} ({})",
+                    name,
+                    repository.url,
+                    repos::branch(repository, &config_mgr.config.device.branch)
+                );
+            }
             
             println!("  Device: {}", config_mgr.config.device.name);
             println!("  Branch: {}", config_mgr.config.device.branch);
             println!();
             
+            if let Ok(git_mgr) = GitManager::open(&ConfigManager::get_dotfiles_path()?) {
+                print_git_status(&git_mgr, &config_mgr)?;
+            }
+            
             println!("{}", "  Global Groups:".bold());
             for group in &config_mgr.config.groups.global {
                 let status = if config_mgr.config.groups.enabled_global.contains(group) {
@@ -232,6 +1169,13 @@ fn main() -> Result<()> {
                 };
                 println!("    {} - {}", group, status);
             }
+            let profile_groups = config_mgr.profile_groups();
+            if !profile_groups.is_empty() {
+                println!("{}", "  Profile Groups:".bold());
+                for group in &profile_groups {
+                    println!("    {} - {}", group, "✅ enabled by profile".green());
+                }
+            }
             
             println!();
             println!("{}", "  Installation Status:".bold());
@@ -247,6 +1191,277 @@ fn main() -> Result<()> {
                     );
                 }
             }
+            
+            if live {
+                println!();
+                print_daemon_status(&config_mgr)?;
+            }
+        }
+        
+        Commands::Stats => {
+            let config_mgr = ConfigManager::new()?;
+            RepoStats::collect(&config_mgr)?.print();
+        }
+        Commands::Facts { json } => {
+            let facts = Facts::gather();
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&facts)?);
+            } else {
+                println!("{}", "🔎 System Facts".bold().cyan());
+                println!("  hostname: {}", facts.hostname);
+                println!("  os: {}", facts.os);
+                println!("  arch: {}", facts.arch);
+                println!("  shell: {}", facts.shell);
+                println!("  cpus: {}", facts.cpus);
+                println!("  wsl: {}", facts.wsl);
+                println!("  package_managers: {}", facts.package_managers.join(", "));
+            }
+        }
+        Commands::Tour => {
+            TourManager::new()?.run()?;
+        }
+        
+        Commands::TestRun { image, binary } => handle_test_run(image, binary)?,
+        
+        Commands::Verify { json } => handle_verify(json)?,
+        
+        Commands::Apply { dry_run } => handle_apply(dry_run)?,
+        
+        Commands::Daemon { interval } => handle_daemon(interval)?,
+        
+        Commands::Sources { update } => {
+            let config_mgr = ConfigManager::new()?;
+            if update {
+                sources::update_all(&config_mgr);
+            }
+            
+            let data_dir = config_mgr.data_dir();
+            if config_mgr.config.sources.is_empty() {
+                println!("No sources configured. Add them under [sources] in config.toml.");
+            }
+            for (name, source) in &config_mgr.config.sources {
+                let checkout = GitManager::open(&sources::checkout_dir(&data_dir, name))
+                    .ok()
+                    .and_then(|git| git.head_id());
+                let state = match checkout {
+                    Some(commit) => git_mgr::short_id(&commit).to_string(),
+                    None => "not cloned; run `zshrcman sources --update`".yellow().to_string(),
+                };
+                
+                println!(
+                    "{} {} ({}) [{}]",
+                    "📚".bold(),
+                    name.bold(),
+                    source.url(),
+                    source.reference().unwrap_or("default branch")
+                );
+                println!("    checkout: {}", state);
+                for group in sources::groups(&data_dir, name) {
+                    println!("    {}", group);
+                }
+            }
+        }
+        
+        Commands::Du => {
+            let config_mgr = ConfigManager::new()?;
+            let usage = disk_usage::report(&config_mgr);
+            
+            println!("{} {}", "💾 Disk usage of".bold(), config_mgr.data_dir().display());
+            for area in &usage {
+                println!("  {:<20} {:>10}", area.label, disk_usage::format_bytes(area.bytes));
+            }
+            let total: u64 = usage.iter().map(|area| area.bytes).sum();
+            println!("  {:<20} {:>10}", "total".bold(), disk_usage::format_bytes(total));
+        }
+        
+        Commands::Env { command: Some(EnvCommands::Explain { variable }), .. } => handle_env_explain(&variable)?,
+        
+        Commands::Env { command: Some(EnvCommands::Secret { reference }), .. } => {
+            let secret = SecretRef::parse(&reference)?;
+            println!("{}", secret.resolve(&ConfigManager::get_dotfiles_path()?)?);
+        }
+        
+        Commands::Env { applied, hook, command: None } => shell_env::print(&applied.unwrap_or_default(), hook)?,
+        
+        Commands::Shell { profile } => session::start(&profile)?,
+        
+        Commands::AutoSwitch(cmd) => handle_auto_switch_command(cmd)?,
+        
+        Commands::Bench { runs, slow } => bench::show(&ConfigManager::new()?, &preflight::home_dir()?, runs.max(1), slow)?,
+        
+        Commands::Clean(cmd) => {
+            let config_mgr = ConfigManager::new()?;
+            let data_dir = config_mgr.data_dir();
+            let mut retention = config_mgr.config.retention;
+            let (cache, logs, snapshots) = match cmd {
+                CleanCommands::Cache => (true, false, false),
+                CleanCommands::Logs { days } => {
+                    retention.log_days = days.unwrap_or(retention.log_days);
+                    (false, true, false)
+                }
+                CleanCommands::Snapshots { keep } => {
+                    retention.history_entries = keep.unwrap_or(retention.history_entries);
+                    (false, false, true)
+                }
+                CleanCommands::All => (true, true, true),
+            };
+            
+            if cache {
+                let freed = disk_usage::clear_cache(&config_mgr.cache_dir())?;
+                println!("{} {}", "🧹 Cleared cache:".green(), disk_usage::format_bytes(freed));
+            }
+            if logs {
+                let freed = disk_usage::prune_logs(&data_dir, &retention, chrono::Utc::now())?;
+                println!(
+                    "{} {} (kept {} days)",
+                    "🧹 Pruned logs:".green(),
+                    disk_usage::format_bytes(freed),
+                    retention.log_days
+                );
+            }
+            if snapshots {
+                let freed = disk_usage::prune_snapshots(&data_dir, &retention)?;
+                println!(
+                    "{} {} (kept {} history entries)",
+                    "🧹 Pruned snapshots:".green(),
+                    disk_usage::format_bytes(freed),
+                    retention.history_entries
+                );
+            }
+        }
+        
+        Commands::Timeline { since, limit, failed } => {
+            let since = since
+                .map(|since| oplog::parse_since(&since, chrono::Utc::now()))
+                .transpose()?;
+            let mut operations = OperationLog::open()?.since(since)?;
+            if failed {
+                operations.retain(|operation| !operation.success);
+            }
+            if operations.is_empty() {
+                println!("No recorded operations.");
+            }
+            
+            let mut day = None;
+            for operation in &operations[operations.len().saturating_sub(limit)..] {
+                let local = operation.started_at.with_timezone(&chrono::Local);
+                if day != Some(local.date_naive()) {
+                    day = Some(local.date_naive());
+                    println!("{}", format!("📅 {}", local.format("%A %Y-%m-%d")).bold());
+                }
+                
+                let mut line = format!(
+                    "  {} {} {} {}",
+                    local.format("%H:%M:%S").to_string().dimmed(),
+                    if operation.success { "✅" } else { "❌" },
+                    operation.args,
+                    oplog::format_duration(operation.duration_ms).dimmed()
+                );
+                if let Some(id) = operation.history_id {
+                    line.push_str(&format!(" {}", format!("config #{}", id).cyan()));
+                }
+                println!("{}", line);
+                if let Some(error) = &operation.error {
+                    println!("      {}", error.red());
+                }
+            }
+        }
+        
+        Commands::History { limit, diff } => {
+            let entries = History::open()?.entries()?;
+            if entries.is_empty() {
+                println!("No recorded changes yet.");
+            }
+            
+            for entry in entries.iter().rev().take(limit) {
+                println!(
+                    "{} {} {}",
+                    format!("#{}", entry.id).bold(),
+                    entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+                    entry.action
+                );
+                if diff {
+                    for line in entry.changed_lines() {
+                        let line = if line.starts_with('+') { line.green() } else { line.red() };
+                        println!("    {}", line);
+                    }
+                }
+            }
+        }
+        
+        Commands::Undo { force } => {
+            let entry = History::open()?.undo(&ConfigManager::get_config_path()?, force)?;
+            println!("{} #{} {}", "↩️  Undid".green(), entry.id, entry.action);
+        }
+        
+        Commands::Diagnostics { output, print } => {
+            let bundle = DiagnosticsBundle::collect(None, None);
+            if print {
+                print!("{}", bundle.contents());
+            } else {
+                let path = bundle.write(output.as_deref())?;
+                println!("{} {}", "📝 Diagnostics written to".green(), path.display());
+                println!("   Review it before attaching it to a bug report.");
+            }
+        }
+        
+        Commands::Tidy { file, dry_run, remove } => {
+            let home = preflight::home_dir()?;
+            let path = file.unwrap_or_else(|| home.join(".zshrc"));
+            tidy::run(&ConfigManager::new()?, &home, &path, dry_run, remove)?;
+        }
+        
+        Commands::GenDocs { out_dir } => handle_gen_docs(&out_dir)?,
+        
+        Commands::Features => {
+            let config_mgr = ConfigManager::new()?;
+            
+            println!("{}", "🧪 Experimental Features:".bold());
+            for feature in Feature::ALL {
+                let status = if features::is_enabled(feature, &config_mgr.config) {
+                    "enabled".green()
+                } else {
+                    "disabled".yellow()
+                };
+                println!("  {} [{}] - {}", feature.name(), status, feature.description());
+            }
+            
+            for unknown in features::unknown_config_features(&config_mgr.config) {
+                println!(
+                    "  {} unknown feature '{}' in [features]",
+                    "⚠️ ".yellow(),
+                    unknown
+                );
+            }
+        }
+        Commands::External(args) => {
+            let (name, rest) = args.split_first().expect("clap always passes the subcommand name");
+            let config_mgr = ConfigManager::new()?;
+            
+            external::run(name, rest, &config_mgr)?;
+        }
+        Commands::Installers => {
+            let config_mgr = ConfigManager::new()?;
+            let builtin = InstallerRegistry::builtin(false, &config_mgr.config.install);
+            
+            println!("{}", "📦 Installers:".bold());
+            for name in builtin.names() {
+                println!("  {} (built-in)", name);
+            }
+            
+            for plugin in plugin::discover(&[config_mgr.data_dir().join("plugins")]) {
+                if builtin.get(plugin.name()).is_some() {
+                    println!(
+                        "  {} {} shadowed by built-in: {}",
+                        "⚠️ ".yellow(),
+                        plugin.name(),
+                        plugin.path().display()
+                    );
+                } else {
+                    println!("  {} (plugin: {})", plugin.name(), plugin.path().display());
+                }
+            }
         }
     }
     
@@ -269,7 +1484,19 @@ fn handle_group_command(cmd: GroupCommands) -> Result<()> {
             }
         }
         
+        GroupCommands::Show { name } => groups::show(&config_mgr, &name)?,
+        
         GroupCommands::Add { name, no_check } => {
+            if let Some((namespace, _)) = name.split_once('/') {
+                if !config_mgr.config.sources.contains_key(namespace)
+                    && !config_mgr.config.repositories.contains_key(namespace)
+                {
+                    anyhow::bail!(
+                        "Unknown source or repository '{}'; add it under [sources] or [repositories] in config.toml",
+                        namespace
+                    );
+                }
+            }
             if !no_check {
                 check_typo(&name, &config_mgr.config.groups.global)?;
             }
@@ -291,6 +1518,82 @@ fn handle_group_command(cmd: GroupCommands) -> Result<()> {
             config_mgr.disable_global_group(&name)?;
             println!("{} {}", "✅ Disabled group:".green(), name);
         }
+        
+        GroupCommands::Rename { old, new } => {
+            let changed = config_mgr.rename_group(&old, &new)?;
+            println!("{} {} → {}", "✅ Renamed group:".green(), old, new);
+            groups::commit_change(&config_mgr, &changed, &format!("Rename group '{}' to '{}'", old, new));
+        }
+        
+        GroupCommands::Copy { source, target } => {
+            let changed = config_mgr.copy_group(&source, &target)?;
+            println!("{} {} → {}", "✅ Copied group:".green(), source, target);
+            groups::commit_change(&config_mgr, &changed, &format!("Copy group '{}' to '{}'", source, target));
+            println!("   Enable it with `zshrcman group enable {}`", target);
+        }
+        
+        GroupCommands::Rollback { name } => {
+            InstallManager::new(config_mgr).rollback_group(&name)?;
+            println!("{} {}", "✅ Rolled back group:".green(), name);
+        }
+        
+        GroupCommands::Edit { name, commit } => {
+            let repository = match name.split_once('/') {
+                Some((source, _)) if config_mgr.config.sources.contains_key(source) => {
+                    anyhow::bail!("'{}' comes from source '{}'; edit it in that repo instead", name, source);
+                }
+                Some((namespace, _)) => match config_mgr.config.repositories.get(namespace) {
+                    Some(repository) => Some((namespace, repository)),
+                    None => anyhow::bail!("Unknown source or repository '{}'", namespace),
+                },
+                None => None,
+            };
+            
+            let dotfiles_path = config_mgr.dotfiles_path().to_path_buf();
+            let (root, local_name) = config_mgr.resolve_group(&name);
+            // Work on this machine's branch of the repository, cloning it
+            // first if it hasn't been synced yet.
+            let checkout = match repository {
+                Some((namespace, repository)) => {
+                    let git_mgr = repos::open(&config_mgr.data_dir(), namespace, repository)?
+                        .with_identity(&config_mgr.config.repository.identity);
+                    let branch = repos::branch(repository, &config_mgr.config.device.branch);
+                    git_mgr.ensure_branch(branch, &repository.main_branch)?;
+                    Some((git_mgr, branch))
+                }
+                None => None,
+            };
+            let relative = std::path::Path::new("groups").join(format!("{}.toml", local_name));
+            let path = root.join(&relative);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            
+            let initial = format!("name = \"{}\"\ndescription = \"\"\npackages = []\n", local_name);
+            let check = |copy: &std::path::Path| {
+                Validator::new(config_mgr.config_path(), &dotfiles_path).in_repo(&root).group_file(copy)
+            };
+            if !Editor::from_env()?.edit_checked(&path, &initial, check)? {
+                println!("No changes to group '{}'", name);
+                return Ok(());
+            }
+            println!("{} {}", "✅ Saved group:".green(), name);
+            
+            if commit {
+                let (git_mgr, branch) = match checkout {
+                    Some(checkout) => checkout,
+                    None => (
+                        GitManager::open(&dotfiles_path)?
+                            .with_mirrors(&config_mgr.config.repository.remotes)?
+                            .with_identity(&config_mgr.config.repository.identity),
+                        config_mgr.config.device.branch.as_str(),
+                    ),
+                };
+                git_mgr.add_path(&relative)?;
+                git_mgr.commit_and_push(&format!("Edit group '{}'", local_name), branch)?;
+                println!("{}", "✅ Committed and pushed".green());
+            }
+        }
     }
     
     Ok(())
@@ -310,6 +1613,14 @@ fn handle_device_command(cmd: DeviceCommands) -> Result<()> {
                 };
                 println!("  {} [{}]", group, status);
             }
+            
+            let tags = &config_mgr.config.device.tags;
+            if !tags.is_empty() {
+                println!("\n{} {}", "🏷️  Tags:".bold(), tags.join(", "));
+                for group in config_mgr.tagged_groups() {
+                    println!("  {} [{}]", group, "tagged".cyan());
+                }
+            }
         }
         
         DeviceCommands::Add { name } => {
@@ -321,6 +1632,7 @@ fn handle_device_command(cmd: DeviceCommands) -> Result<()> {
         }
         
         DeviceCommands::Remove { name } => {
+            require_device_group_or_stale(&config_mgr, &name)?;
             config_mgr.config.groups.per_device.retain(|g| g != &name);
             config_mgr.config.groups.enabled_devices.retain(|g| g != &name);
             config_mgr.save()?;
@@ -328,20 +1640,231 @@ fn handle_device_command(cmd: DeviceCommands) -> Result<()> {
         }
         
         DeviceCommands::Enable { name } => {
-            if config_mgr.config.groups.per_device.contains(&name) {
-                if !config_mgr.config.groups.enabled_devices.contains(&name) {
-                    config_mgr.config.groups.enabled_devices.push(name.clone());
-                    config_mgr.save()?;
-                }
+            require_device_group(&config_mgr, &name)?;
+            if !config_mgr.config.groups.enabled_devices.contains(&name) {
+                config_mgr.config.groups.enabled_devices.push(name.clone());
+                config_mgr.save()?;
             }
             println!("{} {}", "✅ Enabled device group:".green(), name);
         }
         
         DeviceCommands::Disable { name } => {
+            require_device_group_or_stale(&config_mgr, &name)?;
             config_mgr.config.groups.enabled_devices.retain(|g| g != &name);
             config_mgr.save()?;
             println!("{} {}", "✅ Disabled device group:".green(), name);
         }
+        
+        DeviceCommands::Tag { tags } => {
+            for tag in tags {
+                if !config_mgr.config.device.tags.contains(&tag) {
+                    config_mgr.config.device.tags.push(tag);
+                }
+            }
+            config_mgr.save()?;
+            println!("{} {}", "✅ Device tags:".green(), config_mgr.config.device.tags.join(", "));
+            for group in config_mgr.tagged_groups() {
+                if !config_mgr.config.groups.enabled_global.contains(&group) {
+                    println!("  {} targets this device; `zshrcman install` to install it", group);
+                }
+            }
+        }
+        
+        DeviceCommands::Untag { tags } => {
+            config_mgr.config.device.tags.retain(|tag| !tags.contains(tag));
+            config_mgr.save()?;
+            println!("{} {}", "✅ Removed device tags:".green(), tags.join(", "));
+        }
+        
+        DeviceCommands::Prune { older_than } => fleet::prune(&config_mgr, &older_than)?,
+    }
+    
+    Ok(())
+}
+
+/// Prints every definition of `variable`, the one that takes effect first.
+fn handle_env_explain(variable: &str) -> Result<()> {
+    let layers = InstallManager::new(ConfigManager::new()?).env_layers();
+    let settings = layers.explain(variable);
+    let Some(winner) = settings.first() else {
+        return Err(suggest::not_found("Variable", variable, layers.names()));
+    };
+    
+    println!("{} = {}  (from {})", variable.bold(), winner.value, winner);
+    for setting in &settings {
+        let note = if std::ptr::eq(*setting, *winner) {
+            "in effect".green()
+        } else if setting.layer == winner.layer && setting.value != winner.value {
+            "conflict, loses to the earlier group".yellow()
+        } else {
+            "overridden".dimmed()
+        };
+        println!("  {:<28} {}  {}", setting.to_string(), setting.value, note);
+    }
+    if let Ok(current) = std::env::var(variable) {
+        println!("This shell has {}={}", variable, current);
+    }
+    println!("{}", "Precedence: profile > device group > group".dimmed());
+    Ok(())
+}
+
+fn handle_auto_switch_command(cmd: AutoSwitchCommands) -> Result<()> {
+    let dir = std::env::current_dir()?;
+    match cmd {
+        AutoSwitchCommands::Hook => {
+            let shell = EnvironmentManager::new().shell_type().clone();
+            if matches!(shell, ShellType::Cmd | ShellType::Nushell) {
+                anyhow::bail!("Auto switching supports zsh, bash, fish and pwsh; pick one with --shell");
+            }
+            print!("{}", auto_switch::hook_snippet(&shell));
+        }
+        
+        AutoSwitchCommands::Set { profile } => {
+            let config_mgr = ConfigManager::new()?;
+            if !config_mgr.config.profiles.contains_key(&profile) {
+                return Err(suggest::not_found("Profile", &profile, config_mgr.config.profiles.keys()));
+            }
+            let file = auto_switch::set(&dir, &profile)?;
+            println!("✅ {} uses profile '{}'", file.display(), profile);
+        }
+        
+        AutoSwitchCommands::Unset => {
+            let file = dir.join(auto_switch::PROFILE_FILE);
+            if !file.exists() {
+                anyhow::bail!("No {} in {}", auto_switch::PROFILE_FILE, dir.display());
+            }
+            std::fs::remove_file(&file)?;
+            println!("✅ Removed {}", file.display());
+            if let Some((parent, profile)) = auto_switch::find(&dir) {
+                println!("ℹ️  {} still applies profile '{}' here", parent.display(), profile);
+            }
+        }
+        
+        AutoSwitchCommands::Status => match auto_switch::find(&dir) {
+            Some((file, profile)) => println!("📁 Profile '{}' from {}", profile.bold(), file.display()),
+            None => println!("No {} here or above; the active profile applies", auto_switch::PROFILE_FILE),
+        },
+    }
+    Ok(())
+}
+
+fn handle_plugins_command(cmd: PluginsCommands) -> Result<()> {
+    let config_mgr = ConfigManager::new()?;
+    let data_dir = config_mgr.data_dir();
+    let dotfiles = config_mgr.dotfiles_path();
+    let mut lock = Lockfile::load(dotfiles)?;
+    let declared = zsh_plugins::declared(&config_mgr);
+    
+    match cmd {
+        PluginsCommands::List => {
+            if declared.is_empty() {
+                println!("No plugins. Add [[plugins]] to groups/plugins.toml and enable the group.");
+            }
+            for (group, plugin) in &declared {
+                let checkout = GitManager::open(&zsh_plugins::checkout_dir(&data_dir, &plugin.name))
+                    .ok()
+                    .and_then(|git| git.head_id());
+                let locked = lock.plugins.get(&plugin.name).map(|locked| git_mgr::short_id(&locked.commit));
+                let state = match (checkout, locked) {
+                    (Some(commit), Some(locked)) if commit.starts_with(locked) => format!("at {} (locked)", locked),
+                    (Some(commit), _) => format!("at {}, {}", git_mgr::short_id(&commit), "not at its locked commit; run `zshrcman install`".yellow()),
+                    (None, _) => "not cloned; run `zshrcman install`".yellow().to_string(),
+                };
+                let defer = if plugin.defer { " [deferred]" } else { "" };
+                println!("🔌 {}{} ({}) from group '{}'", plugin.name.bold(), defer, plugin.url, group);
+                println!("    {}", state);
+            }
+        }
+        
+        PluginsCommands::Update { name } => {
+            let selected: Vec<&ZshPlugin> = declared
+                .iter()
+                .map(|(_, plugin)| plugin)
+                .filter(|plugin| name.as_ref().is_none_or(|name| plugin.name == *name))
+                .collect();
+            if let (Some(name), true) = (&name, selected.is_empty()) {
+                anyhow::bail!("No enabled plugins group declares a plugin named '{}'", name);
+            }
+            for plugin in selected {
+                match zsh_plugins::update(&data_dir, &mut lock, plugin) {
+                    Ok(commit) => println!("✅ Plugin '{}' locked at {}", plugin.name, git_mgr::short_id(&commit)),
+                    Err(e) => println!("⚠️  Failed to update plugin '{}': {:#}", plugin.name, e),
+                }
+            }
+            lock.save(dotfiles)?;
+        }
+        
+        PluginsCommands::Remove { name } => {
+            if zsh_plugins::remove(&data_dir, &mut lock, &name)? {
+                lock.save(dotfiles)?;
+                println!("✅ Removed plugin '{}'", name);
+            } else {
+                println!("ℹ️  Plugin '{}' isn't installed", name);
+            }
+            if let Some((group, _)) = declared.iter().find(|(_, plugin)| plugin.name == name) {
+                println!(
+                    "{} Group '{}' still declares it; delete it there, or the next install clones it again",
+                    "⚠️ ".yellow(),
+                    group
+                );
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+fn handle_theme_command(cmd: ThemeCommands) -> Result<()> {
+    let mut config_mgr = ConfigManager::new()?;
+    
+    match cmd {
+        ThemeCommands::List => {
+            let active = theme::active_name(&config_mgr.config);
+            if config_mgr.config.themes.is_empty() {
+                println!("No themes. Define them under [themes.<name>] in config.toml.");
+            }
+            for (name, theme) in &config_mgr.config.themes {
+                let marker = if active == Some(name.as_str()) { "▶" } else { " " };
+                let config = theme.config.as_ref().map(|c| format!(" ({})", c.display())).unwrap_or_default();
+                println!("{} {} {:?}{}", marker, name.bold(), theme.prompt, config);
+            }
+            let mut profiles: Vec<_> = config_mgr.config.profiles.values().filter(|p| p.theme.is_some()).collect();
+            profiles.sort_by(|a, b| a.name.cmp(&b.name));
+            for profile in profiles {
+                println!("  profile '{}' uses '{}'", profile.name, profile.theme.as_deref().unwrap_or_default());
+            }
+        }
+        
+        ThemeCommands::Set { name, profile, none } => {
+            let Some(name) = name.or_else(|| none.then(String::new)) else {
+                anyhow::bail!("Name a theme, or pass --none to clear it");
+            };
+            let name = (!name.is_empty()).then_some(name);
+            if let Some(name) = name.as_ref().filter(|name| !config_mgr.config.themes.contains_key(*name)) {
+                anyhow::bail!("Theme '{}' is not defined under [themes] in config.toml", name);
+            }
+            match &profile {
+                Some(profile) => {
+                    if !config_mgr.config.profiles.contains_key(profile) {
+                        return Err(suggest::not_found("Profile", profile, config_mgr.config.profiles.keys()));
+                    }
+                    if let Some(profile) = config_mgr.config.profiles.get_mut(profile) {
+                        profile.theme = name.clone();
+                    }
+                }
+                None => config_mgr.config.theme = name.clone(),
+            }
+            config_mgr.save()?;
+            
+            let scope = profile.map(|p| format!("profile '{}'", p)).unwrap_or_else(|| "default".to_string());
+            match &name {
+                Some(name) => println!("✅ Theme for {}: {}", scope, name),
+                None => println!("✅ Cleared the theme for {}", scope),
+            }
+            if let Some(active) = theme::apply(&config_mgr, &preflight::home_dir()?)? {
+                println!("🎨 Prompt theme '{}'; open a new shell to see it", active);
+            }
+        }
     }
     
     Ok(())
@@ -349,6 +1872,7 @@ fn handle_device_command(cmd: DeviceCommands) -> Result<()> {
 
 fn handle_alias_command(cmd: AliasCommands) -> Result<()> {
     let config_mgr = ConfigManager::new()?;
+    let data_dir = config_mgr.data_dir();
     let mut alias_mgr = AliasManager::new(config_mgr);
     
     match cmd {
@@ -356,16 +1880,29 @@ fn handle_alias_command(cmd: AliasCommands) -> Result<()> {
             alias_mgr.list(group.as_deref())?;
         }
         
-        AliasCommands::Add { group, alias_def } => {
-            alias_mgr.add(&group, &alias_def)?;
+        AliasCommands::Add { group, alias_def, description, shells, no_abbr, force } => {
+            let mut alias = Alias::parse(&alias_def)
+                .with_context(|| format!("Not an alias definition: {}", alias_def))?;
+            alias.description = description;
+            alias.shells = shells;
+            alias.abbr = no_abbr.then_some(false);
+            alias_mgr.add(&group, alias, force)?;
         }
         
         AliasCommands::Remove { group, alias_def } => {
             alias_mgr.remove(&group, &alias_def)?;
         }
         
-        AliasCommands::Toggle { group } => {
-            alias_mgr.toggle(&group)?;
+        AliasCommands::Toggle { group, force } => {
+            alias_mgr.toggle(&group, force)?;
+        }
+        
+        AliasCommands::Stats { group } => {
+            alias_mgr.stats(group.as_deref())?;
+        }
+        
+        AliasCommands::Hook => {
+            print!("{}", alias_usage::hook_snippet(&alias_usage::log_path(&data_dir)));
         }
     }
     
@@ -374,93 +1911,466 @@ fn handle_alias_command(cmd: AliasCommands) -> Result<()> {
 
 fn handle_profile_command(cmd: ProfileCommands) -> Result<()> {
     let config_mgr = ConfigManager::new()?;
-    let mut state_mgr = InstallationStateManager::new(config_mgr);
+    let mut profiles = ProfileService::new(config_mgr);
     
     match cmd {
         ProfileCommands::List => {
             println!("{}", "📋 Profiles:".bold());
-            for (name, _profile) in &state_mgr.profiles {
-                let is_active = state_mgr.active_profile.as_ref() == Some(name);
+            for profile in profiles.list() {
+                let is_active = profiles.active() == Some(profile.name.as_str());
                 let marker = if is_active { " (active)".green() } else { "".normal() };
-                println!("  {}{}", name, marker);
+                if profile.enabled_groups.is_empty() {
+                    println!("  {}{}", profile.name, marker);
+                } else {
+                    println!("  {}{} [groups: {}]", profile.name, marker, profile.enabled_groups.join(", "));
+                }
             }
             
-            if state_mgr.profiles.is_empty() {
+            if profiles.list().is_empty() {
                 println!("  {}", "No profiles created yet".yellow());
             }
         }
         
         ProfileCommands::Create { name, parent } => {
-            state_mgr.create_profile(&name, parent)?;
+            profiles.create(&name, parent)?;
             println!("{} {}", "✅ Created profile:".green(), name);
         }
         
         ProfileCommands::Switch { name } => {
-            let mut switcher = ProfileSwitcher::new(state_mgr);
-            switcher.switch_profile(&name)?;
+            profiles.switch(&name)?;
         }
         
         ProfileCommands::Delete { name } => {
-            if state_mgr.active_profile.as_ref() == Some(&name) {
-                anyhow::bail!("Cannot delete active profile. Switch to another profile first.");
-            }
-            
-            state_mgr.profiles.remove(&name);
-            // Save state through state manager
-            let config_mgr = ConfigManager::new()?;
-            let mut state_mgr_new = InstallationStateManager::new(config_mgr);
-            state_mgr_new.profiles = state_mgr.profiles;
-            state_mgr_new.save_state()?;
-            
+            profiles.delete(&name)?;
             println!("{} {}", "✅ Deleted profile:".green(), name);
         }
         
         ProfileCommands::Activate { name } => {
-            let mut switcher = ProfileSwitcher::new(state_mgr);
-            switcher.activate_profile(&name)?;
+            profiles.activate(&name)?;
         }
         
         ProfileCommands::Deactivate => {
-            let mut switcher = ProfileSwitcher::new(state_mgr);
-            switcher.deactivate_current()?;
+            profiles.deactivate()?;
         }
         
         ProfileCommands::Current => {
-            if let Some(current) = &state_mgr.active_profile {
+            if let Some(current) = profiles.active() {
                 println!("Current profile: {}", current.green());
             } else {
                 println!("{}", "No active profile".yellow());
             }
         }
+        
+        ProfileCommands::EnableGroup { profile, group } => {
+            if profiles.enable_group(&profile, &group)? {
+                println!("{} {} (profile '{}')", "✅ Enabled group:".green(), group, profile);
+            } else {
+                println!("Profile '{}' already enables '{}'", profile, group);
+            }
+        }
+        
+        ProfileCommands::DisableGroup { profile, group } => {
+            if profiles.disable_group(&profile, &group)? {
+                println!("{} {} (profile '{}')", "✅ Disabled group:".green(), group, profile);
+            } else {
+                println!("Profile '{}' doesn't enable '{}'", profile, group);
+            }
+        }
     }
     
     Ok(())
 }
 
+/// Fails, suggesting similar names, unless `name` is a per-device group.
+fn require_device_group(config_mgr: &ConfigManager, name: &str) -> Result<()> {
+    let per_device = &config_mgr.config.groups.per_device;
+    if !per_device.iter().any(|group| group == name) {
+        return Err(suggest::not_found("Device group", name, per_device));
+    }
+    Ok(())
+}
+
+/// `require_device_group`, except that a name left in `enabled_devices`
+/// after its group went away passes with a warning, so it can be cleaned up.
+fn require_device_group_or_stale(config_mgr: &ConfigManager, name: &str) -> Result<()> {
+    let groups = &config_mgr.config.groups;
+    if !groups.per_device.iter().any(|group| group == name) && groups.enabled_devices.iter().any(|group| group == name) {
+        output::warn(format!("⚠️  Device group '{}' is only listed in groups.enabled_devices; removing the stale entry", name));
+        return Ok(());
+    }
+    require_device_group(config_mgr, name)
+}
+
 fn check_typo(name: &str, existing: &[String]) -> Result<()> {
-    const THRESHOLD: f64 = 0.8;
-    
-    for existing_name in existing {
-        let similarity = jaro_winkler(name, existing_name);
-        if similarity > THRESHOLD && name != existing_name {
-            println!(
-                "{} '{}' is similar to existing group '{}'. Did you mean that?",
-                "⚠️  Warning:".yellow(),
-                name,
-                existing_name
-            );
+    let similar = suggest::closest(name, existing);
+    if similar.is_empty() {
+        return Ok(());
+    }
+    
+    println!(
+        "{} '{}' is similar to existing group{} '{}'. Did you mean that?",
+        "⚠️  Warning:".yellow(),
+        name,
+        if similar.len() == 1 { "" } else { "s" },
+        similar.join("', '")
+    );
+    if !prompt::confirm("Continue anyway?", false)? {
+        return Err(errors::fail(ErrorKind::Aborted, "Aborted due to potential typo"));
+    }
+    
+    Ok(())
+}
+
+fn print_daemon_status(config_mgr: &ConfigManager) -> Result<()> {
+    println!("{}", "  Daemon:".bold());
+    let Some(status) = daemon::query(&daemon::socket_path(&config_mgr.data_dir()))? else {
+        println!("    {}", "Not running; start it with `zshrcman daemon`".yellow());
+        return Ok(());
+    };
+    let time = |at: Option<chrono::DateTime<chrono::Utc>>| {
+        at.map(|at| at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "never".to_string())
+    };
+    println!("    Running: pid {} since {}", status.pid, time(status.started));
+    println!("    Commits: {} (last {})", status.commits, time(status.last_commit));
+    if let Some(message) = &status.last_commit_message {
+        println!("      {}", message);
+    }
+    println!("    Last sync: {}", time(status.last_sync));
+    if let Some(error) = &status.last_error {
+        println!("    Last error: {}", error.red());
+    }
+    for path in &status.drifted {
+        println!("    {} {} edited outside the repo", "✏️ ".yellow(), path.display());
+    }
+    Ok(())
+}
+
+/// The dotfiles checkout as of the last fetch; `diff` fetches first.
+fn print_git_status(git_mgr: &GitManager, config_mgr: &ConfigManager) -> Result<()> {
+    println!("{}", "  Dotfiles Repository:".bold());
+    match git_mgr.current_branch() {
+        Some(branch) if branch == config_mgr.config.device.branch => println!("    Checked out: {}", branch),
+        Some(branch) => println!("    Checked out: {} {}", branch, "(not the device branch)".yellow()),
+        None => println!("    Checked out: {}", "no branch".yellow()),
+    }
+    
+    let changed = git_mgr.changed_files()?;
+    if changed.is_empty() {
+        println!("    Working tree: {}", "clean".green());
+    } else {
+        println!("    Working tree: {}", format!("{} uncommitted change(s)", changed.len()).yellow());
+        for path in &changed {
+            println!("      {}", path);
+        }
+    }
+    
+    let mut branches = vec![config_mgr.config.repository.main_branch.as_str()];
+    if !config_mgr.config.device.branch.is_empty() {
+        branches.push(&config_mgr.config.device.branch);
+    }
+    for branch in branches {
+        println!("    {}", git_mgr.divergence(branch)?);
+    }
+    println!();
+    Ok(())
+}
+
+fn handle_diff() -> Result<()> {
+    let config_mgr = ConfigManager::new()?;
+    if config_mgr.config.device.branch.is_empty() {
+        anyhow::bail!("No device branch configured. Run `zshrcman init` first");
+    }
+    let git_mgr = GitManager::open(&ConfigManager::get_dotfiles_path()?)?;
+    let preview = git_mgr.preview_sync(
+        &config_mgr.config.repository.main_branch,
+        &config_mgr.config.device.branch,
+    )?;
+    
+    println!("{}", "📋 Pending sync changes".bold());
+    println!("  {}", preview.main);
+    println!("  {}", preview.device);
+    
+    if !preview.uncommitted.is_empty() {
+        println!("\n{}", format!("Uncommitted ({}), committed before syncing:", preview.uncommitted.len()).bold());
+        for path in &preview.uncommitted {
+            println!("  {}", path);
+        }
+    }
+    for (title, changes) in [
+        (format!("Incoming from {}", config_mgr.config.repository.main_branch), &preview.incoming),
+        (format!("Local on {}", config_mgr.config.device.branch), &preview.outgoing),
+    ] {
+        if !changes.is_empty() {
+            println!("\n{}", format!("{} ({} files):", title, changes.len()).bold());
+            for change in changes {
+                println!("  {} {}", change.status, change.path);
+            }
+        }
+    }
+    
+    let overlapping = preview.overlapping();
+    if !overlapping.is_empty() {
+        println!(
+            "\n{} {}",
+            "⚠️  Changed on both sides, may need --strategy:".yellow(),
+            overlapping.join(", ")
+        );
+    } else if preview.uncommitted.is_empty() && preview.incoming.is_empty() && preview.outgoing.is_empty() {
+        println!("\n{}", "✨ Nothing to sync".green());
+    }
+    Ok(())
+}
+
+fn handle_search(term: &str) -> Result<()> {
+    let matches = search::search(&ConfigManager::get_dotfiles_path()?, term)?;
+    if matches.is_empty() {
+        println!("No matches for '{}'", term);
+        return Ok(());
+    }
+    
+    let plural = if matches.len() == 1 { "" } else { "es" };
+    println!("{}", format!("🔍 {} match{} for '{}'", matches.len(), plural, term).bold());
+    let text_width = matches.iter().map(|m| m.text.chars().count()).max().unwrap_or(0);
+    for m in &matches {
+        let owner = match &m.device {
+            Some(device) => format!("group '{}' on {}", m.group, device),
+            None => format!("group '{}'", m.group),
+        };
+        println!(
+            "  {:<7}  {:<width$}  {}  {}",
+            m.kind.to_string().cyan(),
+            m.text,
+            m.location().dimmed(),
+            owner,
+            width = text_width
+        );
+    }
+    Ok(())
+}
+
+fn handle_which(name: &str) -> Result<()> {
+    let config_mgr = ConfigManager::new()?;
+    let registry = install::installer_registry(&config_mgr);
+    
+    let declarations = which::lookup(&config_mgr, &registry, name)?;
+    if declarations.is_empty() {
+        println!("'{}' isn't declared by any group; try `zshrcman search {}`", name, name);
+        return Ok(());
+    }
+    
+    match &config_mgr.config.active_profile {
+        Some(profile) => println!("{}", format!("🔎 {} (profile '{}')", name, profile).bold()),
+        None => println!("{}", format!("🔎 {}", name).bold()),
+    }
+    for declaration in &declarations {
+        let owner = match &declaration.device {
+            Some(device) => format!("group '{}' on {}", declaration.group, device),
+            None => format!("group '{}'", declaration.group),
+        };
+        println!(
+            "  {} {} in {}  {}",
+            if declaration.active { "✅" } else { "⭕" },
+            declaration.kind,
+            owner.bold(),
+            declaration.location.dimmed()
+        );
+        if declaration.kind != search::Kind::Package {
+            println!("      {}", declaration.text);
+        }
+        
+        let backend = match &declaration.backend {
+            Some(backend) => backend.clone(),
+            None => "no installer registered".yellow().to_string(),
+        };
+        let enabled = if declaration.enabled { "enabled".green() } else { "disabled".yellow() };
+        let installed = if declaration.installed { "installed".green() } else { "not installed".yellow() };
+        println!("      installer: {}, group {}, {}", backend, enabled, installed);
+    }
+    Ok(())
+}
+
+fn handle_verify(json: bool) -> Result<()> {
+    let config_mgr = ConfigManager::new()?;
+    let config = config_mgr.config.clone();
+    let drift = InstallManager::new(config_mgr).verify()?;
+    if !drift.is_empty() {
+        hooks::emit(&config, Event::Drift, serde_json::json!({ "drift": drift }));
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&drift)?);
+    } else if drift.is_empty() {
+        println!("{}", "✅ Everything deployed matches the config".green());
+    } else {
+        verify::print(&drift);
+        println!();
+    }
+    if !drift.is_empty() {
+        return Err(errors::fail(
+            ErrorKind::Drift,
+            format!("{} item(s) drifted; `zshrcman apply` repairs them", drift.len()),
+        ));
+    }
+    Ok(())
+}
+
+fn handle_install_sync_timer(minutes: u64) -> Result<()> {
+    if minutes == 0 {
+        anyhow::bail!("--every needs at least 1 minute");
+    }
+    let config_mgr = ConfigManager::new()?;
+    let timer = SyncTimer {
+        binary: std::env::current_exe().context("Could not locate the zshrcman binary")?,
+        config: config_mgr.config_path().to_path_buf(),
+        minutes,
+        log: config_mgr.data_dir().join("sync-timer.log"),
+    };
+    let enabled = timer.install(&preflight::home_dir()?, &OsType::detect())?;
+    println!("{} Syncing every {} minutes with {}", "✅".green(), minutes, enabled.display());
+    Ok(())
+}
+
+fn handle_daemon(interval: u64) -> Result<()> {
+    let config_mgr = ConfigManager::new()?;
+    features::require(Feature::Daemon, &config_mgr.config)?;
+    let deployed = InstallManager::new(ConfigManager::new()?).deployed_files()?;
+    Daemon::new(config_mgr)
+        .deployed(deployed)
+        .sync_interval((interval > 0).then(|| std::time::Duration::from_secs(interval)))
+        .run()
+}
+
+fn handle_apply(dry_run: bool) -> Result<()> {
+    let mut install_mgr = InstallManager::new(ConfigManager::new()?);
+    let drift = install_mgr.verify()?;
+    if drift.is_empty() {
+        println!("{}", "✅ Everything deployed matches the config; nothing to do".green());
+        return Ok(());
+    }
+    
+    verify::print(&drift);
+    println!();
+    if dry_run {
+        println!("{} item(s) would be repaired", drift.len());
+        return Ok(());
+    }
+    if !prompt::confirm(&format!("Apply {} change(s)?", drift.len()), true)? {
+        return Ok(());
+    }
+    
+    let fixed = install_mgr.apply(&drift)?;
+    println!("{} Repaired {} item(s)", "✅".green(), fixed);
+    Ok(())
+}
+
+fn handle_test_run(image: String, binary: Option<std::path::PathBuf>) -> Result<()> {
+    let config_mgr = ConfigManager::new()?;
+    let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+    let uncommitted = GitManager::open(&dotfiles)?.changed_files()?;
+    if !uncommitted.is_empty() {
+        println!(
+            "{} {} uncommitted change(s) in the dotfiles repo aren't part of the test; commit them first",
+            "⚠️ ".yellow(),
+            uncommitted.len()
+        );
+    }
+    
+    let run = TestRun {
+        image,
+        dotfiles,
+        binary: test_run::binary(binary.as_deref())?,
+        device: config_mgr.config.device.name.clone(),
+        groups: config_mgr.config.groups.enabled_global.clone(),
+        shell: preflight::shell().unwrap_or_else(|_| "/bin/zsh".to_string()),
+    };
+    println!("🐳 Bootstrapping device '{}' in {}", run.device, run.image.cyan());
+    let reports = run.run()?;
+    
+    println!("\n{}", "Groups:".bold());
+    let mut failed = 0;
+    for report in &reports {
+        match &report.outcome {
+            Outcome::Installed => println!("  ✅ {}", report.group),
+            Outcome::Failed(error) => {
+                failed += 1;
+                println!("  ❌ {}: {}", report.group, error);
+            }
+            Outcome::NotRun => println!("  ⏭️  {} (not installed)", report.group),
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} of {} groups failed in {}", failed, reports.len(), run.image);
+    }
+    println!("\n✅ Every group installed in {}", run.image);
+    Ok(())
+}
+
+fn handle_package_command(cmd: PackageCommands) -> Result<()> {
+    let mut config_mgr = ConfigManager::new()?;
+    
+    match cmd {
+        PackageCommands::Install { name, scope, installer, requires } => packages::install(config_mgr, &name, &requires, scope, installer)?,
+        PackageCommands::Remove { name, strategy, force_pinned } => packages::remove(config_mgr, &name, strategy, force_pinned)?,
+        PackageCommands::List { profile, scope, inactive } => packages::list(config_mgr, profile.as_deref(), scope.as_ref(), inactive)?,
+        PackageCommands::Info { name } => packages::info(config_mgr, &name)?,
+        
+        PackageCommands::Hold { name } => {
+            if config_mgr.hold_package(&name)? {
+                println!("{} {}", "📌 Held package:".green(), name);
+            } else {
+                println!("'{}' is already held", name);
+            }
+        }
+        
+        PackageCommands::Unhold { name } => {
+            if config_mgr.unhold_package(&name)? {
+                println!("{} {}", "✅ Released package:".green(), name);
+            } else {
+                println!("'{}' is not held", name);
+            }
+        }
+        
+        PackageCommands::Held => packages::held(&config_mgr),
+    }
+    
+    Ok(())
+}
+
+fn handle_config_command(cmd: ConfigCommands) -> Result<()> {
+    match cmd {
+        ConfigCommands::Validate => {
+            let problems = Validator::new(
+                &ConfigManager::get_config_path()?,
+                &ConfigManager::get_dotfiles_path()?,
+            )
+            .run();
+            
+            if problems.is_empty() {
+                println!("{}", "✅ No problems found".green());
+                return Ok(());
+            }
             
-            use dialoguer::Confirm;
-            let proceed = Confirm::new()
-                .with_prompt("Continue anyway?")
-                .default(false)
-                .interact()?;
+            validate::print(&problems);
             
-            if !proceed {
-                anyhow::bail!("Aborted due to potential typo");
+            let errors = problems.iter().filter(|problem| problem.severity == Severity::Error).count();
+            println!();
+            println!("{} error(s), {} warning(s)", errors, problems.len() - errors);
+            if errors > 0 {
+                return Err(errors::fail(ErrorKind::Config, format!("{} error(s) in the configuration", errors)));
+            }
+        }
+        
+        ConfigCommands::Edit => {
+            let config_path = ConfigManager::get_config_path()?;
+            let dotfiles_path = ConfigManager::get_dotfiles_path()?;
+            let initial = toml::to_string_pretty(&Config::default())?;
+            if Editor::from_env()?.edit_checked(&config_path, &initial, |copy| Validator::new(copy, &dotfiles_path).config_file())? {
+                println!("{}", "✅ Saved config.toml".green());
+            } else {
+                println!("No changes to config.toml");
             }
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,6 +1,5 @@
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +27,70 @@ pub struct Config {
     
     #[serde(default)]
     pub installations: HashMap<String, InstallationRecord>,
+    
+    #[serde(default)]
+    pub features: HashMap<String, bool>,
+    
+    /// User-defined values available to templates as `{{ vars.<name> }}`.
+    #[serde(default)]
+    pub variables: HashMap<String, toml::Value>,
+    
+    #[serde(default)]
+    pub install: InstallSettings,
+    
+    #[serde(default)]
+    pub retention: RetentionSettings,
+    
+    /// External group collections by name; their groups are referenced as
+    /// `<source>/<group>`.
+    #[serde(default)]
+    pub sources: BTreeMap<String, GroupSource>,
+    
+    /// Further dotfiles repos by name, next to the one in `[repository]`;
+    /// their groups are referenced as `<name>/<group>`.
+    #[serde(default)]
+    pub repositories: BTreeMap<String, NamedRepository>,
+    
+    /// Packages held with `zshrcman package hold`: reinstalls leave them at
+    /// their installed version, and bulk removals never uninstall them.
+    #[serde(default)]
+    pub held_packages: BTreeSet<String>,
+    
+    /// Commands and webhooks told about installs, syncs and other changes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<OutputHook>,
+    
+    /// Prompt setups by name, selected with `theme` or a profile's `theme`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub themes: BTreeMap<String, Theme>,
+    
+    /// Theme used when the active profile doesn't pick one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    
+    #[serde(default)]
+    pub zsh: ZshSettings,
+    
+    #[serde(default)]
+    pub windows: WindowsSettings,
+}
+
+/// `[zsh]` in config.toml.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZshSettings {
+    /// Move the managed blocks of `.zshrc` into one file and `zcompile` it.
+    pub compile: bool,
+}
+
+/// `[windows]` in config.toml.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowsSettings {
+    /// Write the active profile's variables and PATH entries into the user
+    /// environment in the registry, so programs started outside a
+    /// configured shell see them too.
+    pub persist_environment: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -35,12 +98,157 @@ pub struct Repository {
     pub url: Option<String>,
     pub main_branch: String,
     pub dotfiles_path: PathBuf,
+    /// Secondary remotes by git remote name, e.g. a self-hosted mirror.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remotes: BTreeMap<String, RemoteConfig>,
+    /// What `sync --force` does with conflicts; `ours` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub force_strategy: Option<ConflictStrategy>,
+    /// Author and signing of the commits zshrcman makes.
+    #[serde(default, skip_serializing_if = "CommitIdentity::is_unset")]
+    pub identity: CommitIdentity,
+}
+
+/// Who zshrcman's commits are by and how they're signed. Anything unset
+/// comes from git's own config (`user.name`, `user.email`, `commit.gpgsign`,
+/// `gpg.format`, `user.signingkey`), as for a commit made with `git commit`.
+///
+/// ```toml
+/// [repository.identity]
+/// name = "Jane Doe"
+/// email = "jane@example.com"
+/// sign = true
+/// signing_format = "ssh"
+/// signing_key = "~/.ssh/id_ed25519.pub"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitIdentity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sign: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_format: Option<SigningFormat>,
+    /// GPG key id, or the SSH key file to sign with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+}
+
+impl CommitIdentity {
+    pub fn is_unset(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningFormat {
+    Gpg,
+    Ssh,
+}
+
+/// How `sync` handles a file changed on both the device branch and the main
+/// branch while rebasing one onto the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Stop and leave the device branch as it was.
+    #[default]
+    Abort,
+    /// Keep this device's version.
+    Ours,
+    /// Keep the main branch's version.
+    Theirs,
+    /// Show both versions of each file and ask which to keep.
+    Interactive,
+}
+
+impl std::str::FromStr for ConflictStrategy {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abort" => Ok(Self::Abort),
+            "ours" => Ok(Self::Ours),
+            "theirs" => Ok(Self::Theirs),
+            "interactive" => Ok(Self::Interactive),
+            _ => Err(format!("unknown strategy '{}'; use abort, ours, theirs or interactive", s)),
+        }
+    }
+}
+
+/// Another dotfiles repo, e.g. a company one next to a personal one. Unlike
+/// a `[sources]` entry it is synced like the main repo, on its own branches,
+/// and can be committed to. It is cloned under `<data_dir>/repos/<name>`.
+///
+/// ```toml
+/// [repositories.work]
+/// url = "git@git.company.com:me/dotfiles.git"
+/// main_branch = "main"       # default
+/// branch = "device/laptop"   # default: the device branch
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedRepository {
+    pub url: String,
+    #[serde(default = "default_main_branch")]
+    pub main_branch: String,
+    /// Branch this machine works on; defaults to `[device].branch`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remotes: BTreeMap<String, RemoteConfig>,
+}
+
+fn default_main_branch() -> String {
+    "main".to_string()
+}
+
+/// A mirror of the dotfiles repository. It is pushed to after origin and
+/// fetched from when origin is unreachable.
+///
+/// ```toml
+/// [repository.remotes.gitea]
+/// url = "https://git.example.com/me/dotfiles.git"
+/// username = "me"
+/// token_env = "GITEA_TOKEN"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    pub url: String,
+    /// Set to false for a fetch-only fallback.
+    pub push: bool,
+    /// Private key for SSH URLs; the SSH agent is used when unset.
+    pub ssh_key: Option<PathBuf>,
+    /// User name for HTTPS URLs.
+    pub username: Option<String>,
+    /// Environment variable holding the HTTPS password or access token.
+    pub token_env: Option<String>,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            push: true,
+            ssh_key: None,
+            username: None,
+            token_env: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Device {
     pub name: String,
     pub branch: String,
+    /// What kind of machine this is, e.g. `["laptop", "gpu"]`. Groups whose
+    /// `target_tags` share one are installed without being enabled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -49,12 +257,360 @@ pub struct Groups {
     pub per_device: Vec<String>,
     pub enabled_global: Vec<String>,
     pub enabled_devices: Vec<String>,
+    /// Groups targeting one of the device's tags that `group disable` turned
+    /// off on this machine anyway.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_tagged: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AliasGroup {
-    pub items: Vec<String>,
-    pub active: Vec<String>,
+    #[serde(deserialize_with = "lenient_aliases")]
+    pub items: Vec<Alias>,
+    #[serde(deserialize_with = "lenient_aliases")]
+    pub active: Vec<Alias>,
+}
+
+/// A shell alias. Written either as the old one-liner (`alias ll='ls -la'`)
+/// or as a table with `name`, `command`, `description`, `shells` and `abbr`;
+/// aliases with none of those extras are saved as one-liners again.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "AliasDef", into = "AliasDef")]
+pub struct Alias {
+    pub name: String,
+    pub command: String,
+    pub description: Option<String>,
+    /// Shells it's defined in (`zsh`, `bash`, `fish`, `powershell`, `nu`);
+    /// all of them when empty.
+    pub shells: Vec<String>,
+    /// Whether fish gets it as an abbreviation, expanded as it's typed, or
+    /// as an alias. Abbreviations unless set to false.
+    pub abbr: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum AliasDef {
+    Line(String),
+    Table {
+        name: String,
+        command: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        shells: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        abbr: Option<bool>,
+    },
+}
+
+impl TryFrom<AliasDef> for Alias {
+    type Error = String;
+    
+    fn try_from(def: AliasDef) -> Result<Self, Self::Error> {
+        match def {
+            AliasDef::Line(line) => Alias::parse(&line).ok_or_else(|| format!("not an alias definition: {}", line)),
+            AliasDef::Table { name, command, description, shells, abbr } => Ok(Alias { name, command, description, shells, abbr }),
+        }
+    }
+}
+
+/// A list of aliases in which a line that isn't an alias definition is
+/// skipped with a warning rather than failing the whole file.
+fn lenient_aliases<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Alias>, D::Error> {
+    let defs = Vec::<AliasDef>::deserialize(deserializer)?;
+    Ok(defs
+        .into_iter()
+        .filter_map(|def| match Alias::try_from(def) {
+            Ok(alias) => Some(alias),
+            Err(e) => {
+                crate::modules::output::warn(format!("⚠️  Skipping alias: {}", e));
+                None
+            }
+        })
+        .collect())
+}
+
+/// Inside double quotes a backslash only escapes `"`, `\`, `$` and `` ` ``.
+fn unescape_double_quoted(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(&next) = chars.peek() {
+                if matches!(next, '"' | '\\' | '$' | '`') {
+                    unescaped.push(next);
+                    chars.next();
+                    continue;
+                }
+            }
+        }
+        unescaped.push(c);
+    }
+    unescaped
+}
+
+impl From<Alias> for AliasDef {
+    fn from(alias: Alias) -> Self {
+        if alias.description.is_none() && alias.shells.is_empty() && alias.abbr.is_none() {
+            return AliasDef::Line(alias.to_string());
+        }
+        AliasDef::Table {
+            name: alias.name,
+            command: alias.command,
+            description: alias.description,
+            shells: alias.shells,
+            abbr: alias.abbr,
+        }
+    }
+}
+
+impl Alias {
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            description: None,
+            shells: Vec::new(),
+            abbr: None,
+        }
+    }
+    
+    /// Parses `alias ll='ls -la'`, `alias ll="ls -la"`, `ll=ls` and the like.
+    /// Flags such as zsh's `-g` are skipped, and quoting is undone the way
+    /// the shell would.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let mut rest = line.strip_prefix("alias ").unwrap_or(line).trim_start();
+        while rest.starts_with('-') {
+            rest = rest.split_once(char::is_whitespace)?.1.trim_start();
+        }
+        let (name, command) = rest.split_once('=')?;
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return None;
+        }
+        let command = command.trim();
+        let unquoted = ['\'', '"']
+            .iter()
+            .find_map(|quote| command.strip_prefix(*quote)?.strip_suffix(*quote))
+            .unwrap_or(command);
+        let command = if command.starts_with('\'') {
+            unquoted.replace("'\\''", "'")
+        } else if command.starts_with('"') {
+            unescape_double_quoted(unquoted)
+        } else {
+            unquoted.to_string()
+        };
+        Some(Self::new(name, command))
+    }
+    
+    /// Whether the alias is defined in `shell`.
+    pub fn applies_to(&self, shell: &str) -> bool {
+        self.shells.is_empty() || self.shells.iter().any(|s| s == shell)
+    }
+    
+    /// The definition in `shell`'s syntax, or `None` if the alias isn't
+    /// meant for it. Fish gets an abbreviation unless `abbr` is false, and
+    /// PowerShell a function, since its aliases can't take arguments. Nu
+    /// aliases name a single command, so one chaining commands (see
+    /// [`Alias::chains_commands`]) is left in a comment there, as for cmd.
+    pub fn render(&self, shell: &str) -> Option<String> {
+        if !self.applies_to(shell) {
+            return None;
+        }
+        let quoted = self.quoted_command();
+        Some(match shell {
+            "fish" if self.abbr != Some(false) => format!("abbr --add {} {}", self.name, quoted),
+            "fish" => format!("alias {} {}", self.name, quoted),
+            "nu" if self.chains_commands() => format!("# {} = {} (nu aliases can't chain commands)", self.name, self.command),
+            "nu" => format!("alias {} = {}", self.name, self.command),
+            "powershell" | "pwsh" => format!("function {} {{ {} }}", self.name, self.command),
+            "cmd" => format!("REM {} = {}", self.name, self.command),
+            _ => format!("alias {}={}", self.name, quoted),
+        })
+    }
+    
+    /// Whether the command runs more than one command through `&&`, `||`,
+    /// `;` or `$(...)`.
+    pub fn chains_commands(&self) -> bool {
+        ["&&", "||", ";", "$("].iter().any(|token| self.command.contains(token))
+    }
+    
+    fn quoted_command(&self) -> String {
+        format!("'{}'", self.command.replace('\'', "'\\''"))
+    }
+}
+
+/// A shell function from a group's `[[functions]]`. `body` is what goes
+/// between the braces.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShellFunction {
+    pub name: String,
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Shells it's defined in; all of them when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shells: Vec<String>,
+}
+
+impl ShellFunction {
+    pub fn applies_to(&self, shell: &str) -> bool {
+        self.shells.is_empty() || self.shells.iter().any(|s| s == shell)
+    }
+    
+    /// The definition in `shell`'s syntax, or `None` if the function isn't
+    /// meant for it, the shell has none (cmd) or can't run its body (nu).
+    pub fn render(&self, shell: &str) -> Option<String> {
+        if !self.applies_to(shell) {
+            return None;
+        }
+        let body = format!("{}\n", self.body.trim_end());
+        match shell {
+            "fish" => Some(format!("function {}\n{}end\n", self.name, body)),
+            "powershell" | "pwsh" => Some(format!("function {} {{\n{}}}\n", self.name, body)),
+            "cmd" | "nu" => None,
+            _ => Some(format!("{}() {{\n{}}}\n", self.name, body)),
+        }
+    }
+}
+
+/// A zsh plugin from the `plugins` group's `[[plugins]]`, cloned into the
+/// data dir and sourced from `.zshrc`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZshPlugin {
+    pub name: String,
+    pub url: String,
+    /// Branch, tag or commit to follow instead of the default branch.
+    #[serde(default, rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// File to source, relative to the checkout; found by name otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Source it right before the first prompt instead of during startup.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub defer: bool,
+}
+
+/// A `[[lazy]]` entry: stubs for `commands` that run `init` the first time
+/// one of them is called, then call the real thing. `nvm`, `pyenv` and
+/// `kubectl` have built-in defaults, so `name` alone is enough for them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LazyLoad {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init: Option<String>,
+}
+
+/// A `[[cron]]` entry, e.g.
+///
+/// ```toml
+/// [[cron]]
+/// schedule = "0 3 * * *"
+/// command = "~/bin/backup.sh"
+/// description = "Nightly backup"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CronJob {
+    /// Five cron fields, or a shortcut such as `@daily`.
+    pub schedule: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A `[[services]]` entry: a systemd user unit (`.service`, `.timer`,
+/// `.socket`, `.path`), deployed and enabled on Linux, or a launchd agent
+/// (`.plist`), loaded on macOS, e.g.
+///
+/// ```toml
+/// [[services]]
+/// source = "services/backup.service"
+/// enable = false  # started by the timer
+///
+/// [[services]]
+/// source = "services/backup.timer"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Service {
+    /// Unit or agent file, relative to the group's repo.
+    pub source: PathBuf,
+    /// Enable and start it; otherwise it's only deployed.
+    #[serde(default = "default_enable")]
+    pub enable: bool,
+}
+
+fn default_enable() -> bool {
+    true
+}
+
+/// A `[[fonts]]` entry: font files from the dotfiles repo or a download,
+/// e.g.
+///
+/// ```toml
+/// [[fonts]]
+/// name = "JetBrainsMono Nerd Font"
+/// url = "https://github.com/ryanoasis/nerd-fonts/releases/download/v3.2.1/JetBrainsMono.tar.xz"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Font {
+    pub name: String,
+    /// A font file, or a `.zip`/`.tar.*` archive of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// A font file or a directory of them, relative to the group's repo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PathBuf>,
+}
+
+/// A `[[macos_defaults]]` entry: a `defaults write` applied on macOS, e.g.
+///
+/// ```toml
+/// [[macos_defaults]]
+/// domain = "com.apple.dock"
+/// key = "autohide"
+/// value = true
+/// restart = "Dock"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacosDefault {
+    /// Preferences domain; `NSGlobalDomain` for global settings.
+    pub domain: String,
+    pub key: String,
+    pub value: DefaultsValue,
+    /// Process to `killall` afterwards so it picks the change up, e.g.
+    /// `Dock` or `Finder`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<String>,
+}
+
+/// A `defaults` value; its type picks `-bool`, `-int`, `-float` or `-string`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DefaultsValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl std::fmt::Display for DefaultsValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DefaultsValue::Bool(value) => write!(f, "{}", value),
+            DefaultsValue::Int(value) => write!(f, "{}", value),
+            DefaultsValue::Float(value) => write!(f, "{}", value),
+            DefaultsValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl std::fmt::Display for Alias {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "alias {}={}", self.name, self.quoted_command())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,29 +619,380 @@ pub struct InstallStatus {
     pub success: bool,
     pub timestamp: Option<chrono::DateTime<chrono::Utc>>,
     pub error: Option<String>,
+    /// How many tries the last install took; more than 1 means it was retried.
+    #[serde(default)]
+    pub attempts: u32,
+    /// Hash of the group's inputs at the last successful install.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Timeout and retry behaviour for package manager commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Kill a single package manager command after this long. 0 disables it.
+    pub timeout_secs: u64,
+    /// Extra attempts after a failed group install.
+    pub retries: u32,
+    /// Delay before the first retry; doubles for each further retry.
+    pub backoff_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 1800,
+            retries: 2,
+            backoff_secs: 5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        (self.timeout_secs > 0).then(|| std::time::Duration::from_secs(self.timeout_secs))
+    }
+    
+    /// Delay before retry number `retry` (1-based).
+    pub fn backoff(&self, retry: u32) -> std::time::Duration {
+        let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+        std::time::Duration::from_secs(self.backoff_secs.saturating_mul(factor))
+    }
+}
+
+/// One installer's `[install.installers.<name>]`: settings left out come
+/// from `[install]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backoff_secs: Option<u64>,
+}
+
+/// `[install]` in config.toml: the default policy plus per-installer ones,
+/// e.g. `[install.installers.npm]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstallSettings {
+    #[serde(flatten)]
+    pub policy: RetryPolicy,
+    pub installers: HashMap<String, RetryOverride>,
+    #[serde(skip_serializing_if = "WindowsInstallSettings::is_default")]
+    pub windows: WindowsInstallSettings,
+}
+
+/// `[install.windows]`: which package manager the `windows` installer uses,
+/// e.g.
+///
+/// ```toml
+/// [install.windows]
+/// prefer = ["scoop", "winget"]
+/// packages = { "Microsoft.PowerToys" = "winget" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowsInstallSettings {
+    /// The first of these that's installed takes packages without an entry
+    /// in `packages`.
+    pub prefer: Vec<String>,
+    /// Package manager per package.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub packages: BTreeMap<String, String>,
+}
+
+impl Default for WindowsInstallSettings {
+    fn default() -> Self {
+        Self {
+            prefer: vec!["winget".to_string(), "scoop".to_string(), "choco".to_string()],
+            packages: BTreeMap::new(),
+        }
+    }
+}
+
+impl WindowsInstallSettings {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl InstallSettings {
+    pub fn policy_for(&self, installer: &str) -> RetryPolicy {
+        let Some(custom) = self.installers.get(installer) else {
+            return self.policy;
+        };
+        RetryPolicy {
+            timeout_secs: custom.timeout_secs.unwrap_or(self.policy.timeout_secs),
+            retries: custom.retries.unwrap_or(self.policy.retries),
+            backoff_secs: custom.backoff_secs.unwrap_or(self.policy.backoff_secs),
+        }
+    }
+}
+
+/// `[retention]` in config.toml: what `zshrcman clean logs` and
+/// `clean snapshots` keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionSettings {
+    /// Drop operation log entries and log files older than this.
+    pub log_days: u32,
+    /// Keep this many of the newest config history entries.
+    pub history_entries: usize,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            log_days: 90,
+            history_entries: 200,
+        }
+    }
+}
+
+/// A `[[hooks]]` entry: a shell command that gets each event as JSON on
+/// stdin, or a URL the event is POSTed to.
+///
+/// ```toml
+/// [[hooks]]
+/// url = "https://hooks.example.com/zshrcman"
+/// events = ["install", "sync_conflict"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputHook {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Events to receive; all of them when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_hook_timeout() -> u64 {
+    10
+}
+
+/// A git repo of shared groups, cloned under `<data_dir>/sources/<name>`.
+///
+/// ```toml
+/// [sources]
+/// work-groups = "git@github.com:platform/dotgroups.git"
+/// team = { url = "https://github.com/team/groups.git", ref = "v2.1.0" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GroupSource {
+    /// Follows the remote's default branch.
+    Url(String),
+    /// Stays on a branch, tag or commit.
+    Pinned {
+        url: String,
+        #[serde(rename = "ref")]
+        reference: String,
+    },
+}
+
+impl GroupSource {
+    pub fn url(&self) -> &str {
+        match self {
+            GroupSource::Url(url) | GroupSource::Pinned { url, .. } => url,
+        }
+    }
+    
+    pub fn reference(&self) -> Option<&str> {
+        match self {
+            GroupSource::Url(_) => None,
+            GroupSource::Pinned { reference, .. } => Some(reference),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GroupConfig {
     pub name: String,
     #[serde(default)]
     pub description: String,
     #[serde(default)]
     pub packages: Vec<String>,
-    #[serde(default)]
-    pub aliases: Vec<String>,
+    #[serde(default, deserialize_with = "lenient_aliases")]
+    pub aliases: Vec<Alias>,
+    /// Shell functions, deployed to `~/.zsh_functions.d` for zsh.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<ShellFunction>,
+    /// Zsh completion files from the repo's `completions/` directory,
+    /// e.g. `["_mytool"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub completions: Vec<String>,
+    /// Zsh options to turn on and off, e.g. `setopt = ["auto_cd"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub setopt: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unsetopt: Vec<String>,
+    /// Zsh key bindings, key sequence to widget, e.g. `"^R" = "history-incremental-search-backward"`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bindkey: BTreeMap<String, String>,
+    /// Heavy tools to load on first use instead of at startup.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lazy: Vec<LazyLoad>,
+    /// Zsh plugin repos, for the `plugins` group.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<ZshPlugin>,
     #[serde(default)]
     pub scripts: Vec<String>,
     #[serde(default)]
     pub files: Vec<FileMapping>,
     #[serde(default)]
     pub ssh_keys: Vec<String>,
+    /// Exported GPG keys in the repo's `gpg/` directory, for the `gpg` group.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gpg_keys: Vec<String>,
+    /// Jobs for the user's crontab.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cron: Vec<CronJob>,
+    /// Systemd user units and launchd agents to deploy and enable.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<Service>,
+    /// Fonts to install, for the `fonts` group.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fonts: Vec<Font>,
+    /// `defaults write` settings, applied on macOS only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub macos_defaults: Vec<MacosDefault>,
+    /// Key git signs commits with (`user.signingkey`, `commit.gpgsign`),
+    /// for the `gpg` group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+    /// Install order: lower goes first, ties keep their configured order.
+    /// Lines groups append to rc files follow the order the groups last
+    /// installed in, so this order after `install --all`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
+    /// Backend used for `packages`; defaults to the installer named like the group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installer: Option<String>,
+    /// Only install the group on machines matching these facts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<GroupCondition>,
+    /// Install the group on every device with one of these tags, e.g.
+    /// `["gpu"]`, whether or not it was enabled there.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub target_tags: Vec<String>,
+    /// Shortcuts for directories, e.g. `proj = "~/code/project"` for
+    /// `cd ~proj` in zsh.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub named_dirs: BTreeMap<String, String>,
+    /// Environment variables, e.g. `EDITOR = "nvim"`. A profile's and a
+    /// device group's win over these (see `env_layers`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Mac App Store ids by app name, so a `mas` group's `packages` can list
+    /// names, e.g. `Xcode = 497799835`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub apps: BTreeMap<String, u64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn is_zero(value: &i32) -> bool {
+    *value == 0
+}
+
+impl GroupConfig {
+    /// Layers a device-level definition of the same group over this one:
+    /// lists gain the entries they don't have yet, and the device's
+    /// description, priority, installer, condition, signing key, named dirs,
+    /// variables, key bindings and app ids win.
+    pub fn merge(&mut self, device: GroupConfig) {
+        fn extend<T: PartialEq>(list: &mut Vec<T>, more: Vec<T>) {
+            for item in more {
+                if !list.contains(&item) {
+                    list.push(item);
+                }
+            }
+        }
+        
+        if !device.description.is_empty() {
+            self.description = device.description;
+        }
+        extend(&mut self.packages, device.packages);
+        extend(&mut self.aliases, device.aliases);
+        extend(&mut self.functions, device.functions);
+        extend(&mut self.lazy, device.lazy);
+        extend(&mut self.plugins, device.plugins);
+        extend(&mut self.completions, device.completions);
+        extend(&mut self.setopt, device.setopt);
+        extend(&mut self.unsetopt, device.unsetopt);
+        extend(&mut self.scripts, device.scripts);
+        extend(&mut self.files, device.files);
+        extend(&mut self.ssh_keys, device.ssh_keys);
+        extend(&mut self.gpg_keys, device.gpg_keys);
+        extend(&mut self.macos_defaults, device.macos_defaults);
+        extend(&mut self.fonts, device.fonts);
+        extend(&mut self.services, device.services);
+        extend(&mut self.cron, device.cron);
+        extend(&mut self.target_tags, device.target_tags);
+        if device.priority != 0 {
+            self.priority = device.priority;
+        }
+        self.installer = device.installer.or(self.installer.take());
+        self.when = device.when.or(self.when.take());
+        self.signing_key = device.signing_key.or(self.signing_key.take());
+        self.named_dirs.extend(device.named_dirs);
+        self.env.extend(device.env);
+        self.bindkey.extend(device.bindkey);
+        self.apps.extend(device.apps);
+    }
+    
+    /// `packages` with the names in `apps` replaced by their ids; other
+    /// packages are kept as they are.
+    pub fn package_ids(&self) -> Vec<String> {
+        self.packages
+            .iter()
+            .map(|package| match self.apps.get(package) {
+                Some(id) => id.to_string(),
+                None => package.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Limits a group to matching machines. Every listed constraint must hold;
+/// empty lists don't constrain anything.
+///
+/// ```toml
+/// [when]
+/// os = ["macos"]
+/// requires = ["brew"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GroupCondition {
+    pub os: Vec<String>,
+    pub arch: Vec<String>,
+    pub hostname: Vec<String>,
+    pub shell: Vec<String>,
+    pub wsl: Option<bool>,
+    /// Programs that must be on PATH, typically package managers.
+    pub requires: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileMapping {
     pub source: PathBuf,
     pub target: PathBuf,
+    /// Render the source as a template. Sources ending in `.tera` always are.
+    #[serde(default)]
+    pub template: bool,
+}
+
+impl FileMapping {
+    pub fn is_template(&self) -> bool {
+        self.template || self.source.extension().is_some_and(|ext| ext == "tera")
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -93,9 +1000,20 @@ pub enum InstallerType {
     Brew,
     Npm,
     Pnpm,
+    Flatpak,
+    Snap,
+    Mas,
+    Nix,
+    Winget,
+    Scoop,
+    Choco,
+    Windows,
     Aliases,
     Ssh,
+    Gpg,
+    Fonts,
     Zshrc,
+    Plugins,
     Custom(String),
 }
 
@@ -105,9 +1023,20 @@ impl InstallerType {
             "brew" => Self::Brew,
             "npm" => Self::Npm,
             "pnpm" => Self::Pnpm,
+            "flatpak" => Self::Flatpak,
+            "snap" => Self::Snap,
+            "mas" => Self::Mas,
+            "nix" => Self::Nix,
+            "winget" => Self::Winget,
+            "scoop" => Self::Scoop,
+            "choco" => Self::Choco,
+            "windows" => Self::Windows,
             "aliases" => Self::Aliases,
             "ssh" => Self::Ssh,
+            "gpg" => Self::Gpg,
+            "fonts" => Self::Fonts,
             "zshrc" => Self::Zshrc,
+            "plugins" => Self::Plugins,
             _ => Self::Custom(name.to_string()),
         }
     }
@@ -120,6 +1049,9 @@ impl Default for Config {
                 url: None,
                 main_branch: "main".to_string(),
                 dotfiles_path: PathBuf::from("~/.local/share/zshrcman/dotfiles"),
+                remotes: BTreeMap::new(),
+                force_strategy: None,
+                identity: CommitIdentity::default(),
             },
             device: Device::default(),
             groups: Groups {
@@ -127,12 +1059,25 @@ impl Default for Config {
                 per_device: vec![],
                 enabled_global: vec!["default".to_string()],
                 enabled_devices: vec![],
+                disabled_tagged: vec![],
             },
             aliases: HashMap::new(),
             status: HashMap::new(),
             profiles: HashMap::new(),
             active_profile: None,
             installations: HashMap::new(),
+            features: HashMap::new(),
+            variables: HashMap::new(),
+            install: InstallSettings::default(),
+            retention: RetentionSettings::default(),
+            sources: BTreeMap::new(),
+            repositories: BTreeMap::new(),
+            held_packages: BTreeSet::new(),
+            hooks: Vec::new(),
+            themes: BTreeMap::new(),
+            theme: None,
+            zsh: ZshSettings::default(),
+            windows: WindowsSettings::default(),
         }
     }
 }
@@ -144,6 +1089,83 @@ pub struct Profile {
     pub packages: HashSet<String>,
     pub environment: EnvironmentState,
     pub os_overrides: HashMap<OsType, ProfileOverride>,
+    /// Groups installed only while this profile (or a child of it) is
+    /// active, on top of the global and device ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enabled_groups: Vec<String>,
+    /// Theme from `[themes]` while this profile (or a child of it that picks
+    /// none) is active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Shell commands run, with the profile's environment, after it becomes
+    /// active.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_activate: Vec<String>,
+    /// Shell commands run, with the profile's environment, before it stops
+    /// being active.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_deactivate: Vec<String>,
+    /// Git identity and SSH key while this profile (or a child of it that
+    /// sets none) is active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<ProfileIdentity>,
+}
+
+/// Who git commits are by, and which SSH key is used, while a profile is
+/// active, e.g.
+///
+/// ```toml
+/// [profiles.work.identity]
+/// name = "Jane Doe"
+/// email = "jane@corp.example"
+/// ssh_key = "~/.ssh/id_work"
+/// ssh_hosts = ["github.com"]
+/// git_dirs = ["~/work/"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileIdentity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Private key SSH offers first; with `IdentitiesOnly`, the only one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<String>,
+    /// Hosts the key is for. Without any, `~/.ssh/config` is left alone.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ssh_hosts: Vec<String>,
+    /// Only use the git identity in repositories below these directories;
+    /// everywhere when empty.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub git_dirs: Vec<String>,
+}
+
+/// A prompt and its configuration, e.g.
+///
+/// ```toml
+/// [themes.work]
+/// prompt = "starship"
+/// config = "themes/work.starship.toml"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Theme {
+    pub prompt: PromptKind,
+    /// Prompt config in the dotfiles repo: a `starship.toml` for starship, a
+    /// `.p10k.zsh` for powerlevel10k.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<PathBuf>,
+    /// Where to clone powerlevel10k from instead of its GitHub repo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptKind {
+    Starship,
+    #[serde(alias = "p10k")]
+    Powerlevel10k,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -162,6 +1184,28 @@ pub struct InstallationRecord {
     pub scope: InstallScope,
     pub location: Option<PathBuf>,
     pub installer_type: String,
+    /// Set with `zshrcman pin`: reinstalls never upgrade the package, and
+    /// removals refuse to uninstall it unless forced.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Packages installed with `--requires` this one, and groups (as
+    /// `group <name>`) that installed it. A package installed only as a
+    /// dependency is removed once nothing requires it any more.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub required_by: BTreeSet<String>,
+    /// Every executable the package provides, when its backend can tell;
+    /// otherwise only `location` is linked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binaries: Vec<PathBuf>,
+}
+
+impl InstallationRecord {
+    /// Whether `zshrcman pin` made this record for a package zshrcman never
+    /// installed itself, e.g. one a group installs. Such a record only holds
+    /// the pin and doesn't count as installed.
+    pub fn is_pin_only(&self) -> bool {
+        self.installer_type.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -173,6 +1217,18 @@ pub enum InstallationSource {
     Dependency(String),
 }
 
+impl std::fmt::Display for InstallationSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallationSource::Profile(profile) => write!(f, "profile {}", profile),
+            InstallationSource::Global => write!(f, "global"),
+            InstallationSource::System => write!(f, "system"),
+            InstallationSource::Manual => write!(f, "manual"),
+            InstallationSource::Dependency(package) => write!(f, "dependency of {}", package),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum InstallScope {
     System,
@@ -182,6 +1238,32 @@ pub enum InstallScope {
     Device,
 }
 
+impl std::str::FromStr for InstallScope {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "profile" => Ok(Self::Profile),
+            "global" => Ok(Self::Global),
+            "local" => Ok(Self::Local),
+            _ => Err(format!("unknown scope '{}'; use profile, global or local", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for InstallScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            InstallScope::System => "system",
+            InstallScope::Global => "global",
+            InstallScope::Profile => "profile",
+            InstallScope::Local => "local",
+            InstallScope::Device => "device",
+        };
+        f.write_str(name)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnvironmentState {
     pub paths_prepend: Vec<String>,
@@ -189,6 +1271,19 @@ pub struct EnvironmentState {
     pub variables: HashMap<String, String>,
     pub aliases: HashMap<String, String>,
     pub active: bool,
+    /// Startup stage per variable name (use `PATH` for the path entries).
+    /// Anything not listed is rendered into the rc file.
+    #[serde(default)]
+    pub stages: HashMap<String, ShellStage>,
+    /// Directory shortcuts like a group's `named_dirs`; a profile's win over
+    /// a group's of the same name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub named_dirs: BTreeMap<String, String>,
+    /// Variables whose values come from a secret backend, as references
+    /// like `op://vault/item/field` (see `secrets::SecretRef`). Startup files
+    /// fetch them when the shell starts instead of holding the value.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub secrets: HashMap<String, String>,
 }
 
 impl Default for EnvironmentState {
@@ -199,6 +1294,72 @@ impl Default for EnvironmentState {
             variables: HashMap::new(),
             aliases: HashMap::new(),
             active: true,
+            stages: HashMap::new(),
+            named_dirs: BTreeMap::new(),
+            secrets: HashMap::new(),
+        }
+    }
+}
+
+impl EnvironmentState {
+    pub fn stage_of(&self, key: &str) -> ShellStage {
+        self.stages.get(key).copied().unwrap_or(ShellStage::Rc)
+    }
+    
+    /// The subset of this state that belongs in `stage`. Aliases and named
+    /// dirs only make sense in interactive shells, so they always land in the
+    /// rc stage.
+    pub fn for_stage(&self, stage: ShellStage) -> EnvironmentState {
+        let paths_here = self.stage_of("PATH") == stage;
+        
+        EnvironmentState {
+            paths_prepend: if paths_here { self.paths_prepend.clone() } else { Vec::new() },
+            paths_append: if paths_here { self.paths_append.clone() } else { Vec::new() },
+            variables: self.variables
+                .iter()
+                .filter(|(key, _)| self.stage_of(key) == stage)
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            aliases: if stage == ShellStage::Rc { self.aliases.clone() } else { HashMap::new() },
+            active: self.active,
+            stages: self.stages.clone(),
+            named_dirs: if stage == ShellStage::Rc { self.named_dirs.clone() } else { BTreeMap::new() },
+            secrets: self.secrets
+                .iter()
+                .filter(|(key, _)| self.stage_of(key) == stage)
+                .map(|(key, reference)| (key.clone(), reference.clone()))
+                .collect(),
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.paths_prepend.is_empty()
+            && self.paths_append.is_empty()
+            && self.variables.is_empty()
+            && self.aliases.is_empty()
+            && self.named_dirs.is_empty()
+            && self.secrets.is_empty()
+    }
+}
+
+/// Which startup file an environment entry is rendered into. For zsh these are
+/// `.zshenv` (every shell), `.zprofile` (login shells) and `.zshrc` (interactive).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ShellStage {
+    Env,
+    Profile,
+    Rc,
+}
+
+impl ShellStage {
+    pub const ALL: [ShellStage; 3] = [ShellStage::Env, ShellStage::Profile, ShellStage::Rc];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShellStage::Env => "env",
+            ShellStage::Profile => "profile",
+            ShellStage::Rc => "rc",
         }
     }
 }
@@ -232,4 +1393,18 @@ pub enum RemovalStrategy {
     SmartRemove,
     ForceRemove,
     MarkUnused,
+}
+
+impl std::str::FromStr for RemovalStrategy {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "deactivate" => Ok(Self::Deactivate),
+            "smart" => Ok(Self::SmartRemove),
+            "force" => Ok(Self::ForceRemove),
+            "mark-unused" => Ok(Self::MarkUnused),
+            _ => Err(format!("unknown strategy '{}'; use deactivate, smart, force or mark-unused", s)),
+        }
+    }
 }
\ No newline at end of file
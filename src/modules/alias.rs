@@ -1,7 +1,96 @@
 use anyhow::{Context, Result};
-use dialoguer::MultiSelect;
-use crate::models::AliasGroup;
+use colored::Colorize;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::PathBuf;
+use crate::models::{Alias, AliasGroup};
+use crate::modules::alias_usage;
 use crate::modules::config::ConfigManager;
+use crate::modules::prompt;
+
+/// Why an alias may not end up meaning what its definition says.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AliasConflict {
+    /// Another alias group defines the same name differently; whichever
+    /// group is written to `~/.zsh_aliases` last wins.
+    Group { group: String, alias: Alias, active: bool },
+    /// The alias hides a command of the same name on PATH.
+    Command(PathBuf),
+}
+
+impl fmt::Display for AliasConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AliasConflict::Group { group, alias, active } => write!(
+                f,
+                "group '{}' defines it as `{}`{}",
+                group,
+                alias.command,
+                if *active { " (active)" } else { "" }
+            ),
+            AliasConflict::Command(path) => write!(f, "it shadows {}", path.display()),
+        }
+    }
+}
+
+/// Conflicts `alias` has as a member of `group`: other groups' aliases of
+/// the same name with a different command, and a command on PATH it hides.
+/// Wrapping a command in itself (`ls='ls --color'`) isn't shadowing.
+pub fn conflicts(aliases: &HashMap<String, AliasGroup>, group: &str, alias: &Alias) -> Vec<AliasConflict> {
+    conflicts_on(aliases, group, alias, std::env::var_os("PATH"))
+}
+
+/// `conflicts`, looking for shadowed commands on `path` instead of `$PATH`.
+pub fn conflicts_on(
+    aliases: &HashMap<String, AliasGroup>,
+    group: &str,
+    alias: &Alias,
+    path: Option<OsString>,
+) -> Vec<AliasConflict> {
+    let mut others: Vec<_> = aliases.iter().filter(|(name, _)| name.as_str() != group).collect();
+    others.sort_by(|a, b| a.0.cmp(b.0));
+    
+    let mut found: Vec<AliasConflict> = others
+        .into_iter()
+        .flat_map(|(name, other)| {
+            other
+                .items
+                .iter()
+                .filter(|item| item.name == alias.name && item.command != alias.command)
+                .map(|item| AliasConflict::Group {
+                    group: name.clone(),
+                    alias: item.clone(),
+                    active: other.active.contains(item),
+                })
+        })
+        .collect();
+    
+    let wraps_itself = alias.command.split_whitespace().next() == Some(alias.name.as_str());
+    if !wraps_itself {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        if let Ok(command) = which::which_in(&alias.name, path, cwd) {
+            found.push(AliasConflict::Command(command));
+        }
+    }
+    found
+}
+
+/// Warns about `conflicts` and asks whether to go ahead; `force` skips the
+/// question.
+fn confirm_despite(alias: &Alias, conflicts: &[AliasConflict], question: &str, force: bool) -> Result<bool> {
+    if conflicts.is_empty() {
+        return Ok(true);
+    }
+    println!("{} Alias '{}' conflicts:", "⚠️ ".yellow(), alias.name);
+    for conflict in conflicts {
+        println!("   - {}", conflict);
+    }
+    if force {
+        return Ok(true);
+    }
+    prompt::confirm(question, false)
+}
 
 pub struct AliasManager {
     config_mgr: ConfigManager,
@@ -23,7 +112,16 @@ impl AliasManager {
                 println!("\n   All aliases:");
                 for alias in &alias_group.items {
                     let status = if alias_group.active.contains(alias) { "✅" } else { "⭕" };
-                    println!("   {} {}", status, alias);
+                    println!("   {} {} → {}", status, alias.name, alias.command);
+                    if let Some(description) = &alias.description {
+                        println!("      {}", description);
+                    }
+                    if !alias.shells.is_empty() {
+                        println!("      shells: {}", alias.shells.join(", "));
+                    }
+                    if alias.abbr == Some(false) {
+                        println!("      fish: alias, not abbreviation");
+                    }
                 }
             } else {
                 println!("No aliases found for group '{}'", group_name);
@@ -42,7 +140,15 @@ impl AliasManager {
         Ok(())
     }
     
-    pub fn add(&mut self, group: &str, alias_def: &str) -> Result<()> {
+    /// Adds `alias` to `group`; an alias of the same name is replaced.
+    /// Conflicts are confirmed first unless `force` is set.
+    pub fn add(&mut self, group: &str, alias: Alias, force: bool) -> Result<()> {
+        let found = conflicts(&self.config_mgr.config.aliases, group, &alias);
+        if !confirm_despite(&alias, &found, "Add it anyway?", force)? {
+            println!("ℹ️  Alias '{}' not added (use --force to override)", alias.name);
+            return Ok(());
+        }
+        
         let alias_group = self.config_mgr.config.aliases
             .entry(group.to_string())
             .or_insert_with(|| AliasGroup {
@@ -50,24 +156,39 @@ impl AliasManager {
                 active: Vec::new(),
             });
         
-        if !alias_group.items.contains(&alias_def.to_string()) {
-            alias_group.items.push(alias_def.to_string());
-            println!("✅ Added alias to group '{}': {}", group, alias_def);
-            
-            self.config_mgr.save()?;
-        } else {
+        if alias_group.items.contains(&alias) {
             println!("ℹ️  Alias already exists in group '{}'", group);
+            return Ok(());
+        }
+        
+        match alias_group.items.iter().position(|a| a.name == alias.name) {
+            Some(idx) => {
+                let old = std::mem::replace(&mut alias_group.items[idx], alias.clone());
+                for active in alias_group.active.iter_mut().filter(|a| **a == old) {
+                    *active = alias.clone();
+                }
+                println!("✅ Updated alias in group '{}': {}", group, alias);
+            }
+            None => {
+                alias_group.items.push(alias.clone());
+                println!("✅ Added alias to group '{}': {}", group, alias);
+            }
         }
         
+        self.config_mgr.save()?;
+        
         Ok(())
     }
     
-    pub fn remove(&mut self, group: &str, alias_def: &str) -> Result<()> {
+    /// Removes the alias named `name_or_def`, or matching it as a definition.
+    pub fn remove(&mut self, group: &str, name_or_def: &str) -> Result<()> {
         if let Some(alias_group) = self.config_mgr.config.aliases.get_mut(group) {
-            alias_group.items.retain(|a| a != alias_def);
-            alias_group.active.retain(|a| a != alias_def);
+            let parsed = Alias::parse(name_or_def);
+            let matches = |a: &Alias| a.name == name_or_def || parsed.as_ref() == Some(a);
+            alias_group.items.retain(|a| !matches(a));
+            alias_group.active.retain(|a| !matches(a));
             
-            println!("✅ Removed alias from group '{}': {}", group, alias_def);
+            println!("✅ Removed alias from group '{}': {}", group, name_or_def);
             
             self.config_mgr.save()?;
         } else {
@@ -77,7 +198,9 @@ impl AliasManager {
         Ok(())
     }
     
-    pub fn toggle(&mut self, group: &str) -> Result<()> {
+    /// Newly activated aliases with conflicts are confirmed one by one
+    /// unless `force` is set; declined ones stay inactive.
+    pub fn toggle(&mut self, group: &str, force: bool) -> Result<()> {
         let alias_group = self.config_mgr.config.aliases
             .get(group)
             .context(format!("Group '{}' not found", group))?
@@ -93,15 +216,22 @@ impl AliasManager {
             .map(|item| alias_group.active.contains(item))
             .collect();
         
-        let selected = MultiSelect::new()
-            .with_prompt(format!("Toggle active aliases for group '{}'", group))
-            .items(&alias_group.items)
-            .defaults(&defaults)
-            .interact()?;
+        let selected = prompt::multi_select(
+            &format!("Toggle active aliases for group '{}'", group),
+            &alias_group.items,
+            &defaults,
+        )?;
         
         let mut active = Vec::new();
         for idx in selected {
-            active.push(alias_group.items[idx].clone());
+            let alias = &alias_group.items[idx];
+            if !alias_group.active.contains(alias) {
+                let found = conflicts(&self.config_mgr.config.aliases, group, alias);
+                if !confirm_despite(alias, &found, &format!("Activate '{}' anyway?", alias.name), force)? {
+                    continue;
+                }
+            }
+            active.push(alias.clone());
         }
         
         self.config_mgr.config.aliases.insert(
@@ -119,4 +249,36 @@ impl AliasManager {
         
         Ok(())
     }
+    
+    /// Usage of the active aliases as logged by the `alias hook` snippet.
+    pub fn stats(&self, group: Option<&str>) -> Result<()> {
+        let log = alias_usage::log_path(&self.config_mgr.data_dir());
+        let usage = alias_usage::read_usage(&log)?;
+        let stats = alias_usage::stats(&self.config_mgr.config, &usage, group);
+        
+        if usage.is_empty() {
+            println!("ℹ️  No alias usage logged yet. To start, add this to ~/.zshrc:");
+            println!("   eval \"$(zshrcman alias hook)\"");
+            return Ok(());
+        }
+        if stats.is_empty() {
+            println!("No active aliases{}", group.map(|g| format!(" in group '{}'", g)).unwrap_or_default());
+            return Ok(());
+        }
+        
+        let (used, unused): (Vec<_>, Vec<_>) = stats.iter().partition(|stat| stat.usage.count > 0);
+        println!("📊 Alias usage:");
+        for stat in &used {
+            let last_used = stat.usage.last_used.map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string()).unwrap_or_default();
+            println!("   {:<16} {:>6}×  last {}  ({})", stat.name, stat.usage.count, last_used, stat.group);
+        }
+        if !unused.is_empty() {
+            println!("\n{} Never used since logging started:", "🪦".normal());
+            for stat in &unused {
+                println!("   {:<16} ({})", stat.name, stat.group);
+            }
+        }
+        
+        Ok(())
+    }
 }
\ No newline at end of file
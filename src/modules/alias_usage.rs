@@ -0,0 +1,96 @@
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::Config;
+
+/// Alias invocations logged by the zsh hook, one `<unix time> <alias>` line
+/// each, in the data dir.
+pub const USAGE_FILE: &str = "alias_usage.log";
+
+pub fn log_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(USAGE_FILE)
+}
+
+/// zsh snippet that appends a line to `log` whenever a command line starts
+/// with an alias. `eval` it from `.zshrc` to opt in.
+pub fn hook_snippet(log: &Path) -> String {
+    format!(
+        r#"# zshrcman alias usage logging
+zmodload zsh/datetime
+_zshrcman_log_alias() {{
+    local word=${{${{(z)1}}[1]}}
+    (( ${{+aliases[$word]}} )) && print -r -- "$EPOCHSECONDS $word" >> {}
+}}
+autoload -Uz add-zsh-hook
+add-zsh-hook preexec _zshrcman_log_alias
+"#,
+        shell_quote(&log.display().to_string())
+    )
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Usage {
+    pub count: usize,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// Invocations per alias name. Malformed lines are skipped; no log means no
+/// usage yet.
+pub fn read_usage(log: &Path) -> Result<HashMap<String, Usage>> {
+    let mut usage: HashMap<String, Usage> = HashMap::new();
+    if !log.exists() {
+        return Ok(usage);
+    }
+    
+    for line in fs::read_to_string(log)?.lines() {
+        let Some((time, name)) = line.trim().split_once(' ') else {
+            continue;
+        };
+        let Some(time) = time.parse::<i64>().ok().and_then(|secs| Utc.timestamp_opt(secs, 0).single()) else {
+            continue;
+        };
+        let entry = usage.entry(name.to_string()).or_default();
+        entry.count += 1;
+        entry.last_used = entry.last_used.max(Some(time));
+    }
+    Ok(usage)
+}
+
+/// How often an active alias of an alias group was used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AliasStat {
+    pub group: String,
+    pub name: String,
+    pub usage: Usage,
+}
+
+/// Every active alias of the alias groups (optionally only `group`), most
+/// used first; unused ones end up last, sorted by group and name.
+pub fn stats(config: &Config, usage: &HashMap<String, Usage>, group: Option<&str>) -> Vec<AliasStat> {
+    let mut stats: Vec<AliasStat> = config
+        .aliases
+        .iter()
+        .filter(|(name, _)| group.is_none_or(|group| group == name.as_str()))
+        .flat_map(|(group, aliases)| {
+            aliases.active.iter().map(|alias| AliasStat {
+                group: group.clone(),
+                name: alias.name.clone(),
+                usage: usage.get(&alias.name).cloned().unwrap_or_default(),
+            })
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        b.usage
+            .count
+            .cmp(&a.usage.count)
+            .then_with(|| a.group.cmp(&b.group))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    stats
+}
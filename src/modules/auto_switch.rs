@@ -0,0 +1,63 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::Config;
+use crate::modules::environment::ShellType;
+use crate::modules::shell_env::APPLIED_VAR;
+
+/// File naming the profile for a directory and everything below it.
+pub const PROFILE_FILE: &str = ".zshrcman-profile";
+
+/// The nearest `.zshrcman-profile` at or above `dir`, and the profile it
+/// names (its first non-empty line). Empty files are skipped.
+pub fn find(dir: &Path) -> Option<(PathBuf, String)> {
+    dir.ancestors().find_map(|dir| {
+        let file = dir.join(PROFILE_FILE);
+        let contents = fs::read_to_string(&file).ok()?;
+        let profile = contents.lines().map(str::trim).find(|line| !line.is_empty())?;
+        Some((file, profile.to_string()))
+    })
+}
+
+/// The profile `dir` asks for, if it names one that exists. Unknown names
+/// are reported on stderr (stdout is being evaluated) and ignored.
+pub fn profile_for(config: &Config, dir: &Path) -> Option<String> {
+    let (file, profile) = find(dir)?;
+    if config.profiles.contains_key(&profile) {
+        return Some(profile);
+    }
+    eprintln!("⚠️  {} names profile '{}', which doesn't exist", file.display(), profile);
+    None
+}
+
+/// Writes `profile` to `dir`'s `.zshrcman-profile`.
+pub fn set(dir: &Path, profile: &str) -> Result<PathBuf> {
+    let file = dir.join(PROFILE_FILE);
+    fs::write(&file, format!("{}\n", profile))?;
+    Ok(file)
+}
+
+/// Snippet for the shell's rc file that re-evaluates `zshrcman env` on every
+/// directory change, and once at startup, so a `.zshrcman-profile` takes
+/// effect on entering its directory and is undone on leaving it.
+pub fn hook_snippet(shell: &ShellType) -> String {
+    let name = shell.name();
+    match shell {
+        ShellType::Fish => format!(
+            "function _zshrcman_auto_switch --on-variable PWD\n    command zshrcman --shell fish env --applied \"${0}\" | source\nend\n_zshrcman_auto_switch\n",
+            APPLIED_VAR
+        ),
+        ShellType::PowerShell => format!(
+            "$global:_zshrcman_auto_prompt = $function:prompt\nfunction global:prompt {{\n    if ($global:_zshrcman_pwd -ne $PWD.Path) {{\n        $global:_zshrcman_pwd = $PWD.Path\n        (& zshrcman --shell pwsh env --applied \"$global:{0}\") -join \"`n\" | Invoke-Expression\n    }}\n    & $global:_zshrcman_auto_prompt\n}}\n",
+            APPLIED_VAR
+        ),
+        ShellType::Zsh => format!(
+            "_zshrcman_auto_switch() {{\n    eval \"$(command zshrcman --shell {0} env --applied \"${{{1}-}}\")\"\n}}\nif (( ! ${{chpwd_functions[(I)_zshrcman_auto_switch]}} )); then\n    chpwd_functions+=(_zshrcman_auto_switch)\nfi\n_zshrcman_auto_switch\n",
+            name, APPLIED_VAR
+        ),
+        _ => format!(
+            "_zshrcman_auto_switch() {{\n    [[ \"$PWD\" == \"${{_zshrcman_pwd-}}\" ]] && return\n    _zshrcman_pwd=\"$PWD\"\n    eval \"$(command zshrcman --shell {0} env --applied \"${{{1}-}}\")\"\n}}\nif [[ \";${{PROMPT_COMMAND:-}};\" != *\";_zshrcman_auto_switch;\"* ]]; then\n    PROMPT_COMMAND=\"_zshrcman_auto_switch${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}\"\nfi\n",
+            name, APPLIED_VAR
+        ),
+    }
+}
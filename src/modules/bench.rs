@@ -0,0 +1,406 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+use crate::models::InstallerType;
+use crate::modules::config::ConfigManager;
+use crate::modules::facts::Facts;
+use crate::modules::zcompile;
+use crate::modules::zsh_plugins;
+
+/// Startup time samples, in milliseconds.
+#[derive(Debug, Clone, Default)]
+pub struct Timing {
+    samples: Vec<f64>,
+}
+
+impl Timing {
+    pub fn new(mut samples: Vec<f64>) -> Self {
+        samples.sort_by(|a, b| a.total_cmp(b));
+        Self { samples }
+    }
+    
+    pub fn runs(&self) -> usize {
+        self.samples.len()
+    }
+    
+    pub fn mean(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+    
+    /// Nearest-rank percentile, e.g. `percentile(90.0)`.
+    pub fn percentile(&self, percent: f64) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let rank = (percent / 100.0 * self.samples.len() as f64).ceil() as usize;
+        self.samples[rank.clamp(1, self.samples.len()) - 1]
+    }
+}
+
+/// A piece of the zsh startup that can be sourced and timed on its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    pub name: String,
+    /// The group it comes from; `None` for managed blocks, which several
+    /// groups feed.
+    pub group: Option<String>,
+    pub path: PathBuf,
+}
+
+/// Startup time of one shell without and with zshrcman's additions.
+#[derive(Debug, Clone)]
+pub struct ShellTiming {
+    pub shell: &'static str,
+    pub before: Timing,
+    pub after: Timing,
+}
+
+/// What `zshrcman bench` measured.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub shells: Vec<ShellTiming>,
+    pub fragments: Vec<(Fragment, Timing)>,
+}
+
+impl Report {
+    /// Mean time each group's fragments add, slowest first.
+    pub fn group_totals(&self) -> Vec<(String, f64)> {
+        let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+        for (fragment, timing) in &self.fragments {
+            if let Some(group) = &fragment.group {
+                *totals.entry(group.clone()).or_default() += timing.mean();
+            }
+        }
+        let mut totals: Vec<(String, f64)> = totals.into_iter().collect();
+        totals.sort_by(|a, b| b.1.total_cmp(&a.1));
+        totals
+    }
+}
+
+const BEGIN: &str = "# >>> zshrcman ";
+const END: &str = "# <<< zshrcman ";
+const SCRIPTS_HEADER: &str = "# zshrcman managed scripts";
+
+/// The managed blocks of an rc file as `(label, content)`, in file order.
+pub fn managed_blocks(content: &str) -> Vec<(String, String)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(String, String)> = None;
+    for line in content.lines() {
+        if let Some(label) = line.strip_prefix(BEGIN).and_then(|rest| rest.strip_suffix(" >>>")) {
+            current = Some((label.to_string(), String::new()));
+        } else if line.starts_with(END) {
+            blocks.extend(current.take());
+        } else if let Some((_, body)) = current.as_mut() {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    blocks
+}
+
+/// `content` without anything zshrcman added: its managed blocks and the
+/// `source` lines the zshrc group appends.
+pub fn strip_managed(content: &str) -> String {
+    let mut kept = Vec::new();
+    let mut in_block = false;
+    let mut in_scripts = false;
+    for line in content.lines() {
+        if line.starts_with(BEGIN) {
+            in_block = true;
+        } else if line.starts_with(END) {
+            in_block = false;
+        } else if line == SCRIPTS_HEADER {
+            in_scripts = true;
+        } else if in_scripts && line.starts_with("source ") {
+            continue;
+        } else if !in_block {
+            in_scripts = false;
+            kept.push(line);
+        }
+    }
+    let mut stripped = kept.join("\n");
+    if !stripped.is_empty() {
+        stripped.push('\n');
+    }
+    stripped
+}
+
+/// The zsh fragments zshrcman adds: its managed blocks in `.zshenv` and
+/// `.zshrc` (or the compiled bundle), the zshrc group's scripts and each
+/// plugin. The plugins block itself is left out since its plugins are timed
+/// one by one. Block
+/// contents are written below `scratch` so they can be sourced.
+pub fn fragments(config_mgr: &ConfigManager, home: &Path, scratch: &Path) -> Result<Vec<Fragment>> {
+    let mut fragments = Vec::new();
+    
+    for rc in [".zshenv", ".zshrc", zcompile::BUNDLE_FILE] {
+        let content = fs::read_to_string(home.join(rc)).unwrap_or_default();
+        for (label, body) in managed_blocks(&content) {
+            if label == "plugins" || label == "bundle" {
+                continue;
+            }
+            let file = rc.rsplit('/').next().unwrap_or(rc).trim_start_matches('.');
+            let path = scratch.join("blocks").join(format!("{}-{}.zsh", file, label.replace('/', "_")));
+            fs::create_dir_all(scratch.join("blocks"))?;
+            fs::write(&path, body)?;
+            fragments.push(Fragment {
+                name: format!("{} block ({})", label, rc),
+                group: None,
+                path,
+            });
+        }
+    }
+    
+    let facts = Facts::gather();
+    let data_dir = config_mgr.data_dir();
+    for group in config_mgr.get_ordered_groups() {
+        let (root, name) = config_mgr.resolve_group(&group);
+        if !matches!(InstallerType::from_group_name(name), InstallerType::Zshrc) {
+            continue;
+        }
+        let Ok((config, _)) = config_mgr.load_merged_group(&group) else {
+            continue;
+        };
+        if config.when.is_some_and(|when| when.check(&facts).is_err()) {
+            continue;
+        }
+        for script in &config.scripts {
+            let path = match script.strip_suffix(".tera") {
+                Some(rendered) => data_dir.join("rendered").join(rendered),
+                None => root.join("scripts").join(script),
+            };
+            if path.is_file() {
+                fragments.push(Fragment {
+                    name: format!("script {}", script),
+                    group: Some(group.clone()),
+                    path,
+                });
+            }
+        }
+    }
+    
+    for (group, plugin) in zsh_plugins::declared(config_mgr) {
+        if let Some(path) = zsh_plugins::entry_file(&data_dir, &plugin).filter(|path| path.is_file()) {
+            fragments.push(Fragment {
+                name: format!("plugin {}", plugin.name),
+                group: Some(group),
+                path,
+            });
+        }
+    }
+    
+    Ok(fragments)
+}
+
+fn quote(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', "'\\''"))
+}
+
+/// A zsh script sourcing each fragment in turn and appending
+/// `<index> <milliseconds>` lines to `results`.
+pub fn timing_script(fragments: &[Fragment], results: &Path) -> String {
+    let mut script = String::from("zmodload zsh/datetime\ntypeset -F __zshrcman_start\n");
+    for (index, fragment) in fragments.iter().enumerate() {
+        script.push_str("__zshrcman_start=$EPOCHREALTIME\n");
+        script.push_str(&format!("source {} </dev/null >/dev/null 2>&1\n", quote(&fragment.path)));
+        script.push_str(&format!(
+            "print -r -- \"{} $(( (EPOCHREALTIME - __zshrcman_start) * 1000 ))\" >> {}\n",
+            index,
+            quote(results)
+        ));
+    }
+    script
+}
+
+/// Groups the lines written by [`timing_script`] by fragment index.
+pub fn parse_timings(output: &str, count: usize) -> Vec<Timing> {
+    let mut samples = vec![Vec::new(); count];
+    for line in output.lines() {
+        let Some((index, millis)) = line.split_once(' ') else {
+            continue;
+        };
+        if let (Ok(index), Ok(millis)) = (index.parse::<usize>(), millis.trim().parse::<f64>()) {
+            if let Some(samples) = samples.get_mut(index) {
+                samples.push(millis);
+            }
+        }
+    }
+    samples.into_iter().map(Timing::new).collect()
+}
+
+fn time_runs(runs: usize, mut command: impl FnMut() -> Command) -> Result<Timing> {
+    let mut samples = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let start = Instant::now();
+        command()
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("Failed to start shell")?;
+        samples.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+    Ok(Timing::new(samples))
+}
+
+/// Copies the rc `files` below `scratch` without zshrcman's
+/// additions, for the "before" runs.
+fn write_stripped(home: &Path, scratch: &Path, files: &[&str]) -> Result<()> {
+    fs::create_dir_all(scratch)?;
+    for file in files {
+        let content = fs::read_to_string(home.join(file)).unwrap_or_default();
+        let name = Path::new(file).file_name().context("rc file without a name")?;
+        fs::write(scratch.join(name), strip_managed(&content))?;
+    }
+    Ok(())
+}
+
+/// Times `runs` interactive startups of each installed shell, with its rc
+/// files as they are and with zshrcman's additions stripped.
+fn time_shell(shell: &'static str, home: &Path, scratch: &Path, runs: usize) -> Result<ShellTiming> {
+    let stripped = scratch.join(shell);
+    let (before, after) = match shell {
+        "zsh" => {
+            write_stripped(home, &stripped, &[".zshenv", ".zshrc"])?;
+            let before = time_runs(runs, || {
+                let mut command = Command::new("zsh");
+                command.args(["-i", "-c", "exit"]).env("ZDOTDIR", &stripped);
+                command
+            })?;
+            let after = time_runs(runs, || {
+                let mut command = Command::new("zsh");
+                command.args(["-i", "-c", "exit"]).env_remove("ZDOTDIR");
+                command
+            })?;
+            (before, after)
+        }
+        "bash" => {
+            write_stripped(home, &stripped, &[".bashrc"])?;
+            let rcfile = stripped.join(".bashrc");
+            let before = time_runs(runs, || {
+                let mut command = Command::new("bash");
+                command.arg("--rcfile").arg(&rcfile).args(["-i", "-c", "exit"]);
+                command
+            })?;
+            let after = time_runs(runs, || {
+                let mut command = Command::new("bash");
+                command.args(["-i", "-c", "exit"]);
+                command
+            })?;
+            (before, after)
+        }
+        _ => {
+            write_stripped(home, &stripped, &[".config/fish/config.fish"])?;
+            let init = format!("source {}", quote(&stripped.join("config.fish")));
+            let before = time_runs(runs, || {
+                let mut command = Command::new("fish");
+                command.args(["--no-config", "-i", "-C", &init, "-c", "exit"]);
+                command
+            })?;
+            let after = time_runs(runs, || {
+                let mut command = Command::new("fish");
+                command.args(["-i", "-c", "exit"]);
+                command
+            })?;
+            (before, after)
+        }
+    };
+    Ok(ShellTiming { shell, before, after })
+}
+
+/// Times shell startup for zsh, bash and fish (those that are installed)
+/// and each zsh fragment zshrcman adds, `runs` times each.
+/// Scratch files go to `bench/` in the cache dir and are removed afterwards.
+pub fn run(config_mgr: &ConfigManager, home: &Path, runs: usize) -> Result<Report> {
+    let scratch = config_mgr.cache_dir().join("bench");
+    let _ = fs::remove_dir_all(&scratch);
+    fs::create_dir_all(&scratch)?;
+    let report = measure(config_mgr, home, &scratch, runs);
+    let _ = fs::remove_dir_all(&scratch);
+    report
+}
+
+fn measure(config_mgr: &ConfigManager, home: &Path, scratch: &Path, runs: usize) -> Result<Report> {
+    let mut report = Report::default();
+    
+    for shell in ["zsh", "bash", "fish"] {
+        if which::which(shell).is_ok() {
+            report.shells.push(time_shell(shell, home, scratch, runs)?);
+        }
+    }
+    if which::which("zsh").is_err() {
+        return Ok(report);
+    }
+    
+    let fragments = fragments(config_mgr, home, scratch)?;
+    if fragments.is_empty() {
+        return Ok(report);
+    }
+    let results = scratch.join("results");
+    let script = timing_script(&fragments, &results);
+    for _ in 0..runs {
+        Command::new("zsh")
+            .args(["-f", "-i", "-c", &script])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("Failed to start zsh")?;
+    }
+    let output = fs::read_to_string(&results).unwrap_or_default();
+    let timings = parse_timings(&output, fragments.len());
+    report.fragments = fragments.into_iter().zip(timings).collect();
+    Ok(report)
+}
+
+/// Runs the benchmark and prints it, flagging fragments and groups that
+/// take `slow` milliseconds or more.
+pub fn show(config_mgr: &ConfigManager, home: &Path, runs: usize, slow: f64) -> Result<()> {
+    let report = run(config_mgr, home, runs)?;
+    if report.shells.is_empty() {
+        println!("No zsh, bash or fish found on PATH");
+        return Ok(());
+    }
+    
+    println!("{} ({} runs each)", "⏱️  Shell startup".bold(), runs);
+    let summary = |timing: &Timing| format!("{:>7.1} ms (p50 {:.1}, p90 {:.1})", timing.mean(), timing.percentile(50.0), timing.percentile(90.0));
+    for shell in &report.shells {
+        let added = shell.after.mean() - shell.before.mean();
+        println!("  {:<5} without zshrcman {}", shell.shell, summary(&shell.before));
+        println!("  {:<5} with zshrcman    {}  {:+.1} ms", "", summary(&shell.after), added);
+    }
+    
+    if report.fragments.is_empty() {
+        return Ok(());
+    }
+    println!("\n{}", "Zsh fragments".bold());
+    for (fragment, timing) in &report.fragments {
+        let group = fragment.group.as_deref().map(|group| format!(" [{}]", group)).unwrap_or_default();
+        let line = format!("  {:<40} {}", format!("{}{}", fragment.name, group), summary(timing));
+        if timing.mean() >= slow {
+            println!("{}  {}", line, "🐢 slow".yellow());
+        } else {
+            println!("{}", line);
+        }
+    }
+    
+    let totals = report.group_totals();
+    if !totals.is_empty() {
+        println!("\n{}", "Groups".bold());
+        for (group, millis) in &totals {
+            if *millis >= slow {
+                println!("  {:<40} {:>7.1} ms  {}", group, millis, "🐢 slow".yellow());
+            } else {
+                println!("  {:<40} {:>7.1} ms", group, millis);
+            }
+        }
+    }
+    Ok(())
+}
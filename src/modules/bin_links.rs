@@ -0,0 +1,220 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use crate::models::{InstallScope, InstallationRecord};
+use crate::modules::installer;
+
+/// A link in a profile's `bin`: `name` pointing at `target`, an executable
+/// of `package`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub name: String,
+    pub target: PathBuf,
+    pub package: String,
+}
+
+/// Two packages providing an executable of the same name. The link goes to
+/// `kept`'s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    pub name: String,
+    pub kept: String,
+    pub skipped: String,
+}
+
+/// What a profile's `bin` should hold.
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub links: Vec<Link>,
+    pub collisions: Vec<Collision>,
+    /// Executables recorded for a package that no longer exist; they need a
+    /// reinstall.
+    pub missing: Vec<Link>,
+}
+
+/// The executables `record` provides: its `binaries`, or else its
+/// `location` under the package's executable name.
+pub fn executables(record: &InstallationRecord) -> Vec<Link> {
+    let link = |name: String, target: &Path| Link {
+        name,
+        target: target.to_path_buf(),
+        package: record.package.clone(),
+    };
+    if record.binaries.is_empty() {
+        return record
+            .location
+            .iter()
+            .map(|location| link(installer::executable_name(&record.package).to_string(), location))
+            .collect();
+    }
+    record
+        .binaries
+        .iter()
+        .filter_map(|binary| Some(link(binary.file_name()?.to_string_lossy().into_owned(), binary)))
+        .collect()
+}
+
+/// The links for `records`. A profile's own install of a package wins over
+/// a shared one, then packages go by name; the first to claim a name keeps
+/// it.
+pub fn plan(records: &[&InstallationRecord]) -> Plan {
+    let mut records = records.to_vec();
+    records.sort_by(|a, b| (a.scope != InstallScope::Local, &a.package).cmp(&(b.scope != InstallScope::Local, &b.package)));
+    
+    let mut plan = Plan::default();
+    for link in records.into_iter().flat_map(executables) {
+        if !link.target.exists() {
+            plan.missing.push(link);
+        } else if let Some(kept) = plan.links.iter().find(|kept| kept.name == link.name) {
+            if kept.package != link.package {
+                plan.collisions.push(Collision {
+                    name: link.name,
+                    kept: kept.package.clone(),
+                    skipped: link.package,
+                });
+            }
+        } else {
+            plan.links.push(link);
+        }
+    }
+    plan
+}
+
+/// Links in `bin` whose target is gone.
+pub fn dangling(bin: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(bin) else {
+        return Vec::new();
+    };
+    let mut dangling: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| target(path).is_some_and(|target| !target.exists()))
+        .collect();
+    dangling.sort();
+    dangling
+}
+
+/// Replaces what's in `bin` with `plan`'s links. Returns the dangling links
+/// that were dropped on the way.
+pub fn sync(bin: &Path, plan: &Plan) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(bin).with_context(|| format!("Failed to create {}", bin.display()))?;
+    let dangling = dangling(bin);
+    for entry in fs::read_dir(bin)? {
+        let path = entry?.path();
+        if path.is_symlink() || path.is_file() {
+            fs::remove_file(&path)?;
+        }
+    }
+    for link in &plan.links {
+        create(&bin.join(&link.name), &link.target)?;
+    }
+    Ok(dangling)
+}
+
+/// Points `link` at `target`, replacing whatever is there. Targets inside
+/// the profile directory are linked relatively so the data directory can
+/// move. On Windows, where symlinks need developer mode or an elevated
+/// shell, a `.cmd` shim stands in for the link.
+pub fn create(link: &Path, target: &Path) -> Result<()> {
+    let mut existing = vec![link.to_path_buf()];
+    // Earlier versions also wrote a `.ps1` shim.
+    existing.extend(["cmd", "ps1"].map(|extension| shim_path(link, extension)));
+    for existing in existing {
+        if fs::symlink_metadata(&existing).is_ok() {
+            fs::remove_file(&existing)?;
+        }
+    }
+    if let Some(parent) = link.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    make_link(&relative(link, target), link, target)
+        .with_context(|| format!("Failed to link {} to {}", link.display(), target.display()))
+}
+
+/// Where `link` leads: the symlink's target resolved against its directory,
+/// or the program a shim runs. None when it's neither.
+pub fn target(link: &Path) -> Option<PathBuf> {
+    if let Ok(target) = fs::read_link(link) {
+        return Some(match link.parent() {
+            Some(dir) if target.is_relative() => normalize(&dir.join(target)),
+            _ => target,
+        });
+    }
+    let shim = if link.extension().is_some_and(|ext| ext == "cmd") { link.to_path_buf() } else { shim_path(link, "cmd") };
+    let script = fs::read_to_string(shim).ok()?;
+    let quoted = script.lines().find_map(|line| line.strip_prefix("@\""))?;
+    quoted.split_once('"').map(|(target, _)| PathBuf::from(target))
+}
+
+/// Whether `link` leads to `expected`.
+pub fn points_at(link: &Path, expected: &Path) -> bool {
+    let Some(target) = target(link) else {
+        return false;
+    };
+    target == expected || fs::canonicalize(&target).is_ok_and(|target| fs::canonicalize(expected).is_ok_and(|expected| target == expected))
+}
+
+/// `target` relative to `link`'s directory when both are in the same
+/// profile directory, e.g. `../prefix/npm/bin/prettier`.
+fn relative(link: &Path, target: &Path) -> PathBuf {
+    let Some(profile_dir) = link.parent().and_then(Path::parent) else {
+        return target.to_path_buf();
+    };
+    match target.strip_prefix(profile_dir) {
+        Ok(rest) => Path::new("..").join(rest),
+        Err(_) => target.to_path_buf(),
+    }
+}
+
+/// `path` with `..` components resolved without touching the filesystem,
+/// so a dangling relative link still reads as where it meant to point.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Shim scripts by extension, each running `{}` with the arguments it was
+/// given. cmd.exe and PowerShell both find `rg` through `rg.cmd`; a `.ps1`
+/// shim would need the target quoted for PowerShell and scripts allowed by
+/// its execution policy, for nothing `.cmd` doesn't already do.
+const SHIMS: [(&str, &str); 1] = [("cmd", "@echo off\r\n@\"{}\" %*\r\n")];
+
+fn shim_path(link: &Path, extension: &str) -> PathBuf {
+    let mut name = link.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    link.with_file_name(name)
+}
+
+/// The shims standing in for a link named `link` to `target`, with their
+/// contents.
+pub fn shims(link: &Path, target: &Path) -> Vec<(PathBuf, String)> {
+    let target = target.display().to_string();
+    SHIMS
+        .iter()
+        .map(|(extension, script)| (shim_path(link, extension), script.replace("{}", &target)))
+        .collect()
+}
+
+#[cfg(unix)]
+fn make_link(source: &Path, link: &Path, _target: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(source, link)?;
+    Ok(())
+}
+
+#[cfg(windows)]
+fn make_link(_source: &Path, link: &Path, target: &Path) -> Result<()> {
+    for (path, script) in shims(link, target) {
+        fs::write(path, script)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,284 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::ConflictStrategy;
+use crate::modules::config::{self, ConfigManager};
+use crate::modules::errors::{self, ErrorKind};
+use crate::modules::fleet;
+use crate::modules::git_mgr::{GitManager, SyncMode};
+use crate::modules::hooks;
+use crate::modules::init::{InitManager, InitOptions};
+use crate::modules::install::InstallManager;
+use crate::modules::profile_switcher::ProfileSwitcher;
+use crate::modules::preflight;
+use crate::modules::prompt;
+use crate::modules::repos;
+use crate::modules::sources;
+use crate::modules::state_manager::InstallationStateManager;
+
+#[derive(Debug, Clone)]
+pub struct BootstrapOptions {
+    pub repo: String,
+    pub device: String,
+    pub profile: Option<String>,
+    pub groups: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Init,
+    Sync,
+    Profile,
+}
+
+/// Phases an unfinished bootstrap got through, kept in
+/// `<data_dir>/bootstrap.json` until it completes so a rerun can pick up
+/// where it stopped. Installs aren't tracked here; each group's install
+/// status already says whether it needs another try.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapProgress {
+    pub repo: String,
+    pub device: String,
+    pub started_at: DateTime<Utc>,
+    pub completed: Vec<Phase>,
+}
+
+impl BootstrapProgress {
+    pub fn new(options: &BootstrapOptions) -> Self {
+        Self {
+            repo: options.repo.clone(),
+            device: options.device.clone(),
+            started_at: Utc::now(),
+            completed: vec![],
+        }
+    }
+    
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("bootstrap.json")
+    }
+    
+    pub fn load(data_dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path(data_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)?;
+        Ok(Some(serde_json::from_str(&contents).with_context(|| format!("Corrupt {}", path.display()))?))
+    }
+    
+    pub fn save(&self, data_dir: &Path) -> Result<()> {
+        config::write_atomic(&Self::path(data_dir), serde_json::to_string_pretty(self)?.as_bytes())
+    }
+    
+    pub fn clear(data_dir: &Path) -> Result<()> {
+        let path = Self::path(data_dir);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+    
+    /// Whether this progress belongs to a bootstrap of the same repo and
+    /// device; progress of any other one is discarded.
+    pub fn matches(&self, options: &BootstrapOptions) -> bool {
+        self.repo == options.repo && self.device == options.device
+    }
+    
+    pub fn is_done(&self, phase: Phase) -> bool {
+        self.completed.contains(&phase)
+    }
+    
+    pub fn complete(&mut self, phase: Phase, data_dir: &Path) -> Result<()> {
+        if !self.is_done(phase) {
+            self.completed.push(phase);
+        }
+        self.save(data_dir)
+    }
+}
+
+/// One-shot, non-interactive setup for a brand-new machine: init, sync,
+/// optional profile switch, then install every enabled group.
+///
+/// Finished phases are recorded, so rerunning the same command after an
+/// interruption skips them once a quick check shows their result is still in
+/// place. Groups that installed before the interruption are skipped too.
+pub struct BootstrapManager;
+
+impl BootstrapManager {
+    pub fn run(options: BootstrapOptions) -> Result<()> {
+        prompt::set_non_interactive(true);
+        
+        // Fail before cloning anything if there's nowhere to install to.
+        preflight::home_dir()?;
+        preflight::shell()?;
+        
+        println!("{}", "🚀 Bootstrapping zshrcman".bold().cyan());
+        
+        let data_dir = ConfigManager::get_data_dir()?;
+        let (mut progress, resuming) = match BootstrapProgress::load(&data_dir)? {
+            Some(progress) if progress.matches(&options) => {
+                println!(
+                    "🔁 Resuming the bootstrap started {}",
+                    progress.started_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                );
+                (progress, true)
+            }
+            Some(progress) => {
+                println!(
+                    "{} Discarding an unfinished bootstrap of {} for device '{}'",
+                    "⚠️ ".yellow(),
+                    progress.repo,
+                    progress.device
+                );
+                (BootstrapProgress::new(&options), false)
+            }
+            None => (BootstrapProgress::new(&options), false),
+        };
+        progress.save(&data_dir)?;
+        
+        Self::phase(1, "Initialize");
+        if progress.is_done(Phase::Init) && Self::init_is_intact(&options) {
+            Self::already_done();
+        } else {
+            InitManager::run(InitOptions {
+                repo: Some(options.repo.clone()),
+                device: Some(options.device.clone()),
+                groups: options.groups.clone(),
+            })
+            .context("Bootstrap failed during init")?;
+            progress.complete(Phase::Init, &data_dir)?;
+        }
+        
+        Self::phase(2, "Sync");
+        if progress.is_done(Phase::Sync) && GitManager::open(&ConfigManager::get_dotfiles_path()?).is_ok() {
+            Self::already_done();
+        } else {
+            Self::sync()?;
+            progress.complete(Phase::Sync, &data_dir)?;
+        }
+        
+        Self::phase(3, "Profile");
+        match &options.profile {
+            Some(profile) if progress.is_done(Phase::Profile) && Self::profile_is_active(profile) => {
+                Self::already_done();
+            }
+            Some(profile) => {
+                Self::switch_profile(profile)?;
+                progress.complete(Phase::Profile, &data_dir)?;
+            }
+            None => println!("   No profile requested, skipping"),
+        }
+        
+        Self::phase(4, "Install");
+        let mut install_mgr = InstallManager::new(ConfigManager::new()?);
+        let (installed, total) = install_mgr.install_progress()?;
+        if resuming && installed > 0 {
+            println!("   {}/{} groups already installed", installed, total);
+        }
+        // A resumed run only installs what's missing or changed.
+        if let Err(e) = install_mgr.install(!resuming) {
+            let (installed, total) = install_mgr.install_progress()?;
+            println!(
+                "{} {} of {} groups didn't install; rerun the same bootstrap command to retry them",
+                "⚠️ ".yellow(),
+                total - installed,
+                total
+            );
+            return Err(e.context("Bootstrap failed during install"));
+        }
+        
+        let (installed, total) = install_mgr.install_progress()?;
+        if installed < total {
+            return Err(errors::fail(
+                ErrorKind::Partial,
+                format!(
+                    "{} of {} groups didn't install; rerun the same bootstrap command to retry them",
+                    total - installed,
+                    total
+                ),
+            ));
+        }
+        
+        BootstrapProgress::clear(&data_dir)?;
+        println!("{}", "✅ Bootstrap complete!".green());
+        Ok(())
+    }
+    
+    fn already_done() {
+        println!("   {} already done", "✓".green());
+    }
+    
+    /// Init's result is still there: the config names this repo and device
+    /// and the clone opens.
+    fn init_is_intact(options: &BootstrapOptions) -> bool {
+        let Ok(config_mgr) = ConfigManager::new() else {
+            return false;
+        };
+        config_mgr.config.repository.url.as_deref() == Some(options.repo.as_str())
+            && config_mgr.config.device.name == options.device
+            && GitManager::open(config_mgr.dotfiles_path()).is_ok()
+    }
+    
+    fn profile_is_active(profile: &str) -> bool {
+        ConfigManager::new().is_ok_and(|config_mgr| config_mgr.config.active_profile.as_deref() == Some(profile))
+    }
+    
+    fn phase(step: usize, name: &str) {
+        println!();
+        println!("{}", format!("[{}/4] {}", step, name).bold());
+    }
+    
+    fn sync() -> Result<()> {
+        let config_mgr = ConfigManager::new()?;
+        let dotfiles_path = ConfigManager::get_dotfiles_path()?;
+        let git_mgr = GitManager::init_or_clone(
+            &dotfiles_path,
+            config_mgr.config.repository.url.as_deref(),
+        )?
+        .with_mirrors(&config_mgr.config.repository.remotes)?
+        .with_identity(&config_mgr.config.repository.identity);
+        
+        if let Err(e) = fleet::publish(&git_mgr, &dotfiles_path, &config_mgr) {
+            println!("{} Couldn't update this device's status report: {:#}", "⚠️ ".yellow(), e);
+        }
+        
+        // A repository without a remote, or a brand-new one without commits,
+        // has nothing to sync yet. Any other failure stops the bootstrap, which
+        // a rerun resumes here.
+        if config_mgr.config.repository.url.is_none() {
+            println!("   No repository URL configured; nothing to sync");
+        } else if git_mgr.head_id().is_none() {
+            println!("   The repository has no commits yet; nothing to sync");
+        } else {
+            let result = git_mgr.sync(
+                &config_mgr.config.repository.main_branch,
+                &config_mgr.config.device.branch,
+                ConflictStrategy::Abort,
+                SyncMode::Both,
+            );
+            hooks::emit_sync(&config_mgr.config, "dotfiles", &config_mgr.config.device.branch, &result);
+            result.context("Bootstrap failed during sync; fix it and rerun `zshrcman bootstrap` to resume")?;
+            println!("   Repository synced");
+        }
+        repos::sync_all(&config_mgr, ConflictStrategy::Abort, SyncMode::Both);
+        sources::update_all(&config_mgr);
+        
+        Ok(())
+    }
+    
+    fn switch_profile(profile: &str) -> Result<()> {
+        let mut state_mgr = InstallationStateManager::new(ConfigManager::new()?);
+        
+        if !state_mgr.profiles.contains_key(profile) {
+            state_mgr.create_profile(profile, None)?;
+            println!("   Created profile '{}'", profile);
+        }
+        
+        let mut switcher = ProfileSwitcher::new(state_mgr);
+        switcher.switch_profile(profile)
+    }
+}
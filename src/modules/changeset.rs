@@ -0,0 +1,231 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::DefaultsValue;
+use crate::modules::cron;
+use crate::modules::macos_defaults;
+use crate::modules::services;
+
+/// What the last install of a group changed, so `group rollback` can undo
+/// exactly that and nothing else.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangeSet {
+    pub group: String,
+    pub applied_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    /// A file was created or overwritten. `backup` holds the previous
+    /// contents, or is `None` if the file didn't exist.
+    FileWritten { path: PathBuf, backup: Option<PathBuf> },
+    /// `text` was appended to a shared file such as `.zshrc`. Rollback removes
+    /// just that text, keeping anything other groups added.
+    TextAppended { path: PathBuf, text: String },
+    /// Packages that weren't installed before this run.
+    PackagesInstalled { installer: String, packages: Vec<String> },
+    /// A macOS `defaults` key was written. `previous` is its value before
+    /// zshrcman first changed it, or `None` if it wasn't set.
+    DefaultsWritten { domain: String, key: String, previous: Option<DefaultsValue> },
+    /// The systemd unit or launchd agent at `path` was enabled and started.
+    ServiceEnabled { path: PathBuf },
+    /// The group's block in the user's crontab was written. `previous` is
+    /// what the block held before, empty if there was none.
+    CrontabWritten {
+        group: String,
+        #[serde(default)]
+        previous: String,
+    },
+}
+
+impl ChangeSet {
+    pub fn new(group: &str) -> Self {
+        Self {
+            group: group.to_string(),
+            applied_at: Some(chrono::Utc::now()),
+            changes: vec![],
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    
+    /// `<data_dir>/changesets/<group>/`, holding `changeset.json` and backups.
+    pub fn dir(data_dir: &Path, group: &str) -> PathBuf {
+        data_dir.join("changesets").join(group)
+    }
+    
+    pub fn load(data_dir: &Path, group: &str) -> Result<Option<Self>> {
+        let path = Self::dir(data_dir, group).join("changeset.json");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)?;
+        Ok(Some(serde_json::from_str(&contents).with_context(|| format!("Corrupt {}", path.display()))?))
+    }
+    
+    /// Replaces the group's previous change set and its backups.
+    pub fn save(&self, data_dir: &Path, staging: &Path) -> Result<()> {
+        let dir = Self::dir(data_dir, &self.group);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(dir.parent().unwrap_or(data_dir))?;
+        fs::rename(staging, &dir).or_else(|_| copy_dir(staging, &dir))?;
+        
+        let mut saved = self.clone();
+        for change in &mut saved.changes {
+            if let Change::FileWritten { backup: Some(backup), .. } = change {
+                if let Ok(relative) = backup.strip_prefix(staging) {
+                    *backup = dir.join(relative);
+                }
+            }
+        }
+        fs::write(dir.join("changeset.json"), serde_json::to_string_pretty(&saved)?)?;
+        Ok(())
+    }
+    
+    pub fn discard(data_dir: &Path, group: &str) -> Result<()> {
+        let dir = Self::dir(data_dir, group);
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects changes during a group install. Backups go to a staging directory
+/// and only replace the previous change set once the run has finished.
+pub struct ChangeRecorder {
+    pub changes: ChangeSet,
+    staging: PathBuf,
+}
+
+impl ChangeRecorder {
+    pub fn new(data_dir: &Path, group: &str) -> Result<Self> {
+        let staging = data_dir
+            .join("changesets")
+            .join(format!(".{}-{}", group, std::process::id()));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        Ok(Self {
+            changes: ChangeSet::new(group),
+            staging,
+        })
+    }
+    
+    /// Call before writing `path`; keeps a copy of what it contained.
+    pub fn before_write(&mut self, path: &Path) -> Result<()> {
+        let backup = if path.is_file() {
+            fs::create_dir_all(&self.staging)?;
+            let backup = self.staging.join(format!("backup-{}", self.changes.changes.len()));
+            fs::copy(path, &backup)?;
+            Some(backup)
+        } else {
+            None
+        };
+        
+        self.changes.changes.push(Change::FileWritten {
+            path: path.to_path_buf(),
+            backup,
+        });
+        Ok(())
+    }
+    
+    pub fn appended(&mut self, path: &Path, text: &str) {
+        if !text.is_empty() {
+            self.changes.changes.push(Change::TextAppended {
+                path: path.to_path_buf(),
+                text: text.to_string(),
+            });
+        }
+    }
+    
+    pub fn defaults_written(&mut self, domain: &str, key: &str, previous: Option<DefaultsValue>) {
+        self.changes.changes.push(Change::DefaultsWritten {
+            domain: domain.to_string(),
+            key: key.to_string(),
+            previous,
+        });
+    }
+    
+    pub fn service_enabled(&mut self, path: &Path) {
+        self.changes.changes.push(Change::ServiceEnabled { path: path.to_path_buf() });
+    }
+    
+    pub fn crontab_written(&mut self, group: &str, previous: String) {
+        self.changes.changes.push(Change::CrontabWritten { group: group.to_string(), previous });
+    }
+    
+    pub fn installed(&mut self, installer: &str, packages: Vec<String>) {
+        if !packages.is_empty() {
+            self.changes.changes.push(Change::PackagesInstalled {
+                installer: installer.to_string(),
+                packages,
+            });
+        }
+    }
+    
+    /// Stores the change set as the group's latest, if anything changed.
+    pub fn finish(self, data_dir: &Path) -> Result<()> {
+        if self.changes.is_empty() {
+            if self.staging.exists() {
+                fs::remove_dir_all(&self.staging)?;
+            }
+            return Ok(());
+        }
+        
+        fs::create_dir_all(&self.staging)?;
+        self.changes.save(data_dir, &self.staging)
+    }
+}
+
+/// Undoes a file, `defaults`, service or crontab change. Package changes are handled by the
+/// caller, which owns the installers.
+pub fn revert_file_change(change: &Change) -> Result<()> {
+    match change {
+        Change::FileWritten { path, backup: Some(backup) } => {
+            fs::copy(backup, path).with_context(|| format!("Failed to restore {}", path.display()))?;
+        }
+        Change::FileWritten { path, backup: None } => {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Change::TextAppended { path, text } => {
+            let contents = fs::read_to_string(path).unwrap_or_default();
+            match contents.rfind(text.as_str()) {
+                Some(at) => {
+                    let mut updated = contents.clone();
+                    updated.replace_range(at..at + text.len(), "");
+                    fs::write(path, updated)?;
+                }
+                None => anyhow::bail!(
+                    "{} no longer contains the text added by this group; leaving it unchanged",
+                    path.display()
+                ),
+            }
+        }
+        Change::DefaultsWritten { domain, key, previous } => macos_defaults::restore(domain, key, previous.as_ref())?,
+        Change::ServiceEnabled { path } => services::disable(path)?,
+        Change::CrontabWritten { group, previous } => {
+            cron::set_block(group, previous)?;
+        }
+        Change::PackagesInstalled { .. } => {}
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        fs::copy(entry.path(), to.join(entry.file_name()))?;
+    }
+    fs::remove_dir_all(from)
+}
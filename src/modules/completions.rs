@@ -0,0 +1,91 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory below home holding the deployed completion files.
+pub const COMPLETIONS_DIR: &str = ".zsh/completions";
+
+/// Names of the files the last deploy wrote, so stale ones are removed and
+/// files added by hand are kept.
+const MANIFEST: &str = ".zshrcman";
+
+/// Deploys group completion files into `~/.zsh/completions`, which
+/// [`fpath_lines`] puts on zsh's `fpath`.
+pub struct CompletionManager {
+    home: PathBuf,
+    dir: PathBuf,
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\'])
+}
+
+impl CompletionManager {
+    pub fn new(home: &Path) -> Self {
+        Self {
+            home: home.to_path_buf(),
+            dir: home.join(COMPLETIONS_DIR),
+        }
+    }
+    
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    
+    /// Copies `files` (file name to source) into the directory and removes
+    /// the ones earlier deploys wrote that aren't wanted anymore. Files
+    /// zshrcman didn't write are left alone. Returns whether anything
+    /// changed.
+    pub fn deploy(&self, files: &BTreeMap<String, PathBuf>) -> Result<bool> {
+        let manifest = self.dir.join(MANIFEST);
+        let previous = fs::read_to_string(&manifest).unwrap_or_default();
+        let mut changed = false;
+        
+        for name in previous.lines() {
+            if is_valid_name(name) && !files.contains_key(name) && fs::remove_file(self.dir.join(name)).is_ok() {
+                changed = true;
+            }
+        }
+        
+        if files.is_empty() {
+            let _ = fs::remove_file(&manifest);
+            let _ = fs::remove_dir(&self.dir);
+            return Ok(changed);
+        }
+        
+        fs::create_dir_all(&self.dir)?;
+        for (name, source) in files {
+            if !is_valid_name(name) {
+                anyhow::bail!("Invalid completion file name '{}'", name);
+            }
+            let content = fs::read(source)?;
+            let target = self.dir.join(name);
+            if fs::read(&target).ok().as_deref() != Some(content.as_slice()) {
+                fs::write(&target, content)?;
+                changed = true;
+            }
+        }
+        fs::write(&manifest, files.keys().map(|name| format!("{}\n", name)).collect::<String>())?;
+        Ok(changed)
+    }
+    
+    /// Deletes zsh's completion dumps (`~/.zcompdump*`) so the next
+    /// `compinit` rebuilds them with the new files. Returns how many went.
+    pub fn reset_dump(&self) -> Result<usize> {
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.home)?.filter_map(|entry| entry.ok()) {
+            if entry.file_name().to_string_lossy().starts_with(".zcompdump") && entry.path().is_file() {
+                fs::remove_file(entry.path())?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// The `.zshenv` lines putting the completions directory on `fpath`. They
+/// go there rather than in `.zshrc` so they come before `compinit` runs.
+pub fn fpath_lines() -> String {
+    format!("# zshrcman completions\n\nfpath=(\"$HOME/{}\" $fpath)\n", COMPLETIONS_DIR)
+}
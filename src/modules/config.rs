@@ -1,43 +1,176 @@
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
-use std::fs;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, Write};
 use std::path::{Path, PathBuf};
-use crate::models::{Config, GroupConfig, InstallStatus};
+use std::sync::Mutex;
+use crate::models::{Config, GroupConfig, InstallScope, InstallStatus, InstallationRecord, InstallationSource, Profile};
+use crate::modules::output;
+use crate::modules::repos;
+use crate::modules::sources;
+use crate::modules::suggest;
+
+/// Prefix of environment variables that override config.toml keys.
+pub const ENV_PREFIX: &str = "ZSHRCMAN_";
+
+/// Path of config.toml itself; also set by `--config`. Wins over
+/// [`CONFIG_DIR_ENV`].
+pub const CONFIG_ENV: &str = "ZSHRCMAN_CONFIG";
+/// Directory holding config.toml, instead of the platform config dir.
+pub const CONFIG_DIR_ENV: &str = "ZSHRCMAN_CONFIG_DIR";
+/// Directory holding the dotfiles clone, logs and profiles, instead of the
+/// platform data dir.
+pub const DATA_DIR_ENV: &str = "ZSHRCMAN_DATA_DIR";
+
+/// Locations set through [`CONFIG_ENV`], [`CONFIG_DIR_ENV`] and
+/// [`DATA_DIR_ENV`], so separate contexts (a work laptop setup, a test
+/// sandbox) can live side by side. Relative paths are taken from the working
+/// directory.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PathOverrides {
+    pub config_path: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+}
+
+impl PathOverrides {
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+    
+    /// Reads the variables through `lookup`, so tests don't have to touch the
+    /// process environment.
+    pub fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let path = |name: &str| {
+            let value = lookup(name).filter(|value| !value.trim().is_empty())?;
+            let path = PathBuf::from(value);
+            Some(match std::env::current_dir() {
+                Ok(cwd) if path.is_relative() => cwd.join(path),
+                _ => path,
+            })
+        };
+        
+        Self {
+            config_path: path(CONFIG_ENV).or_else(|| Some(path(CONFIG_DIR_ENV)?.join("config.toml"))),
+            data_dir: path(DATA_DIR_ENV),
+        }
+    }
+}
 
 pub struct ConfigManager {
     config_path: PathBuf,
+    dotfiles_path: PathBuf,
     pub config: Config,
+    /// Groups matching the device tags they were found for; see
+    /// `tagged_groups`.
+    tagged: Mutex<Option<(Vec<String>, Vec<String>)>>,
+    /// Each group's `priority`, read once for `get_ordered_groups`.
+    priorities: Mutex<HashMap<String, i32>>,
+    /// The `ZSHRCMAN_*` variables the config was loaded with.
+    env: Vec<(String, String)>,
+    /// What they overrode, kept out of what `save` writes.
+    overrides: Vec<EnvOverride>,
 }
 
 impl ConfigManager {
     pub fn new() -> Result<Self> {
         let config_path = Self::get_config_path()?;
-        let config = Self::load_or_create(&config_path)?;
+        let dotfiles_path = Self::get_dotfiles_path()?;
+        Self::with_paths(config_path, dotfiles_path)
+    }
+    
+    /// Opens a config stored outside the standard project directories, e.g. a
+    /// throwaway sandbox used by `zshrcman tour`.
+    pub fn with_paths(config_path: PathBuf, dotfiles_path: PathBuf) -> Result<Self> {
+        Self::with_env(config_path, dotfiles_path, std::env::vars())
+    }
+    
+    /// Like `with_paths`, with `vars` in place of the process environment
+    /// for the `ZSHRCMAN_*` overrides.
+    pub fn with_env(config_path: PathBuf, dotfiles_path: PathBuf, vars: impl Iterator<Item = (String, String)>) -> Result<Self> {
+        let env: Vec<(String, String)> = vars.filter(|(name, _)| name.starts_with(ENV_PREFIX)).collect();
+        let (config, overrides) = env_overrides(Self::load_or_create(&config_path)?, env.iter().cloned())?;
         
         Ok(Self {
             config_path,
+            dotfiles_path,
             config,
+            tagged: Mutex::new(None),
+            priorities: Mutex::new(HashMap::new()),
+            env,
+            overrides,
         })
     }
     
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+    
+    /// Reads the config file again, for a long-running process to pick up
+    /// changes other commands saved.
+    pub fn reload(&mut self) -> Result<()> {
+        (self.config, self.overrides) = env_overrides(Self::load_or_create(&self.config_path)?, self.env.iter().cloned())?;
+        self.forget_group_files();
+        Ok(())
+    }
+    
+    /// Drops what was read from the group files, for when they may have
+    /// changed.
+    fn forget_group_files(&mut self) {
+        *self.tagged.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+        self.priorities.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+    }
+    
+    pub fn dotfiles_path(&self) -> &Path {
+        &self.dotfiles_path
+    }
+    
+    /// Directory holding the dotfiles checkout, logs and plugins.
+    pub fn data_dir(&self) -> PathBuf {
+        self.dotfiles_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.dotfiles_path.clone())
+    }
+    
+    /// Downloads and other files that can be re-created; `clean cache` empties it.
+    pub fn cache_dir(&self) -> PathBuf {
+        self.data_dir().join("cache")
+    }
+    
     pub fn get_config_path() -> Result<PathBuf> {
-        let proj_dirs = ProjectDirs::from("com", "zshrcman", "zshrcman")
-            .context("Could not determine project directories")?;
+        let config_path = match PathOverrides::from_env().config_path {
+            Some(path) => path,
+            None => {
+                let proj_dirs = ProjectDirs::from("com", "zshrcman", "zshrcman")
+                    .context("Could not determine project directories")?;
+                proj_dirs.config_dir().join("config.toml")
+            }
+        };
         
-        let config_dir = proj_dirs.config_dir();
-        fs::create_dir_all(config_dir)?;
+        if let Some(config_dir) = config_path.parent() {
+            fs::create_dir_all(config_dir)?;
+        }
+        Ok(config_path)
+    }
+    
+    /// The data dir, without a ConfigManager; see [`ConfigManager::data_dir`].
+    pub fn get_data_dir() -> Result<PathBuf> {
+        let data_dir = match PathOverrides::from_env().data_dir {
+            Some(dir) => dir,
+            None => {
+                let proj_dirs = ProjectDirs::from("com", "zshrcman", "zshrcman")
+                    .context("Could not determine project directories")?;
+                proj_dirs.data_dir().to_path_buf()
+            }
+        };
         
-        Ok(config_dir.join("config.toml"))
+        fs::create_dir_all(&data_dir)?;
+        Ok(data_dir)
     }
     
     pub fn get_dotfiles_path() -> Result<PathBuf> {
-        let proj_dirs = ProjectDirs::from("com", "zshrcman", "zshrcman")
-            .context("Could not determine project directories")?;
-        
-        let data_dir = proj_dirs.data_dir();
-        fs::create_dir_all(data_dir)?;
-        
-        Ok(data_dir.join("dotfiles"))
+        Ok(Self::get_data_dir()?.join("dotfiles"))
     }
     
     fn load_or_create(path: &Path) -> Result<Config> {
@@ -51,15 +184,41 @@ impl ConfigManager {
         }
     }
     
+    /// Writes the config. Keys environment variables overrode keep their
+    /// value from the file, unless the command changed them.
     pub fn save(&self) -> Result<()> {
-        let toml = toml::to_string_pretty(&self.config)?;
-        fs::write(&self.config_path, toml)?;
-        Ok(())
+        let toml = if self.overrides.is_empty() {
+            toml::to_string_pretty(&self.config)?
+        } else {
+            let mut root = toml::Value::try_from(&self.config)?;
+            for env_override in &self.overrides {
+                env_override.revert(&mut root);
+            }
+            let config: Config = root.try_into().context("Failed to leave the environment overrides out of the config")?;
+            toml::to_string_pretty(&config)?
+        };
+        write_atomic(&self.config_path, toml.as_bytes())
+    }
+    
+    /// Where a group lives: the repo root its files are relative to, and its
+    /// name inside that repo. Namespaced `<source>/<group>` and
+    /// `<repository>/<group>` names resolve into that checkout, everything
+    /// else into the dotfiles repo.
+    pub fn resolve_group<'a>(&self, group_name: &'a str) -> (PathBuf, &'a str) {
+        if let Some((namespace, group)) = group_name.split_once('/') {
+            if self.config.sources.contains_key(namespace) {
+                return (sources::checkout_dir(&self.data_dir(), namespace), group);
+            }
+            if self.config.repositories.contains_key(namespace) {
+                return (repos::checkout_dir(&self.data_dir(), namespace), group);
+            }
+        }
+        (self.dotfiles_path.clone(), group_name)
     }
     
     pub fn load_group_config(&self, group_name: &str) -> Result<GroupConfig> {
-        let dotfiles_path = Self::get_dotfiles_path()?;
-        let group_path = dotfiles_path.join("groups").join(format!("{}.toml", group_name));
+        let (root, name) = self.resolve_group(group_name);
+        let group_path = root.join("groups").join(format!("{}.toml", name));
         
         if !group_path.exists() {
             anyhow::bail!("Group config file does not exist: {:?}", group_path);
@@ -71,8 +230,7 @@ impl ConfigManager {
     }
     
     pub fn load_device_group_config(&self, device: &str, group_name: &str) -> Result<GroupConfig> {
-        let dotfiles_path = Self::get_dotfiles_path()?;
-        let group_path = dotfiles_path
+        let group_path = self.dotfiles_path
             .join("devices")
             .join(device)
             .join("groups")
@@ -87,6 +245,40 @@ impl ConfigManager {
         Ok(config)
     }
     
+    /// The group as this device sees it: the global definition with the
+    /// device's `devices/<device>/groups/<name>.toml` merged over it, and the
+    /// files that went into it. Either one alone is enough.
+    pub fn load_merged_group(&self, group_name: &str) -> Result<(GroupConfig, Vec<PathBuf>)> {
+        let (root, name) = self.resolve_group(group_name);
+        let global_path = root.join("groups").join(format!("{}.toml", name));
+        let device_path = self.dotfiles_path
+            .join("devices")
+            .join(&self.config.device.name)
+            .join("groups")
+            .join(format!("{}.toml", group_name));
+        
+        let mut merged: Option<GroupConfig> = None;
+        let mut files = Vec::new();
+        for path in [Some(global_path), (!self.config.device.name.is_empty()).then_some(device_path)] {
+            let Some(path) = path.filter(|path| path.is_file()) else {
+                continue;
+            };
+            let contents = fs::read_to_string(&path)?;
+            let group: GroupConfig = toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            match &mut merged {
+                Some(merged) => merged.merge(group),
+                None => merged = Some(group),
+            }
+            files.push(path);
+        }
+        
+        match merged {
+            Some(group) => Ok((group, files)),
+            None => anyhow::bail!("Group '{}' has no group file", group_name),
+        }
+    }
+    
     pub fn add_global_group(&mut self, name: String) -> Result<()> {
         if !self.config.groups.global.contains(&name) {
             self.config.groups.global.push(name);
@@ -100,36 +292,355 @@ impl ConfigManager {
             anyhow::bail!("Cannot remove built-in 'default' group");
         }
         
+        self.require_global_group_or_stale(name)?;
+        
         self.config.groups.global.retain(|g| g != name);
         self.config.groups.enabled_global.retain(|g| g != name);
         self.save()?;
         Ok(())
     }
     
+    /// Enables a global group, or turns a tagged group disabled with
+    /// `disable_global_group` back on.
     pub fn enable_global_group(&mut self, name: &str) -> Result<()> {
-        if self.config.groups.global.contains(&name.to_string()) {
-            if !self.config.groups.enabled_global.contains(&name.to_string()) {
-                self.config.groups.enabled_global.push(name.to_string());
-                self.save()?;
-            }
-        } else {
-            anyhow::bail!("Group '{}' is not defined", name);
+        if self.config.groups.disabled_tagged.iter().any(|g| g == name) {
+            self.config.groups.disabled_tagged.retain(|g| g != name);
+            return self.save();
+        }
+        self.require_global_group(name)?;
+        if !self.config.groups.enabled_global.contains(&name.to_string()) {
+            self.config.groups.enabled_global.push(name.to_string());
+            self.save()?;
         }
         Ok(())
     }
     
+    /// Disables a global group. A group that targets one of the device's
+    /// tags is also kept off this device until it's enabled again.
     pub fn disable_global_group(&mut self, name: &str) -> Result<()> {
+        let tagged = self.tagged_groups().iter().any(|g| g == name);
+        if !tagged {
+            self.require_global_group_or_stale(name)?;
+        }
         self.config.groups.enabled_global.retain(|g| g != name);
+        if tagged {
+            self.config.groups.disabled_tagged.push(name.to_string());
+        }
         self.save()?;
         Ok(())
     }
     
+    /// Fails, suggesting similar names, unless `name` is a global group.
+    fn require_global_group(&self, name: &str) -> Result<()> {
+        if !self.config.groups.global.iter().any(|g| g == name) {
+            return Err(suggest::not_found("Group", name, &self.config.groups.global));
+        }
+        Ok(())
+    }
+    
+    /// `require_global_group`, except that a name left in `enabled_global`
+    /// after its group went away passes with a warning, so it can be cleaned
+    /// up.
+    fn require_global_group_or_stale(&self, name: &str) -> Result<()> {
+        let stale = !self.config.groups.global.iter().any(|g| g == name)
+            && self.config.groups.enabled_global.iter().any(|g| g == name);
+        if stale {
+            output::warn(format!("⚠️  Group '{}' is only listed in groups.enabled_global; removing the stale entry", name));
+            return Ok(());
+        }
+        self.require_global_group(name)
+    }
+    
+    /// Renames a global group: its group file and directory, the device-level
+    /// files of the same name, its place in `[groups]` and in profiles'
+    /// `enabled_groups`, its alias group and its install status. Returns the
+    /// repo-relative paths that changed.
+    pub fn rename_group(&mut self, old: &str, new: &str) -> Result<Vec<PathBuf>> {
+        if old == "default" {
+            anyhow::bail!("Cannot rename built-in 'default' group");
+        }
+        self.transfer_group(old, new, true)
+    }
+    
+    /// Duplicates a global group's files and alias group under a new name,
+    /// listed in `per_device` too if the original is. The copy starts out
+    /// disabled and not installed.
+    pub fn copy_group(&mut self, source: &str, target: &str) -> Result<Vec<PathBuf>> {
+        self.transfer_group(source, target, false)
+    }
+    
+    /// Moves (or copies) the group's files first and saves the config last;
+    /// if either step fails, the files are put back as they were.
+    fn transfer_group(&mut self, from: &str, to: &str, rename: bool) -> Result<Vec<PathBuf>> {
+        if let Some(name) = [from, to].into_iter().find(|name| name.contains('/')) {
+            anyhow::bail!("'{}' lives in a source or repository; change it there", name);
+        }
+        self.require_global_group(from)?;
+        if self.config.groups.global.iter().any(|g| g == to) {
+            anyhow::bail!("Group '{}' already exists", to);
+        }
+        
+        let mut group_dirs = vec![PathBuf::from("groups")];
+        let devices = self.dotfiles_path.join("devices");
+        if devices.is_dir() {
+            for entry in fs::read_dir(&devices)?.filter_map(|entry| entry.ok()) {
+                group_dirs.push(Path::new("devices").join(entry.file_name()).join("groups"));
+            }
+        }
+        let mut moves = Vec::new();
+        for dir in &group_dirs {
+            moves.push((dir.join(format!("{}.toml", from)), dir.join(format!("{}.toml", to))));
+            moves.push((dir.join(from), dir.join(to)));
+        }
+        moves.retain(|(source, _)| self.dotfiles_path.join(source).exists());
+        if let Some((_, target)) = moves.iter().find(|(_, target)| self.dotfiles_path.join(target).exists()) {
+            anyhow::bail!("{} already exists", target.display());
+        }
+        
+        // The group files are about to change under the caches.
+        self.forget_group_files();
+        let mut done = Vec::new();
+        for (source, target) in &moves {
+            let result = transfer_path(&self.dotfiles_path.join(source), &self.dotfiles_path.join(target), rename)
+                .and_then(|_| {
+                    if target.extension().is_some_and(|ext| ext == "toml") {
+                        rename_group_file(&self.dotfiles_path.join(target), from, to)?;
+                    }
+                    Ok(())
+                });
+            if let Err(e) = result {
+                return Err(self.undo_transfer(&done, rename, e));
+            }
+            done.push((source.clone(), target.clone()));
+        }
+        
+        let previous = self.config.clone();
+        let groups = &mut self.config.groups;
+        if rename {
+            let profile_lists = self.config.profiles.values_mut().map(|profile| &mut profile.enabled_groups);
+            let lists = [&mut groups.global, &mut groups.per_device, &mut groups.enabled_global, &mut groups.enabled_devices];
+            for list in lists.into_iter().chain(profile_lists) {
+                for name in list.iter_mut().filter(|name| *name == from) {
+                    *name = to.to_string();
+                }
+            }
+            if let Some(aliases) = self.config.aliases.remove(from) {
+                self.config.aliases.insert(to.to_string(), aliases);
+            }
+            if let Some(status) = self.config.status.remove(from) {
+                self.config.status.insert(to.to_string(), status);
+            }
+        } else {
+            groups.global.push(to.to_string());
+            if groups.per_device.iter().any(|name| name == from) {
+                groups.per_device.push(to.to_string());
+            }
+            if let Some(aliases) = self.config.aliases.get(from).cloned() {
+                self.config.aliases.insert(to.to_string(), aliases);
+            }
+        }
+        if let Err(e) = self.save() {
+            self.config = previous;
+            return Err(self.undo_transfer(&done, rename, e));
+        }
+        
+        Ok(done
+            .into_iter()
+            .flat_map(|(source, target)| if rename { vec![source, target] } else { vec![target] })
+            .collect())
+    }
+    
+    /// Puts the transferred files back after `error`, which comes back
+    /// naming any that couldn't be restored.
+    fn undo_transfer(&self, done: &[(PathBuf, PathBuf)], rename: bool, error: anyhow::Error) -> anyhow::Error {
+        let mut failed = Vec::new();
+        for (source, target) in done.iter().rev() {
+            let (source, target) = (self.dotfiles_path.join(source), self.dotfiles_path.join(target));
+            let result = if rename {
+                fs::rename(&target, &source)
+            } else if target.is_dir() {
+                fs::remove_dir_all(&target)
+            } else {
+                fs::remove_file(&target)
+            };
+            if let Err(e) = result {
+                failed.push(format!("{} ({})", source.display(), e));
+            }
+        }
+        if failed.is_empty() {
+            return error;
+        }
+        error.context(format!("Also failed to restore {}; put them back by hand", failed.join(", ")))
+    }
+    
+    pub fn is_held(&self, package: &str) -> bool {
+        self.config.held_packages.contains(package)
+    }
+    
+    /// Whether `zshrcman pin` pinned the package's installation record.
+    pub fn is_pinned(&self, package: &str) -> bool {
+        self.config.installations.get(package).is_some_and(|record| record.pinned)
+    }
+    
+    /// Records `packages`, which installing `group` through `installer`
+    /// pulled in, as dependencies of the group. Packages recorded already
+    /// only gain the group in `required_by`.
+    pub fn record_group_packages(&mut self, group: &str, installer: &str, packages: &[String]) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+        let dependent = group_dependent(group);
+        for package in packages {
+            let record = self.config.installations.entry(package.clone()).or_insert_with(|| InstallationRecord {
+                package: package.clone(),
+                version: None,
+                installed_at: chrono::Utc::now(),
+                installed_by: InstallationSource::Manual,
+                active_for: Default::default(),
+                scope: InstallScope::Global,
+                location: None,
+                installer_type: String::new(),
+                pinned: false,
+                required_by: Default::default(),
+                binaries: Vec::new(),
+            });
+            // A pin made before the group installed the package carries over.
+            if record.is_pin_only() {
+                record.installed_at = chrono::Utc::now();
+                record.installed_by = InstallationSource::Dependency(dependent.clone());
+                record.installer_type = installer.to_string();
+            }
+            record.required_by.insert(dependent.clone());
+        }
+        self.save()
+    }
+    
+    /// `packages` minus those removing `group` has to leave installed: ones
+    /// a profile installed itself or something else still requires.
+    /// Packages without a record are the group's.
+    pub fn group_packages_to_remove(&self, group: &str, packages: &[String]) -> Vec<String> {
+        let dependent = group_dependent(group);
+        packages
+            .iter()
+            .filter(|package| match self.config.installations.get(*package) {
+                Some(record) if !record.is_pin_only() => {
+                    matches!(record.installed_by, InstallationSource::Dependency(_))
+                        && record.required_by.iter().all(|by| *by == dependent)
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect()
+    }
+    
+    /// Drops `group` from every record's `required_by`, and the records of
+    /// the `removed` packages only the group needed.
+    pub fn release_group_packages(&mut self, group: &str, removed: &[String]) -> Result<()> {
+        let dependent = group_dependent(group);
+        let mut changed = false;
+        for record in self.config.installations.values_mut() {
+            changed |= record.required_by.remove(&dependent);
+        }
+        self.config.installations.retain(|package, record| {
+            let orphaned = removed.contains(package)
+                && record.required_by.is_empty()
+                && matches!(&record.installed_by, InstallationSource::Dependency(by) if *by == dependent);
+            changed |= orphaned;
+            !orphaned
+        });
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+    
+    /// Returns false if the package was already held.
+    pub fn hold_package(&mut self, package: &str) -> Result<bool> {
+        let added = self.config.held_packages.insert(package.to_string());
+        if added {
+            self.save()?;
+        }
+        Ok(added)
+    }
+    
+    /// Returns false if the package wasn't held.
+    pub fn unhold_package(&mut self, package: &str) -> Result<bool> {
+        let removed = self.config.held_packages.remove(package);
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+    
     pub fn update_install_status(&mut self, group: &str, status: InstallStatus) -> Result<()> {
         self.config.status.insert(group.to_string(), status);
         self.save()?;
         Ok(())
     }
     
+    /// Groups enabled by the active profile and its parents, parents first.
+    pub fn profile_groups(&self) -> Vec<String> {
+        let mut chain: Vec<&Profile> = Vec::new();
+        let mut next = self.config.active_profile.as_deref();
+        while let Some(profile) = next.and_then(|name| self.config.profiles.get(name)) {
+            if chain.iter().any(|seen| seen.name == profile.name) {
+                break;
+            }
+            chain.push(profile);
+            next = profile.parent.as_deref();
+        }
+        
+        let mut groups: Vec<String> = Vec::new();
+        for profile in chain.iter().rev() {
+            for group in &profile.enabled_groups {
+                if !groups.contains(group) {
+                    groups.push(group.clone());
+                }
+            }
+        }
+        groups
+    }
+    
+    /// Groups in the repo's `groups/` whose `target_tags` share a tag with
+    /// this device, by name, minus those in `disabled_tagged`. Groups that
+    /// fail to parse are left to `validate`. The group files are only read
+    /// again when the device's tags change.
+    pub fn tagged_groups(&self) -> Vec<String> {
+        let tags = &self.config.device.tags;
+        let mut cached = self.tagged.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let groups = match &*cached {
+            Some((found_for, groups)) if found_for == tags => groups,
+            _ => &cached.insert((tags.clone(), self.find_tagged_groups())).1,
+        };
+        groups.iter().filter(|group| !self.config.groups.disabled_tagged.contains(group)).cloned().collect()
+    }
+    
+    fn find_tagged_groups(&self) -> Vec<String> {
+        let tags = &self.config.device.tags;
+        if tags.is_empty() {
+            return Vec::new();
+        }
+        let Ok(entries) = fs::read_dir(self.dotfiles_path.join("groups")) else {
+            return Vec::new();
+        };
+        
+        let mut groups: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter_map(|name| name.strip_suffix(".toml").map(String::from))
+            .filter(|name| {
+                self.load_merged_group(name)
+                    .is_ok_and(|(group, _)| group.target_tags.iter().any(|tag| tags.contains(tag)))
+            })
+            .collect();
+        groups.sort();
+        groups
+    }
+    
+    /// The effective groups in install order: sorted by their `priority`,
+    /// and otherwise `default`, the enabled global groups, those of the active
+    /// profile, the enabled device groups, then the groups targeting one of
+    /// the device's tags.
     pub fn get_ordered_groups(&self) -> Vec<String> {
         let mut groups = Vec::new();
         
@@ -141,18 +652,341 @@ impl ConfigManager {
             }
         }
         
+        for group in self.profile_groups() {
+            if !groups.contains(&group) {
+                groups.push(group);
+            }
+        }
+        
         for device_group in &self.config.groups.enabled_devices {
             if !groups.contains(device_group) {
                 groups.push(device_group.clone());
             }
         }
         
+        for tagged in self.tagged_groups() {
+            if !groups.contains(&tagged) {
+                groups.push(tagged);
+            }
+        }
+        
+        // Stable, so equal priorities keep the order above.
+        groups.sort_by_cached_key(|group| self.priority(group));
         groups
     }
     
+    /// The group's `priority`, 0 with a warning when its file doesn't parse
+    /// (installing it reports the error), and 0 for groups without a file.
+    fn priority(&self, group: &str) -> i32 {
+        let mut priorities = self.priorities.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *priorities.entry(group.to_string()).or_insert_with(|| match self.load_merged_group(group) {
+            Ok((config, _)) => config.priority,
+            Err(e) if e.downcast_ref::<toml::de::Error>().is_some() => {
+                output::warn(format!("⚠️  Ordering group '{}' with priority 0; it doesn't parse: {:#}", group, e));
+                0
+            }
+            Err(_) => 0,
+        })
+    }
+    
     pub fn clear_all_status(&mut self) -> Result<()> {
         self.config.status.clear();
         self.save()?;
         Ok(())
     }
+}
+
+/// A config key an environment variable overrode: its path of table keys,
+/// the value it got and the one it had before (`None` if it had none).
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvOverride {
+    pub path: Vec<String>,
+    pub value: toml::Value,
+    pub original: Option<toml::Value>,
+}
+
+impl EnvOverride {
+    /// Puts the original value back in `root`, unless the key no longer
+    /// holds the overriding value.
+    fn revert(&self, root: &mut toml::Value) {
+        let Some((key, parents)) = self.path.split_last() else {
+            return;
+        };
+        let mut table = root.as_table_mut();
+        for parent in parents {
+            table = table.and_then(|table| table.get_mut(parent)).and_then(toml::Value::as_table_mut);
+        }
+        let Some(table) = table.filter(|table| table.get(key) == Some(&self.value)) else {
+            return;
+        };
+        match &self.original {
+            Some(original) => table.insert(key.clone(), original.clone()),
+            None => table.remove(key),
+        };
+    }
+}
+
+/// Overrides config keys from `ZSHRCMAN_<SECTION>_<KEY>` variables, e.g.
+/// `ZSHRCMAN_REPOSITORY_URL` sets `url` under `[repository]` and
+/// `ZSHRCMAN_VARIABLES_EMAIL` sets `email` under `[variables]`.
+///
+/// The section must be a top-level table of config.toml; the rest of the name
+/// is the key, matched regardless of case (a new key is lowercased). Nested
+/// tables are matched the same way (`ZSHRCMAN_STATUS_BREW_INSTALLED`). Values
+/// are parsed according to the existing value's type: booleans, integers, and
+/// comma-separated lists for arrays. Variables that don't name a section, such
+/// as `ZSHRCMAN_YES`, are left to the CLI.
+pub fn apply_env_overrides(config: Config, vars: impl Iterator<Item = (String, String)>) -> Result<Config> {
+    env_overrides(config, vars).map(|(config, _)| config)
+}
+
+/// [`apply_env_overrides`], also returning what was overridden.
+pub fn env_overrides(config: Config, vars: impl Iterator<Item = (String, String)>) -> Result<(Config, Vec<EnvOverride>)> {
+    let mut overrides: Vec<(String, String)> = vars
+        .filter_map(|(name, value)| {
+            let rest = name.strip_prefix(ENV_PREFIX)?;
+            Some((rest.to_lowercase(), value))
+        })
+        .collect();
+    if overrides.is_empty() {
+        return Ok((config, vec![]));
+    }
+    overrides.sort();
+    
+    let mut root = toml::Value::try_from(&config)?;
+    let mut applied = Vec::new();
+    for (path, raw) in &overrides {
+        let Some(table) = root.as_table_mut() else {
+            break;
+        };
+        
+        // Only descend into existing top-level sections; unknown ones are CLI flags.
+        let Some(section) = longest_key_prefix(table, path) else {
+            continue;
+        };
+        let Some(rest) = path.get(section.len() + 1..).filter(|_| path.len() > section.len()) else {
+            continue;
+        };
+        let Some(section_table) = table.get_mut(&section).and_then(toml::Value::as_table_mut) else {
+            continue;
+        };
+        
+        let mut env_override = set_override(section_table, rest, raw)
+            .with_context(|| format!("Invalid value for {}{}", ENV_PREFIX, path.to_uppercase()))?;
+        env_override.path.insert(0, section);
+        tracing::debug!("config override from {}{}", ENV_PREFIX, path.to_uppercase());
+        applied.push(env_override);
+    }
+    
+    if applied.is_empty() {
+        return Ok((config, applied));
+    }
+    let config = root.try_into().context("Environment overrides produced an invalid config")?;
+    Ok((config, applied))
+}
+
+/// How a group shows up in `required_by` and as an `installed_by`
+/// dependency, e.g. `group cuda`.
+fn group_dependent(group: &str) -> String {
+    format!("group {}", group)
+}
+
+/// The longest key of `table` that equals `path` or prefixes it followed by
+/// `_`, ignoring case; `path` is lowercase.
+fn longest_key_prefix(table: &toml::Table, path: &str) -> Option<String> {
+    table
+        .keys()
+        .filter(|key| {
+            let key = key.to_lowercase();
+            path == key || path.starts_with(&format!("{}_", key))
+        })
+        .max_by_key(|key| key.len())
+        .cloned()
+}
+
+/// Sets the key `path` names in `table` from `raw`, returning the override
+/// with its path below `table`.
+fn set_override(table: &mut toml::Table, path: &str, raw: &str) -> Result<EnvOverride> {
+    if let Some(key) = longest_key_prefix(table, path) {
+        if key.len() != path.len() {
+            if let Some(nested) = table.get_mut(&key).and_then(toml::Value::as_table_mut) {
+                let mut env_override = set_override(nested, &path[key.len() + 1..], raw)?;
+                env_override.path.insert(0, key);
+                return Ok(env_override);
+            }
+        }
+    }
+    
+    let key = table.keys().find(|key| key.to_lowercase() == path).cloned().unwrap_or_else(|| path.to_string());
+    let original = table.get(&key).cloned();
+    let value = parse_override(original.as_ref(), raw)?;
+    table.insert(key.clone(), value.clone());
+    Ok(EnvOverride { path: vec![key], value, original })
+}
+
+fn parse_override(existing: Option<&toml::Value>, raw: &str) -> Result<toml::Value> {
+    Ok(match existing {
+        Some(toml::Value::Boolean(_)) => toml::Value::Boolean(match raw.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => true,
+            "0" | "false" | "no" | "off" | "" => false,
+            other => anyhow::bail!("expected a boolean, got '{}'", other),
+        }),
+        Some(toml::Value::Integer(_)) => toml::Value::Integer(raw.trim().parse()?),
+        Some(toml::Value::Array(_)) => toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        ),
+        Some(_) => toml::Value::String(raw.to_string()),
+        // New keys: accept TOML literals (`true`, `3`, `["a"]`), else a plain string.
+        None => toml::from_str::<toml::Table>(&format!("v = {}", raw))
+            .ok()
+            .and_then(|mut t| t.remove("v"))
+            .unwrap_or_else(|| toml::Value::String(raw.to_string())),
+    })
+}
+
+fn transfer_path(source: &Path, target: &Path, rename: bool) -> Result<()> {
+    let result = if rename {
+        fs::rename(source, target)
+    } else {
+        copy_tree(source, target)
+    };
+    result.with_context(|| format!("Failed to {} {}", if rename { "move" } else { "copy" }, source.display()))
+}
+
+fn copy_tree(source: &Path, target: &Path) -> std::io::Result<()> {
+    if !source.is_dir() {
+        return fs::copy(source, target).map(|_| ());
+    }
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        copy_tree(&entry.path(), &target.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Points the `name = "..."` line of a group file at its new name, leaving
+/// the rest of the file as written.
+fn rename_group_file(path: &Path, from: &str, to: &str) -> Result<()> {
+    let contents = fs::read_to_string(path)?;
+    let mut renamed = false;
+    let lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            let is_name = line.split_once('=').is_some_and(|(key, value)| {
+                key.trim() == "name" && value.trim().trim_matches(|c| c == '"' || c == '\'') == from
+            });
+            if is_name && !renamed {
+                renamed = true;
+                format!("name = \"{}\"", to)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if renamed {
+        let mut updated = lines.join("\n");
+        if contents.ends_with('\n') {
+            updated.push('\n');
+        }
+        write_atomic(path, updated.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it into
+/// place, so a crash mid-write leaves the old file intact. A symlinked `path`
+/// has its target replaced rather than the link, and the file keeps its mode.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let resolved = resolve_symlink(path)?;
+    let path = resolved.as_path();
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("{} is not a file path", path.display()))?;
+    let temp = path.with_file_name(format!(".{}.{}.tmp", file_name.to_string_lossy(), std::process::id()));
+    let permissions = fs::metadata(path).ok().map(|metadata| metadata.permissions());
+    
+    let written = File::create(&temp)
+        .and_then(|mut file| file.write_all(contents).and_then(|_| file.sync_all()))
+        .and_then(|_| match permissions {
+            Some(permissions) => fs::set_permissions(&temp, permissions),
+            None => Ok(()),
+        })
+        .and_then(|_| fs::rename(&temp, path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(e).with_context(|| format!("Failed to write {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Follows `path` through any symlinks, including one whose target doesn't
+/// exist yet.
+fn resolve_symlink(path: &Path) -> Result<PathBuf> {
+    let mut resolved = path.to_path_buf();
+    for _ in 0..40 {
+        match fs::symlink_metadata(&resolved) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target = fs::read_link(&resolved)
+                    .with_context(|| format!("Failed to read symlink {}", resolved.display()))?;
+                resolved = match resolved.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            }
+            _ => return Ok(resolved),
+        }
+    }
+    anyhow::bail!("Too many levels of symlinks at {}", path.display())
+}
+
+/// Advisory lock on config.toml, held for as long as the value lives. It
+/// keeps concurrent zshrcman processes from overwriting each other's changes.
+pub struct ConfigLock {
+    _file: File,
+}
+
+impl ConfigLock {
+    /// Takes the lock. If another process holds it, waits for it to finish,
+    /// or fails straight away when `wait` is false.
+    pub fn acquire(config_path: &Path, wait: bool) -> Result<Self> {
+        let lock_path = config_path.with_extension("toml.lock");
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+        
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(fs::TryLockError::WouldBlock) => {
+                let mut holder = String::new();
+                let _ = file.read_to_string(&mut holder);
+                let holder = match holder.trim() {
+                    "" => "another zshrcman process".to_string(),
+                    pid => format!("zshrcman (pid {})", pid),
+                };
+                
+                if !wait {
+                    anyhow::bail!("{} is busy: {} is using it", config_path.display(), holder);
+                }
+                output::info(format!("⏳ Waiting for {} to finish...", holder));
+                file.lock()?;
+            }
+            Err(fs::TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {}", lock_path.display()));
+            }
+        }
+        
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", std::process::id())?;
+        Ok(Self { _file: file })
+    }
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use crate::models::CronJob;
+
+fn markers(group: &str) -> (String, String) {
+    (format!("# >>> zshrcman cron {} >>>", group), format!("# <<< zshrcman cron {} <<<", group))
+}
+
+/// Whether `schedule` looks like five cron fields or an `@` shortcut.
+pub fn is_valid_schedule(schedule: &str) -> bool {
+    const SHORTCUTS: &[&str] = &["@reboot", "@yearly", "@annually", "@monthly", "@weekly", "@daily", "@midnight", "@hourly"];
+    if schedule.starts_with('@') {
+        return SHORTCUTS.contains(&schedule);
+    }
+    schedule.split_whitespace().count() == 5
+}
+
+/// The crontab lines for `jobs`, each preceded by its description.
+pub fn render(jobs: &[CronJob]) -> String {
+    let mut lines = String::new();
+    for job in jobs {
+        if let Some(description) = &job.description {
+            lines.push_str(&format!("# {}\n", description));
+        }
+        lines.push_str(&format!("{} {}\n", job.schedule, escape_command(&job.command)));
+    }
+    lines
+}
+
+/// `command` with its `%` escaped: cron turns a bare one into a newline and
+/// feeds the rest to the command's stdin. Already escaped ones stay as they are.
+pub fn escape_command(command: &str) -> String {
+    let mut escaped = String::new();
+    let mut backslash = false;
+    for c in command.chars() {
+        if c == '%' && !backslash {
+            escaped.push('\\');
+        }
+        backslash = c == '\\' && !backslash;
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// What `group`'s managed block in `crontab` holds, empty when it has none.
+pub fn block(crontab: &str, group: &str) -> String {
+    let (begin, end) = markers(group);
+    let mut content = String::new();
+    let mut in_block = false;
+    for line in crontab.lines() {
+        if line == begin {
+            in_block = true;
+        } else if line == end {
+            in_block = false;
+        } else if in_block {
+            content.push_str(line);
+            content.push('\n');
+        }
+    }
+    content
+}
+
+/// Replaces (or appends, or removes when `content` is empty) `group`'s
+/// managed block in `crontab`, leaving every other line alone.
+pub fn replace_block(crontab: &str, group: &str, content: &str) -> String {
+    let (begin, end) = markers(group);
+    let mut kept = String::new();
+    let mut in_block = false;
+    for line in crontab.lines() {
+        if line == begin {
+            in_block = true;
+        } else if line == end {
+            in_block = false;
+        } else if !in_block {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+    if !content.is_empty() {
+        kept.push_str(&format!("{}\n{}{}\n", begin, content, end));
+    }
+    kept
+}
+
+/// Whether this machine has a `crontab` command.
+pub fn is_available() -> bool {
+    which::which("crontab").is_ok()
+}
+
+/// The user's crontab; empty when they don't have one. Any other failure is
+/// an error, as writing after it would wipe the jobs we couldn't read.
+pub fn read() -> Result<String> {
+    let output = Command::new("crontab").arg("-l").output().context("Failed to run crontab")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if is_missing_crontab(&stderr) {
+            return Ok(String::new());
+        }
+        anyhow::bail!("Couldn't read your crontab: {}", stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether `crontab -l` failed only because the user has no crontab yet.
+pub fn is_missing_crontab(stderr: &str) -> bool {
+    stderr.contains("no crontab for")
+}
+
+/// Replaces the user's crontab with `crontab`.
+pub fn write(crontab: &str) -> Result<()> {
+    let mut process = Command::new("crontab")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to run crontab")?;
+    if let Some(mut stdin) = process.stdin.take() {
+        stdin.write_all(crontab.as_bytes())?;
+    }
+    if !process.wait()?.success() {
+        anyhow::bail!("crontab rejected the new jobs; check their schedules");
+    }
+    Ok(())
+}
+
+/// Puts `group`'s `jobs` into the user's crontab, or takes its block out when
+/// there are none. Returns the block's previous content if the crontab
+/// changed.
+pub fn apply(group: &str, jobs: &[CronJob]) -> Result<Option<String>> {
+    set_block(group, &render(jobs))
+}
+
+/// Sets `group`'s block to `content` (removing it when empty), returning the
+/// previous content if the crontab changed.
+pub fn set_block(group: &str, content: &str) -> Result<Option<String>> {
+    let current = read()?;
+    let updated = replace_block(&current, group, content);
+    if updated == current {
+        return Ok(None);
+    }
+    write(&updated)?;
+    Ok(Some(block(&current, group)))
+}
@@ -0,0 +1,276 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::models::ConflictStrategy;
+use crate::modules::config::{ConfigLock, ConfigManager};
+use crate::modules::git_mgr::{GitManager, SyncMode};
+use crate::modules::hooks::{self, Event};
+use crate::modules::output;
+use crate::modules::verify::{Drift, DriftKind, Subject};
+
+/// Socket in the data dir that `zshrcman status --live` asks.
+pub const SOCKET_FILE: &str = "daemon.sock";
+/// How long the repo has to stay quiet before its changes are committed, so
+/// an editor's save or a `git checkout` becomes one commit.
+pub const SETTLE: Duration = Duration::from_secs(2);
+/// Prefix of the daemon's commit messages.
+pub const COMMIT_PREFIX: &str = "Auto-commit: ";
+
+/// What the daemon reports over its socket.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub started: Option<DateTime<Utc>>,
+    pub commits: usize,
+    pub last_commit: Option<DateTime<Utc>>,
+    pub last_commit_message: Option<String>,
+    pub last_sync: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Deployed files edited since the daemon started.
+    pub drifted: Vec<PathBuf>,
+}
+
+pub fn socket_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SOCKET_FILE)
+}
+
+/// What a changed repo path belongs to: `group <name>` for anything of a
+/// group, `device <name>` for a device's overrides, else the path.
+fn describe(path: &str) -> String {
+    let mut parts = path.split('/');
+    match (parts.next(), parts.next()) {
+        (Some("groups"), Some(name)) => format!("group {}", name.trim_end_matches(".toml")),
+        (Some("devices"), Some(device)) => format!("device {}", device),
+        _ => path.to_string(),
+    }
+}
+
+/// A commit message naming what the changed `files` belong to, e.g.
+/// `Auto-commit: update group brew, files/zshrc`.
+pub fn commit_message(files: &[String]) -> String {
+    let mut subjects: Vec<String> = vec![];
+    for file in files {
+        let subject = describe(file);
+        if !subjects.contains(&subject) {
+            subjects.push(subject);
+        }
+    }
+    let shown = subjects.len().min(3);
+    let mut message = format!("{}update {}", COMMIT_PREFIX, subjects[..shown].join(", "));
+    if subjects.len() > shown {
+        message.push_str(&format!(" and {} more", subjects.len() - shown));
+    }
+    message
+}
+
+/// Whether a watcher event for `path` is a change to the repo's files rather
+/// than git's own bookkeeping.
+pub fn is_repo_change(repo: &Path, path: &Path) -> bool {
+    path.strip_prefix(repo)
+        .is_ok_and(|relative| relative.components().next() != Some(Component::Normal(".git".as_ref())))
+}
+
+/// The running daemon's status, `None` when none answers on `socket`.
+#[cfg(unix)]
+pub fn query(socket: &Path) -> Result<Option<DaemonStatus>> {
+    let Ok(stream) = std::os::unix::net::UnixStream::connect(socket) else {
+        return Ok(None);
+    };
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let status = serde_json::from_reader(stream).context("The daemon sent an unreadable status")?;
+    Ok(Some(status))
+}
+
+#[cfg(not(unix))]
+pub fn query(_socket: &Path) -> Result<Option<DaemonStatus>> {
+    Ok(None)
+}
+
+/// Answers every connection to `socket` with the current status.
+#[cfg(unix)]
+fn serve(socket: &Path, status: Arc<Mutex<DaemonStatus>>) -> Result<()> {
+    if let Some(running) = query(socket)? {
+        anyhow::bail!("The daemon is already running (pid {})", running.pid);
+    }
+    let _ = std::fs::remove_file(socket);
+    let listener = std::os::unix::net::UnixListener::bind(socket)
+        .with_context(|| format!("Failed to listen on {}", socket.display()))?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let status = status.lock().unwrap().clone();
+            let _ = serde_json::to_writer(stream, &status);
+        }
+    });
+    Ok(())
+}
+
+// Windows has no unix sockets to answer `status --live` on.
+#[cfg(not(unix))]
+fn serve(_socket: &Path, _status: Arc<Mutex<DaemonStatus>>) -> Result<()> {
+    Ok(())
+}
+
+/// Watches the dotfiles repo and commits changes once they settle, syncs the
+/// device branch every `sync_interval` and notes edits to deployed files.
+pub struct Daemon {
+    config_mgr: ConfigManager,
+    deployed: Vec<PathBuf>,
+    sync_interval: Option<Duration>,
+}
+
+impl Daemon {
+    pub fn new(config_mgr: ConfigManager) -> Self {
+        Self {
+            config_mgr,
+            deployed: vec![],
+            sync_interval: None,
+        }
+    }
+    
+    /// Deployed files to watch for edits made outside the repo.
+    pub fn deployed(mut self, files: Vec<PathBuf>) -> Self {
+        self.deployed = files;
+        self
+    }
+    
+    /// Pulls and pushes this often; `None` only commits.
+    pub fn sync_interval(mut self, interval: Option<Duration>) -> Self {
+        self.sync_interval = interval;
+        self
+    }
+    
+    fn git(&self) -> Result<GitManager> {
+        let repository = &self.config_mgr.config.repository;
+        Ok(GitManager::open(self.config_mgr.dotfiles_path())?
+            .with_mirrors(&repository.remotes)?
+            .with_identity(&repository.identity))
+    }
+    
+    /// Commits everything changed in the repo, returning the message, or
+    /// `None` when the working tree is clean.
+    pub fn commit_changes(&self) -> Result<Option<String>> {
+        let git = self.git()?;
+        let files = git.changed_files()?;
+        if files.is_empty() {
+            return Ok(None);
+        }
+        let message = commit_message(&files);
+        let _lock = ConfigLock::acquire(self.config_mgr.config_path(), true)?;
+        git.stage_paths(&[PathBuf::from(".")])?;
+        git.commit(&message)?;
+        Ok(Some(message))
+    }
+    
+    fn sync(&self) -> Result<()> {
+        let config = &self.config_mgr.config;
+        let _lock = ConfigLock::acquire(self.config_mgr.config_path(), true)?;
+        let result = self.git()?.sync(
+            &config.repository.main_branch,
+            &config.device.branch,
+            ConflictStrategy::Abort,
+            SyncMode::Both,
+        );
+        hooks::emit_sync(config, "dotfiles", &config.device.branch, &result);
+        result
+    }
+    
+    /// Picks up config changes saved since the last cycle, keeping the
+    /// config it has when the file can't be read.
+    fn reload(&mut self, status: &Mutex<DaemonStatus>) {
+        if let Err(e) = self.config_mgr.reload() {
+            output::warn(format!("⚠️  Failed to reload the config: {:#}", e));
+            status.lock().unwrap().last_error = Some(format!("{:#}", e));
+        }
+    }
+    
+    /// Runs until the watcher stops.
+    pub fn run(&mut self) -> Result<()> {
+        let status = Arc::new(Mutex::new(DaemonStatus {
+            pid: std::process::id(),
+            started: Some(Utc::now()),
+            ..Default::default()
+        }));
+        let data_dir = self.config_mgr.data_dir();
+        std::fs::create_dir_all(&data_dir)?;
+        serve(&socket_path(&data_dir), status.clone())?;
+        
+        let repo = self.config_mgr.dotfiles_path().to_path_buf();
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).context("Failed to start the file watcher")?;
+        watcher
+            .watch(&repo, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}; run zshrcman init first", repo.display()))?;
+        // Editors often save by replacing the file, so watch the directories.
+        let dirs: BTreeSet<&Path> = self.deployed.iter().filter_map(|file| file.parent()).filter(|dir| dir.is_dir()).collect();
+        for dir in dirs {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        output::info(format!("👀 Watching {} and {} deployed file(s)", repo.display(), self.deployed.len()));
+        
+        let mut changed_at: Option<Instant> = None;
+        let mut synced_at = Instant::now();
+        loop {
+            match events.recv_timeout(Duration::from_millis(500)) {
+                Ok(Ok(event)) if !matches!(event.kind, EventKind::Access(_)) => {
+                    for path in event.paths {
+                        if self.deployed.contains(&path) {
+                            let mut locked = status.lock().unwrap();
+                            if !locked.drifted.contains(&path) {
+                                locked.drifted.push(path.clone());
+                                drop(locked);
+                                output::info(format!("✏️  {} was edited; `zshrcman verify` shows how it differs", path.display()));
+                                let drift = Drift::new(DriftKind::Modified, "deployed files", Subject::File { path }, "edited");
+                                hooks::emit(&self.config_mgr.config, Event::Drift, serde_json::json!({ "drift": [drift] }));
+                            }
+                        } else if is_repo_change(&repo, &path) {
+                            changed_at = Some(Instant::now());
+                        }
+                    }
+                }
+                Ok(Ok(_)) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Ok(Err(e)) => status.lock().unwrap().last_error = Some(e.to_string()),
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            
+            if changed_at.is_some_and(|at| at.elapsed() >= SETTLE) {
+                changed_at = None;
+                self.reload(&status);
+                let result = self.commit_changes();
+                let mut status = status.lock().unwrap();
+                match result {
+                    Ok(Some(message)) => {
+                        output::info(format!("📝 {}", message));
+                        status.commits += 1;
+                        status.last_commit = Some(Utc::now());
+                        status.last_commit_message = Some(message);
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        output::warn(format!("⚠️  Failed to commit: {:#}", e));
+                        status.last_error = Some(format!("{:#}", e));
+                    }
+                }
+            }
+            
+            if self.sync_interval.is_some_and(|interval| synced_at.elapsed() >= interval) {
+                synced_at = Instant::now();
+                self.reload(&status);
+                let result = self.sync();
+                let mut status = status.lock().unwrap();
+                match result {
+                    Ok(()) => status.last_sync = Some(Utc::now()),
+                    Err(e) => {
+                        output::warn(format!("⚠️  Failed to sync: {:#}", e));
+                        status.last_error = Some(format!("{:#}", e));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
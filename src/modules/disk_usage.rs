@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::RetentionSettings;
+use crate::modules::config::ConfigManager;
+use crate::modules::history::History;
+use crate::modules::oplog::{OperationLog, OPERATIONS_LOG};
+
+/// Size of one area of the data dir, as shown by `zshrcman du`.
+#[derive(Debug, Clone)]
+pub struct Usage {
+    pub label: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// Bytes used by a file, or by everything below a directory. Symlinks count
+/// as themselves, not what they point to, so profile bins that link into
+/// other directories aren't counted twice.
+pub fn size_of(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    
+    fs::read_dir(path)
+        .map(|entries| entries.filter_map(|entry| entry.ok()).map(|entry| size_of(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Everything zshrcman keeps under its data dir, largest areas first.
+/// Profiles are listed one by one; missing areas are left out.
+pub fn report(config_mgr: &ConfigManager) -> Vec<Usage> {
+    let data_dir = config_mgr.data_dir();
+    let mut areas = vec![
+        ("dotfiles".to_string(), config_mgr.dotfiles_path().to_path_buf()),
+        ("sources".to_string(), data_dir.join("sources")),
+        ("repos".to_string(), data_dir.join("repos")),
+        ("cache".to_string(), config_mgr.cache_dir()),
+        ("logs".to_string(), data_dir.join("logs")),
+        ("history".to_string(), History::new(&data_dir).path().to_path_buf()),
+        ("changesets".to_string(), data_dir.join("changesets")),
+        ("rendered".to_string(), data_dir.join("rendered")),
+        ("plugins".to_string(), data_dir.join("plugins")),
+    ];
+    if let Ok(entries) = fs::read_dir(data_dir.join("profiles")) {
+        let mut profiles: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+        profiles.sort();
+        for path in profiles {
+            let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            areas.push((format!("profiles/{}", name), path));
+        }
+    }
+    
+    let mut usage: Vec<Usage> = areas
+        .into_iter()
+        .filter(|(_, path)| path.exists())
+        .map(|(label, path)| Usage {
+            bytes: size_of(&path),
+            label,
+            path,
+        })
+        .collect();
+    usage.sort_by_key(|area| std::cmp::Reverse(area.bytes));
+    usage
+}
+
+/// `512 B`, `4.0 KiB`, `12.3 MiB`, `1.1 GiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Empties the cache dir. Returns the bytes freed.
+pub fn clear_cache(cache_dir: &Path) -> Result<u64> {
+    let freed = size_of(cache_dir);
+    if cache_dir.exists() {
+        fs::remove_dir_all(cache_dir).with_context(|| format!("Failed to remove {}", cache_dir.display()))?;
+    }
+    Ok(freed)
+}
+
+/// Drops operation log entries older than the retention period, and other
+/// files in the logs dir last modified before it. Returns the bytes freed.
+pub fn prune_logs(data_dir: &Path, retention: &RetentionSettings, now: DateTime<Utc>) -> Result<u64> {
+    let logs_dir = data_dir.join("logs");
+    let before = size_of(&logs_dir);
+    let cutoff = now - Duration::days(i64::from(retention.log_days));
+    
+    OperationLog::new(data_dir).prune(cutoff)?;
+    
+    if let Ok(entries) = fs::read_dir(&logs_dir) {
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            if !path.is_file() || path.file_name().is_some_and(|name| name == OPERATIONS_LOG) {
+                continue;
+            }
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified());
+            if modified.is_ok_and(|modified| DateTime::<Utc>::from(modified) < cutoff) {
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+    
+    Ok(before.saturating_sub(size_of(&logs_dir)))
+}
+
+/// Keeps only the newest config history entries, and removes staging dirs
+/// that interrupted installs left in `changesets/`. Returns the bytes freed.
+pub fn prune_snapshots(data_dir: &Path, retention: &RetentionSettings) -> Result<u64> {
+    let history = History::new(data_dir);
+    let changesets = data_dir.join("changesets");
+    let before = size_of(history.path()) + size_of(&changesets);
+    
+    history.prune(retention.history_entries)?;
+    
+    // Staging dirs are named `.<group>-<pid>`. Installs hold the config lock,
+    // as does `clean`, so any that exist now are left over.
+    if let Ok(entries) = fs::read_dir(&changesets) {
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let staging = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if staging && path.is_dir() {
+                fs::remove_dir_all(&path)?;
+            }
+        }
+    }
+    
+    Ok(before.saturating_sub(size_of(history.path()) + size_of(&changesets)))
+}
@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use crate::modules::config;
+use crate::modules::prompt;
+use crate::modules::validate::{self, Problem, Severity};
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then `notepad` on Windows or
+/// `vi` elsewhere. The value may carry arguments, e.g. `code --wait`.
+pub struct Editor {
+    command: Vec<String>,
+}
+
+impl Editor {
+    pub fn new(command: Vec<String>) -> Self {
+        Self { command }
+    }
+    
+    /// Splits the variable the way a shell would, so quoted paths with spaces
+    /// stay one argument. Windows paths use backslashes as separators, so
+    /// there it only splits on whitespace.
+    pub fn from_env() -> Result<Self> {
+        let command = ["VISUAL", "EDITOR"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+        let words = if cfg!(windows) {
+            command.split_whitespace().map(String::from).collect()
+        } else {
+            shell_words::split(&command).with_context(|| format!("Can't parse editor command '{}'", command))?
+        };
+        Ok(Self::new(words))
+    }
+    
+    fn open(&self, path: &Path) -> Result<()> {
+        let (program, args) = self.command.split_first().context("No editor configured; set $EDITOR")?;
+        let status = Command::new(program)
+            .args(args)
+            .arg(path)
+            .status()
+            .with_context(|| format!("Failed to start editor '{}'", program))?;
+        if !status.success() {
+            anyhow::bail!("Editor '{}' exited with {}", program, status);
+        }
+        Ok(())
+    }
+    
+    /// Edits a copy of `path`, starting from `initial` if it doesn't exist,
+    /// and only replaces `path` once `check` finds no errors in the copy.
+    /// On errors the user can re-open the copy or give up, which fails.
+    /// Returns whether `path` changed.
+    pub fn edit_checked(
+        &self,
+        path: &Path,
+        initial: &str,
+        check: impl Fn(&Path) -> Vec<Problem>,
+    ) -> Result<bool> {
+        let original = match fs::read_to_string(path) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        
+        // The copy lives in a private directory next to the file rather than
+        // in the shared temp dir, since configs can hold secrets. It keeps
+        // the file name, so group files still validate against their name.
+        let parent = path.parent().context("Nothing to edit")?;
+        fs::create_dir_all(parent)?;
+        let dir = tempfile::Builder::new()
+            .prefix(".zshrcman-edit-")
+            .tempdir_in(parent)
+            .with_context(|| format!("Failed to create a working copy in {}", parent.display()))?;
+        let copy = dir.path().join(path.file_name().context("Nothing to edit")?);
+        let before = original.as_deref().unwrap_or(initial);
+        fs::write(&copy, before)?;
+        
+        let result = self.edit_until_valid(path, &copy, &check).and_then(|edited| {
+            if edited == before {
+                return Ok(false);
+            }
+            config::write_atomic(path, edited.as_bytes())?;
+            Ok(true)
+        });
+        
+        drop(dir);
+        result
+    }
+    
+    /// The copy's contents once they pass `check`. Problems are reported
+    /// against `path` rather than the temporary copy.
+    fn edit_until_valid(&self, path: &Path, copy: &Path, check: &impl Fn(&Path) -> Vec<Problem>) -> Result<String> {
+        loop {
+            self.open(copy)?;
+            
+            let mut problems = check(copy);
+            for problem in problems.iter_mut().filter(|problem| problem.file == copy) {
+                problem.file = path.to_path_buf();
+            }
+            validate::print(&problems);
+            if !problems.iter().any(|problem| problem.severity == Severity::Error) {
+                return Ok(fs::read_to_string(copy)?);
+            }
+            
+            if !prompt::confirm("Re-open the editor to fix them?", !prompt::is_non_interactive())? {
+                anyhow::bail!("Discarded the invalid changes");
+            }
+        }
+    }
+}
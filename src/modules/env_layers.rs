@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Where a variable was set, weakest first: a profile's variables win over
+/// a device group's, which win over a global group's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    GlobalGroup,
+    DeviceGroup,
+    Profile,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Layer::GlobalGroup => "group",
+            Layer::DeviceGroup => "device group",
+            Layer::Profile => "profile",
+        })
+    }
+}
+
+/// One definition of a variable: its value, and the layer and group or
+/// profile it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting {
+    pub layer: Layer,
+    pub origin: String,
+    pub value: String,
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} '{}'", self.layer, self.origin)
+    }
+}
+
+/// Every definition of every variable across the enabled groups and the
+/// active profile, in the order they were added.
+#[derive(Debug, Default)]
+pub struct EnvLayers {
+    settings: BTreeMap<String, Vec<Setting>>,
+}
+
+impl EnvLayers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Adds the variables `origin` (a group or profile name) sets on `layer`.
+    pub fn add(&mut self, layer: Layer, origin: &str, variables: &BTreeMap<String, String>) {
+        for (name, value) in variables {
+            self.settings.entry(name.clone()).or_default().push(Setting {
+                layer,
+                origin: origin.to_string(),
+                value: value.clone(),
+            });
+        }
+    }
+    
+    /// Every definition of `name`, the winning one first.
+    pub fn explain(&self, name: &str) -> Vec<&Setting> {
+        let mut settings: Vec<&Setting> = self.settings.get(name).into_iter().flatten().collect();
+        // Stable, so the first definition on a layer stays ahead of later ones.
+        settings.sort_by_key(|setting| std::cmp::Reverse(setting.layer));
+        settings
+    }
+    
+    /// The definition of `name` that takes effect: the one on the strongest
+    /// layer, and on that layer the first.
+    pub fn winner(&self, name: &str) -> Option<&Setting> {
+        self.explain(name).into_iter().next()
+    }
+    
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.settings.keys()
+    }
+    
+    /// Variables set differently on the same layer, where nothing but the
+    /// order of the groups decides. Overrides by a stronger layer are meant
+    /// to happen and aren't reported.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        for name in self.settings.keys() {
+            let settings = self.explain(name);
+            let winner = settings[0];
+            for other in settings.iter().skip(1).filter(|other| other.layer == winner.layer && other.value != winner.value) {
+                conflicts.push(format!(
+                    "'{}' is {} in {} but {} in {}; keeping {}",
+                    name, winner.value, winner, other.value, other, winner.value
+                ));
+            }
+        }
+        conflicts
+    }
+    
+    /// The winning values that groups set. Variables the active profile sets
+    /// are left to the profile's environment, which writes its own; the
+    /// block is rewritten whenever the profile changes.
+    pub fn group_variables(&self) -> BTreeMap<String, String> {
+        self.names()
+            .filter_map(|name| self.winner(name).map(|setting| (name, setting)))
+            .filter(|(_, setting)| setting.layer != Layer::Profile)
+            .map(|(name, setting)| (name.clone(), setting.value.clone()))
+            .collect()
+    }
+}
@@ -1,8 +1,42 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
-use crate::models::EnvironmentState;
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap};
+use crate::models::{Alias, EnvironmentState, ShellFunction, ShellStage};
+use crate::modules::functions;
+use crate::modules::named_dirs;
+use crate::modules::output;
+use crate::modules::preflight;
+use crate::modules::windows_env;
+use crate::modules::zcompile;
+
+/// Managed block holding the named dirs of installed groups.
+pub const NAMED_DIRS_BLOCK: &str = "named-dirs";
+
+/// Managed block loading the shell functions of installed groups.
+pub const FUNCTIONS_BLOCK: &str = "functions";
+
+/// Managed block sourcing the zsh plugins of installed `plugins` groups.
+pub const PLUGINS_BLOCK: &str = "plugins";
+
+/// Managed block with the zsh options and key bindings of installed groups.
+pub const OPTIONS_BLOCK: &str = "options";
+
+/// Managed block in the env file putting group completions on `fpath`.
+pub const COMPLETIONS_BLOCK: &str = "completions";
+
+/// Managed block exporting the variables of installed groups.
+pub const VARIABLES_BLOCK: &str = "variables";
+
+/// Managed block setting up the active theme's prompt.
+pub const THEME_BLOCK: &str = "theme";
+
+/// Managed block with the lazy-load stubs of installed groups.
+pub const LAZY_BLOCK: &str = "lazy";
+
+/// Managed block in `.zshrc` sourcing the compiled bundle of the others.
+const BUNDLE_BLOCK: &str = "bundle";
 
 #[derive(Debug, Clone)]
 pub enum ShellType {
@@ -11,16 +45,66 @@ pub enum ShellType {
     Fish,
     PowerShell,
     Cmd,
+    Nushell,
+}
+
+impl ShellType {
+    /// The name used in alias and function `shells` lists.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShellType::Zsh => "zsh",
+            ShellType::Bash => "bash",
+            ShellType::Fish => "fish",
+            ShellType::PowerShell => "powershell",
+            ShellType::Cmd => "cmd",
+            ShellType::Nushell => "nu",
+        }
+    }
 }
 
 pub struct EnvironmentManager {
     shell_type: ShellType,
+    home: Option<PathBuf>,
+    persist_record: Option<PathBuf>,
+}
+
+impl Default for EnvironmentManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl EnvironmentManager {
     pub fn new() -> Self {
-        let shell_type = Self::detect_shell();
-        Self { shell_type }
+        Self::for_shell(Self::detect_shell())
+    }
+    
+    pub fn for_shell(shell_type: ShellType) -> Self {
+        Self { shell_type, home: None, persist_record: None }
+    }
+    
+    /// Writes startup files below `home` instead of the user's home.
+    pub fn with_home(mut self, home: PathBuf) -> Self {
+        self.home = Some(home);
+        self
+    }
+    
+    /// Also writes profile environments into the Windows user environment,
+    /// keeping what they replace in `record` (see `windows_env::persist`).
+    pub fn with_persistence(mut self, record: PathBuf) -> Self {
+        self.persist_record = Some(record);
+        self
+    }
+    
+    pub fn shell_type(&self) -> &ShellType {
+        &self.shell_type
+    }
+    
+    fn home_dir(&self) -> Result<PathBuf> {
+        match &self.home {
+            Some(home) => Ok(home.clone()),
+            None => preflight::home_dir(),
+        }
     }
     
     fn detect_shell() -> ShellType {
@@ -31,10 +115,12 @@ impl EnvironmentManager {
                 ShellType::Cmd
             }
         } else {
-            match env::var("SHELL").unwrap_or_default().as_str() {
+            match preflight::shell().unwrap_or_default().as_str() {
                 s if s.contains("zsh") => ShellType::Zsh,
                 s if s.contains("bash") => ShellType::Bash,
                 s if s.contains("fish") => ShellType::Fish,
+                s if s.contains("pwsh") || s.contains("powershell") => ShellType::PowerShell,
+                s if Path::new(s).file_name().is_some_and(|name| name == "nu") => ShellType::Nushell,
                 _ => ShellType::Bash,
             }
         }
@@ -53,6 +139,12 @@ impl EnvironmentManager {
             env::set_var(key, value);
         }
         
+        // Setting them in-process does nothing for programs started later
+        if let Some(record) = &self.persist_record {
+            let home = self.home_dir()?;
+            windows_env::persist(record, env_state, |path| expand_windows_path(path, &home))?;
+        }
+        
         Ok(())
     }
     
@@ -66,6 +158,10 @@ impl EnvironmentManager {
             env::remove_var(key);
         }
         
+        if let Some(record) = &self.persist_record {
+            windows_env::restore(record)?;
+        }
+        
         Ok(())
     }
     
@@ -75,77 +171,145 @@ impl EnvironmentManager {
             ShellType::Fish => self.generate_fish_config(env_state),
             ShellType::PowerShell => self.generate_powershell_config(env_state),
             ShellType::Cmd => self.generate_cmd_config(env_state),
+            ShellType::Nushell => self.generate_nushell_config(env_state),
         }
     }
     
+    /// Renders the environment into managed blocks of the startup files for
+    /// the current shell, one block per stage (see `get_stage_config_path`).
+    /// Stages with nothing to render have their block removed.
     pub fn write_shell_config(&self, env_state: &EnvironmentState) -> Result<()> {
-        let config = self.generate_shell_config(env_state)?;
-        let config_path = self.get_profile_env_path()?;
-        
-        // Create parent directory if needed
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)?;
+        self.require_shell()?;
+        self.migrate_profile_env()?;
+        for stage in ShellStage::ALL {
+            let staged = env_state.for_stage(stage);
+            let content = if staged.is_empty() {
+                String::new()
+            } else {
+                self.generate_shell_config(&staged)?
+            };
+            
+            let path = self.get_stage_config_path(stage)?;
+            self.write_managed_block(&path, stage.as_str(), &content)?;
         }
         
-        fs::write(&config_path, config)?;
+        Ok(())
+    }
+    
+    /// Removes every zshrcman managed block from the current shell's startup files.
+    pub fn clear_shell_config(&self) -> Result<()> {
+        self.require_shell()?;
+        self.migrate_profile_env()?;
+        for stage in ShellStage::ALL {
+            let path = self.get_stage_config_path(stage)?;
+            self.write_managed_block(&path, stage.as_str(), "")?;
+        }
+        Ok(())
+    }
+    
+    /// Older releases wrote the environment to `profile.env` and sourced it
+    /// from the rc file; drop that source line and the file so the variables
+    /// aren't set twice next to the managed blocks.
+    fn migrate_profile_env(&self) -> Result<()> {
+        let legacy = self.home_dir()?
+            .join(".local")
+            .join("share")
+            .join("zshrcman")
+            .join("env")
+            .join("profile.env");
+        let legacy_str = legacy.to_string_lossy().to_string();
         
-        // Source the config in the main shell config file
-        self.add_source_line(&config_path)?;
+        let shell_config = self.get_shell_config_path()?;
+        if shell_config.exists() {
+            let existing = fs::read_to_string(&shell_config)?;
+            let lines: Vec<&str> = existing.lines().collect();
+            let mut kept: Vec<&str> = Vec::new();
+            let mut i = 0;
+            while i < lines.len() {
+                if lines[i] == "# zshrcman environment"
+                    && lines.get(i + 1).is_some_and(|next| next.contains(legacy_str.as_str()))
+                {
+                    // The source line was appended after a blank separator
+                    if kept.last().is_some_and(|line| line.is_empty()) {
+                        kept.pop();
+                    }
+                    i += 2;
+                    continue;
+                }
+                kept.push(lines[i]);
+                i += 1;
+            }
+            
+            if kept.len() != lines.len() {
+                let mut updated = kept.join("\n");
+                if !updated.is_empty() {
+                    updated.push('\n');
+                }
+                fs::write(&shell_config, updated)?;
+                output::info(format!("Removed the old profile.env source line from {}", shell_config.display()));
+            }
+        }
         
+        if legacy.exists() {
+            fs::remove_file(&legacy)?;
+        }
+        Ok(())
+    }
+    
+    /// Without SHELL we'd be guessing which startup files to write.
+    fn require_shell(&self) -> Result<()> {
+        if !cfg!(windows) {
+            preflight::shell()?;
+        }
         Ok(())
     }
     
     fn apply_path_changes(&self, env_state: &EnvironmentState) -> Result<()> {
-        let mut current_path = env::var("PATH").unwrap_or_default();
+        let current_path = env::var_os("PATH").unwrap_or_default();
+        let mut paths: Vec<PathBuf> = env::split_paths(&current_path).collect();
         
         // Prepend paths
         for path in &env_state.paths_prepend {
-            let expanded = self.expand_path(path)?;
-            if !current_path.contains(&expanded) {
-                current_path = format!("{}:{}", expanded, current_path);
+            let expanded = PathBuf::from(self.expand_path(path)?);
+            if !paths.contains(&expanded) {
+                paths.insert(0, expanded);
             }
         }
         
         // Append paths
         for path in &env_state.paths_append {
-            let expanded = self.expand_path(path)?;
-            if !current_path.contains(&expanded) {
-                current_path = format!("{}:{}", current_path, expanded);
+            let expanded = PathBuf::from(self.expand_path(path)?);
+            if !paths.contains(&expanded) {
+                paths.push(expanded);
             }
         }
         
-        env::set_var("PATH", current_path);
+        env::set_var("PATH", env::join_paths(paths)?);
         Ok(())
     }
     
     fn remove_path_changes(&self, env_state: &EnvironmentState) -> Result<()> {
-        let current_path = env::var("PATH").unwrap_or_default();
-        let mut paths: Vec<String> = current_path.split(':').map(|s| s.to_string()).collect();
+        let current_path = env::var_os("PATH").unwrap_or_default();
+        let mut paths: Vec<PathBuf> = env::split_paths(&current_path).collect();
         
-        // Remove prepended paths
-        for path in &env_state.paths_prepend {
-            let expanded = self.expand_path(path)?;
-            paths.retain(|p| p != &expanded);
-        }
-        
-        // Remove appended paths
-        for path in &env_state.paths_append {
-            let expanded = self.expand_path(path)?;
+        // Remove prepended and appended paths
+        for path in env_state.paths_prepend.iter().chain(&env_state.paths_append) {
+            let expanded = PathBuf::from(self.expand_path(path)?);
             paths.retain(|p| p != &expanded);
         }
         
-        env::set_var("PATH", paths.join(":"));
+        env::set_var("PATH", env::join_paths(paths)?);
         Ok(())
     }
     
     fn expand_path(&self, path: &str) -> Result<String> {
         // Expand environment variables and tilde
         let expanded = if path.starts_with("~/") {
-            let home = env::var("HOME").context("HOME not set")?;
-            path.replacen("~", &home, 1)
+            let home = self.home_dir()?;
+            path.replacen("~", &home.to_string_lossy(), 1)
         } else if path.starts_with("$HOME") {
-            let home = env::var("HOME").context("HOME not set")?;
-            path.replacen("$HOME", &home, 1)
+            let home = self.home_dir()?;
+            path.replacen("$HOME", &home.to_string_lossy(), 1)
         } else {
             path.to_string()
         };
@@ -160,11 +324,11 @@ impl EnvironmentManager {
         
         // PATH modifications
         for path in &env_state.paths_prepend {
-            script.push_str(&format!("export PATH=\"{}:$PATH\"\n", path));
+            script.push_str(&format!("export PATH={}:\"$PATH\"\n", quote(&self.shell_type, path)));
         }
         
         for path in &env_state.paths_append {
-            script.push_str(&format!("export PATH=\"$PATH:{}\"\n", path));
+            script.push_str(&format!("export PATH=\"$PATH\":{}\n", quote(&self.shell_type, path)));
         }
         
         if !env_state.paths_prepend.is_empty() || !env_state.paths_append.is_empty() {
@@ -172,17 +336,19 @@ impl EnvironmentManager {
         }
         
         // Environment variables
-        for (key, value) in &env_state.variables {
-            script.push_str(&format!("export {}=\"{}\"\n", key, value));
-        }
+        script.push_str(&self.variables_config(&env_state.variables));
+        script.push_str(&self.secrets_config(&env_state.secrets));
         
-        if !env_state.variables.is_empty() {
+        if !env_state.variables.is_empty() || !env_state.secrets.is_empty() {
             script.push('\n');
         }
         
         // Aliases
-        for (alias, command) in &env_state.aliases {
-            script.push_str(&format!("alias {}='{}'\n", alias, command));
+        script.push_str(&alias_lines(env_state, "bash"));
+        
+        if !env_state.named_dirs.is_empty() {
+            script.push('\n');
+            script.push_str(&self.named_dirs_config(&env_state.named_dirs));
         }
         
         Ok(script)
@@ -195,11 +361,11 @@ impl EnvironmentManager {
         
         // PATH modifications
         for path in &env_state.paths_prepend {
-            script.push_str(&format!("set -gx PATH {} $PATH\n", path));
+            script.push_str(&format!("set -gx PATH {} $PATH\n", quote(&self.shell_type, path)));
         }
         
         for path in &env_state.paths_append {
-            script.push_str(&format!("set -gx PATH $PATH {}\n", path));
+            script.push_str(&format!("set -gx PATH $PATH {}\n", quote(&self.shell_type, path)));
         }
         
         if !env_state.paths_prepend.is_empty() || !env_state.paths_append.is_empty() {
@@ -207,17 +373,19 @@ impl EnvironmentManager {
         }
         
         // Environment variables
-        for (key, value) in &env_state.variables {
-            script.push_str(&format!("set -gx {} \"{}\"\n", key, value));
-        }
+        script.push_str(&self.variables_config(&env_state.variables));
+        script.push_str(&self.secrets_config(&env_state.secrets));
         
-        if !env_state.variables.is_empty() {
+        if !env_state.variables.is_empty() || !env_state.secrets.is_empty() {
             script.push('\n');
         }
         
         // Aliases
-        for (alias, command) in &env_state.aliases {
-            script.push_str(&format!("alias {} '{}'\n", alias, command));
+        script.push_str(&alias_lines(env_state, "fish"));
+        
+        if !env_state.named_dirs.is_empty() {
+            script.push('\n');
+            script.push_str(&self.named_dirs_config(&env_state.named_dirs));
         }
         
         Ok(script)
@@ -233,30 +401,32 @@ impl EnvironmentManager {
             script.push_str("$env:Path = @(");
             
             for path in &env_state.paths_prepend {
-                script.push_str(&format!("\n    \"{}\",", path));
+                script.push_str(&format!("\n    {},", quote(&self.shell_type, path)));
             }
             
             script.push_str("\n    $env:Path");
             
             for path in &env_state.paths_append {
-                script.push_str(&format!(",\n    \"{}\"", path));
+                script.push_str(&format!(",\n    {}", quote(&self.shell_type, path)));
             }
             
-            script.push_str("\n) -join ';'\n\n");
+            script.push_str("\n) -join [IO.Path]::PathSeparator\n\n");
         }
         
         // Environment variables
-        for (key, value) in &env_state.variables {
-            script.push_str(&format!("$env:{} = \"{}\"\n", key, value));
-        }
+        script.push_str(&self.variables_config(&env_state.variables));
+        script.push_str(&self.secrets_config(&env_state.secrets));
         
-        if !env_state.variables.is_empty() {
+        if !env_state.variables.is_empty() || !env_state.secrets.is_empty() {
             script.push('\n');
         }
         
         // Aliases (functions in PowerShell)
-        for (alias, command) in &env_state.aliases {
-            script.push_str(&format!("function {} {{ {} }}\n", alias, command));
+        script.push_str(&alias_lines(env_state, "powershell"));
+        
+        if !env_state.named_dirs.is_empty() {
+            script.push('\n');
+            script.push_str(&self.named_dirs_config(&env_state.named_dirs));
         }
         
         Ok(script)
@@ -269,102 +439,438 @@ impl EnvironmentManager {
         
         // PATH modifications
         if !env_state.paths_prepend.is_empty() || !env_state.paths_append.is_empty() {
-            script.push_str("set PATH=");
+            script.push_str("set \"PATH=");
             
             for path in &env_state.paths_prepend {
-                script.push_str(&format!("{};", path));
+                script.push_str(&format!("{};", quote(&self.shell_type, path)));
             }
             
             script.push_str("%PATH%");
             
             for path in &env_state.paths_append {
-                script.push_str(&format!(";{}", path));
+                script.push_str(&format!(";{}", quote(&self.shell_type, path)));
             }
             
-            script.push_str("\n\n");
+            script.push_str("\"\n\n");
         }
         
         // Environment variables
-        for (key, value) in &env_state.variables {
-            script.push_str(&format!("set {}={}\n", key, value));
-        }
+        script.push_str(&self.variables_config(&env_state.variables));
+        script.push_str(&self.secrets_config(&env_state.secrets));
         
-        if !env_state.variables.is_empty() {
+        if !env_state.variables.is_empty() || !env_state.secrets.is_empty() {
             script.push('\n');
         }
         
         // Note: CMD doesn't support aliases directly
         if !env_state.aliases.is_empty() {
             script.push_str("REM Aliases not supported in CMD batch files\n");
-            for (alias, command) in &env_state.aliases {
-                script.push_str(&format!("REM {} = {}\n", alias, command));
-            }
+            script.push_str(&alias_lines(env_state, "cmd"));
+        }
+        
+        if !env_state.named_dirs.is_empty() {
+            script.push('\n');
+            script.push_str(&self.named_dirs_config(&env_state.named_dirs));
         }
         
         Ok(script)
     }
     
-    fn get_profile_env_path(&self) -> Result<PathBuf> {
-        let home = env::var("HOME").unwrap_or_else(|_| {
-            env::var("USERPROFILE").unwrap_or_else(|_| ".".to_string())
-        });
+    fn generate_nushell_config(&self, env_state: &EnvironmentState) -> Result<String> {
+        let mut script = String::new();
         
-        Ok(PathBuf::from(home)
-            .join(".local")
-            .join("share")
-            .join("zshrcman")
-            .join("env")
-            .join("profile.env"))
-    }
-    
-    fn add_source_line(&self, env_path: &PathBuf) -> Result<()> {
-        let shell_config = self.get_shell_config_path()?;
-        let env_path_str = env_path.to_string_lossy();
+        script.push_str("# zshrcman profile environment\n\n");
         
-        let source_line = match self.shell_type {
-            ShellType::Zsh | ShellType::Bash => {
-                format!("[ -f {} ] && source {}", env_path_str, env_path_str)
-            }
-            ShellType::Fish => {
-                format!("test -f {}; and source {}", env_path_str, env_path_str)
+        // PATH modifications; nushell keeps PATH as a list
+        if !env_state.paths_prepend.is_empty() || !env_state.paths_append.is_empty() {
+            let list = |paths: &[String]| paths.iter().map(|path| quote(&self.shell_type, path)).collect::<Vec<_>>().join(", ");
+            script.push_str("$env.PATH = ($env.PATH | split row (char esep)");
+            if !env_state.paths_prepend.is_empty() {
+                script.push_str(&format!(" | prepend [{}]", list(&env_state.paths_prepend)));
             }
-            ShellType::PowerShell => {
-                format!(". \"{}\"", env_path_str)
+            if !env_state.paths_append.is_empty() {
+                script.push_str(&format!(" | append [{}]", list(&env_state.paths_append)));
             }
-            ShellType::Cmd => {
-                return Ok(()); // CMD doesn't have a persistent config file like shells
+            script.push_str(")\n\n");
+        }
+        
+        // Environment variables
+        script.push_str(&self.variables_config(&env_state.variables));
+        script.push_str(&self.secrets_config(&env_state.secrets));
+        
+        if !env_state.variables.is_empty() || !env_state.secrets.is_empty() {
+            script.push('\n');
+        }
+        
+        // Aliases
+        script.push_str(&alias_lines(env_state, "nu"));
+        
+        if !env_state.named_dirs.is_empty() {
+            script.push('\n');
+            script.push_str(&self.named_dirs_config(&env_state.named_dirs));
+        }
+        
+        Ok(script)
+    }
+    
+    /// `variables` exported in the current shell's syntax, sorted by name.
+    pub fn variables_config<'a>(&self, variables: impl IntoIterator<Item = (&'a String, &'a String)>) -> String {
+        let mut variables: Vec<_> = variables.into_iter().collect();
+        variables.sort();
+        variables
+            .into_iter()
+            .map(|(key, value)| {
+                let value = quote(&self.shell_type, value);
+                match self.shell_type {
+                    ShellType::Zsh | ShellType::Bash => format!("export {}={}\n", key, value),
+                    ShellType::Fish => format!("set -gx {} {}\n", key, value),
+                    ShellType::PowerShell => format!("$env:{} = {}\n", key, value),
+                    ShellType::Cmd => format!("set \"{}={}\"\n", key, value),
+                    ShellType::Nushell => format!("$env.{} = {}\n", key, value),
+                }
+            })
+            .collect()
+    }
+    
+    /// Variables set from `secrets` (name to reference) when the shell
+    /// starts, by asking `zshrcman env secret`, so the values never end up in
+    /// the startup file.
+    pub fn secrets_config(&self, secrets: &HashMap<String, String>) -> String {
+        let mut secrets: Vec<_> = secrets.iter().collect();
+        secrets.sort();
+        secrets
+            .into_iter()
+            .map(|(key, reference)| secret_line(&self.shell_type, key, reference) + "\n")
+            .collect()
+    }
+    
+    /// Named dirs for the current shell: `hash -d` in zsh, and variables
+    /// elsewhere (with `cdable_vars` in bash, so `cd proj` works there too).
+    pub fn named_dirs_config(&self, named_dirs: &BTreeMap<String, String>) -> String {
+        let mut script = String::new();
+        if let ShellType::Bash = self.shell_type {
+            script.push_str("shopt -s cdable_vars\n");
+        }
+        
+        for (name, path) in named_dirs {
+            if named_dirs::is_reserved(name) {
+                output::warn(format!("⚠️  Skipping named dir '{}': it would overwrite the shell's ${}", name, name));
+                continue;
             }
+            let path = quote(&self.shell_type, path);
+            let line = match self.shell_type {
+                ShellType::Zsh => format!("hash -d {}={}", name, path),
+                ShellType::Bash => format!("{}={}", name, path),
+                ShellType::Fish => format!("set -g {} {}", name, path),
+                ShellType::PowerShell => format!("$global:{} = {}", name, path),
+                ShellType::Cmd => format!("set \"{}={}\"", name, path),
+                ShellType::Nushell => format!("$env.{} = {}", name, path),
+            };
+            script.push_str(&line);
+            script.push('\n');
+        }
+        script
+    }
+    
+    /// Whether the rc file has a named dirs block to update or remove.
+    pub fn has_named_dirs_block(&self) -> Result<bool> {
+        self.has_block(NAMED_DIRS_BLOCK)
+    }
+    
+    /// Whether the rc file has a variables block to update or remove.
+    pub fn has_variables_block(&self) -> Result<bool> {
+        self.has_block(VARIABLES_BLOCK)
+    }
+    
+    /// Whether the rc file has a functions block to update or remove.
+    pub fn has_functions_block(&self) -> Result<bool> {
+        self.has_block(FUNCTIONS_BLOCK)
+    }
+    
+    /// Whether the rc file has a plugins block to update or remove.
+    pub fn has_plugins_block(&self) -> Result<bool> {
+        self.has_block(PLUGINS_BLOCK)
+    }
+    
+    /// Whether the rc file has an options block to update or remove.
+    pub fn has_options_block(&self) -> Result<bool> {
+        self.has_block(OPTIONS_BLOCK)
+    }
+    
+    /// Whether the rc file has a theme block to update or remove.
+    pub fn has_theme_block(&self) -> Result<bool> {
+        self.has_block(THEME_BLOCK)
+    }
+    
+    /// Whether the rc file has a lazy-loading block to update or remove.
+    pub fn has_lazy_block(&self) -> Result<bool> {
+        self.has_block(LAZY_BLOCK)
+    }
+    
+    /// Whether the env file (`.zshenv` for zsh) has a completions block to
+    /// update or remove.
+    pub fn has_completions_block(&self) -> Result<bool> {
+        let (begin, _) = self.block_markers(COMPLETIONS_BLOCK);
+        let path = self.get_stage_config_path(ShellStage::Env)?;
+        Ok(fs::read_to_string(path).is_ok_and(|contents| contents.lines().any(|line| line == begin)))
+    }
+    
+    fn has_block(&self, label: &str) -> Result<bool> {
+        let (begin, _) = self.block_markers(label);
+        let path = self.block_path()?;
+        Ok(fs::read_to_string(path).is_ok_and(|contents| contents.lines().any(|line| line == begin)))
+    }
+    
+    /// Renders the named dirs of installed groups into their own managed
+    /// block of the rc file, or removes the block when there are none.
+    pub fn write_named_dirs(&self, named_dirs: &BTreeMap<String, String>) -> Result<()> {
+        self.require_shell()?;
+        let path = self.block_path()?;
+        self.write_managed_block(&path, NAMED_DIRS_BLOCK, &self.named_dirs_block(named_dirs))
+    }
+    
+    /// What [`write_named_dirs`](Self::write_named_dirs) puts in the block.
+    pub fn named_dirs_block(&self, named_dirs: &BTreeMap<String, String>) -> String {
+        if named_dirs.is_empty() {
+            String::new()
+        } else {
+            format!("# zshrcman named directories\n\n{}", self.named_dirs_config(named_dirs))
+        }
+    }
+    
+    /// Renders the variables of installed groups into their own managed
+    /// block of the rc file, or removes the block when there are none.
+    pub fn write_variables(&self, variables: &BTreeMap<String, String>) -> Result<()> {
+        self.require_shell()?;
+        let path = self.block_path()?;
+        self.write_managed_block(&path, VARIABLES_BLOCK, &self.variables_block(variables))
+    }
+    
+    /// What [`write_variables`](Self::write_variables) puts in the block.
+    pub fn variables_block(&self, variables: &BTreeMap<String, String>) -> String {
+        if variables.is_empty() {
+            String::new()
+        } else {
+            format!("# zshrcman group variables\n\n{}", self.variables_config(variables))
+        }
+    }
+    
+    /// The content of the managed block `label` (one of the `*_BLOCK`
+    /// labels), or `None` when the file doesn't have it.
+    pub fn read_block(&self, label: &str) -> Result<Option<String>> {
+        let path = if label == COMPLETIONS_BLOCK {
+            self.get_stage_config_path(ShellStage::Env)?
+        } else {
+            self.block_path()?
+        };
+        let (begin, end) = self.block_markers(label);
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Ok(None);
         };
         
-        // Check if source line already exists
-        if shell_config.exists() {
-            let content = fs::read_to_string(&shell_config)?;
-            if content.contains(&source_line) {
-                return Ok(());
+        let mut lines = contents.lines().skip_while(|line| *line != begin);
+        if lines.next().is_none() {
+            return Ok(None);
+        }
+        let mut block = String::new();
+        for line in lines.take_while(|line| *line != end) {
+            block.push_str(line);
+            block.push('\n');
+        }
+        Ok(Some(block))
+    }
+    
+    /// Loads the functions of installed groups from their own managed block
+    /// of the rc file, or removes the block when there are none.
+    pub fn write_functions(&self, functions: &[ShellFunction]) -> Result<()> {
+        self.require_shell()?;
+        let content = functions::loader(functions, self.shell_type.name());
+        let path = self.block_path()?;
+        self.write_managed_block(&path, FUNCTIONS_BLOCK, &content)
+    }
+    
+    /// Replaces the plugins block of the rc file with `content` (see
+    /// `zsh_plugins::source_lines`), or removes it when that's empty.
+    pub fn write_plugins(&self, content: &str) -> Result<()> {
+        let path = self.block_path()?;
+        self.write_managed_block(&path, PLUGINS_BLOCK, content)
+    }
+    
+    /// Replaces the options block of the rc file with `content` (see
+    /// `ZshOptions::render`), or removes it when that's empty.
+    pub fn write_options(&self, content: &str) -> Result<()> {
+        let path = self.block_path()?;
+        self.write_managed_block(&path, OPTIONS_BLOCK, content)
+    }
+    
+    /// Replaces the completions block of the env file with `content` (see
+    /// `completions::fpath_lines`), or removes it when that's empty.
+    pub fn write_completions(&self, content: &str) -> Result<()> {
+        let path = self.get_stage_config_path(ShellStage::Env)?;
+        self.write_managed_block(&path, COMPLETIONS_BLOCK, content)
+    }
+    
+    /// Replaces the theme block of the rc file with `content` (see
+    /// `theme::prompt_lines`), or removes it when that's empty.
+    pub fn write_theme(&self, content: &str) -> Result<()> {
+        let path = self.block_path()?;
+        self.write_managed_block(&path, THEME_BLOCK, content)
+    }
+    
+    /// Replaces the lazy-loading block of the rc file with `content` (see
+    /// `LazyLoads::render`), or removes it when that's empty.
+    pub fn write_lazy(&self, content: &str) -> Result<()> {
+        let path = self.block_path()?;
+        self.write_managed_block(&path, LAZY_BLOCK, content)
+    }
+    
+    /// Moves the managed blocks of `.zshrc` into the bundle file, compiles
+    /// it and sources it from a single block where the first one was; or,
+    /// with `compile` off, moves them back and removes the bundle. Later
+    /// block writes go to the bundle while it exists. Returns whether
+    /// anything changed.
+    pub fn set_compiled(&self, compile: bool) -> Result<bool> {
+        let bundle = zcompile::bundle_path(&self.home_dir()?);
+        if compile == bundle.exists() {
+            return Ok(false);
+        }
+        
+        let rc = self.get_shell_config_path()?;
+        let (begin, end) = self.block_markers(BUNDLE_BLOCK);
+        if compile {
+            self.write_managed_block(&rc, BUNDLE_BLOCK, "")?;
+            let content = fs::read_to_string(&rc).unwrap_or_default();
+            let source_block = format!("{}\n{}{}\n", begin, zcompile::source_lines(), end);
+            let Some((kept, blocks)) = zcompile::bundle(&content, &source_block) else {
+                return Ok(false);
+            };
+            if let Some(parent) = bundle.parent() {
+                fs::create_dir_all(parent)?;
             }
+            fs::write(&bundle, blocks)?;
+            fs::write(&rc, kept)?;
+            self.compile_bundle(&bundle);
+        } else {
+            let blocks = fs::read_to_string(&bundle)?;
+            let content = fs::read_to_string(&rc).unwrap_or_default();
+            fs::write(&rc, zcompile::unbundle(&content, &begin, &end, &blocks))?;
+            fs::remove_file(&bundle)?;
+            let _ = fs::remove_file(bundle.with_extension("zsh.zwc"));
         }
-        
-        // Add source line
-        let mut content = if shell_config.exists() {
-            fs::read_to_string(&shell_config)?
+        Ok(true)
+    }
+    
+    /// A failed compile leaves an older `.zwc` behind, which zsh ignores
+    /// in favor of the newer source; it's removed anyway to avoid doubt.
+    fn compile_bundle(&self, bundle: &Path) {
+        if zcompile::compile(bundle).is_err() {
+            let _ = fs::remove_file(bundle.with_extension("zsh.zwc"));
+        }
+    }
+    
+    /// Where the rc file's managed blocks live: the zsh bundle while
+    /// `[zsh] compile` has it in place, the rc file otherwise.
+    fn block_path(&self) -> Result<PathBuf> {
+        if matches!(self.shell_type, ShellType::Zsh) {
+            let bundle = zcompile::bundle_path(&self.home_dir()?);
+            if bundle.exists() {
+                return Ok(bundle);
+            }
+        }
+        self.get_shell_config_path()
+    }
+    
+    fn comment_prefix(&self) -> &'static str {
+        match self.shell_type {
+            ShellType::Cmd => "REM",
+            _ => "#",
+        }
+    }
+    
+    fn block_markers(&self, label: &str) -> (String, String) {
+        let prefix = self.comment_prefix();
+        (
+            format!("{} >>> zshrcman {} >>>", prefix, label),
+            format!("{} <<< zshrcman {} <<<", prefix, label),
+        )
+    }
+    
+    /// Replaces (or appends, or removes when `content` is empty) the managed
+    /// block `label` (a stage name, or `named-dirs`) in `path`, leaving
+    /// everything outside it untouched.
+    fn write_managed_block(&self, path: &Path, label: &str, content: &str) -> Result<()> {
+        let (begin, end) = self.block_markers(label);
+        
+        let existing = if path.exists() {
+            fs::read_to_string(path)?
+        } else if content.is_empty() {
+            return Ok(());
         } else {
             String::new()
         };
         
-        if !content.ends_with('\n') && !content.is_empty() {
-            content.push('\n');
+        let mut kept = Vec::new();
+        let mut in_block = false;
+        for line in existing.lines() {
+            if line == begin {
+                in_block = true;
+            } else if line == end {
+                in_block = false;
+            } else if !in_block {
+                kept.push(line);
+            }
         }
         
-        content.push_str(&format!("\n# zshrcman environment\n{}\n", source_line));
+        let mut updated = kept.join("\n");
+        if !content.is_empty() {
+            if !updated.is_empty() && !updated.ends_with('\n') {
+                updated.push('\n');
+            }
+            updated.push_str(&format!("{}\n{}", begin, content));
+            if !content.ends_with('\n') {
+                updated.push('\n');
+            }
+            updated.push_str(&end);
+        }
+        if !updated.is_empty() {
+            updated.push('\n');
+        }
+        
+        if updated == existing {
+            return Ok(());
+        }
         
-        fs::write(&shell_config, content)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, updated)?;
+        if path.ends_with(zcompile::BUNDLE_FILE) {
+            self.compile_bundle(path);
+        }
         Ok(())
     }
     
+    /// Startup file for a stage. Shells without a separate env/login file
+    /// (fish, PowerShell, cmd) keep all stages in their single config file;
+    /// nushell's go into `env.nu` next to its `config.nu`.
+    fn get_stage_config_path(&self, stage: ShellStage) -> Result<PathBuf> {
+        let home = self.home_dir()?;
+        
+        let config_file = match (&self.shell_type, stage) {
+            (ShellType::Nushell, ShellStage::Env | ShellStage::Profile) => {
+                return Ok(self.get_shell_config_path()?.with_file_name("env.nu"));
+            }
+            (ShellType::Zsh, ShellStage::Env) => ".zshenv",
+            (ShellType::Zsh, ShellStage::Profile) => ".zprofile",
+            (ShellType::Bash, ShellStage::Env | ShellStage::Profile) => ".bash_profile",
+            _ => return self.block_path(),
+        };
+        
+        Ok(home.join(config_file))
+    }
+    
     fn get_shell_config_path(&self) -> Result<PathBuf> {
-        let home = env::var("HOME").unwrap_or_else(|_| {
-            env::var("USERPROFILE").unwrap_or_else(|_| ".".to_string())
-        });
+        let home = self.home_dir()?;
         
         let config_file = match self.shell_type {
             ShellType::Zsh => ".zshrc",
@@ -378,8 +884,95 @@ impl EnvironmentManager {
                 }
             }
             ShellType::Cmd => "zshrcman_env.bat",
+            ShellType::Nushell => {
+                if cfg!(windows) {
+                    "AppData/Roaming/nushell/config.nu"
+                } else if cfg!(target_os = "macos") {
+                    "Library/Application Support/nushell/config.nu"
+                } else {
+                    ".config/nushell/config.nu"
+                }
+            }
         };
         
-        Ok(PathBuf::from(home).join(config_file))
+        Ok(home.join(config_file))
+    }
+}
+
+/// The statement setting `name` to what `zshrcman env secret` prints for
+/// `reference`.
+pub(crate) fn secret_line(shell: &ShellType, name: &str, reference: &str) -> String {
+    let reference = quote(shell, reference);
+    match shell {
+        ShellType::Zsh | ShellType::Bash => format!("export {}=\"$(zshrcman env secret {})\"", name, reference),
+        ShellType::Fish => format!("set -gx {} (zshrcman env secret {} | string collect)", name, reference),
+        ShellType::PowerShell => format!("$env:{} = (zshrcman env secret {}) -join \"`n\"", name, reference),
+        ShellType::Cmd => format!("for /f \"usebackq delims=\" %%v in (`zshrcman env secret \"{}\"`) do set \"{}=%%v\"", reference, name),
+        ShellType::Nushell => format!("$env.{} = (^zshrcman env secret {})", name, reference),
+    }
+}
+
+/// What follows a leading `~` or `$HOME` in `value`, if it starts with one.
+fn home_relative(value: &str) -> Option<&str> {
+    value
+        .strip_prefix('~')
+        .or_else(|| value.strip_prefix("$HOME"))
+        .filter(|rest| rest.is_empty() || rest.starts_with(['/', '\\']))
+}
+
+/// `path` with a leading `~` or `$HOME` replaced by `home`, for the
+/// registry, which knows neither.
+fn expand_windows_path(path: &str, home: &Path) -> String {
+    match home_relative(path) {
+        Some(rest) => format!("{}{}", home.display(), rest.replace('/', "\\")),
+        None => path.to_string(),
     }
+}
+
+/// `value` as a string literal for `shell`, so quotes, `$` and the like in
+/// it are taken literally. A leading `~` or `$HOME` is the exception and
+/// becomes the shell's home directory. Cmd's values go unquoted into a
+/// `set "NAME=value"`, which only needs `%` doubled.
+pub(crate) fn quote(shell: &ShellType, value: &str) -> String {
+    let escape = |value: &str, special: &[char], escape: char| {
+        value.chars().fold(String::new(), |mut escaped, c| {
+            if special.contains(&c) {
+                escaped.push(escape);
+            }
+            escaped.push(c);
+            escaped
+        })
+    };
+    let (home, rest) = match home_relative(value) {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    match shell {
+        ShellType::Zsh | ShellType::Bash | ShellType::Fish => {
+            let special: &[char] = if matches!(shell, ShellType::Fish) { &['\\', '"', '$'] } else { &['\\', '"', '$', '`'] };
+            format!("\"{}{}\"", if home { "$HOME" } else { "" }, escape(rest, special, '\\'))
+        }
+        ShellType::PowerShell => format!("\"{}{}\"", if home { "$HOME" } else { "" }, escape(rest, &['`', '"', '$'], '`')),
+        ShellType::Cmd => format!("{}{}", if home { "%USERPROFILE%" } else { "" }, rest.replace('%', "%%")),
+        ShellType::Nushell if home => format!("$\"($env.HOME){}\"", escape(rest, &['\\', '"', '('], '\\')),
+        ShellType::Nushell => format!("\"{}\"", escape(rest, &['\\', '"'], '\\')),
+    }
+}
+
+/// The profile's aliases in `shell`'s syntax, one per line, sorted by name.
+/// Warns about the ones nu can't define.
+pub(crate) fn alias_lines(env_state: &EnvironmentState, shell: &str) -> String {
+    let mut aliases: Vec<_> = env_state.aliases.iter().collect();
+    aliases.sort();
+    aliases
+        .into_iter()
+        .map(|(name, command)| Alias::new(name.as_str(), command.as_str()))
+        .inspect(|alias| {
+            if shell == "nu" && alias.chains_commands() {
+                output::warn(format!("⚠️  Alias '{}' chains commands, which nu aliases can't; skipped for nu", alias.name));
+            }
+        })
+        .filter_map(|alias| alias.render(shell))
+        .map(|line| line + "\n")
+        .collect()
 }
\ No newline at end of file
@@ -0,0 +1,152 @@
+use std::error::Error;
+use std::fmt;
+
+/// What kind of failure ended a run. Each has its own exit code so scripts
+/// wrapping zshrcman can tell them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Anything not classified below.
+    Other,
+    /// config.toml or a group file is unreadable or invalid.
+    Config,
+    /// A git operation or the network failed: clone, fetch, push, a sync
+    /// that didn't rebase cleanly, or working offline.
+    Git,
+    /// A package manager failed to install or remove packages.
+    Installer,
+    /// The user declined to go on or interrupted a prompt.
+    Aborted,
+    /// Some groups or items succeeded and others failed.
+    Partial,
+    /// `verify` found deployed items that differ from the config.
+    Drift,
+    /// An external `zshrcman-<name>` command exited non-zero or was killed.
+    /// zshrcman always exits with 8 then, so a command's own code can't be
+    /// mistaken for one of the kinds above; that code is only in the error
+    /// message (see [`exited`]).
+    External,
+}
+
+impl ErrorKind {
+    pub const ALL: [ErrorKind; 8] = [
+        ErrorKind::Other,
+        ErrorKind::Config,
+        ErrorKind::Git,
+        ErrorKind::Installer,
+        ErrorKind::Aborted,
+        ErrorKind::Partial,
+        ErrorKind::Drift,
+        ErrorKind::External,
+    ];
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Config => 2,
+            ErrorKind::Git => 3,
+            ErrorKind::Installer => 4,
+            ErrorKind::Aborted => 5,
+            ErrorKind::Partial => 6,
+            ErrorKind::Drift => 7,
+            ErrorKind::External => 8,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Other => "other",
+            ErrorKind::Config => "config",
+            ErrorKind::Git => "git",
+            ErrorKind::Installer => "installer",
+            ErrorKind::Aborted => "aborted",
+            ErrorKind::Partial => "partial",
+            ErrorKind::Drift => "drift",
+            ErrorKind::External => "external",
+        }
+    }
+}
+
+/// An error tagged with its kind. It prints exactly like the error it wraps,
+/// so tagging never changes what the user sees.
+#[derive(Debug)]
+pub struct Classified {
+    pub kind: ErrorKind,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Classified {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.error, f)
+    }
+}
+
+impl Error for Classified {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Tags `error` with `kind`. An error that already has a kind keeps it, as
+/// the code closest to the failure knows best.
+pub fn tag(kind: ErrorKind, error: anyhow::Error) -> anyhow::Error {
+    if error.downcast_ref::<Classified>().is_some() {
+        return error;
+    }
+    anyhow::Error::new(Classified { kind, error })
+}
+
+/// A new error of `kind`.
+pub fn fail(kind: ErrorKind, message: impl fmt::Display) -> anyhow::Error {
+    tag(kind, anyhow::anyhow!("{}", message))
+}
+
+/// `program` exiting with `code` (`None` when killed by a signal), as an
+/// `External` error naming the code.
+pub fn exited(program: &str, code: Option<i32>) -> anyhow::Error {
+    match code {
+        Some(code) => fail(ErrorKind::External, format!("{} exited with code {}", program, code)),
+        None => fail(ErrorKind::External, format!("{} was killed", program)),
+    }
+}
+
+pub trait ResultExt<T> {
+    /// Tags the error, if any, with `kind`.
+    fn kind(self, kind: ErrorKind) -> anyhow::Result<T>;
+}
+
+impl<T> ResultExt<T> for anyhow::Result<T> {
+    fn kind(self, kind: ErrorKind) -> anyhow::Result<T> {
+        self.map_err(|error| tag(kind, error))
+    }
+}
+
+/// The kind of `error`: the tag closest to the failure, as `tag` never
+/// replaces one, else guessed from the errors git, TOML parsing and prompts
+/// return.
+pub fn classify(error: &anyhow::Error) -> ErrorKind {
+    for cause in error.chain() {
+        if let Some(classified) = cause.downcast_ref::<Classified>() {
+            return classified.kind;
+        }
+    }
+    for cause in error.chain() {
+        if cause.is::<git2::Error>() {
+            return ErrorKind::Git;
+        }
+        if cause.is::<toml::de::Error>() {
+            return ErrorKind::Config;
+        }
+        if cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::Interrupted)
+        {
+            return ErrorKind::Aborted;
+        }
+        if let Some(dialoguer::Error::IO(e)) = cause.downcast_ref::<dialoguer::Error>() {
+            if e.kind() == std::io::ErrorKind::Interrupted {
+                return ErrorKind::Aborted;
+            }
+        }
+    }
+    ErrorKind::Other
+}
@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// One thing that happened, written as a JSON line with an `event` field
+/// naming the variant.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StreamEvent {
+    GroupStarted { group: String },
+    GroupFinished { group: String, success: bool, error: Option<String> },
+    PackageInstalled { installer: String, package: String },
+    FileWritten { path: PathBuf },
+    /// `repository` is `dotfiles` for the main repo.
+    Synced { repository: String, branch: String },
+    /// A file changed on both the device branch and the main branch.
+    SyncConflict { repository: String, branch: String, error: String },
+    /// Any other sync failure, e.g. the network or credentials.
+    SyncFailed { repository: String, branch: String, error: String },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a StreamEvent,
+}
+
+static SINK: Mutex<Option<File>> = Mutex::new(None);
+
+/// `event` as the JSON line written to the sink, newline included.
+pub fn line(event: &StreamEvent, timestamp: DateTime<Utc>) -> Result<String> {
+    let mut line = serde_json::to_string(&Line { timestamp, event })?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Appends events to `path` from now on.
+pub fn open_file(path: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {} for events", path.display()))?;
+    *SINK.lock().unwrap() = Some(file);
+    Ok(())
+}
+
+/// Writes events to the inherited file descriptor `fd`, e.g. a pipe a
+/// wrapper opened with `3>`.
+#[cfg(unix)]
+pub fn open_fd(fd: i32) -> Result<()> {
+    if fd <= 2 {
+        anyhow::bail!("--events-fd {} would mix events into stdin, stdout or stderr; use 3 or higher", fd);
+    }
+    let path = PathBuf::from(format!("/dev/fd/{}", fd));
+    if !path.exists() {
+        anyhow::bail!("--events-fd {} is not an open file descriptor", fd);
+    }
+    open_file(&path)
+}
+
+#[cfg(not(unix))]
+pub fn open_fd(_fd: i32) -> Result<()> {
+    anyhow::bail!("--events-fd needs a Unix system; use --events-file instead")
+}
+
+/// Stops writing events, closing the sink.
+pub fn close() {
+    *SINK.lock().unwrap() = None;
+}
+
+/// Writes `event` to the sink, if one was opened. A sink that stopped
+/// accepting writes (the reader went away) is closed rather than failing the
+/// run.
+pub fn emit(event: StreamEvent) {
+    let mut sink = SINK.lock().unwrap();
+    let Some(file) = sink.as_mut() else {
+        return;
+    };
+    let written = line(&event, Utc::now()).and_then(|line| Ok(file.write_all(line.as_bytes())?));
+    if written.is_err() {
+        *sink = None;
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::NamedTempFile;
+use crate::modules::config::ConfigManager;
+use crate::modules::errors;
+use crate::modules::plugin;
+
+/// `zshrcman foo` runs an executable named `zshrcman-foo`.
+pub const SUBCOMMAND_PREFIX: &str = "zshrcman-";
+
+/// Finds `zshrcman-<name>` on PATH.
+pub fn find(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}", SUBCOMMAND_PREFIX, name);
+    let path = env::var_os("PATH")?;
+    
+    env::split_paths(&path).find_map(|dir| {
+        let candidate = dir.join(&file_name);
+        if plugin::is_executable(&candidate) {
+            return Some(candidate);
+        }
+        if cfg!(windows) {
+            let exe = candidate.with_extension("exe");
+            if plugin::is_executable(&exe) {
+                return Some(exe);
+            }
+        }
+        None
+    })
+}
+
+/// Runs an external subcommand. A non-zero exit comes back as an
+/// [`errors::exited`] error, so zshrcman exits with 8.
+///
+/// The command gets zshrcman's paths in its environment, plus `ZSHRCMAN_STATE`:
+/// a JSON snapshot of config.toml (groups, profiles, install status) it can
+/// read instead of parsing TOML itself. The snapshot is deleted afterwards;
+/// commands that change state should call back into `zshrcman`.
+pub fn run(name: &str, args: &[String], config_mgr: &ConfigManager) -> Result<()> {
+    let program = find(name).with_context(|| {
+        format!(
+            "Unknown command '{}'. Run `zshrcman --help` for built-in commands; \
+             external commands are executables named {}{} on PATH",
+            name, SUBCOMMAND_PREFIX, name
+        )
+    })?;
+    
+    let state = write_state(config_mgr)?;
+    let status = command(&program, args, config_mgr, state.path()).status();
+    drop(state);
+    let status = status.with_context(|| format!("Failed to run {}", program.display()))?;
+    
+    if !status.success() {
+        return Err(errors::exited(&format!("{}{}", SUBCOMMAND_PREFIX, name), status.code()));
+    }
+    Ok(())
+}
+
+/// The `ZSHRCMAN_STATE` snapshot: a temp file with a random name, readable
+/// only by the user (it holds the whole config), removed when dropped.
+pub fn write_state(config_mgr: &ConfigManager) -> Result<NamedTempFile> {
+    let mut state = tempfile::Builder::new().prefix("zshrcman-state-").suffix(".json").tempfile()?;
+    serde_json::to_writer_pretty(&mut state, &config_mgr.config)?;
+    state.flush()?;
+    Ok(state)
+}
+
+/// `program` with zshrcman's paths and the state snapshot in its environment.
+pub fn command(program: &Path, args: &[String], config_mgr: &ConfigManager, state: &Path) -> Command {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .env("ZSHRCMAN_BIN", env::current_exe().unwrap_or_else(|_| PathBuf::from("zshrcman")))
+        .env("ZSHRCMAN_VERSION", env!("CARGO_PKG_VERSION"))
+        .env("ZSHRCMAN_CONFIG", config_mgr.config_path())
+        .env("ZSHRCMAN_DOTFILES", config_mgr.dotfiles_path())
+        .env("ZSHRCMAN_DATA_DIR", config_mgr.data_dir())
+        .env("ZSHRCMAN_CACHE_DIR", config_mgr.cache_dir())
+        .env("ZSHRCMAN_STATE", state);
+    command
+}
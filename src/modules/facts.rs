@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use crate::models::GroupCondition;
+
+/// Package managers looked for on PATH.
+const PACKAGE_MANAGERS: [&str; 19] = [
+    "brew", "port", "mas", "apt", "dnf", "yum", "pacman", "zypper", "apk", "nix",
+    "flatpak", "snap", "winget", "scoop", "choco", "npm", "pnpm", "cargo", "pip",
+];
+
+/// Information about the machine zshrcman runs on, used by group conditions
+/// and as `{{ facts.* }}` in templates.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Facts {
+    pub hostname: String,
+    pub os: String,
+    pub arch: String,
+    /// Login shell name, e.g. `zsh`, from `$SHELL`.
+    pub shell: String,
+    pub cpus: usize,
+    pub wsl: bool,
+    pub package_managers: Vec<String>,
+}
+
+impl Facts {
+    pub fn gather() -> Self {
+        Self {
+            hostname: hostname::get()
+                .ok()
+                .and_then(|name| name.into_string().ok())
+                .unwrap_or_default(),
+            os: env::consts::OS.to_string(),
+            arch: env::consts::ARCH.to_string(),
+            shell: env::var("SHELL")
+                .ok()
+                .and_then(|shell| shell.rsplit('/').next().map(String::from))
+                .unwrap_or_default(),
+            cpus: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            wsl: detect_wsl(),
+            package_managers: PACKAGE_MANAGERS
+                .iter()
+                .filter(|pm| which::which(pm).is_ok())
+                .map(|pm| pm.to_string())
+                .collect(),
+        }
+    }
+    
+    pub fn has_package_manager(&self, name: &str) -> bool {
+        self.package_managers.iter().any(|pm| pm == name)
+    }
+}
+
+impl GroupCondition {
+    /// `Err` explains the first constraint that doesn't hold.
+    pub fn check(&self, facts: &Facts) -> Result<(), String> {
+        let lists = [
+            ("os", &self.os, &facts.os),
+            ("arch", &self.arch, &facts.arch),
+            ("hostname", &self.hostname, &facts.hostname),
+            ("shell", &self.shell, &facts.shell),
+        ];
+        for (name, allowed, actual) in lists {
+            if !allowed.is_empty() && !allowed.iter().any(|a| a.eq_ignore_ascii_case(actual)) {
+                return Err(format!("{} is '{}', needs one of {}", name, actual, allowed.join(", ")));
+            }
+        }
+        
+        if let Some(wsl) = self.wsl {
+            if wsl != facts.wsl {
+                return Err(if wsl { "not running under WSL" } else { "running under WSL" }.to_string());
+            }
+        }
+        
+        for program in &self.requires {
+            if !facts.has_package_manager(program) && which::which(program).is_err() {
+                return Err(format!("'{}' is not on PATH", program));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+fn detect_wsl() -> bool {
+    if env::var_os("WSL_DISTRO_NAME").is_some() {
+        return true;
+    }
+    fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|release| release.to_lowercase().contains("microsoft"))
+        .unwrap_or(false)
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use crate::models::Config;
+use crate::modules::output;
+
+/// Experimental subsystems that are off unless enabled in `[features]` or with
+/// `--enable-feature`. Gating lets big capabilities land incrementally without
+/// changing behaviour for everyone else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    Daemon,
+    EventLog,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 2] = [Feature::Daemon, Feature::EventLog];
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Feature::Daemon => "daemon",
+            Feature::EventLog => "event-log",
+        }
+    }
+    
+    pub fn description(&self) -> &'static str {
+        match self {
+            Feature::Daemon => "Background watcher that auto-commits and syncs dotfiles",
+            Feature::EventLog => "Machine-readable event stream for scripting",
+        }
+    }
+    
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|f| f.name() == name)
+    }
+}
+
+static CLI_ENABLED: Mutex<Vec<Feature>> = Mutex::new(Vec::new());
+static WARNED: Mutex<Vec<Feature>> = Mutex::new(Vec::new());
+
+/// Records features enabled for this run via `--enable-feature`.
+pub fn enable_from_cli(names: &[String]) -> Result<()> {
+    let mut enabled = CLI_ENABLED.lock().unwrap();
+    for name in names {
+        match Feature::from_name(name) {
+            Some(feature) => enabled.push(feature),
+            None => anyhow::bail!(
+                "Unknown feature '{}'. Available: {}",
+                name,
+                Feature::ALL.iter().map(|f| f.name()).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+    Ok(())
+}
+
+pub fn is_enabled(feature: Feature, config: &Config) -> bool {
+    CLI_ENABLED.lock().unwrap().contains(&feature)
+        || config.features.get(feature.name()).copied().unwrap_or(false)
+}
+
+/// Errors unless `feature` is enabled, and prints a one-time warning when it is.
+pub fn require(feature: Feature, config: &Config) -> Result<()> {
+    if !is_enabled(feature, config) {
+        anyhow::bail!(
+            "'{}' is an experimental feature. Enable it with --enable-feature {} \
+             or set `{} = true` under [features] in config.toml",
+            feature.name(),
+            feature.name(),
+            feature.name()
+        );
+    }
+    
+    let mut warned = WARNED.lock().unwrap();
+    if !warned.contains(&feature) {
+        warned.push(feature);
+        output::warn(format!("⚠️  '{}' is experimental and may change or break without notice", feature.name()));
+    }
+    
+    Ok(())
+}
+
+/// Entries under `[features]` that don't name a known feature, usually typos.
+pub fn unknown_config_features(config: &Config) -> Vec<String> {
+    let known: HashSet<&str> = Feature::ALL.iter().map(|f| f.name()).collect();
+    config
+        .features
+        .keys()
+        .filter(|name| !known.contains(name.as_str()))
+        .cloned()
+        .collect()
+}
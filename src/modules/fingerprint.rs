@@ -0,0 +1,62 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+/// A content hash over everything a group install reads. If it matches the
+/// one stored after the last successful install, the group can be skipped.
+pub struct Fingerprint {
+    hasher: Sha256,
+}
+
+impl Fingerprint {
+    pub fn new() -> Self {
+        Self { hasher: Sha256::new() }
+    }
+    
+    /// Adds a labelled value. Labels keep e.g. a renamed file from hashing
+    /// the same as the original.
+    pub fn add(&mut self, label: &str, bytes: &[u8]) -> &mut Self {
+        for part in [label.as_bytes(), bytes] {
+            self.hasher.update((part.len() as u64).to_le_bytes());
+            self.hasher.update(part);
+        }
+        self
+    }
+    
+    /// Adds a file's contents, or a directory's files in name order. Missing
+    /// paths hash differently from empty files.
+    pub fn add_path(&mut self, path: &Path) -> &mut Self {
+        let label = path.display().to_string();
+        if path.is_dir() {
+            let mut entries: Vec<_> = fs::read_dir(path)
+                .map(|entries| entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect())
+                .unwrap_or_default();
+            entries.sort();
+            self.add(&label, b"dir");
+            for entry in entries {
+                self.add_path(&entry);
+            }
+        } else {
+            match fs::read(path) {
+                Ok(contents) => self.add(&label, &contents),
+                Err(_) => self.add(&label, b"missing"),
+            };
+        }
+        self
+    }
+    
+    /// The hash as lowercase hex.
+    pub fn finish(self) -> String {
+        self.hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+use std::time::Duration;
+use crate::modules::installer::Installer;
+use crate::modules::output;
+
+/// Remote used for packages that don't name one.
+pub const DEFAULT_REMOTE: &str = "flathub";
+const FLATHUB_URL: &str = "https://dl.flathub.org/repo/flathub.flatpakrepo";
+
+/// A flatpak package spec, `[remote:]app-id[//branch]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatpakRef<'a> {
+    pub remote: &'a str,
+    /// The app id, with its branch if one was given.
+    pub app: &'a str,
+}
+
+impl<'a> FlatpakRef<'a> {
+    pub fn parse(spec: &'a str) -> Self {
+        match spec.split_once(':') {
+            Some((remote, app)) => Self { remote, app },
+            None => Self {
+                remote: DEFAULT_REMOTE,
+                app: spec,
+            },
+        }
+    }
+    
+    /// The app id without the branch.
+    pub fn id(&self) -> &'a str {
+        self.app.split("//").next().unwrap_or(self.app)
+    }
+}
+
+/// Reads `Version:` (or `Branch:`, which every app has) from `flatpak info`.
+pub fn parse_info(output: &str) -> Option<String> {
+    let field = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(name).map(|value| value.trim().to_string()))
+            .filter(|value| !value.is_empty())
+    };
+    field("Version:").or_else(|| field("Branch:"))
+}
+
+/// Installs desktop apps from Flatpak remotes into the user installation.
+/// Flathub is added as a remote when a package needs it.
+#[derive(Debug, Clone, Default)]
+pub struct FlatpakInstaller {
+    timeout: Option<Duration>,
+    dry_run: bool,
+}
+
+impl FlatpakInstaller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+    
+    fn run(&self, args: &[&str], label: &str, check_status: bool) -> Result<()> {
+        if self.dry_run {
+            println!("   [dry-run] flatpak {}", args.join(" "));
+            return Ok(());
+        }
+        let outcome = output::stream_command(Command::new("flatpak").args(args), label, self.timeout)
+            .context("Failed to run flatpak")?;
+        if outcome.timed_out {
+            anyhow::bail!("{} timed out after {}s", label, self.timeout.unwrap_or_default().as_secs());
+        }
+        if check_status && !outcome.status.success() {
+            anyhow::bail!("{} failed: {}", label, outcome.stderr_tail);
+        }
+        Ok(())
+    }
+}
+
+impl Installer for FlatpakInstaller {
+    fn name(&self) -> &str {
+        "flatpak"
+    }
+    
+    fn install(&self, packages: &[String]) -> Result<()> {
+        let refs: Vec<FlatpakRef> = packages.iter().map(|package| FlatpakRef::parse(package)).collect();
+        if refs.iter().any(|flatpak| flatpak.remote == DEFAULT_REMOTE) {
+            self.run(
+                &["remote-add", "--user", "--if-not-exists", DEFAULT_REMOTE, FLATHUB_URL],
+                "flatpak remote-add flathub",
+                true,
+            )?;
+        }
+        for (i, flatpak) in refs.iter().enumerate() {
+            let label = format!("[{}/{}] flatpak install {}", i + 1, refs.len(), flatpak.app);
+            self.run(&["install", "--user", "--noninteractive", "-y", flatpak.remote, flatpak.app], &label, true)?;
+        }
+        Ok(())
+    }
+    
+    fn uninstall(&self, packages: &[String]) -> Result<()> {
+        for (i, package) in packages.iter().enumerate() {
+            let flatpak = FlatpakRef::parse(package);
+            let label = format!("[{}/{}] flatpak uninstall {}", i + 1, packages.len(), flatpak.app);
+            self.run(&["uninstall", "--user", "--noninteractive", "-y", flatpak.app], &label, false)?;
+        }
+        Ok(())
+    }
+    
+    fn version(&self, package: &str) -> Result<Option<String>> {
+        let output = match Command::new("flatpak").args(["info", "--user", FlatpakRef::parse(package).id()]).output() {
+            Ok(output) => output,
+            Err(_) => return Ok(None),
+        };
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(parse_info(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
@@ -0,0 +1,283 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::modules::config::ConfigManager;
+use crate::modules::facts::Facts;
+use crate::modules::git_mgr::GitManager;
+use crate::modules::{offline, oplog, output, prompt};
+
+/// What a device commits about itself to its branch at every sync, so
+/// `zshrcman fleet` can show all machines from any one of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceReport {
+    pub device: String,
+    #[serde(default)]
+    pub hostname: String,
+    #[serde(default)]
+    pub os: String,
+    pub synced_at: DateTime<Utc>,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub enabled_groups: Vec<String>,
+    #[serde(default)]
+    pub installed_groups: Vec<String>,
+    /// Groups whose last install failed, with the error.
+    #[serde(default)]
+    pub failed_groups: BTreeMap<String, String>,
+}
+
+impl DeviceReport {
+    pub fn from_config(config_mgr: &ConfigManager, facts: &Facts, synced_at: DateTime<Utc>) -> Self {
+        let config = &config_mgr.config;
+        let mut installed_groups = Vec::new();
+        let mut failed_groups = BTreeMap::new();
+        for (group, status) in &config.status {
+            if status.installed && status.success {
+                installed_groups.push(group.clone());
+            } else if !status.success {
+                let error = status.error.clone().unwrap_or_else(|| "unknown error".to_string());
+                failed_groups.insert(group.clone(), error);
+            }
+        }
+        installed_groups.sort();
+        
+        Self {
+            device: config.device.name.clone(),
+            hostname: facts.hostname.clone(),
+            os: format!("{}/{}", facts.os, facts.arch),
+            synced_at,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            active_profile: config.active_profile.clone(),
+            enabled_groups: config_mgr.get_ordered_groups(),
+            installed_groups,
+            failed_groups,
+        }
+    }
+}
+
+/// Where a device's report lives in the dotfiles repo.
+pub fn report_path(device: &str) -> PathBuf {
+    Path::new("devices").join(device).join("status.toml")
+}
+
+/// Writes this device's report and commits it to its branch, for the next
+/// push to publish. Does nothing unless the device branch is checked out, or
+/// when nothing but the sync time changed since the last report: the
+/// branch's last commit already says when the device synced.
+pub fn publish(git_mgr: &GitManager, dotfiles: &Path, config_mgr: &ConfigManager) -> Result<()> {
+    let device = &config_mgr.config.device;
+    if device.name.is_empty() || git_mgr.current_branch().as_deref() != Some(device.branch.as_str()) {
+        return Ok(());
+    }
+    
+    let report = DeviceReport::from_config(config_mgr, &Facts::gather(), Utc::now());
+    let relative = report_path(&device.name);
+    let path = dotfiles.join(&relative);
+    let previous = fs::read_to_string(&path).ok().and_then(|text| toml::from_str::<DeviceReport>(&text).ok());
+    if previous.is_some_and(|previous| DeviceReport { synced_at: previous.synced_at, ..report.clone() } == previous) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, toml::to_string_pretty(&report)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    
+    git_mgr.add_path(&relative)?;
+    git_mgr.commit(&format!("Update status of {}", device.name))
+}
+
+/// One device branch and what it last reported.
+#[derive(Debug, Clone)]
+pub struct FleetEntry {
+    pub branch: String,
+    pub last_commit: DateTime<Utc>,
+    /// `None` when the device never synced with a version that reports, or
+    /// its report couldn't be read.
+    pub report: Option<DeviceReport>,
+}
+
+impl FleetEntry {
+    /// The device name from the report, else from the branch name.
+    pub fn device(&self) -> &str {
+        match &self.report {
+            Some(report) if !report.device.is_empty() => &report.device,
+            _ => self.branch.strip_prefix("device/").unwrap_or(&self.branch),
+        }
+    }
+    
+    /// When the device last synced. The report is only committed when it
+    /// changes, so a later commit on the branch counts too.
+    pub fn last_sync(&self) -> DateTime<Utc> {
+        self.report.as_ref().map_or(self.last_commit, |report| report.synced_at.max(self.last_commit))
+    }
+}
+
+/// Every `device/*` branch known locally or fetched from origin, most
+/// recently synced first.
+pub fn collect(git_mgr: &GitManager) -> Result<Vec<FleetEntry>> {
+    let mut entries = Vec::new();
+    for (branch, last_commit) in git_mgr.device_branches()? {
+        let device = branch.strip_prefix("device/").unwrap_or(&branch);
+        let report = git_mgr
+            .read_file(&branch, &report_path(device))?
+            .and_then(|contents| toml::from_str::<DeviceReport>(&contents).ok());
+        entries.push(FleetEntry {
+            branch,
+            last_commit,
+            report,
+        });
+    }
+    
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_sync()));
+    Ok(entries)
+}
+
+/// Prints every device branch with its status report, fetching them first
+/// unless offline.
+pub fn show(config_mgr: &ConfigManager) -> Result<()> {
+    let git_mgr = GitManager::open(&ConfigManager::get_dotfiles_path()?)?;
+    if !offline::is_offline() {
+        if let Err(e) = git_mgr.fetch_tracking("device/*") {
+            output::warn(format!("⚠️  Couldn't fetch device branches ({:#}); showing the last fetch", e));
+        }
+    }
+    
+    let entries = collect(&git_mgr)?;
+    if entries.is_empty() {
+        println!("No device branches found");
+        return Ok(());
+    }
+    
+    let rows: Vec<[String; 5]> = entries
+        .iter()
+        .map(|entry| {
+            let marker = if entry.branch == config_mgr.config.device.branch { "* " } else { "  " };
+            let device = format!("{}{}", marker, entry.device());
+            match &entry.report {
+                Some(report) => [
+                    device,
+                    entry.last_sync().with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string(),
+                    report.active_profile.clone().unwrap_or_else(|| "-".to_string()),
+                    report.enabled_groups.join(", "),
+                    if report.failed_groups.is_empty() {
+                        "-".to_string()
+                    } else {
+                        report.failed_groups.keys().cloned().collect::<Vec<_>>().join(", ")
+                    },
+                ],
+                None => [
+                    device,
+                    format!(
+                        "{} (no report)",
+                        entry.last_commit.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                    ),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                ],
+            }
+        })
+        .collect();
+    
+    let headers = ["  DEVICE", "LAST SYNC", "PROFILE", "GROUPS", "FAILED"];
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: [&str; 5]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    
+    println!("{}", format!("🖥️  Fleet ({} devices)", rows.len()).bold());
+    println!("{}", line(headers).dimmed());
+    for (row, entry) in rows.iter().zip(&entries) {
+        let text = line(row.each_ref().map(String::as_str));
+        let failed = entry.report.as_ref().is_some_and(|report| !report.failed_groups.is_empty());
+        if failed {
+            println!("{}", text.red());
+        } else {
+            println!("{}", text);
+        }
+    }
+    
+    let failures: Vec<(&str, &String, &String)> = entries
+        .iter()
+        .filter_map(|entry| entry.report.as_ref().map(|report| (entry.device(), report)))
+        .flat_map(|(device, report)| report.failed_groups.iter().map(move |(group, error)| (device, group, error)))
+        .collect();
+    if !failures.is_empty() {
+        println!("\n{}", "Failed installs:".bold());
+        for (device, group, error) in failures {
+            println!("  {} / {}: {}", device, group, error);
+        }
+    }
+    Ok(())
+}
+
+/// Offers to delete other devices' branches here and on origin, with those
+/// last committed to before `older_than` preselected.
+pub fn prune(config_mgr: &ConfigManager, older_than: &str) -> Result<()> {
+    let now = chrono::Utc::now();
+    let cutoff = oplog::parse_since(older_than, now)?;
+    let git_mgr = GitManager::open(&ConfigManager::get_dotfiles_path()?)?
+        .with_mirrors(&config_mgr.config.repository.remotes)?;
+    if !offline::is_offline() {
+        if let Err(e) = git_mgr.fetch_tracking("device/*") {
+            output::warn(format!("⚠️  Couldn't fetch device branches ({:#}); listing the last fetched ones", e));
+        }
+    }
+    
+    // This machine's own branch can't go.
+    let branches: Vec<(String, chrono::DateTime<chrono::Utc>)> = git_mgr
+        .device_branches()?
+        .into_iter()
+        .filter(|(name, _)| *name != config_mgr.config.device.branch)
+        .collect();
+    if branches.is_empty() {
+        println!("No device branches other than {}", config_mgr.config.device.branch);
+        return Ok(());
+    }
+    
+    let items: Vec<String> = branches
+        .iter()
+        .map(|(name, last)| {
+            let date = last.with_timezone(&chrono::Local).format("%Y-%m-%d");
+            format!("{}  last commit {} ({} days ago)", name, date, (now - *last).num_days())
+        })
+        .collect();
+    let stale: Vec<bool> = branches.iter().map(|(_, last)| *last < cutoff).collect();
+    let selected = prompt::multi_select("Device branches to delete", &items, &stale)?;
+    if selected.is_empty() {
+        println!("Nothing selected");
+        return Ok(());
+    }
+    if !prompt::confirm(&format!("Delete {} branch(es) here and on origin?", selected.len()), true)? {
+        return Ok(());
+    }
+    
+    for idx in selected {
+        let name = &branches[idx].0;
+        match git_mgr.delete_branch(name) {
+            Ok(()) => println!("{} {}", "🗑️  Deleted".green(), name),
+            Err(e) => output::warn(format!("⚠️  Failed to delete '{}': {:#}", name, e)),
+        }
+    }
+    Ok(())
+}
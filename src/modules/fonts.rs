@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::models::{Font, OsType};
+use crate::modules::offline;
+
+/// File extensions of the font files that get installed.
+pub const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc"];
+
+/// Where per-user fonts go on `os`.
+pub fn fonts_dir(home: &Path, os: &OsType) -> PathBuf {
+    match os {
+        OsType::MacOS => home.join("Library").join("Fonts"),
+        OsType::Windows => home.join("AppData").join("Local").join("Microsoft").join("Windows").join("Fonts"),
+        _ => home.join(".local").join("share").join("fonts"),
+    }
+}
+
+/// Whether `name` can name a font's cache directory: not empty, `.` or `..`,
+/// and without path separators, so it can't point outside the cache.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+/// The name Windows lists the font under in the registry.
+pub fn registry_name(font: &Path) -> String {
+    let kind = match font.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("otf") => "OpenType",
+        _ => "TrueType",
+    };
+    format!("{} ({})", font.file_stem().unwrap_or_default().to_string_lossy(), kind)
+}
+
+fn is_font(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| FONT_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Font files at `path`: the file itself, or every one below the directory,
+/// sorted.
+pub fn font_files(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        return if is_font(path) { vec![path.to_path_buf()] } else { vec![] };
+    }
+    let mut files = Vec::new();
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            files.extend(font_files(&entry.path()));
+        }
+    }
+    files.sort();
+    files
+}
+
+fn run(command: &mut Command, what: &str) -> Result<()> {
+    let output = command.output().with_context(|| format!("Failed to run {}", what))?;
+    if !output.status.success() {
+        anyhow::bail!("{} failed: {}", what, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Downloads `url` into `dir` (emptied first) and unpacks it if it's an
+/// archive. Returns the directory holding the font files.
+pub fn download(name: &str, url: &str, dir: &Path) -> Result<PathBuf> {
+    if offline::is_offline() {
+        anyhow::bail!("Can't download font '{}' while offline", name);
+    }
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    fs::create_dir_all(dir)?;
+    
+    let file_name = url.rsplit('/').next().filter(|name| !name.is_empty()).unwrap_or("font");
+    let download = dir.join(file_name);
+    run(Command::new("curl").args(["-fsSL", "-o"]).arg(&download).arg(url), &format!("curl {}", url))?;
+    
+    if file_name.ends_with(".zip") {
+        if which::which("unzip").is_ok() {
+            run(Command::new("unzip").args(["-o", "-q"]).arg(&download).arg("-d").arg(dir), "unzip")?;
+        } else if cfg!(windows) {
+            run(
+                Command::new("powershell")
+                    .args(["-NoProfile", "-NonInteractive", "-Command", "Expand-Archive -Force -LiteralPath $args[0] -DestinationPath $args[1]"])
+                    .arg(&download)
+                    .arg(dir),
+                "Expand-Archive",
+            )?;
+        } else {
+            anyhow::bail!("Font '{}' is a .zip archive; install unzip to extract it", name);
+        }
+    } else if file_name.contains(".tar") || file_name.ends_with(".tgz") {
+        run(Command::new("tar").arg("-xf").arg(&download).arg("-C").arg(dir), "tar")?;
+    }
+    Ok(dir.to_path_buf())
+}
+
+/// Makes newly copied `installed` fonts usable: Linux rebuilds the fontconfig
+/// cache, Windows needs them registered for the user. macOS picks them up
+/// by itself.
+pub fn refresh(fonts_dir: &Path, os: &OsType, installed: &[PathBuf]) -> Result<()> {
+    match os {
+        OsType::Windows => {
+            for font in installed {
+                run(
+                    Command::new("reg")
+                        .args(["add", "HKCU\\Software\\Microsoft\\Windows NT\\CurrentVersion\\Fonts", "/v"])
+                        .arg(registry_name(font))
+                        .args(["/t", "REG_SZ", "/d"])
+                        .arg(font)
+                        .arg("/f"),
+                    "reg add",
+                )?;
+            }
+        }
+        OsType::MacOS => {}
+        _ => {
+            if which::which("fc-cache").is_ok() {
+                run(Command::new("fc-cache").arg("-f").arg(fonts_dir), "fc-cache")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Where `font`'s files come from: its `source` in the group's repo `root`,
+/// or its `url` downloaded below `cache_dir`.
+pub fn fetch(font: &Font, root: &Path, cache_dir: &Path) -> Result<PathBuf> {
+    if !is_valid_name(&font.name) {
+        anyhow::bail!("'{}' is not a valid font name; it can't contain '/', '\\' or be '..'", font.name);
+    }
+    match (&font.source, &font.url) {
+        (Some(source), None) => Ok(root.join(source)),
+        (None, Some(url)) => download(&font.name, url, &cache_dir.join("fonts").join(&font.name)),
+        _ => anyhow::bail!("font '{}' needs exactly one of `source` or `url`", font.name),
+    }
+}
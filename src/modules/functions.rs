@@ -0,0 +1,135 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::ShellFunction;
+
+/// Directory below home that zsh autoloads the functions from.
+pub const FUNCTIONS_DIR: &str = ".zsh_functions.d";
+
+/// Names of the files the last deploy wrote, so functions that left every
+/// group are removed again and files added by hand are left alone.
+const MANIFEST: &str = ".zshrcman";
+
+/// Functions gathered from several groups, with the clashes and unusable
+/// names found along the way.
+#[derive(Debug, Default)]
+pub struct Functions {
+    functions: Vec<ShellFunction>,
+    /// Where each function in `functions` came from.
+    origins: Vec<String>,
+    pub problems: Vec<String>,
+}
+
+impl Functions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Adds `functions` defined by `origin`, e.g. `group 'git'`. A name that
+    /// is already taken by a different definition keeps the first one.
+    pub fn add(&mut self, origin: &str, functions: &[ShellFunction]) {
+        for function in functions {
+            if !is_valid_name(&function.name) {
+                self.problems.push(format!("'{}' in {} is not a valid name; skipping it", function.name, origin));
+                continue;
+            }
+            match self.functions.iter().position(|f| f.name == function.name) {
+                Some(idx) if self.functions[idx] == *function => {}
+                Some(idx) => self.problems.push(format!(
+                    "'{}' is defined in {} and {}; keeping the one from {}",
+                    function.name, self.origins[idx], origin, self.origins[idx]
+                )),
+                None => {
+                    self.functions.push(function.clone());
+                    self.origins.push(origin.to_string());
+                }
+            }
+        }
+    }
+    
+    pub fn list(&self) -> &[ShellFunction] {
+        &self.functions
+    }
+}
+
+/// Function names become file names in the autoload directory.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name.starts_with('-')
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.' | '+'))
+}
+
+/// Deploys group functions: one autoload file per function in
+/// `~/.zsh_functions.d` for zsh, and definitions in the rc file for other
+/// shells (see [`loader`]).
+pub struct FunctionManager {
+    dir: PathBuf,
+}
+
+impl FunctionManager {
+    pub fn new(home: &Path) -> Self {
+        Self { dir: home.join(FUNCTIONS_DIR) }
+    }
+    
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    
+    /// Writes each function meant for zsh to `<dir>/<name>` and removes the
+    /// files of earlier deploys that are no longer wanted. Returns the names
+    /// written.
+    pub fn deploy(&self, functions: &[ShellFunction]) -> Result<Vec<String>> {
+        let wanted: Vec<&ShellFunction> = functions.iter().filter(|f| f.applies_to("zsh")).collect();
+        let manifest = self.dir.join(MANIFEST);
+        let previous = fs::read_to_string(&manifest).unwrap_or_default();
+        
+        for name in previous.lines() {
+            if is_valid_name(name) && !wanted.iter().any(|f| f.name == name) {
+                let _ = fs::remove_file(self.dir.join(name));
+            }
+        }
+        
+        if wanted.is_empty() {
+            let _ = fs::remove_file(&manifest);
+            let _ = fs::remove_dir(&self.dir);
+            return Ok(vec![]);
+        }
+        
+        fs::create_dir_all(&self.dir)?;
+        let mut names = Vec::new();
+        for function in wanted {
+            fs::write(self.dir.join(&function.name), format!("{}\n", function.body.trim_end()))?;
+            names.push(function.name.clone());
+        }
+        fs::write(&manifest, names.iter().map(|name| format!("{}\n", name)).collect::<String>())?;
+        Ok(names)
+    }
+}
+
+/// What the rc file needs for `functions` in `shell`: zsh puts the autoload
+/// directory on `fpath` and autoloads the names, other shells get the
+/// definitions themselves. Empty when nothing applies.
+pub fn loader(functions: &[ShellFunction], shell: &str) -> String {
+    if shell == "zsh" {
+        let names: Vec<&str> = functions
+            .iter()
+            .filter(|f| f.applies_to("zsh"))
+            .map(|f| f.name.as_str())
+            .collect();
+        if names.is_empty() {
+            return String::new();
+        }
+        return format!(
+            "# zshrcman functions\n\nfpath=(\"$HOME/{}\" $fpath)\nautoload -Uz {}\n",
+            FUNCTIONS_DIR,
+            names.join(" ")
+        );
+    }
+    
+    let definitions: Vec<String> = functions.iter().filter_map(|f| f.render(shell)).collect();
+    if definitions.is_empty() {
+        return String::new();
+    }
+    format!("# zshrcman functions\n\n{}", definitions.join("\n"))
+}
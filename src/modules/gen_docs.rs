@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use clap::{Arg, Command};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Writes a man page for `cmd` and each visible subcommand, e.g.
+/// `zshrcman.1`, `zshrcman-profile-switch.1`, into `out_dir`. Returns the
+/// pages written.
+pub fn man_pages(cmd: Command, out_dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(out_dir).with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let mut written = Vec::new();
+    for cmd in visible(&built(cmd)) {
+        let page = clap_mangen::Man::new(cmd.clone())
+            .generate_to(out_dir)
+            .with_context(|| format!("Failed to write the man page of {}", cmd.get_name()))?;
+        written.push(page);
+    }
+    Ok(written)
+}
+
+/// A markdown reference of `cmd` and every visible subcommand: usage, what
+/// it does and its arguments.
+pub fn markdown(cmd: Command) -> String {
+    let cmd = built(cmd);
+    let mut out = format!("# {} command reference\n", cmd.get_name());
+    let root = cmd.get_name().to_string();
+    for cmd in visible(&cmd) {
+        let name = cmd.get_bin_name().unwrap_or(cmd.get_name());
+        out.push_str(&format!("\n## `{}`\n\n", name));
+        if let Some(about) = cmd.get_long_about().or(cmd.get_about()) {
+            out.push_str(&format!("{}\n\n", about));
+        }
+        out.push_str(&format!("```\n{}\n```\n", cmd.clone().render_usage().to_string().trim_start_matches("Usage: ")));
+
+        // Global options are listed once, under the top-level command.
+        let args: Vec<String> = cmd
+            .get_arguments()
+            .filter(|arg| !arg.is_hide_set() && (cmd.get_name() == root || !arg.is_global_set()))
+            .map(argument)
+            .collect();
+        if !args.is_empty() {
+            out.push_str(&format!("\n{}\n", args.join("\n")));
+        }
+        let subcommands: Vec<String> = cmd
+            .get_subcommands()
+            .filter(|sub| !sub.is_hide_set())
+            .map(|sub| match sub.get_about() {
+                Some(about) => format!("- `{}`: {}", sub.get_name(), about),
+                None => format!("- `{}`", sub.get_name()),
+            })
+            .collect();
+        if !subcommands.is_empty() {
+            out.push_str(&format!("\nSubcommands:\n\n{}\n", subcommands.join("\n")));
+        }
+    }
+    out
+}
+
+/// `cmd` with display and bin names filled in for every subcommand, as clap
+/// does before parsing.
+fn built(cmd: Command) -> Command {
+    let mut cmd = cmd.disable_help_subcommand(true);
+    cmd.build();
+    cmd
+}
+
+/// `cmd` and its visible subcommands, depth first.
+fn visible(cmd: &Command) -> Vec<&Command> {
+    let mut commands = vec![cmd];
+    for sub in cmd.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        commands.extend(visible(sub));
+    }
+    commands
+}
+
+/// One argument as a list item: `` - `--every <EVERY>`: Minutes between
+/// scheduled syncs (default: 60) ``.
+fn argument(arg: &Arg) -> String {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("-{}", short));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("--{}", long));
+    }
+    let takes_values = arg.get_num_args().is_none_or(|n| n.takes_values());
+    let values = match arg.get_value_names() {
+        Some(values) => values.iter().map(|value| format!("<{}>", value)).collect::<Vec<_>>().join(" "),
+        None => format!("<{}>", arg.get_id()),
+    };
+    let mut item = if names.is_empty() {
+        format!("- `{}`", values)
+    } else if takes_values {
+        format!("- `{} {}`", names.join(", "), values)
+    } else {
+        format!("- `{}`", names.join(", "))
+    };
+    if let Some(help) = arg.get_help() {
+        item.push_str(&format!(": {}", help));
+    }
+    let defaults: Vec<String> = arg.get_default_values().iter().map(|value| value.to_string_lossy().into_owned()).collect();
+    if !defaults.is_empty() && takes_values {
+        item.push_str(&format!(" (default: {})", defaults.join(", ")));
+    }
+    if let Some(env) = arg.get_env() {
+        item.push_str(&format!(" (env: {})", env.to_string_lossy()));
+    }
+    item
+}
@@ -1,27 +1,259 @@
 use anyhow::{Context, Result};
 use git2::{
-    BranchType, Cred, FetchOptions, PushOptions, RemoteCallbacks, 
-    Repository, ResetType, Signature
+    BranchType, Cred, CredentialType, ErrorCode, FetchOptions, IndexEntry, PushOptions,
+    RemoteCallbacks, Repository, Signature
 };
-use std::path::Path;
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::models::{CommitIdentity, ConflictStrategy, RemoteConfig, SigningFormat};
+use crate::modules::errors::{self, ErrorKind, ResultExt};
+use crate::modules::{offline, output, preflight, prompt};
 
 pub struct GitManager {
     repo: Repository,
+    mirrors: BTreeMap<String, RemoteConfig>,
+    identity: CommitIdentity,
+}
+
+/// Which directions `sync` goes in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncMode {
+    /// Pull and rebase onto the main branch, then push the device branch.
+    #[default]
+    Both,
+    /// Pull and rebase only; nothing leaves this machine.
+    PullOnly,
+    /// Push the device branch as it is, without fetching.
+    PushOnly,
+}
+
+/// What `sync` would change, worked out without touching any branch.
+#[derive(Debug)]
+pub struct SyncPreview {
+    /// Files with uncommitted changes, which sync commits first.
+    pub uncommitted: Vec<String>,
+    pub main: Divergence,
+    pub device: Divergence,
+    /// Files the main branch changed since the device branch forked from it.
+    pub incoming: Vec<FileChange>,
+    /// Files the device branch changed since then.
+    pub outgoing: Vec<FileChange>,
+}
+
+impl SyncPreview {
+    /// Files changed on both sides, which may need a conflict strategy.
+    pub fn overlapping(&self) -> Vec<&str> {
+        self.incoming
+            .iter()
+            .filter(|change| self.outgoing.iter().any(|other| other.path == change.path))
+            .map(|change| change.path.as_str())
+            .collect()
+    }
+}
+
+/// How a local branch compares with its counterpart on origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub branch: String,
+    /// `None` when origin has no such branch (yet).
+    pub ahead_behind: Option<(usize, usize)>,
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.ahead_behind {
+            None => write!(f, "{}: not on origin yet", self.branch),
+            Some((0, 0)) => write!(f, "{}: up to date with origin/{}", self.branch, self.branch),
+            Some((ahead, behind)) => write!(
+                f,
+                "{}: {} ahead, {} behind origin/{}",
+                self.branch, ahead, behind, self.branch
+            ),
+        }
+    }
+}
+
+/// A sync stopped on a file changed on both branches, as opposed to failing
+/// to fetch, push or authenticate.
+#[derive(Debug)]
+pub struct Conflict(pub String);
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Conflict {}
+
+/// The first 8 characters of commit id `id` for display, or all of it when
+/// it's shorter or can't be cut there (e.g. a hand-edited lockfile).
+pub fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+/// Whether `error` is a sync that stopped on a [`Conflict`] or one git
+/// reported while rebasing.
+pub fn is_conflict(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<Conflict>()
+            || cause
+                .downcast_ref::<git2::Error>()
+                .is_some_and(|e| matches!(e.code(), ErrorCode::Conflict | ErrorCode::MergeConflict | ErrorCode::Unmerged))
+    })
+}
+
+/// A file added (`A`), modified (`M`), deleted (`D`) or renamed (`R`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub status: char,
+    pub path: String,
 }
 
 impl GitManager {
+    /// Opens the repository at `path`, or clones `remote_url` into it if
+    /// `path` is missing or an empty directory, e.g. one an interrupted clone
+    /// left behind. Without a URL a new repository is initialized.
     pub fn init_or_clone(path: &Path, remote_url: Option<&str>) -> Result<Self> {
         let repo = if let Some(url) = remote_url {
-            if path.exists() {
-                Repository::open(path)?
-            } else {
-                Self::clone_repo(url, path)?
+            match Repository::open(path) {
+                Ok(repo) => repo,
+                Err(_) if fs::read_dir(path).map_or(true, |mut entries| entries.next().is_none()) => {
+                    offline::require_online(&format!("clone {}", url))?;
+                    Self::clone_repo(url, path)?
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("{} exists but is not a git repository", path.display()))
+                }
             }
         } else {
             Repository::init(path)?
         };
         
-        Ok(Self { repo })
+        Ok(Self { repo, mirrors: BTreeMap::new(), identity: CommitIdentity::default() })
+    }
+    
+    /// Opens an existing dotfiles repository without creating or cloning one.
+    pub fn open(path: &Path) -> Result<Self> {
+        let repo = Repository::open(path)
+            .with_context(|| format!("No dotfiles repository at {}", path.display()))?;
+        Ok(Self { repo, mirrors: BTreeMap::new(), identity: CommitIdentity::default() })
+    }
+    
+    /// Adds the `[repository.remotes]` mirrors, creating or updating the git
+    /// remotes of the same names.
+    pub fn with_mirrors(mut self, mirrors: &BTreeMap<String, RemoteConfig>) -> Result<Self> {
+        for (name, mirror) in mirrors {
+            if name == "origin" {
+                anyhow::bail!("'origin' is the primary remote; give the mirror at {} another name", mirror.url);
+            }
+            
+            match self.repo.find_remote(name) {
+                Ok(remote) if remote.url() == Some(mirror.url.as_str()) => {}
+                Ok(_) => self.repo.remote_set_url(name, &mirror.url)?,
+                Err(_) => {
+                    self.repo.remote(name, &mirror.url)?;
+                }
+            }
+        }
+        
+        self.mirrors = mirrors.clone();
+        Ok(self)
+    }
+    
+    /// Uses `identity` for the author and signature of new commits.
+    pub fn with_identity(mut self, identity: &CommitIdentity) -> Self {
+        self.identity = identity.clone();
+        self
+    }
+    
+    /// Author of new commits: the configured identity, else git's
+    /// `user.name` and `user.email`, else `zshrcman <zshrcman@localhost>`.
+    pub fn signature(&self) -> Result<Signature<'static>> {
+        let config = self.repo.config()?;
+        let name = self.identity.name.clone()
+            .or_else(|| config.get_string("user.name").ok())
+            .unwrap_or_else(|| "zshrcman".to_string());
+        let email = self.identity.email.clone()
+            .or_else(|| config.get_string("user.email").ok())
+            .unwrap_or_else(|| "zshrcman@localhost".to_string());
+        Ok(Signature::now(&name, &email)?)
+    }
+    
+    /// How new commits are signed, or `None` when they aren't.
+    fn signing(&self) -> Result<Option<(SigningFormat, Option<String>)>> {
+        let config = self.repo.config()?;
+        let sign = self.identity.sign
+            .or_else(|| config.get_bool("commit.gpgsign").ok())
+            .unwrap_or(false);
+        if !sign {
+            return Ok(None);
+        }
+        
+        let format = self.identity.signing_format.unwrap_or_else(|| {
+            match config.get_string("gpg.format").as_deref() {
+                Ok("ssh") => SigningFormat::Ssh,
+                _ => SigningFormat::Gpg,
+            }
+        });
+        let key = self.identity.signing_key.clone().or_else(|| config.get_string("user.signingkey").ok());
+        Ok(Some((format, key)))
+    }
+    
+    /// Creates a commit by `author`, signed when signing is on, and points
+    /// `update_ref` at it. For `HEAD` that's the checked-out branch, which is
+    /// created if it has no commits yet.
+    fn create_commit(
+        &self,
+        update_ref: Option<&str>,
+        author: &Signature,
+        message: &str,
+        tree: &git2::Tree,
+        parents: &[&git2::Commit],
+    ) -> Result<git2::Oid> {
+        let committer = self.signature()?;
+        let Some((format, key)) = self.signing()? else {
+            return Ok(self.repo.commit(update_ref, author, &committer, message, tree, parents)?);
+        };
+        
+        let buffer = self.repo.commit_create_buffer(author, &committer, message, tree, parents)?;
+        let buffer = buffer.as_str().context("Commit is not valid UTF-8")?;
+        let oid = self.repo.commit_signed(buffer, &sign_buffer(format, key.as_deref(), buffer)?, None)?;
+        
+        if let Some(name) = update_ref {
+            let reference = self.repo.find_reference(name)?;
+            let target = reference.symbolic_target().unwrap_or(name).to_string();
+            let summary = message.lines().next().unwrap_or_default();
+            self.repo.reference(&target, oid, true, summary)?;
+        }
+        Ok(oid)
+    }
+    
+    /// Signs the commits after `base` on the checked-out branch by creating
+    /// them again; libgit2 can't sign the commits a rebase makes.
+    fn sign_commits_since(&self, base: git2::Oid) -> Result<()> {
+        if self.signing()?.is_none() {
+            return Ok(());
+        }
+        
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.hide(base)?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        let commits = revwalk.collect::<Result<Vec<_>, _>>()?;
+        
+        let mut parent = self.repo.find_commit(base)?;
+        for oid in commits {
+            let commit = self.repo.find_commit(oid)?;
+            let message = String::from_utf8_lossy(commit.message_bytes()).into_owned();
+            let signed = self.create_commit(Some("HEAD"), &commit.author(), &message, &commit.tree()?, &[&parent])?;
+            parent = self.repo.find_commit(signed)?;
+        }
+        Ok(())
     }
     
     fn clone_repo(url: &str, path: &Path) -> Result<Repository> {
@@ -41,7 +273,13 @@ impl GitManager {
             .context("Failed to clone repository")
     }
     
+    /// Branches on origin. Offline, the branches fetched last time are
+    /// listed instead.
     pub fn list_remote_branches(&self) -> Result<Vec<String>> {
+        if offline::is_offline() {
+            return self.cached_remote_branches();
+        }
+        
         let mut remote = self.repo.find_remote("origin")?;
         
         let mut callbacks = RemoteCallbacks::new();
@@ -68,6 +306,19 @@ impl GitManager {
         Ok(branches)
     }
     
+    fn cached_remote_branches(&self) -> Result<Vec<String>> {
+        let mut branches = Vec::new();
+        for branch in self.repo.branches(Some(BranchType::Remote))? {
+            let (branch, _) = branch?;
+            if let Some(name) = branch.name()?.and_then(|name| name.strip_prefix("origin/")) {
+                if name != "HEAD" {
+                    branches.push(name.to_string());
+                }
+            }
+        }
+        Ok(branches)
+    }
+    
     pub fn checkout_branch(&self, branch: &str, create: bool) -> Result<()> {
         if create {
             let head = self.repo.head()?;
@@ -84,17 +335,30 @@ impl GitManager {
         Ok(())
     }
     
+    /// Checks out `branch`, creating it first from `origin/<branch>` if only
+    /// the remote has it, or else from the local `base` branch.
+    pub fn ensure_branch(&self, branch: &str, base: &str) -> Result<()> {
+        if self.repo.find_branch(branch, BranchType::Local).is_err() {
+            let start = match self.repo.find_branch(&format!("origin/{}", branch), BranchType::Remote) {
+                Ok(remote) => remote.get().peel_to_commit()?,
+                Err(_) => self
+                    .repo
+                    .find_branch(base, BranchType::Local)
+                    .with_context(|| format!("No branch '{}' to start '{}' from", base, branch))?
+                    .get()
+                    .peel_to_commit()?,
+            };
+            self.repo.branch(branch, &start, false)?;
+        }
+        self.checkout_branch(branch, false)
+    }
+    
     pub fn fetch_and_pull(&self, branch: &str) -> Result<()> {
-        let mut remote = self.repo.find_remote("origin")?;
-        
-        let mut fetch_options = FetchOptions::new();
-        let mut callbacks = RemoteCallbacks::new();
-        callbacks.credentials(|_url, username_from_url, _allowed_types| {
-            Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"))
-        });
-        fetch_options.remote_callbacks(callbacks);
-        
-        remote.fetch(&[branch], Some(&mut fetch_options), None)?;
+        if offline::is_offline() {
+            output::info(format!("📴 Offline; using '{}' as last fetched", branch));
+            return Ok(());
+        }
+        self.fetch_with_fallback(branch)?;
         
         let fetch_head = self.repo.find_reference("FETCH_HEAD")?;
         let fetch_commit = self.repo.reference_to_annotated_commit(&fetch_head)?;
@@ -103,24 +367,25 @@ impl GitManager {
         
         if analysis.0.is_fast_forward() {
             let refname = format!("refs/heads/{}", branch);
+            // Check out the new tree before moving the ref; checking out HEAD
+            // afterwards would compare the tree with itself and write nothing.
+            let target = self.repo.find_object(fetch_commit.id(), None)?;
+            self.repo.checkout_tree(&target, None)?;
             let mut reference = self.repo.find_reference(&refname)?;
             reference.set_target(fetch_commit.id(), "Fast-forward")?;
             self.repo.set_head(&refname)?;
-            self.repo.checkout_head(None)?;
         } else if analysis.0.is_normal() {
             let head_commit = self.repo.reference_to_annotated_commit(&self.repo.head()?)?;
             self.repo.merge(&[&fetch_commit], None, None)?;
             
-            let signature = Signature::now("zshrcman", "zshrcman@localhost")?;
             let tree_id = self.repo.index()?.write_tree()?;
             let tree = self.repo.find_tree(tree_id)?;
             let parent_commit = self.repo.find_commit(head_commit.id())?;
             let fetch_commit_obj = self.repo.find_commit(fetch_commit.id())?;
             
-            self.repo.commit(
+            self.create_commit(
                 Some("HEAD"),
-                &signature,
-                &signature,
+                &self.signature()?,
                 "Merge from origin",
                 &tree,
                 &[&parent_commit, &fetch_commit_obj],
@@ -130,14 +395,112 @@ impl GitManager {
         Ok(())
     }
     
+    /// Fetches `branch` from origin, or from the first mirror that answers
+    /// when origin can't be reached.
+    fn fetch_with_fallback(&self, branch: &str) -> Result<()> {
+        let origin_error = match self.fetch_from("origin", None, branch) {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        
+        for (name, mirror) in &self.mirrors {
+            match self.fetch_from(name, Some(mirror), branch) {
+                Ok(()) => {
+                    output::warn(format!("⚠️  origin unreachable ({:#}); fetched from mirror '{}'", origin_error, name));
+                    return Ok(());
+                }
+                Err(e) => output::warn(format!("⚠️  Mirror '{}' unreachable too: {:#}", name, e)),
+            }
+        }
+        
+        Err(origin_error)
+    }
+    
+    fn fetch_from(&self, remote_name: &str, mirror: Option<&RemoteConfig>, branch: &str) -> Result<()> {
+        let mut remote = self.repo.find_remote(remote_name)?;
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(credentials(mirror));
+        
+        remote
+            .fetch(&[branch], Some(&mut fetch_options), None)
+            .with_context(|| format!("Failed to fetch from '{}'", remote_name))
+    }
+    
+    /// Pushes `branch` to origin and every pushable mirror, replacing what's
+    /// there when `force` is set. A failing mirror only warns; a failing
+    /// origin is an error. Offline, nothing is pushed.
+    pub fn push(&self, branch: &str, force: bool) -> Result<()> {
+        if offline::is_offline() {
+            println!("📴 Offline; '{}' not pushed", branch);
+            return Ok(());
+        }
+        
+        let refspec = format!("{}refs/heads/{}", if force { "+" } else { "" }, branch);
+        self.push_everywhere(&refspec)
+    }
+    
+    /// Pushes `refspec` to origin, then to the pushable mirrors, which only
+    /// warn on failure.
+    fn push_everywhere(&self, refspec: &str) -> Result<()> {
+        let result = self.push_to("origin", None, refspec);
+        for (name, mirror) in self.mirrors.iter().filter(|(_, mirror)| mirror.push) {
+            if let Err(e) = self.push_to(name, Some(mirror), refspec) {
+                output::warn(format!("⚠️  {:#}", e));
+            }
+        }
+        result
+    }
+    
+    fn push_to(&self, remote_name: &str, mirror: Option<&RemoteConfig>, refspec: &str) -> Result<()> {
+        let mut remote = self.repo.find_remote(remote_name)?;
+        let mut push_options = PushOptions::new();
+        push_options.remote_callbacks(credentials(mirror));
+        
+        remote
+            .push(&[refspec], Some(&mut push_options))
+            .with_context(|| format!("Failed to push to '{}'", remote_name))
+    }
+    
+    /// Deletes `branch` here and, when origin has it, on origin and the
+    /// pushable mirrors. Offline, only the local branch is deleted.
+    pub fn delete_branch(&self, branch: &str) -> Result<()> {
+        let local = self.repo.find_branch(branch, BranchType::Local).ok();
+        if local.as_ref().is_some_and(|local| local.is_head()) {
+            anyhow::bail!("'{}' is checked out", branch);
+        }
+        
+        let tracking = format!("origin/{}", branch);
+        if self.repo.find_branch(&tracking, BranchType::Remote).is_ok() {
+            if offline::is_offline() {
+                println!("📴 Offline; '{}' is left on origin", branch);
+            } else {
+                self.push_everywhere(&format!(":refs/heads/{}", branch))?;
+                // The push usually drops the tracking branch already.
+                if let Ok(mut tracking) = self.repo.find_branch(&tracking, BranchType::Remote) {
+                    tracking.delete()?;
+                }
+            }
+        }
+        if let Some(mut local) = local {
+            local.delete()?;
+        }
+        Ok(())
+    }
+    
+    /// Commits the index and pushes `branch` to origin and every pushable
+    /// mirror. A failing mirror only warns; a failing origin is an error.
     pub fn commit_and_push(&self, message: &str, branch: &str) -> Result<()> {
+        self.commit(message)?;
+        self.push(branch, false)
+    }
+    
+    /// Commits the index onto HEAD without pushing.
+    pub fn commit(&self, message: &str) -> Result<()> {
         let mut index = self.repo.index()?;
         
         let tree_id = index.write_tree()?;
         let tree = self.repo.find_tree(tree_id)?;
         
-        let signature = Signature::now("zshrcman", "zshrcman@localhost")?;
-        
         let parent_commit = if let Ok(head) = self.repo.head() {
             let oid = head.target().context("No HEAD target")?;
             Some(self.repo.find_commit(oid)?)
@@ -151,47 +514,378 @@ impl GitManager {
             vec![]
         };
         
-        self.repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            message,
-            &tree,
-            &parent_commits,
-        )?;
+        self.create_commit(Some("HEAD"), &self.signature()?, message, &tree, &parent_commits)?;
+        Ok(())
+    }
+    
+    /// Fetches all branches and tags from origin and checks out `reference`
+    /// (a branch, tag or commit) detached, or origin's default branch when it
+    /// is `None`. Local changes are discarded. Returns the commit id.
+    pub fn fetch_and_checkout(&self, url: &str, reference: Option<&str>) -> Result<String> {
+        self.repo.remote_set_url("origin", url)?;
+        // Offline, check out `reference` as it was last fetched.
+        if !offline::is_offline() {
+            let mut remote = self.repo.find_remote("origin")?;
+            let mut fetch_options = FetchOptions::new();
+            fetch_options.remote_callbacks(credentials(None));
+            remote
+                .fetch(
+                    &["+refs/heads/*:refs/remotes/origin/*", "+refs/tags/*:refs/tags/*"],
+                    Some(&mut fetch_options),
+                    None,
+                )
+                .with_context(|| format!("Failed to fetch {}", url))?;
+        }
         
-        let mut remote = self.repo.find_remote("origin")?;
-        let mut push_options = PushOptions::new();
-        let mut callbacks = RemoteCallbacks::new();
-        callbacks.credentials(|_url, username_from_url, _allowed_types| {
-            Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"))
-        });
-        push_options.remote_callbacks(callbacks);
+        let reference = reference.unwrap_or("HEAD");
+        let object = [
+            format!("refs/remotes/origin/{}", reference),
+            format!("refs/tags/{}", reference),
+            reference.to_string(),
+        ]
+        .iter()
+        .find_map(|spec| self.repo.revparse_single(spec).ok())
+        .with_context(|| format!("'{}' is not a branch, tag or commit of {}", reference, url))?;
         
-        remote.push(&[&format!("refs/heads/{}", branch)], Some(&mut push_options))?;
+        let commit = object.peel_to_commit()?;
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout.force();
+        self.repo.checkout_tree(commit.as_object(), Some(&mut checkout))?;
+        self.repo.set_head_detached(commit.id())?;
         
+        Ok(commit.id().to_string())
+    }
+    
+    /// The checked-out branch, or `None` for a detached or unborn HEAD.
+    pub fn current_branch(&self) -> Option<String> {
+        let head = self.repo.head().ok()?;
+        if !head.is_branch() {
+            return None;
+        }
+        head.shorthand().map(String::from)
+    }
+    
+    pub fn head_id(&self) -> Option<String> {
+        let commit = self.repo.head().ok()?.peel_to_commit().ok()?;
+        Some(commit.id().to_string())
+    }
+    
+    /// `device/*` branches, local or fetched from origin, with the time of
+    /// their latest commit.
+    pub fn device_branches(&self) -> Result<Vec<(String, DateTime<Utc>)>> {
+        let mut latest: HashMap<String, DateTime<Utc>> = HashMap::new();
+        
+        for branch in self.repo.branches(None)? {
+            let (branch, _) = branch?;
+            let Some(name) = branch.name()? else {
+                continue;
+            };
+            let name = name.strip_prefix("origin/").unwrap_or(name);
+            if !name.starts_with("device/") {
+                continue;
+            }
+            
+            let commit = branch.get().peel_to_commit()?;
+            let time = commit_time(&commit);
+            let entry = latest.entry(name.to_string()).or_insert(time);
+            if time > *entry {
+                *entry = time;
+            }
+        }
+        
+        let mut branches: Vec<(String, DateTime<Utc>)> = latest.into_iter().collect();
+        branches.sort();
+        Ok(branches)
+    }
+    
+    /// When each file under `prefix` was last changed on the current branch,
+    /// looking back at most `max_commits` commits.
+    pub fn last_changes(&self, prefix: &str, max_commits: usize) -> Result<HashMap<String, DateTime<Utc>>> {
+        let mut changes = HashMap::new();
+        
+        let mut revwalk = self.repo.revwalk()?;
+        if revwalk.push_head().is_err() {
+            return Ok(changes);
+        }
+        
+        for oid in revwalk.take(max_commits) {
+            let commit = self.repo.find_commit(oid?)?;
+            let tree = commit.tree()?;
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+            
+            let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+            for delta in diff.deltas() {
+                let Some(path) = delta.new_file().path().and_then(|p| p.to_str()) else {
+                    continue;
+                };
+                if path.starts_with(prefix) {
+                    changes.entry(path.to_string()).or_insert_with(|| commit_time(&commit));
+                }
+            }
+        }
+        
+        Ok(changes)
+    }
+    
+    /// Stages one file, given relative to the repo root.
+    pub fn add_path(&self, path: &Path) -> Result<()> {
+        let mut index = self.repo.index()?;
+        index.add_path(path)?;
+        index.write()?;
+        Ok(())
+    }
+    
+    /// Stages files and directories under `paths`, including deletions.
+    pub fn stage_paths(&self, paths: &[PathBuf]) -> Result<()> {
+        let mut index = self.repo.index()?;
+        index.add_all(paths, git2::IndexAddOption::DEFAULT, None)?;
+        index.update_all(paths, None)?;
+        index.write()?;
         Ok(())
     }
     
     pub fn add_all(&self) -> Result<()> {
         let mut index = self.repo.index()?;
-        index.add_all(&["."], git2::IndexAddOption::DEFAULT, None)?;
+        index.add_all(["."], git2::IndexAddOption::DEFAULT, None)?;
         index.write()?;
         Ok(())
     }
     
-    pub fn sync(&self, main_branch: &str, device_branch: &str) -> Result<()> {
-        self.fetch_and_pull(main_branch)?;
+    /// Fetches both branches and compares them with origin, leaving the
+    /// local branches and working tree alone. Offline or when origin can't be
+    /// reached the comparison uses whatever was fetched last.
+    pub fn preview_sync(&self, main_branch: &str, device_branch: &str) -> Result<SyncPreview> {
+        for branch in [main_branch, device_branch].into_iter().filter(|_| !offline::is_offline()) {
+            if let Err(e) = self.fetch_tracking(branch) {
+                output::warn(format!("⚠️  Couldn't fetch '{}' ({:#}); comparing with the last fetch", branch, e));
+            }
+        }
         
-        self.checkout_branch(main_branch, false)?;
+        let main = self.divergence(main_branch)?;
+        let device = self.divergence(device_branch)?;
+        
+        // Sync rebases onto the freshly pulled main branch, so compare with
+        // origin's copy when there is one.
+        let upstream = self
+            .branch_commit(&format!("origin/{}", main_branch), BranchType::Remote)
+            .or_else(|| self.branch_commit(main_branch, BranchType::Local));
+        let local = self.branch_commit(device_branch, BranchType::Local);
+        let (incoming, outgoing) = match (upstream, local) {
+            (Some(upstream), Some(local)) => {
+                let base = self.repo.find_commit(self.repo.merge_base(upstream.id(), local.id())?)?;
+                (self.changed_between(&base, &upstream)?, self.changed_between(&base, &local)?)
+            }
+            _ => (Vec::new(), Vec::new()),
+        };
+        
+        Ok(SyncPreview {
+            uncommitted: self.changed_files()?,
+            main,
+            device,
+            incoming,
+            outgoing,
+        })
+    }
+    
+    /// Updates `refs/remotes/origin/<branch>` without merging anything,
+    /// dropping it when origin no longer has the branch. `branch` may be a
+    /// pattern like `device/*`.
+    pub fn fetch_tracking(&self, branch: &str) -> Result<()> {
+        let mut remote = self.repo.find_remote("origin")?;
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(credentials(None)).prune(git2::FetchPrune::On);
+        
+        let refspec = format!("+refs/heads/{0}:refs/remotes/origin/{0}", branch);
+        remote
+            .fetch(&[&refspec], Some(&mut fetch_options), None)
+            .context("Failed to fetch from 'origin'")
+    }
+    
+    /// Contents of `path` at the tip of `branch`, preferring whichever of the
+    /// local branch and `origin/<branch>` was committed to last. `None` when
+    /// the branch doesn't exist or has no such file.
+    pub fn read_file(&self, branch: &str, path: &Path) -> Result<Option<String>> {
+        let local = self.branch_commit(branch, BranchType::Local);
+        let remote = self.branch_commit(&format!("origin/{}", branch), BranchType::Remote);
+        let commit = match (local, remote) {
+            (Some(local), Some(remote)) => {
+                if commit_time(&remote) > commit_time(&local) { remote } else { local }
+            }
+            (Some(commit), None) | (None, Some(commit)) => commit,
+            (None, None) => return Ok(None),
+        };
+        
+        let entry = match commit.tree()?.get_path(path) {
+            Ok(entry) => entry,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let blob = entry.to_object(&self.repo)?.peel_to_blob()?;
+        Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+    }
+    
+    fn branch_commit(&self, name: &str, branch_type: BranchType) -> Option<git2::Commit<'_>> {
+        self.repo.find_branch(name, branch_type).ok()?.get().peel_to_commit().ok()
+    }
+    
+    /// The commit `origin/<branch>` was at when it was last fetched.
+    pub fn tracking_id(&self, branch: &str) -> Option<String> {
+        self.branch_commit(&format!("origin/{}", branch), BranchType::Remote)
+            .map(|commit| commit.id().to_string())
+    }
+    
+    /// Compares `branch` with `origin/<branch>` as of the last fetch.
+    pub fn divergence(&self, branch: &str) -> Result<Divergence> {
+        let local = self.branch_commit(branch, BranchType::Local);
+        let remote = self.branch_commit(&format!("origin/{}", branch), BranchType::Remote);
+        let ahead_behind = match (local, remote) {
+            (Some(local), Some(remote)) => Some(self.repo.graph_ahead_behind(local.id(), remote.id())?),
+            (None, Some(remote)) => Some((0, self.count_commits(remote.id())?)),
+            (_, None) => None,
+        };
+        Ok(Divergence {
+            branch: branch.to_string(),
+            ahead_behind,
+        })
+    }
+    
+    fn count_commits(&self, tip: git2::Oid) -> Result<usize> {
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(tip)?;
+        Ok(revwalk.count())
+    }
+    
+    fn changed_between(&self, from: &git2::Commit, to: &git2::Commit) -> Result<Vec<FileChange>> {
+        let mut diff = self.repo.diff_tree_to_tree(Some(&from.tree()?), Some(&to.tree()?), None)?;
+        diff.find_similar(None)?;
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| {
+                let status = match delta.status() {
+                    git2::Delta::Added => 'A',
+                    git2::Delta::Deleted => 'D',
+                    git2::Delta::Renamed => 'R',
+                    _ => 'M',
+                };
+                let file = if status == 'D' { delta.old_file() } else { delta.new_file() };
+                let path = file.path()?.to_string_lossy().into_owned();
+                Some(FileChange { status, path })
+            })
+            .collect())
+    }
+    
+    /// Pulls `main_branch`, rebases `device_branch` onto it and pushes it,
+    /// or only one half of that per `mode`. Files changed on both are
+    /// resolved according to `strategy`; with `Abort` the rebase is undone
+    /// and the branch left as it was.
+    ///
+    /// Uncommitted edits, e.g. a group file changed by hand, are committed to
+    /// the device branch first so the rebase carries them along. If the sync
+    /// fails they are put back as uncommitted changes. A push that fails
+    /// after a clean rebase is a git error too, but the rebase is kept for
+    /// the next sync to push.
+    pub fn sync(&self, main_branch: &str, device_branch: &str, strategy: ConflictStrategy, mode: SyncMode) -> Result<()> {
+        if mode == SyncMode::PushOnly {
+            offline::require_online("push")?;
+        }
+        let autosaved = self.autosave(device_branch)?;
+        
+        let result = self.sync_in_mode(main_branch, device_branch, strategy, mode);
+        if let (Err(_), Some(previous)) = (&result, autosaved) {
+            if let Err(e) = self.restore_autosave(device_branch, previous) {
+                return result.context(format!("Also failed to restore your local changes: {:#}", e));
+            }
+            println!("   Your local changes are uncommitted again");
+        }
+        result?;
+        
+        if mode != SyncMode::Both {
+            return Ok(());
+        }
+        // The device branch is this machine's own, and a rebase rewrites it.
+        self.push(device_branch, true)
+            .with_context(|| format!("Synced locally, but pushing '{}' failed", device_branch))
+            .kind(ErrorKind::Git)
+    }
+    
+    /// Files with uncommitted changes, untracked ones included.
+    pub fn changed_files(&self) -> Result<Vec<String>> {
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let statuses = self.repo.statuses(Some(&mut options))?;
+        Ok(statuses
+            .iter()
+            .filter(|entry| !entry.status().is_ignored())
+            .filter_map(|entry| entry.path().map(String::from))
+            .collect())
+    }
+    
+    /// Commits uncommitted changes to `device_branch`, returning the commit
+    /// it had before, or `None` when there was nothing to commit.
+    fn autosave(&self, device_branch: &str) -> Result<Option<git2::Oid>> {
+        let files = self.changed_files()?;
+        if files.is_empty() {
+            return Ok(None);
+        }
+        let Ok(head) = self.repo.head() else {
+            // Nothing committed yet; the first sync has nothing to rebase.
+            return Ok(None);
+        };
+        if head.shorthand() != Some(device_branch) {
+            anyhow::bail!(
+                "Uncommitted changes on '{}' ({}); commit or discard them before syncing",
+                head.shorthand().unwrap_or("HEAD"),
+                files.join(", ")
+            );
+        }
+        let parent = head.peel_to_commit()?;
+        
+        let mut index = self.repo.index()?;
+        index.add_all(["."], git2::IndexAddOption::DEFAULT, None)?;
+        index.update_all(["."], None)?;
+        index.write()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
         
+        let shown: Vec<&str> = files.iter().take(3).map(String::as_str).collect();
+        let more = if files.len() > shown.len() { format!(" and {} more", files.len() - shown.len()) } else { String::new() };
+        let message = format!("Save local changes to {}{} before sync\n\n{}", shown.join(", "), more, files.join("\n"));
+        self.create_commit(Some("HEAD"), &self.signature()?, &message, &tree, &[&parent])?;
+        
+        println!("   📝 Committed local changes to {} before syncing", files.join(", "));
+        Ok(Some(parent.id()))
+    }
+    
+    /// Moves `device_branch` back to `previous`, keeping the autosaved
+    /// changes in the working tree.
+    fn restore_autosave(&self, device_branch: &str, previous: git2::Oid) -> Result<()> {
         self.checkout_branch(device_branch, false)?;
+        let commit = self.repo.find_commit(previous)?;
+        self.repo.reset(commit.as_object(), git2::ResetType::Mixed, None)?;
+        Ok(())
+    }
+    
+    /// Rebases the device branch onto the main branch, or for `PushOnly`
+    /// just pushes it as it is.
+    fn sync_in_mode(&self, main_branch: &str, device_branch: &str, strategy: ConflictStrategy, mode: SyncMode) -> Result<()> {
+        if mode == SyncMode::PushOnly {
+            self.checkout_branch(device_branch, false)?;
+            return self.push(device_branch, true);
+        }
+        self.rebase_onto_main(main_branch, device_branch, strategy)
+    }
+    
+    fn rebase_onto_main(&self, main_branch: &str, device_branch: &str, strategy: ConflictStrategy) -> Result<()> {
+        // Pull into the main branch itself, not whatever is checked out.
+        self.checkout_branch(main_branch, false)?;
+        self.fetch_and_pull(main_branch)?;
         
-        let main_ref = self.repo.revparse_single(&format!("refs/heads/{}", main_branch))?;
-        let main_commit = main_ref.peel_to_commit()?;
+        self.checkout_branch(device_branch, false)?;
         
         let mut rebase_opts = git2::RebaseOptions::new();
-        let signature = Signature::now("zshrcman", "zshrcman@localhost")?;
+        let signature = self.signature()?;
         
         let annotated = self.repo.reference_to_annotated_commit(
             &self.repo.find_reference(&format!("refs/heads/{}", main_branch))?
@@ -199,15 +893,213 @@ impl GitManager {
         
         let mut rebase = self.repo.rebase(None, Some(&annotated), None, Some(&mut rebase_opts))?;
         
-        while let Some(_op) = rebase.next() {
-            if let Err(e) = rebase.commit(None, &signature, None) {
+        while let Some(operation) = rebase.next() {
+            let result = operation
+                .map_err(anyhow::Error::from)
+                .and_then(|_| self.resolve_conflicts(strategy, main_branch, device_branch));
+            if let Err(e) = result {
                 rebase.abort()?;
-                return Err(anyhow::anyhow!("Rebase failed: {}", e));
+                return Err(e);
+            }
+            
+            match rebase.commit(None, &signature, None) {
+                Ok(_) => {}
+                // The main branch already has this change.
+                Err(e) if e.code() == ErrorCode::Applied => {}
+                Err(e) => {
+                    rebase.abort()?;
+                    return Err(anyhow::Error::new(e).context("Rebase failed"));
+                }
             }
         }
         
         rebase.finish(Some(&signature))?;
+        self.sign_commits_since(annotated.id())?;
+        
+        Ok(())
+    }
+    
+    /// Settles every conflicted file in the index by keeping one side. During
+    /// the rebase "ours" in git terms is the main branch being built on and
+    /// "theirs" the device commit being replayed, so they're swapped here.
+    fn resolve_conflicts(&self, strategy: ConflictStrategy, main_branch: &str, device_branch: &str) -> Result<()> {
+        let mut index = self.repo.index()?;
+        if !index.has_conflicts() {
+            return Ok(());
+        }
+        let workdir = self.repo.workdir().context("Cannot resolve conflicts in a bare repository")?.to_path_buf();
+        
+        let conflicts = index.conflicts()?.collect::<Result<Vec<_>, _>>()?;
+        for conflict in conflicts {
+            let (remote, local) = (conflict.our, conflict.their);
+            let Some(path) = [&local, &remote, &conflict.ancestor]
+                .into_iter()
+                .flatten()
+                .next()
+                .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            else {
+                continue;
+            };
+            
+            let keep_local = match strategy {
+                ConflictStrategy::Abort => {
+                    return Err(Conflict(format!(
+                        "{} changed on both {} and {}; rerun with --strategy ours, theirs or interactive",
+                        path, device_branch, main_branch
+                    ))
+                    .into())
+                }
+                ConflictStrategy::Ours => true,
+                ConflictStrategy::Theirs => false,
+                ConflictStrategy::Interactive => {
+                    let local = self.blob_text(local.as_ref())?;
+                    let remote = self.blob_text(remote.as_ref())?;
+                    choose_side(&path, &local, &remote, device_branch, main_branch)?
+                }
+            };
+            
+            let target = workdir.join(&path);
+            let relative = Path::new(&path);
+            match if keep_local { local } else { remote } {
+                Some(entry) => {
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(&target, self.repo.find_blob(entry.id)?.content())?;
+                    // Adding the path also clears its conflict.
+                    index.add_path(relative)?;
+                }
+                None => {
+                    if target.exists() {
+                        fs::remove_file(&target)?;
+                    }
+                    index.remove_path(relative)?;
+                }
+            }
+            println!(
+                "   Resolved {} with {}'s version",
+                path,
+                if keep_local { device_branch } else { main_branch }
+            );
+        }
         
+        index.write()?;
         Ok(())
     }
+    
+    /// The text of one side of a conflict; `None` if that side deleted it.
+    fn blob_text(&self, entry: Option<&IndexEntry>) -> Result<Option<String>> {
+        entry
+            .map(|entry| Ok(String::from_utf8_lossy(self.repo.find_blob(entry.id)?.content()).into_owned()))
+            .transpose()
+    }
+}
+
+/// Shows the lines each side has that the other doesn't and asks which
+/// version of `path` to keep. Returns true for the device's.
+fn choose_side(path: &str, local: &Option<String>, remote: &Option<String>, device_branch: &str, main_branch: &str) -> Result<bool> {
+    println!();
+    println!("⚔️  Conflict in {}", path);
+    match (local, remote) {
+        (Some(local), Some(remote)) => {
+            let local_lines: Vec<&str> = local.lines().collect();
+            let remote_lines: Vec<&str> = remote.lines().collect();
+            for line in remote_lines.iter().filter(|line| !local_lines.contains(line)) {
+                println!("   - {}  ({})", line, main_branch);
+            }
+            for line in local_lines.iter().filter(|line| !remote_lines.contains(line)) {
+                println!("   + {}  ({})", line, device_branch);
+            }
+        }
+        (Some(_), None) => println!("   Deleted on {}, changed on {}", main_branch, device_branch),
+        (None, Some(_)) => println!("   Deleted on {}, changed on {}", device_branch, main_branch),
+        (None, None) => {}
+    }
+    
+    let choices = [
+        format!("Keep {}'s version", device_branch),
+        format!("Keep {}'s version", main_branch),
+        "Abort the sync".to_string(),
+    ];
+    // Without a terminal to ask on, give up rather than guess.
+    match prompt::select("Which version?", &choices, 2)? {
+        0 => Ok(true),
+        1 => Ok(false),
+        _ => Err(errors::fail(ErrorKind::Aborted, format!("Sync aborted at {}", path))),
+    }
+}
+
+/// Credentials for origin (the SSH agent) or a mirror's configured auth.
+fn credentials(mirror: Option<&RemoteConfig>) -> RemoteCallbacks<'static> {
+    let mirror = mirror.cloned().unwrap_or_default();
+    let mut callbacks = RemoteCallbacks::new();
+    
+    callbacks.credentials(move |_url, username_from_url, allowed_types| {
+        if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Some(var) = &mirror.token_env {
+                let token = std::env::var(var)
+                    .map_err(|_| git2::Error::from_str(&format!("{} is not set", var)))?;
+                let username = mirror.username.as_deref().or(username_from_url).unwrap_or("git");
+                return Cred::userpass_plaintext(username, &token);
+            }
+        }
+        
+        let username = username_from_url.unwrap_or("git");
+        match &mirror.ssh_key {
+            Some(key) => Cred::ssh_key(username, None, key, None),
+            None => Cred::ssh_key_from_agent(username),
+        }
+    });
+    callbacks
+}
+
+/// Detached signature of a commit buffer, made the way `git commit -S` does.
+fn sign_buffer(format: SigningFormat, key: Option<&str>, buffer: &str) -> Result<String> {
+    let mut command = match format {
+        SigningFormat::Gpg => {
+            let mut command = Command::new("gpg");
+            command.args(["--status-fd=2", "-bsa"]);
+            if let Some(key) = key {
+                command.args(["-u", key]);
+            }
+            command
+        }
+        SigningFormat::Ssh => {
+            let key = key.context("SSH signing needs repository.identity.signing_key or git's user.signingkey")?;
+            let key = match key.strip_prefix("~/") {
+                Some(rest) => preflight::home_dir()?.join(rest),
+                None => key.into(),
+            };
+            let mut command = Command::new("ssh-keygen");
+            command.args(["-Y", "sign", "-n", "git", "-f"]).arg(key);
+            command
+        }
+    };
+    
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {} to sign the commit", program))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(buffer.as_bytes())?;
+    }
+    
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} couldn't sign the commit: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).context("Signature is not valid UTF-8")
+}
+
+fn commit_time(commit: &git2::Commit) -> DateTime<Utc> {
+    Utc.timestamp_opt(commit.time().seconds(), 0)
+        .single()
+        .unwrap_or_default()
 }
\ No newline at end of file
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Directory of a group's repo holding the exported keys of `gpg_keys`.
+pub const KEYS_DIR: &str = "gpg";
+
+/// How long the signing check waits, passphrase prompt included, before
+/// giving up on an agent that never answers.
+const SIGN_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Makes sure gpg is there before anything is imported.
+pub fn ensure_installed() -> Result<()> {
+    if which::which("gpg").is_err() {
+        anyhow::bail!("gpg is not installed; add gnupg to a package group that installs before 'gpg'");
+    }
+    Ok(())
+}
+
+/// Imports an exported public or (passphrase protected) private key. gpg
+/// keeps the passphrase on private keys, so nothing is asked here.
+pub fn import(key_file: &Path) -> Result<()> {
+    let output = Command::new("gpg")
+        .args(["--batch", "--import"])
+        .arg(key_file)
+        .output()
+        .context("Failed to run gpg")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "gpg --import {} failed: {}",
+            key_file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// The `git config` settings that make git sign commits with `key`.
+pub fn git_settings(key: &str) -> Vec<(&'static str, String)> {
+    vec![
+        ("user.signingkey", key.to_string()),
+        ("commit.gpgsign", "true".to_string()),
+    ]
+}
+
+/// Writes [`git_settings`] into the git config file `gitconfig`.
+pub fn configure_git(gitconfig: &Path, key: &str) -> Result<()> {
+    for (name, value) in git_settings(key) {
+        let status = Command::new("git")
+            .arg("config")
+            .arg("--file")
+            .arg(gitconfig)
+            .args([name, &value])
+            .status()
+            .context("Failed to run git")?;
+        if !status.success() {
+            anyhow::bail!("git config {} failed", name);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that gpg has the secret part of `key` and that the agent can sign
+/// with it, which asks for the passphrase the way git will.
+pub fn verify(key: &str) -> Result<()> {
+    let listed = Command::new("gpg")
+        .args(["--batch", "--list-secret-keys", key])
+        .output()
+        .context("Failed to run gpg")?;
+    if !listed.status.success() {
+        anyhow::bail!("gpg has no secret key '{}'; add its exported private key to gpg_keys", key);
+    }
+    
+    let _ = Command::new("gpgconf").args(["--launch", "gpg-agent"]).status();
+    let mut sign = Command::new("gpg")
+        .args(["--clearsign", "--local-user", key])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .context("Failed to run gpg")?;
+    if let Some(mut stdin) = sign.stdin.take() {
+        stdin.write_all(b"zshrcman signing check\n")?;
+    }
+    
+    let deadline = Instant::now() + SIGN_TIMEOUT;
+    let status = loop {
+        if let Some(status) = sign.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = sign.kill();
+            let _ = sign.wait();
+            anyhow::bail!(
+                "gpg did not sign with '{}' within {}s; check that gpg-agent has a pinentry (GPG_TTY=$(tty) for terminals)",
+                key,
+                SIGN_TIMEOUT.as_secs()
+            );
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+    if !status.success() {
+        anyhow::bail!(
+            "gpg could not sign with '{}'; check that gpg-agent runs and has a pinentry (GPG_TTY=$(tty) for terminals)",
+            key
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::PathBuf;
+use crate::modules::config::ConfigManager;
+use crate::modules::facts::Facts;
+use crate::modules::git_mgr::GitManager;
+use crate::modules::install;
+use crate::modules::lazy_load;
+use crate::modules::output;
+
+/// Commits moved or copied group files to the device branch. The config
+/// change already happened, so a failure only warns.
+pub fn commit_change(config_mgr: &ConfigManager, changed: &[PathBuf], message: &str) {
+    if changed.is_empty() {
+        return;
+    }
+    let result = GitManager::open(config_mgr.dotfiles_path())
+        .map(|git_mgr| git_mgr.with_identity(&config_mgr.config.repository.identity))
+        .and_then(|git_mgr| {
+            git_mgr.stage_paths(changed)?;
+            git_mgr.commit(message)
+        });
+    match result {
+        Ok(()) => println!("{}", "✅ Committed; push it with `zshrcman sync`".green()),
+        Err(e) => output::warn(format!("⚠️  Couldn't commit the group files: {:#}", e)),
+    }
+}
+
+/// Prints what group `name` sets up, merged with this device's override,
+/// and how much of it is installed and active.
+pub fn show(config_mgr: &ConfigManager, name: &str) -> Result<()> {
+    let (group, files) = config_mgr.load_merged_group(name)?;
+    let config = &config_mgr.config;
+    
+    println!("{}", format!("📦 Group '{}'", name).bold());
+    if !group.description.is_empty() {
+        println!("  {}", group.description);
+    }
+    for path in &files {
+        let relative = path.strip_prefix(config_mgr.dotfiles_path()).unwrap_or(path);
+        println!("  {}", relative.display().to_string().dimmed());
+    }
+    
+    let enabled = config_mgr.get_ordered_groups().iter().any(|g| g == name);
+    let install = match config.status.get(name) {
+        Some(status) if status.success && status.installed => "installed".green(),
+        Some(status) if !status.success => "install failed".red(),
+        _ => "not installed".yellow(),
+    };
+    println!(
+        "  Status: {}, {}",
+        if enabled { "enabled".green() } else { "disabled".yellow() },
+        install
+    );
+    if group.priority != 0 {
+        println!("  Priority: {}", group.priority);
+    }
+    if !group.target_tags.is_empty() {
+        println!("  Target tags: {}", group.target_tags.join(", "));
+    }
+    if let Some(condition) = &group.when {
+        if let Err(reason) = condition.check(&Facts::gather()) {
+            println!("  {} {}", "Skipped on this machine:".yellow(), reason);
+        }
+    }
+    
+    if !group.packages.is_empty() {
+        let backend = group.installer.clone().unwrap_or_else(|| config_mgr.resolve_group(name).1.to_string());
+        let registry = install::installer_registry(config_mgr);
+        let installer = registry.get(&backend);
+        
+        match installer {
+            Some(_) => println!("\n{}", format!("Packages ({}):", backend).bold()),
+            None => println!("\n{} {}", "Packages:".bold(), "no installer registered".yellow()),
+        }
+        for (package, id) in group.packages.iter().zip(group.package_ids()) {
+            let marker = match installer.map(|installer| installer.is_installed(&id).unwrap_or(false)) {
+                Some(true) => "✅",
+                Some(false) => "❌",
+                None => "  ",
+            };
+            let held = if config_mgr.is_held(&id) { " 📌 held" } else { "" };
+            let id = if id != *package { format!(" ({})", id) } else { String::new() };
+            println!("  {} {}{}{}", marker, package, id, held);
+        }
+    }
+    
+    if !group.aliases.is_empty() {
+        println!("\n{}", "Aliases:".bold());
+        let active = config.aliases.get(name).map(|aliases| &aliases.active);
+        for alias in &group.aliases {
+            let marker = if active.is_some_and(|active| active.contains(alias)) { "✅" } else { "⭕" };
+            match &alias.description {
+                Some(description) => println!("  {} {}  {}", marker, alias, description.dimmed()),
+                None => println!("  {} {}", marker, alias),
+            }
+        }
+    }
+    
+    for (verb, options) in [("setopt", &group.setopt), ("unsetopt", &group.unsetopt)] {
+        if !options.is_empty() {
+            println!("\n{} {}", format!("{}:", verb).bold(), options.join(" "));
+        }
+    }
+    if !group.bindkey.is_empty() {
+        println!("\n{}", "Key bindings:".bold());
+        for (key, widget) in &group.bindkey {
+            println!("  {} → {}", key, widget);
+        }
+    }
+    
+    if !group.functions.is_empty() {
+        println!("\n{}", "Functions:".bold());
+        for function in &group.functions {
+            let shells = if function.shells.is_empty() { String::new() } else { format!(" ({})", function.shells.join(", ")) };
+            match &function.description {
+                Some(description) => println!("  {}(){}  {}", function.name, shells, description.dimmed()),
+                None => println!("  {}(){}", function.name, shells),
+            }
+        }
+    }
+    
+    if !group.lazy.is_empty() {
+        println!("\n{}", "Lazy loaded:".bold());
+        for entry in &group.lazy {
+            match lazy_load::resolve(entry) {
+                Some(stub) => println!("  {} ({})", entry.name, stub.commands.join(", ")),
+                None => println!("  {} {}", entry.name, "(needs commands and init)".yellow()),
+            }
+        }
+    }
+    
+    for (title, items) in [("Scripts:", &group.scripts), ("Completions:", &group.completions), ("SSH keys:", &group.ssh_keys), ("GPG keys:", &group.gpg_keys)] {
+        if !items.is_empty() {
+            println!("\n{}", title.bold());
+            for item in items {
+                println!("  {}", item);
+            }
+        }
+    }
+    
+    if !group.cron.is_empty() {
+        println!("\n{}", "Cron jobs:".bold());
+        for job in &group.cron {
+            match &job.description {
+                Some(description) => println!("  {} {}  {}", job.schedule, job.command, description.dimmed()),
+                None => println!("  {} {}", job.schedule, job.command),
+            }
+        }
+    }
+    
+    if !group.services.is_empty() {
+        println!("\n{}", "Services:".bold());
+        for service in &group.services {
+            let enable = if service.enable { "" } else { " (not enabled)" };
+            println!("  {}{}", service.source.display(), enable);
+        }
+    }
+    
+    if !group.fonts.is_empty() {
+        println!("\n{}", "Fonts:".bold());
+        for font in &group.fonts {
+            let from = font.url.clone().or_else(|| font.source.as_ref().map(|source| source.display().to_string()));
+            println!("  {} ({})", font.name, from.unwrap_or_default());
+        }
+    }
+    
+    if !group.macos_defaults.is_empty() {
+        println!("\n{}", "macOS defaults:".bold());
+        for entry in &group.macos_defaults {
+            println!("  {} {} = {}", entry.domain, entry.key, entry.value);
+        }
+    }
+    
+    if let Some(key) = &group.signing_key {
+        println!("\n{} {}", "Signing key:".bold(), key);
+    }
+    
+    if !group.files.is_empty() {
+        println!("\n{}", "Files:".bold());
+        for mapping in &group.files {
+            let template = if mapping.is_template() { " (template)" } else { "" };
+            println!("  {} → {}{}", mapping.source.display(), mapping.target.display(), template);
+        }
+    }
+    
+    if !group.named_dirs.is_empty() {
+        println!("\n{}", "Named directories:".bold());
+        for (dir_name, path) in &group.named_dirs {
+            println!("  ~{} → {}", dir_name, path);
+        }
+    }
+    
+    if !group.env.is_empty() {
+        println!("\n{}", "Environment:".bold());
+        for (variable, value) in &group.env {
+            println!("  {}={}", variable, value);
+        }
+    }
+    Ok(())
+}
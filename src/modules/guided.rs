@@ -0,0 +1,89 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::io::IsTerminal;
+use crate::modules::prompt;
+
+/// A task on the menu `zshrcman` shows when run without a subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    Status,
+    Install,
+    Sync,
+    SwitchProfile,
+    ListAliases,
+    AddAlias,
+    ToggleAliases,
+    Quit,
+}
+
+impl Task {
+    pub const ALL: [Task; 8] = [
+        Task::Status,
+        Task::Install,
+        Task::Sync,
+        Task::SwitchProfile,
+        Task::ListAliases,
+        Task::AddAlias,
+        Task::ToggleAliases,
+        Task::Quit,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Task::Status => "Show status",
+            Task::Install => "Install groups",
+            Task::Sync => "Sync with the dotfiles repo",
+            Task::SwitchProfile => "Switch profile",
+            Task::ListAliases => "List aliases",
+            Task::AddAlias => "Add an alias",
+            Task::ToggleAliases => "Choose active aliases",
+            Task::Quit => "Quit",
+        }
+    }
+
+    /// The command line that does the same, shown next to the task so the
+    /// menu teaches the subcommands.
+    pub fn command(&self) -> Option<&'static str> {
+        match self {
+            Task::Status => Some("zshrcman status"),
+            Task::Install => Some("zshrcman install"),
+            Task::Sync => Some("zshrcman sync"),
+            Task::SwitchProfile => Some("zshrcman profile switch <name>"),
+            Task::ListAliases => Some("zshrcman alias list"),
+            Task::AddAlias => Some("zshrcman alias add <group> <definition>"),
+            Task::ToggleAliases => Some("zshrcman alias toggle <group>"),
+            Task::Quit => None,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self.command() {
+            Some(command) => format!("{:<28} {}", self.description(), command.dimmed()),
+            None => self.description().to_string(),
+        }
+    }
+}
+
+/// Whether the menu can be shown: stdin and stdout are a terminal and
+/// `--yes` isn't set.
+pub fn available() -> bool {
+    !prompt::is_non_interactive() && std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+pub fn choose() -> Result<Task> {
+    let labels: Vec<String> = Task::ALL.iter().map(Task::label).collect();
+    Ok(Task::ALL[prompt::select("What would you like to do?", &labels, 0)?])
+}
+
+/// Lets the user pick one of `names`, sorted, with `current` preselected.
+/// None if there is nothing to pick.
+pub fn pick(prompt: &str, names: &[String], current: Option<&str>) -> Result<Option<String>> {
+    let mut names = names.to_vec();
+    names.sort();
+    if names.is_empty() {
+        return Ok(None);
+    }
+    let default = current.and_then(|current| names.iter().position(|name| name == current)).unwrap_or(0);
+    let index = prompt::select(prompt, &names, default)?;
+    Ok(Some(names.swap_remove(index)))
+}
@@ -0,0 +1,297 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::modules::config::{self, ConfigManager};
+use crate::modules::diagnostics::{self, REDACTED};
+
+/// Append-only log of config.toml changes, one JSON entry per line.
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// Tables of config.toml that record what is installed on this machine
+/// rather than settings. `undo` leaves them as they are.
+const MACHINE_STATE: [&str; 2] = ["status", "installations"];
+
+/// One CLI action that changed config.toml. `None` contents mean the file
+/// didn't exist. Secret values are stored redacted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub action: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    /// Set on entries written by `undo`, naming the entry they reverted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undoes: Option<u64>,
+}
+
+impl HistoryEntry {
+    /// Lines removed (`-`) and added (`+`) by this change, in file order.
+    /// Lines are matched up in order, so a moved or repeated line shows up
+    /// too.
+    pub fn changed_lines(&self) -> Vec<String> {
+        let before: Vec<&str> = self.before.as_deref().unwrap_or_default().lines().collect();
+        let after: Vec<&str> = self.after.as_deref().unwrap_or_default().lines().collect();
+        
+        // common[i][j]: longest common subsequence of before[i..] and after[j..].
+        let mut common = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+        for i in (0..before.len()).rev() {
+            for j in (0..after.len()).rev() {
+                common[i][j] = if before[i] == after[j] {
+                    common[i + 1][j + 1] + 1
+                } else {
+                    common[i + 1][j].max(common[i][j + 1])
+                };
+            }
+        }
+        
+        let (mut i, mut j) = (0, 0);
+        let mut lines = Vec::new();
+        while i < before.len() || j < after.len() {
+            if i < before.len() && j < after.len() && before[i] == after[j] {
+                i += 1;
+                j += 1;
+            } else if i < before.len() && (j == after.len() || common[i + 1][j] >= common[i][j + 1]) {
+                lines.push(format!("- {}", before[i]));
+                i += 1;
+            } else {
+                lines.push(format!("+ {}", after[j]));
+                j += 1;
+            }
+        }
+        lines
+    }
+}
+
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            path: data_dir.join(HISTORY_FILE),
+        }
+    }
+    
+    /// The history of the standard config, next to the dotfiles checkout.
+    pub fn open() -> Result<Self> {
+        let dotfiles_path = ConfigManager::get_dotfiles_path()?;
+        Ok(Self::new(dotfiles_path.parent().unwrap_or(&dotfiles_path)))
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Corrupt entry on line {} of {}", i + 1, self.path.display()))
+            })
+            .collect()
+    }
+    
+    /// Appends an entry, with secret values redacted, unless `before` and
+    /// `after` are identical.
+    pub fn record(
+        &self,
+        action: &str,
+        before: Option<String>,
+        after: Option<String>,
+        undoes: Option<u64>,
+    ) -> Result<Option<HistoryEntry>> {
+        let before = before.map(|contents| redact(&contents));
+        let after = after.map(|contents| redact(&contents));
+        if before == after {
+            return Ok(None);
+        }
+        
+        let entry = HistoryEntry {
+            id: self.entries()?.last().map_or(1, |last| last.id + 1),
+            timestamp: chrono::Utc::now(),
+            action: action.to_string(),
+            before,
+            after,
+            undoes,
+        };
+        
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(Some(entry))
+    }
+    
+    /// Drops all but the newest `keep` entries. Returns how many were dropped.
+    pub fn prune(&self, keep: usize) -> Result<usize> {
+        let entries = self.entries()?;
+        let dropped = entries.len().saturating_sub(keep);
+        if dropped == 0 {
+            return Ok(0);
+        }
+        
+        let mut contents = String::new();
+        for entry in &entries[dropped..] {
+            contents.push_str(&serde_json::to_string(entry)?);
+            contents.push('\n');
+        }
+        config::write_atomic(&self.path, contents.as_bytes())?;
+        Ok(dropped)
+    }
+    
+    /// The newest change that hasn't been undone yet. Undo entries themselves
+    /// are skipped, so repeated undos walk further back.
+    pub fn last_undoable(&self) -> Result<Option<HistoryEntry>> {
+        let entries = self.entries()?;
+        let undone: HashSet<u64> = entries.iter().filter_map(|entry| entry.undoes).collect();
+        Ok(entries
+            .into_iter()
+            .rev()
+            .find(|entry| entry.undoes.is_none() && !undone.contains(&entry.id)))
+    }
+    
+    /// Restores the settings in config.toml to how they were before the last
+    /// change. What's installed (`MACHINE_STATE`) stays as it is now, and so
+    /// do secrets the entry only has redacted. Refuses if the settings were
+    /// edited since, unless `force` is set.
+    pub fn undo(&self, config_path: &Path, force: bool) -> Result<HistoryEntry> {
+        let target = self.last_undoable()?.context("Nothing to undo")?;
+        let current = read_optional(config_path)?;
+        
+        let redacted = current.as_deref().map(redact);
+        if !force && settings(redacted.as_deref()) != settings(target.after.as_deref()) {
+            anyhow::bail!(
+                "{} has changed since '{}'; use --force to restore the earlier version anyway",
+                config_path.display(),
+                target.action
+            );
+        }
+        
+        let restored = restore(target.before.as_deref(), current.as_deref())?;
+        match &restored {
+            Some(contents) => config::write_atomic(config_path, contents.as_bytes())?,
+            None => {
+                if config_path.exists() {
+                    fs::remove_file(config_path)?;
+                }
+            }
+        }
+        
+        self.record(&format!("undo '{}'", target.action), current, restored, Some(target.id))?;
+        Ok(target)
+    }
+}
+
+/// config.toml as it was before a command ran.
+pub struct Snapshot {
+    config_path: PathBuf,
+    before: Option<String>,
+}
+
+impl Snapshot {
+    pub fn take(config_path: &Path) -> Result<Self> {
+        Ok(Self {
+            config_path: config_path.to_path_buf(),
+            before: read_optional(config_path)?,
+        })
+    }
+    
+    /// Records `action` if the command changed config.toml.
+    pub fn record(self, history: &History, action: &str) -> Result<Option<HistoryEntry>> {
+        let after = read_optional(&self.config_path)?;
+        history.record(action, self.before, after, None)
+    }
+}
+
+/// `contents` with secret values replaced. A file that doesn't parse only
+/// has credentials in URLs stripped.
+fn redact(contents: &str) -> String {
+    match contents.parse::<toml::Value>() {
+        Ok(mut value) => {
+            diagnostics::redact_value(&mut value);
+            toml::to_string_pretty(&value).unwrap_or_else(|_| diagnostics::redact_text(contents))
+        }
+        Err(_) => diagnostics::redact_text(contents),
+    }
+}
+
+/// The settings in `contents`, without `MACHINE_STATE`, for comparing.
+fn settings(contents: Option<&str>) -> Option<toml::Value> {
+    let contents = contents?;
+    Some(match contents.parse::<toml::Table>() {
+        Ok(mut table) => {
+            for key in MACHINE_STATE {
+                table.remove(key);
+            }
+            toml::Value::Table(table)
+        }
+        Err(_) => toml::Value::String(contents.to_string()),
+    })
+}
+
+/// config.toml with the settings of `before` and the `MACHINE_STATE` and
+/// redacted secrets of `current`. `None` when nothing is left to write.
+fn restore(before: Option<&str>, current: Option<&str>) -> Result<Option<String>> {
+    let current: toml::Table = current.and_then(|contents| contents.parse().ok()).unwrap_or_default();
+    let mut restored = match before {
+        Some(contents) => match contents.parse::<toml::Table>() {
+            Ok(table) => table,
+            // A file that didn't parse is put back as it was.
+            Err(_) => return Ok(Some(contents.to_string())),
+        },
+        None => toml::Table::new(),
+    };
+    
+    restore_secrets(&mut restored, &current);
+    for key in MACHINE_STATE {
+        match current.get(key) {
+            Some(value) => restored.insert(key.to_string(), value.clone()),
+            None => restored.remove(key),
+        };
+    }
+    if before.is_none() && restored.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(toml::to_string_pretty(&restored)?))
+}
+
+/// Puts `current`'s values back where `table` only has them redacted, and
+/// drops redacted values `current` doesn't have.
+fn restore_secrets(table: &mut toml::Table, current: &toml::Table) {
+    let empty = toml::Table::new();
+    table.retain(|key, value| match value {
+        toml::Value::Table(inner) => {
+            restore_secrets(inner, current.get(key).and_then(toml::Value::as_table).unwrap_or(&empty));
+            true
+        }
+        toml::Value::String(s) if s.contains(REDACTED) => match current.get(key) {
+            Some(known) => {
+                *value = known.clone();
+                true
+            }
+            None => false,
+        },
+        _ => true,
+    });
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
@@ -0,0 +1,216 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use crate::models::{Config, OutputHook};
+use crate::modules::events::{self, StreamEvent};
+use crate::modules::{diagnostics, git_mgr, offline, output};
+
+/// What a hook can be told about. Hooks pick events by `as_str` name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Any command that may change the machine finished, successfully or not.
+    Operation,
+    /// An install ran; `data.groups` has each group's outcome.
+    Install,
+    /// The dotfiles repo synced with its main branch.
+    Sync,
+    /// Syncing stopped because a file changed on both the device branch and
+    /// the main branch.
+    SyncConflict,
+    /// Syncing failed for any other reason, e.g. the network or credentials.
+    SyncFailed,
+    /// Deployed files or packages no longer match the config; `data.drift`
+    /// lists what differs.
+    Drift,
+}
+
+impl Event {
+    pub const ALL: [Event; 6] = [
+        Event::Operation,
+        Event::Install,
+        Event::Sync,
+        Event::SyncConflict,
+        Event::SyncFailed,
+        Event::Drift,
+    ];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Event::Operation => "operation",
+            Event::Install => "install",
+            Event::Sync => "sync",
+            Event::SyncConflict => "sync_conflict",
+            Event::SyncFailed => "sync_failed",
+            Event::Drift => "drift",
+        }
+    }
+}
+
+/// The JSON document each hook receives.
+#[derive(Debug, Serialize)]
+pub struct Payload<'a> {
+    pub event: &'static str,
+    pub timestamp: DateTime<Utc>,
+    pub version: &'static str,
+    pub hostname: String,
+    pub device: &'a str,
+    pub profile: Option<&'a str>,
+    pub data: serde_json::Value,
+}
+
+impl<'a> Payload<'a> {
+    pub fn new(config: &'a Config, event: Event, data: serde_json::Value) -> Self {
+        Self {
+            event: event.as_str(),
+            timestamp: Utc::now(),
+            version: env!("CARGO_PKG_VERSION"),
+            hostname: hostname::get().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+            device: &config.device.name,
+            profile: config.active_profile.as_deref(),
+            data,
+        }
+    }
+}
+
+impl OutputHook {
+    pub fn wants(&self, event: Event) -> bool {
+        self.events.is_empty() || self.events.iter().any(|name| name == event.as_str())
+    }
+    
+    /// The command or URL, with any credentials in it redacted.
+    pub fn describe(&self) -> String {
+        diagnostics::redact_text(self.url.as_deref().or(self.command.as_deref()).unwrap_or_default())
+    }
+    
+    fn process(&self) -> Result<Command> {
+        match (&self.command, &self.url) {
+            (Some(command), None) => {
+                let mut process = if cfg!(windows) {
+                    let mut process = Command::new("cmd");
+                    process.arg("/C");
+                    process
+                } else {
+                    let mut process = Command::new("sh");
+                    process.arg("-c");
+                    process
+                };
+                process.arg(command);
+                Ok(process)
+            }
+            (None, Some(url)) => {
+                let mut process = Command::new("curl");
+                process
+                    .args(["-fsS", "-X", "POST", "-H", "Content-Type: application/json", "--data-binary", "@-"])
+                    .args(["--max-time", &self.timeout_secs.to_string()])
+                    .arg(url);
+                Ok(process)
+            }
+            _ => anyhow::bail!("a hook needs exactly one of `command` or `url`"),
+        }
+    }
+}
+
+/// Sends `event` to every hook that wants it. Hooks run one after another and
+/// can't fail the operation; a failing hook only prints a warning. Offline,
+/// URL hooks are skipped. Secrets in `data` are redacted first.
+pub fn emit(config: &Config, event: Event, mut data: serde_json::Value) {
+    let hooks: Vec<&OutputHook> = config
+        .hooks
+        .iter()
+        .filter(|hook| hook.wants(event) && !(hook.url.is_some() && offline::is_offline()))
+        .collect();
+    if hooks.is_empty() {
+        return;
+    }
+    
+    redact(&mut data);
+    let body = match serde_json::to_vec(&Payload::new(config, event, data)) {
+        Ok(body) => body,
+        Err(e) => {
+            output::warn(format!("⚠️  Failed to encode '{}' event: {}", event.as_str(), e));
+            return;
+        }
+    };
+    for hook in hooks {
+        if let Err(e) = deliver(hook, event, &body) {
+            output::warn(format!("⚠️  Hook {} failed: {:#}", hook.describe(), e));
+        }
+    }
+}
+
+/// Blanks the values of secret-looking keys and credentials in URLs, so an
+/// error message or command line can't carry a token to a webhook.
+fn redact(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(text) => *text = diagnostics::redact_text(text),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+        serde_json::Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if field.is_string() && diagnostics::is_secret_key(key) {
+                    *field = diagnostics::REDACTED.into();
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Runs the hook with `body` on stdin, killing it after its timeout.
+pub fn deliver(hook: &OutputHook, event: Event, body: &[u8]) -> Result<()> {
+    let mut child = hook
+        .process()?
+        .env("ZSHRCMAN_EVENT", event.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .context("Failed to start")?;
+    
+    if let Some(mut stdin) = child.stdin.take() {
+        // Written from a thread so a hook that never reads its input can't
+        // block past the timeout; one that doesn't read it isn't an error.
+        let body = body.to_vec();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&body);
+        });
+    }
+    
+    let deadline = Instant::now() + Duration::from_secs(hook.timeout_secs);
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if !status.success() {
+                anyhow::bail!("exited with {}", status);
+            }
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("timed out after {}s", hook.timeout_secs);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// `sync`, `sync_conflict` or `sync_failed` for the outcome of syncing
+/// `repository` (`dotfiles` for the main repo) on `branch`.
+pub fn emit_sync(config: &Config, repository: &str, branch: &str, result: &Result<()>) {
+    let (event, error) = match result {
+        Ok(()) => (Event::Sync, None),
+        Err(e) if git_mgr::is_conflict(e) => (Event::SyncConflict, Some(format!("{:#}", e))),
+        Err(e) => (Event::SyncFailed, Some(format!("{:#}", e))),
+    };
+    let data = serde_json::json!({ "repository": repository, "branch": branch, "error": error });
+    emit(config, event, data);
+    
+    let (repository, branch) = (repository.to_string(), branch.to_string());
+    events::emit(match (event, error) {
+        (Event::SyncConflict, Some(error)) => StreamEvent::SyncConflict { repository, branch, error },
+        (_, Some(error)) => StreamEvent::SyncFailed { repository, branch, error },
+        (_, None) => StreamEvent::Synced { repository, branch },
+    });
+}
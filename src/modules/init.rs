@@ -1,66 +1,83 @@
-use anyhow::{Context, Result};
-use dialoguer::{Input, MultiSelect, Select};
+use anyhow::Result;
 use std::fs;
 use std::path::Path;
-use crate::models::{AliasGroup, GroupConfig};
+use crate::models::{Alias, AliasGroup, GroupConfig};
 use crate::modules::config::ConfigManager;
 use crate::modules::git_mgr::GitManager;
+use crate::modules::prompt;
+
+/// Answers that would otherwise be prompted for. Anything left as `None` is
+/// asked interactively, or falls back to its default in non-interactive mode.
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    pub repo: Option<String>,
+    pub device: Option<String>,
+    pub groups: Option<Vec<String>>,
+}
 
 pub struct InitManager;
 
 impl InitManager {
-    pub fn run() -> Result<()> {
+    pub fn run(options: InitOptions) -> Result<()> {
         println!("🚀 Welcome to zshrcman initialization!");
         
         let mut config_mgr = ConfigManager::new()?;
         
-        let remote_url: String = Input::new()
-            .with_prompt("Enter remote Git repository URL")
-            .interact_text()?;
+        let remote_url = match options.repo {
+            Some(url) => url,
+            None => prompt::input("Enter remote Git repository URL", None, "--repo <url>")?,
+        };
         
         config_mgr.config.repository.url = Some(remote_url.clone());
         
         let dotfiles_path = ConfigManager::get_dotfiles_path()?;
         fs::create_dir_all(&dotfiles_path)?;
         
-        let git_mgr = GitManager::init_or_clone(&dotfiles_path, Some(&remote_url))?;
+        let git_mgr = GitManager::init_or_clone(&dotfiles_path, Some(&remote_url))?
+            .with_mirrors(&config_mgr.config.repository.remotes)?
+            .with_identity(&config_mgr.config.repository.identity);
         
         let branches = git_mgr.list_remote_branches()
             .unwrap_or_else(|_| vec!["main".to_string()]);
         
-        let mut branch_options = branches.clone();
-        branch_options.push("Create new device branch".to_string());
-        
-        let branch_selection = Select::new()
-            .with_prompt("Select or create a device branch")
-            .items(&branch_options)
-            .default(branch_options.len() - 1)
-            .interact()?;
-        
-        let device_branch = if branch_selection == branch_options.len() - 1 {
-            let device_name: String = Input::new()
-                .with_prompt("Enter device name")
-                .interact_text()?;
-            
+        let device_branch = if let Some(device_name) = options.device {
             let branch_name = format!("device/{}", device_name);
-            git_mgr.checkout_branch(&branch_name, true)?;
-            
-            Self::scaffold_device_files(&dotfiles_path, &device_name)?;
+            let exists = branches.contains(&branch_name);
+            Self::setup_device_branch(&git_mgr, &dotfiles_path, &device_name, !exists)?;
             
             config_mgr.config.device.name = device_name;
             config_mgr.config.device.branch = branch_name.clone();
             branch_name
         } else {
-            let branch = branches[branch_selection].clone();
-            git_mgr.checkout_branch(&branch, false)?;
+            let mut branch_options = branches.clone();
+            branch_options.push("Create new device branch".to_string());
             
-            let device_name = branch.strip_prefix("device/")
-                .unwrap_or(&branch)
-                .to_string();
+            let branch_selection = prompt::select(
+                "Select or create a device branch",
+                &branch_options,
+                branch_options.len() - 1,
+            )?;
             
-            config_mgr.config.device.name = device_name;
-            config_mgr.config.device.branch = branch.clone();
-            branch
+            if branch_selection == branch_options.len() - 1 {
+                let device_name = prompt::input("Enter device name", None, "--device <name>")?;
+                let branch_name = format!("device/{}", device_name);
+                Self::setup_device_branch(&git_mgr, &dotfiles_path, &device_name, true)?;
+                
+                config_mgr.config.device.name = device_name;
+                config_mgr.config.device.branch = branch_name.clone();
+                branch_name
+            } else {
+                let branch = branches[branch_selection].clone();
+                git_mgr.checkout_branch(&branch, false)?;
+                
+                let device_name = branch.strip_prefix("device/")
+                    .unwrap_or(&branch)
+                    .to_string();
+                
+                config_mgr.config.device.name = device_name;
+                config_mgr.config.device.branch = branch.clone();
+                branch
+            }
         };
         
         Self::ensure_default_groups(&dotfiles_path)?;
@@ -69,18 +86,25 @@ impl InitManager {
             "default", "brew", "npm", "pnpm", "aliases", "ssh", "zshrc"
         ];
         
-        let selected_groups = MultiSelect::new()
-            .with_prompt("Select groups to enable")
-            .items(&built_in_groups)
-            .defaults(&vec![true, false, false, false, false, false, false])
-            .interact()?;
+        let enabled_groups = match options.groups {
+            Some(groups) => groups,
+            None => {
+                let selected_groups = prompt::multi_select(
+                    "Select groups to enable",
+                    &built_in_groups,
+                    &[true, false, false, false, false, false, false],
+                )?;
+                
+                selected_groups
+                    .into_iter()
+                    .map(|idx| built_in_groups[idx].to_string())
+                    .collect()
+            }
+        };
         
-        let mut enabled_groups = Vec::new();
-        for idx in selected_groups {
-            enabled_groups.push(built_in_groups[idx].to_string());
-            
-            if !config_mgr.config.groups.global.contains(&built_in_groups[idx].to_string()) {
-                config_mgr.config.groups.global.push(built_in_groups[idx].to_string());
+        for group in &enabled_groups {
+            if !config_mgr.config.groups.global.contains(group) {
+                config_mgr.config.groups.global.push(group.clone());
             }
         }
         config_mgr.config.groups.enabled_global = enabled_groups;
@@ -88,10 +112,11 @@ impl InitManager {
         for group in &config_mgr.config.groups.enabled_global {
             if let Ok(group_config) = config_mgr.load_group_config(group) {
                 if !group_config.aliases.is_empty() {
-                    let active_aliases = MultiSelect::new()
-                        .with_prompt(&format!("Select active aliases for group '{}'", group))
-                        .items(&group_config.aliases)
-                        .interact()?;
+                    let active_aliases = prompt::multi_select(
+                        &format!("Select active aliases for group '{}'", group),
+                        &group_config.aliases,
+                        &[],
+                    )?;
                     
                     let mut active = Vec::new();
                     for idx in active_aliases {
@@ -126,6 +151,22 @@ impl InitManager {
         Ok(())
     }
     
+    fn setup_device_branch(
+        git_mgr: &GitManager,
+        dotfiles_path: &Path,
+        device_name: &str,
+        create: bool,
+    ) -> Result<()> {
+        let branch_name = format!("device/{}", device_name);
+        git_mgr.checkout_branch(&branch_name, create)?;
+        
+        if create {
+            Self::scaffold_device_files(dotfiles_path, device_name)?;
+        }
+        
+        Ok(())
+    }
+    
     fn scaffold_device_files(dotfiles_path: &Path, device_name: &str) -> Result<()> {
         let device_dir = dotfiles_path.join("devices").join(device_name);
         fs::create_dir_all(&device_dir)?;
@@ -152,13 +193,13 @@ impl InitManager {
             description: "Default configuration for all devices".to_string(),
             packages: vec![],
             aliases: vec![
-                r#"alias ll="ls -la""#.to_string(),
-                r#"alias ..="cd ..""#.to_string(),
-                r#"alias ...="cd ../..""#.to_string(),
+                Alias::new("ll", "ls -la"),
+                Alias::new("..", "cd .."),
+                Alias::new("...", "cd ../.."),
             ],
             scripts: vec![],
             files: vec![],
-            ssh_keys: vec![],
+            ..Default::default()
         };
         
         if !groups_dir.join("default.toml").exists() {
@@ -173,7 +214,7 @@ impl InitManager {
             aliases: vec![],
             scripts: vec![],
             files: vec![],
-            ssh_keys: vec![],
+            ..Default::default()
         };
         
         if !groups_dir.join("brew.toml").exists() {
@@ -188,7 +229,7 @@ impl InitManager {
             aliases: vec![],
             scripts: vec![],
             files: vec![],
-            ssh_keys: vec![],
+            ..Default::default()
         };
         
         if !groups_dir.join("npm.toml").exists() {
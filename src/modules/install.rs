@@ -1,79 +1,248 @@
 use anyhow::{Context, Result};
-use dialoguer::Confirm;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use crate::models::{InstallerType, InstallStatus};
+use crate::models::{CronJob, FileMapping, Font, GroupConfig, InstallationRecord, InstallerType, InstallStatus, MacosDefault, OsType, Service, ZshPlugin};
+use crate::modules::bin_links;
+use crate::modules::changeset::{self, Change, ChangeRecorder, ChangeSet};
+use crate::modules::completions::{self, CompletionManager};
 use crate::modules::config::ConfigManager;
+use crate::modules::cron;
+use crate::modules::env_layers::{EnvLayers, Layer};
+use crate::modules::environment::{self, EnvironmentManager, ShellType};
+use crate::modules::errors::{self, ErrorKind, ResultExt};
+use crate::modules::events::{self, StreamEvent};
+use crate::modules::facts::Facts;
+use crate::modules::fingerprint::Fingerprint;
+use crate::modules::fonts;
+use crate::modules::hooks::{self, Event};
+use crate::modules::functions::{self, FunctionManager, Functions};
+use crate::modules::git_mgr::{self, GitManager};
+use crate::modules::gpg;
+use crate::modules::installer::{Installer, InstallerRegistry};
+use crate::modules::lazy_load::LazyLoads;
+use crate::modules::macos_defaults;
+use crate::modules::named_dirs::NamedDirs;
+use crate::modules::output;
+use crate::modules::preflight;
+use crate::modules::prompt;
+use crate::modules::scripting::{self, ScriptContext};
+use crate::modules::services::{self, ServiceManager};
+use crate::modules::template::TemplateVars;
+use crate::modules::theme;
+use crate::modules::tidy;
+use crate::modules::verify::{self, Drift, DriftKind, Subject};
+use crate::modules::vscode;
+use crate::modules::zcompile;
+use crate::modules::zsh_options::ZshOptions;
+use crate::modules::zsh_plugins::{self, Lockfile};
 
 pub struct InstallManager {
     config_mgr: ConfigManager,
+    home: Option<PathBuf>,
+    dry_run: bool,
+    force_pinned: bool,
+    registry: InstallerRegistry,
+    facts: Facts,
 }
 
 impl InstallManager {
     pub fn new(config_mgr: ConfigManager) -> Self {
-        Self { config_mgr }
+        let registry = InstallerRegistry::builtin(false, &config_mgr.config.install);
+        Self {
+            config_mgr,
+            home: None,
+            dry_run: false,
+            force_pinned: false,
+            registry,
+            facts: Facts::gather(),
+        }
+    }
+    
+    /// Deploys files into `home` instead of the user's real home directory.
+    pub fn with_home(mut self, home: PathBuf) -> Self {
+        self.home = Some(home);
+        self
+    }
+    
+    /// Prints package manager commands instead of running them. Files are still
+    /// written, so pair this with `with_home` to keep the real environment untouched.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self.registry.register_builtins(dry_run, &self.config_mgr.config.install);
+        self
+    }
+    
+    /// Lets removals uninstall pinned packages (`--force-pinned`).
+    pub fn force_pinned(mut self, force: bool) -> Self {
+        self.force_pinned = force;
+        self
+    }
+    
+    /// Adds a package backend. Groups whose name or `installer` field matches
+    /// its name are installed with it; a built-in of the same name is replaced.
+    pub fn with_installer(mut self, installer: impl Installer + 'static) -> Self {
+        self.registry.register(installer);
+        self
+    }
+    
+    pub fn registry(&self) -> &InstallerRegistry {
+        &self.registry
+    }
+    
+    /// Registers external installer plugins from the plugins dir and PATH.
+    fn load_plugins(&mut self) {
+        let plugins_dir = self.config_mgr.data_dir().join("plugins");
+        self.registry.register_plugins(&[plugins_dir], self.dry_run);
     }
     
+    fn home_dir(&self) -> Result<PathBuf> {
+        match &self.home {
+            Some(home) => Ok(home.clone()),
+            None => preflight::home_dir(),
+        }
+    }
+    
+    /// Installs the enabled groups. Groups whose inputs haven't changed since
+    /// their last successful install are skipped unless `all` is set, which
+    /// also installs every group without prompting.
     pub fn install(&mut self, all: bool) -> Result<()> {
+        self.load_plugins();
         let groups = self.config_mgr.get_ordered_groups();
         
-        println!("🔧 Installing groups: {:?}", groups);
+        output::info(format!("🔧 Installing groups: {:?}", groups));
         
-        for group in groups {
+        let total = groups.len();
+        let mut unchanged = 0;
+        let mut failed = vec![];
+        let mut outcomes = vec![];
+        for (index, group) in groups.into_iter().enumerate() {
+            if let Some(reason) = self.unmet_condition(&group) {
+                output::info(format!("⏭️  Skipping group '{}' ({})", group, reason));
+                continue;
+            }
+            
+            let fingerprint = self.fingerprint(&group)?;
+            if !all && self.is_up_to_date(&group, &fingerprint) {
+                output::info(format!("✔️  Group '{}' is up to date", group));
+                unchanged += 1;
+                continue;
+            }
+            
             if !all {
-                let proceed = Confirm::new()
-                    .with_prompt(format!("Install group '{}'?", group))
-                    .default(true)
-                    .interact()?;
+                let proceed = prompt::confirm(&format!("Install group '{}'?", group), true)?;
                 
                 if !proceed {
-                    println!("⏭️  Skipping group '{}'", group);
+                    output::info(format!("⏭️  Skipping group '{}'", group));
                     continue;
                 }
             }
             
-            println!("📦 Installing group '{}'...", group);
+            output::info(format!("📦 Installing group '{}' [{}/{}]...", group, index + 1, total));
             
-            let result = self.install_group(&group);
+            events::emit(StreamEvent::GroupStarted { group: group.clone() });
+            let mut attempts = 0;
+            let result = self.install_group(&group, &mut attempts);
+            events::emit(StreamEvent::GroupFinished {
+                group: group.clone(),
+                success: result.is_ok(),
+                error: result.as_ref().err().map(|e| format!("{:#}", e)),
+            });
             
             let status = match &result {
                 Ok(_) => {
-                    println!("✅ Successfully installed group '{}'", group);
+                    output::info(format!("✅ Successfully installed group '{}'", group));
                     InstallStatus {
                         installed: true,
                         success: true,
                         timestamp: Some(chrono::Utc::now()),
                         error: None,
+                        attempts,
+                        fingerprint: (!self.dry_run).then_some(fingerprint),
                     }
                 }
                 Err(e) => {
                     println!("❌ Failed to install group '{}': {}", group, e);
+                    failed.push((group.clone(), errors::classify(e)));
                     InstallStatus {
                         installed: false,
                         success: false,
                         timestamp: Some(chrono::Utc::now()),
                         error: Some(e.to_string()),
+                        attempts,
+                        fingerprint: None,
                     }
                 }
             };
             
+            outcomes.push(serde_json::json!({ "group": group, "success": status.success, "error": status.error }));
             self.config_mgr.update_install_status(&group, status)?;
         }
         
-        println!("🎉 Installation complete!");
-        Ok(())
+        if !self.dry_run {
+            if let Err(e) = self.write_named_dirs() {
+                println!("⚠️  Failed to write named directories: {:#}", e);
+            }
+            if let Err(e) = self.write_variables() {
+                println!("⚠️  Failed to write group variables: {:#}", e);
+            }
+            if let Err(e) = self.write_functions() {
+                println!("⚠️  Failed to deploy shell functions: {:#}", e);
+            }
+            if let Err(e) = self.write_completions() {
+                println!("⚠️  Failed to deploy completions: {:#}", e);
+            }
+            if let Err(e) = self.write_options() {
+                println!("⚠️  Failed to write zsh options and key bindings: {:#}", e);
+            }
+            if let Err(e) = self.write_lazy() {
+                println!("⚠️  Failed to write lazy-load stubs: {:#}", e);
+            }
+            if let Err(e) = self.write_plugins() {
+                println!("⚠️  Failed to write zsh plugin sources: {:#}", e);
+            }
+            if let Err(e) = self.home_dir().and_then(|home| theme::apply(&self.config_mgr, &home)) {
+                println!("⚠️  Failed to set up the prompt theme: {:#}", e);
+            }
+            if let Err(e) = self.compile_zshrc() {
+                println!("⚠️  Failed to compile the zsh config: {:#}", e);
+            }
+        }
+        
+        if !self.dry_run && !outcomes.is_empty() {
+            hooks::emit(&self.config_mgr.config, Event::Install, serde_json::json!({ "groups": outcomes }));
+        }
+        
+        if unchanged > 0 {
+            output::info(format!("⏭️  {} unchanged group(s) skipped; use --all to reinstall them", unchanged));
+        }
+        if failed.is_empty() {
+            println!("🎉 Installation complete!");
+            return Ok(());
+        }
+        
+        let names: Vec<&str> = failed.iter().map(|(group, _)| group.as_str()).collect();
+        let message = format!("{} group(s) failed to install: {}", failed.len(), names.join(", "));
+        // Groups that installed or were already up to date make it a partial success.
+        if outcomes.len() > failed.len() || unchanged > 0 {
+            return Err(errors::fail(ErrorKind::Partial, message));
+        }
+        let kind = failed[0].1;
+        let kind = if failed.iter().all(|(_, other)| *other == kind) { kind } else { ErrorKind::Other };
+        Err(errors::fail(kind, message))
     }
     
     pub fn remove_all(&mut self) -> Result<()> {
-        println!("🗑️  Removing all installed groups...");
+        output::info("🗑️  Removing all installed groups...");
+        self.load_plugins();
         
         for (group, status) in self.config_mgr.config.status.clone() {
             if status.installed {
-                println!("📦 Uninstalling group '{}'...", group);
+                output::info(format!("📦 Uninstalling group '{}'...", group));
                 
                 match self.uninstall_group(&group) {
-                    Ok(_) => println!("✅ Successfully uninstalled group '{}'", group),
+                    Ok(_) => output::info(format!("✅ Successfully uninstalled group '{}'", group)),
                     Err(e) => println!("⚠️  Failed to uninstall group '{}': {}", group, e),
                 }
             }
@@ -81,165 +250,1182 @@ impl InstallManager {
         
         self.config_mgr.clear_all_status()?;
         
+        let env_mgr = EnvironmentManager::new().with_home(self.home_dir()?);
+        if env_mgr.has_named_dirs_block()? {
+            env_mgr.write_named_dirs(&Default::default())?;
+        }
+        if env_mgr.has_variables_block()? {
+            env_mgr.write_variables(&Default::default())?;
+        }
+        FunctionManager::new(&self.home_dir()?).deploy(&[])?;
+        if env_mgr.has_functions_block()? {
+            env_mgr.write_functions(&[])?;
+        }
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).with_home(self.home_dir()?);
+        if zsh.has_plugins_block()? {
+            zsh.write_plugins("")?;
+        }
+        if zsh.has_options_block()? {
+            zsh.write_options("")?;
+        }
+        let manager = CompletionManager::new(&self.home_dir()?);
+        if manager.deploy(&BTreeMap::new())? {
+            manager.reset_dump()?;
+        }
+        if zsh.has_completions_block()? {
+            zsh.write_completions("")?;
+        }
+        if zsh.has_theme_block()? {
+            zsh.write_theme("")?;
+        }
+        if zsh.has_lazy_block()? {
+            zsh.write_lazy("")?;
+        }
+        zsh.set_compiled(false)?;
+        
         println!("🎉 All groups removed!");
         Ok(())
     }
     
-    fn install_group(&self, group_name: &str) -> Result<()> {
-        let installer_type = InstallerType::from_group_name(group_name);
+    /// The named dirs of every enabled group that applies to this machine,
+    /// except those the active profile redefines; the profile's environment
+    /// renders its own.
+    fn named_dirs(&self) -> NamedDirs {
+        let mut named_dirs = NamedDirs::new();
+        for group in self.config_mgr.get_ordered_groups() {
+            if self.unmet_condition(&group).is_some() {
+                continue;
+            }
+            if let Some(group_config) = self.load_group(&group) {
+                named_dirs.add(&format!("group '{}'", group), &group_config.named_dirs);
+            }
+        }
         
-        let group_config = if let Ok(config) = self.config_mgr.load_group_config(group_name) {
-            config
-        } else if let Ok(config) = self.config_mgr.load_device_group_config(
-            &self.config_mgr.config.device.name, 
-            group_name
-        ) {
-            config
-        } else {
+        let config = &self.config_mgr.config;
+        if let Some((name, profile)) = config.active_profile.as_ref().and_then(|name| config.profiles.get_key_value(name)) {
+            named_dirs.override_with(&format!("profile '{}'", name), &profile.environment.named_dirs);
+        }
+        named_dirs
+    }
+    
+    fn write_named_dirs(&self) -> Result<()> {
+        let named_dirs = self.named_dirs();
+        for problem in &named_dirs.problems {
+            println!("⚠️  Named directory {}", problem);
+        }
+        
+        let env_mgr = EnvironmentManager::new().with_home(self.home_dir()?);
+        let dirs = named_dirs.to_map();
+        if dirs.is_empty() && !env_mgr.has_named_dirs_block()? {
             return Ok(());
-        };
+        }
+        env_mgr.write_named_dirs(&dirs)
+    }
+    
+    /// Every variable set by an enabled group that applies to this machine
+    /// or by the active profile, on its layer: groups enabled for the device
+    /// are device groups, the others global ones.
+    pub fn env_layers(&self) -> EnvLayers {
+        let config = &self.config_mgr.config;
+        let mut layers = EnvLayers::new();
+        let tagged = self.config_mgr.tagged_groups();
+        for group in self.config_mgr.get_ordered_groups() {
+            if self.unmet_condition(&group).is_some() {
+                continue;
+            }
+            if let Some(group_config) = self.load_group(&group) {
+                let layer = if config.groups.enabled_devices.contains(&group) || tagged.contains(&group) { Layer::DeviceGroup } else { Layer::GlobalGroup };
+                layers.add(layer, &group, &group_config.env);
+            }
+        }
+        
+        if let Some((name, profile)) = config.active_profile.as_ref().and_then(|name| config.profiles.get_key_value(name)) {
+            let variables = profile.environment.variables.iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+            layers.add(Layer::Profile, name, &variables);
+        }
+        layers
+    }
+    
+    /// Rewrites the group variables block for the groups and profile active
+    /// now. It leaves out what the profile sets, so `ProfileSwitcher` calls
+    /// this on every switch and the profile's values win whichever startup
+    /// file is read first.
+    pub fn write_variables(&self) -> Result<()> {
+        let layers = self.env_layers();
+        for conflict in layers.conflicts() {
+            output::warn(format!("⚠️  Environment variable {}", conflict));
+        }
         
-        match installer_type {
-            InstallerType::Brew => self.install_brew(&group_config.packages),
-            InstallerType::Npm => self.install_npm(&group_config.packages),
-            InstallerType::Pnpm => self.install_pnpm(&group_config.packages),
-            InstallerType::Aliases => self.install_aliases(group_name),
-            InstallerType::Ssh => self.install_ssh(&group_config.ssh_keys),
-            InstallerType::Zshrc => self.install_zshrc(&group_config.scripts),
-            InstallerType::Custom(_) => {
-                println!("ℹ️  Custom installer for '{}' not implemented", group_name);
-                Ok(())
+        let env_mgr = EnvironmentManager::new().with_home(self.home_dir()?);
+        let variables = layers.group_variables();
+        if variables.is_empty() && !env_mgr.has_variables_block()? {
+            return Ok(());
+        }
+        env_mgr.write_variables(&variables)
+    }
+    
+    /// The functions of every enabled group that applies to this machine.
+    fn functions(&self) -> Functions {
+        let mut functions = Functions::new();
+        for group in self.config_mgr.get_ordered_groups() {
+            if self.unmet_condition(&group).is_some() {
+                continue;
+            }
+            if let Some(group_config) = self.load_group(&group) {
+                functions.add(&format!("group '{}'", group), &group_config.functions);
             }
         }
+        functions
     }
     
-    fn uninstall_group(&self, group_name: &str) -> Result<()> {
-        let installer_type = InstallerType::from_group_name(group_name);
+    /// Deploys the functions of every enabled group that applies to this
+    /// machine and loads them from the rc file.
+    fn write_functions(&self) -> Result<()> {
+        let functions = self.functions();
+        for problem in &functions.problems {
+            println!("⚠️  Function {}", problem);
+        }
         
-        let group_config = if let Ok(config) = self.config_mgr.load_group_config(group_name) {
-            config
-        } else if let Ok(config) = self.config_mgr.load_device_group_config(
-            &self.config_mgr.config.device.name, 
-            group_name
-        ) {
-            config
-        } else {
+        let home = self.home_dir()?;
+        FunctionManager::new(&home).deploy(functions.list())?;
+        let env_mgr = EnvironmentManager::new().with_home(home);
+        if functions.list().is_empty() && !env_mgr.has_functions_block()? {
             return Ok(());
-        };
+        }
+        env_mgr.write_functions(functions.list())
+    }
+    
+    /// The completion files of every enabled group that applies to this
+    /// machine by name, and what's wrong with the ones left out.
+    fn completion_files(&self) -> (BTreeMap<String, PathBuf>, Vec<String>) {
+        let mut files: BTreeMap<String, PathBuf> = BTreeMap::new();
+        let mut problems = vec![];
+        for group in self.config_mgr.get_ordered_groups() {
+            if self.unmet_condition(&group).is_some() {
+                continue;
+            }
+            let Some(group_config) = self.load_group(&group) else {
+                continue;
+            };
+            let (root, _) = self.config_mgr.resolve_group(&group);
+            for name in &group_config.completions {
+                let source = root.join("completions").join(name);
+                if !source.is_file() {
+                    problems.push(format!("{} of group '{}' doesn't exist", source.display(), group));
+                } else if files.contains_key(name) {
+                    problems.push(format!("'{}' of group '{}' is already provided by another group", name, group));
+                } else {
+                    files.insert(name.clone(), source);
+                }
+            }
+        }
+        (files, problems)
+    }
+    
+    /// Deploys the completion files of every enabled group that applies to
+    /// this machine, puts their directory on `fpath`, and drops the
+    /// completion dump when they changed so `compinit` picks them up.
+    fn write_completions(&self) -> Result<()> {
+        let (files, problems) = self.completion_files();
+        for problem in &problems {
+            println!("⚠️  Completion {}", problem);
+        }
         
-        match installer_type {
-            InstallerType::Brew => self.uninstall_brew(&group_config.packages),
-            InstallerType::Npm => self.uninstall_npm(&group_config.packages),
-            InstallerType::Pnpm => self.uninstall_pnpm(&group_config.packages),
-            InstallerType::Aliases => self.uninstall_aliases(),
-            InstallerType::Ssh => Ok(()),
-            InstallerType::Zshrc => Ok(()),
-            InstallerType::Custom(_) => Ok(()),
+        let home = self.home_dir()?;
+        let manager = CompletionManager::new(&home);
+        if manager.deploy(&files)? {
+            let removed = manager.reset_dump()?;
+            if removed > 0 {
+                output::info("🔄 Completions changed; compinit will rebuild its dump in new shells");
+            }
+        }
+        
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).with_home(home);
+        if files.is_empty() && !zsh.has_completions_block()? {
+            return Ok(());
+        }
+        zsh.write_completions(&completions_block(&files))
+    }
+    
+    /// The zsh options and key bindings of every enabled group that applies
+    /// to this machine.
+    fn zsh_options(&self) -> ZshOptions {
+        let mut options = ZshOptions::new();
+        for group in self.config_mgr.get_ordered_groups() {
+            if self.unmet_condition(&group).is_some() {
+                continue;
+            }
+            if let Some(group_config) = self.load_group(&group) {
+                options.add(&format!("group '{}'", group), &group_config);
+            }
         }
+        options
     }
     
-    fn install_brew(&self, packages: &[String]) -> Result<()> {
-        if packages.is_empty() {
+    /// Renders the zsh options and key bindings of every enabled group that
+    /// applies to this machine into `.zshrc`.
+    fn write_options(&self) -> Result<()> {
+        let options = self.zsh_options();
+        for problem in &options.problems {
+            println!("⚠️  Zsh {}", problem);
+        }
+        
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).with_home(self.home_dir()?);
+        if options.is_empty() && !zsh.has_options_block()? {
+            return Ok(());
+        }
+        zsh.write_options(&options.render())
+    }
+    
+    /// The `[[lazy]]` entries of every enabled group that applies to this
+    /// machine.
+    fn lazy_loads(&self) -> LazyLoads {
+        let mut lazy = LazyLoads::new();
+        for group in self.config_mgr.get_ordered_groups() {
+            if self.unmet_condition(&group).is_some() {
+                continue;
+            }
+            if let Some(group_config) = self.load_group(&group) {
+                lazy.add(&format!("group '{}'", group), &group_config.lazy);
+            }
+        }
+        lazy
+    }
+    
+    /// Writes lazy-load stubs for the `[[lazy]]` entries of every enabled
+    /// group that applies to this machine into `.zshrc`.
+    fn write_lazy(&self) -> Result<()> {
+        let lazy = self.lazy_loads();
+        for problem in &lazy.problems {
+            println!("⚠️  Lazy loading: {}", problem);
+        }
+        
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).with_home(self.home_dir()?);
+        if lazy.stubs().is_empty() && !zsh.has_lazy_block()? {
             return Ok(());
         }
+        zsh.write_lazy(&lazy.render())
+    }
+    
+    /// Bundles and compiles the managed blocks of `.zshrc` when `[zsh]
+    /// compile` is on, and undoes that when it's turned off.
+    fn compile_zshrc(&self) -> Result<()> {
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).with_home(self.home_dir()?);
+        let compile = self.config_mgr.config.zsh.compile;
+        if zsh.set_compiled(compile)? {
+            if compile {
+                output::info(format!("⚡ Managed zsh config moved to ~/{} and compiled", zcompile::BUNDLE_FILE));
+            } else {
+                output::info("⚡ Managed zsh config moved back into .zshrc");
+            }
+        }
+        Ok(())
+    }
+    
+    /// The plugins of every enabled `plugins` group that applies to this
+    /// machine, in group order. A plugin name that two groups use keeps its
+    /// first definition.
+    fn zsh_plugins(&self) -> Vec<ZshPlugin> {
+        let mut plugins: Vec<ZshPlugin> = Vec::new();
+        for group in self.config_mgr.get_ordered_groups() {
+            let is_plugins = matches!(
+                InstallerType::from_group_name(self.config_mgr.resolve_group(&group).1),
+                InstallerType::Plugins
+            );
+            if !is_plugins || self.unmet_condition(&group).is_some() {
+                continue;
+            }
+            for plugin in self.load_group(&group).map(|g| g.plugins).unwrap_or_default() {
+                if !plugins.iter().any(|p| p.name == plugin.name) {
+                    plugins.push(plugin);
+                }
+            }
+        }
+        plugins
+    }
+    
+    /// Sources the plugins of every enabled `plugins` group that applies to
+    /// this machine from `.zshrc`.
+    fn write_plugins(&self) -> Result<()> {
+        let plugins = self.zsh_plugins();
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).with_home(self.home_dir()?);
+        if plugins.is_empty() && !zsh.has_plugins_block()? {
+            return Ok(());
+        }
+        zsh.write_plugins(&zsh_plugins::source_lines(&self.config_mgr.data_dir(), &plugins))
+    }
+    
+    /// Clones the plugins and checks out their locked commits, locking new
+    /// ones in `plugins.lock`.
+    fn install_plugins(&self, plugins: &[ZshPlugin]) -> Result<()> {
+        if plugins.is_empty() {
+            return Ok(());
+        }
+        if self.dry_run {
+            for plugin in plugins {
+                let dir = zsh_plugins::checkout_dir(&self.config_mgr.data_dir(), &plugin.name);
+                println!("   [dry-run] clone {} into {}", plugin.url, dir.display());
+            }
+            return Ok(());
+        }
+        
+        let dotfiles = self.config_mgr.dotfiles_path();
+        let data_dir = self.config_mgr.data_dir();
+        let mut lock = Lockfile::load(dotfiles)?;
+        let before = lock.clone();
+        let result = plugins.iter().try_for_each(|plugin| {
+            let commit = zsh_plugins::sync(&data_dir, &mut lock, plugin)?;
+            output::info(format!("🔌 Plugin '{}' at {}", plugin.name, git_mgr::short_id(&commit)));
+            Ok(())
+        });
+        if lock != before {
+            lock.save(dotfiles)?;
+        }
+        result
+    }
+    
+    fn load_group(&self, group_name: &str) -> Option<GroupConfig> {
+        self.config_mgr.load_group_config(group_name).ok().or_else(|| {
+            self.config_mgr
+                .load_device_group_config(&self.config_mgr.config.device.name, group_name)
+                .ok()
+        })
+    }
+    
+    /// Hashes everything installing `group_name` reads: its TOML and group
+    /// directory, the files, keys and scripts it references, its aliases and
+    /// the template variables, plus the target home directory.
+    pub fn fingerprint(&self, group_name: &str) -> Result<String> {
+        let dotfiles_path = self.config_mgr.dotfiles_path();
+        let (root, name) = self.config_mgr.resolve_group(group_name);
+        let device_groups = dotfiles_path
+            .join("devices")
+            .join(&self.config_mgr.config.device.name)
+            .join("groups");
+        
+        let mut fingerprint = Fingerprint::new();
+        fingerprint.add("home", self.home_dir()?.display().to_string().as_bytes());
+        for (groups_dir, name) in [(root.join("groups"), name), (device_groups, group_name)] {
+            fingerprint.add_path(&groups_dir.join(format!("{}.toml", name)));
+            fingerprint.add_path(&groups_dir.join(name));
+        }
+        
+        if let Some(group_config) = self.load_group(group_name) {
+            for mapping in &group_config.files {
+                fingerprint.add_path(&root.join(&mapping.source));
+            }
+            for key in &group_config.ssh_keys {
+                fingerprint.add_path(&root.join("ssh").join(key));
+            }
+            for source in group_config.fonts.iter().filter_map(|font| font.source.as_ref()) {
+                fingerprint.add_path(&root.join(source));
+            }
+            for key in &group_config.gpg_keys {
+                fingerprint.add_path(&root.join(gpg::KEYS_DIR).join(key));
+            }
+            for script in &group_config.scripts {
+                fingerprint.add_path(&root.join("scripts").join(script));
+            }
+            for service in &group_config.services {
+                fingerprint.add_path(&root.join(&service.source));
+            }
+        }
         
-        let output = Command::new("brew")
-            .arg("install")
-            .args(packages)
-            .output()
-            .context("Failed to run brew install")?;
+        let config = &self.config_mgr.config;
+        if let Some(aliases) = config.aliases.get(group_name) {
+            fingerprint.add("aliases", serde_json::to_string(aliases)?.as_bytes());
+        }
+        let variables: std::collections::BTreeMap<_, _> = config.variables.iter().collect();
+        fingerprint.add("variables", serde_json::to_string(&variables)?.as_bytes());
         
-        if !output.status.success() {
-            anyhow::bail!("brew install failed: {}", String::from_utf8_lossy(&output.stderr));
+        Ok(fingerprint.finish())
+    }
+    
+    /// How many of the groups `install` would consider are installed and
+    /// unchanged, out of how many. Groups ruled out by `[when]` don't count.
+    pub fn install_progress(&self) -> Result<(usize, usize)> {
+        let mut done = 0;
+        let mut total = 0;
+        for group in self.config_mgr.get_ordered_groups() {
+            if self.unmet_condition(&group).is_some() {
+                continue;
+            }
+            total += 1;
+            if self.is_up_to_date(&group, &self.fingerprint(&group)?) {
+                done += 1;
+            }
         }
+        Ok((done, total))
+    }
+    
+    fn is_up_to_date(&self, group_name: &str, fingerprint: &str) -> bool {
+        self.config_mgr.config.status.get(group_name).is_some_and(|status| {
+            status.installed && status.success && status.fingerprint.as_deref() == Some(fingerprint)
+        })
+    }
+    
+    /// Why the group's `[when]` condition rules out this machine, if it does.
+    fn unmet_condition(&self, group_name: &str) -> Option<String> {
+        let condition = self.load_group(group_name)?.when?;
+        condition.check(&self.facts).err()
+    }
+    
+    fn installer_for(&self, group_name: &str, group_config: &GroupConfig) -> Result<Option<&dyn Installer>> {
+        match &group_config.installer {
+            Some(name) => match self.registry.get(name) {
+                Some(installer) => Ok(Some(installer)),
+                None => anyhow::bail!(
+                    "Group '{}' uses unknown installer '{}'. Available: {}",
+                    group_name,
+                    name,
+                    self.registry.names().join(", ")
+                ),
+            },
+            None => Ok(self.registry.get(self.config_mgr.resolve_group(group_name).1)),
+        }
+    }
+    
+    /// Installs a group and records what changed, replacing the group's
+    /// previous change set. Partial changes are recorded even on failure.
+    /// `attempts` counts package installs, including retries.
+    /// Applies the group, recording the packages it installed as its
+    /// dependencies.
+    fn install_group(&mut self, group_name: &str, attempts: &mut u32) -> Result<()> {
+        let data_dir = self.config_mgr.data_dir();
+        let mut recorder = ChangeRecorder::new(&data_dir, group_name)?;
+        
+        let result = self.apply_group(group_name, &mut recorder, attempts);
+        if self.dry_run {
+            return result;
+        }
+        if result.is_ok() {
+            for change in &recorder.changes.changes {
+                if let Change::PackagesInstalled { installer, packages } = change {
+                    self.config_mgr.record_group_packages(group_name, installer, packages)?;
+                }
+            }
+        }
+        recorder.finish(&data_dir)?;
+        result
+    }
+    
+    fn apply_group(&self, group_name: &str, recorder: &mut ChangeRecorder, attempts: &mut u32) -> Result<()> {
+        let Some(group_config) = self.load_group(group_name) else {
+            return Ok(());
+        };
         
+        let (root, name) = self.config_mgr.resolve_group(group_name);
+        if let Some(installer) = self.installer_for(group_name, &group_config)? {
+            let packages = self.without_installed_holds(installer, &group_config.package_ids());
+            recorder.installed(installer.name(), self.not_installed(installer, &packages));
+            if !packages.is_empty() {
+                self.install_packages(installer, &packages, attempts)?;
+            }
+        } else {
+            match InstallerType::from_group_name(name) {
+                InstallerType::Aliases => self.install_aliases(group_name, recorder)?,
+                InstallerType::Ssh => self.install_ssh(&root, &group_config.ssh_keys, recorder)?,
+                InstallerType::Gpg => self.install_gpg(&root, &group_config, recorder)?,
+                InstallerType::Fonts => self.install_fonts(&root, &group_config.fonts, recorder)?,
+                InstallerType::Zshrc => self.install_zshrc(&root, &group_config.scripts, recorder)?,
+                InstallerType::Plugins => self.install_plugins(&group_config.plugins)?,
+                _ => {
+                    if !group_config.packages.is_empty() {
+                        output::info(format!("ℹ️  No installer registered for '{}'; skipping its packages", group_name));
+                    }
+                }
+            }
+        }
+        
+        self.install_files(&root, &group_config.files, recorder)?;
+        self.apply_macos_defaults(group_name, &group_config.macos_defaults, recorder)?;
+        self.install_services(&root, &group_config.services, recorder)?;
+        self.install_cron(group_name, &group_config.cron, recorder)?;
+        self.run_install_script(group_name)
+    }
+    
+    /// Writes the group's jobs into its managed block of the user's crontab.
+    fn install_cron(&self, group_name: &str, jobs: &[CronJob], recorder: &mut ChangeRecorder) -> Result<()> {
+        if jobs.is_empty() {
+            return Ok(());
+        }
+        if !cron::is_available() {
+            output::info(format!("ℹ️  Skipping {} cron job(s); there's no crontab here", jobs.len()));
+            return Ok(());
+        }
+        if self.dry_run {
+            for line in cron::render(jobs).lines() {
+                println!("   [dry-run] crontab: {}", line);
+            }
+            return Ok(());
+        }
+        
+        let previous = match cron::apply(group_name, jobs)? {
+            Some(previous) => {
+                output::info(format!("⏰ Updated {} cron job(s)", jobs.len()));
+                previous
+            }
+            None => cron::render(jobs),
+        };
+        recorder.crontab_written(group_name, previous);
         Ok(())
     }
     
-    fn uninstall_brew(&self, packages: &[String]) -> Result<()> {
-        if packages.is_empty() {
+    /// Deploys the group's systemd units or launchd agents for this OS from
+    /// its repo `root`, then enables and starts those marked `enable`.
+    fn install_services(&self, root: &Path, units: &[Service], recorder: &mut ChangeRecorder) -> Result<()> {
+        if units.is_empty() {
             return Ok(());
         }
         
-        Command::new("brew")
-            .arg("uninstall")
-            .args(packages)
-            .output()
-            .context("Failed to run brew uninstall")?;
+        let home_dir = self.home_dir()?;
+        let os = OsType::detect();
+        let mut deployed = Vec::new();
+        for unit in units {
+            let Some(target) = services::target(&unit.source, &home_dir, &os) else {
+                output::info(format!("ℹ️  Skipping {}; it's not a service for this OS", unit.source.display()));
+                continue;
+            };
+            if self.dry_run {
+                let enable = if unit.enable { " and enable it" } else { "" };
+                println!("   [dry-run] deploy {} to {}{}", unit.source.display(), target.display(), enable);
+                continue;
+            }
+            
+            let source = root.join(&unit.source);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            recorder.before_write(&target)?;
+            fs::copy(&source, &target).with_context(|| format!("Failed to copy {}", source.display()))?;
+            deployed.push((target, unit.enable));
+        }
         
+        if deployed.iter().any(|(target, _)| ServiceManager::for_file(target) == Some(ServiceManager::Systemd)) {
+            services::reload(ServiceManager::Systemd)?;
+        }
+        for (target, enable) in deployed {
+            if enable {
+                services::enable(&target)?;
+                recorder.service_enabled(&target);
+                output::info(format!("⚙️  Enabled {}", target.display()));
+            }
+        }
         Ok(())
     }
     
-    fn install_npm(&self, packages: &[String]) -> Result<()> {
-        if packages.is_empty() {
+    /// Stops, disables and removes the group's deployed units and agents.
+    fn remove_services(&self, units: &[Service]) -> Result<()> {
+        let home_dir = self.home_dir()?;
+        let os = OsType::detect();
+        let mut reload = false;
+        for unit in units {
+            let Some(target) = services::target(&unit.source, &home_dir, &os) else {
+                continue;
+            };
+            if !target.exists() {
+                continue;
+            }
+            if self.dry_run {
+                println!("   [dry-run] disable and remove {}", target.display());
+                continue;
+            }
+            services::disable(&target)?;
+            fs::remove_file(&target)?;
+            reload |= ServiceManager::for_file(&target) == Some(ServiceManager::Systemd);
+        }
+        if reload {
+            services::reload(ServiceManager::Systemd)?;
+        }
+        Ok(())
+    }
+    
+    /// Writes the group's `defaults` settings on macOS, recording each key's
+    /// value from before zshrcman first changed it (kept from the last
+    /// install's change set) so rollbacks and removals can put it back.
+    fn apply_macos_defaults(&self, group_name: &str, defaults: &[MacosDefault], recorder: &mut ChangeRecorder) -> Result<()> {
+        if defaults.is_empty() {
+            return Ok(());
+        }
+        if OsType::detect() != OsType::MacOS {
+            output::info(format!("ℹ️  Skipping {} macOS defaults; this isn't a Mac", defaults.len()));
             return Ok(());
         }
+        if self.dry_run {
+            for entry in defaults {
+                println!("   [dry-run] defaults {}", macos_defaults::write_args(&entry.domain, &entry.key, &entry.value).join(" "));
+            }
+            return Ok(());
+        }
+        
+        let last = ChangeSet::load(&self.config_mgr.data_dir(), group_name)?;
+        let mut restarts: Vec<&str> = Vec::new();
+        for entry in defaults {
+            let recorded = last.iter().flat_map(|set| &set.changes).find_map(|change| match change {
+                Change::DefaultsWritten { domain, key, previous } if *domain == entry.domain && *key == entry.key => {
+                    Some(previous.clone())
+                }
+                _ => None,
+            });
+            let current = macos_defaults::read(&entry.domain, &entry.key)?;
+            if current.as_ref() != Some(&entry.value) {
+                macos_defaults::write(entry)?;
+                if let Some(process) = entry.restart.as_deref().filter(|process| !restarts.contains(process)) {
+                    restarts.push(process);
+                }
+            }
+            recorder.defaults_written(&entry.domain, &entry.key, recorded.unwrap_or(current));
+        }
         
-        let output = Command::new("npm")
-            .arg("install")
-            .arg("-g")
-            .args(packages)
-            .output()
-            .context("Failed to run npm install")?;
+        for process in restarts {
+            macos_defaults::restart(process);
+        }
+        Ok(())
+    }
+    
+    /// Installs `packages`, retrying with exponential backoff according to the
+    /// installer's `[install]` policy. Retries skip packages that made it in.
+    fn install_packages(&self, installer: &dyn Installer, packages: &[String], attempts: &mut u32) -> Result<()> {
+        let policy = self.config_mgr.config.install.policy_for(installer.name());
+        let mut pending = packages.to_vec();
         
-        if !output.status.success() {
-            anyhow::bail!("npm install failed: {}", String::from_utf8_lossy(&output.stderr));
+        loop {
+            *attempts += 1;
+            let error = match installer.install(&pending) {
+                Ok(()) => {
+                    self.emit_installed(installer, packages);
+                    return Ok(());
+                }
+                Err(e) => e,
+            };
+            
+            if self.dry_run || *attempts > policy.retries {
+                let error = errors::tag(ErrorKind::Installer, error);
+                if *attempts > 1 {
+                    return Err(error.context(format!("Giving up after {} attempts", attempts)));
+                }
+                return Err(error);
+            }
+            
+            let delay = policy.backoff(*attempts);
+            output::warn(format!(
+                "⚠️  {} (attempt {}/{}); retrying in {}s",
+                error,
+                attempts,
+                policy.retries + 1,
+                delay.as_secs()
+            ));
+            std::thread::sleep(delay);
+            
+            pending = self.not_installed(installer, &pending);
+            if pending.is_empty() {
+                self.emit_installed(installer, packages);
+                return Ok(());
+            }
+        }
+    }
+    
+    fn emit_installed(&self, installer: &dyn Installer, packages: &[String]) {
+        if self.dry_run {
+            return;
+        }
+        for package in packages {
+            events::emit(StreamEvent::PackageInstalled {
+                installer: installer.name().to_string(),
+                package: package.clone(),
+            });
         }
+    }
+    
+    /// `packages` minus held or pinned ones that are already installed, so
+    /// reinstalling a group can't upgrade them. Held packages that are
+    /// missing still get installed.
+    fn without_installed_holds(&self, installer: &dyn Installer, packages: &[String]) -> Vec<String> {
+        let (held, packages): (Vec<String>, Vec<String>) = packages.iter().cloned().partition(|package| {
+            (self.config_mgr.is_held(package) || self.config_mgr.is_pinned(package))
+                && installer.is_installed(package).unwrap_or(false)
+        });
+        if !held.is_empty() {
+            output::info(format!("📌 Held, left as installed: {}", held.join(", ")));
+        }
+        packages
+    }
+    
+    /// `packages` minus held ones, which bulk removals never uninstall, and
+    /// pinned ones unless `force_pinned` is set.
+    fn without_holds(&self, packages: &[String]) -> Vec<String> {
+        let (held, packages): (Vec<String>, Vec<String>) =
+            packages.iter().cloned().partition(|package| self.config_mgr.is_held(package));
+        if !held.is_empty() {
+            output::info(format!("📌 Held, not removed: {}", held.join(", ")));
+        }
+        let (pinned, packages): (Vec<String>, Vec<String>) = packages
+            .into_iter()
+            .partition(|package| self.config_mgr.is_pinned(package) && !self.force_pinned);
+        if !pinned.is_empty() {
+            output::info(format!("📌 Pinned, not removed (--force-pinned removes them): {}", pinned.join(", ")));
+        }
+        packages
+    }
+    
+    /// Packages the installer doesn't report as installed yet; only these are
+    /// removed again by a rollback.
+    fn not_installed(&self, installer: &dyn Installer, packages: &[String]) -> Vec<String> {
+        if self.dry_run {
+            return vec![];
+        }
+        packages
+            .iter()
+            .filter(|package| !installer.is_installed(package).unwrap_or(false))
+            .cloned()
+            .collect()
+    }
+    
+    /// Undoes the last install of `group_name`: restores overwritten files,
+    /// removes appended text and uninstalls packages that run added.
+    pub fn rollback_group(&mut self, group_name: &str) -> Result<()> {
+        self.load_plugins();
+        let data_dir = self.config_mgr.data_dir();
         
+        let Some(changes) = ChangeSet::load(&data_dir, group_name)? else {
+            anyhow::bail!("No recorded install of group '{}' to roll back", group_name);
+        };
+        
+        output::info(format!("⏪ Rolling back group '{}'...", group_name));
+        let mut failures = 0;
+        let mut removed = Vec::new();
+        for change in changes.changes.iter().rev() {
+            let result = match change {
+                Change::PackagesInstalled { installer, packages } => match self.registry.get(installer) {
+                    Some(installer) => {
+                        let packages = self.without_holds(&self.config_mgr.group_packages_to_remove(group_name, packages));
+                        uninstall_unless_empty(installer, &packages).map(|()| removed.extend(packages))
+                    }
+                    None => Err(anyhow::anyhow!("Installer '{}' is no longer available", installer)),
+                },
+                _ => changeset::revert_file_change(change),
+            };
+            
+            if let Err(e) = result {
+                println!("⚠️  {}", e);
+                failures += 1;
+            }
+        }
+        
+        ChangeSet::discard(&data_dir, group_name)?;
+        self.config_mgr.release_group_packages(group_name, &removed)?;
+        self.config_mgr.update_install_status(group_name, InstallStatus {
+            installed: false,
+            success: failures == 0,
+            timestamp: Some(chrono::Utc::now()),
+            error: (failures > 0).then(|| format!("{} change(s) could not be rolled back", failures)),
+            attempts: 0,
+            fingerprint: None,
+        })?;
+        
+        if failures > 0 {
+            return Err(errors::fail(
+                ErrorKind::Partial,
+                format!("Rolled back group '{}' with {} failure(s)", group_name, failures),
+            ));
+        }
         Ok(())
     }
     
-    fn uninstall_npm(&self, packages: &[String]) -> Result<()> {
-        if packages.is_empty() {
-            return Ok(());
+    /// Compares what's deployed on this machine with what the config and
+    /// `plugins.lock` declare: the files, packages and plugins of installed
+    /// groups, groups still installed but no longer enabled, the managed
+    /// blocks of the startup files and the active profile's bin links.
+    /// Nothing is changed.
+    pub fn verify(&mut self) -> Result<Vec<Drift>> {
+        self.load_plugins();
+        let home = self.home_dir()?;
+        let vars = self.template_vars()?;
+        let mut drift = vec![];
+        
+        let enabled = self.config_mgr.get_ordered_groups();
+        let mut extraneous: Vec<&String> = self
+            .config_mgr
+            .config
+            .status
+            .iter()
+            .filter(|(group, status)| status.installed && !enabled.contains(group))
+            .map(|(group, _)| group)
+            .collect();
+        extraneous.sort();
+        for group in extraneous {
+            drift.push(Drift::new(
+                DriftKind::Extraneous,
+                group,
+                Subject::Group { name: group.clone() },
+                "installed but no longer enabled",
+            ));
         }
         
-        Command::new("npm")
-            .arg("uninstall")
-            .arg("-g")
-            .args(packages)
-            .output()
-            .context("Failed to run npm uninstall")?;
+        let installed: Vec<String> = self
+            .config_mgr
+            .get_ordered_groups()
+            .into_iter()
+            .filter(|group| {
+                self.config_mgr.config.status.get(group).is_some_and(|status| status.installed && status.success)
+                    && self.unmet_condition(group).is_none()
+            })
+            .collect();
+        for group in &installed {
+            let Some(group_config) = self.load_group(group) else {
+                continue;
+            };
+            if !self.is_up_to_date(group, &self.fingerprint(group)?) {
+                drift.push(Drift::new(
+                    DriftKind::OutOfDate,
+                    group,
+                    Subject::Group { name: group.clone() },
+                    "changed since it was installed",
+                ));
+            }
+            
+            let (root, _) = self.config_mgr.resolve_group(group);
+            for mapping in &group_config.files {
+                let source = root.join(&mapping.source);
+                let expected = if mapping.is_template() {
+                    vars.render_file(&source).map(String::into_bytes)
+                } else {
+                    fs::read(&source).map_err(Into::into)
+                };
+                // A missing source is a config problem for `config validate`.
+                if let Ok(expected) = expected {
+                    drift.extend(verify::check_file(group, &file_target(&home, mapping), &expected));
+                }
+            }
+            
+            if let Some(installer) = self.installer_for(group, &group_config)? {
+                for package in group_config.package_ids() {
+                    if !installer.is_installed(&package).unwrap_or(false) {
+                        drift.push(Drift::new(
+                            DriftKind::NotInstalled,
+                            group,
+                            Subject::Package { name: package },
+                            "was uninstalled outside zshrcman",
+                        ));
+                    }
+                }
+            }
+        }
+        
+        for (env_mgr, label, expected) in self.managed_blocks()? {
+            drift.extend(verify::check_block(label, &expected, env_mgr.read_block(label)?.as_deref()));
+        }
         
+        let data_dir = self.config_mgr.data_dir();
+        let lock = Lockfile::load(self.config_mgr.dotfiles_path())?;
+        for plugin in self.zsh_plugins() {
+            let Some(locked) = lock.plugins.get(&plugin.name) else {
+                continue;
+            };
+            let dir = zsh_plugins::checkout_dir(&data_dir, &plugin.name);
+            let item = Subject::Plugin { name: plugin.name.clone() };
+            if !dir.exists() {
+                drift.push(Drift::new(DriftKind::Missing, verify::STARTUP_FILES, item, "checkout is gone"));
+                continue;
+            }
+            let head = GitManager::open(&dir).ok().and_then(|git| git.head_id());
+            if head.as_deref() != Some(locked.commit.as_str()) {
+                let head = head.map(|head| head[..8.min(head.len())].to_string()).unwrap_or_else(|| "nothing".to_string());
+                let detail = format!("at {} instead of the locked {}", head, &locked.commit[..8.min(locked.commit.len())]);
+                drift.push(Drift::new(DriftKind::WrongCommit, verify::STARTUP_FILES, item, detail));
+            }
+        }
+        
+        let config = &self.config_mgr.config;
+        if let Some((name, profile)) = config.active_profile.as_ref().and_then(|name| config.profiles.get_key_value(name)) {
+            let bin = data_dir.join("profiles").join(name).join("bin");
+            let owner = format!("profile '{}'", name);
+            let records: Vec<&InstallationRecord> = profile.packages.iter().filter_map(|package| config.installations.get(package)).collect();
+            let plan = bin_links::plan(&records);
+            for link in plan.links.iter().chain(&plan.missing) {
+                drift.extend(verify::check_link(&owner, &bin.join(&link.name), &link.target));
+            }
+        }
+        Ok(drift)
+    }
+    
+    /// Where the `files` of installed groups are deployed.
+    pub fn deployed_files(&self) -> Result<Vec<PathBuf>> {
+        let home = self.home_dir()?;
+        let mut files = vec![];
+        for group in self.config_mgr.get_ordered_groups() {
+            let installed = self.config_mgr.config.status.get(&group).is_some_and(|status| status.installed);
+            if let Some(group_config) = self.load_group(&group).filter(|_| installed) {
+                files.extend(group_config.files.iter().map(|mapping| file_target(&home, mapping)));
+            }
+        }
+        Ok(files)
+    }
+    
+    /// Repairs what `verify` found: installs out-of-date groups and missing
+    /// packages again, rewrites drifted files and managed blocks, checks out
+    /// plugins at their locked commits, relinks profile bins and removes
+    /// groups that are no longer enabled. Returns how many items were fixed.
+    pub fn apply(&mut self, drift: &[Drift]) -> Result<usize> {
+        self.load_plugins();
+        let mut fixed = 0;
+        let mut failures = 0;
+        let mut blocks_written = false;
+        
+        for item in drift {
+            let result = match (&item.kind, &item.subject) {
+                (DriftKind::Extraneous, Subject::Group { name }) => self.remove_group(name),
+                (DriftKind::OutOfDate, Subject::Group { name }) => self.reinstall_group(name),
+                (_, Subject::File { path }) => self.repair_file(&item.owner, path),
+                (_, Subject::Package { name }) => self.repair_package(&item.owner, name),
+                (_, Subject::Block { .. }) if blocks_written => Ok(()),
+                (_, Subject::Block { .. }) => {
+                    blocks_written = true;
+                    self.write_blocks()
+                }
+                (_, Subject::Plugin { name }) => {
+                    let plugins: Vec<ZshPlugin> = self.zsh_plugins().into_iter().filter(|plugin| &plugin.name == name).collect();
+                    self.install_plugins(&plugins)
+                }
+                (_, Subject::Link { path, location }) => relink(path, location),
+                (kind, subject) => Err(anyhow::anyhow!("Don't know how to repair {:?} {}", kind, subject)),
+            };
+            match result {
+                Ok(()) => fixed += 1,
+                Err(e) => {
+                    println!("⚠️  Failed to repair {}: {:#}", item.subject, e);
+                    failures += 1;
+                }
+            }
+        }
+        
+        if failures > 0 {
+            let message = format!("{} of {} item(s) could not be repaired", failures, drift.len());
+            let kind = if fixed > 0 { ErrorKind::Partial } else { ErrorKind::Other };
+            return Err(errors::fail(kind, message));
+        }
+        Ok(fixed)
+    }
+    
+    /// Removes a group that is installed but no longer enabled, by rolling
+    /// back its last install when one was recorded.
+    fn remove_group(&mut self, group_name: &str) -> Result<()> {
+        output::info(format!("🗑️  Removing group '{}'...", group_name));
+        if ChangeSet::load(&self.config_mgr.data_dir(), group_name)?.is_some() {
+            return self.rollback_group(group_name);
+        }
+        self.uninstall_group(group_name)?;
+        self.config_mgr.update_install_status(group_name, InstallStatus {
+            installed: false,
+            success: true,
+            timestamp: Some(chrono::Utc::now()),
+            error: None,
+            attempts: 0,
+            fingerprint: None,
+        })
+    }
+    
+    fn reinstall_group(&mut self, group_name: &str) -> Result<()> {
+        output::info(format!("📦 Reinstalling group '{}'...", group_name));
+        let fingerprint = self.fingerprint(group_name)?;
+        let mut attempts = 0;
+        self.install_group(group_name, &mut attempts)?;
+        self.config_mgr.update_install_status(group_name, InstallStatus {
+            installed: true,
+            success: true,
+            timestamp: Some(chrono::Utc::now()),
+            error: None,
+            attempts,
+            fingerprint: (!self.dry_run).then_some(fingerprint),
+        })
+    }
+    
+    /// Copies or renders the mapping of `group_name` that targets `target`
+    /// again. The group's change set keeps what its install recorded, so an
+    /// edited file is first copied aside with `tidy::backup`.
+    fn repair_file(&self, group_name: &str, target: &Path) -> Result<()> {
+        let Some(group_config) = self.load_group(group_name) else {
+            anyhow::bail!("Group '{}' no longer exists", group_name);
+        };
+        let home = self.home_dir()?;
+        let Some(mapping) = group_config.files.iter().find(|mapping| file_target(&home, mapping) == target) else {
+            anyhow::bail!("Group '{}' no longer deploys {}", group_name, target.display());
+        };
+        
+        let (root, _) = self.config_mgr.resolve_group(group_name);
+        let source = root.join(&mapping.source);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let backup = if target.is_file() { Some(tidy::backup(target)?) } else { None };
+        if mapping.is_template() {
+            fs::write(target, self.template_vars()?.render_file(&source)?)?;
+        } else {
+            fs::copy(&source, target).with_context(|| format!("Failed to copy {}", source.display()))?;
+        }
+        events::emit(StreamEvent::FileWritten { path: target.to_path_buf() });
+        match backup {
+            Some(backup) => output::info(format!("📄 Restored {}; the edited copy is {}", target.display(), backup.display())),
+            None => output::info(format!("📄 Restored {}", target.display())),
+        }
         Ok(())
     }
     
-    fn install_pnpm(&self, packages: &[String]) -> Result<()> {
-        if packages.is_empty() {
+    fn repair_package(&self, group_name: &str, package: &str) -> Result<()> {
+        let Some(group_config) = self.load_group(group_name) else {
+            anyhow::bail!("Group '{}' no longer exists", group_name);
+        };
+        let Some(installer) = self.installer_for(group_name, &group_config)? else {
+            anyhow::bail!("Group '{}' has no installer", group_name);
+        };
+        let mut attempts = 0;
+        self.install_packages(installer, &[package.to_string()], &mut attempts)
+    }
+    
+    /// Rewrites every managed block of the startup files.
+    fn write_blocks(&self) -> Result<()> {
+        self.write_named_dirs()?;
+        self.write_variables()?;
+        self.write_functions()?;
+        self.write_completions()?;
+        self.write_options()?;
+        self.write_lazy()?;
+        self.write_plugins()
+    }
+    
+    /// The managed blocks `install` writes, with the manager of the file
+    /// each lives in and the content it should have.
+    fn managed_blocks(&self) -> Result<Vec<(EnvironmentManager, &'static str, String)>> {
+        let home = self.home_dir()?;
+        let env_mgr = || EnvironmentManager::new().with_home(home.clone());
+        let zsh = || EnvironmentManager::for_shell(ShellType::Zsh).with_home(home.clone());
+        
+        let named_dirs = env_mgr().named_dirs_block(&self.named_dirs().to_map());
+        let variables = env_mgr().variables_block(&self.env_layers().group_variables());
+        let functions = {
+            let env_mgr = env_mgr();
+            functions::loader(self.functions().list(), env_mgr.shell_type().name())
+        };
+        let plugins = zsh_plugins::source_lines(&self.config_mgr.data_dir(), &self.zsh_plugins());
+        Ok(vec![
+            (env_mgr(), environment::NAMED_DIRS_BLOCK, named_dirs),
+            (env_mgr(), environment::VARIABLES_BLOCK, variables),
+            (env_mgr(), environment::FUNCTIONS_BLOCK, functions),
+            (zsh(), environment::COMPLETIONS_BLOCK, completions_block(&self.completion_files().0)),
+            (zsh(), environment::OPTIONS_BLOCK, self.zsh_options().render()),
+            (zsh(), environment::LAZY_BLOCK, self.lazy_loads().render()),
+            (zsh(), environment::PLUGINS_BLOCK, plugins),
+        ])
+    }
+    
+    fn template_vars(&self) -> Result<TemplateVars> {
+        Ok(TemplateVars::from_config(&self.config_mgr.config, &self.home_dir()?, &self.facts))
+    }
+    
+    /// Copies each mapping's source from the dotfiles repo to its target,
+    /// rendering templates on the way.
+    /// Copies or renders `files`, whose sources are relative to `root`.
+    fn install_files(&self, root: &Path, files: &[FileMapping], recorder: &mut ChangeRecorder) -> Result<()> {
+        if files.is_empty() {
             return Ok(());
         }
         
-        let output = Command::new("pnpm")
-            .arg("add")
-            .arg("-g")
-            .args(packages)
-            .output()
-            .context("Failed to run pnpm add")?;
+        let home_dir = self.home_dir()?;
+        let vars = self.template_vars()?;
         
-        if !output.status.success() {
-            anyhow::bail!("pnpm add failed: {}", String::from_utf8_lossy(&output.stderr));
+        for mapping in files {
+            let source = root.join(&mapping.source);
+            let target = file_target(&home_dir, mapping);
+            
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            
+            recorder.before_write(&target)?;
+            if mapping.is_template() {
+                fs::write(&target, vars.render_file(&source)?)?;
+            } else {
+                fs::copy(&source, &target)
+                    .with_context(|| format!("Failed to copy {}", source.display()))?;
+            }
+            events::emit(StreamEvent::FileWritten { path: target });
         }
         
         Ok(())
     }
     
-    fn uninstall_pnpm(&self, packages: &[String]) -> Result<()> {
-        if packages.is_empty() {
+    /// Runs `groups/<name>/install.rhai` (or the device-specific one) if present.
+    fn run_install_script(&self, group_name: &str) -> Result<()> {
+        let dotfiles_path = self.config_mgr.dotfiles_path();
+        let (root, name) = self.config_mgr.resolve_group(group_name);
+        let candidates = [
+            root.join("groups").join(name),
+            dotfiles_path
+                .join("devices")
+                .join(&self.config_mgr.config.device.name)
+                .join("groups")
+                .join(group_name),
+        ];
+        
+        let Some(group_dir) = candidates
+            .into_iter()
+            .find(|dir| dir.join(scripting::INSTALL_SCRIPT).is_file())
+        else {
             return Ok(());
-        }
+        };
+        
+        output::info(format!("📜 Running {} for '{}'", scripting::INSTALL_SCRIPT, group_name));
+        let context = ScriptContext {
+            group: group_name.to_string(),
+            group_dir: group_dir.clone(),
+            home: self.home_dir()?,
+            dry_run: self.dry_run,
+        };
+        scripting::run_install_script(&group_dir.join(scripting::INSTALL_SCRIPT), &context)
+    }
+    
+    /// Undoes the group's services, cron jobs and `defaults`, and uninstalls
+    /// the packages only it needed.
+    fn uninstall_group(&mut self, group_name: &str) -> Result<()> {
+        let Some(group_config) = self.load_group(group_name) else {
+            return Ok(());
+        };
         
-        Command::new("pnpm")
-            .arg("remove")
-            .arg("-g")
-            .args(packages)
-            .output()
-            .context("Failed to run pnpm remove")?;
+        self.restore_macos_defaults(group_name)?;
+        self.remove_services(&group_config.services)?;
+        if !group_config.cron.is_empty() && cron::is_available() && !self.dry_run {
+            cron::apply(group_name, &[])?;
+        }
+        if let Some(installer) = self.installer_for(group_name, &group_config)? {
+            let packages = self.without_holds(&self.config_mgr.group_packages_to_remove(group_name, &group_config.package_ids()));
+            uninstall_unless_empty(installer, &packages)?;
+            if !self.dry_run {
+                self.config_mgr.release_group_packages(group_name, &packages)?;
+            }
+            return Ok(());
+        }
         
+        match InstallerType::from_group_name(self.config_mgr.resolve_group(group_name).1) {
+            InstallerType::Aliases => self.uninstall_aliases(),
+            _ => Ok(()),
+        }
+    }
+    
+    /// Puts back the `defaults` values the group's last install changed.
+    fn restore_macos_defaults(&self, group_name: &str) -> Result<()> {
+        let Some(changes) = ChangeSet::load(&self.config_mgr.data_dir(), group_name)? else {
+            return Ok(());
+        };
+        for change in changes.changes.iter().rev() {
+            if matches!(change, Change::DefaultsWritten { .. }) {
+                changeset::revert_file_change(change)?;
+            }
+        }
         Ok(())
     }
     
-    fn install_aliases(&self, group_name: &str) -> Result<()> {
-        let home_dir = dirs::home_dir().context("Could not find home directory")?;
+    fn install_aliases(&self, group_name: &str, recorder: &mut ChangeRecorder) -> Result<()> {
+        let home_dir = self.home_dir()?;
         let aliases_file = home_dir.join(".zsh_aliases");
         
         let mut aliases_content = if aliases_file.exists() {
@@ -248,21 +1434,24 @@ impl InstallManager {
             String::new()
         };
         
+        let mut added = String::new();
         if let Some(alias_group) = self.config_mgr.config.aliases.get(group_name) {
-            aliases_content.push_str(&format!("\n# Aliases from zshrcman group '{}'\n", group_name));
+            added.push_str(&format!("\n# Aliases from zshrcman group '{}'\n", group_name));
             
-            for alias in &alias_group.active {
-                aliases_content.push_str(&format!("{}\n", alias));
+            for line in alias_group.active.iter().filter_map(|alias| alias.render("zsh")) {
+                added.push_str(&format!("{}\n", line));
             }
         }
+        aliases_content.push_str(&added);
         
         fs::write(&aliases_file, aliases_content)?;
+        recorder.appended(&aliases_file, &added);
         
         Ok(())
     }
     
     fn uninstall_aliases(&self) -> Result<()> {
-        let home_dir = dirs::home_dir().context("Could not find home directory")?;
+        let home_dir = self.home_dir()?;
         let aliases_file = home_dir.join(".zsh_aliases");
         
         if aliases_file.exists() {
@@ -279,22 +1468,23 @@ impl InstallManager {
         Ok(())
     }
     
-    fn install_ssh(&self, keys: &[String]) -> Result<()> {
+    /// Copies `ssh/<key>` from the group's repo `root` into `~/.ssh`.
+    fn install_ssh(&self, root: &Path, keys: &[String], recorder: &mut ChangeRecorder) -> Result<()> {
         if keys.is_empty() {
             return Ok(());
         }
         
-        let dotfiles_path = ConfigManager::get_dotfiles_path()?;
-        let home_dir = dirs::home_dir().context("Could not find home directory")?;
+        let home_dir = self.home_dir()?;
         let ssh_dir = home_dir.join(".ssh");
         
         fs::create_dir_all(&ssh_dir)?;
         
         for key_name in keys {
-            let source = dotfiles_path.join("ssh").join(key_name);
+            let source = root.join("ssh").join(key_name);
             let target = ssh_dir.join(key_name);
             
             if source.exists() {
+                recorder.before_write(&target)?;
                 fs::copy(&source, &target)?;
                 
                 #[cfg(unix)]
@@ -305,22 +1495,106 @@ impl InstallManager {
                     fs::set_permissions(&target, perms)?;
                 }
                 
-                Command::new("ssh-add")
-                    .arg(&target)
-                    .output()
-                    .context("Failed to run ssh-add")?;
+                if self.dry_run {
+                    println!("   [dry-run] ssh-add {}", target.display());
+                } else {
+                    Command::new("ssh-add")
+                        .arg(&target)
+                        .output()
+                        .context("Failed to run ssh-add")?;
+                }
             }
         }
         
         Ok(())
     }
     
-    fn install_zshrc(&self, scripts: &[String]) -> Result<()> {
+    /// Imports `gpg/<key>` from the group's repo `root`, sets git up to sign
+    /// commits with `signing_key` and checks that signing works.
+    fn install_gpg(&self, root: &Path, group: &GroupConfig, recorder: &mut ChangeRecorder) -> Result<()> {
+        if group.gpg_keys.is_empty() && group.signing_key.is_none() {
+            return Ok(());
+        }
+        
+        if self.dry_run {
+            for key_name in &group.gpg_keys {
+                println!("   [dry-run] gpg --import {}", root.join(gpg::KEYS_DIR).join(key_name).display());
+            }
+            if let Some(key) = &group.signing_key {
+                for (name, value) in gpg::git_settings(key) {
+                    println!("   [dry-run] git config --global {} {}", name, value);
+                }
+            }
+            return Ok(());
+        }
+        
+        let keys: Vec<PathBuf> = group.gpg_keys.iter().map(|key_name| root.join(gpg::KEYS_DIR).join(key_name)).collect();
+        let missing: Vec<String> = keys.iter().filter(|key| !key.exists()).map(|key| key.display().to_string()).collect();
+        if !missing.is_empty() {
+            anyhow::bail!("gpg_keys of '{}' not found: {}", group.name, missing.join(", "));
+        }
+        
+        gpg::ensure_installed()?;
+        for key in &keys {
+            gpg::import(key)?;
+        }
+        
+        if let Some(key) = &group.signing_key {
+            let gitconfig = self.home_dir()?.join(".gitconfig");
+            recorder.before_write(&gitconfig)?;
+            gpg::configure_git(&gitconfig, key)?;
+            gpg::verify(key)?;
+            output::info(format!("🔏 git signs commits with {}", key));
+        }
+        
+        Ok(())
+    }
+    
+    /// Copies each font's files, from the group's repo `root` or downloaded,
+    /// into the platform's fonts directory and refreshes the font cache.
+    fn install_fonts(&self, root: &Path, fonts: &[Font], recorder: &mut ChangeRecorder) -> Result<()> {
+        if fonts.is_empty() {
+            return Ok(());
+        }
+        
+        let os = OsType::detect();
+        let target_dir = fonts::fonts_dir(&self.home_dir()?, &os);
+        if self.dry_run {
+            for font in fonts {
+                let from = font.url.clone().or_else(|| font.source.as_ref().map(|source| root.join(source).display().to_string()));
+                println!("   [dry-run] install font '{}' from {} into {}", font.name, from.unwrap_or_default(), target_dir.display());
+            }
+            return Ok(());
+        }
+        
+        fs::create_dir_all(&target_dir)?;
+        let mut installed = Vec::new();
+        for font in fonts {
+            let files = fonts::font_files(&fonts::fetch(font, root, &self.config_mgr.cache_dir())?);
+            if files.is_empty() {
+                anyhow::bail!("Font '{}' has no .ttf, .otf or .ttc files", font.name);
+            }
+            for file in files {
+                let Some(file_name) = file.file_name() else {
+                    continue;
+                };
+                let target = target_dir.join(file_name);
+                recorder.before_write(&target)?;
+                fs::copy(&file, &target).with_context(|| format!("Failed to copy {}", file.display()))?;
+                installed.push(target);
+            }
+            output::info(format!("🔤 Installed font '{}'", font.name));
+        }
+        fonts::refresh(&target_dir, &os, &installed)
+    }
+    
+    /// Sources `scripts/<script>` from the group's repo `root` in `.zshrc`.
+    fn install_zshrc(&self, root: &Path, scripts: &[String], recorder: &mut ChangeRecorder) -> Result<()> {
         if scripts.is_empty() {
             return Ok(());
         }
         
-        let home_dir = dirs::home_dir().context("Could not find home directory")?;
+        let home_dir = self.home_dir()?;
         let zshrc_file = home_dir.join(".zshrc");
         
         let mut zshrc_content = if zshrc_file.exists() {
@@ -329,19 +1603,75 @@ impl InstallManager {
             String::new()
         };
         
-        let dotfiles_path = ConfigManager::get_dotfiles_path()?;
+        let rendered_dir = self.config_mgr.data_dir().join("rendered");
+        let vars = self.template_vars()?;
         
-        zshrc_content.push_str("\n# zshrcman managed scripts\n");
+        let mut added = String::from("\n# zshrcman managed scripts\n");
         
         for script in scripts {
-            let script_path = dotfiles_path.join("scripts").join(script);
-            if script_path.exists() {
-                zshrc_content.push_str(&format!("source {}\n", script_path.display()));
+            let mut script_path = root.join("scripts").join(script);
+            if !script_path.exists() {
+                continue;
             }
+            
+            // Templated fragments are rendered outside the repo and sourced from there.
+            if let Some(rendered_name) = script.strip_suffix(".tera") {
+                let rendered_path = rendered_dir.join(rendered_name);
+                if let Some(parent) = rendered_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&rendered_path, vars.render_file(&script_path)?)?;
+                script_path = rendered_path;
+            }
+            
+            added.push_str(&format!("source {}\n", script_path.display()));
         }
+        zshrc_content.push_str(&added);
         
         fs::write(&zshrc_file, zshrc_content)?;
+        recorder.appended(&zshrc_file, &added);
         
         Ok(())
     }
+}
+
+/// Points the profile bin `link` at `location` again.
+/// The built-in backends plus installer plugins, as group installs use them.
+pub fn installer_registry(config_mgr: &ConfigManager) -> InstallerRegistry {
+    let mut registry = InstallerRegistry::builtin(false, &config_mgr.config.install);
+    registry.register_plugins(&[config_mgr.data_dir().join("plugins")], false);
+    registry
+}
+
+fn relink(link: &Path, location: &Path) -> Result<()> {
+    if !location.exists() {
+        anyhow::bail!("{} no longer exists; reinstall the package", location.display());
+    }
+    bin_links::create(link, location)
+}
+
+/// Where `mapping` is deployed: `~/` is the home directory and
+/// `$VSCODE_USER/` VS Code's user directory. Other relative targets are
+/// relative to the home directory too, not to wherever zshrcman runs.
+fn file_target(home: &Path, mapping: &FileMapping) -> PathBuf {
+    match mapping.target.strip_prefix("~") {
+        Ok(relative) => home.join(relative),
+        Err(_) => vscode::resolve_target(&mapping.target, home, &OsType::detect()).unwrap_or_else(|| home.join(&mapping.target)),
+    }
+}
+
+/// The completions block for deployed completion `files`.
+fn completions_block(files: &BTreeMap<String, PathBuf>) -> String {
+    if files.is_empty() {
+        String::new()
+    } else {
+        completions::fpath_lines()
+    }
+}
+
+fn uninstall_unless_empty(installer: &dyn Installer, packages: &[String]) -> Result<()> {
+    if packages.is_empty() {
+        return Ok(());
+    }
+    installer.uninstall(packages).kind(ErrorKind::Installer)
 }
\ No newline at end of file
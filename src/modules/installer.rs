@@ -0,0 +1,423 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+use crate::models::InstallSettings;
+use crate::modules::facts::Facts;
+use crate::modules::flatpak::FlatpakInstaller;
+use crate::modules::mas::MasInstaller;
+use crate::modules::nix::NixInstaller;
+use crate::modules::output;
+use crate::modules::plugin;
+use crate::modules::windows_packages::WindowsInstaller;
+
+/// A package backend such as brew or npm. Implement this to teach zshrcman a
+/// new way of installing the `packages` listed in a group.
+pub trait Installer {
+    /// Backend name; a group with this name is installed by this backend.
+    fn name(&self) -> &str;
+    
+    fn install(&self, packages: &[String]) -> Result<()>;
+    
+    /// Best-effort removal. A package that is already gone is not an error.
+    fn uninstall(&self, packages: &[String]) -> Result<()>;
+    
+    /// Installed version of `package`, or `None` when it isn't installed.
+    fn version(&self, package: &str) -> Result<Option<String>>;
+    
+    fn is_installed(&self, package: &str) -> Result<bool> {
+        Ok(self.version(package)?.is_some())
+    }
+    
+    /// Where the installed package lives, for `InstallationRecord::location`
+    /// and the profile bin links. Backends that can't tell return `None`.
+    fn location(&self, _package: &str) -> Result<Option<PathBuf>> {
+        Ok(None)
+    }
+    
+    /// Every executable the installed package provides, each linked into the
+    /// profile bin under its own name. Defaults to just its `location`.
+    fn binaries(&self, package: &str) -> Result<Vec<PathBuf>> {
+        Ok(self.location(package)?.into_iter().collect())
+    }
+}
+
+/// An installer that shells out to a package manager CLI, e.g.
+/// `brew install <packages>` / `brew uninstall <packages>`.
+#[derive(Debug, Clone)]
+pub struct CommandInstaller {
+    name: String,
+    program: String,
+    install_args: Vec<String>,
+    uninstall_args: Vec<String>,
+    query_args: Vec<String>,
+    query_lists_all: bool,
+    prefix_args: Vec<String>,
+    parse: fn(&str, &str) -> Option<String>,
+    timeout: Option<Duration>,
+    dry_run: bool,
+}
+
+impl CommandInstaller {
+    pub fn new(name: &str, program: &str, install_args: &[&str], uninstall_args: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            program: program.to_string(),
+            install_args: install_args.iter().map(|s| s.to_string()).collect(),
+            uninstall_args: uninstall_args.iter().map(|s| s.to_string()).collect(),
+            query_args: vec![],
+            query_lists_all: false,
+            prefix_args: vec![],
+            parse: parse_version,
+            timeout: None,
+            dry_run: false,
+        }
+    }
+    
+    pub fn brew() -> Self {
+        Self::new("brew", "brew", &["install"], &["uninstall"])
+            .query_args(&["list", "--versions"])
+            .prefix_args(&["--prefix"])
+    }
+    
+    pub fn npm() -> Self {
+        Self::new("npm", "npm", &["install", "-g"], &["uninstall", "-g"])
+            .query_args(&["ls", "-g", "--depth=0"])
+    }
+    
+    pub fn pnpm() -> Self {
+        Self::new("pnpm", "pnpm", &["add", "-g"], &["remove", "-g"])
+            .query_args(&["ls", "-g", "--depth=0"])
+    }
+    
+    /// VS Code extensions, by id (`publisher.name`).
+    pub fn vscode() -> Self {
+        Self::new("vscode", "code", &["--install-extension"], &["--uninstall-extension"])
+            .query_args(&["--list-extensions", "--show-versions"])
+            .query_lists_all()
+    }
+    
+    /// Snap packages. Snaps needing classic confinement can't be installed
+    /// this way.
+    pub fn snap() -> Self {
+        Self::new("snap", "snap", &["install"], &["remove"])
+            .query_args(&["list"])
+    }
+    
+    /// winget packages, by id (e.g. `Git.Git`).
+    pub fn winget() -> Self {
+        Self::new(
+            "winget",
+            "winget",
+            &["install", "--exact", "--silent", "--accept-package-agreements", "--accept-source-agreements", "--id"],
+            &["uninstall", "--exact", "--silent", "--id"],
+        )
+        .query_args(&["list", "--exact", "--accept-source-agreements", "--id"])
+        .parse_with(parse_table_version)
+    }
+    
+    /// Scoop apps. Scoop is a PowerShell script with a `.cmd` shim on Windows.
+    pub fn scoop() -> Self {
+        let program = if cfg!(windows) { "scoop.cmd" } else { "scoop" };
+        Self::new("scoop", program, &["install"], &["uninstall"])
+            .query_args(&["list"])
+    }
+    
+    /// Chocolatey packages. Before Chocolatey 2.0 `list` searched the
+    /// community feed unless told `--local-only`; 2.0 only lists local
+    /// packages and rejects the flag.
+    pub fn choco() -> Self {
+        let installer = Self::new("choco", "choco", &["install", "-y"], &["uninstall", "-y"]);
+        if choco_major_version().is_some_and(|major| major < 2) {
+            installer.query_args(&["list", "--local-only", "--exact", "--limit-output"])
+        } else {
+            installer.query_args(&["list", "--exact", "--limit-output"])
+        }
+    }
+    
+    /// Arguments for listing one installed package, e.g. `brew list --versions <pkg>`.
+    /// Without them the backend can't tell what is installed and reports nothing.
+    pub fn query_args(mut self, args: &[&str]) -> Self {
+        self.query_args = args.iter().map(|s| s.to_string()).collect();
+        self
+    }
+    
+    /// The query arguments list every installed package, so the package
+    /// isn't passed to them.
+    pub fn query_lists_all(mut self) -> Self {
+        self.query_lists_all = true;
+        self
+    }
+    
+    /// Arguments that print where one package is installed, e.g.
+    /// `brew --prefix <pkg>`, whose `bin` then holds its executables.
+    pub fn prefix_args(mut self, args: &[&str]) -> Self {
+        self.prefix_args = args.iter().map(|s| s.to_string()).collect();
+        self
+    }
+    
+    /// Reads versions from the query output with `parse` instead of
+    /// [`parse_version`].
+    pub fn parse_with(mut self, parse: fn(&str, &str) -> Option<String>) -> Self {
+        self.parse = parse;
+        self
+    }
+    
+    /// The program this backend runs.
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+    
+    /// Kills a package manager command that runs longer than this.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    
+    /// Prints the commands instead of running them.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+    
+    /// Runs the command once per package so progress is visible per package.
+    fn run(&self, args: &[String], packages: &[String], check_status: bool) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+        
+        if self.dry_run {
+            println!("   [dry-run] {} {} {}", self.program, args.join(" "), packages.join(" "));
+            return Ok(());
+        }
+        
+        for (i, package) in packages.iter().enumerate() {
+            let label = format!("[{}/{}] {} {} {}", i + 1, packages.len(), self.program, args.join(" "), package);
+            let outcome = output::stream_command(Command::new(&self.program).args(args).arg(package), &label, self.timeout)
+                .with_context(|| format!("Failed to run {} {}", self.program, args.join(" ")))?;
+            
+            if outcome.timed_out {
+                anyhow::bail!(
+                    "{} {} {} timed out after {}s",
+                    self.program,
+                    args.join(" "),
+                    package,
+                    self.timeout.unwrap_or_default().as_secs()
+                );
+            }
+            if check_status && !outcome.status.success() {
+                anyhow::bail!(
+                    "{} {} {} failed: {}",
+                    self.program,
+                    args.join(" "),
+                    package,
+                    outcome.stderr_tail
+                );
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+impl Installer for CommandInstaller {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    
+    fn install(&self, packages: &[String]) -> Result<()> {
+        self.run(&self.install_args, packages, true)
+    }
+    
+    fn uninstall(&self, packages: &[String]) -> Result<()> {
+        self.run(&self.uninstall_args, packages, false)
+    }
+    
+    fn version(&self, package: &str) -> Result<Option<String>> {
+        if self.query_args.is_empty() {
+            return Ok(None);
+        }
+        
+        let mut query = Command::new(&self.program);
+        query.args(&self.query_args);
+        if !self.query_lists_all {
+            query.arg(package);
+        }
+        let output = match query.output() {
+            Ok(output) => output,
+            Err(_) => return Ok(None),
+        };
+        if !output.status.success() {
+            return Ok(None);
+        }
+        
+        Ok((self.parse)(&String::from_utf8_lossy(&output.stdout), package))
+    }
+
+    /// The executable named like the installed package, e.g. `node` for
+    /// `node@20`, in the `bin` of the prefix the backend reports, or else on
+    /// PATH. Links are kept as they are: brew's `opt/<pkg>` link, for one,
+    /// survives upgrades where the versioned directory behind it doesn't.
+    fn location(&self, package: &str) -> Result<Option<PathBuf>> {
+        if self.dry_run || !self.is_installed(package)? {
+            return Ok(None);
+        }
+        let executable = executable_name(package);
+        if !self.prefix_args.is_empty() {
+            let output = Command::new(&self.program).args(&self.prefix_args).arg(package).output().ok();
+            if let Some(output) = output.filter(|output| output.status.success()) {
+                let prefix = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                let path = PathBuf::from(&prefix).join("bin").join(executable);
+                if !prefix.is_empty() && path.exists() {
+                    return Ok(Some(path));
+                }
+            }
+        }
+        Ok(which::which(executable).ok())
+    }
+}
+
+/// The executable a package most likely provides: its name without a
+/// scope or version, e.g. `node` for `node@20` and `prettier` for
+/// `@acme/prettier`.
+pub fn executable_name(package: &str) -> &str {
+    let name = package.rsplit('/').next().unwrap_or(package);
+    match name.find('@') {
+        Some(at) if at > 0 => &name[..at],
+        _ => name,
+    }
+}
+
+/// The major version `choco --version` reports, if choco is installed.
+fn choco_major_version() -> Option<u32> {
+    which::which("choco").ok()?;
+    let output = Command::new("choco").arg("--version").output().ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().split('.').next()?.parse().ok()
+}
+
+/// Finds `package`'s version in package manager listing output. Understands
+/// `name 1.2.3` (brew, pnpm, scoop), `name@1.2.3` (npm) and `name|1.2.3`
+/// (choco) lines.
+pub fn parse_version(output: &str, package: &str) -> Option<String> {
+    for token in output.split_whitespace() {
+        let rest = token.strip_prefix(package);
+        if let Some(version) = rest.and_then(|rest| rest.strip_prefix('@').or_else(|| rest.strip_prefix('|'))) {
+            if !version.is_empty() {
+                return Some(version.to_string());
+            }
+        }
+    }
+    
+    output.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(version)) if name == package => Some(version.to_string()),
+            _ => None,
+        }
+    })
+}
+
+/// Finds `package`'s version in a table whose rows have the package id
+/// followed by its version in some column, like `winget list`'s
+/// `Name  Id  Version  Source`.
+pub fn parse_table_version(output: &str, package: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut tokens = line.split_whitespace().skip_while(|token| !token.eq_ignore_ascii_case(package));
+        tokens.next()?;
+        tokens.next().map(str::to_string)
+    })
+}
+
+/// Installers by name. A group is installed by the backend named in its
+/// `installer` field, or by the one sharing the group's name.
+#[derive(Default)]
+pub struct InstallerRegistry {
+    installers: BTreeMap<String, Box<dyn Installer>>,
+    /// Names whose backend is still the built-in one.
+    builtin: BTreeSet<String>,
+}
+
+impl InstallerRegistry {
+    /// A registry with the built-in backends (brew, npm, pnpm, vscode, snap,
+    /// flatpak, mas, nix, winget, scoop, choco and windows, which picks one
+    /// of those).
+    pub fn builtin(dry_run: bool, settings: &InstallSettings) -> Self {
+        let mut registry = Self::default();
+        registry.register_builtins(dry_run, settings);
+        registry
+    }
+    
+    /// (Re)registers the built-in backends, leaving custom ones in place.
+    /// Each gets the command timeout configured for it in `settings`.
+    pub fn register_builtins(&mut self, dry_run: bool, settings: &InstallSettings) {
+        for installer in [
+            CommandInstaller::brew(),
+            CommandInstaller::npm(),
+            CommandInstaller::pnpm(),
+            CommandInstaller::vscode(),
+            CommandInstaller::snap(),
+        ] {
+            let timeout = settings.policy_for(installer.name()).timeout();
+            self.register_builtin(installer.timeout(timeout).dry_run(dry_run));
+        }
+        let windows: Vec<CommandInstaller> = [CommandInstaller::winget(), CommandInstaller::scoop(), CommandInstaller::choco()]
+            .into_iter()
+            .map(|installer| {
+                let timeout = settings.policy_for(installer.name()).timeout();
+                installer.timeout(timeout).dry_run(dry_run)
+            })
+            .collect();
+        for installer in &windows {
+            self.register_builtin(installer.clone());
+        }
+        self.register_builtin(WindowsInstaller::new(&settings.windows, windows));
+        let timeout = settings.policy_for("flatpak").timeout();
+        self.register_builtin(FlatpakInstaller::new().timeout(timeout).dry_run(dry_run));
+        let timeout = settings.policy_for("mas").timeout();
+        self.register_builtin(MasInstaller::new().timeout(timeout).dry_run(dry_run));
+        let timeout = settings.policy_for("nix").timeout();
+        self.register_builtin(NixInstaller::new().timeout(timeout).dry_run(dry_run));
+    }
+    
+    /// Registers a built-in backend unless a custom one took its name.
+    fn register_builtin(&mut self, installer: impl Installer + 'static) {
+        let name = installer.name().to_string();
+        if self.installers.contains_key(&name) && !self.builtin.contains(&name) {
+            return;
+        }
+        self.installers.insert(name.clone(), Box::new(installer));
+        self.builtin.insert(name);
+    }
+    
+    /// Registers `zshrcman-installer-<name>` executables found in `dirs` or on
+    /// PATH. Plugins never shadow a backend that is already registered.
+    pub fn register_plugins(&mut self, dirs: &[PathBuf], dry_run: bool) {
+        for installer in plugin::discover(dirs) {
+            if self.get(installer.name()).is_none() {
+                self.register(installer.dry_run(dry_run));
+            }
+        }
+    }
+    
+    /// Adds a backend, replacing any existing one with the same name.
+    /// Re-registering the built-ins leaves it in place.
+    pub fn register(&mut self, installer: impl Installer + 'static) {
+        self.builtin.remove(installer.name());
+        self.installers.insert(installer.name().to_string(), Box::new(installer));
+    }
+    
+    pub fn get(&self, name: &str) -> Option<&dyn Installer> {
+        self.installers.get(name).map(|installer| installer.as_ref())
+    }
+    
+    pub fn names(&self) -> Vec<&str> {
+        self.installers.keys().map(String::as_str).collect()
+    }
+    
+    /// The backend for a package installed without naming one: the first
+    /// registered package manager found on this machine, brew first.
+    pub fn preferred(&self, facts: &Facts) -> Option<&dyn Installer> {
+        facts.package_managers.iter().find_map(|name| self.get(name))
+    }
+}
@@ -0,0 +1,110 @@
+use crate::models::LazyLoad;
+
+/// Commands and init code for tools that `[[lazy]]` knows by name.
+const PRESETS: [(&str, &[&str], &str); 3] = [
+    (
+        "nvm",
+        &["nvm", "node", "npm", "npx"],
+        "export NVM_DIR=\"${NVM_DIR:-$HOME/.nvm}\"\n[[ -s \"$NVM_DIR/nvm.sh\" ]] && source \"$NVM_DIR/nvm.sh\"",
+    ),
+    ("pyenv", &["pyenv", "python", "python3", "pip"], "eval \"$(command pyenv init -)\""),
+    ("kubectl", &["kubectl"], "source <(command kubectl completion zsh)"),
+];
+
+/// A lazy-loaded tool with its preset filled in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stub {
+    pub name: String,
+    pub commands: Vec<String>,
+    pub init: String,
+}
+
+/// The `[[lazy]]` entries of several groups, with the problems found along
+/// the way.
+#[derive(Debug, Default)]
+pub struct LazyLoads {
+    stubs: Vec<Stub>,
+    pub problems: Vec<String>,
+}
+
+fn is_valid_command(command: &str) -> bool {
+    !command.is_empty() && command.chars().all(|c| c.is_ascii_alphanumeric() || "_-.+".contains(c))
+}
+
+/// `entry` with the preset of the same name filling whatever it leaves out.
+pub fn resolve(entry: &LazyLoad) -> Option<Stub> {
+    let preset = PRESETS.iter().find(|(name, _, _)| *name == entry.name);
+    let commands = if entry.commands.is_empty() {
+        preset?.1.iter().map(|command| command.to_string()).collect()
+    } else {
+        entry.commands.clone()
+    };
+    let init = match &entry.init {
+        Some(init) => init.clone(),
+        None => preset?.2.to_string(),
+    };
+    Some(Stub {
+        name: entry.name.clone(),
+        commands,
+        init,
+    })
+}
+
+impl LazyLoads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Adds the entries of `origin`, e.g. `group 'node'`. A command already
+    /// stubbed by an earlier entry keeps its first stub.
+    pub fn add(&mut self, origin: &str, entries: &[LazyLoad]) {
+        for entry in entries {
+            let Some(mut stub) = resolve(entry) else {
+                self.problems.push(format!(
+                    "'{}' in {} needs `commands` and `init`; only nvm, pyenv and kubectl have defaults",
+                    entry.name, origin
+                ));
+                continue;
+            };
+            if let Some(command) = stub.commands.iter().find(|command| !is_valid_command(command)) {
+                self.problems.push(format!("'{}' in {} has an invalid command name '{}'; skipping it", entry.name, origin, command));
+                continue;
+            }
+            
+            stub.commands.retain(|command| {
+                let taken = self.stubs.iter().any(|other| other.commands.contains(command));
+                if taken {
+                    self.problems.push(format!("'{}' in {} is already lazy-loaded by an earlier entry; keeping the first", command, origin));
+                }
+                !taken
+            });
+            if !stub.commands.is_empty() {
+                self.stubs.push(stub);
+            }
+        }
+    }
+    
+    pub fn stubs(&self) -> &[Stub] {
+        &self.stubs
+    }
+    
+    /// The `.zshrc` block: one stub function per tool, covering all its
+    /// commands, that removes itself, runs the init code and calls the
+    /// command again. Empty when there's nothing to load.
+    pub fn render(&self) -> String {
+        if self.stubs.is_empty() {
+            return String::new();
+        }
+        
+        let mut script = String::from("# zshrcman lazy loading\n");
+        for stub in &self.stubs {
+            let commands = stub.commands.join(" ");
+            script.push_str(&format!("\n# {}\n{}() {{\n    unfunction {} 2>/dev/null\n", stub.name, commands, commands));
+            for line in stub.init.lines() {
+                script.push_str(&format!("    {}\n", line));
+            }
+            script.push_str("    \"$0\" \"$@\"\n}\n");
+        }
+        script
+    }
+}
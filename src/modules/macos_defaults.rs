@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+use crate::models::{DefaultsValue, MacosDefault};
+
+/// The `defaults write` arguments setting `domain`'s `key` to `value`.
+pub fn write_args(domain: &str, key: &str, value: &DefaultsValue) -> Vec<String> {
+    let (flag, value) = match value {
+        DefaultsValue::Bool(value) => ("-bool", value.to_string()),
+        DefaultsValue::Int(value) => ("-int", value.to_string()),
+        DefaultsValue::Float(value) => ("-float", value.to_string()),
+        DefaultsValue::String(value) => ("-string", value.clone()),
+    };
+    vec!["write".to_string(), domain.to_string(), key.to_string(), flag.to_string(), value]
+}
+
+/// Turns what `defaults read-type` and `defaults read` print into a value.
+/// `None` for types zshrcman can't write back (arrays, dictionaries, data,
+/// dates).
+pub fn parse(read_type: &str, read: &str) -> Option<DefaultsValue> {
+    let read = read.trim_end_matches('\n');
+    match read_type.trim().strip_prefix("Type is ")? {
+        "boolean" => Some(DefaultsValue::Bool(read == "1")),
+        "integer" => read.parse().ok().map(DefaultsValue::Int),
+        "float" => read.parse().ok().map(DefaultsValue::Float),
+        "string" => Some(DefaultsValue::String(read.to_string())),
+        _ => None,
+    }
+}
+
+fn defaults(args: &[&str]) -> Result<Option<String>> {
+    let output = Command::new("defaults").args(args).output().context("Failed to run defaults")?;
+    Ok(output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+/// The current value of `domain`'s `key`, `None` if it isn't set. Values
+/// that couldn't be restored later are an error, so they're left alone.
+pub fn read(domain: &str, key: &str) -> Result<Option<DefaultsValue>> {
+    let Some(read_type) = defaults(&["read-type", domain, key])? else {
+        return Ok(None);
+    };
+    let read = defaults(&["read", domain, key])?.unwrap_or_default();
+    match parse(&read_type, &read) {
+        Some(value) => Ok(Some(value)),
+        None => anyhow::bail!(
+            "{} {} holds a value that isn't a boolean, integer, float or string ({}); not changing it",
+            domain,
+            key,
+            read_type.trim()
+        ),
+    }
+}
+
+pub fn write(entry: &MacosDefault) -> Result<()> {
+    let status = Command::new("defaults")
+        .args(write_args(&entry.domain, &entry.key, &entry.value))
+        .status()
+        .context("Failed to run defaults")?;
+    if !status.success() {
+        anyhow::bail!("defaults write {} {} failed", entry.domain, entry.key);
+    }
+    Ok(())
+}
+
+/// Puts `domain`'s `key` back to `previous`, or deletes it when it wasn't
+/// set before.
+pub fn restore(domain: &str, key: &str, previous: Option<&DefaultsValue>) -> Result<()> {
+    let args = match previous {
+        Some(value) => write_args(domain, key, value),
+        None => vec!["delete".to_string(), domain.to_string(), key.to_string()],
+    };
+    let status = Command::new("defaults").args(&args).status().context("Failed to run defaults")?;
+    if !status.success() && previous.is_some() {
+        anyhow::bail!("Failed to restore {} {}", domain, key);
+    }
+    Ok(())
+}
+
+/// Restarts `process` so it reads its preferences again. macOS relaunches
+/// the Dock, Finder and SystemUIServer by itself.
+pub fn restart(process: &str) {
+    let _ = Command::new("killall").arg(process).status();
+}
@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+use std::time::Duration;
+use crate::modules::installer::Installer;
+use crate::modules::errors::{self, ErrorKind};
+use crate::modules::output;
+
+/// Whether mas output says nobody is signed into the App Store.
+pub fn is_signed_out(output: &str) -> bool {
+    let output = output.to_lowercase();
+    output.contains("not signed in") || output.contains("sign in to the app store")
+}
+
+/// Finds the version of app `id` in `mas list` output, whose lines look like
+/// `497799835  Xcode  (15.4)`.
+pub fn parse_list(output: &str, id: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut tokens = line.split_whitespace();
+        if tokens.next()? != id {
+            return None;
+        }
+        let version = tokens.last()?.strip_prefix('(')?.strip_suffix(')')?;
+        Some(version.to_string())
+    })
+}
+
+/// Installs Mac App Store apps by id. Apps must have been bought with the
+/// signed-in Apple ID; without a sign-in the install fails.
+#[derive(Debug, Clone, Default)]
+pub struct MasInstaller {
+    timeout: Option<Duration>,
+    dry_run: bool,
+}
+
+impl MasInstaller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+    
+    /// `false` only when mas says nobody is signed in. Recent macOS versions
+    /// don't let mas tell, so that counts as signed in.
+    fn signed_in(&self) -> Result<bool> {
+        let output = Command::new("mas").arg("account").output().context("Failed to run mas")?;
+        let said = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        Ok(!is_signed_out(&said))
+    }
+}
+
+fn signed_out(count: usize) -> anyhow::Error {
+    errors::fail(
+        ErrorKind::Installer,
+        format!("Not signed into the App Store; {} app(s) not installed. Sign in and run install again.", count),
+    )
+}
+
+impl Installer for MasInstaller {
+    fn name(&self) -> &str {
+        "mas"
+    }
+    
+    fn install(&self, packages: &[String]) -> Result<()> {
+        if let Some(name) = packages.iter().find(|package| package.parse::<u64>().is_err()) {
+            anyhow::bail!("'{}' is not an App Store id; map it to one in the group's [apps]", name);
+        }
+        if self.dry_run {
+            for id in packages {
+                println!("   [dry-run] mas install {}", id);
+            }
+            return Ok(());
+        }
+        if which::which("mas").is_err() {
+            anyhow::bail!("mas is not installed; add it to the brew group");
+        }
+        if !self.signed_in()? {
+            return Err(signed_out(packages.len()));
+        }
+        
+        for (i, id) in packages.iter().enumerate() {
+            let label = format!("[{}/{}] mas install {}", i + 1, packages.len(), id);
+            let outcome = output::stream_command(Command::new("mas").args(["install", id]), &label, self.timeout)?;
+            if outcome.timed_out {
+                anyhow::bail!("{} timed out after {}s", label, self.timeout.unwrap_or_default().as_secs());
+            }
+            if is_signed_out(&outcome.stderr_tail) {
+                return Err(signed_out(packages.len() - i));
+            }
+            if !outcome.status.success() {
+                anyhow::bail!("{} failed: {}", label, outcome.stderr_tail);
+            }
+        }
+        Ok(())
+    }
+    
+    fn uninstall(&self, packages: &[String]) -> Result<()> {
+        for id in packages {
+            if self.dry_run {
+                println!("   [dry-run] mas uninstall {}", id);
+                continue;
+            }
+            let status = Command::new("mas").args(["uninstall", id]).status().context("Failed to run mas")?;
+            if !status.success() {
+                println!("⚠️  mas could not remove {}; delete it from /Applications", id);
+            }
+        }
+        Ok(())
+    }
+    
+    fn version(&self, package: &str) -> Result<Option<String>> {
+        let output = match Command::new("mas").arg("list").output() {
+            Ok(output) => output,
+            Err(_) => return Ok(None),
+        };
+        Ok(parse_list(&String::from_utf8_lossy(&output.stdout), package))
+    }
+}
@@ -5,4 +5,76 @@ pub mod install;
 pub mod alias;
 pub mod state_manager;
 pub mod profile_switcher;
-pub mod environment;
\ No newline at end of file
+pub mod environment;
+pub mod errors;
+pub mod tour;
+pub mod prompt;
+pub mod bootstrap;
+pub mod features;
+pub mod installer;
+pub mod profile_service;
+pub mod diagnostics;
+pub mod plugin;
+pub mod scripting;
+pub mod external;
+pub mod stats;
+pub mod template;
+pub mod facts;
+pub mod preflight;
+pub mod output;
+pub mod changeset;
+pub mod fingerprint;
+pub mod history;
+pub mod sources;
+pub mod oplog;
+pub mod validate;
+pub mod editor;
+pub mod disk_usage;
+pub mod repos;
+pub mod hooks;
+pub mod named_dirs;
+pub mod tidy;
+pub mod offline;
+pub mod fleet;
+pub mod search;
+pub mod which;
+pub mod functions;
+pub mod alias_usage;
+pub mod zsh_plugins;
+pub mod theme;
+pub mod zsh_options;
+pub mod completions;
+pub mod bench;
+pub mod lazy_load;
+pub mod zcompile;
+pub mod shell_env;
+pub mod session;
+pub mod auto_switch;
+pub mod profile_hooks;
+pub mod profile_identity;
+pub mod gpg;
+pub mod macos_defaults;
+pub mod vscode;
+pub mod fonts;
+pub mod services;
+pub mod cron;
+pub mod flatpak;
+pub mod windows_packages;
+pub mod mas;
+pub mod nix;
+pub mod test_run;
+pub mod verify;
+pub mod daemon;
+pub mod events;
+pub mod notifications;
+pub mod sync_timer;
+pub mod suggest;
+pub mod guided;
+pub mod gen_docs;
+pub mod prefix;
+pub mod bin_links;
+pub mod windows_env;
+pub mod env_layers;
+pub mod secrets;
+pub mod groups;
+pub mod packages;
\ No newline at end of file
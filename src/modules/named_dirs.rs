@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+/// Named dirs gathered from several groups and the active profile, with the
+/// clashes and unusable names found along the way.
+#[derive(Debug, Default)]
+pub struct NamedDirs {
+    /// Name to (path, where it was defined).
+    dirs: BTreeMap<String, (String, String)>,
+    pub problems: Vec<String>,
+}
+
+impl NamedDirs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Adds `dirs` defined by `origin`, e.g. `group 'work'`. A name that is
+    /// already taken for a different path keeps its first definition.
+    pub fn add(&mut self, origin: &str, dirs: &BTreeMap<String, String>) {
+        for (name, path) in dirs {
+            if !is_valid_name(name) {
+                self.problems.push(format!("'{}' in {} is not a valid name; skipping it", name, origin));
+                continue;
+            }
+            if is_reserved(name) {
+                self.problems.push(format!("'{}' in {} would overwrite a shell variable; skipping it", name, origin));
+                continue;
+            }
+            match self.dirs.get(name) {
+                Some((existing, _)) if existing == path => {}
+                Some((existing, first)) => self.problems.push(format!(
+                    "'{}' is {} in {} but {} in {}; keeping {}",
+                    name, existing, first, path, origin, existing
+                )),
+                None => {
+                    self.dirs.insert(name.clone(), (path.clone(), origin.to_string()));
+                }
+            }
+        }
+    }
+    
+    /// Drops the entries `dirs` redefines, so `origin` (the active profile)
+    /// can render its own in their place.
+    pub fn override_with(&mut self, origin: &str, dirs: &BTreeMap<String, String>) {
+        for (name, path) in dirs {
+            if let Some((existing, first)) = self.dirs.remove(name) {
+                if existing != *path {
+                    self.problems.push(format!(
+                        "'{}' is {} in {} but {} in {}; using {}",
+                        name, existing, first, path, origin, path
+                    ));
+                }
+            }
+        }
+    }
+    
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        self.dirs.iter().map(|(name, (path, _))| (name.clone(), path.clone())).collect()
+    }
+}
+
+/// Names must also work as shell variables, which bash and fish use instead
+/// of zsh's `hash -d`.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Variables the shells rely on, which bash, fish and the others would
+/// overwrite with the named dir's path. zsh ties `path`, `fpath` and the
+/// like to their uppercase arrays, and fish makes `status` and `argv`
+/// read-only.
+const RESERVED: &[&str] = &[
+    "PATH", "HOME", "PWD", "OLDPWD", "SHELL", "USER", "LOGNAME", "TERM", "IFS", "LANG", "TMPDIR",
+    "HOSTNAME", "PS1", "PS2", "PS3", "PS4", "PROMPT", "CDPATH", "FPATH", "MANPATH", "path", "fpath",
+    "cdpath", "manpath", "argv", "status", "pipestatus",
+];
+
+/// Whether `name` is a shell variable a named dir must not replace.
+pub fn is_reserved(name: &str) -> bool {
+    RESERVED.contains(&name)
+}
+
+/// `path` with a leading `~` written as `home`, e.g. `$HOME`, since a quoted
+/// `~` isn't expanded.
+pub fn expand_home(path: &str, home: &str) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", home, rest),
+        _ => path.to_string(),
+    }
+}
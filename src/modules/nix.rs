@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::Duration;
+use crate::modules::installer::Installer;
+use crate::modules::output;
+
+/// What `nix profile` needs when the nix config doesn't enable it already.
+const EXPERIMENTAL_FEATURES: &str = "nix-command flakes";
+
+/// The flake installable for `package`: a plain name comes from nixpkgs,
+/// anything with a `#` is a flake reference already.
+pub fn installable(package: &str) -> String {
+    if package.contains('#') {
+        package.to_string()
+    } else {
+        format!("nixpkgs#{}", package)
+    }
+}
+
+/// The attribute name of `package`, which `nix profile` names its entry
+/// after, e.g. `ripgrep` for `nixpkgs#ripgrep`.
+pub fn attr_name(package: &str) -> &str {
+    let attr = package.rsplit('#').next().unwrap_or(package);
+    attr.rsplit('.').next().unwrap_or(attr)
+}
+
+/// Whether `nix config show` output turns on flakes and the nix command.
+pub fn has_flakes(config: &str) -> bool {
+    config.lines().any(|line| {
+        let Some((key, value)) = line.split_once('=') else {
+            return false;
+        };
+        let features: Vec<&str> = value.split_whitespace().collect();
+        key.trim() == "experimental-features" && features.contains(&"flakes") && features.contains(&"nix-command")
+    })
+}
+
+/// The store path of `package` in `nix profile list --json` output. Newer
+/// nix keys elements by name, older versions list them with an `attrPath`.
+pub fn store_path(profile_json: &str, package: &str) -> Option<PathBuf> {
+    let profile: serde_json::Value = serde_json::from_str(profile_json).ok()?;
+    let name = attr_name(package);
+    let element = match &profile["elements"] {
+        serde_json::Value::Object(elements) => elements.get(name),
+        serde_json::Value::Array(elements) => elements.iter().find(|element| {
+            element["attrPath"].as_str().is_some_and(|path| attr_name(path) == name)
+        }),
+        _ => None,
+    }?;
+    element["storePaths"].get(0)?.as_str().map(PathBuf::from)
+}
+
+/// The version in a store path name, e.g. `14.1.0` in
+/// `/nix/store/<hash>-ripgrep-14.1.0`: what follows the first `-` that is
+/// followed by a digit.
+pub fn store_path_version(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let (_hash, name) = name.split_once('-')?;
+    name.match_indices('-')
+        .find(|(i, _)| name[i + 1..].starts_with(|c: char| c.is_ascii_digit()))
+        .map(|(i, _)| name[i + 1..].to_string())
+}
+
+/// Installs packages into the user's nix profile with `nix profile install`,
+/// from nixpkgs unless the package is a flake reference.
+#[derive(Debug, Clone, Default)]
+pub struct NixInstaller {
+    timeout: Option<Duration>,
+    dry_run: bool,
+    /// Whether the nix config enables flakes, asked once per installer.
+    flakes: OnceLock<bool>,
+}
+
+impl NixInstaller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+    
+    /// `nix`, with flakes switched on for this call when the nix config
+    /// doesn't do it.
+    fn nix(&self) -> Command {
+        let mut command = Command::new("nix");
+        let enabled = *self.flakes.get_or_init(|| {
+            Command::new("nix")
+                .args(["--extra-experimental-features", "nix-command", "config", "show"])
+                .output()
+                .is_ok_and(|output| has_flakes(&String::from_utf8_lossy(&output.stdout)))
+        });
+        if !enabled {
+            command.args(["--extra-experimental-features", EXPERIMENTAL_FEATURES]);
+        }
+        command
+    }
+    
+    fn run(&self, args: &[&str], label: &str, check_status: bool) -> Result<()> {
+        if self.dry_run {
+            println!("   [dry-run] nix {}", args.join(" "));
+            return Ok(());
+        }
+        let outcome = output::stream_command(self.nix().args(args), label, self.timeout).context("Failed to run nix")?;
+        if outcome.timed_out {
+            anyhow::bail!("{} timed out after {}s", label, self.timeout.unwrap_or_default().as_secs());
+        }
+        if check_status && !outcome.status.success() {
+            anyhow::bail!("{} failed: {}", label, outcome.stderr_tail);
+        }
+        Ok(())
+    }
+    
+    fn profile_json(&self) -> Option<String> {
+        let output = self.nix().args(["profile", "list", "--json"]).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl Installer for NixInstaller {
+    fn name(&self) -> &str {
+        "nix"
+    }
+    
+    fn install(&self, packages: &[String]) -> Result<()> {
+        for (i, package) in packages.iter().enumerate() {
+            let label = format!("[{}/{}] nix profile install {}", i + 1, packages.len(), package);
+            self.run(&["profile", "install", &installable(package)], &label, true)?;
+        }
+        Ok(())
+    }
+    
+    fn uninstall(&self, packages: &[String]) -> Result<()> {
+        for (i, package) in packages.iter().enumerate() {
+            let label = format!("[{}/{}] nix profile remove {}", i + 1, packages.len(), package);
+            self.run(&["profile", "remove", attr_name(package)], &label, false)?;
+        }
+        Ok(())
+    }
+    
+    fn version(&self, package: &str) -> Result<Option<String>> {
+        let Some(path) = self.profile_json().and_then(|json| store_path(&json, package)) else {
+            return Ok(None);
+        };
+        Ok(Some(store_path_version(&path).unwrap_or_else(|| "unknown".to_string())))
+    }
+    
+    /// The package's executable of the same name in its store path. `None`
+    /// when it has none, as a bin link to the store directory would be no
+    /// use; `binaries` still finds whatever else is in its `bin`.
+    fn location(&self, package: &str) -> Result<Option<PathBuf>> {
+        let Some(path) = self.profile_json().and_then(|json| store_path(&json, package)) else {
+            return Ok(None);
+        };
+        let executable = path.join("bin").join(attr_name(package));
+        Ok(executable.exists().then_some(executable))
+    }
+    
+    /// Everything in the store path's `bin`.
+    fn binaries(&self, package: &str) -> Result<Vec<PathBuf>> {
+        let Some(path) = self.profile_json().and_then(|json| store_path(&json, package)) else {
+            return Ok(Vec::new());
+        };
+        let Ok(entries) = std::fs::read_dir(path.join("bin")) else {
+            return Ok(Vec::new());
+        };
+        let mut binaries: Vec<PathBuf> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+        binaries.sort();
+        Ok(binaries)
+    }
+}
@@ -0,0 +1,30 @@
+use crate::modules::output;
+
+/// The notification to show after a sync, if it did anything worth one:
+/// pulled changes from `main_branch` or failed.
+pub fn sync_outcome(main_branch: &str, pulled: bool, result: &anyhow::Result<()>) -> Option<(String, String)> {
+    match result {
+        Err(e) => Some((
+            "zshrcman sync needs attention".to_string(),
+            format!("{:#}. Run `zshrcman sync` in a terminal to resolve it.", e),
+        )),
+        Ok(()) if pulled => Some((
+            "Dotfiles updated".to_string(),
+            format!("Pulled new changes from origin/{}. Run `zshrcman install` to apply them.", main_branch),
+        )),
+        Ok(()) => None,
+    }
+}
+
+/// Shows a desktop notification. Machines without a notification service
+/// (a headless box, an SSH session) only get a warning.
+pub fn send(summary: &str, body: &str) {
+    let shown = notify_rust::Notification::new()
+        .appname("zshrcman")
+        .summary(summary)
+        .body(body)
+        .show();
+    if let Err(e) = shown {
+        output::warn(format!("⚠️  Couldn't show a desktop notification ({}): {}", e, summary));
+    }
+}
@@ -0,0 +1,25 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::modules::errors::{self, ErrorKind};
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Offline mode (`--offline`, `ZSHRCMAN_OFFLINE`) skips everything that talks
+/// to a remote: fetches, pushes and webhook hooks. Local work like commits,
+/// rebasing onto the last fetched main branch and installs carries on.
+pub fn set_offline(enabled: bool) {
+    OFFLINE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fails when offline, for things that can't be done without the network
+/// at all, like a first clone. `what` completes "Can't ... while offline".
+pub fn require_online(what: &str) -> Result<()> {
+    if is_offline() {
+        return Err(errors::fail(ErrorKind::Git, format!("Can't {} while offline; run without --offline", what)));
+    }
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::modules::config::{self, ConfigManager};
+use crate::modules::diagnostics::{self, REDACTED};
+
+/// Log of every command that may change this machine, under `<data_dir>/logs`
+/// so diagnostics bundles pick it up too.
+pub const OPERATIONS_LOG: &str = "operations.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Subcommand path, e.g. `install` or `profile switch`.
+    pub command: String,
+    /// The command line without the binary, through [`redact_args`].
+    pub args: String,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The `history` entry of the config change this command made, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_id: Option<u64>,
+}
+
+pub struct OperationLog {
+    path: PathBuf,
+}
+
+impl OperationLog {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            path: data_dir.join("logs").join(OPERATIONS_LOG),
+        }
+    }
+    
+    pub fn open() -> Result<Self> {
+        let dotfiles_path = ConfigManager::get_dotfiles_path()?;
+        Ok(Self::new(dotfiles_path.parent().unwrap_or(&dotfiles_path)))
+    }
+    
+    pub fn append(&self, operation: &Operation) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(operation)?)?;
+        Ok(())
+    }
+    
+    /// Operations started at or after `since`, oldest first. Lines that don't
+    /// parse, e.g. from an interrupted write, are skipped.
+    pub fn since(&self, since: Option<DateTime<Utc>>) -> Result<Vec<Operation>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        
+        let contents = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        let mut operations: Vec<Operation> = contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .filter(|operation: &Operation| since.is_none_or(|since| operation.started_at >= since))
+            .collect();
+        operations.sort_by_key(|operation| operation.started_at);
+        Ok(operations)
+    }
+    
+    /// Drops operations started before `before`. Returns how many were
+    /// dropped.
+    pub fn prune(&self, before: DateTime<Utc>) -> Result<usize> {
+        let total = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents.lines().count(),
+            Err(_) => return Ok(0),
+        };
+        let kept = self.since(Some(before))?;
+        
+        let mut contents = String::new();
+        for operation in &kept {
+            contents.push_str(&serde_json::to_string(operation)?);
+            contents.push('\n');
+        }
+        config::write_atomic(&self.path, contents.as_bytes())?;
+        Ok(total - kept.len())
+    }
+}
+
+/// `args` joined for the log, with secrets taken out: values of
+/// `NAME=value` and `--name value` / `--name=value` arguments whose name
+/// looks secret (see `diagnostics::is_secret_key`), and credentials in URLs.
+pub fn redact_args<S: AsRef<str>>(args: &[S]) -> String {
+    let is_secret = |name: &str| diagnostics::is_secret_key(&name.replace('-', "_"));
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut hide_next = false;
+    for arg in args.iter().map(AsRef::as_ref) {
+        if std::mem::take(&mut hide_next) {
+            redacted.push(REDACTED.to_string());
+            continue;
+        }
+        match arg.split_once('=') {
+            Some((name, _)) if is_secret(name) => redacted.push(format!("{}={}", name, REDACTED)),
+            None if arg.starts_with("--") && is_secret(arg) => {
+                hide_next = true;
+                redacted.push(arg.to_string());
+            }
+            _ => redacted.push(diagnostics::redact_text(arg)),
+        }
+    }
+    redacted.join(" ")
+}
+
+/// Parses `--since`: a relative age like `90m`, `12h`, `2d` or `1w`, or a
+/// local date like `2024-05-01`.
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        return chrono::Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|local| local.with_timezone(&Utc))
+            .with_context(|| format!("{} has no local midnight", value));
+    }
+    
+    let split = value.char_indices().last().map_or(0, |(i, _)| i);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .with_context(|| format!("Invalid age '{}'; use e.g. 12h, 2d, 1w or 2024-05-01", value))?;
+    if amount < 0 {
+        anyhow::bail!("Invalid age '{}'; it can't be negative", value);
+    }
+    let age = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => anyhow::bail!("Invalid age '{}'; use e.g. 12h, 2d, 1w or 2024-05-01", value),
+    };
+    Ok(now - age)
+}
+
+/// Short human duration: `850ms`, `12s`, `2m 13s`, `1h 5m`.
+pub fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    match secs {
+        0 => format!("{}ms", ms),
+        1..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
@@ -0,0 +1,208 @@
+use anyhow::{Context, Result};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How much zshrcman prints, set once from `--quiet` / `--verbose`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only errors and final results.
+    Quiet,
+    /// Progress messages and spinners.
+    Normal,
+    /// Everything, including the full output of package managers.
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Lines of stderr kept for the error message when a command fails.
+const STDERR_TAIL_LINES: usize = 20;
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+pub fn is_quiet() -> bool {
+    verbosity() == Verbosity::Quiet
+}
+
+/// Prints a progress message unless `--quiet` was given.
+pub fn info(message: impl Display) {
+    if !is_quiet() {
+        println!("{}", message);
+    }
+}
+
+/// Prints a warning to stderr unless `--quiet` was given.
+pub fn warn(message: impl Display) {
+    if !is_quiet() {
+        eprintln!("{}", message);
+    }
+}
+
+/// A spinner on stderr. Hidden in quiet mode and when stderr isn't a terminal.
+pub fn spinner(message: impl Into<String>) -> ProgressBar {
+    if is_quiet() {
+        return ProgressBar::hidden();
+    }
+    
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(
+        ProgressStyle::with_template("   {spinner:.cyan} {prefix} {wide_msg:.dim}")
+            .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+    );
+    spinner.set_prefix(message.into());
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+/// Exit status and the last lines of stderr of a streamed command.
+#[derive(Debug)]
+pub struct CommandOutcome {
+    pub status: ExitStatus,
+    pub stderr_tail: String,
+    /// The command was killed for running longer than its timeout.
+    pub timed_out: bool,
+}
+
+/// Runs `command` behind a spinner labelled `label`, streaming its output as
+/// it arrives: the latest line is shown next to the spinner, and with
+/// `--verbose` every line is printed. The command is killed once `timeout`
+/// has passed; anything it started that still holds its output open is left
+/// behind rather than waited for.
+pub fn stream_command(command: &mut Command, label: &str, timeout: Option<Duration>) -> Result<CommandOutcome> {
+    stream(command, label, timeout, |_| {})
+}
+
+/// Runs `command` like [`stream_command`], also appending each line of its
+/// output to `log`.
+pub fn stream_command_to(command: &mut Command, label: &str, timeout: Option<Duration>, log: &mut impl Write) -> Result<CommandOutcome> {
+    let mut written = Ok(());
+    let outcome = stream(command, label, timeout, |line| {
+        if written.is_ok() {
+            written = writeln!(log, "{}", line);
+        }
+    })?;
+    written?;
+    Ok(outcome)
+}
+
+fn stream(command: &mut Command, label: &str, timeout: Option<Duration>, mut also: impl FnMut(&str)) -> Result<CommandOutcome> {
+    let spinner = spinner(label);
+    let verbose = verbosity() == Verbosity::Verbose;
+    let outcome = run_lines(command, label, timeout, |_, line| {
+        also(line);
+        if verbose {
+            spinner.println(format!("     {}", line));
+        } else if !line.trim().is_empty() {
+            spinner.set_message(line.trim().to_string());
+        }
+    });
+    spinner.finish_and_clear();
+    outcome
+}
+
+/// Runs `command` like [`stream_command`] but shows nothing, returning its
+/// stdout along with the outcome. For output that mustn't reach the
+/// terminal, such as secrets.
+pub fn capture_command(command: &mut Command, label: &str, timeout: Option<Duration>) -> Result<(CommandOutcome, String)> {
+    let mut stdout = Vec::new();
+    let outcome = run_lines(command, label, timeout, |is_stderr, line| {
+        if !is_stderr {
+            stdout.push(line.to_string());
+        }
+    })?;
+    Ok((outcome, stdout.join("\n")))
+}
+
+/// Runs `command` without stdin, passing each line of its stdout and stderr
+/// to `on_line` as it arrives.
+fn run_lines(
+    command: &mut Command,
+    label: &str,
+    timeout: Option<Duration>,
+    mut on_line: impl FnMut(bool, &str),
+) -> Result<CommandOutcome> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", label))?;
+    
+    let (tx, rx) = mpsc::channel();
+    let readers: Vec<_> = [
+        child.stdout.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child.stderr.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .enumerate()
+    .filter_map(|(i, stream)| Some((i == 1, stream?)))
+    .map(|(is_stderr, stream)| {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                if tx.send((is_stderr, line)).is_err() {
+                    break;
+                }
+            }
+        })
+    })
+    .collect();
+    drop(tx);
+    
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut timed_out = false;
+    let mut stderr_tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+    loop {
+        if !timed_out && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            timed_out = true;
+            let _ = child.kill();
+        }
+        
+        let (is_stderr, line) = match rx.recv_timeout(Duration::from_millis(200)) {
+            Ok(received) => received,
+            // A killed command's children can keep the pipes open forever.
+            Err(mpsc::RecvTimeoutError::Timeout) if timed_out && child.try_wait()?.is_some() => break,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        
+        on_line(is_stderr, &line);
+        
+        if is_stderr {
+            if stderr_tail.len() == STDERR_TAIL_LINES {
+                stderr_tail.pop_front();
+            }
+            stderr_tail.push_back(line);
+        }
+    }
+    
+    if !timed_out {
+        for reader in readers {
+            let _ = reader.join();
+        }
+    }
+    let status = child.wait()?;
+    
+    Ok(CommandOutcome {
+        status,
+        stderr_tail: Vec::from(stderr_tail).join("\n"),
+        timed_out,
+    })
+}
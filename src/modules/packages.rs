@@ -0,0 +1,144 @@
+use anyhow::Result;
+use colored::Colorize;
+use crate::models::{InstallScope, InstallationRecord, RemovalStrategy};
+use crate::modules::config::ConfigManager;
+use crate::modules::facts::Facts;
+use crate::modules::install;
+use crate::modules::prefix;
+use crate::modules::profile_switcher::ProfileSwitcher;
+use crate::modules::state_manager::InstallationStateManager;
+use crate::modules::suggest;
+
+/// Installs `name` and the packages it `requires` for `scope` with
+/// `installer`, defaulting to the first package manager found when any of
+/// them isn't installed yet.
+pub fn install(config_mgr: ConfigManager, name: &str, requires: &[String], scope: InstallScope, installer: Option<String>) -> Result<()> {
+    let registry = install::installer_registry(&config_mgr);
+    // Packages installed already are only activated; no backend needed.
+    let missing = scope == InstallScope::Local
+        || std::iter::once(name)
+            .chain(requires.iter().map(String::as_str))
+            .any(|package| config_mgr.config.installations.get(package).is_none_or(|record| record.is_pin_only()));
+    let installer = match installer {
+        None if scope == InstallScope::Local => match prefix::preferred(&Facts::gather()) {
+            Some(backend) => Some(backend.to_string()),
+            None => anyhow::bail!("None of {} found for a local install; pass --installer <name>", prefix::BACKENDS.join(", ")),
+        },
+        None if missing => match registry.preferred(&Facts::gather()) {
+            Some(preferred) => Some(preferred.name().to_string()),
+            None => anyhow::bail!("No package manager found; pass --installer <name>"),
+        },
+        installer => installer,
+    };
+    let mut state_mgr = InstallationStateManager::new(config_mgr).with_registry(registry);
+    state_mgr.install_with_dependencies(name, requires, scope, installer.as_deref())?;
+    println!("{} {}", "✅ Installed package:".green(), name);
+    link_profile_binaries(state_mgr)?;
+    Ok(())
+}
+
+/// Removes `name`, installed with `pkg install`, according to `strategy`.
+pub fn remove(config_mgr: ConfigManager, name: &str, strategy: RemovalStrategy, force_pinned: bool) -> Result<()> {
+    let registry = install::installer_registry(&config_mgr);
+    let mut state_mgr = InstallationStateManager::new(config_mgr).with_registry(registry).force_pinned(force_pinned);
+    if state_mgr.get_package_info(name).is_none() {
+        anyhow::bail!("'{}' wasn't installed with `zshrcman pkg install`", name);
+    }
+    state_mgr.handle_removal(name, strategy)?;
+    println!("{} {}", "✅ Removed package:".green(), name);
+    link_profile_binaries(state_mgr)?;
+    Ok(())
+}
+
+/// Prints the packages installed with `pkg install` as a table, filtered like
+/// [`InstallationStateManager::list_packages`].
+pub fn list(config_mgr: ConfigManager, profile: Option<&str>, scope: Option<&InstallScope>, inactive: bool) -> Result<()> {
+    let state_mgr = InstallationStateManager::new(config_mgr);
+    let records = state_mgr.list_packages(profile, scope, inactive)?;
+    if records.is_empty() {
+        println!("No packages installed with `zshrcman pkg install` match");
+        return Ok(());
+    }
+    
+    let rows: Vec<[String; 5]> = records
+        .iter()
+        .map(|record| {
+            let mut active: Vec<&str> = record.active_for.iter().map(String::as_str).collect();
+            active.sort();
+            let active = if active.is_empty() { "-".to_string() } else { active.join(", ") };
+            let name = if record.pinned { format!("{} (pinned)", record.package) } else { record.package.clone() };
+            [name, record.version.clone().unwrap_or_else(|| "-".to_string()), record.scope.to_string(), record.installer_type.clone(), active]
+        })
+        .collect();
+    let header = ["PACKAGE", "VERSION", "SCOPE", "INSTALLER", "ACTIVE FOR"];
+    let widths: Vec<usize> = (0..4)
+        .map(|column| rows.iter().map(|row| row[column].chars().count()).chain([header[column].len()]).max().unwrap_or(0))
+        .collect();
+    let line = |row: [&str; 5]| {
+        format!("{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {}", row[0], row[1], row[2], row[3], row[4], w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3])
+    };
+    println!("{}", line(header).bold());
+    for row in &rows {
+        println!("{}", line([&row[0], &row[1], &row[2], &row[3], &row[4]]));
+    }
+    Ok(())
+}
+
+/// Prints the record of `name`, installed with `pkg install`.
+pub fn info(config_mgr: ConfigManager, name: &str) -> Result<()> {
+    let state_mgr = InstallationStateManager::new(config_mgr);
+    let record = state_mgr
+        .get_package_info(name)
+        .ok_or_else(|| suggest::not_found("Package", name, state_mgr.installations.keys()))?;
+    show(record);
+    Ok(())
+}
+
+/// Prints the held packages, then the pinned ones.
+pub fn held(config_mgr: &ConfigManager) {
+    let mut pinned: Vec<&String> =
+        config_mgr.config.installations.values().filter(|record| record.pinned).map(|record| &record.package).collect();
+    pinned.sort();
+    if config_mgr.config.held_packages.is_empty() && pinned.is_empty() {
+        println!("No held or pinned packages");
+    }
+    for name in &config_mgr.config.held_packages {
+        println!("📌 {}", name);
+    }
+    for name in pinned {
+        println!("📌 {} (pinned)", name);
+    }
+}
+
+/// Points the active profile's bin links at its packages as they are now.
+fn link_profile_binaries(state_mgr: InstallationStateManager) -> Result<()> {
+    let Some(profile) = state_mgr.active_profile.clone() else {
+        return Ok(());
+    };
+    ProfileSwitcher::new(state_mgr).update_active_binaries(&profile)
+}
+
+fn show(record: &InstallationRecord) {
+    println!("{}", format!("📦 Package '{}'", record.package).bold());
+    println!("  Version: {}", record.version.as_deref().unwrap_or("unknown"));
+    println!("  Installer: {}", record.installer_type);
+    println!("  Installed: {} by {}", record.installed_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"), record.installed_by);
+    println!("  Scope: {}", record.scope);
+    match &record.location {
+        Some(location) => println!("  Location: {}", location.display()),
+        None => println!("  Location: {}", "unknown".dimmed()),
+    }
+    let mut active: Vec<&String> = record.active_for.iter().collect();
+    active.sort();
+    if active.is_empty() {
+        println!("  Active for: {}", "no profile".yellow());
+    } else {
+        println!("  Active for: {}", active.iter().map(|profile| profile.as_str()).collect::<Vec<_>>().join(", "));
+    }
+    if !record.required_by.is_empty() {
+        println!("  Required by: {}", record.required_by.iter().map(String::as_str).collect::<Vec<_>>().join(", "));
+    }
+    if record.pinned {
+        println!("  📌 Pinned; unpin it with `zshrcman unpin {}`", record.package);
+    }
+}
@@ -0,0 +1,216 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::modules::installer::Installer;
+
+/// Executables named `zshrcman-installer-<name>` act as the installer `<name>`.
+pub const PLUGIN_PREFIX: &str = "zshrcman-installer-";
+
+/// Bumped when the request or response format changes incompatibly.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A request written as one JSON object to the plugin's stdin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginRequest {
+    pub protocol: u32,
+    pub action: PluginAction,
+    pub packages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginAction {
+    Plan,
+    Install,
+    Uninstall,
+    Check,
+}
+
+/// The JSON object a plugin prints on stdout. `versions` answers `check`:
+/// installed packages map to their version, missing ones are left out.
+/// `plan` answers `plan` with the steps an install would take, without
+/// changing anything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginResponse {
+    pub ok: bool,
+    pub message: Option<String>,
+    pub versions: HashMap<String, String>,
+    pub plan: Vec<String>,
+}
+
+/// An installer backend provided by an external executable.
+#[derive(Debug, Clone)]
+pub struct PluginInstaller {
+    name: String,
+    path: PathBuf,
+    dry_run: bool,
+}
+
+impl PluginInstaller {
+    pub fn new(name: &str, path: PathBuf) -> Self {
+        Self {
+            name: name.to_string(),
+            path,
+            dry_run: false,
+        }
+    }
+    
+    /// Asks the plugin for a `plan` instead of installing or uninstalling.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    /// The steps the plugin would take to install `packages`.
+    pub fn plan(&self, packages: &[String]) -> Result<Vec<String>> {
+        Ok(self.call(PluginAction::Plan, packages)?.plan)
+    }
+    
+    /// Runs `action`, or in dry-run mode prints the plugin's plan for it.
+    fn apply(&self, action: PluginAction, packages: &[String]) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+        
+        if !self.dry_run {
+            return self.call(action, packages).map(|_| ());
+        }
+        
+        if action == PluginAction::Install {
+            for step in self.plan(packages)? {
+                println!("   [dry-run] {}", step);
+            }
+        } else {
+            println!("   [dry-run] {} uninstall {}", self.name, packages.join(" "));
+        }
+        Ok(())
+    }
+    
+    fn call(&self, action: PluginAction, packages: &[String]) -> Result<PluginResponse> {
+        let request = PluginRequest {
+            protocol: PROTOCOL_VERSION,
+            action,
+            packages: packages.to_vec(),
+        };
+        
+        let mut child = Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to start plugin {}", self.path.display()))?;
+        
+        if let Some(mut stdin) = child.stdin.take() {
+            serde_json::to_writer(&mut stdin, &request)?;
+            stdin.write_all(b"\n")?;
+        }
+        
+        let output = child.wait_with_output()?;
+        let response: PluginResponse = serde_json::from_slice(&output.stdout).with_context(|| {
+            format!("Plugin '{}' returned an invalid response", self.name)
+        })?;
+        
+        if !response.ok || !output.status.success() {
+            anyhow::bail!(
+                "Plugin '{}' failed: {}",
+                self.name,
+                response.message.as_deref().unwrap_or("no message")
+            );
+        }
+        
+        Ok(response)
+    }
+}
+
+impl Installer for PluginInstaller {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    
+    fn install(&self, packages: &[String]) -> Result<()> {
+        self.apply(PluginAction::Install, packages)
+    }
+    
+    fn uninstall(&self, packages: &[String]) -> Result<()> {
+        self.apply(PluginAction::Uninstall, packages)
+    }
+    
+    fn version(&self, package: &str) -> Result<Option<String>> {
+        let mut response = self.call(PluginAction::Check, &[package.to_string()])?;
+        Ok(response.versions.remove(package))
+    }
+}
+
+/// Finds plugins in `dirs` followed by every directory on PATH. When two
+/// directories provide the same name, the first one wins.
+pub fn discover(dirs: &[PathBuf]) -> Vec<PluginInstaller> {
+    let path_dirs = env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default();
+    
+    let mut plugins: Vec<PluginInstaller> = Vec::new();
+    for dir in dirs.iter().chain(path_dirs.iter()) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        
+        let mut found: Vec<(String, PathBuf)> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| is_executable(path))
+            .filter_map(|path| {
+                let name = plugin_name(path.file_name()?.to_str()?)?;
+                Some((name.to_string(), path))
+            })
+            .collect();
+        found.sort();
+        
+        for (name, path) in found {
+            if !plugins.iter().any(|p| p.name == name) {
+                plugins.push(PluginInstaller::new(&name, path));
+            }
+        }
+    }
+    
+    plugins
+}
+
+/// `zshrcman-installer-conda` -> `conda`. Extensions are ignored so
+/// `zshrcman-installer-conda.exe` works on Windows.
+pub fn plugin_name(file_name: &str) -> Option<&str> {
+    let name = file_name.strip_prefix(PLUGIN_PREFIX)?;
+    let name = match name.rsplit_once('.') {
+        Some((stem, _)) if cfg!(windows) => stem,
+        _ => name,
+    };
+    if name.is_empty() { None } else { Some(name) }
+}
+
+pub(crate) fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    if !metadata.is_file() {
+        return false;
+    }
+    
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    }
+    
+    #[cfg(not(unix))]
+    {
+        true
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::modules::facts::Facts;
+use crate::modules::installer::{self, CommandInstaller, Installer};
+
+/// Backends that can install into a profile's own prefix.
+pub const BACKENDS: [&str; 3] = ["cargo", "npm", "pip"];
+
+/// The first of [`BACKENDS`] found on this machine, what a local install
+/// without `--installer` uses.
+pub fn preferred(facts: &Facts) -> Option<&'static str> {
+    BACKENDS.into_iter().find(|backend| facts.has_package_manager(backend))
+}
+
+/// Where `backend` installs for `profile`, e.g.
+/// `<data dir>/profiles/work/prefix/npm`. Kept apart from the profile's
+/// `bin` links, which are rewritten on every switch.
+pub fn dir(data_dir: &Path, profile: &str, backend: &str) -> PathBuf {
+    data_dir.join("profiles").join(profile).join("prefix").join(backend)
+}
+
+/// The version of `package` in `cargo install --list` output, whose rows
+/// look like `ripgrep v14.1.0:`.
+pub fn parse_cargo_list(output: &str, package: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (name, version) = line.split_once(' ')?;
+        (name == package).then(|| version.trim_end_matches(':').trim_start_matches('v').to_string())
+    })
+}
+
+/// The binaries listed under `package` in `cargo install --list` output,
+/// indented below its `ripgrep v14.1.0:` row.
+pub fn parse_cargo_binaries(output: &str, package: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip_while(|line| line.split_once(' ').is_none_or(|(name, _)| name != package))
+        .skip(1)
+        .take_while(|line| line.starts_with(char::is_whitespace))
+        .map(|line| line.trim().to_string())
+        .collect()
+}
+
+/// The executables `pip show -f` lists among a package's files, which sit
+/// in the virtualenv's `bin`, e.g. `../../../bin/http`.
+pub fn parse_pip_binaries(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().rsplit_once("bin/"))
+        .filter(|(dirs, _)| dirs.split('/').all(|dir| dir.is_empty() || dir == ".."))
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// The commands a `package.json` declares in `bin`: its keys, or the
+/// package's own name when `bin` is a single path.
+pub fn parse_npm_binaries(package_json: &str) -> Vec<String> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(package_json) else {
+        return Vec::new();
+    };
+    match &json["bin"] {
+        serde_json::Value::Object(bins) => bins.keys().cloned().collect(),
+        serde_json::Value::String(_) => json["name"]
+            .as_str()
+            .map(|name| vec![installer::executable_name(name).to_string()])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// The npm package name without a version, e.g. `@acme/cli` for
+/// `@acme/cli@2`.
+fn npm_name(package: &str) -> &str {
+    match package.get(1..).and_then(|rest| rest.find('@')) {
+        Some(at) => &package[..at + 1],
+        None => package,
+    }
+}
+
+/// The version in `pip show` output's `Version: 1.2.3` line.
+pub fn parse_pip_show(output: &str, _package: &str) -> Option<String> {
+    output.lines().find_map(|line| line.strip_prefix("Version:").map(|version| version.trim().to_string()))
+}
+
+/// Installs npm, pip or cargo packages into one profile's prefix, so two
+/// profiles can each have their own version of a CLI. pip packages go into
+/// a virtualenv created at the prefix on first use.
+pub struct PrefixInstaller {
+    backend: String,
+    root: PathBuf,
+    inner: CommandInstaller,
+}
+
+impl PrefixInstaller {
+    /// None unless `backend` is one of [`BACKENDS`].
+    pub fn new(backend: &str, root: PathBuf) -> Option<Self> {
+        let prefix = root.to_string_lossy().into_owned();
+        let inner = match backend {
+            "npm" => CommandInstaller::new("npm", "npm", &["install", "-g", "--prefix", &prefix], &["uninstall", "-g", "--prefix", &prefix])
+                .query_args(&["ls", "-g", "--depth=0", "--prefix", &prefix]),
+            "cargo" => CommandInstaller::new("cargo", "cargo", &["install", "--root", &prefix], &["uninstall", "--root", &prefix])
+                .query_args(&["install", "--list", "--root", &prefix])
+                .query_lists_all()
+                .parse_with(parse_cargo_list),
+            "pip" => {
+                let pip = root.join("bin").join("pip").to_string_lossy().into_owned();
+                CommandInstaller::new("pip", &pip, &["install"], &["uninstall", "-y"])
+                    .query_args(&["show"])
+                    .parse_with(parse_pip_show)
+            }
+            _ => return None,
+        };
+        Some(Self { backend: backend.to_string(), root, inner })
+    }
+    
+    /// Where the prefix's executables end up.
+    pub fn bin_dir(&self) -> PathBuf {
+        self.root.join("bin")
+    }
+    
+    /// Names of the executables `package` put into the prefix's `bin`.
+    fn binary_names(&self, package: &str) -> Vec<String> {
+        match self.backend.as_str() {
+            "npm" => {
+                let manifest = self.root.join("lib/node_modules").join(npm_name(package)).join("package.json");
+                fs::read_to_string(manifest).map(|json| parse_npm_binaries(&json)).unwrap_or_default()
+            }
+            "cargo" => Command::new("cargo")
+                .args(["install", "--list", "--root"])
+                .arg(&self.root)
+                .output()
+                .map(|output| parse_cargo_binaries(&String::from_utf8_lossy(&output.stdout), package))
+                .unwrap_or_default(),
+            _ => Command::new(self.bin_dir().join("pip"))
+                .args(["show", "-f", package])
+                .output()
+                .map(|output| parse_pip_binaries(&String::from_utf8_lossy(&output.stdout)))
+                .unwrap_or_default(),
+        }
+    }
+    
+    fn create_venv(&self) -> Result<()> {
+        if self.backend != "pip" || self.bin_dir().join("pip").exists() {
+            return Ok(());
+        }
+        let status = Command::new("python3")
+            .args(["-m", "venv"])
+            .arg(&self.root)
+            .status()
+            .context("Failed to run python3 -m venv")?;
+        if !status.success() {
+            anyhow::bail!("python3 -m venv {} failed: {}", self.root.display(), status);
+        }
+        Ok(())
+    }
+}
+
+impl Installer for PrefixInstaller {
+    fn name(&self) -> &str {
+        &self.backend
+    }
+    
+    fn install(&self, packages: &[String]) -> Result<()> {
+        fs::create_dir_all(&self.root).with_context(|| format!("Failed to create {}", self.root.display()))?;
+        self.create_venv()?;
+        self.inner.install(packages)
+    }
+    
+    fn uninstall(&self, packages: &[String]) -> Result<()> {
+        if !self.root.exists() {
+            return Ok(());
+        }
+        self.inner.uninstall(packages)
+    }
+    
+    fn version(&self, package: &str) -> Result<Option<String>> {
+        if !self.root.exists() {
+            return Ok(None);
+        }
+        self.inner.version(package)
+    }
+    
+    /// The package's executable in the prefix's `bin`.
+    fn location(&self, package: &str) -> Result<Option<PathBuf>> {
+        let executable = self.bin_dir().join(installer::executable_name(package));
+        Ok(executable.exists().then_some(executable))
+    }
+    
+    /// What the package's manifest says it installed into the prefix's `bin`.
+    fn binaries(&self, package: &str) -> Result<Vec<PathBuf>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+        let mut binaries: Vec<PathBuf> = self
+            .binary_names(package)
+            .into_iter()
+            .map(|name| self.bin_dir().join(name))
+            .filter(|path| path.exists())
+            .collect();
+        if binaries.is_empty() {
+            binaries.extend(self.location(package)?);
+        }
+        Ok(binaries)
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::env;
+use std::path::PathBuf;
+use crate::modules::config;
+
+/// Checks HOME and SHELL before any command runs and applies `--home`,
+/// `--shell` and `--config`. Overrides are exported to the process
+/// environment so config paths, child processes and shell detection all agree
+/// on them.
+///
+/// When HOME is unset but the user database knows the home directory, that
+/// is used with a warning. Otherwise nothing is guessed: [`home_dir`] and
+/// [`shell`] fail with guidance instead of writing into the working directory.
+pub fn apply(home: Option<PathBuf>, shell: Option<String>, config: Option<PathBuf>) -> Result<()> {
+    if let Some(home) = home {
+        let home = if home.is_absolute() { home } else { env::current_dir()?.join(home) };
+        if !home.is_dir() {
+            anyhow::bail!("--home {} is not an existing directory", home.display());
+        }
+        env::set_var("HOME", &home);
+    } else if var("HOME").is_none() && !cfg!(windows) {
+        if let Some(home) = dirs::home_dir().filter(|h| h.is_absolute() && h.is_dir()) {
+            eprintln!(
+                "{} HOME is not set; using {} from the user database (pass --home to choose)",
+                "⚠️  Warning:".yellow(),
+                home.display()
+            );
+            env::set_var("HOME", &home);
+        }
+    }
+    
+    if let Some(shell) = shell {
+        let shell = match which::which(&shell) {
+            Ok(path) => path.display().to_string(),
+            Err(_) => shell,
+        };
+        env::set_var("SHELL", shell);
+    }
+    
+    if let Some(config) = config {
+        let config = if config.is_absolute() { config } else { env::current_dir()?.join(config) };
+        if config.is_dir() {
+            anyhow::bail!("--config {} is a directory; pass the path of a config.toml", config.display());
+        }
+        env::set_var(config::CONFIG_ENV, config);
+    }
+    
+    Ok(())
+}
+
+/// The user's home directory: HOME, or USERPROFILE on Windows.
+pub fn home_dir() -> Result<PathBuf> {
+    check_home(var("HOME").or_else(|| if cfg!(windows) { var("USERPROFILE") } else { None }))
+}
+
+/// `home` as the home directory, failing with guidance when it is missing
+/// or relative.
+pub(crate) fn check_home(home: Option<String>) -> Result<PathBuf> {
+    let home = home.context("HOME is not set. Set it or pass --home <dir> to say where dotfiles should go")?;
+    
+    let home = PathBuf::from(home);
+    if !home.is_absolute() {
+        anyhow::bail!("HOME is '{}', which is not an absolute path. Pass --home <dir>", home.display());
+    }
+    Ok(home)
+}
+
+/// The login shell from SHELL (set by `--shell`).
+pub fn shell() -> Result<String> {
+    check_shell(var("SHELL"))
+}
+
+/// `shell` as the login shell, failing with guidance when it is missing.
+pub(crate) fn check_shell(shell: Option<String>) -> Result<String> {
+    shell.context(
+        "SHELL is not set, so zshrcman can't tell which startup files to write. \
+         Set it or pass --shell <zsh|bash|fish>",
+    )
+}
+
+/// A non-empty environment variable.
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::env;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use crate::models::EnvironmentState;
+use crate::modules::{named_dirs, output};
+
+/// Log of every profile hook's output, in the data dir's `logs/`.
+pub const HOOKS_LOG: &str = "profile-hooks.log";
+
+/// How long one hook command may run. A switch waits for its hooks, so a
+/// VPN client stuck on a prompt must not hang it.
+pub const TIMEOUT: Duration = Duration::from_secs(60);
+
+pub fn log_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("logs").join(HOOKS_LOG)
+}
+
+/// When a hook runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Activate,
+    Deactivate,
+}
+
+impl Trigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Trigger::Activate => "on_activate",
+            Trigger::Deactivate => "on_deactivate",
+        }
+    }
+}
+
+/// A hook command that didn't succeed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookFailure {
+    pub command: String,
+    pub reason: String,
+}
+
+impl fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` {}", self.command, self.reason)
+    }
+}
+
+/// The variables a hook sees on top of zshrcman's own: the profile's
+/// variables, its PATH entries and `ZSHRCMAN_PROFILE`.
+pub fn hook_env(profile: &str, env_state: &EnvironmentState) -> Vec<(String, String)> {
+    let home = env::var("HOME").unwrap_or_default();
+    let expand = |path: &String| PathBuf::from(named_dirs::expand_home(path, &home));
+    let current = env::var_os("PATH").unwrap_or_default();
+    let paths: Vec<PathBuf> = env_state
+        .paths_prepend
+        .iter()
+        .map(expand)
+        .chain(env::split_paths(&current))
+        .chain(env_state.paths_append.iter().map(expand))
+        .collect();
+    
+    let mut vars: Vec<(String, String)> = env_state.variables.iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+    vars.sort();
+    if let Ok(path) = env::join_paths(paths) {
+        vars.push(("PATH".to_string(), path.to_string_lossy().into_owned()));
+    }
+    vars.push(("ZSHRCMAN_PROFILE".to_string(), profile.to_string()));
+    vars
+}
+
+fn shell_command(command: &str) -> Command {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C");
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.arg("-c");
+        process
+    };
+    process.arg(command);
+    process
+}
+
+/// Runs the profile's `trigger` commands one after another with its
+/// environment, appending their output to `log`; each is killed after
+/// `timeout`. A failing command doesn't stop the others; the failures are
+/// returned for the caller to report.
+pub fn run(
+    commands: &[String],
+    profile: &str,
+    trigger: Trigger,
+    env_state: &EnvironmentState,
+    log: &Path,
+    timeout: Option<Duration>,
+) -> Result<Vec<HookFailure>> {
+    if commands.is_empty() {
+        return Ok(Vec::new());
+    }
+    if let Some(parent) = log.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut log_file = OpenOptions::new().create(true).append(true).open(log)?;
+    let vars = hook_env(profile, env_state);
+    
+    let mut failures = Vec::new();
+    for command in commands {
+        writeln!(log_file, "=== {} {} '{}': {}", Utc::now().to_rfc3339(), trigger.as_str(), profile, command)?;
+        let mut process = shell_command(command);
+        process.envs(vars.iter().map(|(key, value)| (key, value)));
+        let label = format!("{} {}", trigger.as_str(), command);
+        let reason = match output::stream_command_to(&mut process, &label, timeout, &mut log_file) {
+            Ok(outcome) if outcome.timed_out => Some(format!("timed out after {}s", timeout.unwrap_or_default().as_secs())),
+            Ok(outcome) if outcome.status.success() => None,
+            Ok(outcome) => Some(match outcome.status.code() {
+                Some(code) => format!("exited with status {}", code),
+                None => "was killed by a signal".to_string(),
+            }),
+            Err(e) => Some(format!("could not be started: {:#}", e)),
+        };
+        if let Some(reason) = reason {
+            writeln!(log_file, "=== failed: {}", reason)?;
+            failures.push(HookFailure {
+                command: command.clone(),
+                reason,
+            });
+        }
+    }
+    Ok(failures)
+}
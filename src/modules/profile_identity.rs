@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::{Profile, ProfileIdentity};
+
+const BEGIN: &str = "# >>> zshrcman profile >>>";
+const END: &str = "# <<< zshrcman profile <<<";
+
+/// The identity in effect for `profile`: the first one set along its parent
+/// chain.
+pub fn for_profile<'a>(profiles: &'a HashMap<String, Profile>, profile: &str) -> Option<&'a ProfileIdentity> {
+    let mut seen: Vec<&str> = Vec::new();
+    let mut next = Some(profile);
+    while let Some(profile) = next.and_then(|name| profiles.get(name)) {
+        if seen.contains(&profile.name.as_str()) {
+            break;
+        }
+        if let Some(identity) = &profile.identity {
+            return Some(identity);
+        }
+        seen.push(&profile.name);
+        next = profile.parent.as_deref();
+    }
+    None
+}
+
+/// The git config zshrcman keeps for `profile`, included from `~/.gitconfig`
+/// while it's active.
+pub fn gitconfig_path(data_dir: &Path, profile: &str) -> PathBuf {
+    data_dir.join("profiles").join(profile).join("gitconfig")
+}
+
+/// Contents of the profile's git config. Empty without a name or email.
+pub fn gitconfig(identity: &ProfileIdentity) -> String {
+    if identity.name.is_none() && identity.email.is_none() {
+        return String::new();
+    }
+    let mut config = String::from("[user]\n");
+    if let Some(name) = &identity.name {
+        config.push_str(&format!("\tname = {}\n", quote(name)));
+    }
+    if let Some(email) = &identity.email {
+        config.push_str(&format!("\temail = {}\n", quote(email)));
+    }
+    config
+}
+
+/// `value` as a double-quoted git config value, so `#`, `;`, quotes and
+/// line breaks in it can't end it early or add settings.
+pub fn quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+/// The `~/.gitconfig` lines including `path`: once per `git_dirs` entry as
+/// an `includeIf "gitdir:..."`, or unconditionally.
+pub fn include_lines(path: &Path, git_dirs: &[String]) -> String {
+    let path = quote(&path.display().to_string());
+    if git_dirs.is_empty() {
+        return format!("[include]\n\tpath = {}\n", path);
+    }
+    git_dirs
+        .iter()
+        .map(|dir| {
+            let dir = if dir.ends_with('/') { dir.clone() } else { format!("{}/", dir) };
+            // Subsection names take the same escapes but can't hold a line
+            // break at all.
+            let dir = dir.replace('\n', "").replace('\\', "\\\\").replace('"', "\\\"");
+            format!("[includeIf \"gitdir:{}\"]\n\tpath = {}\n", dir, path)
+        })
+        .collect()
+}
+
+/// The `~/.ssh/config` lines pointing the profile's hosts at its key, if it
+/// has one and names hosts; with `IdentitiesOnly` a catch-all block would
+/// take every other key away. They end with `Match all` so that whatever
+/// follows them in the file applies to every host, as it did before.
+pub fn ssh_lines(identity: &ProfileIdentity) -> String {
+    let Some(key) = &identity.ssh_key else {
+        return String::new();
+    };
+    if identity.ssh_hosts.is_empty() {
+        return String::new();
+    }
+    format!("Host {}\n    IdentityFile {}\n    IdentitiesOnly yes\nMatch all\n", identity.ssh_hosts.join(" "), key)
+}
+
+/// Replaces (or removes, when `content` is empty) zshrcman's block in
+/// `existing`. It goes at the top when `first`, and at the end otherwise.
+/// Fails if a marker is missing its partner, rather than guess how much of
+/// the file is ours.
+pub fn replace_block(existing: &str, content: &str, first: bool) -> Result<String> {
+    let mut kept = String::new();
+    let mut in_block = false;
+    for (i, line) in existing.lines().enumerate() {
+        match (line == BEGIN, line == END) {
+            (true, _) if in_block => anyhow::bail!("line {}: a second '{}' before '{}'", i + 1, BEGIN, END),
+            (true, _) => in_block = true,
+            (_, true) if !in_block => anyhow::bail!("line {}: '{}' without '{}'", i + 1, END, BEGIN),
+            (_, true) => in_block = false,
+            _ if !in_block => {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+            _ => {}
+        }
+    }
+    if in_block {
+        anyhow::bail!("'{}' is never closed by '{}'", BEGIN, END);
+    }
+    if content.is_empty() {
+        return Ok(kept);
+    }
+    
+    let block = format!("{}\n{}{}\n", BEGIN, content, END);
+    Ok(if first { format!("{}{}", block, kept) } else { format!("{}{}", kept, block) })
+}
+
+fn write_block(path: &Path, content: &str, first: bool) -> Result<()> {
+    let existing = match fs::read_to_string(path) {
+        Ok(existing) => existing,
+        Err(_) if content.is_empty() => return Ok(()),
+        Err(_) => String::new(),
+    };
+    let updated = replace_block(&existing, content, first).with_context(|| format!("Fix the zshrcman markers in {}", path.display()))?;
+    if updated != existing {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, updated)?;
+    }
+    Ok(())
+}
+
+/// Points `~/.gitconfig` and `~/.ssh/config` at `identity` of `profile`, or
+/// takes zshrcman's blocks out of them when there's none. Git's block goes
+/// last so it overrides the user's own `[user]`; SSH's goes first because
+/// SSH keeps the first value it reads.
+pub fn apply(home: &Path, data_dir: &Path, profile: &str, identity: Option<&ProfileIdentity>) -> Result<()> {
+    let mut include = String::new();
+    if let Some(identity) = identity {
+        let config = gitconfig(identity);
+        if !config.is_empty() {
+            let path = gitconfig_path(data_dir, profile);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, config)?;
+            include = include_lines(&path, &identity.git_dirs);
+        }
+    }
+    write_block(&home.join(".gitconfig"), &include, false)?;
+    write_block(&home.join(".ssh").join("config"), &identity.map(ssh_lines).unwrap_or_default(), true)
+}
+
+/// Takes zshrcman's blocks out of `~/.gitconfig` and `~/.ssh/config`.
+pub fn clear(home: &Path) -> Result<()> {
+    apply(home, Path::new(""), "", None)
+}
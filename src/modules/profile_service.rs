@@ -0,0 +1,103 @@
+use anyhow::Result;
+use crate::models::Profile;
+use crate::modules::config::ConfigManager;
+use crate::modules::profile_switcher::ProfileSwitcher;
+use crate::modules::state_manager::InstallationStateManager;
+use crate::modules::suggest;
+
+/// High-level profile operations: the same behaviour as `zshrcman profile ...`,
+/// for callers embedding zshrcman as a library.
+pub struct ProfileService {
+    switcher: ProfileSwitcher,
+}
+
+impl ProfileService {
+    pub fn new(config_mgr: ConfigManager) -> Self {
+        let state_mgr = InstallationStateManager::new(config_mgr);
+        Self {
+            switcher: ProfileSwitcher::new(state_mgr),
+        }
+    }
+
+    /// All profiles, sorted by name.
+    pub fn list(&self) -> Vec<&Profile> {
+        let mut profiles: Vec<&Profile> = self.switcher.state().profiles.values().collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        profiles
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.switcher.state().profiles.get(name)
+    }
+
+    pub fn active(&self) -> Option<&str> {
+        self.switcher.state().active_profile.as_deref()
+    }
+
+    pub fn create(&mut self, name: &str, parent: Option<String>) -> Result<()> {
+        self.switcher.state_mut().create_profile(name, parent)
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<()> {
+        if self.active() == Some(name) {
+            anyhow::bail!("Cannot delete active profile. Switch to another profile first.");
+        }
+
+        let state_mgr = self.switcher.state_mut();
+        if state_mgr.profiles.remove(name).is_none() {
+            return Err(suggest::not_found("Profile", name, state_mgr.profiles.keys()));
+        }
+        state_mgr.save_state()
+    }
+
+    /// Makes `name` the persisted active profile and applies its environment.
+    pub fn switch(&mut self, name: &str) -> Result<()> {
+        self.switcher.switch_profile(name)
+    }
+
+    /// Applies a profile's environment without changing the persisted active profile.
+    pub fn activate(&mut self, name: &str) -> Result<()> {
+        self.switcher.activate_profile(name)
+    }
+
+    pub fn deactivate(&mut self) -> Result<()> {
+        self.switcher.deactivate_current()
+    }
+
+    /// Adds `group` to the groups the profile enables. Returns false if it
+    /// already did.
+    pub fn enable_group(&mut self, profile: &str, group: &str) -> Result<bool> {
+        let state_mgr = self.switcher.state_mut();
+        if state_mgr.config().load_merged_group(group).is_err() {
+            anyhow::bail!("Group '{}' has no group file", group);
+        }
+        let Some(profile_data) = state_mgr.profiles.get_mut(profile) else {
+            return Err(suggest::not_found("Profile", profile, state_mgr.profiles.keys()));
+        };
+        if profile_data.enabled_groups.iter().any(|g| g == group) {
+            return Ok(false);
+        }
+        profile_data.enabled_groups.push(group.to_string());
+        state_mgr.save_state()?;
+        Ok(true)
+    }
+
+    /// Returns false if the profile didn't enable `group`.
+    pub fn disable_group(&mut self, profile: &str, group: &str) -> Result<bool> {
+        let state_mgr = self.switcher.state_mut();
+        let Some(profile_data) = state_mgr.profiles.get_mut(profile) else {
+            return Err(suggest::not_found("Profile", profile, state_mgr.profiles.keys()));
+        };
+        let before = profile_data.enabled_groups.len();
+        profile_data.enabled_groups.retain(|g| g != group);
+        if profile_data.enabled_groups.len() == before {
+            return Ok(false);
+        }
+        state_mgr.save_state()?;
+        Ok(true)
+    }
+
+    pub fn state(&self) -> &InstallationStateManager {
+        self.switcher.state()
+    }
+}
@@ -1,9 +1,46 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::env;
+use crate::models::InstallationRecord;
 use crate::modules::state_manager::InstallationStateManager;
 use crate::modules::environment::EnvironmentManager;
+use crate::modules::bin_links;
+use crate::modules::config::ConfigManager;
+use crate::modules::install::InstallManager;
+use crate::modules::output;
+use crate::modules::preflight;
+use crate::modules::profile_hooks::{self, Trigger};
+use crate::modules::profile_identity;
+use crate::modules::suggest;
+use crate::modules::theme;
+use crate::modules::windows_env;
+
+/// `profile`'s bin dir under `data_dir`. Older releases kept it under
+/// `~/.local/share/zshrcman` whatever the platform, so on macOS and Windows
+/// that one is moved over (or dropped, when the new one already exists).
+pub fn migrate_bin_dir(home: &Path, data_dir: &Path, profile: &str) -> Result<PathBuf> {
+    let bin = data_dir.join("profiles").join(profile).join("bin");
+    let legacy_profile = home.join(".local/share/zshrcman/profiles").join(profile);
+    let legacy = legacy_profile.join("bin");
+    if legacy == bin || !legacy.is_dir() {
+        return Ok(bin);
+    }
+    
+    // The links are rewritten from the installed packages on the next
+    // switch, so if they can't be moved they can go.
+    let moved = !bin.exists()
+        && bin.parent().is_some_and(|parent| fs::create_dir_all(parent).is_ok())
+        && fs::rename(&legacy, &bin).is_ok();
+    if moved {
+        output::info(format!("Moved {}'s bin dir from {} to {}", profile, legacy.display(), bin.display()));
+    } else {
+        fs::remove_dir_all(&legacy)?;
+        output::info(format!("Removed {}'s old bin dir {}", profile, legacy.display()));
+    }
+    let _ = fs::remove_dir(&legacy_profile);
+    Ok(bin)
+}
 
 pub struct ProfileSwitcher {
     state_mgr: InstallationStateManager,
@@ -12,17 +49,34 @@ pub struct ProfileSwitcher {
 
 impl ProfileSwitcher {
     pub fn new(state_mgr: InstallationStateManager) -> Self {
-        let env_mgr = EnvironmentManager::new();
+        let mut env_mgr = EnvironmentManager::new();
+        if cfg!(windows) && state_mgr.config().config.windows.persist_environment {
+            env_mgr = env_mgr.with_persistence(windows_env::UndoRecord::path(&state_mgr.config().data_dir()));
+        }
         Self { state_mgr, env_mgr }
     }
     
+    pub fn state(&self) -> &InstallationStateManager {
+        &self.state_mgr
+    }
+    
+    pub fn state_mut(&mut self) -> &mut InstallationStateManager {
+        &mut self.state_mgr
+    }
+    
     pub fn switch_profile(&mut self, new_profile: &str) -> Result<()> {
         let start = std::time::Instant::now();
         
         let old_profile = self.state_mgr.active_profile.clone();
+        let old_groups = self.state_mgr.config().get_ordered_groups();
+        let old_theme = theme::active_name(&self.state_mgr.config().config).map(String::from);
+        if !self.state_mgr.profiles.contains_key(new_profile) {
+            return Err(suggest::not_found("Profile", new_profile, self.state_mgr.profiles.keys()));
+        }
         
         // Step 1: Deactivate old profile's environment
         if let Some(old) = &old_profile {
+            self.run_hooks(old, Trigger::Deactivate);
             self.deactivate_environment(old)?;
         }
         
@@ -37,9 +91,13 @@ impl ProfileSwitcher {
         
         // Step 5: Update shell configuration
         self.update_shell_config(new_profile)?;
+        self.refresh_group_variables();
         
         let duration = start.elapsed();
         println!("✅ Switched to profile '{}' in {:?}", new_profile, duration);
+        self.report_group_changes(&old_groups);
+        self.switch_theme(old_theme);
+        self.run_hooks(new_profile, Trigger::Activate);
         
         Ok(())
     }
@@ -48,28 +106,116 @@ impl ProfileSwitcher {
         self.activate_environment(profile)?;
         self.update_active_binaries(profile)?;
         self.update_shell_config(profile)?;
+        self.refresh_group_variables();
         println!("✅ Profile '{}' activated", profile);
+        self.run_hooks(profile, Trigger::Activate);
         Ok(())
     }
     
     pub fn deactivate_current(&mut self) -> Result<()> {
         if let Some(profile) = self.state_mgr.active_profile.clone() {
+            let old_groups = self.state_mgr.config().get_ordered_groups();
+            let old_theme = theme::active_name(&self.state_mgr.config().config).map(String::from);
+            self.run_hooks(&profile, Trigger::Deactivate);
             self.deactivate_environment(&profile)?;
             self.clear_profile_binaries(&profile)?;
             self.state_mgr.active_profile = None;
+            self.state_mgr.save_state()?;
+            self.refresh_group_variables();
             println!("✅ Profile '{}' deactivated", profile);
+            self.report_group_changes(&old_groups);
+            self.switch_theme(old_theme);
         }
         Ok(())
     }
     
+    /// Rewrites the group variables block, which leaves out what the active
+    /// profile sets, from the config as just saved.
+    fn refresh_group_variables(&self) {
+        let config = self.state_mgr.config();
+        let result = ConfigManager::with_paths(config.config_path().to_path_buf(), config.dotfiles_path().to_path_buf())
+            .and_then(|config_mgr| InstallManager::new(config_mgr).write_variables());
+        if let Err(e) = result {
+            output::warn(format!("⚠️  Failed to update the group variables: {:#}", e));
+        }
+    }
+    
+    /// Runs the profile's `on_activate` or `on_deactivate` commands. They run
+    /// before a profile is taken down and after one is fully set up, and
+    /// failures only warn, so a failing hook never leaves a switch half done.
+    fn run_hooks(&self, profile: &str, trigger: Trigger) {
+        let Some(state) = self.state_mgr.profiles.get(profile) else {
+            return;
+        };
+        let commands = match trigger {
+            Trigger::Activate => &state.on_activate,
+            Trigger::Deactivate => &state.on_deactivate,
+        };
+        if commands.is_empty() {
+            return;
+        }
+        
+        let log = profile_hooks::log_path(&self.state_mgr.config().data_dir());
+        output::info(format!("🪝 Running {} hooks of profile '{}'...", trigger.as_str(), profile));
+        match profile_hooks::run(commands, profile, trigger, &state.environment, &log, Some(profile_hooks::TIMEOUT)) {
+            Ok(failures) if failures.is_empty() => {}
+            Ok(failures) => {
+                for failure in &failures {
+                    output::warn(format!("⚠️  {} hook {}", trigger.as_str(), failure));
+                }
+                output::warn(format!("   Their output is in {}", log.display()));
+            }
+            Err(e) => output::warn(format!("⚠️  Failed to run {} hooks: {:#}", trigger.as_str(), e)),
+        }
+    }
+    
+    /// Lists the groups that the profile change enabled or disabled. Nothing
+    /// is installed or removed here; that's up to `zshrcman install`.
+    fn report_group_changes(&self, old_groups: &[String]) {
+        let new_groups = self.state_mgr.config().get_ordered_groups();
+        let enabled: Vec<&str> = new_groups.iter().filter(|g| !old_groups.contains(g)).map(String::as_str).collect();
+        let disabled: Vec<&str> = old_groups.iter().filter(|g| !new_groups.contains(g)).map(String::as_str).collect();
+        
+        if !enabled.is_empty() {
+            println!("📦 Now enabled: {}; run `zshrcman install` to install them", enabled.join(", "));
+        }
+        if !disabled.is_empty() {
+            println!(
+                "📦 No longer enabled: {}; they stay installed until `zshrcman group rollback <name>`",
+                disabled.join(", ")
+            );
+        }
+    }
+    
+    /// Rewrites the prompt setup when the profile change picked another
+    /// theme. A failure only warns; the switch itself already happened.
+    fn switch_theme(&self, old_theme: Option<String>) {
+        let config_mgr = self.state_mgr.config();
+        if theme::active_name(&config_mgr.config).map(String::from) == old_theme {
+            return;
+        }
+        match preflight::home_dir().and_then(|home| theme::apply(config_mgr, &home)) {
+            Ok(Some(name)) => println!("🎨 Prompt theme '{}'; open a new shell to see it", name),
+            Ok(None) => println!("🎨 Prompt theme removed"),
+            Err(e) => println!("⚠️  Failed to switch the prompt theme: {:#}", e),
+        }
+    }
+    
     fn activate_environment(&self, profile: &str) -> Result<()> {
         if let Some(profile_state) = self.state_mgr.profiles.get(profile) {
             // Apply environment variables
             self.env_mgr.apply_profile_environment(&profile_state.environment)?;
             
+            // Persist them into the shell startup files for future shells
+            self.env_mgr.write_shell_config(&profile_state.environment)?;
+            
             // Update PATH with profile-specific directories
             let profile_bin_dir = self.get_profile_bin_dir(profile)?;
             self.add_to_path(&profile_bin_dir)?;
+            
+            // Point git and SSH at the profile's identity
+            let identity = profile_identity::for_profile(&self.state_mgr.profiles, profile);
+            profile_identity::apply(&preflight::home_dir()?, &ConfigManager::get_data_dir()?, profile, identity)?;
         }
         
         Ok(())
@@ -79,39 +225,41 @@ impl ProfileSwitcher {
         if let Some(profile_state) = self.state_mgr.profiles.get(profile) {
             // Remove profile-specific environment variables
             self.env_mgr.clear_profile_environment(&profile_state.environment)?;
+            self.env_mgr.clear_shell_config()?;
             
             // Remove from PATH
             let profile_bin_dir = self.get_profile_bin_dir(profile)?;
             self.remove_from_path(&profile_bin_dir)?;
+            
+            // Back to the user's own git and SSH identity
+            profile_identity::clear(&preflight::home_dir()?)?;
         }
         
         Ok(())
     }
     
-    fn update_active_binaries(&self, profile: &str) -> Result<()> {
+    /// Rewrites `profile`'s bin links to the executables of its active
+    /// packages, reporting name clashes, executables that are gone and the
+    /// dangling links dropped.
+    pub fn update_active_binaries(&self, profile: &str) -> Result<()> {
         let profile_bin = self.get_profile_bin_dir(profile)?;
+        let packages = self.state_mgr.get_active_packages(profile)?;
+        let records: Vec<&InstallationRecord> =
+            packages.iter().filter_map(|package| self.state_mgr.installations.get(package)).collect();
+        let plan = bin_links::plan(&records);
         
-        // Create profile bin directory if it doesn't exist
-        fs::create_dir_all(&profile_bin)?;
-        
-        // Clear old symlinks
-        if profile_bin.exists() {
-            for entry in fs::read_dir(&profile_bin)? {
-                let entry = entry?;
-                if entry.path().is_file() || entry.path().is_symlink() {
-                    fs::remove_file(entry.path())?;
-                }
-            }
+        for collision in &plan.collisions {
+            println!(
+                "⚠️  {} is provided by both {} and {}; linked {}'s",
+                collision.name, collision.kept, collision.skipped, collision.kept
+            );
         }
-        
-        // Create new symlinks for active packages
-        for package in self.state_mgr.get_active_packages(profile)? {
-            if let Some(record) = self.state_mgr.get_package_info(&package) {
-                if let Some(location) = &record.location {
-                    let target = profile_bin.join(&package);
-                    self.create_symlink(location, &target)?;
-                }
-            }
+        for link in &plan.missing {
+            println!("⚠️  {}'s {} is gone ({}); reinstall it", link.package, link.name, link.target.display());
+        }
+        let dangling = bin_links::sync(&profile_bin, &plan)?;
+        if !dangling.is_empty() {
+            println!("🔗 Removed {} dangling link(s) from {}", dangling.len(), profile_bin.display());
         }
         
         Ok(())
@@ -162,21 +310,14 @@ impl ProfileSwitcher {
     }
     
     fn get_profile_bin_dir(&self, profile: &str) -> Result<PathBuf> {
-        let home = env::var("HOME").context("HOME not set")?;
-        Ok(PathBuf::from(home)
-            .join(".local")
-            .join("share")
-            .join("zshrcman")
-            .join("profiles")
-            .join(profile)
-            .join("bin"))
+        migrate_bin_dir(&preflight::home_dir()?, &ConfigManager::get_data_dir()?, profile)
     }
     
     fn get_shell_config_path(&self) -> Result<PathBuf> {
-        let home = env::var("HOME").context("HOME not set")?;
+        let home = preflight::home_dir()?;
         
         // Determine shell config file based on current shell
-        let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
+        let shell = preflight::shell()?;
         
         let config_file = if shell.contains("zsh") {
             ".zshrc"
@@ -188,41 +329,28 @@ impl ProfileSwitcher {
             ".profile"
         };
         
-        Ok(PathBuf::from(home).join(config_file))
+        Ok(home.join(config_file))
     }
     
-    fn add_to_path(&self, dir: &PathBuf) -> Result<()> {
-        let current_path = env::var("PATH").unwrap_or_default();
-        let dir_str = dir.to_string_lossy();
+    /// Puts `dir` first on PATH, split and joined the platform's way (`;`
+    /// on Windows).
+    fn add_to_path(&self, dir: &Path) -> Result<()> {
+        let current_path = env::var_os("PATH").unwrap_or_default();
+        let mut paths: Vec<PathBuf> = env::split_paths(&current_path).collect();
         
-        if !current_path.contains(&*dir_str) {
-            let new_path = format!("{}:{}", dir_str, current_path);
-            env::set_var("PATH", new_path);
+        if !paths.iter().any(|path| path == dir) {
+            paths.insert(0, dir.to_path_buf());
+            env::set_var("PATH", env::join_paths(paths)?);
         }
         
         Ok(())
     }
     
-    fn remove_from_path(&self, dir: &PathBuf) -> Result<()> {
-        let current_path = env::var("PATH").unwrap_or_default();
-        let dir_str = dir.to_string_lossy();
-        
-        let paths: Vec<&str> = current_path.split(':').filter(|p| *p != dir_str).collect();
-        let new_path = paths.join(":");
+    fn remove_from_path(&self, dir: &Path) -> Result<()> {
+        let current_path = env::var_os("PATH").unwrap_or_default();
+        let paths = env::split_paths(&current_path).filter(|path| path != dir);
         
-        env::set_var("PATH", new_path);
-        Ok(())
-    }
-    
-    #[cfg(unix)]
-    fn create_symlink(&self, source: &PathBuf, target: &PathBuf) -> Result<()> {
-        std::os::unix::fs::symlink(source, target)?;
-        Ok(())
-    }
-    
-    #[cfg(windows)]
-    fn create_symlink(&self, source: &PathBuf, target: &PathBuf) -> Result<()> {
-        std::os::windows::fs::symlink_file(source, target)?;
+        env::set_var("PATH", env::join_paths(paths)?);
         Ok(())
     }
 }
\ No newline at end of file
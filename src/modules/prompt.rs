@@ -0,0 +1,82 @@
+use anyhow::Result;
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Thin wrappers around dialoguer prompts. When non-interactive mode is on
+/// (`--yes`, `ZSHRCMAN_YES`), every prompt resolves to its default without
+/// touching the terminal, so zshrcman can run from provisioning scripts and CI.
+pub fn set_non_interactive(enabled: bool) {
+    NON_INTERACTIVE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_non_interactive() -> bool {
+    NON_INTERACTIVE.load(Ordering::Relaxed)
+}
+
+pub fn confirm(prompt: &str, default: bool) -> Result<bool> {
+    if is_non_interactive() {
+        println!("{} {}", prompt, if default { "[yes]" } else { "[no]" });
+        return Ok(default);
+    }
+
+    Ok(Confirm::new()
+        .with_prompt(prompt)
+        .default(default)
+        .interact()?)
+}
+
+pub fn select<T: ToString>(prompt: &str, items: &[T], default: usize) -> Result<usize> {
+    if is_non_interactive() {
+        if let Some(item) = items.get(default) {
+            println!("{} [{}]", prompt, item.to_string());
+        }
+        return Ok(default);
+    }
+
+    Ok(Select::new()
+        .with_prompt(prompt)
+        .items(items)
+        .default(default)
+        .interact()?)
+}
+
+pub fn multi_select<T: ToString>(prompt: &str, items: &[T], defaults: &[bool]) -> Result<Vec<usize>> {
+    if is_non_interactive() {
+        return Ok(defaults
+            .iter()
+            .enumerate()
+            .filter(|(idx, selected)| **selected && *idx < items.len())
+            .map(|(idx, _)| idx)
+            .collect());
+    }
+
+    Ok(MultiSelect::new()
+        .with_prompt(prompt)
+        .items(items)
+        .defaults(defaults)
+        .interact()?)
+}
+
+/// Reads a line of text. In non-interactive mode the default is used, and a
+/// prompt without a default is an error naming the flag that must be passed.
+pub fn input(prompt: &str, default: Option<&str>, flag_hint: &str) -> Result<String> {
+    if is_non_interactive() {
+        return match default {
+            Some(value) => Ok(value.to_string()),
+            None => anyhow::bail!(
+                "'{}' requires an answer in non-interactive mode; pass {}",
+                prompt,
+                flag_hint
+            ),
+        };
+    }
+
+    let mut input = Input::<String>::new().with_prompt(prompt);
+    if let Some(value) = default {
+        input = input.default(value.to_string()).allow_empty(value.is_empty());
+    }
+
+    Ok(input.interact_text()?)
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::{CommitIdentity, ConflictStrategy, NamedRepository};
+use crate::modules::config::ConfigManager;
+use crate::modules::git_mgr::{GitManager, SyncMode};
+use crate::modules::hooks;
+use crate::modules::output;
+
+/// Checkout of the repository `name`, next to the main dotfiles checkout.
+pub fn checkout_dir(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join("repos").join(name)
+}
+
+/// The branch this machine works on in `repository`.
+pub fn branch<'a>(repository: &'a NamedRepository, device_branch: &'a str) -> &'a str {
+    repository.branch.as_deref().unwrap_or(device_branch)
+}
+
+/// Opens the checkout of `name`, cloning it first if needed, with its
+/// mirrors set up.
+pub fn open(data_dir: &Path, name: &str, repository: &NamedRepository) -> Result<GitManager> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("Invalid repository name '{}'; use letters, digits, '-' or '_'", name);
+    }
+    
+    let dir = checkout_dir(data_dir, name);
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    GitManager::init_or_clone(&dir, Some(&repository.url))?.with_mirrors(&repository.remotes)
+}
+
+/// Clones the repository if needed and syncs its branch with its main branch,
+/// the same way `sync` does for the main repo. Commits are made as `identity`.
+pub fn sync(
+    data_dir: &Path,
+    name: &str,
+    repository: &NamedRepository,
+    device_branch: &str,
+    strategy: ConflictStrategy,
+    mode: SyncMode,
+    identity: &CommitIdentity,
+) -> Result<()> {
+    let git = open(data_dir, name, repository)?.with_identity(identity);
+    let branch = branch(repository, device_branch);
+    git.ensure_branch(branch, &repository.main_branch)?;
+    git.sync(&repository.main_branch, branch, strategy, mode)
+}
+
+/// Syncs every configured repository in `mode`, resolving conflicts with
+/// `strategy`. A failing one only warns, so the others still sync.
+pub fn sync_all(config_mgr: &ConfigManager, strategy: ConflictStrategy, mode: SyncMode) {
+    let data_dir = config_mgr.data_dir();
+    let device_branch = &config_mgr.config.device.branch;
+    for (name, repository) in &config_mgr.config.repositories {
+        let result = sync(
+            &data_dir,
+            name,
+            repository,
+            device_branch,
+            strategy,
+            mode,
+            &config_mgr.config.repository.identity,
+        );
+        hooks::emit_sync(&config_mgr.config, name, branch(repository, device_branch), &result);
+        match result {
+            Ok(()) => output::info(format!("📁 Repository '{}' synced", name)),
+            Err(e) => println!("⚠️  Failed to sync repository '{}': {:#}", name, e),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// File name of a group's install script, stored next to its TOML as
+/// `groups/<name>/install.rhai`.
+pub const INSTALL_SCRIPT: &str = "install.rhai";
+
+/// What an install script may touch: it reads from the group directory and
+/// writes only below `home`.
+#[derive(Debug, Clone)]
+pub struct ScriptContext {
+    pub group: String,
+    pub group_dir: PathBuf,
+    pub home: PathBuf,
+    pub dry_run: bool,
+}
+
+/// Resolves a path a script wants to write to. `~/` and relative paths land in
+/// `home`; anything that would escape it is rejected.
+pub fn resolve_target(home: &Path, target: &str) -> Result<PathBuf> {
+    let relative = target.strip_prefix("~/").unwrap_or(target);
+    let path = Path::new(relative);
+    
+    if path.is_absolute() && !path.starts_with(home) {
+        anyhow::bail!("'{}' is outside the home directory", target);
+    }
+    if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        anyhow::bail!("'{}' must not contain '..'", target);
+    }
+    
+    Ok(home.join(path))
+}
+
+/// Resolves a path a script wants to read, relative to the group directory.
+pub fn resolve_source(group_dir: &Path, source: &str) -> Result<PathBuf> {
+    let path = Path::new(source);
+    if path.is_absolute() || path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        anyhow::bail!("'{}' must be a path inside the group directory", source);
+    }
+    Ok(group_dir.join(path))
+}
+
+/// Replaces `{{ key }}` placeholders with values from `vars`.
+pub fn render_placeholders(template: &str, vars: &[(String, String)]) -> String {
+    let mut out = template.to_string();
+    for (key, value) in vars {
+        out = out.replace(&format!("{{{{{}}}}}", key), value);
+        out = out.replace(&format!("{{{{ {} }}}}", key), value);
+    }
+    out
+}
+
+#[cfg(feature = "scripting")]
+pub fn run_install_script(script: &Path, context: &ScriptContext) -> Result<()> {
+    use anyhow::Context;
+    use rhai::{Array, Dynamic, Engine, EvalAltResult, Map};
+    use std::fs;
+    use std::process::Command;
+    
+    type ScriptResult<T> = std::result::Result<T, Box<EvalAltResult>>;
+    
+    fn script_err(e: impl std::fmt::Display) -> Box<EvalAltResult> {
+        e.to_string().into()
+    }
+    
+    let source = fs::read_to_string(script)
+        .with_context(|| format!("Failed to read {}", script.display()))?;
+    
+    // Limits keep a runaway script from hanging or exhausting memory; they
+    // don't make it safe to run, since `run` starts any program.
+    let mut engine = Engine::new();
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.set_max_operations(5_000_000);
+    engine.set_max_call_levels(64);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(10 * 1024 * 1024);
+    engine.set_max_array_size(100_000);
+    engine.set_max_map_size(100_000);
+    
+    let ctx = context.clone();
+    engine.register_fn("run", move |program: &str, args: Array| -> ScriptResult<String> {
+        let args: Vec<String> = args.into_iter().map(|a| a.to_string()).collect();
+        if ctx.dry_run {
+            println!("   [dry-run] {} {}", program, args.join(" "));
+            return Ok(String::new());
+        }
+        
+        let output = Command::new(program)
+            .args(&args)
+            .current_dir(&ctx.group_dir)
+            .output()
+            .map_err(|e| script_err(format!("Failed to run {}: {}", program, e)))?;
+        if !output.status.success() {
+            return Err(script_err(format!(
+                "{} {} failed: {}",
+                program,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    });
+    
+    let ctx = context.clone();
+    engine.register_fn("copy", move |source: &str, target: &str| -> ScriptResult<()> {
+        let source = resolve_source(&ctx.group_dir, source).map_err(script_err)?;
+        let target = resolve_target(&ctx.home, target).map_err(script_err)?;
+        if ctx.dry_run {
+            println!("   [dry-run] copy {} -> {}", source.display(), target.display());
+            return Ok(());
+        }
+        
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(script_err)?;
+        }
+        fs::copy(&source, &target).map_err(script_err)?;
+        Ok(())
+    });
+    
+    let ctx = context.clone();
+    engine.register_fn("template", move |source: &str, target: &str, vars: Map| -> ScriptResult<()> {
+        let source = resolve_source(&ctx.group_dir, source).map_err(script_err)?;
+        let target = resolve_target(&ctx.home, target).map_err(script_err)?;
+        let vars: Vec<(String, String)> = vars
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        
+        let contents = fs::read_to_string(&source).map_err(script_err)?;
+        let rendered = render_placeholders(&contents, &vars);
+        if ctx.dry_run {
+            println!("   [dry-run] template {} -> {}", source.display(), target.display());
+            return Ok(());
+        }
+        
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(script_err)?;
+        }
+        fs::write(&target, rendered).map_err(script_err)?;
+        Ok(())
+    });
+    
+    let ctx = context.clone();
+    engine.register_fn("exists", move |target: &str| -> ScriptResult<bool> {
+        Ok(resolve_target(&ctx.home, target).map_err(script_err)?.exists())
+    });
+    
+    engine.register_fn("env", |name: &str| -> Dynamic {
+        std::env::var(name).map(Dynamic::from).unwrap_or(Dynamic::UNIT)
+    });
+    
+    let mut scope = rhai::Scope::new();
+    scope.push_constant("GROUP", context.group.clone());
+    scope.push_constant("HOME", context.home.display().to_string());
+    scope.push_constant("OS", std::env::consts::OS.to_string());
+    scope.push_constant("DRY_RUN", context.dry_run);
+    
+    engine
+        .run_with_scope(&mut scope, &source)
+        .map_err(|e| anyhow::anyhow!("{} failed: {}", script.display(), e))
+}
+
+#[cfg(not(feature = "scripting"))]
+pub fn run_install_script(script: &Path, _context: &ScriptContext) -> Result<()> {
+    anyhow::bail!(
+        "{} needs scripting support; rebuild zshrcman with the `scripting` feature",
+        script.display()
+    )
+}
@@ -0,0 +1,200 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use strsim::jaro_winkler;
+use crate::models::GroupConfig;
+use crate::modules::tidy;
+
+/// Names this similar to the term count as a match even without containing it.
+const FUZZY_THRESHOLD: f64 = 0.85;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    Group,
+    Package,
+    Alias,
+    Script,
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Kind::Group => "group",
+            Kind::Package => "package",
+            Kind::Alias => "alias",
+            Kind::Script => "script",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Something in a group TOML that matched the search term.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub kind: Kind,
+    /// The package, alias definition, script or group name as written.
+    pub text: String,
+    pub group: String,
+    /// Set for per-device groups under `devices/<name>/groups`.
+    pub device: Option<String>,
+    /// Relative to the dotfiles repo.
+    pub file: PathBuf,
+    /// One-based line in `file`, when it could be found.
+    pub line: Option<usize>,
+    /// 1.0 for an exact name match, lower for looser ones.
+    pub score: f64,
+}
+
+impl Match {
+    /// `groups/k8s.toml:7`
+    pub fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{}", self.file.display(), line),
+            None => self.file.display().to_string(),
+        }
+    }
+}
+
+/// Searches the group, package, alias and script names of every global and
+/// per-device group in the dotfiles repo, best matches first. Files that
+/// don't parse are skipped with a warning.
+pub fn search(dotfiles: &Path, term: &str) -> Result<Vec<Match>> {
+    let mut matches = Vec::new();
+    for (file, device) in group_files(dotfiles)? {
+        let contents = fs::read_to_string(dotfiles.join(&file))?;
+        match toml::from_str::<GroupConfig>(&contents) {
+            Ok(group) => matches.extend(search_group(&group, &contents, term, &file, device.as_deref())),
+            Err(e) => println!("⚠️  Skipping {}: {}", file.display(), e),
+        }
+    }
+    
+    matches.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.kind.cmp(&b.kind))
+            .then_with(|| a.file.cmp(&b.file))
+    });
+    Ok(matches)
+}
+
+/// `groups/*.toml` and `devices/*/groups/*.toml`, relative to the repo, with
+/// the device each per-device group belongs to.
+fn group_files(dotfiles: &Path) -> Result<Vec<(PathBuf, Option<String>)>> {
+    let mut files: Vec<(PathBuf, Option<String>)> = toml_files(&dotfiles.join("groups"))?
+        .into_iter()
+        .map(|name| (Path::new("groups").join(name), None))
+        .collect();
+    
+    let devices_dir = dotfiles.join("devices");
+    if devices_dir.is_dir() {
+        let mut devices: Vec<String> = fs::read_dir(&devices_dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        devices.sort();
+        for device in devices {
+            let groups = Path::new("devices").join(&device).join("groups");
+            for name in toml_files(&dotfiles.join(&groups))? {
+                files.push((groups.join(name), Some(device.clone())));
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn toml_files(dir: &Path) -> Result<Vec<String>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".toml"))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+fn search_group(group: &GroupConfig, contents: &str, term: &str, file: &Path, device: Option<&str>) -> Vec<Match> {
+    let name = if group.name.is_empty() {
+        file.file_stem().unwrap_or_default().to_string_lossy().into_owned()
+    } else {
+        group.name.clone()
+    };
+    
+    let alias_lines: Vec<String> = group.aliases.iter().map(ToString::to_string).collect();
+    let mut candidates: Vec<(Kind, &str, Option<f64>)> = vec![(Kind::Group, name.as_str(), score(term, &name))];
+    for package in &group.packages {
+        candidates.push((Kind::Package, package, score(term, package)));
+    }
+    for (alias, text) in group.aliases.iter().zip(&alias_lines) {
+        // The alias name counts fully; a hit only in what it expands to or
+        // its description ranks a little lower, but above near-misses.
+        let by_name = score(term, &alias.name);
+        let described = alias.description.as_deref().is_some_and(|description| contains(description, term));
+        let by_value = (contains(&alias.command, term) || described).then_some(0.85);
+        candidates.push((Kind::Alias, text, best(by_name, by_value)));
+    }
+    for script in &group.scripts {
+        let file_name = Path::new(script).file_name().and_then(|f| f.to_str()).unwrap_or(script);
+        candidates.push((Kind::Script, script, best(score(term, file_name), score(term, script))));
+    }
+    
+    candidates
+        .into_iter()
+        .filter_map(|(kind, text, score)| {
+            let score = score?;
+            let line = match kind {
+                Kind::Group => line_of(contents, "name"),
+                // Quotes inside the alias are escaped in the TOML.
+                Kind::Alias => line_of(contents, text).or_else(|| {
+                    let alias_name = tidy::alias_name(text)?;
+                    line_of(contents, &format!("{}=", alias_name))
+                        .or_else(|| line_of(contents, &format!("name = \"{}\"", alias_name)))
+                }),
+                _ => line_of(contents, text),
+            };
+            Some(Match {
+                kind,
+                text: text.to_string(),
+                group: name.clone(),
+                device: device.map(String::from),
+                file: file.to_path_buf(),
+                line,
+                score,
+            })
+        })
+        .collect()
+}
+
+/// How well `candidate` matches `term`, ignoring case: 1.0 when equal, 0.9
+/// when it contains the term, else its Jaro-Winkler similarity if that's
+/// close enough, scaled below the substring matches.
+pub fn score(term: &str, candidate: &str) -> Option<f64> {
+    let term = term.to_lowercase();
+    let candidate = candidate.to_lowercase();
+    if term == candidate {
+        return Some(1.0);
+    }
+    if candidate.contains(&term) {
+        return Some(0.9);
+    }
+    let similarity = jaro_winkler(&term, &candidate);
+    (similarity >= FUZZY_THRESHOLD).then_some(similarity * 0.8)
+}
+
+fn best(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn contains(haystack: &str, term: &str) -> bool {
+    haystack.to_lowercase().contains(&term.to_lowercase())
+}
+
+fn line_of(contents: &str, needle: &str) -> Option<usize> {
+    contents.lines().position(|line| line.contains(needle)).map(|index| index + 1)
+}
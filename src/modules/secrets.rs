@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use crate::modules::output;
+use crate::modules::preflight;
+
+/// How long a backend gets to print a secret. Startup files wait for it, so
+/// a locked vault or a sign-in prompt must not hang every new shell.
+pub const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Overrides the age identity file secrets are decrypted with.
+pub const AGE_IDENTITY_VAR: &str = "ZSHRCMAN_AGE_IDENTITY";
+
+/// Where a secret lives, from the scheme of its reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// `op://vault/item/field`, read with the 1Password CLI.
+    OnePassword,
+    /// `bw://item`, the item's password from the Bitwarden CLI.
+    Bitwarden,
+    /// `pass://path`, the first line of a `pass` entry.
+    Pass,
+    /// `age://path`, an age-encrypted file relative to the dotfiles repo.
+    Age,
+}
+
+/// A reference to a secret such as `op://Private/GitHub/token`. Profiles
+/// keep these instead of the values, which are only fetched when the
+/// environment is activated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRef {
+    pub backend: Backend,
+    pub path: String,
+}
+
+impl SecretRef {
+    pub fn parse(reference: &str) -> Result<Self> {
+        let (scheme, path) = reference
+            .split_once("://")
+            .with_context(|| format!("'{}' is not a secret reference like op://vault/item/field", reference))?;
+        let backend = match scheme {
+            "op" => Backend::OnePassword,
+            "bw" => Backend::Bitwarden,
+            "pass" => Backend::Pass,
+            "age" => Backend::Age,
+            _ => anyhow::bail!("Unknown secret backend '{}' in '{}'; use op, bw, pass or age", scheme, reference),
+        };
+        if path.is_empty() {
+            anyhow::bail!("'{}' doesn't say which secret", reference);
+        }
+        Ok(Self { backend, path: path.to_string() })
+    }
+    
+    /// The program and arguments printing the secret. `repo` is the
+    /// dotfiles repo age files are relative to.
+    pub fn command(&self, repo: &Path) -> Vec<String> {
+        match self.backend {
+            Backend::OnePassword => vec!["op".into(), "read".into(), format!("op://{}", self.path)],
+            Backend::Bitwarden => vec!["bw".into(), "get".into(), "password".into(), self.path.clone()],
+            Backend::Pass => vec!["pass".into(), "show".into(), self.path.clone()],
+            Backend::Age => vec![
+                "age".into(),
+                "--decrypt".into(),
+                "--identity".into(),
+                age_identity().display().to_string(),
+                repo.join(&self.path).display().to_string(),
+            ],
+        }
+    }
+    
+    /// Fetches the secret, giving up after [`TIMEOUT`]. The backend gets no
+    /// stdin, so it fails instead of prompting. A trailing newline is
+    /// dropped, and only the first line of a `pass` entry is the secret.
+    pub fn resolve(&self, repo: &Path) -> Result<String> {
+        let command = self.command(repo);
+        let (outcome, stdout) = output::capture_command(Command::new(&command[0]).args(&command[1..]), &command[0], Some(TIMEOUT))
+            .with_context(|| format!("Failed to run {}", command[0]))?;
+        if outcome.timed_out {
+            anyhow::bail!("{} didn't answer within {}s for {}; is it unlocked?", command[0], TIMEOUT.as_secs(), self.path);
+        }
+        if !outcome.status.success() {
+            anyhow::bail!("{} failed for {}: {}", command[0], self.path, outcome.stderr_tail.trim());
+        }
+        Ok(secret_value(self.backend, &stdout))
+    }
+}
+
+/// The secret in a backend's output.
+pub fn secret_value(backend: Backend, output: &str) -> String {
+    match backend {
+        Backend::Pass => output.lines().next().unwrap_or_default().to_string(),
+        _ => output.strip_suffix('\n').map(|value| value.strip_suffix('\r').unwrap_or(value)).unwrap_or(output).to_string(),
+    }
+}
+
+/// The age identity: [`AGE_IDENTITY_VAR`], or age's usual
+/// `~/.config/age/keys.txt`.
+pub fn age_identity() -> PathBuf {
+    match env::var_os(AGE_IDENTITY_VAR) {
+        Some(path) => PathBuf::from(path),
+        None => preflight::home_dir().unwrap_or_default().join(".config/age/keys.txt"),
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::models::OsType;
+
+/// What runs a service file, going by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceManager {
+    Systemd,
+    Launchd,
+}
+
+impl ServiceManager {
+    pub fn for_file(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "service" | "timer" | "socket" | "path" => Some(ServiceManager::Systemd),
+            "plist" => Some(ServiceManager::Launchd),
+            _ => None,
+        }
+    }
+    
+    pub fn os(&self) -> OsType {
+        match self {
+            ServiceManager::Systemd => OsType::Linux,
+            ServiceManager::Launchd => OsType::MacOS,
+        }
+    }
+    
+    /// Where the user's units or agents live.
+    pub fn dir(&self, home: &Path) -> PathBuf {
+        match self {
+            ServiceManager::Systemd => home.join(".config").join("systemd").join("user"),
+            ServiceManager::Launchd => home.join("Library").join("LaunchAgents"),
+        }
+    }
+}
+
+/// Where `source` is deployed, if it's a service file for this machine's OS.
+pub fn target(source: &Path, home: &Path, os: &OsType) -> Option<PathBuf> {
+    let manager = ServiceManager::for_file(source)?;
+    if manager.os() != *os {
+        return None;
+    }
+    Some(manager.dir(home).join(source.file_name()?))
+}
+
+fn run(program: &str, args: &[&str], path: &Path) -> Result<bool> {
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to run {}", program))?;
+    Ok(status.success())
+}
+
+fn unit_name(path: &Path) -> &Path {
+    path.file_name().map(Path::new).unwrap_or(path)
+}
+
+/// Makes the service manager pick up deployed files again.
+pub fn reload(manager: ServiceManager) -> Result<()> {
+    if manager == ServiceManager::Systemd {
+        let status = Command::new("systemctl")
+            .args(["--user", "daemon-reload"])
+            .status()
+            .context("Failed to run systemctl")?;
+        if !status.success() {
+            anyhow::bail!("systemctl --user daemon-reload failed");
+        }
+    }
+    Ok(())
+}
+
+/// Enables and (re)starts the deployed unit or agent at `path`, so one
+/// running already picks up a redeployed file.
+pub fn enable(path: &Path) -> Result<()> {
+    let enabled = match ServiceManager::for_file(path) {
+        Some(ServiceManager::Systemd) => {
+            run("systemctl", &["--user", "enable"], unit_name(path))?
+                && run("systemctl", &["--user", "restart"], unit_name(path))?
+        }
+        Some(ServiceManager::Launchd) => {
+            // `load` refuses an agent that's loaded already; one that isn't
+            // fails to unload, which is fine.
+            let _ = Command::new("launchctl").arg("unload").arg(path).stderr(Stdio::null()).status();
+            run("launchctl", &["load", "-w"], path)?
+        }
+        None => anyhow::bail!("{} is not a systemd unit or launchd agent", path.display()),
+    };
+    if !enabled {
+        anyhow::bail!("Failed to enable {}", path.display());
+    }
+    Ok(())
+}
+
+/// Stops and disables the unit or agent at `path`. One that isn't loaded
+/// anymore is not an error.
+pub fn disable(path: &Path) -> Result<()> {
+    match ServiceManager::for_file(path) {
+        Some(ServiceManager::Systemd) => run("systemctl", &["--user", "disable", "--now"], unit_name(path))?,
+        Some(ServiceManager::Launchd) => run("launchctl", &["unload", "-w"], path)?,
+        None => true,
+    };
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use crate::modules::config::ConfigManager;
+use crate::modules::environment::{EnvironmentManager, ShellType};
+use crate::modules::shell_env;
+use crate::modules::suggest;
+
+/// Set in shells started by `zshrcman shell`, naming their profile. `zshrcman
+/// env` keeps that profile instead of the persisted active one.
+pub const SESSION_VAR: &str = "ZSHRCMAN_SESSION_PROFILE";
+
+/// The profile of the `zshrcman shell` session we're in, if any.
+pub fn session_profile() -> Option<String> {
+    env::var(SESSION_VAR).ok().filter(|profile| !profile.is_empty())
+}
+
+fn quote(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', "'\\''"))
+}
+
+/// Writes startup files into `dir` that load the user's usual rc file and
+/// then evaluate `script`, and returns the command starting an interactive
+/// `shell` with them. Cmd and nushell have no way to do that.
+pub fn command(shell: &ShellType, dir: &Path, script: &str) -> Result<Command> {
+    fs::create_dir_all(dir)?;
+    let command = match shell {
+        ShellType::Zsh => {
+            // zsh reads .zshenv and .zshrc from ZDOTDIR; ours hand over to the
+            // user's and switch back in between so ours is still the .zshrc read.
+            fs::write(
+                dir.join(".zshenv"),
+                "_zshrcman_session_dir=\"$ZDOTDIR\"\n\
+                 ZDOTDIR=\"${_ZSHRCMAN_ZDOTDIR:-$HOME}\"\n\
+                 [[ -r \"$ZDOTDIR/.zshenv\" ]] && source \"$ZDOTDIR/.zshenv\"\n\
+                 _zshrcman_user_zdotdir=\"$ZDOTDIR\"\n\
+                 ZDOTDIR=\"$_zshrcman_session_dir\"\n",
+            )?;
+            fs::write(
+                dir.join(".zshrc"),
+                format!(
+                    "ZDOTDIR=\"$_zshrcman_user_zdotdir\"\n\
+                     unset _zshrcman_session_dir _zshrcman_user_zdotdir _ZSHRCMAN_ZDOTDIR\n\
+                     [[ -r \"$ZDOTDIR/.zshrc\" ]] && source \"$ZDOTDIR/.zshrc\"\n{}",
+                    script
+                ),
+            )?;
+            let mut command = Command::new("zsh");
+            command.arg("-i").env("ZDOTDIR", dir);
+            if let Ok(zdotdir) = env::var("ZDOTDIR") {
+                command.env("_ZSHRCMAN_ZDOTDIR", zdotdir);
+            }
+            command
+        }
+        ShellType::Bash => {
+            let rcfile = dir.join("bashrc");
+            fs::write(&rcfile, format!("[[ -r ~/.bashrc ]] && source ~/.bashrc\n{}", script))?;
+            let mut command = Command::new("bash");
+            command.arg("--rcfile").arg(rcfile).arg("-i");
+            command
+        }
+        ShellType::Fish => {
+            let file = dir.join("session.fish");
+            fs::write(&file, script)?;
+            let mut command = Command::new("fish");
+            command.args(["-i", "-C"]).arg(format!("source {}", quote(&file)));
+            command
+        }
+        ShellType::PowerShell => {
+            let file = dir.join("session.ps1");
+            fs::write(&file, script)?;
+            let mut command = Command::new("pwsh");
+            command.args(["-NoExit", "-Command"]).arg(format!(". {}", quote(&file)));
+            command
+        }
+        ShellType::Cmd | ShellType::Nushell => anyhow::bail!("zshrcman shell supports zsh, bash, fish and pwsh; pick one with --shell"),
+    };
+    Ok(command)
+}
+
+/// Starts an interactive shell with profile `name`'s environment, leaving
+/// the active profile alone; the session lasts until that shell exits.
+pub fn start(name: &str) -> Result<()> {
+    let config_mgr = ConfigManager::new()?;
+    let config = &config_mgr.config;
+    let Some(profile) = config.profiles.get(name) else {
+        return Err(suggest::not_found("Profile", name, config.profiles.keys()));
+    };
+    let shell = EnvironmentManager::new().shell_type().clone();
+    
+    // The rc files bring in the active profile's environment; undo that first.
+    let current = session_profile().or(config.active_profile.clone()).unwrap_or_default();
+    let current_env = config.profiles.get(&current).map(|profile| &profile.environment);
+    let script = shell_env::script(&shell, (&current, current_env), Some((name, &profile.environment)));
+    
+    let dir = config_mgr.cache_dir().join("sessions").join(std::process::id().to_string());
+    let mut command = command(&shell, &dir, &script)?;
+    command.env(SESSION_VAR, name);
+    let bin = config_mgr.data_dir().join("profiles").join(name).join("bin");
+    if bin.is_dir() {
+        let path = env::var_os("PATH").unwrap_or_default();
+        command.env("PATH", env::join_paths(std::iter::once(bin).chain(env::split_paths(&path)))?);
+    }
+    
+    println!("🐚 Starting {} with profile '{}'; exit to return", shell.name(), name.bold());
+    let status = command.status();
+    let _ = fs::remove_dir_all(&dir);
+    status.with_context(|| format!("Failed to start {}", shell.name()))?;
+    println!("👋 Left profile '{}'", name);
+    Ok(())
+}
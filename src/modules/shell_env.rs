@@ -0,0 +1,182 @@
+use anyhow::Result;
+use crate::models::EnvironmentState;
+use crate::modules::auto_switch;
+use crate::modules::config::ConfigManager;
+use crate::modules::environment::{self, quote, EnvironmentManager, ShellType};
+use crate::modules::session;
+
+/// Shell variable (not exported) naming the profile whose environment the
+/// shell has applied, so the next `zshrcman env` only prints what changed.
+pub const APPLIED_VAR: &str = "_zshrcman_profile";
+
+fn sorted_variables(env_state: &EnvironmentState) -> Vec<(&String, &String)> {
+    let mut variables: Vec<_> = env_state.variables.iter().collect();
+    variables.sort();
+    variables
+}
+
+/// Statements applying `env_state`: PATH entries (skipped when already
+/// there, so evaluating them twice is harmless), variables and aliases.
+fn apply_lines(shell: &ShellType, env_state: &EnvironmentState) -> String {
+    let mut script = String::new();
+    for (path, prepend) in env_state.paths_prepend.iter().map(|path| (path, true)).chain(env_state.paths_append.iter().map(|path| (path, false))) {
+        let path = quote(shell, path);
+        let line = match (shell, prepend) {
+            (ShellType::Fish, true) => format!("contains -- {0} $PATH; or set -gx PATH {0} $PATH", path),
+            (ShellType::Fish, false) => format!("contains -- {0} $PATH; or set -gx PATH $PATH {0}", path),
+            (ShellType::PowerShell, true) => format!(
+                "if (($env:PATH -split [IO.Path]::PathSeparator) -notcontains {0}) {{ $env:PATH = {0} + [IO.Path]::PathSeparator + $env:PATH }}",
+                path
+            ),
+            (ShellType::PowerShell, false) => format!(
+                "if (($env:PATH -split [IO.Path]::PathSeparator) -notcontains {0}) {{ $env:PATH = $env:PATH + [IO.Path]::PathSeparator + {0} }}",
+                path
+            ),
+            (_, true) => format!("case \":$PATH:\" in *:{0}:*) ;; *) export PATH={0}:\"$PATH\" ;; esac", path),
+            (_, false) => format!("case \":$PATH:\" in *:{0}:*) ;; *) export PATH=\"$PATH\":{0} ;; esac", path),
+        };
+        script.push_str(&line);
+        script.push('\n');
+    }
+    
+    for (key, value) in sorted_variables(env_state) {
+        let line = match shell {
+            ShellType::Fish => format!("set -gx {} {}", key, quote(shell, value)),
+            ShellType::PowerShell => format!("$env:{} = {}", key, quote(shell, value)),
+            _ => format!("export {}={}", key, quote(shell, value)),
+        };
+        script.push_str(&line);
+        script.push('\n');
+    }
+    
+    let mut secrets: Vec<_> = env_state.secrets.iter().collect();
+    secrets.sort();
+    for (key, reference) in secrets {
+        script.push_str(&environment::secret_line(shell, key, reference));
+        script.push('\n');
+    }
+    
+    script.push_str(&environment::alias_lines(env_state, shell.name()));
+    script
+}
+
+/// Statements undoing [`apply_lines`] for `env_state`.
+fn undo_lines(shell: &ShellType, env_state: &EnvironmentState) -> String {
+    let mut script = String::new();
+    for path in env_state.paths_prepend.iter().chain(&env_state.paths_append) {
+        let path = quote(shell, path);
+        let line = match shell {
+            ShellType::Fish => format!("set -gx PATH (string match -v -- {} $PATH)", path),
+            ShellType::PowerShell => format!(
+                "$env:PATH = (($env:PATH -split [IO.Path]::PathSeparator) | Where-Object {{ $_ -ne {} }}) -join [IO.Path]::PathSeparator",
+                path
+            ),
+            _ => format!("PATH=\":$PATH:\"; PATH=\"${{PATH//:{}:/:}}\"; PATH=\"${{PATH#:}}\"; export PATH=\"${{PATH%:}}\"", path),
+        };
+        script.push_str(&line);
+        script.push('\n');
+    }
+    
+    let mut keys: Vec<_> = env_state.variables.keys().chain(env_state.secrets.keys()).collect();
+    keys.sort();
+    for key in keys {
+        let line = match shell {
+            ShellType::Fish => format!("set -e {}", key),
+            ShellType::PowerShell => format!("Remove-Item Env:{} -ErrorAction SilentlyContinue", key),
+            _ => format!("unset {}", key),
+        };
+        script.push_str(&line);
+        script.push('\n');
+    }
+    
+    let mut aliases: Vec<_> = env_state.aliases.keys().collect();
+    aliases.sort();
+    for name in aliases {
+        let line = match shell {
+            ShellType::Fish => format!("abbr --erase {0}; functions -e {0}", name),
+            ShellType::PowerShell => format!("Remove-Item Function:{} -ErrorAction SilentlyContinue", name),
+            _ => format!("unalias {} 2>/dev/null", name),
+        };
+        script.push_str(&line);
+        script.push('\n');
+    }
+    script
+}
+
+fn set_applied(shell: &ShellType, profile: &str) -> String {
+    match shell {
+        ShellType::Fish => format!("set -g {} {}\n", APPLIED_VAR, quote(shell, profile)),
+        ShellType::PowerShell => format!("$global:{} = {}\n", APPLIED_VAR, quote(shell, profile)),
+        _ => format!("{}={}\n", APPLIED_VAR, quote(shell, profile)),
+    }
+}
+
+/// What a shell that has `applied` (profile name and environment) needs to
+/// evaluate to get the `active` one instead. Empty when they're the same
+/// profile; the environment of an `applied` profile that no longer exists
+/// can't be undone and is left alone.
+pub fn script(shell: &ShellType, applied: (&str, Option<&EnvironmentState>), active: Option<(&str, &EnvironmentState)>) -> String {
+    let active_name = active.map(|(name, _)| name).unwrap_or_default();
+    if applied.0 == active_name {
+        return String::new();
+    }
+    
+    let mut script = String::new();
+    if let Some(env_state) = applied.1 {
+        script.push_str(&undo_lines(shell, env_state));
+    }
+    if let Some((_, env_state)) = active.filter(|(_, env_state)| env_state.active) {
+        script.push_str(&apply_lines(shell, env_state));
+    }
+    script.push_str(&set_applied(shell, active_name));
+    script
+}
+
+/// Snippet for the shell's rc file that evaluates `zshrcman env` before
+/// each prompt, so profile switches reach running shells.
+pub fn hook_snippet(shell: &ShellType) -> String {
+    let name = shell.name();
+    match shell {
+        ShellType::Fish => format!(
+            "function _zshrcman_env_hook --on-event fish_prompt\n    command zshrcman --shell fish env --applied \"${0}\" | source\nend\n",
+            APPLIED_VAR
+        ),
+        ShellType::PowerShell => format!(
+            "$global:_zshrcman_prompt = $function:prompt\nfunction global:prompt {{\n    (& zshrcman --shell pwsh env --applied \"$global:{0}\") -join \"`n\" | Invoke-Expression\n    & $global:_zshrcman_prompt\n}}\n",
+            APPLIED_VAR
+        ),
+        ShellType::Zsh => format!(
+            "_zshrcman_env_hook() {{\n    eval \"$(command zshrcman --shell {0} env --applied \"${{{1}-}}\")\"\n}}\nif (( ! ${{precmd_functions[(I)_zshrcman_env_hook]}} )); then\n    precmd_functions=(_zshrcman_env_hook $precmd_functions)\nfi\n",
+            name, APPLIED_VAR
+        ),
+        _ => format!(
+            "_zshrcman_env_hook() {{\n    eval \"$(command zshrcman --shell {0} env --applied \"${{{1}-}}\")\"\n}}\nif [[ \";${{PROMPT_COMMAND:-}};\" != *\";_zshrcman_env_hook;\"* ]]; then\n    PROMPT_COMMAND=\"_zshrcman_env_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}\"\nfi\n",
+            name, APPLIED_VAR
+        ),
+    }
+}
+
+/// Prints the script `eval "$(zshrcman env)"` runs: undoing profile
+/// `applied` and applying the active one, or with `hook` the snippet that
+/// runs it at every prompt.
+pub fn print(applied: &str, hook: bool) -> Result<()> {
+    let shell = EnvironmentManager::new().shell_type().clone();
+    if matches!(shell, ShellType::Cmd | ShellType::Nushell) {
+        anyhow::bail!("zshrcman env supports zsh, bash, fish and pwsh; pick one with --shell");
+    }
+    if hook {
+        print!("{}", hook_snippet(&shell));
+        return Ok(());
+    }
+    
+    let config = ConfigManager::new()?.config;
+    let environment = |name: &str| config.profiles.get(name).map(|profile| &profile.environment);
+    let active_name = session::session_profile()
+        .or_else(|| std::env::current_dir().ok().and_then(|dir| auto_switch::profile_for(&config, &dir)))
+        .or(config.active_profile.clone());
+    let active = active_name
+        .as_deref()
+        .and_then(|name| environment(name).map(|env_state| (name, env_state)));
+    print!("{}", script(&shell, (applied, environment(applied)), active));
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::GroupSource;
+use crate::modules::config::ConfigManager;
+use crate::modules::git_mgr::{self, GitManager};
+use crate::modules::output;
+
+/// Checkout of the source `name`, below the data dir so it never ends up in
+/// the dotfiles repo.
+pub fn checkout_dir(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join("sources").join(name)
+}
+
+/// Clones the source if needed and moves it to its pinned ref, or to the
+/// remote's default branch. Returns the checked-out commit id.
+pub fn update(data_dir: &Path, name: &str, source: &GroupSource) -> Result<String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("Invalid source name '{}'; use letters, digits, '-' or '_'", name);
+    }
+    
+    let dir = checkout_dir(data_dir, name);
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    
+    let git = GitManager::init_or_clone(&dir, Some(source.url()))?;
+    git.fetch_and_checkout(source.url(), source.reference())
+}
+
+/// Groups a checked-out source provides, as `<source>/<group>` names.
+pub fn groups(data_dir: &Path, name: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(checkout_dir(data_dir, name).join("groups")) else {
+        return vec![];
+    };
+    
+    let mut groups: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| Some(format!("{}/{}", name, path.file_stem()?.to_str()?)))
+        .collect();
+    groups.sort();
+    groups
+}
+
+/// Updates every configured source. A failing source only warns, so the
+/// others still update.
+pub fn update_all(config_mgr: &ConfigManager) {
+    let data_dir = config_mgr.data_dir();
+    for (name, source) in &config_mgr.config.sources {
+        match update(&data_dir, name, source) {
+            Ok(commit) => output::info(format!("📚 Source '{}' at {}", name, git_mgr::short_id(&commit))),
+            Err(e) => println!("⚠️  Failed to update source '{}': {:#}", name, e),
+        }
+    }
+}
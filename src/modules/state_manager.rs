@@ -1,17 +1,21 @@
-use anyhow::{Context, Result};
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use anyhow::Result;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use crate::models::{
     InstallationRecord, InstallationSource, InstallScope, 
-    Profile, RemovalStrategy, OsType
+    Profile, RemovalStrategy
 };
 use crate::modules::config::ConfigManager;
+use crate::modules::installer::{Installer, InstallerRegistry};
+use crate::modules::prefix::{self, PrefixInstaller};
+use crate::modules::suggest;
 
 pub struct InstallationStateManager {
     pub installations: HashMap<String, InstallationRecord>,
     pub profiles: HashMap<String, Profile>,
     pub active_profile: Option<String>,
     config_mgr: ConfigManager,
+    force_pinned: bool,
+    registry: InstallerRegistry,
 }
 
 impl InstallationStateManager {
@@ -25,11 +29,52 @@ impl InstallationStateManager {
             profiles,
             active_profile,
             config_mgr,
+            force_pinned: false,
+            registry: InstallerRegistry::default(),
         }
     }
     
+    /// Backends that install and uninstall packages. Without them only the
+    /// installation records change.
+    pub fn with_registry(mut self, registry: InstallerRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+    
+    /// Lets removals uninstall pinned packages (`--force-pinned`).
+    pub fn force_pinned(mut self, force: bool) -> Self {
+        self.force_pinned = force;
+        self
+    }
+    
+    pub fn config(&self) -> &ConfigManager {
+        &self.config_mgr
+    }
+    
     pub fn is_installed(&self, package: &str) -> bool {
-        self.installations.contains_key(package)
+        self.installations.get(package).is_some_and(|record| !record.is_pin_only())
+    }
+    
+    /// The record key of a profile-local install, `<profile>/<package>`;
+    /// each profile has its own.
+    pub fn local_key(profile: &str, package: &str) -> String {
+        format!("{}/{}", profile, package)
+    }
+    
+    /// The key of the record `package` refers to: the current profile's
+    /// local install of it if there is one, else the shared one.
+    pub fn resolve(&self, package: &str) -> String {
+        if let Some(profile) = &self.active_profile {
+            let key = Self::local_key(profile, package);
+            if self.installations.contains_key(&key) {
+                return key;
+            }
+        }
+        package.to_string()
+    }
+    
+    pub fn is_pinned(&self, package: &str) -> bool {
+        self.installations.get(package).is_some_and(|record| record.pinned)
     }
     
     pub fn is_active(&self, package: &str) -> bool {
@@ -41,18 +86,78 @@ impl InstallationStateManager {
         false
     }
     
-    pub fn smart_install(&mut self, package: &str, scope: InstallScope) -> Result<()> {
-        if self.is_installed(package) {
+    /// Installs `package` with the backend named `installer`, or only
+    /// records it when that's None. A package that is installed already is
+    /// activated for the current profile instead; one that was there only as
+    /// a dependency is kept from now on even when nothing requires it.
+    /// `InstallScope::Local` installs into the current profile's own prefix.
+    pub fn smart_install(&mut self, package: &str, scope: InstallScope, installer: Option<&str>) -> Result<()> {
+        let key = match scope {
+            InstallScope::Local => Self::local_key(&self.profile_id(), package),
+            _ => package.to_string(),
+        };
+        if let Some(record) = self.installations.get_mut(&key).filter(|record| !record.is_pin_only()) {
+            if matches!(record.installed_by, InstallationSource::Dependency(_)) {
+                let profile_id = self.active_profile.clone().unwrap_or_else(|| "default".to_string());
+                record.installed_by = InstallationSource::Profile(profile_id);
+            }
             println!("📦 {} already installed, activating for current profile", package);
-            self.activate_for_profile(package)?;
+            self.activate_for_profile(&key)?;
         } else {
             println!("📦 Installing {} with scope {:?}", package, scope);
-            self.perform_installation(package, scope)?;
+            self.perform_installation(package, scope, installer)?;
         }
         Ok(())
     }
     
+    /// Installs the packages `package` requires, then `package` itself.
+    /// Those not installed yet are recorded as its dependencies and go away
+    /// with the last package requiring them.
+    pub fn install_with_dependencies(
+        &mut self,
+        package: &str,
+        requires: &[String],
+        scope: InstallScope,
+        installer: Option<&str>,
+    ) -> Result<()> {
+        if scope == InstallScope::Local && !requires.is_empty() {
+            anyhow::bail!("Profile-local packages can't have --requires");
+        }
+        for dependency in requires {
+            if self.is_installed(dependency) {
+                self.activate_for_profile(dependency)?;
+            } else {
+                println!("📦 Installing {} for {}", dependency, package);
+                self.perform_installation(dependency, scope.clone(), installer)?;
+                if let Some(record) = self.installations.get_mut(dependency) {
+                    record.installed_by = InstallationSource::Dependency(package.to_string());
+                }
+            }
+            if let Some(record) = self.installations.get_mut(dependency) {
+                record.required_by.insert(package.to_string());
+            }
+        }
+        self.smart_install(package, scope, installer)?;
+        self.save_state()
+    }
+    
     pub fn handle_removal(&mut self, package: &str, strategy: RemovalStrategy) -> Result<()> {
+        let key = self.resolve(package);
+        let package = key.as_str();
+        // Held packages are never uninstalled or marked for garbage collection,
+        // and pinned ones only when forced.
+        let uninstalls = matches!(
+            strategy,
+            RemovalStrategy::SmartRemove | RemovalStrategy::ForceRemove | RemovalStrategy::MarkUnused
+        );
+        if uninstalls && self.is_pinned(package) && !self.force_pinned {
+            anyhow::bail!("{} is pinned; unpin it with `zshrcman unpin {}` or pass --force-pinned", package, package);
+        }
+        if uninstalls && self.config_mgr.is_held(package) {
+            println!("📌 {} is held, deactivated only", package);
+            return self.deactivate_for_profile(package);
+        }
+        
         match strategy {
             RemovalStrategy::Deactivate => {
                 self.deactivate_for_profile(package)?;
@@ -67,8 +172,13 @@ impl InstallationStateManager {
             
             RemovalStrategy::SmartRemove => {
                 let usage_count = self.get_usage_count(package)?;
+                let required_by = self.installations.get(package).map(|record| record.required_by.clone()).unwrap_or_default();
                 
-                if usage_count <= 1 {
+                if !required_by.is_empty() {
+                    self.deactivate_for_profile(package)?;
+                    let names: Vec<&str> = required_by.iter().map(String::as_str).collect();
+                    println!("ℹ️ {} is still required by {}, deactivated only", package, names.join(", "));
+                } else if usage_count <= 1 {
                     self.perform_uninstallation(package)?;
                 } else {
                     self.deactivate_for_profile(package)?;
@@ -90,7 +200,7 @@ impl InstallationStateManager {
         Ok(())
     }
     
-    fn activate_for_profile(&mut self, package: &str) -> Result<()> {
+    pub fn activate_for_profile(&mut self, package: &str) -> Result<()> {
         if let Some(profile_id) = &self.active_profile {
             if let Some(record) = self.installations.get_mut(package) {
                 record.active_for.insert(profile_id.clone());
@@ -120,12 +230,15 @@ impl InstallationStateManager {
         Ok(())
     }
     
-    fn perform_installation(&mut self, package: &str, scope: InstallScope) -> Result<()> {
-        // This would call the actual installer (brew, npm, etc.)
-        // For now, we'll create a record
-        let profile_id = self.active_profile.clone().unwrap_or_else(|| "default".to_string());
-        
-        let record = InstallationRecord {
+    /// Runs the backend's install, then records the package with the version
+    /// and location the backend reports, active for the current profile.
+    fn perform_installation(&mut self, package: &str, scope: InstallScope, installer: Option<&str>) -> Result<()> {
+        let profile_id = self.profile_id();
+        let key = match scope {
+            InstallScope::Local => Self::local_key(&profile_id, package),
+            _ => package.to_string(),
+        };
+        let mut record = InstallationRecord {
             package: package.to_string(),
             version: None,
             installed_at: chrono::Utc::now(),
@@ -135,28 +248,111 @@ impl InstallationStateManager {
                 set.insert(profile_id.clone());
                 set
             },
-            scope,
+            scope: scope.clone(),
             location: None,
             installer_type: "auto".to_string(),
+            // A pin made before the install carries over.
+            pinned: self.installations.get(&key).is_some_and(|record| record.pinned),
+            required_by: BTreeSet::new(),
+            binaries: Vec::new(),
         };
         
-        self.installations.insert(package.to_string(), record);
+        if let Some(name) = installer {
+            let local;
+            let backend: &dyn Installer = if scope == InstallScope::Local {
+                local = self.local_installer(&profile_id, name)?;
+                &local
+            } else {
+                match self.registry.get(name) {
+                    Some(backend) => backend,
+                    None => {
+                        let names: Vec<String> = self.registry.names().into_iter().map(String::from).collect();
+                        return Err(suggest::not_found("Installer", name, &names));
+                    }
+                }
+            };
+            backend.install(&[package.to_string()])?;
+            fill_details(&mut record, backend)?;
+        }
+        
+        self.installations.insert(key.clone(), record);
         
         if let Some(profile) = self.profiles.get_mut(&profile_id) {
-            profile.packages.insert(package.to_string());
+            profile.packages.insert(key);
         }
         
         self.save_state()?;
         Ok(())
     }
     
+    /// Uninstalls `package` with the backend that installed it, if that one
+    /// is registered, and forgets it. Dependencies nothing else requires any
+    /// more are removed along with it.
     fn perform_uninstallation(&mut self, package: &str) -> Result<()> {
-        // This would call the actual uninstaller
+        if let Some(record) = self.installations.get(package) {
+            let local = match (&record.scope, &record.installed_by) {
+                (InstallScope::Local, InstallationSource::Profile(profile)) => {
+                    let root = prefix::dir(&self.config_mgr.data_dir(), profile, &record.installer_type);
+                    PrefixInstaller::new(&record.installer_type, root)
+                }
+                _ => None,
+            };
+            let backend = match &local {
+                Some(local) => Some(local as &dyn Installer),
+                None => self.registry.get(&record.installer_type),
+            };
+            match backend {
+                Some(backend) => backend.uninstall(std::slice::from_ref(&record.package))?,
+                None if !self.registry.names().is_empty() => {
+                    println!("ℹ️  No backend recorded for {}; removing its record only", package);
+                }
+                None => {}
+            }
+        }
         self.installations.remove(package);
         self.save_state()?;
+        self.remove_orphaned_dependencies(package)
+    }
+    
+    /// Drops `package` from what its dependencies are required by, and
+    /// uninstalls those left required by nothing that were only installed as
+    /// dependencies. Held and pinned ones stay.
+    fn remove_orphaned_dependencies(&mut self, package: &str) -> Result<()> {
+        let mut orphans = Vec::new();
+        for record in self.installations.values_mut() {
+            if record.required_by.remove(package)
+                && record.required_by.is_empty()
+                && matches!(record.installed_by, InstallationSource::Dependency(_))
+            {
+                orphans.push(record.package.clone());
+            }
+        }
+        self.save_state()?;
+        
+        orphans.sort();
+        for orphan in orphans {
+            if self.is_pinned(&orphan) || self.config_mgr.is_held(&orphan) {
+                println!("📌 {} is no longer required but is held or pinned, kept", orphan);
+                continue;
+            }
+            println!("🗑️  Removing {}, no longer required by anything", orphan);
+            self.perform_uninstallation(&orphan)?;
+            self.remove_from_all_profiles(&orphan)?;
+        }
         Ok(())
     }
     
+    fn profile_id(&self) -> String {
+        self.active_profile.clone().unwrap_or_else(|| "default".to_string())
+    }
+    
+    /// `backend` installing into `profile`'s own prefix.
+    fn local_installer(&self, profile: &str, backend: &str) -> Result<PrefixInstaller> {
+        PrefixInstaller::new(backend, prefix::dir(&self.config_mgr.data_dir(), profile, backend)).ok_or_else(|| {
+            anyhow::anyhow!("{} can't install into a profile; local packages use {}", backend, prefix::BACKENDS.join(", "))
+        })
+    }
+    
     fn remove_from_profile_list(&mut self, package: &str) -> Result<()> {
         if let Some(profile_id) = &self.active_profile {
             if let Some(profile) = self.profiles.get_mut(profile_id) {
@@ -212,6 +408,11 @@ impl InstallationStateManager {
             packages: HashSet::new(),
             environment: Default::default(),
             os_overrides: HashMap::new(),
+            enabled_groups: Vec::new(),
+            theme: None,
+            on_activate: Vec::new(),
+            on_deactivate: Vec::new(),
+            identity: None,
         };
         
         self.profiles.insert(name.to_string(), profile);
@@ -221,7 +422,7 @@ impl InstallationStateManager {
     
     pub fn switch_profile(&mut self, name: &str) -> Result<()> {
         if !self.profiles.contains_key(name) {
-            anyhow::bail!("Profile '{}' does not exist", name);
+            return Err(suggest::not_found("Profile", name, self.profiles.keys()));
         }
         
         self.active_profile = Some(name.to_string());
@@ -237,7 +438,85 @@ impl InstallationStateManager {
         }
     }
     
+    /// Pins `package`. One zshrcman has no record of, e.g. a group's, gets a
+    /// pin-only record (see `InstallationRecord::is_pin_only`) that doesn't
+    /// count as installed. Returns false if it was already pinned.
+    ///
+    /// There is no separate upgrade command: packages are only ever upgraded
+    /// by reinstalling their group, which leaves pinned ones out.
+    pub fn pin(&mut self, package: &str) -> Result<bool> {
+        let record = self.installations.entry(package.to_string()).or_insert_with(|| InstallationRecord {
+            package: package.to_string(),
+            version: None,
+            installed_at: chrono::Utc::now(),
+            installed_by: InstallationSource::Manual,
+            active_for: HashSet::new(),
+            scope: InstallScope::Global,
+            location: None,
+            installer_type: String::new(),
+            pinned: false,
+            required_by: BTreeSet::new(),
+            binaries: Vec::new(),
+        });
+        if record.pinned {
+            return Ok(false);
+        }
+        record.pinned = true;
+        self.save_state()?;
+        Ok(true)
+    }
+    
+    /// Returns false if `package` wasn't pinned. A pin-only record goes
+    /// away with its pin.
+    pub fn unpin(&mut self, package: &str) -> Result<bool> {
+        match self.installations.get_mut(package) {
+            Some(record) if record.pinned => {
+                if record.is_pin_only() {
+                    self.installations.remove(package);
+                } else {
+                    record.pinned = false;
+                }
+                self.save_state()?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+    
+    /// The record of `package`, the current profile's local one first.
     pub fn get_package_info(&self, package: &str) -> Option<&InstallationRecord> {
-        self.installations.get(package)
+        self.installations.get(&self.resolve(package))
+    }
+    
+    /// Installed packages by name: those active for `profile` if given, of
+    /// `scope` if given, and only those active for no profile at all when
+    /// `inactive` is set.
+    pub fn list_packages(&self, profile: Option<&str>, scope: Option<&InstallScope>, inactive: bool) -> Result<Vec<&InstallationRecord>> {
+        if let Some(profile) = profile {
+            if !self.profiles.contains_key(profile) {
+                return Err(suggest::not_found("Profile", profile, self.profiles.keys()));
+            }
+        }
+        let mut records: Vec<&InstallationRecord> = self
+            .installations
+            .values()
+            .filter(|record| !record.is_pin_only())
+            .filter(|record| profile.is_none_or(|profile| record.active_for.contains(profile)))
+            .filter(|record| scope.is_none_or(|scope| record.scope == *scope))
+            .filter(|record| !inactive || record.active_for.is_empty())
+            .collect();
+        records.sort_by(|a, b| a.package.cmp(&b.package));
+        Ok(records)
     }
+}
+
+/// Fills in the record's installer, version, location and binaries from the
+/// backend that installed it, so its profile bin links point at the right
+/// place. Every install through a backend goes through here.
+fn fill_details(record: &mut InstallationRecord, installer: &dyn Installer) -> Result<()> {
+    record.installer_type = installer.name().to_string();
+    record.version = installer.version(&record.package)?;
+    record.location = installer.location(&record.package)?;
+    record.binaries = installer.binaries(&record.package)?;
+    Ok(())
 }
\ No newline at end of file
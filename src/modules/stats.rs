@@ -0,0 +1,228 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use crate::models::GroupConfig;
+use crate::modules::config::ConfigManager;
+use crate::modules::git_mgr::GitManager;
+use crate::modules::oplog::{Operation, OperationLog};
+
+/// How far back `stats` looks in git history for group changes.
+const HISTORY_DEPTH: usize = 1000;
+
+/// Outcomes of every `install`, `pkg install`, ... run in `operations`, by
+/// the month it started in. Each group's status only remembers its latest
+/// install, so it can't say how often installs failed over time.
+pub fn install_outcomes(operations: &[Operation]) -> BTreeMap<String, InstallOutcomes> {
+    let mut outcomes: BTreeMap<String, InstallOutcomes> = BTreeMap::new();
+    for operation in operations.iter().filter(|operation| operation.command.split_whitespace().last() == Some("install")) {
+        let month = outcomes.entry(operation.started_at.format("%Y-%m").to_string()).or_default();
+        if operation.success {
+            month.succeeded += 1;
+        } else {
+            month.failed += 1;
+        }
+    }
+    outcomes
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupSummary {
+    pub name: String,
+    /// `global` or the device name for device-specific groups.
+    pub scope: String,
+    pub backend: String,
+    pub packages: usize,
+    pub last_changed: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub packages: usize,
+    pub variables: usize,
+    pub aliases: usize,
+}
+
+/// Install outcomes per month, from the install commands in the operation
+/// log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstallOutcomes {
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl InstallOutcomes {
+    pub fn failure_rate(&self) -> f64 {
+        let total = self.succeeded + self.failed;
+        if total == 0 { 0.0 } else { self.failed as f64 / total as f64 }
+    }
+}
+
+/// A summary of the dotfiles repo and local state for `zshrcman stats`.
+#[derive(Debug, Clone, Default)]
+pub struct RepoStats {
+    pub groups: Vec<GroupSummary>,
+    pub devices: Vec<(String, DateTime<Utc>)>,
+    pub profiles: Vec<ProfileSummary>,
+    pub outcomes: BTreeMap<String, InstallOutcomes>,
+}
+
+impl RepoStats {
+    pub fn collect(config_mgr: &ConfigManager) -> Result<Self> {
+        let dotfiles = config_mgr.dotfiles_path();
+        let device = &config_mgr.config.device.name;
+        let git = GitManager::open(dotfiles).ok();
+        
+        let history = match &git {
+            Some(git) => git.last_changes("", HISTORY_DEPTH).unwrap_or_default(),
+            None => Default::default(),
+        };
+        
+        let mut groups = Vec::new();
+        let device_groups = format!("devices/{}/groups", device);
+        for (dir, scope) in [("groups", "global"), (device_groups.as_str(), device.as_str())] {
+            for (file, group) in read_groups(&dotfiles.join(dir)) {
+                groups.push(GroupSummary {
+                    backend: group.installer.clone().unwrap_or_else(|| group.name.clone()),
+                    name: group.name,
+                    scope: scope.to_string(),
+                    packages: group.packages.len(),
+                    last_changed: history.get(&format!("{}/{}", dir, file)).copied(),
+                });
+            }
+        }
+        
+        let devices = match &git {
+            Some(git) => git.device_branches().unwrap_or_default(),
+            None => vec![],
+        };
+        
+        let mut profiles: Vec<ProfileSummary> = config_mgr
+            .config
+            .profiles
+            .values()
+            .map(|profile| ProfileSummary {
+                name: profile.name.clone(),
+                packages: profile.packages.len(),
+                variables: profile.environment.variables.len(),
+                aliases: profile.environment.aliases.len(),
+            })
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        
+        let operations = OperationLog::new(&config_mgr.data_dir()).since(None).unwrap_or_default();
+        
+        Ok(Self {
+            groups,
+            devices,
+            profiles,
+            outcomes: install_outcomes(&operations),
+        })
+    }
+    
+    /// Package counts per backend, only for groups that list packages.
+    pub fn packages_per_backend(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for group in self.groups.iter().filter(|g| g.packages > 0) {
+            *counts.entry(group.backend.clone()).or_insert(0) += group.packages;
+        }
+        counts
+    }
+    
+    pub fn print(&self) {
+        println!("{}", "📈 zshrcman Stats".bold().cyan());
+        println!();
+        
+        let total_packages: usize = self.groups.iter().map(|g| g.packages).sum();
+        println!("  Groups: {}   Packages: {}", self.groups.len(), total_packages);
+        
+        println!();
+        println!("{}", "  Packages per backend:".bold());
+        let per_backend = self.packages_per_backend();
+        if per_backend.is_empty() {
+            println!("    {}", "No packages".yellow());
+        }
+        for (backend, count) in per_backend {
+            println!("    {:<12} {}", backend, count);
+        }
+        
+        println!();
+        println!("{}", "  Biggest groups:".bold());
+        let mut biggest: Vec<&GroupSummary> = self.groups.iter().filter(|g| g.packages > 0).collect();
+        biggest.sort_by(|a, b| b.packages.cmp(&a.packages).then(a.name.cmp(&b.name)));
+        if biggest.is_empty() {
+            println!("    {}", "No packages".yellow());
+        }
+        for group in biggest.iter().take(5) {
+            println!("    {:<20} {} packages ({})", group.name, group.packages, group.scope);
+        }
+        
+        println!();
+        println!("{}", "  Recently changed groups:".bold());
+        let mut recent: Vec<&GroupSummary> = self.groups.iter().filter(|g| g.last_changed.is_some()).collect();
+        recent.sort_by_key(|g| std::cmp::Reverse(g.last_changed));
+        if recent.is_empty() {
+            println!("    {}", "No committed groups".yellow());
+        }
+        for group in recent.iter().take(5) {
+            if let Some(changed) = group.last_changed {
+                println!("    {:<20} {}", group.name, changed.format("%Y-%m-%d %H:%M"));
+            }
+        }
+        
+        println!();
+        println!("{}", "  Devices (last sync):".bold());
+        if self.devices.is_empty() {
+            println!("    {}", "No device branches".yellow());
+        }
+        for (branch, time) in &self.devices {
+            println!("    {:<20} {}", branch, time.format("%Y-%m-%d %H:%M"));
+        }
+        
+        println!();
+        println!("{}", "  Profiles:".bold());
+        if self.profiles.is_empty() {
+            println!("    {}", "No profiles".yellow());
+        }
+        for profile in &self.profiles {
+            println!(
+                "    {:<20} {} packages, {} variables, {} aliases",
+                profile.name, profile.packages, profile.variables, profile.aliases
+            );
+        }
+        
+        println!();
+        println!("{}", "  Install outcomes by month:".bold());
+        if self.outcomes.is_empty() {
+            println!("    {}", "No installs recorded".yellow());
+        }
+        for (month, outcome) in &self.outcomes {
+            let rate = format!("{:.0}% failed", outcome.failure_rate() * 100.0);
+            let rate = if outcome.failed > 0 { rate.red() } else { rate.green() };
+            println!("    {}  {} ok, {} failed ({})", month, outcome.succeeded, outcome.failed, rate);
+        }
+    }
+}
+
+/// Parses every `*.toml` group in `dir`, keyed by file name. Files that don't
+/// parse are skipped.
+fn read_groups(dir: &Path) -> Vec<(String, GroupConfig)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    
+    let mut groups: Vec<(String, GroupConfig)> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| {
+            let file = path.file_name()?.to_str()?.to_string();
+            let group = toml::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            Some((file, group))
+        })
+        .collect();
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+    groups
+}
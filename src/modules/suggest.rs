@@ -0,0 +1,36 @@
+use strsim::jaro_winkler;
+
+/// How similar two names must be for one to be offered as the other.
+pub const THRESHOLD: f64 = 0.8;
+
+/// Up to three of `candidates` that look like `name`, most similar first.
+/// `name` itself is never suggested.
+pub fn closest<'a, I>(name: &str, candidates: I) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    let mut scored: Vec<(f64, &str)> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.as_str() != name)
+        .map(|candidate| (jaro_winkler(name, candidate), candidate.as_str()))
+        .filter(|(similarity, _)| *similarity > THRESHOLD)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored.into_iter().take(3).map(|(_, candidate)| candidate).collect()
+}
+
+/// "Group 'gti' does not exist. Did you mean 'git'?", or without the
+/// question when nothing comes close.
+pub fn not_found<'a, I>(what: &str, name: &str, candidates: I) -> anyhow::Error
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    let message = format!("{} '{}' does not exist", what, name);
+    let quoted: Vec<String> = closest(name, candidates).iter().map(|candidate| format!("'{}'", candidate)).collect();
+    match quoted.as_slice() {
+        [] => anyhow::anyhow!(message),
+        [only] => anyhow::anyhow!("{}. Did you mean {}?", message, only),
+        _ => anyhow::anyhow!("{}. Did you mean one of {}?", message, quoted.join(", ")),
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::OsType;
+use crate::modules::services::{self, ServiceManager};
+
+/// Name of the systemd units and launchd agent, without extension.
+pub const NAME: &str = "zshrcman-sync";
+const LAUNCHD_LABEL: &str = "com.zshrcman.sync";
+
+fn manager(os: &OsType) -> Result<ServiceManager> {
+    match os {
+        OsType::Linux => Ok(ServiceManager::Systemd),
+        OsType::MacOS => Ok(ServiceManager::Launchd),
+        _ => anyhow::bail!("Scheduled sync needs systemd (Linux) or launchd (macOS)"),
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Runs `zshrcman sync --notify` every `minutes` from a systemd user timer
+/// or a launchd agent.
+#[derive(Debug, Clone)]
+pub struct SyncTimer {
+    pub binary: PathBuf,
+    /// config.toml the scheduled runs use, as they don't see the shell's
+    /// environment.
+    pub config: PathBuf,
+    pub minutes: u64,
+    /// Where launchd writes the runs' output; systemd keeps it in the journal.
+    pub log: PathBuf,
+}
+
+impl SyncTimer {
+    pub fn args(&self) -> Vec<String> {
+        vec![
+            self.binary.display().to_string(),
+            "--config".to_string(),
+            self.config.display().to_string(),
+            "--yes".to_string(),
+            "sync".to_string(),
+            "--notify".to_string(),
+        ]
+    }
+    
+    /// The files to deploy for `manager`, by file name. The last one is the
+    /// one to enable.
+    pub fn files(&self, manager: ServiceManager) -> Vec<(String, String)> {
+        match manager {
+            ServiceManager::Systemd => {
+                let exec = self.args().iter().map(|arg| format!("\"{}\"", arg)).collect::<Vec<_>>().join(" ");
+                vec![
+                    (
+                        format!("{}.service", NAME),
+                        format!(
+                            "[Unit]\nDescription=Sync the zshrcman dotfiles repo\n\n[Service]\nType=oneshot\nExecStart={}\n",
+                            exec
+                        ),
+                    ),
+                    (
+                        format!("{}.timer", NAME),
+                        format!(
+                            "[Unit]\nDescription=Run zshrcman sync every {} minutes\n\n[Timer]\nOnBootSec=5min\nOnUnitActiveSec={}min\n\n[Install]\nWantedBy=timers.target\n",
+                            self.minutes, self.minutes
+                        ),
+                    ),
+                ]
+            }
+            ServiceManager::Launchd => {
+                let log = xml_escape(&self.log.display().to_string());
+                let mut plist = String::from(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                     <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+                     <plist version=\"1.0\">\n<dict>\n",
+                );
+                plist.push_str(&format!("    <key>Label</key>\n    <string>{}</string>\n", LAUNCHD_LABEL));
+                plist.push_str("    <key>ProgramArguments</key>\n    <array>\n");
+                for arg in self.args() {
+                    plist.push_str(&format!("        <string>{}</string>\n", xml_escape(&arg)));
+                }
+                plist.push_str("    </array>\n");
+                plist.push_str(&format!("    <key>StartInterval</key>\n    <integer>{}</integer>\n", self.minutes * 60));
+                for key in ["StandardOutPath", "StandardErrorPath"] {
+                    plist.push_str(&format!("    <key>{}</key>\n    <string>{}</string>\n", key, log));
+                }
+                plist.push_str("</dict>\n</plist>\n");
+                vec![(format!("{}.plist", LAUNCHD_LABEL), plist)]
+            }
+        }
+    }
+    
+    /// Writes the timer into `home` and starts it. Returns the enabled file.
+    pub fn install(&self, home: &Path, os: &OsType) -> Result<PathBuf> {
+        let manager = manager(os)?;
+        let dir = manager.dir(home);
+        fs::create_dir_all(&dir)?;
+        let mut written = vec![];
+        for (name, content) in self.files(manager) {
+            let path = dir.join(name);
+            // launchd only rereads an agent that was unloaded.
+            if manager == ServiceManager::Launchd && path.exists() {
+                services::disable(&path)?;
+            }
+            fs::write(&path, content)?;
+            written.push(path);
+        }
+        let enabled = written.pop().expect("every manager has a timer file");
+        services::reload(manager)?;
+        services::enable(&enabled)?;
+        Ok(enabled)
+    }
+}
+
+/// Stops and deletes an installed timer. Returns whether there was one.
+pub fn remove(home: &Path, os: &OsType) -> Result<bool> {
+    let manager = manager(os)?;
+    let dir = manager.dir(home);
+    let names = match manager {
+        ServiceManager::Systemd => vec![format!("{}.timer", NAME), format!("{}.service", NAME)],
+        ServiceManager::Launchd => vec![format!("{}.plist", LAUNCHD_LABEL)],
+    };
+    let mut removed = false;
+    for path in names.iter().map(|name| dir.join(name)).filter(|path| path.exists()) {
+        services::disable(&path)?;
+        fs::remove_file(&path)?;
+        removed = true;
+    }
+    if removed {
+        services::reload(manager)?;
+    }
+    Ok(removed)
+}
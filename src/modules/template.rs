@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::models::Config;
+use crate::modules::facts::Facts;
+
+/// Values available to templated dotfiles:
+///
+/// - `{{ device.name }}`, `{{ device.branch }}`
+/// - `{{ profile }}`: the active profile, or an empty string
+/// - `{{ os }}`, `{{ arch }}`: as reported by Rust, e.g. `macos`, `aarch64`
+/// - `{{ home }}`: the home directory files are deployed into
+/// - `{{ vars.<name> }}`: entries under `[variables]` in config.toml
+/// - `{{ facts.<name> }}`: see [`Facts`], e.g. `facts.hostname`, `facts.wsl`
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateVars {
+    pub device: DeviceVars,
+    pub profile: String,
+    pub os: String,
+    pub arch: String,
+    pub home: String,
+    pub vars: HashMap<String, toml::Value>,
+    pub facts: Facts,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceVars {
+    pub name: String,
+    pub branch: String,
+}
+
+impl TemplateVars {
+    pub fn from_config(config: &Config, home: &Path, facts: &Facts) -> Self {
+        Self {
+            device: DeviceVars {
+                name: config.device.name.clone(),
+                branch: config.device.branch.clone(),
+            },
+            profile: config.active_profile.clone().unwrap_or_default(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            home: home.display().to_string(),
+            vars: config.variables.clone(),
+            facts: facts.clone(),
+        }
+    }
+    
+    /// Renders `template`; `name` identifies it in error messages.
+    pub fn render(&self, name: &str, template: &str) -> Result<String> {
+        let context = tera::Context::from_serialize(self)?;
+        tera::Tera::one_off(template, &context, false)
+            .map_err(|e| anyhow::anyhow!(template_error(&e)))
+            .with_context(|| format!("Failed to render template {}", name))
+    }
+    
+    pub fn render_file(&self, path: &Path) -> Result<String> {
+        let template = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        self.render(&path.display().to_string(), &template)
+    }
+}
+
+/// Tera nests the useful part ("Variable `x` not found") in the error source.
+fn template_error(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(inner) = source {
+        message = format!("{}: {}", message, inner);
+        source = inner.source();
+    }
+    message
+}
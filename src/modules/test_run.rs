@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::models::Config;
+
+/// Image used when `--image` isn't given.
+pub const DEFAULT_IMAGE: &str = "ubuntu:24.04";
+
+/// Printed by the container between zshrcman's own output and the
+/// config.toml the report is read from.
+pub const REPORT_MARKER: &str = "<<< zshrcman test-run report >>>";
+
+/// Where things are inside the container.
+const DOTFILES: &str = "/dotfiles";
+const BINARY: &str = "/usr/local/bin/zshrcman";
+const CONFIG: &str = "/zshrcman/config.toml";
+
+/// Runs in the container with the device as `$1`, the groups as `$2` and
+/// [`REPORT_MARKER`] as `$3`. The mounted repo belongs to another user,
+/// which git refuses to clone from unless it's marked safe.
+const SCRIPT: &str = r#"printf '[safe]\n\tdirectory = *\n' > "$HOME/.gitconfig"
+zshrcman bootstrap /dotfiles --device "$1" ${2:+--groups "$2"}
+zshrcman install --all
+echo "$3"
+cat "$ZSHRCMAN_CONFIG"
+"#;
+
+/// A bootstrap of the dotfiles repo in a throwaway container.
+#[derive(Debug, Clone)]
+pub struct TestRun {
+    pub image: String,
+    pub dotfiles: PathBuf,
+    /// A Linux zshrcman binary to run inside the container.
+    pub binary: PathBuf,
+    pub device: String,
+    pub groups: Vec<String>,
+    pub shell: String,
+}
+
+/// How a group fared in the container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Installed,
+    Failed(String),
+    /// Enabled, but install never got to it.
+    NotRun,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupReport {
+    pub group: String,
+    pub outcome: Outcome,
+}
+
+impl TestRun {
+    /// The `docker run` arguments: the repo and binary mounted read-only,
+    /// zshrcman's config and data kept below `/zshrcman`.
+    pub fn docker_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = vec!["run".into(), "--rm".into()];
+        for (source, target) in [(&self.dotfiles, DOTFILES), (&self.binary, BINARY)] {
+            args.push("-v".into());
+            args.push(format!("{}:{}:ro", source.display(), target));
+        }
+        for (name, value) in [
+            ("ZSHRCMAN_CONFIG", CONFIG.to_string()),
+            ("ZSHRCMAN_DATA_DIR", "/zshrcman/data".to_string()),
+            ("SHELL", self.shell.clone()),
+        ] {
+            args.push("-e".into());
+            args.push(format!("{}={}", name, value));
+        }
+        args.extend([self.image.clone(), "sh".into(), "-c".into(), SCRIPT.into(), "sh".into()]);
+        args.push(self.device.clone());
+        args.push(self.groups.join(","));
+        args.push(REPORT_MARKER.to_string());
+        args
+    }
+    
+    /// Runs the container, passing its output through until the report, and
+    /// returns how each group did.
+    pub fn run(&self) -> Result<Vec<GroupReport>> {
+        let mut child = Command::new("docker")
+            .args(self.docker_args())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .context("Failed to run docker; is it installed and running?")?;
+        
+        let mut output = String::new();
+        let mut in_report = false;
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if in_report {
+                    output.push_str(&line);
+                    output.push('\n');
+                } else if line == REPORT_MARKER {
+                    in_report = true;
+                } else {
+                    println!("   │ {}", line);
+                }
+            }
+        }
+        child.wait()?;
+        
+        if !in_report {
+            anyhow::bail!("The container stopped before zshrcman could report; see its output above");
+        }
+        parse_report(&output)
+    }
+}
+
+/// Reads the container's config.toml into one report per group.
+pub fn parse_report(config_toml: &str) -> Result<Vec<GroupReport>> {
+    if config_toml.trim().is_empty() {
+        anyhow::bail!("zshrcman didn't get as far as writing its config in the container");
+    }
+    let config: Config = toml::from_str(config_toml).context("Could not read the container's config.toml")?;
+    Ok(report(&config))
+}
+
+/// One report per enabled or attempted group, sorted by name.
+pub fn report(config: &Config) -> Vec<GroupReport> {
+    let mut groups: Vec<&String> = config
+        .groups
+        .enabled_global
+        .iter()
+        .chain(&config.groups.enabled_devices)
+        .chain(config.status.keys())
+        .collect();
+    groups.sort();
+    groups.dedup();
+    
+    groups
+        .into_iter()
+        .map(|group| {
+            let outcome = match config.status.get(group) {
+                Some(status) if status.installed && status.success => Outcome::Installed,
+                Some(status) => Outcome::Failed(status.error.clone().unwrap_or_else(|| "failed".to_string())),
+                None => Outcome::NotRun,
+            };
+            GroupReport {
+                group: group.clone(),
+                outcome,
+            }
+        })
+        .collect()
+}
+
+/// The zshrcman to mount: `binary` if given, else this one, which only runs
+/// in a container when this is Linux.
+pub fn binary(binary: Option<&Path>) -> Result<PathBuf> {
+    match binary {
+        Some(path) => Ok(path.to_path_buf()),
+        None if cfg!(target_os = "linux") => std::env::current_exe().context("Could not locate the zshrcman binary"),
+        None => anyhow::bail!("The container needs a Linux zshrcman; pass one with --binary"),
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+use crate::models::{Config, PromptKind, Theme};
+use crate::modules::config::ConfigManager;
+use crate::modules::environment::{EnvironmentManager, ShellType};
+use crate::modules::git_mgr::GitManager;
+
+pub const P10K_URL: &str = "https://github.com/romkatv/powerlevel10k.git";
+
+/// Checkout of powerlevel10k, below the data dir.
+pub fn p10k_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("themes").join("powerlevel10k")
+}
+
+/// The theme in effect: the first one picked along the active profile's
+/// parent chain, else the top-level `theme`.
+pub fn active_name(config: &Config) -> Option<&str> {
+    let mut seen: Vec<&str> = Vec::new();
+    let mut next = config.active_profile.as_deref();
+    while let Some(profile) = next.and_then(|name| config.profiles.get(name)) {
+        if seen.contains(&profile.name.as_str()) {
+            break;
+        }
+        if let Some(theme) = &profile.theme {
+            return Some(theme);
+        }
+        seen.push(&profile.name);
+        next = profile.parent.as_deref();
+    }
+    config.theme.as_deref()
+}
+
+fn quote(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', "'\\''"))
+}
+
+/// The `.zshrc` lines that set up `theme`'s prompt.
+pub fn prompt_lines(name: &str, theme: &Theme, dotfiles: &Path, data_dir: &Path) -> String {
+    let config = theme.config.as_ref().map(|config| dotfiles.join(config));
+    let mut script = format!("# zshrcman theme '{}'\n\n", name);
+    match theme.prompt {
+        PromptKind::Starship => {
+            if let Some(config) = config {
+                script.push_str(&format!("export STARSHIP_CONFIG={}\n", quote(&config)));
+            }
+            script.push_str("eval \"$(starship init zsh)\"\n");
+        }
+        PromptKind::Powerlevel10k => {
+            script.push_str(&format!("source {}\n", quote(&p10k_dir(data_dir).join("powerlevel10k.zsh-theme"))));
+            if let Some(config) = config {
+                script.push_str(&format!("[[ -r {0} ]] && source {0}\n", quote(&config)));
+            }
+        }
+    }
+    script
+}
+
+/// Sets up the active theme's prompt in `.zshrc` below `home`, cloning
+/// powerlevel10k if it needs it, or removes the theme block when no theme
+/// is active. Returns the theme's name.
+pub fn apply(config_mgr: &ConfigManager, home: &Path) -> Result<Option<String>> {
+    let zsh = EnvironmentManager::for_shell(ShellType::Zsh).with_home(home.to_path_buf());
+    let Some(name) = active_name(&config_mgr.config) else {
+        if zsh.has_theme_block()? {
+            zsh.write_theme("")?;
+        }
+        return Ok(None);
+    };
+    let Some(theme) = config_mgr.config.themes.get(name) else {
+        anyhow::bail!("Theme '{}' is not defined under [themes] in config.toml", name);
+    };
+    
+    let data_dir = config_mgr.data_dir();
+    match theme.prompt {
+        PromptKind::Starship => {
+            if which::which("starship").is_err() {
+                println!(
+                    "{} starship isn't on PATH; add it to a package group, e.g. brew",
+                    "⚠️ ".yellow()
+                );
+            }
+        }
+        PromptKind::Powerlevel10k => {
+            let dir = p10k_dir(&data_dir);
+            if !dir.join("powerlevel10k.zsh-theme").exists() {
+                if let Some(parent) = dir.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                GitManager::init_or_clone(&dir, Some(theme.url.as_deref().unwrap_or(P10K_URL)))?;
+            }
+        }
+    }
+    if let Some(config) = &theme.config {
+        if !config_mgr.dotfiles_path().join(config).exists() {
+            println!(
+                "{} Theme '{}' config {} doesn't exist in the dotfiles repo",
+                "⚠️ ".yellow(),
+                name,
+                config.display()
+            );
+        }
+    }
+    
+    zsh.write_theme(&prompt_lines(name, theme, config_mgr.dotfiles_path(), &data_dir))?;
+    Ok(Some(name.to_string()))
+}
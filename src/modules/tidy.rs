@@ -0,0 +1,278 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::modules::config::{self, ConfigManager};
+use crate::modules::named_dirs;
+use crate::modules::prompt;
+
+/// Prefix of lines `tidy` commented out, so they're easy to find and revert.
+pub const COMMENT_PREFIX: &str = "# [zshrcman tidy] ";
+
+/// Aliases and PATH entries zshrcman already sets up, with where each comes from.
+#[derive(Debug, Default)]
+pub struct Managed {
+    home: String,
+    aliases: BTreeMap<String, String>,
+    paths: BTreeMap<String, String>,
+}
+
+impl Managed {
+    pub fn new(home: &Path) -> Self {
+        Self {
+            home: home.display().to_string(),
+            ..Default::default()
+        }
+    }
+    
+    /// Aliases of the enabled groups and alias groups, and the aliases and
+    /// PATH entries of the active profile.
+    pub fn from_config(config_mgr: &ConfigManager, home: &Path) -> Self {
+        let mut managed = Self::new(home);
+        for group in config_mgr.get_ordered_groups() {
+            if let Ok(group_config) = config_mgr.load_group_config(&group) {
+                let origin = format!("group '{}'", group);
+                for alias in &group_config.aliases {
+                    managed.add_alias(&alias.name, &origin);
+                }
+            }
+        }
+        
+        let config = &config_mgr.config;
+        for (group, aliases) in &config.aliases {
+            let origin = format!("alias group '{}'", group);
+            for alias in &aliases.items {
+                managed.add_alias(&alias.name, &origin);
+            }
+        }
+        
+        let profile = config.active_profile.as_ref().and_then(|name| config.profiles.get(name));
+        if let Some(profile) = profile {
+            let origin = format!("profile '{}'", profile.name);
+            for name in profile.environment.aliases.keys() {
+                managed.add_alias(name, &origin);
+            }
+            for path in profile.environment.paths_prepend.iter().chain(&profile.environment.paths_append) {
+                managed.add_path(path, &origin);
+            }
+        }
+        managed
+    }
+    
+    /// The first origin registered for a name is the one reported.
+    pub fn add_alias(&mut self, name: &str, origin: &str) {
+        self.aliases.entry(name.to_string()).or_insert_with(|| origin.to_string());
+    }
+    
+    pub fn add_path(&mut self, path: &str, origin: &str) {
+        let path = self.normalize(path);
+        self.paths.entry(path).or_insert_with(|| origin.to_string());
+    }
+    
+    /// `~/bin`, `$HOME/bin` and `${HOME}/bin` all compare equal.
+    fn normalize(&self, path: &str) -> String {
+        let path = path.replace("${HOME}", &self.home).replace("$HOME", &self.home);
+        let path = named_dirs::expand_home(&path, &self.home);
+        path.trim_end_matches('/').to_string()
+    }
+}
+
+/// A line of the rc file that repeats something zshrcman manages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Zero-based line number.
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>4}: {}  ({})", self.line + 1, self.text.trim(), self.reason)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Comment,
+    Remove,
+}
+
+/// Lines of `contents` made redundant by `managed`. zshrcman's own managed
+/// blocks and `source` lines are left alone, as are comments.
+pub fn analyze(contents: &str, managed: &Managed) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut in_block = false;
+    let mut in_scripts = false;
+    
+    for (line_no, line) in contents.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.contains(">>> zshrcman ") {
+            in_block = true;
+            continue;
+        }
+        if trimmed.contains("<<< zshrcman ") {
+            in_block = false;
+            continue;
+        }
+        // Sourced fragments follow this header up to the next blank line.
+        if trimmed == "# zshrcman managed scripts" {
+            in_scripts = true;
+            continue;
+        }
+        if trimmed.is_empty() {
+            in_scripts = false;
+        }
+        if in_block || in_scripts || trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        
+        let reason = if let Some(name) = alias_name(trimmed) {
+            managed
+                .aliases
+                .get(name)
+                .map(|origin| format!("alias '{}' is managed by {}", name, origin))
+        } else if let Some(entries) = path_entries(trimmed) {
+            let origins: Option<Vec<&String>> = entries
+                .iter()
+                .map(|entry| managed.paths.get(&managed.normalize(entry)))
+                .collect();
+            match origins {
+                Some(origins) if !origins.is_empty() => Some(format!(
+                    "{} already on PATH from {}",
+                    entries.join(", "),
+                    origins[0]
+                )),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        
+        if let Some(reason) = reason {
+            findings.push(Finding {
+                line: line_no,
+                text: line.to_string(),
+                reason,
+            });
+        }
+    }
+    findings
+}
+
+/// `contents` with each chosen line commented out or removed.
+pub fn apply(contents: &str, chosen: &[&Finding], action: Action) -> String {
+    let mut lines = Vec::new();
+    for (line_no, line) in contents.lines().enumerate() {
+        if !chosen.iter().any(|finding| finding.line == line_no) {
+            lines.push(line.to_string());
+        } else if action == Action::Comment {
+            lines.push(format!("{}{}", COMMENT_PREFIX, line));
+        }
+    }
+    
+    let mut updated = lines.join("\n");
+    if contents.ends_with('\n') && !updated.is_empty() {
+        updated.push('\n');
+    }
+    updated
+}
+
+/// Copies `path` to `<path>.zshrcman-<timestamp>` and returns the copy.
+pub fn backup(path: &Path) -> Result<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".zshrcman-{}", stamp));
+    let backup = path.with_file_name(name);
+    fs::copy(path, &backup).with_context(|| format!("Failed to back up {}", path.display()))?;
+    Ok(backup)
+}
+
+/// Writes `contents` to `path` after backing it up.
+pub fn write_with_backup(path: &Path, contents: &str) -> Result<PathBuf> {
+    let backup = backup(path)?;
+    config::write_atomic(path, contents.as_bytes())?;
+    Ok(backup)
+}
+
+/// The name in `alias name=value`, skipping options like zsh's `-g`.
+pub fn alias_name(line: &str) -> Option<&str> {
+    let mut rest = line.trim().strip_prefix("alias ")?.trim_start();
+    while rest.starts_with('-') {
+        rest = rest.split_once(char::is_whitespace)?.1.trim_start();
+    }
+    let (name, _) = rest.split_once('=')?;
+    (!name.is_empty()).then_some(name)
+}
+
+/// The directories a line adds to PATH, for `export PATH="dir:$PATH"` and
+/// zsh's `path=(dir $path)` forms.
+fn path_entries(line: &str) -> Option<Vec<String>> {
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    if let Some(value) = line.strip_prefix("PATH=") {
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        return Some(
+            value
+                .split(':')
+                .filter(|entry| !entry.is_empty() && *entry != "$PATH" && *entry != "${PATH}")
+                .map(String::from)
+                .collect(),
+        );
+    }
+    
+    let value = line.strip_prefix("path=(").or_else(|| line.strip_prefix("path+=("))?;
+    let value = value.strip_suffix(')')?;
+    Some(
+        value
+            .split_whitespace()
+            .map(|entry| entry.trim_matches(|c| c == '"' || c == '\''))
+            .filter(|entry| *entry != "$path" && *entry != "${path[@]}")
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Lists the lines of `path` that duplicate what zshrcman manages and, unless
+/// `dry_run`, comments out or removes the ones picked (removing without asking
+/// with `remove`), keeping a backup.
+pub fn run(config_mgr: &ConfigManager, home: &Path, path: &Path, dry_run: bool, remove: bool) -> Result<()> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    
+    let managed = Managed::from_config(config_mgr, home);
+    let findings = analyze(&contents, &managed);
+    if findings.is_empty() {
+        println!("{}", format!("✨ Nothing in {} duplicates what zshrcman manages", path.display()).green());
+        return Ok(());
+    }
+    
+    println!("{}", format!("🧹 {} redundant line(s) in {}:", findings.len(), path.display()).bold());
+    for finding in &findings {
+        println!("  {}", finding);
+    }
+    if dry_run {
+        return Ok(());
+    }
+    
+    let selected = prompt::multi_select("Lines to tidy", &findings, &vec![true; findings.len()])?;
+    if selected.is_empty() {
+        println!("Nothing selected; {} left as it was", path.display());
+        return Ok(());
+    }
+    let action = if remove {
+        Action::Remove
+    } else {
+        match prompt::select("What to do with them", &["Comment out", "Remove"], 0)? {
+            0 => Action::Comment,
+            _ => Action::Remove,
+        }
+    };
+    
+    let chosen: Vec<&Finding> = selected.iter().map(|&idx| &findings[idx]).collect();
+    let backup = write_with_backup(path, &apply(&contents, &chosen, action))?;
+    let verb = if action == Action::Remove { "Removed" } else { "Commented out" };
+    println!("{} {} line(s) in {}", format!("✅ {}", verb).green(), chosen.len(), path.display());
+    println!("   Backup saved to {}", backup.display());
+    Ok(())
+}
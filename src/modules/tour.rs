@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::{Alias, AliasGroup, GroupConfig};
+use crate::modules::alias::AliasManager;
+use crate::modules::config::ConfigManager;
+use crate::modules::install::InstallManager;
+use crate::modules::prompt;
+use crate::modules::state_manager::InstallationStateManager;
+
+/// Walks a new user through the group → alias → profile → install model using a
+/// throwaway sandbox, so nothing in the real config, dotfiles repo or HOME changes.
+pub struct TourManager {
+    sandbox: PathBuf,
+}
+
+impl TourManager {
+    pub fn new() -> Result<Self> {
+        let sandbox = std::env::temp_dir().join(format!("zshrcman-tour-{}", std::process::id()));
+        fs::create_dir_all(sandbox.join("home"))?;
+        fs::create_dir_all(sandbox.join("config"))?;
+        fs::create_dir_all(sandbox.join("dotfiles").join("groups"))?;
+        
+        Ok(Self { sandbox })
+    }
+    
+    pub fn run(&self) -> Result<()> {
+        println!("{}", "🎓 Welcome to the zshrcman tour!".bold().cyan());
+        println!("   Everything in this tour happens inside a sandbox:");
+        println!("   {}", self.sandbox.display());
+        println!("   Your real config, dotfiles and HOME are never touched.");
+        println!();
+        
+        let group = self.step_create_group()?;
+        self.step_add_aliases()?;
+        let profile = self.step_create_profile()?;
+        self.step_dry_run_install(&group, &profile)?;
+        
+        println!();
+        println!("{}", "🎉 Tour complete!".bold().green());
+        println!("   Next steps on your real machine:");
+        println!("     zshrcman init              # connect your dotfiles repo");
+        println!("     zshrcman group add <name>  # create your own groups");
+        println!("     zshrcman install           # deploy enabled groups");
+        println!();
+        
+        let keep = prompt::confirm("Keep the sandbox around to explore it?", false)?;
+        
+        if keep {
+            println!("   Sandbox kept at {}", self.sandbox.display());
+        } else {
+            fs::remove_dir_all(&self.sandbox)
+                .with_context(|| format!("Failed to remove sandbox {:?}", self.sandbox))?;
+        }
+        
+        Ok(())
+    }
+    
+    fn config_manager(&self) -> Result<ConfigManager> {
+        ConfigManager::with_paths(
+            self.sandbox.join("config").join("config.toml"),
+            self.sandbox.join("dotfiles"),
+        )
+    }
+    
+    fn home(&self) -> PathBuf {
+        self.sandbox.join("home")
+    }
+    
+    fn step_create_group(&self) -> Result<String> {
+        Self::heading(1, "Groups");
+        println!("   A group bundles packages, aliases, scripts and files that belong together.");
+        println!("   Global groups live in groups/<name>.toml in your dotfiles repo and are");
+        println!("   enabled or disabled per machine. A group named after a backend");
+        println!("   (brew, npm, pnpm) has its packages installed by that backend.");
+        println!();
+        
+        let name = prompt::input("Name your first group", Some("brew"), "")?;
+        let packages = prompt::input(
+            "Packages it should contain (space separated)",
+            Some("ripgrep fd"),
+            "",
+        )?;
+        
+        let group_config = GroupConfig {
+            name: name.clone(),
+            description: "Created during the zshrcman tour".to_string(),
+            packages: packages.split_whitespace().map(String::from).collect(),
+            aliases: vec![],
+            scripts: vec![],
+            files: vec![],
+            ..Default::default()
+        };
+        
+        let groups_dir = self.sandbox.join("dotfiles").join("groups");
+        fs::write(
+            groups_dir.join(format!("{}.toml", name)),
+            toml::to_string_pretty(&group_config)?,
+        )?;
+        
+        let mut config_mgr = self.config_manager()?;
+        config_mgr.add_global_group(name.clone())?;
+        config_mgr.enable_global_group(&name)?;
+        
+        println!("   {} groups/{}.toml", "✅ Wrote".green(), name);
+        Self::show_file(&groups_dir.join(format!("{}.toml", name)))?;
+        
+        Ok(name)
+    }
+    
+    fn step_add_aliases(&self) -> Result<()> {
+        Self::heading(2, "Aliases");
+        println!("   Aliases are stored per group and can be toggled active/inactive.");
+        println!("   The built-in `aliases` group deploys active aliases to ~/.zsh_aliases.");
+        println!();
+        
+        let groups_dir = self.sandbox.join("dotfiles").join("groups");
+        let aliases_config = GroupConfig {
+            name: "aliases".to_string(),
+            description: "Shell aliases".to_string(),
+            packages: vec![],
+            aliases: vec![],
+            scripts: vec![],
+            files: vec![],
+            ..Default::default()
+        };
+        fs::write(groups_dir.join("aliases.toml"), toml::to_string_pretty(&aliases_config)?)?;
+        
+        let mut config_mgr = self.config_manager()?;
+        config_mgr.add_global_group("aliases".to_string())?;
+        config_mgr.enable_global_group("aliases")?;
+        
+        let mut alias_mgr = AliasManager::new(config_mgr);
+        let mut added = 0;
+        loop {
+            let default = if added == 0 { r#"alias ll="ls -la""# } else { "" };
+            let alias_def = prompt::input("Add an alias (leave empty to continue)", Some(default), "")?;
+            
+            // Non-interactive runs would otherwise keep re-adding the default
+            if alias_def.trim().is_empty() || (added > 0 && prompt::is_non_interactive()) {
+                break;
+            }
+            
+            match Alias::parse(&alias_def) {
+                Some(alias) => alias_mgr.add("aliases", alias, false)?,
+                None => {
+                    println!("   {} Not an alias definition, try ll='ls -la'", "⚠️ ".yellow());
+                    continue;
+                }
+            }
+            added += 1;
+        }
+        
+        // Mark everything active so the install step has something to deploy
+        let mut config_mgr = self.config_manager()?;
+        if let Some(AliasGroup { items, active }) = config_mgr.config.aliases.get_mut("aliases") {
+            *active = items.clone();
+        }
+        config_mgr.save()?;
+        
+        println!("   {} {} alias(es) added and activated", "✅".green(), added);
+        Ok(())
+    }
+    
+    fn step_create_profile(&self) -> Result<String> {
+        Self::heading(3, "Profiles");
+        println!("   Profiles (e.g. work, personal) track which installed packages are active");
+        println!("   and carry their own environment. Switching is fast: nothing is reinstalled.");
+        println!();
+        
+        let name = prompt::input("Name a profile", Some("work"), "")?;
+        
+        let mut state_mgr = InstallationStateManager::new(self.config_manager()?);
+        state_mgr.create_profile(&name, None)?;
+        state_mgr.switch_profile(&name)?;
+        
+        println!("   {} Created and switched to profile '{}'", "✅".green(), name);
+        Ok(name)
+    }
+    
+    fn step_dry_run_install(&self, group: &str, profile: &str) -> Result<()> {
+        Self::heading(4, "Install (dry run)");
+        println!("   `zshrcman install` walks the enabled groups in order and hands each one");
+        println!("   to its installer. Package commands are only printed here, and files are");
+        println!("   written to the sandbox HOME instead of yours.");
+        println!();
+        
+        let proceed = prompt::confirm("Run the dry-run install now?", true)?;
+        
+        if !proceed {
+            return Ok(());
+        }
+        
+        let mut install_mgr = InstallManager::new(self.config_manager()?)
+            .with_home(self.home())
+            .dry_run(true);
+        install_mgr.install(true)?;
+        
+        let aliases_file = self.home().join(".zsh_aliases");
+        if aliases_file.exists() {
+            println!();
+            println!("   {} ~/.zsh_aliases in the sandbox:", "📄".normal());
+            Self::show_file(&aliases_file)?;
+        }
+        
+        let config_mgr = self.config_manager()?;
+        println!();
+        println!("   Sandbox state after the run:");
+        println!("     enabled groups: {}", config_mgr.config.groups.enabled_global.join(", "));
+        println!("     active profile: {}", profile);
+        for (name, status) in &config_mgr.config.status {
+            let icon = if status.success { "✅" } else { "❌" };
+            println!("     {} {}", icon, name);
+        }
+        println!("   Your group '{}' is now tracked just like on a real machine.", group);
+        
+        Ok(())
+    }
+    
+    fn heading(step: usize, title: &str) {
+        println!();
+        println!("{}", format!("Step {}/4 · {}", step, title).bold());
+    }
+    
+    fn show_file(path: &Path) -> Result<()> {
+        let contents = fs::read_to_string(path)?;
+        for line in contents.lines() {
+            println!("   │ {}", line.dimmed());
+        }
+        Ok(())
+    }
+}
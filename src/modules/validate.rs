@@ -0,0 +1,446 @@
+use colored::Colorize;
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::{Config, GroupConfig};
+use crate::modules::cron;
+use crate::modules::fonts;
+use crate::modules::gpg;
+use crate::modules::hooks::Event;
+use crate::modules::installer::InstallerRegistry;
+use crate::modules::named_dirs;
+use crate::modules::repos;
+use crate::modules::services::ServiceManager;
+use crate::modules::sources;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One finding of `zshrcman config validate`: the file, the TOML path inside
+/// it (empty for file-level problems) and what is wrong.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if !self.path.is_empty() {
+            write!(f, ": {}", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// `path` the way `serde_path_to_error` writes type errors' paths, e.g.
+/// `fonts[0].name`, so the same key reads the same in every problem.
+fn toml_path(path: &serde_ignored::Path) -> String {
+    match path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, index } => format!("{}[{}]", toml_path(parent), index),
+        serde_ignored::Path::Map { parent, key } => match toml_path(parent) {
+            parent if parent.is_empty() => key.clone(),
+            parent => format!("{}.{}", parent, key),
+        },
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => toml_path(parent),
+    }
+}
+
+/// Checks config.toml and every group file without loading them through
+/// ConfigManager, so a config that doesn't parse can still be diagnosed.
+pub struct Validator {
+    config_path: PathBuf,
+    dotfiles_path: PathBuf,
+    group_root: PathBuf,
+    problems: Vec<Problem>,
+}
+
+impl Validator {
+    pub fn new(config_path: &Path, dotfiles_path: &Path) -> Self {
+        Self {
+            config_path: config_path.to_path_buf(),
+            dotfiles_path: dotfiles_path.to_path_buf(),
+            group_root: dotfiles_path.to_path_buf(),
+            problems: vec![],
+        }
+    }
+    
+    /// Checks the files, scripts and keys a group refers to in `root`, the
+    /// checkout of the repository it lives in, instead of the dotfiles repo.
+    pub fn in_repo(mut self, root: &Path) -> Self {
+        self.group_root = root.to_path_buf();
+        self
+    }
+    
+    /// Checks config.toml and every group file.
+    pub fn run(mut self) -> Vec<Problem> {
+        self.validate_config();
+        let installers = self.installer_names();
+        for file in self.group_files() {
+            self.validate_group(&file, &installers);
+        }
+        self.problems
+    }
+    
+    /// Checks only config.toml, e.g. an edited copy before it replaces the
+    /// real one.
+    pub fn config_file(mut self) -> Vec<Problem> {
+        self.validate_config();
+        self.problems
+    }
+    
+    /// Checks only the group file at `file`. Its name is taken from the file
+    /// name, so an edited copy must keep it.
+    pub fn group_file(mut self, file: &Path) -> Vec<Problem> {
+        let installers = self.installer_names();
+        self.validate_group(file, &installers);
+        self.problems
+    }
+    
+    fn validate_config(&mut self) {
+        if !self.config_path.exists() {
+            return;
+        }
+        let file = self.config_path.clone();
+        match self.parse::<Config>(&file) {
+            Some(_) if !self.dotfiles_path.exists() => {
+                let message = format!("dotfiles repo {} not found; group references were not checked", self.dotfiles_path.display());
+                self.problem(Severity::Warning, &file, "", message);
+            }
+            Some(config) => self.check_config(&config),
+            None => {}
+        }
+    }
+    
+    fn validate_group(&mut self, file: &Path, installers: &HashSet<String>) {
+        if let Some(group) = self.parse::<GroupConfig>(file) {
+            self.check_group(file, &group, installers);
+        }
+    }
+    
+    fn problem(&mut self, severity: Severity, file: &Path, path: impl Into<String>, message: impl Into<String>) {
+        self.problems.push(Problem {
+            severity,
+            file: file.to_path_buf(),
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+    
+    /// Parses `file` as `T`, reporting type errors with their TOML path and
+    /// line, and keys `T` doesn't know about.
+    fn parse<T: DeserializeOwned>(&mut self, file: &Path) -> Option<T> {
+        let contents = match fs::read_to_string(file) {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.problem(Severity::Error, file, "", format!("cannot read: {}", e));
+                return None;
+            }
+        };
+        
+        let parsed: Result<T, _> = serde_path_to_error::deserialize(toml::Deserializer::new(&contents));
+        if let Err(e) = parsed {
+            let line = e
+                .inner()
+                .span()
+                .map(|span| format!(" (line {})", contents[..span.start].matches('\n').count() + 1))
+                .unwrap_or_default();
+            let path = match e.path().to_string() {
+                root if root == "." => String::new(),
+                path => path,
+            };
+            self.problem(Severity::Error, file, path, format!("{}{}", e.inner().message().trim(), line));
+            return None;
+        }
+        
+        let mut unknown = vec![];
+        let value = serde_ignored::deserialize(toml::Deserializer::new(&contents), |path| {
+            unknown.push(toml_path(&path));
+        });
+        for path in unknown {
+            self.problem(Severity::Warning, file, path, "unknown key; it is ignored");
+        }
+        value.ok()
+    }
+    
+    /// `groups/*.toml` and `devices/*/groups/*.toml` in the dotfiles repo.
+    fn group_files(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![self.dotfiles_path.join("groups")];
+        if let Ok(devices) = fs::read_dir(self.dotfiles_path.join("devices")) {
+            dirs.extend(devices.filter_map(|entry| entry.ok()).map(|entry| entry.path().join("groups")));
+        }
+        
+        let mut files: Vec<PathBuf> = dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        files.sort();
+        files
+    }
+    
+    fn installer_names(&self) -> HashSet<String> {
+        let mut registry = InstallerRegistry::builtin(false, &Default::default());
+        if let Some(data_dir) = self.dotfiles_path.parent() {
+            registry.register_plugins(&[data_dir.join("plugins")], false);
+        }
+        registry.names().into_iter().map(String::from).collect()
+    }
+    
+    fn check_group(&mut self, file: &Path, group: &GroupConfig, installers: &HashSet<String>) {
+        let stem = file.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        if group.name != stem {
+            self.problem(
+                Severity::Warning,
+                file,
+                "name",
+                format!("'{}' differs from the file name; the group is referenced as '{}'", group.name, stem),
+            );
+        }
+        
+        for (i, mapping) in group.files.iter().enumerate() {
+            if !self.group_root.join(&mapping.source).exists() {
+                self.problem(
+                    Severity::Error,
+                    file,
+                    format!("files[{}].source", i),
+                    format!("{} does not exist", mapping.source.display()),
+                );
+            }
+        }
+        for (i, script) in group.scripts.iter().enumerate() {
+            if !self.group_root.join("scripts").join(script).exists() {
+                self.problem(Severity::Error, file, format!("scripts[{}]", i), format!("scripts/{} does not exist", script));
+            }
+        }
+        for (i, key) in group.ssh_keys.iter().enumerate() {
+            if !self.group_root.join("ssh").join(key).exists() {
+                self.problem(Severity::Error, file, format!("ssh_keys[{}]", i), format!("ssh/{} does not exist", key));
+            }
+        }
+        for (i, key) in group.gpg_keys.iter().enumerate() {
+            if !self.group_root.join(gpg::KEYS_DIR).join(key).exists() {
+                self.problem(Severity::Error, file, format!("gpg_keys[{}]", i), format!("{}/{} does not exist", gpg::KEYS_DIR, key));
+            }
+        }
+        for (i, job) in group.cron.iter().enumerate() {
+            if !cron::is_valid_schedule(&job.schedule) {
+                self.problem(
+                    Severity::Error,
+                    file,
+                    format!("cron[{}].schedule", i),
+                    format!("'{}' is not five cron fields or an @ shortcut like @daily", job.schedule),
+                );
+            }
+        }
+        for (i, service) in group.services.iter().enumerate() {
+            if !self.group_root.join(&service.source).exists() {
+                self.problem(Severity::Error, file, format!("services[{}].source", i), format!("{} does not exist", service.source.display()));
+            } else if ServiceManager::for_file(&service.source).is_none() {
+                self.problem(
+                    Severity::Error,
+                    file,
+                    format!("services[{}].source", i),
+                    "not a systemd unit (.service, .timer, .socket, .path) or launchd agent (.plist)",
+                );
+            }
+        }
+        for (i, font) in group.fonts.iter().enumerate() {
+            if !fonts::is_valid_name(&font.name) {
+                self.problem(Severity::Error, file, format!("fonts[{}].name", i), "can't contain '/', '\\' or be '..'");
+            }
+            match (&font.source, &font.url) {
+                (Some(source), None) if !self.group_root.join(source).exists() => {
+                    self.problem(Severity::Error, file, format!("fonts[{}].source", i), format!("{} does not exist", source.display()));
+                }
+                (Some(_), None) | (None, Some(_)) => {}
+                _ => self.problem(Severity::Error, file, format!("fonts[{}]", i), "needs exactly one of `source` or `url`"),
+            }
+        }
+        if group.installer.as_deref().unwrap_or(stem) == "mas" {
+            for (i, id) in group.package_ids().iter().enumerate() {
+                if id.parse::<u64>().is_err() {
+                    self.problem(
+                        Severity::Error,
+                        file,
+                        format!("packages[{}]", i),
+                        format!("'{}' is not an App Store id or a name in [apps]", id),
+                    );
+                }
+            }
+        }
+        for name in group.named_dirs.keys().filter(|name| !named_dirs::is_valid_name(name)) {
+            self.problem(
+                Severity::Error,
+                file,
+                format!("named_dirs.{}", name),
+                "not a valid name; use letters, digits and '_', not starting with a digit",
+            );
+        }
+        for name in group.named_dirs.keys().filter(|name| named_dirs::is_reserved(name)) {
+            self.problem(
+                Severity::Error,
+                file,
+                format!("named_dirs.{}", name),
+                "reserved shell variable; pick another name",
+            );
+        }
+        for name in group.env.keys().filter(|name| !named_dirs::is_valid_name(name)) {
+            self.problem(
+                Severity::Error,
+                file,
+                format!("env.{}", name),
+                "not a valid variable name; use letters, digits and '_', not starting with a digit",
+            );
+        }
+        for (i, _) in group.target_tags.iter().enumerate().filter(|(_, tag)| tag.trim().is_empty()) {
+            self.problem(Severity::Error, file, format!("target_tags[{}]", i), "empty tag");
+        }
+        if let Some(installer) = &group.installer {
+            if !installers.contains(installer) {
+                self.problem(Severity::Error, file, "installer", format!("unknown installer '{}'", installer));
+            }
+        }
+    }
+    
+    fn check_config(&mut self, config: &Config) {
+        let file = self.config_path.clone();
+        let groups = &config.groups;
+        
+        for (key, names) in [("groups.global", &groups.global), ("groups.enabled_global", &groups.enabled_global)] {
+            for (i, name) in names.iter().enumerate() {
+                if let Some(message) = self.missing_group(config, name, None) {
+                    self.problem(Severity::Error, &file, format!("{}[{}]", key, i), message);
+                }
+            }
+        }
+        for (i, name) in groups.enabled_global.iter().enumerate() {
+            if !groups.global.contains(name) {
+                self.problem(
+                    Severity::Warning,
+                    &file,
+                    format!("groups.enabled_global[{}]", i),
+                    format!("'{}' is enabled but not listed in groups.global", name),
+                );
+            }
+        }
+        
+        let device = (!config.device.name.is_empty()).then_some(config.device.name.as_str());
+        for (key, names) in [("groups.per_device", &groups.per_device), ("groups.enabled_devices", &groups.enabled_devices)] {
+            for (i, name) in names.iter().enumerate() {
+                if let Some(message) = device.and_then(|device| self.missing_group(config, name, Some(device))) {
+                    self.problem(Severity::Error, &file, format!("{}[{}]", key, i), message);
+                }
+            }
+        }
+        
+        for (i, hook) in config.hooks.iter().enumerate() {
+            if hook.command.is_some() == hook.url.is_some() {
+                self.problem(Severity::Error, &file, format!("hooks[{}]", i), "set exactly one of `command` or `url`");
+            }
+            for (j, event) in hook.events.iter().enumerate() {
+                if !Event::ALL.iter().any(|known| known.as_str() == event) {
+                    self.problem(
+                        Severity::Warning,
+                        &file,
+                        format!("hooks[{}].events[{}]", i, j),
+                        format!("unknown event '{}'; it never fires", event),
+                    );
+                }
+            }
+        }
+        
+        for name in config.repositories.keys().filter(|name| config.sources.contains_key(*name)) {
+            self.problem(
+                Severity::Warning,
+                &file,
+                format!("repositories.{}", name),
+                format!("'{}' is also a source; '{}/<group>' resolves to the source", name, name),
+            );
+        }
+        
+        if let Some(active) = &config.active_profile {
+            if !config.profiles.contains_key(active) {
+                self.problem(Severity::Error, &file, "active_profile", format!("profile '{}' does not exist", active));
+            }
+        }
+        if let Some(theme) = config.theme.as_ref().filter(|theme| !config.themes.contains_key(*theme)) {
+            self.problem(Severity::Error, &file, "theme", format!("theme '{}' is not defined under [themes]", theme));
+        }
+        for (name, profile) in &config.profiles {
+            // Either a global group or one of this device's will do.
+            for (i, group) in profile.enabled_groups.iter().enumerate() {
+                let missing = self.missing_group(config, group, None).filter(|_| {
+                    device.is_none_or(|device| self.missing_group(config, group, Some(device)).is_some())
+                });
+                if let Some(message) = missing {
+                    self.problem(Severity::Error, &file, format!("profiles.{}.enabled_groups[{}]", name, i), message);
+                }
+            }
+            if let Some(theme) = profile.theme.as_ref().filter(|theme| !config.themes.contains_key(*theme)) {
+                self.problem(
+                    Severity::Error,
+                    &file,
+                    format!("profiles.{}.theme", name),
+                    format!("theme '{}' is not defined under [themes]", theme),
+                );
+            }
+            if let Some(parent) = profile.parent.as_ref().filter(|parent| !config.profiles.contains_key(*parent)) {
+                self.problem(
+                    Severity::Error,
+                    &file,
+                    format!("profiles.{}.parent", name),
+                    format!("profile '{}' does not exist", parent),
+                );
+            }
+        }
+    }
+    
+    /// Why `name` can't be resolved to a group file, if it can't.
+    fn missing_group(&self, config: &Config, name: &str, device: Option<&str>) -> Option<String> {
+        if let Some((namespace, group)) = name.split_once('/') {
+            let data_dir = self.dotfiles_path.parent().unwrap_or(&self.dotfiles_path);
+            let (kind, checkout) = if config.sources.contains_key(namespace) {
+                ("source", sources::checkout_dir(data_dir, namespace))
+            } else if config.repositories.contains_key(namespace) {
+                ("repository", repos::checkout_dir(data_dir, namespace))
+            } else {
+                return Some(format!("'{}' refers to unknown source or repository '{}'", name, namespace));
+            };
+            if checkout.exists() && !checkout.join("groups").join(format!("{}.toml", group)).exists() {
+                return Some(format!("{} '{}' has no group '{}'", kind, namespace, group));
+            }
+            return None;
+        }
+        
+        let dir = match device {
+            Some(device) => self.dotfiles_path.join("devices").join(device).join("groups"),
+            None => self.dotfiles_path.join("groups"),
+        };
+        let file = dir.join(format!("{}.toml", name));
+        (!file.exists()).then(|| format!("dangling reference: {} does not exist", file.display()))
+    }
+}
+
+/// Prints one line per problem, marked as error or warning.
+pub fn print(problems: &[Problem]) {
+    for problem in problems {
+        match problem.severity {
+            Severity::Error => println!("❌ {}", problem.to_string().red()),
+            Severity::Warning => println!("⚠️  {}", problem.to_string().yellow()),
+        }
+    }
+}
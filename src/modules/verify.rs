@@ -0,0 +1,150 @@
+use colored::Colorize;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::modules::bin_links;
+
+/// How something deployed differs from what the config declares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    /// A deployed file or managed block was edited, by hand or by another
+    /// tool.
+    Modified,
+    /// A deployed file, managed block or plugin checkout is gone.
+    Missing,
+    /// A package of an installed group was uninstalled outside zshrcman.
+    NotInstalled,
+    /// A plugin checkout isn't at the commit `plugins.lock` pins.
+    WrongCommit,
+    /// A profile bin link points somewhere else, or at nothing.
+    BrokenLink,
+    /// The group's config changed since it was installed.
+    OutOfDate,
+    /// The group is installed but no longer enabled.
+    Extraneous,
+}
+
+impl DriftKind {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            DriftKind::Modified => "✏️ ",
+            DriftKind::Missing => "🕳️ ",
+            DriftKind::NotInstalled => "📦",
+            DriftKind::WrongCommit => "🔀",
+            DriftKind::BrokenLink => "🔗",
+            DriftKind::OutOfDate => "🕰️ ",
+            DriftKind::Extraneous => "🗑️ ",
+        }
+    }
+}
+
+/// What drifted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Subject {
+    Group { name: String },
+    File { path: PathBuf },
+    Block { label: String },
+    Package { name: String },
+    Plugin { name: String },
+    /// A profile bin link and the package location it should point at.
+    Link { path: PathBuf, location: PathBuf },
+}
+
+impl fmt::Display for Subject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Subject::Group { name } => write!(f, "group {}", name),
+            Subject::File { path } | Subject::Link { path, .. } => write!(f, "{}", path.display()),
+            Subject::Block { label } => write!(f, "{} block", label),
+            Subject::Package { name } => write!(f, "{}", name),
+            Subject::Plugin { name } => write!(f, "plugin {}", name),
+        }
+    }
+}
+
+/// One difference found by `zshrcman verify`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Drift {
+    pub kind: DriftKind,
+    /// The group or profile the subject belongs to, or `startup files` for
+    /// the managed blocks and plugins all groups share.
+    pub owner: String,
+    pub subject: Subject,
+    pub detail: String,
+}
+
+impl Drift {
+    pub fn new(kind: DriftKind, owner: &str, subject: Subject, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            owner: owner.to_string(),
+            subject,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Owner of the managed blocks in the shell startup files.
+pub const STARTUP_FILES: &str = "startup files";
+
+/// Compares the file `target` deployed by `owner` with what it should hold.
+pub fn check_file(owner: &str, target: &Path, expected: &[u8]) -> Option<Drift> {
+    let item = Subject::File { path: target.to_path_buf() };
+    match fs::read(target) {
+        Err(_) => Some(Drift::new(DriftKind::Missing, owner, item, "deployed file is gone")),
+        Ok(actual) if actual != expected => Some(Drift::new(DriftKind::Modified, owner, item, "differs from the repo")),
+        Ok(_) => None,
+    }
+}
+
+/// Compares the managed block `label` with what install would write into
+/// it. An empty `expected` means there should be no block.
+pub fn check_block(label: &str, expected: &str, actual: Option<&str>) -> Option<Drift> {
+    let item = Subject::Block { label: label.to_string() };
+    match actual {
+        None if expected.is_empty() => None,
+        None => Some(Drift::new(DriftKind::Missing, STARTUP_FILES, item, "managed block was removed")),
+        Some(actual) if actual.trim_end() != expected.trim_end() => {
+            Some(Drift::new(DriftKind::Modified, STARTUP_FILES, item, "edited inside the zshrcman markers"))
+        }
+        Some(_) => None,
+    }
+}
+
+/// Checks that the profile bin `link` points at `location`.
+pub fn check_link(owner: &str, link: &Path, location: &Path) -> Option<Drift> {
+    let item = Subject::Link {
+        path: link.to_path_buf(),
+        location: location.to_path_buf(),
+    };
+    match bin_links::target(link) {
+        None => Some(Drift::new(DriftKind::BrokenLink, owner, item, format!("missing; should point at {}", location.display()))),
+        Some(target) if !bin_links::points_at(link, location) => Some(Drift::new(
+            DriftKind::BrokenLink,
+            owner,
+            item,
+            format!("points at {} instead of {}", target.display(), location.display()),
+        )),
+        Some(_) if !location.exists() => Some(Drift::new(DriftKind::BrokenLink, owner, item, format!("{} no longer exists", location.display()))),
+        Some(_) => None,
+    }
+}
+
+/// Prints `drift` grouped by owner, in the order owners first appear.
+pub fn print(drift: &[Drift]) {
+    let mut owners: Vec<&str> = vec![];
+    for item in drift {
+        if !owners.contains(&item.owner.as_str()) {
+            owners.push(&item.owner);
+        }
+    }
+    for owner in owners {
+        println!("{}", owner.bold());
+        for item in drift.iter().filter(|item| item.owner == owner) {
+            println!("  {} {}: {}", item.kind.symbol(), item.subject, item.detail.yellow());
+        }
+    }
+}
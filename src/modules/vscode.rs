@@ -0,0 +1,23 @@
+use std::path::{Path, PathBuf};
+use crate::models::OsType;
+
+/// Start of `files` targets in VS Code's user settings directory, which
+/// differs per OS, e.g. `$VSCODE_USER/settings.json`.
+pub const USER_DIR_VAR: &str = "$VSCODE_USER";
+
+/// VS Code's user settings directory on `os`.
+pub fn user_dir(home: &Path, os: &OsType) -> PathBuf {
+    let config_dir = match os {
+        OsType::MacOS => home.join("Library").join("Application Support"),
+        OsType::Windows => home.join("AppData").join("Roaming"),
+        _ => home.join(".config"),
+    };
+    config_dir.join("Code").join("User")
+}
+
+/// Where a `files` target below [`USER_DIR_VAR`] goes on `os`; `None` for
+/// other targets.
+pub fn resolve_target(target: &Path, home: &Path, os: &OsType) -> Option<PathBuf> {
+    let relative = target.strip_prefix(USER_DIR_VAR).ok()?;
+    Some(user_dir(home, os).join(relative))
+}
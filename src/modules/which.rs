@@ -0,0 +1,132 @@
+use anyhow::Result;
+use crate::modules::config::ConfigManager;
+use crate::modules::installer::InstallerRegistry;
+use crate::modules::search::{self, Kind};
+
+/// A group that declares the package, alias or script `zshrcman which` looked
+/// up, and its state on this machine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub kind: Kind,
+    /// The package, alias definition or script as written.
+    pub text: String,
+    pub group: String,
+    /// Set for per-device groups under `devices/<name>/groups`.
+    pub device: Option<String>,
+    /// `groups/k8s.toml:7`, or the alias group in config.toml.
+    pub location: String,
+    /// What installs it: the package backend, `aliases` (`~/.zsh_aliases`) or
+    /// `zshrc` (sourced from `.zshrc`). `None` when no backend is registered.
+    pub backend: Option<String>,
+    /// The group is enabled here, globally or by the active profile; another
+    /// device's group never is.
+    pub enabled: bool,
+    /// Packages: the backend reports it installed. Aliases and scripts: the
+    /// group's last install succeeded.
+    pub installed: bool,
+    /// In effect for the current profile: enabled and installed, an alias
+    /// toggled active, a package not deactivated for the profile.
+    pub active: bool,
+}
+
+/// Every group declaring a package, alias or script named exactly `name`,
+/// plus alias groups in config.toml that no group file accounts for.
+pub fn lookup(config_mgr: &ConfigManager, registry: &InstallerRegistry, name: &str) -> Result<Vec<Declaration>> {
+    let config = &config_mgr.config;
+    let enabled_groups = config_mgr.get_ordered_groups();
+    let mut declarations: Vec<Declaration> = search::search(config_mgr.dotfiles_path(), name)?
+        .into_iter()
+        .filter(|found| found.score >= 1.0 && found.kind != Kind::Group)
+        .map(|found| {
+            let enabled = match &found.device {
+                Some(device) => *device == config.device.name && config.groups.enabled_devices.contains(&found.group),
+                None => enabled_groups.contains(&found.group),
+            };
+            let group_installed = config.status.get(&found.group).is_some_and(|status| status.installed && status.success);
+            let (backend, installed, active) = match found.kind {
+                Kind::Package => {
+                    let backend = package_backend(config_mgr, registry, &found.group, found.device.as_deref());
+                    let installed = backend
+                        .and_then(|backend| registry.get(backend))
+                        .is_some_and(|installer| installer.is_installed(&found.text).unwrap_or(false));
+                    (backend.map(String::from), installed, enabled && installed && active_for_profile(config_mgr, &found.text))
+                }
+                Kind::Alias => {
+                    let toggled = config
+                        .aliases
+                        .get(&found.group)
+                        .is_some_and(|aliases| aliases.active.iter().any(|alias| alias.to_string() == found.text));
+                    (Some("aliases".to_string()), group_installed, enabled && group_installed && toggled)
+                }
+                _ => (Some("zshrc".to_string()), group_installed, enabled && group_installed),
+            };
+            
+            Declaration {
+                kind: found.kind,
+                location: found.location(),
+                text: found.text,
+                group: found.group,
+                device: found.device,
+                backend,
+                enabled,
+                installed,
+                active,
+            }
+        })
+        .collect();
+    
+    let mut alias_groups: Vec<_> = config.aliases.iter().collect();
+    alias_groups.sort_by(|a, b| a.0.cmp(b.0));
+    for (group, aliases) in alias_groups {
+        for alias in &aliases.items {
+            let text = alias.to_string();
+            let declared = declarations.iter().any(|d| d.kind == Kind::Alias && d.group == *group && d.text == text);
+            if declared || alias.name != name {
+                continue;
+            }
+            let installed = config.status.get(group).is_some_and(|status| status.installed && status.success);
+            let enabled = enabled_groups.contains(group);
+            declarations.push(Declaration {
+                kind: Kind::Alias,
+                text,
+                group: group.clone(),
+                device: None,
+                location: format!("config.toml [aliases.{}]", group),
+                backend: Some("aliases".to_string()),
+                enabled,
+                installed,
+                active: enabled && installed && aliases.active.contains(alias),
+            });
+        }
+    }
+    Ok(declarations)
+}
+
+/// The installer named by the group's `installer` field, or the one named
+/// like the group; `None` if it isn't registered.
+fn package_backend<'a>(
+    config_mgr: &ConfigManager,
+    registry: &'a InstallerRegistry,
+    group: &str,
+    device: Option<&str>,
+) -> Option<&'a str> {
+    let group_config = match device {
+        Some(device) => config_mgr.load_device_group_config(device, group),
+        None => config_mgr.load_group_config(group),
+    };
+    let name = match group_config {
+        Ok(group_config) if group_config.installer.is_some() => group_config.installer?,
+        _ => config_mgr.resolve_group(group).1.to_string(),
+    };
+    registry.get(&name).map(|installer| installer.name())
+}
+
+/// False only when the package's installation record exists and leaves out
+/// the active profile, i.e. it was deactivated there.
+fn active_for_profile(config_mgr: &ConfigManager, package: &str) -> bool {
+    let config = &config_mgr.config;
+    match (&config.active_profile, config.installations.get(package)) {
+        (Some(profile), Some(record)) => record.active_for.contains(profile),
+        _ => true,
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::models::EnvironmentState;
+use crate::modules::config;
+
+/// Registry key holding the user's environment, which Explorer hands to
+/// every program it starts.
+pub const KEY: &str = r"HKCU\Environment";
+
+/// A value under [`KEY`]. `kind` is `REG_SZ`, or `REG_EXPAND_SZ` for values
+/// with `%VAR%` references expanded on read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegValue {
+    pub kind: String,
+    pub data: String,
+}
+
+impl RegValue {
+    /// `data` as an expandable string when it refers to other variables.
+    pub fn new(data: String) -> Self {
+        let kind = if data.contains('%') { "REG_EXPAND_SZ" } else { "REG_SZ" };
+        Self { kind: kind.to_string(), data }
+    }
+}
+
+/// What zshrcman changed in the user environment, so deactivating the
+/// profile can undo it: each variable's value before the first change
+/// (`None` if it wasn't set), and the entries it put into the user `Path`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoRecord {
+    pub previous: BTreeMap<String, Option<RegValue>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_entries: Vec<String>,
+}
+
+impl UndoRecord {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("windows_env.json")
+    }
+    
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).with_context(|| format!("Corrupt {}", path.display()))
+    }
+    
+    pub fn save(&self, path: &Path) -> Result<()> {
+        config::write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+    
+    /// Notes `name`'s value before the first change; later changes keep it.
+    pub fn remember(&mut self, name: &str, current: Option<RegValue>) -> Option<&RegValue> {
+        self.previous.entry(name.to_string()).or_insert(current).as_ref()
+    }
+}
+
+/// The value in `reg query <key> /v <name>` output, whose row looks like
+/// `    Path    REG_EXPAND_SZ    %USERPROFILE%\bin`.
+pub fn parse_query(output: &str, name: &str) -> Option<RegValue> {
+    output.lines().find_map(|line| {
+        let mut fields = line.trim_start().splitn(3, "    ");
+        let (found, kind) = (fields.next()?, fields.next()?);
+        (found.eq_ignore_ascii_case(name) && kind.starts_with("REG_")).then(|| RegValue {
+            kind: kind.to_string(),
+            data: fields.next().unwrap_or_default().to_string(),
+        })
+    })
+}
+
+/// The user `Path` with `env_state`'s entries around `current`, after taking
+/// out the ones zshrcman `added` before, and the entries that are now
+/// zshrcman's. Whatever else is in `current`, such as entries an installer
+/// added meanwhile, stays; entries the user already has aren't moved or
+/// claimed.
+pub fn user_path(
+    current: Option<&str>,
+    added: &[String],
+    env_state: &EnvironmentState,
+    expand: impl Fn(&str) -> String,
+) -> (String, Vec<String>) {
+    let contains = |entries: &[String], entry: &str| entries.iter().any(|other| other.eq_ignore_ascii_case(entry));
+    let own: Vec<String> = current
+        .unwrap_or_default()
+        .split(';')
+        .filter(|entry| !entry.is_empty() && !contains(added, entry))
+        .map(String::from)
+        .collect();
+    
+    let mut front: Vec<String> = Vec::new();
+    for entry in env_state.paths_prepend.iter().rev().map(|path| expand(path)) {
+        if !contains(&own, &entry) && !contains(&front, &entry) {
+            front.push(entry);
+        }
+    }
+    let mut back: Vec<String> = Vec::new();
+    for entry in env_state.paths_append.iter().map(|path| expand(path)) {
+        if !contains(&own, &entry) && !contains(&front, &entry) && !contains(&back, &entry) {
+            back.push(entry);
+        }
+    }
+    
+    let path = front.iter().chain(&own).chain(&back).map(String::as_str).collect::<Vec<_>>().join(";");
+    (path, front.into_iter().chain(back).collect())
+}
+
+fn reg(args: &[&str]) -> Result<std::process::Output> {
+    Command::new("reg").args(args).output().context("Failed to run reg")
+}
+
+/// `name`'s current value under [`KEY`], `None` if it isn't set.
+pub fn read(name: &str) -> Result<Option<RegValue>> {
+    let output = reg(&["query", KEY, "/v", name])?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(parse_query(&String::from_utf8_lossy(&output.stdout), name))
+}
+
+/// Sets `name` to `value`, or deletes it when `value` is `None`.
+pub fn write(name: &str, value: Option<&RegValue>) -> Result<()> {
+    let output = match value {
+        Some(value) => reg(&["add", KEY, "/v", name, "/t", &value.kind, "/d", &value.data, "/f"])?,
+        None => reg(&["delete", KEY, "/v", name, "/f"])?,
+    };
+    if !output.status.success() && value.is_some() {
+        anyhow::bail!("Failed to set {} in {}: {}", name, KEY, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// PowerShell sending `WM_SETTINGCHANGE` with "Environment" to all
+/// top-level windows; `setx` does the same after writing.
+const BROADCAST: &str = r#"Add-Type -Namespace Zshrcman -Name Native -MemberDefinition '[DllImport("user32.dll", CharSet = CharSet.Unicode)] public static extern IntPtr SendMessageTimeout(IntPtr hWnd, uint msg, UIntPtr wParam, string lParam, uint flags, uint timeout, out UIntPtr result);'
+$result = [UIntPtr]::Zero
+[void][Zshrcman.Native]::SendMessageTimeout([IntPtr]0xffff, 0x1a, [UIntPtr]::Zero, 'Environment', 2, 5000, [ref]$result)"#;
+
+/// Tells running programs, Explorer above all, that the environment
+/// changed, so terminals opened from now on see it. Already open ones keep
+/// what they had.
+pub fn broadcast() {
+    let _ = Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-Command", BROADCAST]).status();
+}
+
+/// Writes `env_state`'s variables and PATH entries into the user
+/// environment, recording in `record` what they replace. Values get the same
+/// `~` expansion as PATH entries, as the registry knows no `~`.
+pub fn persist(record: &Path, env_state: &EnvironmentState, expand: impl Fn(&str) -> String) -> Result<()> {
+    let mut undo = UndoRecord::load(record)?;
+    let mut changes: Vec<(String, RegValue)> = env_state
+        .variables
+        .iter()
+        .map(|(name, value)| (name.clone(), RegValue::new(expand(value))))
+        .collect();
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    
+    let has_paths = !env_state.paths_prepend.is_empty() || !env_state.paths_append.is_empty();
+    if has_paths || !undo.path_entries.is_empty() {
+        let current = read("Path")?;
+        let (data, added) = user_path(current.as_ref().map(|value| value.data.as_str()), &undo.path_entries, env_state, &expand);
+        let kind = current.map(|value| value.kind).unwrap_or_else(|| RegValue::new(data.clone()).kind);
+        undo.path_entries = added;
+        changes.push(("Path".to_string(), RegValue { kind, data }));
+    }
+    
+    for (name, value) in &changes {
+        if name != "Path" {
+            undo.remember(name, read(name)?);
+        }
+        undo.save(record)?;
+        write(name, Some(value))?;
+    }
+    if !changes.is_empty() {
+        broadcast();
+    }
+    Ok(())
+}
+
+/// Puts back the variables [`persist`] replaced, takes its entries out of
+/// the user `Path` as it is now, and forgets the record.
+pub fn restore(record: &Path) -> Result<()> {
+    let undo = UndoRecord::load(record)?;
+    for (name, previous) in &undo.previous {
+        write(name, previous.as_ref())?;
+    }
+    if !undo.path_entries.is_empty() {
+        if let Some(current) = read("Path")? {
+            let (data, _) = user_path(Some(&current.data), &undo.path_entries, &EnvironmentState::default(), str::to_string);
+            write("Path", Some(&RegValue { kind: current.kind, data }))?;
+        }
+    }
+    if record.exists() {
+        fs::remove_file(record)?;
+    }
+    if !undo.previous.is_empty() || !undo.path_entries.is_empty() {
+        broadcast();
+    }
+    Ok(())
+}
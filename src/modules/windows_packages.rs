@@ -0,0 +1,104 @@
+use anyhow::Result;
+use crate::models::WindowsInstallSettings;
+use crate::modules::installer::{CommandInstaller, Installer};
+
+/// The `windows` backend: installs each package with the package manager
+/// `[install.windows]` picks for it among winget, scoop and choco.
+pub struct WindowsInstaller {
+    managers: Vec<CommandInstaller>,
+    available: Vec<String>,
+    settings: WindowsInstallSettings,
+}
+
+impl WindowsInstaller {
+    /// Picks among `managers`, treating those whose program is on PATH as
+    /// available.
+    pub fn new(settings: &WindowsInstallSettings, managers: Vec<CommandInstaller>) -> Self {
+        let available = managers
+            .iter()
+            .filter(|manager| which::which(manager.program()).is_ok())
+            .map(|manager| manager.name().to_string())
+            .collect();
+        Self {
+            managers,
+            available,
+            settings: settings.clone(),
+        }
+    }
+    
+    /// Treats exactly `names` as available instead.
+    pub fn with_available(mut self, names: &[&str]) -> Self {
+        self.available = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+    
+    /// The package managers found on this machine.
+    pub fn available(&self) -> &[String] {
+        &self.available
+    }
+    
+    /// The manager for `package`: the one `packages` names for it, else the
+    /// first available one in `prefer` order.
+    pub fn manager_for(&self, package: &str) -> Result<&CommandInstaller> {
+        let name = match self.settings.packages.get(package) {
+            Some(name) => {
+                if !self.available.contains(name) {
+                    anyhow::bail!("{} is set to install with {}, which isn't installed", package, name);
+                }
+                name
+            }
+            None => match self.settings.prefer.iter().find(|name| self.available.contains(name)) {
+                Some(name) => name,
+                None => anyhow::bail!(
+                    "No Windows package manager found for {}; install one of {}",
+                    package,
+                    self.settings.prefer.join(", ")
+                ),
+            },
+        };
+        match self.managers.iter().find(|manager| manager.name() == name) {
+            Some(manager) => Ok(manager),
+            None => anyhow::bail!("Unknown Windows package manager '{}'; use winget, scoop or choco", name),
+        }
+    }
+    
+    /// `packages` grouped by their manager, in first-use order.
+    fn by_manager(&self, packages: &[String]) -> Result<Vec<(&CommandInstaller, Vec<String>)>> {
+        let mut groups: Vec<(&CommandInstaller, Vec<String>)> = Vec::new();
+        for package in packages {
+            let manager = self.manager_for(package)?;
+            match groups.iter_mut().find(|(existing, _)| existing.name() == manager.name()) {
+                Some((_, packages)) => packages.push(package.clone()),
+                None => groups.push((manager, vec![package.clone()])),
+            }
+        }
+        Ok(groups)
+    }
+}
+
+impl Installer for WindowsInstaller {
+    fn name(&self) -> &str {
+        "windows"
+    }
+    
+    fn install(&self, packages: &[String]) -> Result<()> {
+        for (manager, packages) in self.by_manager(packages)? {
+            manager.install(&packages)?;
+        }
+        Ok(())
+    }
+    
+    fn uninstall(&self, packages: &[String]) -> Result<()> {
+        for (manager, packages) in self.by_manager(packages)? {
+            manager.uninstall(&packages)?;
+        }
+        Ok(())
+    }
+    
+    fn version(&self, package: &str) -> Result<Option<String>> {
+        match self.manager_for(package) {
+            Ok(manager) => manager.version(package),
+            Err(_) => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// File below home that the managed blocks of `.zshrc` move to when
+/// `[zsh] compile` is on; zsh loads its `.zwc` when that's newer.
+pub const BUNDLE_FILE: &str = ".zsh/zshrcman.zsh";
+
+const BEGIN: &str = "# >>> zshrcman ";
+const END: &str = "# <<< zshrcman ";
+
+pub fn bundle_path(home: &Path) -> PathBuf {
+    home.join(BUNDLE_FILE)
+}
+
+/// The lines `.zshrc` sources the bundle with.
+pub fn source_lines() -> String {
+    format!("[[ -r \"$HOME/{0}\" ]] && source \"$HOME/{0}\"\n", BUNDLE_FILE)
+}
+
+/// Compiles `path` to `path.zwc`. Does nothing without zsh on `PATH`.
+pub fn compile(path: &Path) -> Result<()> {
+    if which::which("zsh").is_err() {
+        return Ok(());
+    }
+    let status = Command::new("zsh")
+        .args(["-fc", "zcompile -- \"$1\"", "zsh"])
+        .arg(path)
+        .status()
+        .context("Failed to run zsh")?;
+    if !status.success() {
+        anyhow::bail!("zcompile {} failed", path.display());
+    }
+    Ok(())
+}
+
+/// Splits `rc` into the rc without its managed blocks, with `replacement`
+/// where the first one was, and the blocks themselves (markers included).
+/// `None` when there are no blocks to move.
+pub fn bundle(rc: &str, replacement: &str) -> Option<(String, String)> {
+    let mut kept = String::new();
+    let mut blocks = String::new();
+    let mut in_block = false;
+    let mut replaced = false;
+    for line in rc.lines() {
+        if line.starts_with(BEGIN) {
+            in_block = true;
+            if !replaced {
+                kept.push_str(replacement);
+                replaced = true;
+            }
+        }
+        if in_block {
+            blocks.push_str(line);
+            blocks.push('\n');
+        } else {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+        if line.starts_with(END) {
+            in_block = false;
+        }
+    }
+    replaced.then_some((kept, blocks))
+}
+
+/// Puts `blocks` back into `rc` in place of the block that began with
+/// `begin` and ended with `end`.
+pub fn unbundle(rc: &str, begin: &str, end: &str, blocks: &str) -> String {
+    let mut restored = String::new();
+    let mut in_block = false;
+    for line in rc.lines() {
+        if line == begin {
+            in_block = true;
+            restored.push_str(blocks);
+        } else if line == end {
+            in_block = false;
+        } else if !in_block {
+            restored.push_str(line);
+            restored.push('\n');
+        }
+    }
+    restored
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use crate::models::GroupConfig;
+
+/// The `setopt`, `unsetopt` and `bindkey` entries of several groups, with
+/// the contradictions found along the way.
+#[derive(Debug, Default)]
+pub struct ZshOptions {
+    setopt: Vec<String>,
+    unsetopt: Vec<String>,
+    /// Key to (widget, where it was bound).
+    bindkey: BTreeMap<String, (String, String)>,
+    pub problems: Vec<String>,
+}
+
+/// Zsh ignores case and underscores in option names, so `AUTO_CD`,
+/// `autocd` and `auto_cd` are the same option.
+pub fn normalize(option: &str) -> String {
+    option.chars().filter(|c| *c != '_').collect::<String>().to_lowercase()
+}
+
+fn is_valid_option(option: &str) -> bool {
+    !option.is_empty() && option.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl ZshOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Adds the group's options and key bindings; `origin` names it, e.g.
+    /// `group 'vi-mode'`. An option turned on and off by different groups,
+    /// or a key bound to different widgets, keeps its first setting.
+    pub fn add(&mut self, origin: &str, group: &GroupConfig) {
+        for (options, others, verb) in [
+            (&group.setopt, &group.unsetopt, "setopt"),
+            (&group.unsetopt, &group.setopt, "unsetopt"),
+        ] {
+            for option in options {
+                if !is_valid_option(option) {
+                    self.problems.push(format!("option '{}' in {} is not a valid name; skipping it", option, origin));
+                    continue;
+                }
+                let name = normalize(option);
+                if others.iter().any(|other| normalize(other) == name) {
+                    self.problems.push(format!("option '{}' is both set and unset in {}; skipping it", option, origin));
+                    continue;
+                }
+                
+                let (same, opposite) = if verb == "setopt" {
+                    (&mut self.setopt, &self.unsetopt)
+                } else {
+                    (&mut self.unsetopt, &self.setopt)
+                };
+                if opposite.contains(&name) {
+                    self.problems.push(format!("option '{}' ({} in {}) contradicts an earlier group; keeping the first", option, verb, origin));
+                } else if !same.contains(&name) {
+                    same.push(name);
+                }
+            }
+        }
+        
+        for (key, widget) in &group.bindkey {
+            match self.bindkey.get(key) {
+                Some((existing, _)) if existing == widget => {}
+                Some((existing, first)) => self.problems.push(format!(
+                    "key '{}' is bound to {} in {} but {} in {}; keeping {}",
+                    key, existing, first, widget, origin, existing
+                )),
+                None => {
+                    self.bindkey.insert(key.clone(), (widget.clone(), origin.to_string()));
+                }
+            }
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.setopt.is_empty() && self.unsetopt.is_empty() && self.bindkey.is_empty()
+    }
+    
+    /// The `.zshrc` block: `setopt`, `unsetopt`, then one `bindkey` per key.
+    /// Empty when there's nothing to set.
+    pub fn render(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        
+        let mut script = String::from("# zshrcman options and key bindings\n\n");
+        if !self.setopt.is_empty() {
+            script.push_str(&format!("setopt {}\n", self.setopt.join(" ")));
+        }
+        if !self.unsetopt.is_empty() {
+            script.push_str(&format!("unsetopt {}\n", self.unsetopt.join(" ")));
+        }
+        for (key, (widget, _)) in &self.bindkey {
+            script.push_str(&format!("bindkey '{}' {}\n", key.replace('\'', "'\\''"), widget));
+        }
+        script
+    }
+}
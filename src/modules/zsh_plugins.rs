@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::models::{InstallerType, ZshPlugin};
+use crate::modules::config::ConfigManager;
+use crate::modules::git_mgr::GitManager;
+
+/// Commits the plugins are pinned to, in the dotfiles repo so every machine
+/// gets the same ones.
+pub const LOCK_FILE: &str = "plugins.lock";
+
+/// Checkout of the plugin `name`, below the data dir. (`plugins/` there holds
+/// installer plugins.)
+pub fn checkout_dir(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join("zsh-plugins").join(name)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPlugin {
+    pub url: String,
+    pub commit: String,
+}
+
+/// `plugins.lock`: plugin name to the URL and commit it was installed at.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Lockfile {
+    pub plugins: BTreeMap<String, LockedPlugin>,
+}
+
+impl Lockfile {
+    pub fn path(dotfiles: &Path) -> PathBuf {
+        dotfiles.join(LOCK_FILE)
+    }
+    
+    pub fn load(dotfiles: &Path) -> Result<Self> {
+        let path = Self::path(dotfiles);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+    
+    pub fn save(&self, dotfiles: &Path) -> Result<()> {
+        let path = Self::path(dotfiles);
+        if self.plugins.is_empty() {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        fs::write(path, format!("# Generated by zshrcman; pins zsh plugins to commits.\n{}", toml::to_string_pretty(self)?))?;
+        Ok(())
+    }
+}
+
+/// The plugins of the enabled `plugins` groups with the group declaring
+/// each, in install order.
+pub fn declared(config_mgr: &ConfigManager) -> Vec<(String, ZshPlugin)> {
+    config_mgr
+        .get_ordered_groups()
+        .into_iter()
+        .filter(|group| matches!(InstallerType::from_group_name(config_mgr.resolve_group(group).1), InstallerType::Plugins))
+        .filter_map(|group| {
+            let (config, _) = config_mgr.load_merged_group(&group).ok()?;
+            Some(config.plugins.into_iter().map(move |plugin| (group.clone(), plugin)))
+        })
+        .flatten()
+        .collect()
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("Invalid plugin name '{}'; use letters, digits, '-' or '_'", name);
+    }
+    Ok(())
+}
+
+/// Clones the plugin if needed and checks out its locked commit. Unlocked
+/// plugins, and ones whose URL changed, move to their `ref` (or the default
+/// branch) and get locked there. Returns the checked-out commit.
+pub fn sync(data_dir: &Path, lock: &mut Lockfile, plugin: &ZshPlugin) -> Result<String> {
+    let locked = lock
+        .plugins
+        .get(&plugin.name)
+        .filter(|locked| locked.url == plugin.url)
+        .map(|locked| locked.commit.clone());
+    match locked {
+        Some(commit) => checkout(data_dir, plugin, Some(&commit)),
+        None => update(data_dir, lock, plugin),
+    }
+}
+
+/// Moves the plugin to the newest commit of its `ref` or default branch and
+/// locks it there.
+pub fn update(data_dir: &Path, lock: &mut Lockfile, plugin: &ZshPlugin) -> Result<String> {
+    let commit = checkout(data_dir, plugin, plugin.reference.as_deref())?;
+    lock.plugins.insert(
+        plugin.name.clone(),
+        LockedPlugin {
+            url: plugin.url.clone(),
+            commit: commit.clone(),
+        },
+    );
+    Ok(commit)
+}
+
+fn checkout(data_dir: &Path, plugin: &ZshPlugin, reference: Option<&str>) -> Result<String> {
+    validate_name(&plugin.name)?;
+    let dir = checkout_dir(data_dir, &plugin.name);
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    
+    let git = GitManager::init_or_clone(&dir, Some(&plugin.url))?;
+    git.fetch_and_checkout(&plugin.url, reference)
+        .with_context(|| format!("Failed to check out plugin '{}'", plugin.name))
+}
+
+/// Deletes the plugin's checkout and lock entry. Returns whether there was
+/// anything to remove.
+pub fn remove(data_dir: &Path, lock: &mut Lockfile, name: &str) -> Result<bool> {
+    validate_name(name)?;
+    let dir = checkout_dir(data_dir, name);
+    let cloned = dir.exists();
+    if cloned {
+        fs::remove_dir_all(&dir)?;
+    }
+    Ok(lock.plugins.remove(name).is_some() || cloned)
+}
+
+/// The file to source: the plugin's `source`, or the first of
+/// `<name>.plugin.zsh`, `<name>.zsh`, `<name>.zsh-theme`, `init.zsh` and any
+/// `*.plugin.zsh` that exists in the checkout.
+pub fn entry_file(data_dir: &Path, plugin: &ZshPlugin) -> Option<PathBuf> {
+    let dir = checkout_dir(data_dir, &plugin.name);
+    if let Some(source) = &plugin.source {
+        return Some(dir.join(source));
+    }
+    
+    let candidates = [
+        format!("{}.plugin.zsh", plugin.name),
+        format!("{}.zsh", plugin.name),
+        format!("{}.zsh-theme", plugin.name),
+        "init.zsh".to_string(),
+    ];
+    if let Some(found) = candidates.iter().map(|name| dir.join(name)).find(|path| path.is_file()) {
+        return Some(found);
+    }
+    
+    let mut plugin_files: Vec<PathBuf> = fs::read_dir(&dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.to_string_lossy().ends_with(".plugin.zsh"))
+        .collect();
+    plugin_files.sort();
+    plugin_files.into_iter().next()
+}
+
+/// The `.zshrc` block loading `plugins` in order: the others during startup,
+/// then the deferred ones from a one-shot `precmd` hook before the first
+/// prompt. Plugins without an entry file are left out.
+pub fn source_lines(data_dir: &Path, plugins: &[ZshPlugin]) -> String {
+    let line = |plugin: &ZshPlugin| {
+        entry_file(data_dir, plugin).map(|file| {
+            let file = format!("'{}'", file.display().to_string().replace('\'', "'\\''"));
+            format!("[[ -r {} ]] && source {}\n", file, file)
+        })
+    };
+    let eager: String = plugins.iter().filter(|p| !p.defer).filter_map(line).collect();
+    let deferred: String = plugins.iter().filter(|p| p.defer).filter_map(line).collect();
+    if eager.is_empty() && deferred.is_empty() {
+        return String::new();
+    }
+    
+    let mut script = format!("# zshrcman plugins\n\n{}", eager);
+    if !deferred.is_empty() {
+        let body: String = deferred.lines().map(|line| format!("    {}\n", line)).collect();
+        script.push_str(&format!(
+            "_zshrcman_deferred_plugins() {{\n    add-zsh-hook -d precmd _zshrcman_deferred_plugins\n{}}}\nautoload -Uz add-zsh-hook\nadd-zsh-hook precmd _zshrcman_deferred_plugins\n",
+            body
+        ));
+    }
+    script
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{Alias, AliasGroup, GroupConfig};
+    use crate::modules::alias::{self, AliasConflict};
+    use std::collections::HashMap;
+    
+    #[test]
+    fn test_parse_old_alias_strings() {
+        assert_eq!(Alias::parse(r#"alias ll="ls -la""#), Some(Alias::new("ll", "ls -la")));
+        assert_eq!(Alias::parse("alias gs='git status'"), Some(Alias::new("gs", "git status")));
+        assert_eq!(Alias::parse("k=kubectl"), Some(Alias::new("k", "kubectl")));
+        assert_eq!(Alias::parse(r"alias say='echo '\''hi'\'''"), Some(Alias::new("say", "echo 'hi'")));
+        assert_eq!(Alias::parse("alias -g G='| grep'"), Some(Alias::new("G", "| grep")));
+        assert_eq!(Alias::parse(r#"alias say="echo \"hi\" \$USER""#), Some(Alias::new("say", r#"echo "hi" $USER"#)));
+        assert_eq!(Alias::parse(r#"alias win="dir C:\Users""#), Some(Alias::new("win", r"dir C:\Users")));
+        assert_eq!(Alias::parse("ls -la"), None);
+    }
+    
+    #[test]
+    fn test_invalid_alias_lines_are_skipped() {
+        let group: GroupConfig = toml::from_str(r#"
+            name = "shell"
+            aliases = ["alias ll='ls -la'", "ls -la", { name = "k", command = "kubectl" }]
+        "#).unwrap();
+        assert_eq!(group.aliases, vec![Alias::new("ll", "ls -la"), Alias::new("k", "kubectl")]);
+    }
+    
+    #[test]
+    fn test_group_file_mixes_strings_and_tables() {
+        let group: GroupConfig = toml::from_str(
+            r#"
+name = "git"
+aliases = [
+    'alias gs="git status"',
+    { name = "gl", command = "git log --oneline", description = "Short log", shells = ["zsh", "bash"] },
+]
+"#,
+        )
+        .unwrap();
+        
+        assert_eq!(group.aliases[0], Alias::new("gs", "git status"));
+        assert_eq!(group.aliases[1].description.as_deref(), Some("Short log"));
+        assert!(group.aliases[1].applies_to("bash") && !group.aliases[1].applies_to("fish"));
+        assert!(toml::from_str::<GroupConfig>("name = \"x\"\naliases = [\"oops\"]").unwrap().aliases.is_empty());
+        
+        let saved = toml::to_string(&group).unwrap();
+        assert!(saved.contains("alias gs='git status'"));
+        let reloaded: GroupConfig = toml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.aliases, group.aliases);
+    }
+    
+    #[test]
+    fn test_render_per_shell() {
+        let mut alias = Alias::new("say", "echo 'hi'");
+        assert_eq!(alias.render("zsh").unwrap(), r"alias say='echo '\''hi'\'''");
+        assert_eq!(alias.render("fish").unwrap(), r"abbr --add say 'echo '\''hi'\'''");
+        assert_eq!(alias.render("powershell").unwrap(), "function say { echo 'hi' }");
+        assert_eq!(alias.render("nu").unwrap(), "alias say = echo 'hi'");
+        for command in ["make && make test", "cd /tmp; ls", "echo $(date)"] {
+            let chained = Alias::new("x", command);
+            assert!(chained.chains_commands());
+            assert_eq!(chained.render("nu").unwrap(), format!("# x = {} (nu aliases can't chain commands)", command));
+        }
+        
+        alias.abbr = Some(false);
+        assert_eq!(alias.render("fish").unwrap(), r"alias say 'echo '\''hi'\'''");
+        let saved = toml::to_string(&GroupConfig { aliases: vec![alias.clone()], ..Default::default() }).unwrap();
+        assert!(saved.contains("abbr = false"));
+        assert_eq!(toml::from_str::<GroupConfig>(&saved).unwrap().aliases, vec![alias.clone()]);
+        
+        alias.shells = vec!["fish".to_string()];
+        assert_eq!(alias.render("zsh"), None);
+    }
+    
+    #[test]
+    fn test_conflicts_across_groups_and_commands() {
+        // A PATH holding only a `tool` command, so the host's doesn't matter.
+        let bin = tempfile::TempDir::new().unwrap();
+        let tool = bin.path().join(if cfg!(windows) { "tool.exe" } else { "tool" });
+        std::fs::write(&tool, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let conflicts = |aliases: &HashMap<String, AliasGroup>, group: &str, alias: &Alias| {
+            alias::conflicts_on(aliases, group, alias, Some(bin.path().as_os_str().to_os_string()))
+        };
+        
+        let mut aliases = HashMap::new();
+        let gs = Alias::new("gs", "git status -sb");
+        aliases.insert("git".to_string(), AliasGroup { items: vec![gs.clone()], active: vec![gs.clone()] });
+        aliases.insert("mine".to_string(), AliasGroup { items: vec![Alias::new("gs", "git status")], active: vec![] });
+        
+        assert_eq!(
+            conflicts(&aliases, "mine", &Alias::new("gs", "git status")),
+            vec![AliasConflict::Group { group: "git".to_string(), alias: gs.clone(), active: true }]
+        );
+        // The same definition in another group is fine.
+        let elsewhere = conflicts(&aliases, "other", &gs);
+        assert!(matches!(elsewhere.as_slice(), [AliasConflict::Group { group, active: false, .. }] if group == "mine"));
+        
+        let shadowing = conflicts(&aliases, "mine", &Alias::new("tool", "other-tool"));
+        assert!(matches!(shadowing.as_slice(), [AliasConflict::Command(path)] if path == &tool));
+        assert!(conflicts(&aliases, "mine", &Alias::new("tool", "tool -x")).is_empty());
+        assert!(conflicts(&aliases, "mine", &Alias::new("sh", "zsh")).is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{Alias, AliasGroup};
+    use crate::modules::alias_usage;
+    use crate::tests::temp_config;
+    use std::fs;
+    
+    #[test]
+    fn test_stats_rank_used_aliases_and_list_unused() {
+        let (dir, mut config_mgr) = temp_config();
+        let log = alias_usage::log_path(dir.path());
+        fs::write(&log, "1700000000 gs\n1700000100 gs\n1700000050 ll\nbroken\n1700000200 other\n").unwrap();
+        
+        let git = vec![Alias::new("gs", "git status"), Alias::new("gp", "git push")];
+        config_mgr.config.aliases.insert("git".to_string(), AliasGroup { items: git.clone(), active: git });
+        let ll = Alias::new("ll", "ls -la");
+        config_mgr.config.aliases.insert("default".to_string(), AliasGroup { items: vec![ll.clone()], active: vec![ll] });
+        
+        let usage = alias_usage::read_usage(&log).unwrap();
+        assert_eq!(usage["gs"].count, 2);
+        assert_eq!(usage["gs"].last_used.unwrap().timestamp(), 1700000100);
+        
+        let stats = alias_usage::stats(&config_mgr.config, &usage, None);
+        let ranked: Vec<(&str, usize)> = stats.iter().map(|s| (s.name.as_str(), s.usage.count)).collect();
+        assert_eq!(ranked, vec![("gs", 2), ("ll", 1), ("gp", 0)]);
+        assert_eq!(alias_usage::stats(&config_mgr.config, &usage, Some("default")).len(), 1);
+    }
+    
+    #[test]
+    fn test_hook_snippet_quotes_log_path() {
+        let snippet = alias_usage::hook_snippet(std::path::Path::new("/tmp/it's/alias_usage.log"));
+        assert!(snippet.contains(r">> '/tmp/it'\''s/alias_usage.log'"));
+        assert!(snippet.contains("add-zsh-hook preexec _zshrcman_log_alias"));
+    }
+}
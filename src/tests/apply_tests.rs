@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::config::ConfigManager;
+    use crate::modules::install::InstallManager;
+    use crate::modules::verify::{DriftKind, Subject};
+    use crate::tests::temp_config;
+    use std::fs;
+    
+    fn setup() -> (tempfile::TempDir, ConfigManager) {
+        let (dir, mut config_mgr) = temp_config();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        fs::create_dir_all(dotfiles.join("files")).unwrap();
+        fs::write(
+            dotfiles.join("groups/tools.toml"),
+            "name = \"tools\"\n\n[[files]]\nsource = \"files/tool.conf\"\ntarget = \"~/.tool.conf\"\n",
+        )
+        .unwrap();
+        fs::write(dotfiles.join("files/tool.conf"), "color = true\n").unwrap();
+        config_mgr.add_global_group("tools".to_string()).unwrap();
+        config_mgr.enable_global_group("tools").unwrap();
+        (dir, config_mgr)
+    }
+    
+    #[test]
+    fn test_apply_restores_drifted_files() {
+        let (dir, config_mgr) = setup();
+        let home = dir.path().join("home");
+        let target = home.join(".tool.conf");
+        let mut manager = InstallManager::new(config_mgr).with_home(home.clone());
+        manager.install(true).unwrap();
+        
+        fs::write(&target, "color = false\n").unwrap();
+        let drift = manager.verify().unwrap();
+        assert_eq!(drift.len(), 1);
+        assert_eq!(drift[0].subject, Subject::File { path: target.clone() });
+        
+        assert_eq!(manager.apply(&drift).unwrap(), 1);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "color = true\n");
+        assert_eq!(manager.verify().unwrap(), vec![]);
+        // The edited version is kept next to it.
+        let backups: Vec<_> = fs::read_dir(&home)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains(".tool.conf.zshrcman-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "color = false\n");
+        
+        fs::remove_file(&target).unwrap();
+        let drift = manager.verify().unwrap();
+        assert_eq!(drift[0].kind, DriftKind::Missing);
+        manager.apply(&drift).unwrap();
+        assert!(target.exists());
+    }
+    
+    #[test]
+    fn test_apply_removes_disabled_groups() {
+        let (dir, config_mgr) = setup();
+        let home = dir.path().join("home");
+        let target = home.join(".tool.conf");
+        InstallManager::new(config_mgr).with_home(home.clone()).install(true).unwrap();
+        assert!(target.exists());
+        
+        let paths = || (dir.path().join("config.toml"), dir.path().join("dotfiles"));
+        let mut config_mgr = crate::tests::open_config(paths().0, paths().1).unwrap();
+        config_mgr.disable_global_group("tools").unwrap();
+        let mut manager = InstallManager::new(config_mgr).with_home(home);
+        let drift = manager.verify().unwrap();
+        assert_eq!(drift.len(), 1);
+        assert_eq!((drift[0].kind, drift[0].subject.to_string()), (DriftKind::Extraneous, "group tools".to_string()));
+        
+        manager.apply(&drift).unwrap();
+        assert!(!target.exists());
+        assert_eq!(manager.verify().unwrap(), vec![]);
+        let config_mgr = crate::tests::open_config(paths().0, paths().1).unwrap();
+        assert!(!config_mgr.config.status["tools"].installed);
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{Config, Profile};
+    use crate::modules::auto_switch;
+    use crate::modules::environment::ShellType;
+    use std::fs;
+    use tempfile::TempDir;
+    
+    #[test]
+    fn test_find_walks_up_to_the_nearest_file() {
+        let root = TempDir::new().unwrap();
+        let nested = root.path().join("client/app/src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.path().join(auto_switch::PROFILE_FILE), "personal\n").unwrap();
+        let file = auto_switch::set(&root.path().join("client"), "client-x").unwrap();
+        // Empty files don't stop the search.
+        fs::write(root.path().join("client/app").join(auto_switch::PROFILE_FILE), "\n").unwrap();
+        
+        assert_eq!(auto_switch::find(&nested), Some((file, "client-x".to_string())));
+        assert_eq!(auto_switch::find(root.path()).unwrap().1, "personal");
+    }
+    
+    #[test]
+    fn test_profile_for_ignores_unknown_profiles() {
+        let root = TempDir::new().unwrap();
+        let mut config = Config::default();
+        auto_switch::set(root.path(), "client-x").unwrap();
+        assert_eq!(auto_switch::profile_for(&config, root.path()), None);
+        
+        let profile: Profile = toml::from_str(
+            r#"
+name = "client-x"
+packages = []
+os_overrides = {}
+
+[environment]
+paths_prepend = []
+paths_append = []
+variables = {}
+aliases = {}
+active = true
+"#,
+        )
+        .unwrap();
+        config.profiles.insert("client-x".to_string(), profile);
+        assert_eq!(auto_switch::profile_for(&config, root.path()), Some("client-x".to_string()));
+    }
+    
+    #[test]
+    fn test_hook_snippets_run_on_directory_change() {
+        assert!(auto_switch::hook_snippet(&ShellType::Zsh).contains("chpwd_functions+=(_zshrcman_auto_switch)"));
+        assert!(auto_switch::hook_snippet(&ShellType::Bash).contains("[[ \"$PWD\" == \"${_zshrcman_pwd-}\" ]] && return"));
+        assert!(auto_switch::hook_snippet(&ShellType::Fish).contains("--on-variable PWD"));
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::bench::{self, Fragment, Report, Timing};
+    use std::path::{Path, PathBuf};
+    
+    const ZSHRC: &str = "\
+export EDITOR=vim
+# >>> zshrcman options >>>
+setopt autocd
+# <<< zshrcman options <<<
+alias ll='ls -l'
+
+# zshrcman managed scripts
+source /dotfiles/scripts/prompt.zsh
+source /dotfiles/scripts/nvm.zsh
+echo done
+";
+
+    fn fragment(name: &str, group: Option<&str>) -> Fragment {
+        Fragment {
+            name: name.to_string(),
+            group: group.map(str::to_string),
+            path: PathBuf::from(format!("/tmp/{}", name)),
+        }
+    }
+    
+    #[test]
+    fn test_timing_statistics() {
+        let timing = Timing::new(vec![40.0, 10.0, 30.0, 20.0]);
+        assert_eq!(timing.mean(), 25.0);
+        assert_eq!(timing.percentile(50.0), 20.0);
+        assert_eq!(timing.percentile(90.0), 40.0);
+        assert_eq!(Timing::default().percentile(90.0), 0.0);
+    }
+    
+    #[test]
+    fn test_managed_blocks_and_stripping() {
+        assert_eq!(bench::managed_blocks(ZSHRC), vec![("options".to_string(), "setopt autocd\n".to_string())]);
+        assert_eq!(bench::strip_managed(ZSHRC), "export EDITOR=vim\nalias ll='ls -l'\n\necho done\n");
+    }
+    
+    #[test]
+    fn test_timing_script_round_trip() {
+        let fragments = vec![fragment("a", None), fragment("it's", Some("zshrc"))];
+        let script = bench::timing_script(&fragments, Path::new("/tmp/results"));
+        assert!(script.contains("source '/tmp/it'\\''s' </dev/null >/dev/null 2>&1\n"));
+        assert!(script.contains("print -r -- \"1 $(( (EPOCHREALTIME - __zshrcman_start) * 1000 ))\" >> '/tmp/results'\n"));
+        
+        let timings = bench::parse_timings("0 1.5\n1 12.25\n0 2.5\nnoise\n7 1.0\n", 2);
+        assert_eq!(timings[0].mean(), 2.0);
+        assert_eq!(timings[1].mean(), 12.25);
+    }
+    
+    #[test]
+    fn test_group_totals_slowest_first() {
+        let report = Report {
+            shells: vec![],
+            fragments: vec![
+                (fragment("options block", None), Timing::new(vec![5.0])),
+                (fragment("script prompt.zsh", Some("zshrc")), Timing::new(vec![3.0])),
+                (fragment("plugin autosuggestions", Some("plugins")), Timing::new(vec![8.0])),
+                (fragment("script nvm.zsh", Some("zshrc")), Timing::new(vec![90.0])),
+            ],
+        };
+        assert_eq!(report.group_totals(), vec![("zshrc".to_string(), 93.0), ("plugins".to_string(), 8.0)]);
+    }
+}
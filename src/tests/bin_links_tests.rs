@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{InstallScope, InstallationRecord, InstallationSource};
+    use crate::modules::bin_links::{self, Collision};
+    use crate::modules::verify;
+    use std::collections::{BTreeSet, HashSet};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    
+    fn record(package: &str, scope: InstallScope, location: Option<PathBuf>, binaries: Vec<PathBuf>) -> InstallationRecord {
+        InstallationRecord {
+            package: package.to_string(),
+            version: None,
+            installed_at: chrono::Utc::now(),
+            installed_by: InstallationSource::Profile("work".to_string()),
+            active_for: HashSet::from(["work".to_string()]),
+            scope,
+            location,
+            installer_type: "npm".to_string(),
+            pinned: false,
+            required_by: BTreeSet::new(),
+            binaries,
+        }
+    }
+    
+    fn executable(path: &Path) -> PathBuf {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "#!/bin/sh\n").unwrap();
+        path.to_path_buf()
+    }
+    
+    #[test]
+    fn test_plan_links_every_binary_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("shared/bin");
+        let local = dir.path().join("profiles/work/prefix/npm/bin");
+        let typescript = record(
+            "typescript",
+            InstallScope::Profile,
+            None,
+            vec![executable(&shared.join("tsc")), executable(&shared.join("tsserver"))],
+        );
+        let other_tsc = record("tsc", InstallScope::Profile, Some(executable(&shared.join("other/tsc"))), vec![]);
+        let node = record("node@20", InstallScope::Profile, Some(executable(&shared.join("node"))), vec![]);
+        let local_node = record("node", InstallScope::Local, None, vec![executable(&local.join("node"))]);
+        let gone = record("jq", InstallScope::Profile, Some(shared.join("jq")), vec![]);
+        
+        let plan = bin_links::plan(&[&typescript, &other_tsc, &node, &local_node, &gone]);
+        let links: Vec<(&str, &str)> = plan.links.iter().map(|link| (link.name.as_str(), link.package.as_str())).collect();
+        assert_eq!(links, vec![("node", "node"), ("tsc", "tsc"), ("tsserver", "typescript")]);
+        assert_eq!(
+            plan.collisions,
+            vec![
+                Collision { name: "node".to_string(), kept: "node".to_string(), skipped: "node@20".to_string() },
+                Collision { name: "tsc".to_string(), kept: "tsc".to_string(), skipped: "typescript".to_string() },
+            ]
+        );
+        assert_eq!(plan.missing.len(), 1);
+        assert_eq!(plan.missing[0].name, "jq");
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_sync_links_relatively_and_drops_dangling() {
+        let dir = tempfile::tempdir().unwrap();
+        let profile = dir.path().join("profiles/work");
+        let bin = profile.join("bin");
+        let prettier = executable(&profile.join("prefix/npm/bin/prettier"));
+        let rg = executable(&dir.path().join("store/bin/rg"));
+        fs::create_dir_all(&bin).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("store/bin/gone"), bin.join("gone")).unwrap();
+        
+        let npm = record("prettier", InstallScope::Local, Some(prettier.clone()), vec![]);
+        let ripgrep = record("ripgrep", InstallScope::Profile, None, vec![rg.clone()]);
+        let dropped = bin_links::sync(&bin, &bin_links::plan(&[&npm, &ripgrep])).unwrap();
+        assert_eq!(dropped, vec![bin.join("gone")]);
+        assert!(fs::symlink_metadata(bin.join("gone")).is_err());
+        
+        assert_eq!(fs::read_link(bin.join("prettier")).unwrap(), Path::new("../prefix/npm/bin/prettier"));
+        assert_eq!(fs::read_link(bin.join("rg")).unwrap(), rg);
+        assert_eq!(bin_links::target(&bin.join("prettier")), Some(prettier.clone()));
+        assert_eq!(verify::check_link("profile 'work'", &bin.join("prettier"), &prettier), None);
+        
+        fs::remove_file(&prettier).unwrap();
+        assert_eq!(bin_links::dangling(&bin), vec![bin.join("prettier")]);
+        assert!(verify::check_link("profile 'work'", &bin.join("prettier"), &prettier).is_some());
+    }
+    
+    #[test]
+    fn test_shims_name_their_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("store/rg.exe");
+        let shims = bin_links::shims(&dir.path().join("rg"), &target);
+        let names: Vec<String> = shims.iter().map(|(path, _)| path.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, vec!["rg.cmd"]);
+        assert_eq!(shims[0].1, format!("@echo off\r\n@\"{}\" %*\r\n", target.display()));
+        for (path, script) in &shims {
+            fs::write(path, script).unwrap();
+        }
+        fs::write(dir.path().join("rg.ps1"), "").unwrap();
+        
+        assert_eq!(bin_links::target(&dir.path().join("rg")), Some(target.clone()));
+        assert_eq!(bin_links::target(&dir.path().join("rg.cmd")), Some(target.clone()));
+        assert!(!bin_links::points_at(&dir.path().join("rg"), &dir.path().join("other.exe")));
+        
+        // Relinking replaces the shims too.
+        let rg = executable(&dir.path().join("store/bin/rg"));
+        bin_links::create(&dir.path().join("rg"), &rg).unwrap();
+        assert!(bin_links::points_at(&dir.path().join("rg"), &rg));
+        #[cfg(unix)]
+        assert!(!dir.path().join("rg.cmd").exists() && !dir.path().join("rg.ps1").exists());
+    }
+}
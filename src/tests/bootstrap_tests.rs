@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::bootstrap::{BootstrapOptions, BootstrapProgress, Phase};
+    
+    fn options(device: &str) -> BootstrapOptions {
+        BootstrapOptions {
+            repo: "git@example.com:me/dotfiles.git".to_string(),
+            device: device.to_string(),
+            profile: None,
+            groups: None,
+        }
+    }
+    
+    #[test]
+    fn test_progress_persists_until_cleared() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(BootstrapProgress::load(dir.path()).unwrap().is_none());
+        
+        let mut progress = BootstrapProgress::new(&options("laptop"));
+        progress.complete(Phase::Init, dir.path()).unwrap();
+        progress.complete(Phase::Sync, dir.path()).unwrap();
+        progress.complete(Phase::Sync, dir.path()).unwrap();
+        
+        let loaded = BootstrapProgress::load(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.completed, vec![Phase::Init, Phase::Sync]);
+        assert!(loaded.is_done(Phase::Sync) && !loaded.is_done(Phase::Profile));
+        assert!(loaded.matches(&options("laptop")));
+        assert!(!loaded.matches(&options("desktop")));
+        
+        BootstrapProgress::clear(dir.path()).unwrap();
+        assert!(BootstrapProgress::load(dir.path()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::changeset::{revert_file_change, ChangeRecorder, ChangeSet};
+    use std::fs;
+    
+    #[test]
+    fn test_record_and_revert_group_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let data_dir = dir.path().join("data");
+        let config = dir.path().join("app.conf");
+        let created = dir.path().join("new.conf");
+        let zshrc = dir.path().join(".zshrc");
+        fs::write(&config, "original\n").unwrap();
+        fs::write(&zshrc, "# mine\n").unwrap();
+        
+        let mut recorder = ChangeRecorder::new(&data_dir, "tools").unwrap();
+        recorder.before_write(&config).unwrap();
+        fs::write(&config, "managed\n").unwrap();
+        recorder.before_write(&created).unwrap();
+        fs::write(&created, "managed\n").unwrap();
+        fs::write(&zshrc, "# mine\nsource tools.zsh\n").unwrap();
+        recorder.appended(&zshrc, "source tools.zsh\n");
+        recorder.finish(&data_dir).unwrap();
+        
+        // Another group appends after us; rollback must keep its line.
+        fs::write(&zshrc, "# mine\nsource tools.zsh\nsource other.zsh\n").unwrap();
+        
+        let changes = ChangeSet::load(&data_dir, "tools").unwrap().unwrap();
+        for change in changes.changes.iter().rev() {
+            revert_file_change(change).unwrap();
+        }
+        
+        assert_eq!(fs::read_to_string(&config).unwrap(), "original\n");
+        assert!(!created.exists());
+        assert_eq!(fs::read_to_string(&zshrc).unwrap(), "# mine\nsource other.zsh\n");
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::completions::{self, CompletionManager};
+    use crate::modules::environment::{EnvironmentManager, ShellType};
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    
+    fn sources(dir: &TempDir, files: &[(&str, &str)]) -> BTreeMap<String, PathBuf> {
+        let repo = dir.path().join("completions");
+        fs::create_dir_all(&repo).unwrap();
+        files
+            .iter()
+            .map(|(name, content)| {
+                fs::write(repo.join(name), content).unwrap();
+                (name.to_string(), repo.join(name))
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn test_deploy_copies_and_prunes_completion_files() {
+        let home = TempDir::new().unwrap();
+        let repo = TempDir::new().unwrap();
+        let manager = CompletionManager::new(home.path());
+        
+        let files = sources(&repo, &[("_mytool", "#compdef mytool\n"), ("_other", "#compdef other\n")]);
+        assert!(manager.deploy(&files).unwrap());
+        assert_eq!(fs::read_to_string(manager.dir().join("_mytool")).unwrap(), "#compdef mytool\n");
+        
+        // Unchanged files are not reported as a change.
+        assert!(!manager.deploy(&files).unwrap());
+        
+        // Hand-added files survive, files dropped from groups don't.
+        fs::write(manager.dir().join("_mine"), "#compdef mine\n").unwrap();
+        let files = sources(&repo, &[("_mytool", "#compdef mytool\n")]);
+        assert!(manager.deploy(&files).unwrap());
+        assert!(!manager.dir().join("_other").exists());
+        assert!(manager.dir().join("_mine").exists());
+        
+        assert!(manager.deploy(&BTreeMap::new()).unwrap());
+        assert!(!manager.dir().join("_mytool").exists());
+        assert!(manager.dir().join("_mine").exists());
+    }
+    
+    #[test]
+    fn test_reset_dump_removes_compdump_files() {
+        let home = TempDir::new().unwrap();
+        fs::write(home.path().join(".zcompdump"), "").unwrap();
+        fs::write(home.path().join(".zcompdump-host-5.9"), "").unwrap();
+        fs::write(home.path().join(".zshrc"), "").unwrap();
+        
+        assert_eq!(CompletionManager::new(home.path()).reset_dump().unwrap(), 2);
+        assert!(!home.path().join(".zcompdump").exists());
+        assert!(home.path().join(".zshrc").exists());
+    }
+    
+    #[test]
+    fn test_fpath_block_goes_into_zshenv() {
+        let home = TempDir::new().unwrap();
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).with_home(home.path().to_path_buf());
+        
+        zsh.write_completions(&completions::fpath_lines()).unwrap();
+        let zshenv = fs::read_to_string(home.path().join(".zshenv")).unwrap();
+        assert!(zshenv.contains("fpath=(\"$HOME/.zsh/completions\" $fpath)"));
+        assert!(zsh.has_completions_block().unwrap());
+        assert!(!home.path().join(".zshrc").exists());
+        
+        zsh.write_completions("").unwrap();
+        assert!(!zsh.has_completions_block().unwrap());
+    }
+}
@@ -0,0 +1,294 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::Config;
+    use crate::modules::config::{apply_env_overrides, write_atomic, ConfigLock, ConfigManager, PathOverrides};
+    use std::path::PathBuf;
+    
+    fn vars(pairs: &[(&str, &str)]) -> impl Iterator<Item = (String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+    
+    #[test]
+    fn test_env_overrides_map_to_config_keys() {
+        let config = apply_env_overrides(
+            Config::default(),
+            vars(&[
+                ("ZSHRCMAN_REPOSITORY_URL", "git@github.com:me/dotfiles.git"),
+                ("ZSHRCMAN_REPOSITORY_MAIN_BRANCH", "trunk"),
+                ("ZSHRCMAN_DEVICE_NAME", "ci"),
+                ("ZSHRCMAN_GROUPS_ENABLED_GLOBAL", "default, brew"),
+                ("ZSHRCMAN_VARIABLES_EMAIL", "ci@example.com"),
+                ("ZSHRCMAN_FEATURES_DAEMON", "true"),
+                ("HOME", "/root"),
+            ]),
+        )
+        .unwrap();
+        
+        assert_eq!(config.repository.url.as_deref(), Some("git@github.com:me/dotfiles.git"));
+        assert_eq!(config.repository.main_branch, "trunk");
+        assert_eq!(config.device.name, "ci");
+        assert_eq!(config.groups.enabled_global, vec!["default", "brew"]);
+        assert_eq!(config.variables["email"].as_str(), Some("ci@example.com"));
+        assert_eq!(config.features.get("daemon"), Some(&true));
+    }
+    
+    #[test]
+    fn test_env_overrides_ignore_cli_variables() {
+        let config = apply_env_overrides(
+            Config::default(),
+            vars(&[("ZSHRCMAN_YES", "1"), ("ZSHRCMAN_GROUPS", "a,b"), ("ZSHRCMAN_DEVICE", "x")]),
+        )
+        .unwrap();
+        
+        assert_eq!(config.groups.enabled_global, vec!["default"]);
+        assert_eq!(config.device.name, "");
+    }
+    
+    #[test]
+    fn test_env_overrides_match_mixed_case_keys_and_stay_unsaved() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut config = Config::default();
+        config.variables.insert("GitHubUser".to_string(), toml::Value::String("me".to_string()));
+        std::fs::write(&path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        
+        let env = vars(&[("ZSHRCMAN_VARIABLES_GITHUBUSER", "ci-bot"), ("ZSHRCMAN_DEVICE_NAME", "ci")]);
+        let mut config_mgr = ConfigManager::with_env(path.clone(), dir.path().join("dotfiles"), env).unwrap();
+        assert_eq!(config_mgr.config.variables["GitHubUser"].as_str(), Some("ci-bot"));
+        assert!(!config_mgr.config.variables.contains_key("githubuser"));
+        assert_eq!(config_mgr.config.device.name, "ci");
+        
+        // Saving keeps the file's values for overridden keys the command left alone.
+        config_mgr.config.variables.insert("editor".to_string(), toml::Value::String("vim".to_string()));
+        config_mgr.config.device.name = "laptop".to_string();
+        config_mgr.save().unwrap();
+        let saved: Config = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.variables["GitHubUser"].as_str(), Some("me"));
+        assert_eq!(saved.variables["editor"].as_str(), Some("vim"));
+        assert_eq!(saved.device.name, "laptop");
+    }
+    
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "old").unwrap();
+        
+        write_atomic(&path, b"new").unwrap();
+        
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_follows_symlinks_and_keeps_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        let target = dir.path().join("dotfiles.toml");
+        std::fs::write(&target, "old").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o600)).unwrap();
+        let link = dir.path().join("config.toml");
+        std::os::unix::fs::symlink("dotfiles.toml", &link).unwrap();
+        
+        write_atomic(&link, b"new").unwrap();
+        
+        assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+        assert_eq!(std::fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+    
+    #[test]
+    fn test_config_lock_excludes_second_holder() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        
+        let lock = ConfigLock::acquire(&path, false).unwrap();
+        let busy = ConfigLock::acquire(&path, false).err().unwrap();
+        assert!(busy.to_string().contains(&format!("pid {}", std::process::id())));
+        
+        drop(lock);
+        assert!(ConfigLock::acquire(&path, false).is_ok());
+    }
+    
+    #[test]
+    fn test_path_overrides_from_env() {
+        let lookup = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        
+        assert_eq!(PathOverrides::from_vars(lookup(&[])), PathOverrides::default());
+        
+        let overrides = PathOverrides::from_vars(lookup(&[
+            ("ZSHRCMAN_CONFIG_DIR", "/ctx/work"),
+            ("ZSHRCMAN_DATA_DIR", "/ctx/data"),
+        ]));
+        assert_eq!(overrides.config_path, Some(PathBuf::from("/ctx/work/config.toml")));
+        assert_eq!(overrides.data_dir, Some(PathBuf::from("/ctx/data")));
+        
+        let overrides = PathOverrides::from_vars(lookup(&[
+            ("ZSHRCMAN_CONFIG", "/ctx/sandbox.toml"),
+            ("ZSHRCMAN_CONFIG_DIR", "/ctx/work"),
+            ("ZSHRCMAN_DATA_DIR", " "),
+        ]));
+        assert_eq!(overrides.config_path, Some(PathBuf::from("/ctx/sandbox.toml")));
+        assert_eq!(overrides.data_dir, None);
+        
+        let relative = PathOverrides::from_vars(lookup(&[("ZSHRCMAN_DATA_DIR", "data")]));
+        assert!(relative.data_dir.unwrap().is_absolute());
+    }
+    
+    #[test]
+    fn test_load_merged_group_layers_device_file_over_global() {
+        let (_dir, mut config_mgr) = crate::tests::temp_config();
+        config_mgr.config.device.name = "laptop".to_string();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        let device_groups = dotfiles.join("devices/laptop/groups");
+        std::fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        std::fs::create_dir_all(&device_groups).unwrap();
+        std::fs::write(
+            dotfiles.join("groups/dev.toml"),
+            "name = \"dev\"\ndescription = \"Dev tools\"\npackages = [\"git\", \"jq\"]\ninstaller = \"brew\"\n",
+        )
+        .unwrap();
+        std::fs::write(device_groups.join("dev.toml"), "name = \"dev\"\npackages = [\"jq\", \"kubectl\"]\nssh_keys = [\"id_work\"]\n").unwrap();
+        std::fs::write(device_groups.join("local.toml"), "name = \"local\"\n").unwrap();
+        
+        let (group, files) = config_mgr.load_merged_group("dev").unwrap();
+        assert_eq!(group.description, "Dev tools");
+        assert_eq!(group.packages, vec!["git", "jq", "kubectl"]);
+        assert_eq!(group.installer.as_deref(), Some("brew"));
+        assert_eq!(group.ssh_keys, vec!["id_work"]);
+        assert_eq!(files.len(), 2);
+        
+        assert_eq!(config_mgr.load_merged_group("local").unwrap().1, vec![device_groups.join("local.toml")]);
+        assert!(config_mgr.load_merged_group("missing").is_err());
+    }
+    
+    #[test]
+    fn test_rename_and_copy_group_move_files_and_config_entries() {
+        let (_dir, mut config_mgr) = crate::tests::temp_config();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        std::fs::create_dir_all(dotfiles.join("groups/k8s")).unwrap();
+        std::fs::create_dir_all(dotfiles.join("devices/laptop/groups")).unwrap();
+        std::fs::write(dotfiles.join("groups/k8s.toml"), "# cluster tools\nname = \"k8s\"\npackages = [\"kubectl\"]\n").unwrap();
+        std::fs::write(dotfiles.join("groups/k8s/install.rhai"), "").unwrap();
+        std::fs::write(dotfiles.join("devices/laptop/groups/k8s.toml"), "name = 'k8s'\n").unwrap();
+        config_mgr.add_global_group("k8s".to_string()).unwrap();
+        config_mgr.enable_global_group("k8s").unwrap();
+        config_mgr.config.groups.per_device.push("k8s".to_string());
+        config_mgr.config.groups.enabled_devices.push("k8s".to_string());
+        let work: crate::models::Profile = toml::from_str(
+            "name = \"work\"\npackages = []\nos_overrides = {}\nenabled_groups = [\"k8s\"]\n\n[environment]\npaths_prepend = []\npaths_append = []\nvariables = {}\naliases = {}\nactive = true\n",
+        )
+        .unwrap();
+        config_mgr.config.profiles.insert("work".to_string(), work);
+        config_mgr.config.aliases.insert(
+            "k8s".to_string(),
+            crate::models::AliasGroup { items: vec![crate::models::Alias::new("k", "kubectl")], active: vec![] },
+        );
+        
+        let changed = config_mgr.rename_group("k8s", "kube").unwrap();
+        assert_eq!(changed.len(), 6);
+        assert!(!dotfiles.join("groups/k8s.toml").exists());
+        assert!(dotfiles.join("groups/kube/install.rhai").exists());
+        assert_eq!(
+            std::fs::read_to_string(dotfiles.join("groups/kube.toml")).unwrap(),
+            "# cluster tools\nname = \"kube\"\npackages = [\"kubectl\"]\n"
+        );
+        assert_eq!(config_mgr.load_device_group_config("laptop", "kube").unwrap().name, "kube");
+        assert_eq!(config_mgr.config.groups.enabled_global, vec!["default", "kube"]);
+        assert_eq!(config_mgr.config.groups.per_device, vec!["kube"]);
+        assert_eq!(config_mgr.config.groups.enabled_devices, vec!["kube"]);
+        assert_eq!(config_mgr.config.profiles["work"].enabled_groups, vec!["kube"]);
+        assert!(config_mgr.config.aliases.contains_key("kube") && !config_mgr.config.aliases.contains_key("k8s"));
+        
+        config_mgr.copy_group("kube", "kube-dev").unwrap();
+        assert!(dotfiles.join("groups/kube.toml").exists());
+        assert_eq!(config_mgr.load_group_config("kube-dev").unwrap().name, "kube-dev");
+        assert!(!config_mgr.config.groups.enabled_global.contains(&"kube-dev".to_string()));
+        assert_eq!(config_mgr.config.groups.per_device, vec!["kube", "kube-dev"]);
+        assert_eq!(config_mgr.config.groups.enabled_devices, vec!["kube"]);
+        
+        assert!(config_mgr.copy_group("kube", "kube-dev").is_err());
+        assert!(config_mgr.rename_group("default", "base").is_err());
+        assert!(config_mgr.rename_group("missing", "other").is_err());
+    }
+    
+    #[test]
+    fn test_ordered_groups_follow_priority() {
+        let (_dir, mut config_mgr) = crate::tests::temp_config();
+        config_mgr.config.device.name = "laptop".to_string();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        std::fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        std::fs::create_dir_all(dotfiles.join("devices/laptop/groups")).unwrap();
+        std::fs::write(dotfiles.join("groups/prompt.toml"), "name = \"prompt\"\npriority = 90\n").unwrap();
+        std::fs::write(dotfiles.join("groups/env.toml"), "name = \"env\"\npriority = -10\n").unwrap();
+        std::fs::write(dotfiles.join("groups/tools.toml"), "name = \"tools\"\n").unwrap();
+        std::fs::write(dotfiles.join("devices/laptop/groups/tools.toml"), "name = \"tools\"\npriority = 95\n").unwrap();
+        config_mgr.config.groups.enabled_global = ["prompt", "tools", "env", "plain"].map(String::from).to_vec();
+        
+        assert_eq!(config_mgr.get_ordered_groups(), vec!["env", "default", "plain", "prompt", "tools"]);
+        
+        // Priorities are read once; a reload reads the changed files again.
+        std::fs::write(dotfiles.join("groups/env.toml"), "priority = \"first\"").unwrap();
+        assert_eq!(config_mgr.get_ordered_groups(), vec!["env", "default", "plain", "prompt", "tools"]);
+        config_mgr.config.groups.enabled_global.retain(|group| group != "tools");
+        config_mgr.save().unwrap();
+        config_mgr.reload().unwrap();
+        config_mgr.config.device.name = "laptop".to_string();
+        assert_eq!(config_mgr.get_ordered_groups(), vec!["default", "env", "plain", "prompt"]);
+    }
+    
+    #[test]
+    fn test_tagged_groups_follow_device_tags() {
+        let (_dir, mut config_mgr) = crate::tests::temp_config();
+        config_mgr.config.device.name = "desk".to_string();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        std::fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        std::fs::create_dir_all(dotfiles.join("devices/desk/groups")).unwrap();
+        std::fs::write(dotfiles.join("groups/cuda.toml"), "name = \"cuda\"\ntarget_tags = [\"gpu\"]\n").unwrap();
+        std::fs::write(dotfiles.join("groups/slack.toml"), "name = \"slack\"\ntarget_tags = [\"work\"]\n").unwrap();
+        std::fs::write(dotfiles.join("groups/games.toml"), "name = \"games\"\n").unwrap();
+        std::fs::write(dotfiles.join("devices/desk/groups/games.toml"), "name = \"games\"\ntarget_tags = [\"gpu\"]\n").unwrap();
+        std::fs::write(dotfiles.join("groups/broken.toml"), "target_tags = \"gpu\"").unwrap();
+        
+        assert!(config_mgr.tagged_groups().is_empty());
+        
+        config_mgr.config.device.tags = vec!["laptop".to_string(), "gpu".to_string()];
+        assert_eq!(config_mgr.tagged_groups(), vec!["cuda", "games"]);
+        assert_eq!(config_mgr.get_ordered_groups(), vec!["default", "cuda", "games"]);
+        
+        config_mgr.config.groups.enabled_global.push("slack".to_string());
+        assert_eq!(config_mgr.get_ordered_groups(), vec!["default", "slack", "cuda", "games"]);
+        
+        config_mgr.disable_global_group("cuda").unwrap();
+        assert_eq!(config_mgr.tagged_groups(), vec!["games"]);
+        assert!(!config_mgr.get_ordered_groups().contains(&"cuda".to_string()));
+        
+        config_mgr.enable_global_group("cuda").unwrap();
+        assert!(config_mgr.config.groups.disabled_tagged.is_empty());
+        assert_eq!(config_mgr.tagged_groups(), vec!["cuda", "games"]);
+    }
+    
+    #[test]
+    fn test_reload_picks_up_saved_changes() {
+        let (_dir, mut config_mgr) = crate::tests::temp_config();
+        let mut other = crate::tests::open_config(
+            config_mgr.config_path().to_path_buf(),
+            config_mgr.dotfiles_path().to_path_buf(),
+        )
+        .unwrap();
+        other.add_global_group("brew".to_string()).unwrap();
+        
+        config_mgr.config.device.name = "unsaved".to_string();
+        config_mgr.reload().unwrap();
+        assert!(config_mgr.config.groups.global.contains(&"brew".to_string()));
+        assert_ne!(config_mgr.config.device.name, "unsaved");
+    }
+}
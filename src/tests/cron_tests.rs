@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::GroupConfig;
+    use crate::modules::cron;
+    
+    #[test]
+    fn test_cron_block_is_merged_and_removed() {
+        let group: GroupConfig = toml::from_str(
+            "name = \"backup\"\n\n[[cron]]\nschedule = \"0 3 * * *\"\ncommand = \"~/bin/backup.sh\"\ndescription = \"Nightly backup\"\n",
+        )
+        .unwrap();
+        let existing = "MAILTO=jane@example.com\n*/5 * * * * ~/bin/poll\n";
+        
+        let merged = cron::replace_block(existing, "backup", &cron::render(&group.cron));
+        assert_eq!(
+            merged,
+            "MAILTO=jane@example.com\n*/5 * * * * ~/bin/poll\n\
+             # >>> zshrcman cron backup >>>\n# Nightly backup\n0 3 * * * ~/bin/backup.sh\n# <<< zshrcman cron backup <<<\n"
+        );
+        assert_eq!(cron::replace_block(&merged, "backup", &cron::render(&group.cron)), merged);
+        assert_eq!(cron::replace_block(&merged, "other", ""), merged);
+        assert_eq!(cron::replace_block(&merged, "backup", ""), existing);
+    }
+    
+    #[test]
+    fn test_percent_is_escaped_and_block_read_back() {
+        assert_eq!(cron::escape_command("date +%Y-%m-%d"), "date +\\%Y-\\%m-\\%d");
+        assert_eq!(cron::escape_command("echo 50\\%"), "echo 50\\%");
+        
+        let jobs: Vec<crate::models::CronJob> =
+            toml::from_str::<GroupConfig>("name = \"logs\"\n\n[[cron]]\nschedule = \"@daily\"\ncommand = \"tar czf logs-$(date +%F).tgz logs\"\n")
+                .unwrap()
+                .cron;
+        let rendered = cron::render(&jobs);
+        assert_eq!(rendered, "@daily tar czf logs-$(date +\\%F).tgz logs\n");
+        
+        let crontab = cron::replace_block("MAILTO=me\n", "logs", &rendered);
+        assert_eq!(cron::block(&crontab, "logs"), rendered);
+        assert_eq!(cron::block(&crontab, "other"), "");
+        
+        assert!(cron::is_missing_crontab("no crontab for jane\n"));
+        assert!(!cron::is_missing_crontab("crontab: must be privileged to use -l\n"));
+    }
+    
+    #[test]
+    fn test_cron_schedules() {
+        assert!(cron::is_valid_schedule("0 3 * * *"));
+        assert!(cron::is_valid_schedule("@daily"));
+        assert!(!cron::is_valid_schedule("@sometimes"));
+        assert!(!cron::is_valid_schedule("0 3 * *"));
+    }
+}
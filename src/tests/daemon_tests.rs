@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::daemon::{self, Daemon};
+    use crate::modules::git_mgr::GitManager;
+    use crate::tests::temp_config;
+    use std::fs;
+    use std::path::Path;
+    
+    #[test]
+    fn test_commit_message_names_groups() {
+        let files = |paths: &[&str]| paths.iter().map(|path| path.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            daemon::commit_message(&files(&["groups/brew.toml", "groups/brew/install.rhai", "files/zshrc"])),
+            "Auto-commit: update group brew, files/zshrc"
+        );
+        assert_eq!(
+            daemon::commit_message(&files(&["devices/laptop/groups/npm.toml", "a", "b", "c"])),
+            "Auto-commit: update device laptop, a, b and 1 more"
+        );
+        
+        let repo = Path::new("/home/me/.dotfiles");
+        assert!(daemon::is_repo_change(repo, &repo.join("groups/brew.toml")));
+        assert!(daemon::is_repo_change(repo, &repo.join(".gitignore")));
+        assert!(!daemon::is_repo_change(repo, &repo.join(".git/index")));
+        assert!(!daemon::is_repo_change(repo, Path::new("/home/me/.zshrc")));
+    }
+    
+    #[test]
+    fn test_commit_changes() {
+        let (dir, config_mgr) = temp_config();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        let git = GitManager::init_or_clone(&dotfiles, None).unwrap();
+        fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        fs::write(dotfiles.join("groups/brew.toml"), "name = \"brew\"\n").unwrap();
+        
+        let daemon = Daemon::new(config_mgr);
+        assert_eq!(daemon.commit_changes().unwrap().as_deref(), Some("Auto-commit: update group brew"));
+        assert!(git.changed_files().unwrap().is_empty());
+        assert_eq!(daemon.commit_changes().unwrap(), None);
+        
+        fs::remove_file(dotfiles.join("groups/brew.toml")).unwrap();
+        daemon.commit_changes().unwrap();
+        assert!(git.changed_files().unwrap().is_empty());
+        
+        assert_eq!(daemon::query(&daemon::socket_path(dir.path())).unwrap(), None);
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::RetentionSettings;
+    use crate::modules::disk_usage::{self, format_bytes};
+    use crate::modules::history::History;
+    use crate::modules::oplog::{Operation, OperationLog};
+    use crate::tests::temp_config;
+    use chrono::{Duration, Utc};
+    use std::fs;
+    
+    fn operation(command: &str, days_ago: i64) -> Operation {
+        Operation {
+            started_at: Utc::now() - Duration::days(days_ago),
+            duration_ms: 10,
+            command: command.to_string(),
+            args: command.to_string(),
+            success: true,
+            error: None,
+            history_id: None,
+        }
+    }
+    
+    #[test]
+    fn test_report_lists_areas_and_profiles() {
+        let (_dir, config_mgr) = temp_config();
+        let data_dir = config_mgr.data_dir();
+        fs::create_dir_all(config_mgr.dotfiles_path()).unwrap();
+        fs::write(config_mgr.dotfiles_path().join("zshrc"), vec![b'x'; 3000]).unwrap();
+        fs::create_dir_all(data_dir.join("profiles/work/bin")).unwrap();
+        fs::write(data_dir.join("profiles/work/bin/tool"), "#!/bin/sh\n").unwrap();
+        
+        let usage = disk_usage::report(&config_mgr);
+        let labels: Vec<&str> = usage.iter().map(|area| area.label.as_str()).collect();
+        assert_eq!(labels, vec!["dotfiles", "profiles/work"]);
+        assert_eq!(usage[0].bytes, 3000);
+        
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3000), "2.9 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+    
+    #[test]
+    fn test_prune_logs_and_snapshots_follow_retention() {
+        let (_dir, config_mgr) = temp_config();
+        let data_dir = config_mgr.data_dir();
+        let retention = RetentionSettings { log_days: 30, history_entries: 2 };
+        
+        let log = OperationLog::new(&data_dir);
+        log.append(&operation("install", 45)).unwrap();
+        log.append(&operation("sync", 1)).unwrap();
+        assert!(disk_usage::prune_logs(&data_dir, &retention, Utc::now()).unwrap() > 0);
+        let kept: Vec<String> = log.since(None).unwrap().into_iter().map(|op| op.command).collect();
+        assert_eq!(kept, vec!["sync"]);
+        
+        let history = History::new(&data_dir);
+        for i in 0..3 {
+            history.record("group add", Some(i.to_string()), Some((i + 1).to_string()), None).unwrap();
+        }
+        let staging = data_dir.join("changesets/.base-4242");
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("backup-0"), "old").unwrap();
+        fs::create_dir_all(data_dir.join("changesets/base")).unwrap();
+        
+        disk_usage::prune_snapshots(&data_dir, &retention).unwrap();
+        let ids: Vec<u64> = history.entries().unwrap().iter().map(|entry| entry.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert!(!staging.exists());
+        assert!(data_dir.join("changesets/base").exists());
+        
+        history.record("group add", Some("3".to_string()), Some("4".to_string()), None).unwrap();
+        assert_eq!(history.entries().unwrap().last().unwrap().id, 4);
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::editor::Editor;
+    use crate::modules::validate::Validator;
+    use crate::tests::{non_interactive, temp_config};
+    use std::fs;
+    use std::path::Path;
+    
+    /// An "editor" that replaces the file it is given with `contents`.
+    fn fake_editor(dir: &Path, contents: &str) -> Editor {
+        let replacement = dir.join("replacement.toml");
+        fs::write(&replacement, contents).unwrap();
+        let script = dir.join("editor.sh");
+        fs::write(&script, format!("cp '{}' \"$1\"\n", replacement.display())).unwrap();
+        Editor::new(vec!["sh".to_string(), script.display().to_string()])
+    }
+    
+    #[test]
+    fn test_edit_saves_valid_group_file() {
+        let (dir, config_mgr) = temp_config();
+        let path = config_mgr.dotfiles_path().join("groups/base.toml");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "name = \"base\"\n").unwrap();
+        let check = |copy: &Path| Validator::new(config_mgr.config_path(), config_mgr.dotfiles_path()).group_file(copy);
+        
+        let edited = "name = \"base\"\npackages = [\"ripgrep\"]\n";
+        assert!(fake_editor(dir.path(), edited).edit_checked(&path, "", check).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), edited);
+        
+        assert!(!fake_editor(dir.path(), edited).edit_checked(&path, "", check).unwrap());
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+    
+    #[test]
+    fn test_edit_refuses_invalid_content() {
+        let _non_interactive = non_interactive();
+        let (dir, config_mgr) = temp_config();
+        let path = config_mgr.dotfiles_path().join("groups/base.toml");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "name = \"base\"\n").unwrap();
+        let check = |copy: &Path| Validator::new(config_mgr.config_path(), config_mgr.dotfiles_path()).group_file(copy);
+        
+        let editor = fake_editor(dir.path(), "name = \"base\"\npackages = \"ripgrep\"\n");
+        assert!(editor.edit_checked(&path, "", check).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "name = \"base\"\n");
+        
+        let new_group = config_mgr.dotfiles_path().join("groups/new.toml");
+        assert!(editor.edit_checked(&new_group, "name = \"new\"\n", check).is_err());
+        assert!(!new_group.exists());
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::env_layers::{EnvLayers, Layer};
+    use std::collections::BTreeMap;
+    
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+    
+    #[test]
+    fn test_precedence_and_conflicts() {
+        let mut layers = EnvLayers::new();
+        layers.add(Layer::GlobalGroup, "base", &vars(&[("EDITOR", "nano"), ("PAGER", "less")]));
+        layers.add(Layer::GlobalGroup, "dev", &vars(&[("EDITOR", "vim"), ("PAGER", "less")]));
+        layers.add(Layer::DeviceGroup, "laptop", &vars(&[("EDITOR", "hx")]));
+        layers.add(Layer::Profile, "work", &vars(&[("AWS_PROFILE", "work")]));
+        
+        let editor = layers.explain("EDITOR");
+        assert_eq!(editor.iter().map(|setting| setting.value.as_str()).collect::<Vec<_>>(), ["hx", "nano", "vim"]);
+        assert_eq!(editor[0].to_string(), "device group 'laptop'");
+        assert_eq!(layers.winner("PAGER").unwrap().origin, "base");
+        assert!(layers.explain("TERM").is_empty());
+        
+        // Same layer, different values: order decided, so it's reported.
+        assert!(layers.conflicts().is_empty());
+        layers.add(Layer::DeviceGroup, "desk", &vars(&[("EDITOR", "code")]));
+        assert_eq!(
+            layers.conflicts(),
+            ["'EDITOR' is hx in device group 'laptop' but code in device group 'desk'; keeping hx"]
+        );
+    }
+    
+    #[test]
+    fn test_group_variables_leave_profile_ones_out() {
+        use crate::modules::environment::{EnvironmentManager, ShellType};
+        
+        let mut layers = EnvLayers::new();
+        layers.add(Layer::GlobalGroup, "base", &vars(&[("EDITOR", "nano"), ("LESS", "-R")]));
+        layers.add(Layer::Profile, "work", &vars(&[("EDITOR", "code --wait")]));
+        
+        let variables = layers.group_variables();
+        assert_eq!(variables, vars(&[("LESS", "-R")]));
+        assert_eq!(
+            EnvironmentManager::for_shell(ShellType::Fish).variables_block(&variables),
+            "# zshrcman group variables\n\nset -gx LESS \"-R\"\n"
+        );
+        assert_eq!(EnvironmentManager::for_shell(ShellType::Zsh).variables_block(&BTreeMap::new()), "");
+    }
+    
+    #[test]
+    fn test_group_variables_follow_profile_switch() {
+        use crate::models::Profile;
+        use crate::modules::install::InstallManager;
+        
+        let (_dir, mut config_mgr) = crate::tests::temp_config();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        std::fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        std::fs::write(dotfiles.join("groups/base.toml"), "name = \"base\"\n\n[env]\nEDITOR = \"nvim\"\nLESS = \"-R\"\n").unwrap();
+        config_mgr.config.groups.enabled_global.push("base".to_string());
+        
+        let profile = |name: &str, variables: &str| -> Profile {
+            toml::from_str(&format!(
+                "name = \"{}\"\npackages = []\nos_overrides = {{}}\n\n[environment]\npaths_prepend = []\npaths_append = []\nvariables = {{ {} }}\naliases = {{}}\nactive = true\n",
+                name, variables
+            ))
+            .unwrap()
+        };
+        config_mgr.config.profiles.insert("work".to_string(), profile("work", "EDITOR = \"code --wait\""));
+        config_mgr.config.profiles.insert("personal".to_string(), profile("personal", ""));
+        config_mgr.config.active_profile = Some("work".to_string());
+        config_mgr.save().unwrap();
+        
+        let reload = || crate::tests::open_config(
+            config_mgr.config_path().to_path_buf(),
+            dotfiles.clone(),
+        )
+        .unwrap();
+        assert_eq!(InstallManager::new(reload()).env_layers().group_variables(), vars(&[("LESS", "-R")]));
+        
+        // What refresh_group_variables sees after the switch was saved.
+        let mut switched = reload();
+        switched.config.active_profile = Some("personal".to_string());
+        switched.save().unwrap();
+        assert_eq!(
+            InstallManager::new(reload()).env_layers().group_variables(),
+            vars(&[("EDITOR", "nvim"), ("LESS", "-R")])
+        );
+    }
+}
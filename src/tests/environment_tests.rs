@@ -0,0 +1,183 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::*;
+    
+    #[test]
+    fn test_for_stage_splits_entries() {
+        let mut env_state = EnvironmentState::default();
+        
+        env_state.paths_prepend.push("/opt/homebrew/bin".to_string());
+        env_state.variables.insert("EDITOR".to_string(), "vim".to_string());
+        env_state.variables.insert("AWS_PROFILE".to_string(), "work".to_string());
+        env_state.aliases.insert("ll".to_string(), "ls -la".to_string());
+        env_state.stages.insert("PATH".to_string(), ShellStage::Env);
+        env_state.stages.insert("EDITOR".to_string(), ShellStage::Profile);
+        
+        let env = env_state.for_stage(ShellStage::Env);
+        assert_eq!(env.paths_prepend, vec!["/opt/homebrew/bin".to_string()]);
+        assert!(env.variables.is_empty());
+        assert!(env.aliases.is_empty());
+        
+        let profile = env_state.for_stage(ShellStage::Profile);
+        assert!(profile.paths_prepend.is_empty());
+        assert_eq!(profile.variables.get("EDITOR"), Some(&"vim".to_string()));
+        assert_eq!(profile.variables.len(), 1);
+        
+        let rc = env_state.for_stage(ShellStage::Rc);
+        assert_eq!(rc.variables.get("AWS_PROFILE"), Some(&"work".to_string()));
+        assert_eq!(rc.aliases.len(), 1);
+    }
+    
+    #[test]
+    fn test_unstaged_entries_default_to_rc() {
+        let mut env_state = EnvironmentState::default();
+        env_state.paths_append.push("/opt/bin".to_string());
+        
+        assert_eq!(env_state.stage_of("PATH"), ShellStage::Rc);
+        assert!(env_state.for_stage(ShellStage::Env).is_empty());
+        assert!(!env_state.for_stage(ShellStage::Rc).is_empty());
+    }
+    
+    #[test]
+    fn test_named_dirs_render_per_shell() {
+        use crate::modules::environment::{EnvironmentManager, ShellType};
+        use std::collections::BTreeMap;
+        
+        let named_dirs = BTreeMap::from([
+            ("proj".to_string(), "~/code/project".to_string()),
+            ("logs".to_string(), "/var/log".to_string()),
+        ]);
+        
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).named_dirs_config(&named_dirs);
+        assert_eq!(zsh, "hash -d logs=\"/var/log\"\nhash -d proj=\"$HOME/code/project\"\n");
+        let bash = EnvironmentManager::for_shell(ShellType::Bash).named_dirs_config(&named_dirs);
+        assert!(bash.starts_with("shopt -s cdable_vars\n"));
+        assert!(bash.contains("proj=\"$HOME/code/project\"\n"));
+        let home = BTreeMap::from([("HOME".to_string(), "/srv".to_string())]);
+        assert_eq!(EnvironmentManager::for_shell(ShellType::Bash).named_dirs_config(&home), "shopt -s cdable_vars\n");
+        let fish = EnvironmentManager::for_shell(ShellType::Fish).named_dirs_config(&named_dirs);
+        assert!(fish.contains("set -g proj \"$HOME/code/project\"\n"));
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        let zshrc = dir.path().join(".zshrc");
+        std::fs::write(&zshrc, "export EDITOR=vim\n").unwrap();
+        let env_mgr = EnvironmentManager::for_shell(ShellType::Zsh).with_home(dir.path().to_path_buf());
+        env_mgr.write_named_dirs(&named_dirs).unwrap();
+        assert!(env_mgr.has_named_dirs_block().unwrap());
+        assert!(std::fs::read_to_string(&zshrc).unwrap().contains("hash -d proj="));
+        
+        env_mgr.write_named_dirs(&BTreeMap::new()).unwrap();
+        assert_eq!(std::fs::read_to_string(&zshrc).unwrap(), "export EDITOR=vim\n");
+    }
+    
+    #[test]
+    fn test_legacy_profile_env_is_migrated() {
+        use crate::modules::environment::{EnvironmentManager, ShellType};
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        let legacy = dir.path().join(".local/share/zshrcman/env/profile.env");
+        std::fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        std::fs::write(&legacy, "export EDITOR=vim\n").unwrap();
+        let legacy_str = legacy.to_string_lossy();
+        let zshrc = dir.path().join(".zshrc");
+        std::fs::write(&zshrc, format!(
+            "export PAGER=less\n\n# zshrcman environment\n[ -f {} ] && source {}\n",
+            legacy_str, legacy_str
+        )).unwrap();
+        
+        let mut env_state = EnvironmentState::default();
+        env_state.variables.insert("EDITOR".to_string(), "nvim".to_string());
+        EnvironmentManager::for_shell(ShellType::Zsh)
+            .with_home(dir.path().to_path_buf())
+            .write_shell_config(&env_state)
+            .unwrap();
+        
+        let content = std::fs::read_to_string(&zshrc).unwrap();
+        assert!(content.starts_with("export PAGER=less\n"));
+        assert!(!content.contains("profile.env"));
+        assert!(!content.contains("# zshrcman environment"));
+        assert!(content.contains("export EDITOR=\"nvim\""));
+        assert!(!legacy.exists());
+    }
+    
+    #[test]
+    fn test_nushell_config() {
+        use crate::modules::environment::{EnvironmentManager, ShellType};
+        
+        let mut env_state = EnvironmentState::default();
+        env_state.paths_prepend.push("~/.local/bin".to_string());
+        env_state.paths_append.push("/opt/bin".to_string());
+        env_state.variables.insert("EDITOR".to_string(), "nvim".to_string());
+        env_state.aliases.insert("k".to_string(), "kubectl".to_string());
+        env_state.named_dirs.insert("proj".to_string(), "$HOME/code".to_string());
+        env_state.stages.insert("PATH".to_string(), ShellStage::Env);
+        
+        let nu = EnvironmentManager::for_shell(ShellType::Nushell).generate_shell_config(&env_state).unwrap();
+        assert!(nu.contains("$env.PATH = ($env.PATH | split row (char esep) | prepend [$\"($env.HOME)/.local/bin\"] | append [\"/opt/bin\"])\n"));
+        assert!(nu.contains("$env.EDITOR = \"nvim\"\n"));
+        assert!(nu.contains("alias k = kubectl\n"));
+        assert!(nu.contains("$env.proj = $\"($env.HOME)/code\"\n"));
+        
+        let fish = EnvironmentManager::for_shell(ShellType::Fish).generate_shell_config(&env_state).unwrap();
+        assert!(fish.contains("abbr --add k 'kubectl'\n"));
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        EnvironmentManager::for_shell(ShellType::Nushell)
+            .with_home(dir.path().to_path_buf())
+            .write_shell_config(&env_state)
+            .unwrap();
+        let nushell_dir = dir.path().join(if cfg!(windows) {
+            "AppData/Roaming/nushell"
+        } else if cfg!(target_os = "macos") {
+            "Library/Application Support/nushell"
+        } else {
+            ".config/nushell"
+        });
+        assert!(std::fs::read_to_string(nushell_dir.join("env.nu")).unwrap().contains("$env.PATH"));
+        let config = std::fs::read_to_string(nushell_dir.join("config.nu")).unwrap();
+        assert!(config.contains("alias k = kubectl"));
+        assert!(!config.contains("$env.PATH"));
+    }
+    
+    #[test]
+    fn test_values_are_quoted_per_shell() {
+        use crate::modules::environment::{quote, ShellType};
+        
+        let value = r#"say "hi" to $USER`s 50% \o/"#;
+        assert_eq!(quote(&ShellType::Zsh, value), r#""say \"hi\" to \$USER\`s 50% \\o/""#);
+        assert_eq!(quote(&ShellType::Fish, value), r#""say \"hi\" to \$USER`s 50% \\o/""#);
+        assert_eq!(quote(&ShellType::PowerShell, value), r#""say `"hi`" to `$USER``s 50% \o/""#);
+        assert_eq!(quote(&ShellType::Cmd, value), r#"say "hi" to $USER`s 50%% \o/"#);
+        assert_eq!(quote(&ShellType::Nushell, value), r#""say \"hi\" to $USER`s 50% \\o/""#);
+        
+        assert_eq!(quote(&ShellType::Bash, "~/bin"), "\"$HOME/bin\"");
+        assert_eq!(quote(&ShellType::Bash, "$HOME/$dir"), "\"$HOME/\\$dir\"");
+        assert_eq!(quote(&ShellType::PowerShell, "~\\bin"), "\"$HOME\\bin\"");
+        assert_eq!(quote(&ShellType::Cmd, "~\\bin"), "%USERPROFILE%\\bin");
+        assert_eq!(quote(&ShellType::Nushell, "~/(x)"), "$\"($env.HOME)/\\(x)\"");
+        assert_eq!(quote(&ShellType::Bash, "~user/bin"), "\"~user/bin\"");
+    }
+    
+    #[test]
+    fn test_named_dir_collisions() {
+        use crate::modules::named_dirs::NamedDirs;
+        use std::collections::BTreeMap;
+        
+        let mut named_dirs = NamedDirs::new();
+        named_dirs.add("group 'work'", &BTreeMap::from([("proj".to_string(), "~/work".to_string())]));
+        named_dirs.add("group 'home'", &BTreeMap::from([
+            ("proj".to_string(), "~/home".to_string()),
+            ("notes".to_string(), "~/notes".to_string()),
+            ("my-dir".to_string(), "~/x".to_string()),
+            ("PATH".to_string(), "~/bin".to_string()),
+        ]));
+        named_dirs.add("group 'other'", &BTreeMap::from([("notes".to_string(), "~/notes".to_string())]));
+        assert_eq!(named_dirs.to_map().get("proj"), Some(&"~/work".to_string()));
+        assert_eq!(named_dirs.problems.len(), 3);
+        assert!(!named_dirs.to_map().contains_key("PATH"));
+        
+        named_dirs.override_with("profile 'p'", &BTreeMap::from([("notes".to_string(), "~/p".to_string())]));
+        assert!(!named_dirs.to_map().contains_key("notes"));
+        assert_eq!(named_dirs.problems.len(), 4);
+    }
+}
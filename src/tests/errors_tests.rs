@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::errors::{self, ErrorKind, ResultExt};
+    use crate::modules::install::InstallManager;
+    use crate::modules::installer::Installer;
+    use crate::tests::temp_config;
+    use anyhow::{Context, Result};
+    use std::collections::HashSet;
+    use std::fs;
+    
+    /// Fails to install anything.
+    struct BrokenInstaller;
+    
+    impl Installer for BrokenInstaller {
+        fn name(&self) -> &str {
+            "broken"
+        }
+        
+        fn install(&self, _packages: &[String]) -> Result<()> {
+            anyhow::bail!("broken install failed: exit status 1")
+        }
+        
+        fn uninstall(&self, _packages: &[String]) -> Result<()> {
+            Ok(())
+        }
+        
+        fn version(&self, _package: &str) -> Result<Option<String>> {
+            Ok(None)
+        }
+    }
+    
+    #[test]
+    fn test_classify_errors() {
+        let codes: HashSet<i32> = ErrorKind::ALL.iter().map(|kind| kind.exit_code()).collect();
+        assert_eq!(codes.len(), ErrorKind::ALL.len());
+        assert_eq!(ErrorKind::Other.exit_code(), 1);
+        
+        let tagged: Result<()> = Err(anyhow::anyhow!("push rejected")).kind(ErrorKind::Git);
+        let error = tagged.context("Failed to sync").unwrap_err();
+        assert_eq!(errors::classify(&error), ErrorKind::Git);
+        assert_eq!(format!("{:#}", error), "Failed to sync: push rejected");
+        
+        // Retagging keeps the tag closest to the failure.
+        let retagged = errors::tag(ErrorKind::Other, errors::fail(ErrorKind::Installer, "brew failed"));
+        assert_eq!(errors::classify(&retagged), ErrorKind::Installer);
+        
+        let toml_error = toml::from_str::<toml::Value>("name = ").unwrap_err();
+        assert_eq!(errors::classify(&anyhow::Error::new(toml_error).context("Failed to load group")), ErrorKind::Config);
+        let git_error = git2::Error::from_str("could not resolve host");
+        assert_eq!(errors::classify(&anyhow::Error::new(git_error)), ErrorKind::Git);
+        assert_eq!(errors::classify(&anyhow::anyhow!("something else")), ErrorKind::Other);
+        
+        // An external command's own code is only in the message.
+        let exited = errors::exited("zshrcman-foo", Some(3)).context("Failed to run foo");
+        assert_eq!(errors::classify(&exited), ErrorKind::External);
+        assert_eq!(format!("{:#}", exited), "Failed to run foo: zshrcman-foo exited with code 3");
+        assert_eq!(errors::classify(&errors::exited("zshrcman-foo", None)), ErrorKind::External);
+    }
+    
+    #[test]
+    fn test_install_failures_are_classified() {
+        let (dir, mut config_mgr) = temp_config();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        fs::write(dotfiles.join("groups/broken.toml"), "name = \"broken\"\npackages = [\"jq\"]\n").unwrap();
+        fs::write(dotfiles.join("groups/fine.toml"), "name = \"fine\"\n").unwrap();
+        // The default group is always installed; make it fail too.
+        fs::write(dotfiles.join("groups/default.toml"), "name = \"default\"\ninstaller = \"broken\"\npackages = [\"fd\"]\n").unwrap();
+        config_mgr.add_global_group("broken".to_string()).unwrap();
+        config_mgr.enable_global_group("broken").unwrap();
+        config_mgr.config.install.policy.retries = 0;
+        
+        let home = dir.path().join("home");
+        let mut manager = InstallManager::new(config_mgr).with_home(home.clone()).with_installer(BrokenInstaller);
+        let error = manager.install(true).unwrap_err();
+        assert_eq!(errors::classify(&error), ErrorKind::Installer);
+        assert_eq!(error.to_string(), "2 group(s) failed to install: default, broken");
+        
+        let mut config_mgr = crate::tests::open_config(dir.path().join("config.toml"), dotfiles).unwrap();
+        config_mgr.add_global_group("fine".to_string()).unwrap();
+        config_mgr.enable_global_group("fine").unwrap();
+        config_mgr.config.install.policy.retries = 0;
+        let mut manager = InstallManager::new(config_mgr).with_home(home).with_installer(BrokenInstaller);
+        assert_eq!(errors::classify(&manager.install(true).unwrap_err()), ErrorKind::Partial);
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::events::{self, StreamEvent};
+    use crate::modules::git_mgr::Conflict;
+    use crate::modules::hooks;
+    use crate::modules::install::InstallManager;
+    use crate::tests::temp_config;
+    use chrono::TimeZone;
+    use std::fs;
+    
+    #[test]
+    fn test_event_lines() {
+        let timestamp = chrono::Utc.with_ymd_and_hms(2024, 5, 2, 9, 14, 0).unwrap();
+        let line = events::line(&StreamEvent::PackageInstalled { installer: "brew".to_string(), package: "jq".to_string() }, timestamp).unwrap();
+        assert_eq!(
+            line,
+            "{\"timestamp\":\"2024-05-02T09:14:00Z\",\"event\":\"package_installed\",\"installer\":\"brew\",\"package\":\"jq\"}\n"
+        );
+        
+        let line = events::line(
+            &StreamEvent::SyncConflict {
+                repository: "dotfiles".to_string(),
+                branch: "device/laptop".to_string(),
+                error: "zshrc changed on both".to_string(),
+            },
+            timestamp,
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["event"], "sync_conflict");
+        assert_eq!(value["branch"], "device/laptop");
+    }
+    
+    #[test]
+    fn test_install_emits_events() {
+        let (dir, mut config_mgr) = temp_config();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        fs::create_dir_all(dotfiles.join("files")).unwrap();
+        fs::write(
+            dotfiles.join("groups/eventful.toml"),
+            "name = \"eventful\"\n\n[[files]]\nsource = \"files/tool.conf\"\ntarget = \"~/.tool.conf\"\n",
+        )
+        .unwrap();
+        fs::write(dotfiles.join("files/tool.conf"), "color = true\n").unwrap();
+        config_mgr.add_global_group("eventful".to_string()).unwrap();
+        config_mgr.enable_global_group("eventful").unwrap();
+        
+        let sink = dir.path().join("events.jsonl");
+        events::open_file(&sink).unwrap();
+        let home = dir.path().join("home");
+        let config = config_mgr.config.clone();
+        InstallManager::new(config_mgr).with_home(home.clone()).install(true).unwrap();
+        let conflict = anyhow::Error::new(Conflict("zshrc changed on both".to_string()));
+        hooks::emit_sync(&config, "eventful-repo", "laptop", &Err(conflict));
+        hooks::emit_sync(&config, "eventful-repo", "laptop", &Err(anyhow::anyhow!("Failed to fetch")));
+        events::close();
+        
+        // Other tests may install while the sink is open; keep this one's events.
+        let target = home.join(".tool.conf").display().to_string();
+        let ours: Vec<String> = fs::read_to_string(&sink)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter(|event| {
+                event["group"] == "eventful" || event["path"] == target.as_str() || event["repository"] == "eventful-repo"
+            })
+            .map(|event| event["event"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(ours, vec!["group_started", "file_written", "group_finished", "sync_conflict", "sync_failed"]);
+    }
+}
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::external;
+    
+    #[test]
+    fn test_state_file_is_private_and_removed() {
+        let (_dir, mut config_mgr) = crate::tests::temp_config();
+        config_mgr.config.device.name = "laptop".to_string();
+        
+        let state = external::write_state(&config_mgr).unwrap();
+        let path = state.path().to_path_buf();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("zshrcman-state-") && name.ends_with(".json"));
+        assert_ne!(name, format!("zshrcman-state-{}.json", std::process::id()));
+        
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["device"]["name"], "laptop");
+        
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        
+        drop(state);
+        assert!(!path.exists());
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_command_hands_state_to_the_program() {
+        use std::os::unix::fs::PermissionsExt;
+        
+        let (dir, config_mgr) = crate::tests::temp_config();
+        let program = dir.path().join("zshrcman-report");
+        let out = dir.path().join("out");
+        std::fs::write(&program, "#!/bin/sh\ncat \"$ZSHRCMAN_STATE\" > \"$1\"\necho \"$ZSHRCMAN_CONFIG\" >> \"$1\"\n").unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        
+        let state = external::write_state(&config_mgr).unwrap();
+        let status = external::command(&program, &[out.display().to_string()], &config_mgr, state.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        
+        let report = std::fs::read_to_string(&out).unwrap();
+        assert!(report.contains("\"groups\""));
+        assert!(report.trim_end().ends_with(&config_mgr.config_path().display().to_string()));
+    }
+}
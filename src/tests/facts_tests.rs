@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::GroupCondition;
+    use crate::modules::facts::Facts;
+    
+    fn facts() -> Facts {
+        Facts {
+            hostname: "work-mac".to_string(),
+            os: "macos".to_string(),
+            arch: "aarch64".to_string(),
+            shell: "zsh".to_string(),
+            cpus: 8,
+            wsl: false,
+            package_managers: vec!["brew".to_string()],
+        }
+    }
+    
+    #[test]
+    fn test_condition_matches() {
+        let condition = GroupCondition {
+            os: vec!["macOS".to_string(), "linux".to_string()],
+            requires: vec!["brew".to_string()],
+            wsl: Some(false),
+            ..Default::default()
+        };
+        assert!(condition.check(&facts()).is_ok());
+        assert!(GroupCondition::default().check(&facts()).is_ok());
+    }
+    
+    #[test]
+    fn test_condition_explains_mismatch() {
+        let condition = GroupCondition {
+            os: vec!["linux".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            condition.check(&facts()).unwrap_err(),
+            "os is 'macos', needs one of linux"
+        );
+        
+        let condition = GroupCondition {
+            wsl: Some(true),
+            ..Default::default()
+        };
+        assert!(condition.check(&facts()).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::install::InstallManager;
+    use crate::tests::temp_config;
+    use std::fs;
+    
+    #[test]
+    fn test_group_fingerprint_tracks_inputs() {
+        let (dir, config_mgr) = temp_config();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        fs::create_dir_all(dotfiles.join("files")).unwrap();
+        fs::write(
+            dotfiles.join("groups/tools.toml"),
+            "name = \"tools\"\n\n[[files]]\nsource = \"files/tool.conf\"\ntarget = \"~/.tool.conf\"\n",
+        )
+        .unwrap();
+        fs::write(dotfiles.join("files/tool.conf"), "color = true\n").unwrap();
+        
+        let manager = InstallManager::new(config_mgr).with_home(dir.path().join("home"));
+        let first = manager.fingerprint("tools").unwrap();
+        assert_eq!(manager.fingerprint("tools").unwrap(), first);
+        assert_ne!(manager.fingerprint("other").unwrap(), first);
+        
+        fs::write(dotfiles.join("files/tool.conf"), "color = false\n").unwrap();
+        let changed = manager.fingerprint("tools").unwrap();
+        assert_ne!(changed, first);
+        
+        fs::create_dir_all(dotfiles.join("groups/tools")).unwrap();
+        fs::write(dotfiles.join("groups/tools/install.rhai"), "print(GROUP);\n").unwrap();
+        assert_ne!(manager.fingerprint("tools").unwrap(), changed);
+    }
+}
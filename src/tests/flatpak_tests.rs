@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::flatpak::{self, FlatpakInstaller, FlatpakRef};
+    use crate::modules::installer::{parse_version, Installer};
+    
+    #[test]
+    fn test_flatpak_refs() {
+        let firefox = FlatpakRef::parse("org.mozilla.firefox");
+        assert_eq!(firefox.remote, "flathub");
+        assert_eq!(firefox.app, "org.mozilla.firefox");
+        
+        let beta = FlatpakRef::parse("flathub-beta:org.gimp.GIMP//beta");
+        assert_eq!(beta.remote, "flathub-beta");
+        assert_eq!(beta.app, "org.gimp.GIMP//beta");
+        assert_eq!(beta.id(), "org.gimp.GIMP");
+    }
+    
+    #[test]
+    fn test_flatpak_info_versions() {
+        let info = "\nFirefox - Fast, Private & Safe Web Browser\n\n          ID: org.mozilla.firefox\n         Ref: app/org.mozilla.firefox/x86_64/stable\n      Branch: stable\n     Version: 128.0.3\n";
+        assert_eq!(flatpak::parse_info(info), Some("128.0.3".to_string()));
+        assert_eq!(flatpak::parse_info("          ID: org.example.App\n      Branch: stable\n"), Some("stable".to_string()));
+        assert_eq!(flatpak::parse_info(""), None);
+    }
+    
+    #[test]
+    fn test_flatpak_and_snap_installers() {
+        let installer = FlatpakInstaller::new().dry_run(true);
+        assert_eq!(installer.name(), "flatpak");
+        assert!(installer.install(&["org.mozilla.firefox".to_string()]).is_ok());
+        assert!(installer.uninstall(&["org.mozilla.firefox".to_string()]).is_ok());
+        
+        let snap_list = "Name     Version  Rev   Tracking       Publisher  Notes\nhtop     3.3.0    4310  latest/stable  maxiberta  -\n";
+        assert_eq!(parse_version(snap_list, "htop"), Some("3.3.0".to_string()));
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::InstallStatus;
+    use crate::modules::fleet::{self, DeviceReport};
+    use crate::modules::git_mgr::GitManager;
+    use git2::Repository;
+    use std::fs;
+    
+    fn status(success: bool, error: Option<&str>) -> InstallStatus {
+        InstallStatus {
+            installed: success,
+            success,
+            timestamp: None,
+            error: error.map(String::from),
+            attempts: 1,
+            fingerprint: None,
+        }
+    }
+    
+    #[test]
+    fn test_publish_and_collect_device_reports() {
+        let (dir, mut config_mgr) = crate::tests::temp_config();
+        let repo_path = dir.path().join("repo");
+        Repository::init(&repo_path).unwrap().set_head("refs/heads/main").unwrap();
+        let git = GitManager::open(&repo_path).unwrap();
+        fs::write(repo_path.join("zshrc"), "base\n").unwrap();
+        git.add_all().unwrap();
+        git.commit("Base").unwrap();
+        
+        git.checkout_branch("device/desk", true).unwrap();
+        git.checkout_branch("main", false).unwrap();
+        git.checkout_branch("device/laptop", true).unwrap();
+        
+        config_mgr.config.device.name = "laptop".to_string();
+        config_mgr.config.device.branch = "device/laptop".to_string();
+        config_mgr.config.status.insert("default".to_string(), status(true, None));
+        config_mgr.config.status.insert("k8s".to_string(), status(false, Some("kubectl not found")));
+        config_mgr.config.active_profile = Some("work".to_string());
+        
+        fleet::publish(&git, &repo_path, &config_mgr).unwrap();
+        assert!(git.changed_files().unwrap().is_empty());
+        let published = git.head_id();
+        
+        // Nothing changed, so no new commit.
+        fleet::publish(&git, &repo_path, &config_mgr).unwrap();
+        assert_eq!(git.head_id(), published);
+        
+        let entries = fleet::collect(&git).unwrap();
+        assert_eq!(entries.len(), 2);
+        let laptop = &entries[0];
+        assert_eq!(laptop.device(), "laptop");
+        let report: &DeviceReport = laptop.report.as_ref().unwrap();
+        assert_eq!(report.active_profile.as_deref(), Some("work"));
+        assert_eq!(report.installed_groups, vec!["default".to_string()]);
+        assert_eq!(report.failed_groups.get("k8s").map(String::as_str), Some("kubectl not found"));
+        
+        assert_eq!(entries[1].device(), "desk");
+        assert!(entries[1].report.is_none());
+    }
+    
+    #[test]
+    fn test_publish_skips_other_branches() {
+        let (dir, mut config_mgr) = crate::tests::temp_config();
+        let repo_path = dir.path().join("repo");
+        Repository::init(&repo_path).unwrap().set_head("refs/heads/main").unwrap();
+        let git = GitManager::open(&repo_path).unwrap();
+        fs::write(repo_path.join("zshrc"), "base\n").unwrap();
+        git.add_all().unwrap();
+        git.commit("Base").unwrap();
+        let base = git.head_id();
+        
+        config_mgr.config.device.name = "laptop".to_string();
+        config_mgr.config.device.branch = "device/laptop".to_string();
+        fleet::publish(&git, &repo_path, &config_mgr).unwrap();
+        assert_eq!(git.head_id(), base);
+        assert!(!repo_path.join(fleet::report_path("laptop")).exists());
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{Font, GroupConfig, OsType};
+    use crate::modules::fonts;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+    
+    #[test]
+    fn test_fonts_dir_per_platform() {
+        let home = Path::new("/home/jane");
+        assert_eq!(fonts::fonts_dir(home, &OsType::MacOS), PathBuf::from("/home/jane/Library/Fonts"));
+        assert_eq!(fonts::fonts_dir(home, &OsType::Linux), PathBuf::from("/home/jane/.local/share/fonts"));
+        assert_eq!(
+            fonts::fonts_dir(home, &OsType::Windows),
+            PathBuf::from("/home/jane/AppData/Local/Microsoft/Windows/Fonts")
+        );
+    }
+    
+    #[test]
+    fn test_font_files_from_the_repo() {
+        let root = TempDir::new().unwrap();
+        let dir = root.path().join("fonts").join("Hack");
+        fs::create_dir_all(dir.join("extra")).unwrap();
+        for file in ["Hack-Regular.ttf", "Hack-Bold.TTF", "LICENSE.md", "extra/Hack-Mono.otf"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        
+        let group: GroupConfig = toml::from_str("name = \"fonts\"\n\n[[fonts]]\nname = \"Hack\"\nsource = \"fonts/Hack\"\n").unwrap();
+        let source = fonts::fetch(&group.fonts[0], root.path(), Path::new("/nonexistent")).unwrap();
+        let files: Vec<_> = fonts::font_files(&source).into_iter().map(|file| file.strip_prefix(&dir).unwrap().to_path_buf()).collect();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("Hack-Bold.TTF"), PathBuf::from("Hack-Regular.ttf"), PathBuf::from("extra/Hack-Mono.otf")]
+        );
+    }
+    
+    #[test]
+    fn test_font_needs_one_origin() {
+        let font = Font {
+            name: "Hack".to_string(),
+            url: Some("https://example.com/Hack.zip".to_string()),
+            source: Some(PathBuf::from("fonts/Hack")),
+        };
+        assert!(fonts::fetch(&font, Path::new("/"), Path::new("/tmp")).is_err());
+        
+        let escaping = Font {
+            name: "../../.ssh".to_string(),
+            url: Some("https://example.com/Hack.zip".to_string()),
+            source: None,
+        };
+        assert!(fonts::fetch(&escaping, Path::new("/"), Path::new("/tmp")).is_err());
+        assert!(fonts::is_valid_name("Hack Nerd Font"));
+        assert!(!fonts::is_valid_name(".."));
+        assert!(!fonts::is_valid_name("a\\b"));
+    }
+    
+    #[test]
+    fn test_windows_registry_names() {
+        assert_eq!(fonts::registry_name(Path::new("C:/Fonts/Hack-Regular.ttf")), "Hack-Regular (TrueType)");
+        assert_eq!(fonts::registry_name(Path::new("C:/Fonts/Inter.OTF")), "Inter (OpenType)");
+    }
+}
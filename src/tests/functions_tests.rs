@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{GroupConfig, ShellFunction};
+    use crate::modules::functions::{self, FunctionManager, Functions};
+    use std::fs;
+    use tempfile::TempDir;
+    
+    fn function(name: &str, body: &str) -> ShellFunction {
+        ShellFunction {
+            name: name.to_string(),
+            body: body.to_string(),
+            description: None,
+            shells: vec![],
+        }
+    }
+    
+    #[test]
+    fn test_group_functions_parse() {
+        let group: GroupConfig = toml::from_str(
+            r#"
+name = "tools"
+
+[[functions]]
+name = "mkcd"
+body = '''
+mkdir -p "$1" && cd "$1"
+'''
+shells = ["zsh", "bash"]
+"#,
+        )
+        .unwrap();
+        
+        assert_eq!(group.functions[0].body, "mkdir -p \"$1\" && cd \"$1\"\n");
+        assert_eq!(group.functions[0].render("bash").unwrap(), "mkcd() {\nmkdir -p \"$1\" && cd \"$1\"\n}\n");
+        assert_eq!(group.functions[0].render("fish"), None);
+    }
+    
+    #[test]
+    fn test_deploy_writes_and_prunes_autoload_files() {
+        let home = TempDir::new().unwrap();
+        let manager = FunctionManager::new(home.path());
+        let mut fish_only = function("greet", "echo hi");
+        fish_only.shells = vec!["fish".to_string()];
+        
+        let names = manager.deploy(&[function("mkcd", "mkdir -p \"$1\" && cd \"$1\""), fish_only]).unwrap();
+        assert_eq!(names, vec!["mkcd".to_string()]);
+        assert_eq!(fs::read_to_string(manager.dir().join("mkcd")).unwrap(), "mkdir -p \"$1\" && cd \"$1\"\n");
+        
+        fs::write(manager.dir().join("mine"), "echo mine\n").unwrap();
+        manager.deploy(&[function("up", "cd ..")]).unwrap();
+        assert!(!manager.dir().join("mkcd").exists());
+        assert!(manager.dir().join("up").exists());
+        assert!(manager.dir().join("mine").exists());
+    }
+    
+    #[test]
+    fn test_loader_per_shell_and_clashes() {
+        let mut set = Functions::new();
+        set.add("group 'a'", &[function("up", "cd ..")]);
+        set.add("group 'b'", &[function("up", "cd ../.."), function("../x", "true")]);
+        assert_eq!(set.list().len(), 1);
+        assert_eq!(set.problems.len(), 2);
+        
+        let zsh = functions::loader(set.list(), "zsh");
+        assert!(zsh.contains("fpath=(\"$HOME/.zsh_functions.d\" $fpath)\nautoload -Uz up\n"));
+        assert!(functions::loader(set.list(), "fish").contains("function up\ncd ..\nend\n"));
+        assert!(functions::loader(set.list(), "cmd").is_empty());
+    }
+}
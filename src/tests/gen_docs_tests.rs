@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::gen_docs;
+    use clap::{Arg, ArgAction, Command};
+    use tempfile::TempDir;
+    
+    fn cli() -> Command {
+        Command::new("zshrcman")
+            .about("A Rust-based Zsh/dotfiles manager")
+            .arg(Arg::new("yes").short('y').long("yes").global(true).action(ArgAction::SetTrue).help("Never prompt"))
+            .subcommand(
+                Command::new("sync")
+                    .about("Sync with the dotfiles repo")
+                    .arg(Arg::new("every").long("every").default_value("60").help("Minutes between scheduled syncs")),
+            )
+            .subcommand(Command::new("profile").subcommand(Command::new("switch").arg(Arg::new("name").required(true))))
+            .subcommand(Command::new("gen-docs").hide(true))
+    }
+    
+    #[test]
+    fn test_markdown_reference() {
+        let reference = gen_docs::markdown(cli());
+        assert!(reference.starts_with("# zshrcman command reference\n\n## `zshrcman`\n\nA Rust-based Zsh/dotfiles manager\n"));
+        assert!(reference.contains("- `-y, --yes`: Never prompt\n"));
+        assert!(reference.contains("- `sync`: Sync with the dotfiles repo\n"));
+        assert!(reference.contains(
+            "## `zshrcman sync`\n\nSync with the dotfiles repo\n\n```\nzshrcman sync [OPTIONS]\n```\n\n- `--every <every>`: Minutes between scheduled syncs (default: 60)\n"
+        ));
+        assert!(reference.contains("## `zshrcman profile switch`"));
+        assert!(reference.contains("- `<name>`"));
+        // Global options only under the top-level command; hidden commands not at all.
+        assert_eq!(reference.matches("--yes").count(), 1);
+        assert!(!reference.contains("gen-docs"));
+    }
+    
+    #[test]
+    fn test_man_pages() {
+        let dir = TempDir::new().unwrap();
+        let pages = gen_docs::man_pages(cli(), &dir.path().join("man")).unwrap();
+        let mut names: Vec<String> = pages.iter().map(|page| page.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        names.sort();
+        assert_eq!(names, vec!["zshrcman-profile-switch.1", "zshrcman-profile.1", "zshrcman-sync.1", "zshrcman.1"]);
+        let sync = std::fs::read_to_string(dir.path().join("man/zshrcman-sync.1")).unwrap();
+        assert!(sync.starts_with(".ie \\n(.g .ds Aq \\(aq"));
+        assert!(sync.contains("Minutes between scheduled syncs"));
+    }
+}
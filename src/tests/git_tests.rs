@@ -0,0 +1,269 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{CommitIdentity, ConflictStrategy, RemoteConfig, SigningFormat};
+    use crate::modules::git_mgr::{self, GitManager, SyncMode};
+    use git2::Repository;
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::Path;
+    
+    fn commit_file(git: &GitManager, workdir: &Path, name: &str) {
+        fs::write(workdir.join(name), name).unwrap();
+        git.add_all().unwrap();
+        // Origin is unreachable, so the push as a whole fails after the mirrors.
+        assert!(git.commit_and_push(&format!("Add {}", name), "main").is_err());
+    }
+    
+    #[test]
+    fn test_mirror_receives_pushes_and_serves_fetches() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mirror_path = dir.path().join("mirror.git");
+        Repository::init_bare(&mirror_path).unwrap().set_head("refs/heads/main").unwrap();
+        
+        let mut mirrors = BTreeMap::new();
+        mirrors.insert(
+            "backup".to_string(),
+            RemoteConfig {
+                url: mirror_path.display().to_string(),
+                ..Default::default()
+            },
+        );
+        let missing_origin = dir.path().join("gone.git").display().to_string();
+        
+        let first = dir.path().join("first");
+        let repo = Repository::init(&first).unwrap();
+        repo.remote("origin", &missing_origin).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        let git = GitManager::open(&first).unwrap().with_mirrors(&mirrors).unwrap();
+        commit_file(&git, &first, "a.txt");
+        
+        let mirror = Repository::open_bare(&mirror_path).unwrap();
+        assert!(mirror.find_reference("refs/heads/main").is_ok());
+        
+        let second = dir.path().join("second");
+        let clone = Repository::clone(&mirror_path.display().to_string(), &second).unwrap();
+        clone.remote_set_url("origin", &missing_origin).unwrap();
+        let second_git = GitManager::open(&second).unwrap().with_mirrors(&mirrors).unwrap();
+        
+        commit_file(&git, &first, "b.txt");
+        second_git.fetch_and_pull("main").unwrap();
+        assert!(second.join("b.txt").exists());
+    }
+    
+    #[test]
+    fn test_init_or_clone_clones_into_empty_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let upstream_path = dir.path().join("upstream");
+        let upstream = GitManager::init_or_clone(&upstream_path, None).unwrap();
+        fs::write(upstream_path.join("zshrc"), "zshrc").unwrap();
+        upstream.add_all().unwrap();
+        let _ = upstream.commit_and_push("Add zshrc", "main");
+        
+        // An interrupted clone or `init` can leave an empty directory behind.
+        let clone_path = dir.path().join("clone");
+        fs::create_dir_all(&clone_path).unwrap();
+        let url = upstream_path.display().to_string();
+        GitManager::init_or_clone(&clone_path, Some(&url)).unwrap();
+        assert!(clone_path.join("zshrc").exists());
+        
+        let other = dir.path().join("other");
+        fs::create_dir_all(&other).unwrap();
+        fs::write(other.join("notes"), "").unwrap();
+        assert!(GitManager::init_or_clone(&other, Some(&url)).is_err());
+    }
+    
+    /// A clone whose `laptop` branch and origin's `main` both changed
+    /// `zshrc` since they diverged. The clone's origin is then swapped for a
+    /// bare copy so syncs can push, but it has no `laptop` yet.
+    fn diverged(dir: &Path) -> (GitManager, std::path::PathBuf) {
+        let origin_path = dir.join("origin");
+        Repository::init(&origin_path).unwrap().set_head("refs/heads/main").unwrap();
+        let origin = GitManager::open(&origin_path).unwrap();
+        fs::write(origin_path.join("zshrc"), "base\n").unwrap();
+        origin.add_all().unwrap();
+        let _ = origin.commit_and_push("Base", "main");
+        
+        let clone_path = dir.join("clone");
+        Repository::clone(&origin_path.display().to_string(), &clone_path).unwrap();
+        let clone = GitManager::open(&clone_path).unwrap();
+        clone.checkout_branch("laptop", true).unwrap();
+        fs::write(clone_path.join("zshrc"), "laptop\n").unwrap();
+        clone.add_all().unwrap();
+        let _ = clone.commit_and_push("Laptop change", "laptop");
+        
+        fs::write(origin_path.join("zshrc"), "main\n").unwrap();
+        origin.add_all().unwrap();
+        let _ = origin.commit_and_push("Main change", "main");
+        
+        let bare_path = bare_copy(&origin_path, &dir.join("bare.git"));
+        Repository::open(&clone_path)
+            .unwrap()
+            .remote_set_url("origin", &bare_path.display().to_string())
+            .unwrap();
+        (clone, clone_path)
+    }
+    
+    #[test]
+    fn test_sync_conflict_strategies() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (clone, path) = diverged(&dir.path().join("abort"));
+        let error = clone.sync("main", "laptop", ConflictStrategy::Abort, SyncMode::Both).unwrap_err();
+        assert!(error.to_string().contains("zshrc changed on both"));
+        assert_eq!(fs::read_to_string(path.join("zshrc")).unwrap(), "laptop\n");
+        
+        let (clone, path) = diverged(&dir.path().join("ours"));
+        clone.sync("main", "laptop", ConflictStrategy::Ours, SyncMode::Both).unwrap();
+        assert_eq!(fs::read_to_string(path.join("zshrc")).unwrap(), "laptop\n");
+        
+        let (clone, path) = diverged(&dir.path().join("theirs"));
+        clone.sync("main", "laptop", ConflictStrategy::Theirs, SyncMode::Both).unwrap();
+        assert_eq!(fs::read_to_string(path.join("zshrc")).unwrap(), "main\n");
+        let repo = Repository::open(&path).unwrap();
+        assert_eq!(repo.head().unwrap().shorthand(), Some("laptop"));
+        assert!(!repo.index().unwrap().has_conflicts());
+    }
+    #[test]
+    fn test_sync_saves_uncommitted_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (clone, path) = diverged(&dir.path().join("restored"));
+        fs::write(path.join("k8s.toml"), "edited by hand\n").unwrap();
+        assert!(clone.sync("main", "laptop", ConflictStrategy::Abort, SyncMode::Both).is_err());
+        assert_eq!(clone.changed_files().unwrap(), vec!["k8s.toml".to_string()]);
+        let repo = Repository::open(&path).unwrap();
+        assert_eq!(repo.head().unwrap().peel_to_commit().unwrap().summary(), Some("Laptop change"));
+        
+        let (clone, path) = diverged(&dir.path().join("saved"));
+        fs::write(path.join("k8s.toml"), "edited by hand\n").unwrap();
+        clone.sync("main", "laptop", ConflictStrategy::Theirs, SyncMode::Both).unwrap();
+        assert!(clone.changed_files().unwrap().is_empty());
+        assert_eq!(fs::read_to_string(path.join("k8s.toml")).unwrap(), "edited by hand\n");
+        let repo = Repository::open(&path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary(), Some("Save local changes to k8s.toml before sync"));
+    }
+    #[test]
+    fn test_preview_sync_leaves_branches_alone() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (clone, path) = diverged(dir.path());
+        fs::write(path.join("k8s.toml"), "edited by hand\n").unwrap();
+        let before = clone.head_id();
+        
+        let preview = clone.preview_sync("main", "laptop").unwrap();
+        assert_eq!(preview.uncommitted, vec!["k8s.toml".to_string()]);
+        assert_eq!(preview.main.ahead_behind, Some((0, 1)));
+        // Origin has no `laptop` yet.
+        assert_eq!(preview.device.ahead_behind, None);
+        assert_eq!(preview.overlapping(), vec!["zshrc"]);
+        assert_eq!(clone.head_id(), before);
+        assert_eq!(clone.current_branch().as_deref(), Some("laptop"));
+        assert_eq!(clone.divergence("main").unwrap(), preview.main);
+        assert_eq!(fs::read_to_string(path.join("zshrc")).unwrap(), "laptop\n");
+    }
+    #[test]
+    fn test_commits_use_configured_identity_and_signing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("repo");
+        let repo = Repository::init(&path).unwrap();
+        repo.config().unwrap().set_str("user.name", "Repo User").unwrap();
+        repo.config().unwrap().set_str("user.email", "repo@example.com").unwrap();
+        
+        // Unset fields fall back to git's config.
+        let identity = CommitIdentity {
+            email: Some("jane@example.com".to_string()),
+            ..Default::default()
+        };
+        let git = GitManager::open(&path).unwrap().with_identity(&identity);
+        commit_file(&git, &path, "a.txt");
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.author().name(), Some("Repo User"));
+        assert_eq!(head.author().email(), Some("jane@example.com"));
+        assert!(repo.extract_signature(&head.id(), None).is_err());
+        
+        if which::which("ssh-keygen").is_err() {
+            return;
+        }
+        let key = dir.path().join("id_ed25519");
+        let status = std::process::Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&key)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let identity = CommitIdentity {
+            sign: Some(true),
+            signing_format: Some(SigningFormat::Ssh),
+            signing_key: Some(key.display().to_string()),
+            ..identity
+        };
+        let git = GitManager::open(&path).unwrap().with_identity(&identity);
+        commit_file(&git, &path, "b.txt");
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let (signature, _) = repo.extract_signature(&head.id(), None).unwrap();
+        assert!(signature.as_str().unwrap().contains("BEGIN SSH SIGNATURE"));
+        assert_eq!(head.parent(0).unwrap().summary(), Some("Add a.txt"));
+    }
+    /// A bare clone of `seed`; pushing needs a bare origin.
+    fn bare_copy(seed: &Path, path: &Path) -> std::path::PathBuf {
+        git2::build::RepoBuilder::new()
+            .bare(true)
+            .clone(&seed.display().to_string(), path)
+            .unwrap();
+        path.to_path_buf()
+    }
+    
+    #[test]
+    fn test_sync_pull_only_and_push_only() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let seed_path = dir.path().join("seed");
+        Repository::init(&seed_path).unwrap().set_head("refs/heads/main").unwrap();
+        let seed = GitManager::open(&seed_path).unwrap();
+        fs::write(seed_path.join("zshrc"), "base\n").unwrap();
+        seed.add_all().unwrap();
+        let _ = seed.commit_and_push("Base", "main");
+        
+        let origin_path = bare_copy(&seed_path, &dir.path().join("origin.git"));
+        let clone_path = dir.path().join("clone");
+        Repository::clone(&origin_path.display().to_string(), &clone_path).unwrap();
+        let clone = GitManager::open(&clone_path).unwrap();
+        clone.checkout_branch("laptop", true).unwrap();
+        fs::write(clone_path.join("k8s.toml"), "edited\n").unwrap();
+        
+        let origin = Repository::open_bare(&origin_path).unwrap();
+        clone.sync("main", "laptop", ConflictStrategy::Abort, SyncMode::PullOnly).unwrap();
+        assert!(origin.find_branch("laptop", git2::BranchType::Local).is_err());
+        assert!(clone.changed_files().unwrap().is_empty());
+        
+        clone.sync("main", "laptop", ConflictStrategy::Abort, SyncMode::PushOnly).unwrap();
+        let pushed = origin.find_branch("laptop", git2::BranchType::Local).unwrap();
+        assert_eq!(pushed.get().peel_to_commit().unwrap().id().to_string(), clone.head_id().unwrap());
+    }
+    #[test]
+    fn test_delete_branch_here_and_on_origin() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (_, clone_path) = diverged(dir.path());
+        let origin_path = bare_copy(&clone_path, &dir.path().join("origin.git"));
+        let clone = GitManager::open(&clone_path).unwrap();
+        Repository::open(&clone_path)
+            .unwrap()
+            .remote_set_url("origin", &origin_path.display().to_string())
+            .unwrap();
+        clone.fetch_tracking("*").unwrap();
+        
+        assert!(clone.delete_branch("laptop").is_err());
+        clone.checkout_branch("main", false).unwrap();
+        clone.delete_branch("laptop").unwrap();
+        
+        let origin = Repository::open_bare(&origin_path).unwrap();
+        assert!(origin.find_branch("laptop", git2::BranchType::Local).is_err());
+        let repo = Repository::open(&clone_path).unwrap();
+        assert!(repo.find_branch("laptop", git2::BranchType::Local).is_err());
+        assert!(repo.find_branch("origin/laptop", git2::BranchType::Remote).is_err());
+    }
+    #[test]
+    fn test_short_id_never_panics() {
+        assert_eq!(git_mgr::short_id("0123456789abcdef"), "01234567");
+        assert_eq!(git_mgr::short_id("0123"), "0123");
+        assert_eq!(git_mgr::short_id(""), "");
+        assert_eq!(git_mgr::short_id("0123456é9"), "0123456é9");
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{GroupConfig, InstallerType};
+    use crate::modules::gpg;
+    use std::fs;
+    use tempfile::TempDir;
+    
+    #[test]
+    fn test_gpg_group_config() {
+        let mut group: GroupConfig = toml::from_str("name = \"gpg\"\ngpg_keys = [\"jane.pub.asc\", \"jane.sec.asc\"]\nsigning_key = \"0xABCD1234\"\n").unwrap();
+        assert!(matches!(InstallerType::from_group_name(&group.name), InstallerType::Gpg));
+        assert_eq!(group.gpg_keys, vec!["jane.pub.asc", "jane.sec.asc"]);
+        
+        let device: GroupConfig = toml::from_str("name = \"gpg\"\ngpg_keys = [\"work.sec.asc\"]\nsigning_key = \"0xFFFF0000\"\n").unwrap();
+        group.merge(device);
+        assert_eq!(group.gpg_keys.len(), 3);
+        assert_eq!(group.signing_key.as_deref(), Some("0xFFFF0000"));
+    }
+    
+    #[test]
+    fn test_configure_git_signing() {
+        if which::which("git").is_err() {
+            return;
+        }
+        let home = TempDir::new().unwrap();
+        let gitconfig = home.path().join(".gitconfig");
+        fs::write(&gitconfig, "[user]\n\temail = jane@example.com\n").unwrap();
+        
+        gpg::configure_git(&gitconfig, "0xABCD1234").unwrap();
+        
+        let config = fs::read_to_string(&gitconfig).unwrap();
+        assert!(config.contains("email = jane@example.com"));
+        assert!(config.contains("signingkey = 0xABCD1234"));
+        assert!(config.contains("[commit]\n\tgpgsign = true"));
+    }
+}
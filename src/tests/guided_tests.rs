@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::guided::{self, Task};
+    use crate::tests::non_interactive;
+    
+    #[test]
+    fn test_menu_tasks() {
+        assert_eq!(Task::ALL.first(), Some(&Task::Status));
+        assert_eq!(Task::ALL.last(), Some(&Task::Quit));
+        for task in Task::ALL {
+            let label = task.label();
+            assert!(label.starts_with(task.description()));
+            match task.command() {
+                Some(command) => assert!(command.starts_with("zshrcman ") && label.contains(command)),
+                None => assert_eq!(task, Task::Quit),
+            }
+        }
+    }
+    
+    #[test]
+    fn test_pick_preselects_current() {
+        let _non_interactive = non_interactive();
+        assert!(!guided::available());
+        
+        let profiles = vec!["work".to_string(), "home".to_string(), "oss".to_string()];
+        assert_eq!(guided::pick("Switch to profile", &profiles, Some("oss")).unwrap().as_deref(), Some("oss"));
+        assert_eq!(guided::pick("Switch to profile", &profiles, None).unwrap().as_deref(), Some("home"));
+        assert_eq!(guided::pick("Switch to profile", &profiles, Some("gone")).unwrap().as_deref(), Some("home"));
+        assert_eq!(guided::pick("Switch to profile", &[], None).unwrap(), None);
+    }
+}
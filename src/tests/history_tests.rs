@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::history::{History, HistoryEntry, Snapshot};
+    use std::fs;
+    
+    #[test]
+    fn test_undo_walks_back_through_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = dir.path().join("config.toml");
+        let history = History::new(dir.path());
+        
+        let snapshot = Snapshot::take(&config).unwrap();
+        fs::write(&config, "groups = [\"a\"]\n").unwrap();
+        snapshot.record(&history, "group add a").unwrap();
+        
+        let snapshot = Snapshot::take(&config).unwrap();
+        fs::write(&config, "groups = [\"a\", \"b\"]\n").unwrap();
+        snapshot.record(&history, "group add b").unwrap();
+        
+        // Commands that leave the config alone aren't recorded.
+        Snapshot::take(&config).unwrap().record(&history, "status").unwrap();
+        assert_eq!(history.entries().unwrap().len(), 2);
+        
+        assert_eq!(history.undo(&config, false).unwrap().action, "group add b");
+        assert_eq!(fs::read_to_string(&config).unwrap(), "groups = [\"a\"]\n");
+        
+        assert_eq!(history.undo(&config, false).unwrap().action, "group add a");
+        assert!(!config.exists());
+        
+        assert!(history.undo(&config, false).is_err());
+        assert_eq!(history.entries().unwrap().len(), 4);
+    }
+    
+    #[test]
+    fn test_undo_refuses_after_manual_edit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = dir.path().join("config.toml");
+        let history = History::new(dir.path());
+        fs::write(&config, "a = 1\n").unwrap();
+        
+        let snapshot = Snapshot::take(&config).unwrap();
+        fs::write(&config, "a = 2\n").unwrap();
+        snapshot.record(&history, "config set").unwrap();
+        fs::write(&config, "a = 3\n").unwrap();
+        
+        assert!(history.undo(&config, false).is_err());
+        history.undo(&config, true).unwrap();
+        assert_eq!(fs::read_to_string(&config).unwrap(), "a = 1\n");
+    }
+    
+    #[test]
+    fn test_undo_keeps_install_status_and_secrets() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = dir.path().join("config.toml");
+        let history = History::new(dir.path());
+        fs::write(&config, "theme = \"plain\"\n\n[repository]\ntoken = \"s3cret\"\n").unwrap();
+        
+        let snapshot = Snapshot::take(&config).unwrap();
+        fs::write(&config, "theme = \"fancy\"\n\n[repository]\ntoken = \"s3cret\"\n").unwrap();
+        snapshot.record(&history, "theme set fancy").unwrap();
+        let entries = history.entries().unwrap();
+        assert!(!entries[0].before.as_deref().unwrap().contains("s3cret"));
+        
+        // An install since then only touched the status.
+        fs::write(&config, "theme = \"fancy\"\n\n[repository]\ntoken = \"s3cret\"\n\n[status.brew]\ninstalled = true\n").unwrap();
+        history.undo(&config, false).unwrap();
+        let restored: toml::Table = fs::read_to_string(&config).unwrap().parse().unwrap();
+        assert_eq!(restored["theme"].as_str(), Some("plain"));
+        assert_eq!(restored["repository"]["token"].as_str(), Some("s3cret"));
+        assert_eq!(restored["status"]["brew"]["installed"].as_bool(), Some(true));
+    }
+    
+    #[test]
+    fn test_changed_lines_keep_order() {
+        let entry = HistoryEntry {
+            id: 1,
+            timestamp: chrono::Utc::now(),
+            action: "reorder".to_string(),
+            before: Some("a\nb\nc\n".to_string()),
+            after: Some("b\na\nc\nc\n".to_string()),
+            undoes: None,
+        };
+        assert_eq!(entry.changed_lines(), vec!["- a", "+ a", "+ c"]);
+    }
+}
@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{InstallSettings, RetryPolicy};
+    use crate::modules::install::InstallManager;
+    use crate::modules::installer::{parse_version, CommandInstaller, Installer, InstallerRegistry};
+    use crate::modules::state_manager::InstallationStateManager;
+    use anyhow::Result;
+    use std::sync::{Arc, Mutex};
+    
+    /// Records install/uninstall calls; `node` counts as installed.
+    struct FakeInstaller {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+    
+    impl Installer for FakeInstaller {
+        fn name(&self) -> &str {
+            "fake"
+        }
+        
+        fn install(&self, packages: &[String]) -> Result<()> {
+            self.calls.lock().unwrap().push(format!("install {}", packages.join(" ")));
+            Ok(())
+        }
+        
+        fn uninstall(&self, packages: &[String]) -> Result<()> {
+            self.calls.lock().unwrap().push(format!("uninstall {}", packages.join(" ")));
+            Ok(())
+        }
+        
+        fn version(&self, package: &str) -> Result<Option<String>> {
+            Ok((package == "node").then(|| "20.1.0".to_string()))
+        }
+    }
+    
+    #[test]
+    fn test_parse_version_formats() {
+        assert_eq!(parse_version("ripgrep 14.1.0\n", "ripgrep"), Some("14.1.0".to_string()));
+        assert_eq!(
+            parse_version("/usr/lib\n└── typescript@5.4.5\n", "typescript"),
+            Some("5.4.5".to_string())
+        );
+        assert_eq!(parse_version("ripgrep-all 0.10.6\n", "ripgrep"), None);
+        assert_eq!(parse_version("", "ripgrep"), None);
+    }
+    
+    #[test]
+    fn test_registry_replaces_by_name() {
+        let mut registry = InstallerRegistry::builtin(true, &InstallSettings::default());
+        assert_eq!(registry.names(), vec!["brew", "choco", "flatpak", "mas", "nix", "npm", "pnpm", "scoop", "snap", "vscode", "windows", "winget"]);
+        
+        registry.register(CommandInstaller::new("brew", "echo", &[], &[]).query_args(&["node", "1.0"]));
+        registry.register(CommandInstaller::new("cargo", "cargo", &["install"], &["uninstall"]));
+        // Re-registering the built-ins, as `dry_run` does, keeps the custom brew.
+        registry.register_builtins(false, &InstallSettings::default());
+        assert_eq!(registry.get("brew").unwrap().version("node").unwrap(), Some("1.0".to_string()));
+        
+        assert_eq!(registry.names(), vec!["brew", "cargo", "choco", "flatpak", "mas", "nix", "npm", "pnpm", "scoop", "snap", "vscode", "windows", "winget"]);
+        assert_eq!(registry.get("cargo").map(|i| i.name()), Some("cargo"));
+        assert!(registry.get("apt").is_none());
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_location_resolves_installed_executable() {
+        // `echo sh 5.2 sh` reads like a listing with sh at 5.2.
+        let installed = CommandInstaller::new("shell", "echo", &[], &[]).query_args(&["sh", "5.2"]);
+        let location = installed.location("sh").unwrap().unwrap();
+        assert!(location.is_absolute());
+        
+        // A backend that reports its prefix is asked first, and its link is kept.
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path().join("opt/sh");
+        std::fs::create_dir_all(prefix.join("bin")).unwrap();
+        std::os::unix::fs::symlink(&location, prefix.join("bin/sh")).unwrap();
+        let program = dir.path().join("pm");
+        let script = format!("#!/bin/sh\nif [ \"$1\" = --prefix ]; then echo {}; else echo sh 5.2; fi\n", prefix.display());
+        std::fs::write(&program, script).unwrap();
+        std::fs::set_permissions(&program, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+        let prefixed = CommandInstaller::new("pm", program.to_str().unwrap(), &[], &[]).query_args(&["list"]).prefix_args(&["--prefix"]);
+        assert_eq!(prefixed.location("sh").unwrap(), Some(prefix.join("bin/sh")));
+        
+        let missing = CommandInstaller::new("shell", "false", &[], &[]).query_args(&["list"]);
+        assert_eq!(missing.location("sh").unwrap(), None);
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_stream_command_keeps_stderr_tail() {
+        use crate::modules::output::stream_command;
+        use std::process::Command;
+        
+        let outcome = stream_command(
+            Command::new("sh").args(["-c", "echo progress; echo 'no such formula' >&2; exit 3"]),
+            "fake install",
+            None,
+        )
+        .unwrap();
+        
+        assert_eq!(outcome.status.code(), Some(3));
+        assert_eq!(outcome.stderr_tail, "no such formula");
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_stream_command_kills_on_timeout() {
+        use crate::modules::output::stream_command;
+        use std::process::Command;
+        use std::time::Duration;
+        
+        let started = std::time::Instant::now();
+        let outcome = stream_command(
+            Command::new("sh").args(["-c", "echo started; sleep 30; echo done"]),
+            "slow install",
+            Some(Duration::from_millis(300)),
+        )
+        .unwrap();
+        
+        assert!(outcome.timed_out);
+        assert!(!outcome.status.success());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+    
+    #[test]
+    fn test_retry_policy_per_installer() {
+        let settings: InstallSettings = toml::from_str(
+            "retries = 1\nbackoff_secs = 3\ntimeout_secs = 600\n\n[installers.npm]\nretries = 4\ntimeout_secs = 0\n",
+        )
+        .unwrap();
+        
+        let brew = settings.policy_for("brew");
+        assert_eq!(brew.retries, 1);
+        assert_eq!(brew.timeout_secs, 600);
+        assert_eq!(brew.backoff(1).as_secs(), 3);
+        assert_eq!(brew.backoff(3).as_secs(), 12);
+        
+        let npm = settings.policy_for("npm");
+        assert_eq!(npm.retries, 4);
+        assert_eq!(npm.timeout(), None);
+        assert_eq!(npm.backoff(1).as_secs(), 3);
+        assert_ne!(settings.policy, RetryPolicy::default());
+    }
+    
+    #[test]
+    fn test_held_packages_are_left_alone() {
+        let (dir, mut config_mgr) = crate::tests::temp_config();
+        let groups = config_mgr.dotfiles_path().join("groups");
+        std::fs::create_dir_all(&groups).unwrap();
+        std::fs::write(groups.join("fake.toml"), "name = \"fake\"\npackages = [\"node\", \"jq\", \"rg\"]\n").unwrap();
+        config_mgr.add_global_group("fake".to_string()).unwrap();
+        config_mgr.enable_global_group("fake").unwrap();
+        assert!(config_mgr.hold_package("node").unwrap());
+        assert!(config_mgr.hold_package("jq").unwrap());
+        assert!(!config_mgr.hold_package("jq").unwrap());
+        
+        let calls = Arc::new(Mutex::new(vec![]));
+        let mut manager = InstallManager::new(config_mgr)
+            .with_home(dir.path().join("home"))
+            .with_installer(FakeInstaller { calls: calls.clone() });
+        manager.install(true).unwrap();
+        manager.remove_all().unwrap();
+        
+        // A held package that is missing still gets installed.
+        assert_eq!(*calls.lock().unwrap(), vec!["install jq rg", "uninstall rg"]);
+    }
+    
+    #[test]
+    fn test_pinned_packages_need_force_to_remove() {
+        let (dir, mut config_mgr) = crate::tests::temp_config();
+        let groups = config_mgr.dotfiles_path().join("groups");
+        std::fs::create_dir_all(&groups).unwrap();
+        std::fs::write(groups.join("fake.toml"), "name = \"fake\"\npackages = [\"node\", \"rg\"]\n").unwrap();
+        config_mgr.add_global_group("fake".to_string()).unwrap();
+        config_mgr.enable_global_group("fake").unwrap();
+        let mut state_mgr = InstallationStateManager::new(config_mgr);
+        assert!(state_mgr.pin("node").unwrap());
+        assert!(!state_mgr.pin("node").unwrap());
+        
+        let paths = || (dir.path().join("config.toml"), dir.path().join("dotfiles"));
+        let calls = Arc::new(Mutex::new(vec![]));
+        let manager = |force_pinned| {
+            InstallManager::new(crate::tests::open_config(paths().0, paths().1).unwrap())
+                .with_home(dir.path().join("home"))
+                .with_installer(FakeInstaller { calls: calls.clone() })
+                .force_pinned(force_pinned)
+        };
+        for force_pinned in [false, true] {
+            let mut manager = manager(force_pinned);
+            manager.install(true).unwrap();
+            manager.remove_all().unwrap();
+        }
+        
+        // Installed pinned packages aren't upgraded, and only forced removals take them.
+        assert_eq!(*calls.lock().unwrap(), vec!["install rg", "uninstall rg", "install rg", "uninstall node rg"]);
+    }
+    
+    #[test]
+    fn test_group_packages_are_recorded_as_dependencies() {
+        let (dir, mut config_mgr) = crate::tests::temp_config();
+        let groups = config_mgr.dotfiles_path().join("groups");
+        std::fs::create_dir_all(&groups).unwrap();
+        std::fs::write(groups.join("fake.toml"), "name = \"fake\"\npackages = [\"node\", \"jq\", \"rg\"]\n").unwrap();
+        config_mgr.add_global_group("fake".to_string()).unwrap();
+        config_mgr.enable_global_group("fake").unwrap();
+        
+        let calls = Arc::new(Mutex::new(vec![]));
+        let reload = || crate::tests::open_config(dir.path().join("config.toml"), dir.path().join("dotfiles")).unwrap();
+        let mut manager = InstallManager::new(config_mgr)
+            .with_home(dir.path().join("home"))
+            .with_installer(FakeInstaller { calls: calls.clone() });
+        manager.install(true).unwrap();
+        
+        // node was installed already, so only jq and rg belong to the group.
+        let mut config_mgr = reload();
+        let installations = &config_mgr.config.installations;
+        assert!(!installations.contains_key("node"));
+        assert_eq!(installations["rg"].installed_by.to_string(), "dependency of group fake");
+        assert_eq!(installations["rg"].installer_type, "fake");
+        assert!(installations["rg"].required_by.contains("group fake"));
+        
+        // Another group needing jq keeps it installed when this one goes.
+        config_mgr.record_group_packages("other", "fake", &["jq".to_string()]).unwrap();
+        let mut manager = InstallManager::new(config_mgr)
+            .with_home(dir.path().join("home"))
+            .with_installer(FakeInstaller { calls: calls.clone() });
+        manager.remove_all().unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec!["install node jq rg", "uninstall node rg"]);
+        
+        let installations = reload().config.installations;
+        assert!(!installations.contains_key("rg"));
+        assert_eq!(installations["jq"].required_by.iter().collect::<Vec<_>>(), vec!["group other"]);
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{GroupConfig, LazyLoad};
+    use crate::modules::lazy_load::LazyLoads;
+    
+    fn group(toml: &str) -> GroupConfig {
+        toml::from_str(toml).unwrap()
+    }
+    
+    #[test]
+    fn test_presets_and_custom_entries_render_stubs() {
+        let node = group(
+            r#"
+name = "node"
+
+[[lazy]]
+name = "nvm"
+
+[[lazy]]
+name = "rbenv"
+commands = ["rbenv", "ruby"]
+init = 'eval "$(command rbenv init -)"'
+"#,
+        );
+        let mut lazy = LazyLoads::new();
+        lazy.add("group 'node'", &node.lazy);
+        
+        assert!(lazy.problems.is_empty());
+        let script = lazy.render();
+        assert!(script.contains("nvm node npm npx() {\n    unfunction nvm node npm npx 2>/dev/null\n    export NVM_DIR="));
+        assert!(script.contains("\n# rbenv\nrbenv ruby() {\n    unfunction rbenv ruby 2>/dev/null\n    eval \"$(command rbenv init -)\"\n    \"$0\" \"$@\"\n}\n"));
+    }
+    
+    #[test]
+    fn test_unknown_names_and_duplicate_commands_are_reported() {
+        let entry = |name: &str, commands: &[&str]| LazyLoad {
+            name: name.to_string(),
+            commands: commands.iter().map(|command| command.to_string()).collect(),
+            init: Some("true".to_string()),
+        };
+        let mut lazy = LazyLoads::new();
+        lazy.add("group 'a'", &[LazyLoad { name: "sdkman".to_string(), commands: vec![], init: None }]);
+        lazy.add("group 'b'", &[entry("kube", &["kubectl"]), entry("k8s", &["kubectl", "helm"]), entry("bad", &["a b"])]);
+        
+        assert_eq!(lazy.problems.len(), 3);
+        assert_eq!(lazy.stubs()[1].commands, vec!["helm"]);
+        assert!(LazyLoads::new().render().is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{DefaultsValue, GroupConfig};
+    use crate::modules::changeset::{Change, ChangeSet};
+    use crate::modules::macos_defaults;
+    
+    #[test]
+    fn test_macos_defaults_config() {
+        let group: GroupConfig = toml::from_str(
+            r#"
+name = "macos"
+
+[[macos_defaults]]
+domain = "com.apple.dock"
+key = "autohide"
+value = true
+restart = "Dock"
+
+[[macos_defaults]]
+domain = "NSGlobalDomain"
+key = "KeyRepeat"
+value = 2
+
+[[macos_defaults]]
+domain = "com.apple.dock"
+key = "autohide-delay"
+value = 0.5
+
+[[macos_defaults]]
+domain = "com.apple.finder"
+key = "FXPreferredViewStyle"
+value = "Nlsv"
+"#,
+        )
+        .unwrap();
+        
+        let args: Vec<Vec<String>> = group
+            .macos_defaults
+            .iter()
+            .map(|entry| macos_defaults::write_args(&entry.domain, &entry.key, &entry.value))
+            .collect();
+        assert_eq!(args[0], ["write", "com.apple.dock", "autohide", "-bool", "true"]);
+        assert_eq!(args[1], ["write", "NSGlobalDomain", "KeyRepeat", "-int", "2"]);
+        assert_eq!(args[2], ["write", "com.apple.dock", "autohide-delay", "-float", "0.5"]);
+        assert_eq!(args[3], ["write", "com.apple.finder", "FXPreferredViewStyle", "-string", "Nlsv"]);
+        assert_eq!(group.macos_defaults[0].restart.as_deref(), Some("Dock"));
+    }
+    
+    #[test]
+    fn test_parse_defaults_read() {
+        assert_eq!(macos_defaults::parse("Type is boolean\n", "1\n"), Some(DefaultsValue::Bool(true)));
+        assert_eq!(macos_defaults::parse("Type is integer\n", "15\n"), Some(DefaultsValue::Int(15)));
+        assert_eq!(macos_defaults::parse("Type is float\n", "0.25\n"), Some(DefaultsValue::Float(0.25)));
+        assert_eq!(macos_defaults::parse("Type is string\n", "Nlsv\n"), Some(DefaultsValue::String("Nlsv".to_string())));
+        assert_eq!(macos_defaults::parse("Type is array\n", "(\n)\n"), None);
+    }
+    
+    #[test]
+    fn test_previous_values_survive_the_change_set() {
+        let mut changes = ChangeSet::new("macos");
+        changes.changes.push(Change::DefaultsWritten {
+            domain: "com.apple.dock".to_string(),
+            key: "tilesize".to_string(),
+            previous: Some(DefaultsValue::Int(48)),
+        });
+        changes.changes.push(Change::DefaultsWritten {
+            domain: "com.apple.dock".to_string(),
+            key: "autohide".to_string(),
+            previous: None,
+        });
+        
+        let loaded: ChangeSet = serde_json::from_str(&serde_json::to_string(&changes).unwrap()).unwrap();
+        match (&loaded.changes[0], &loaded.changes[1]) {
+            (Change::DefaultsWritten { previous: first, .. }, Change::DefaultsWritten { previous: second, .. }) => {
+                assert_eq!(first, &Some(DefaultsValue::Int(48)));
+                assert_eq!(second, &None);
+            }
+            _ => panic!("expected defaults changes"),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{GroupConfig, InstallerType};
+    use crate::modules::installer::Installer;
+    use crate::modules::mas::{self, MasInstaller};
+    
+    #[test]
+    fn test_mas_app_names() {
+        let group: GroupConfig = toml::from_str(
+            "name = \"mas\"\npackages = [\"Xcode\", \"904280696\"]\n\n[apps]\nXcode = 497799835\n",
+        )
+        .unwrap();
+        assert!(matches!(InstallerType::from_group_name(&group.name), InstallerType::Mas));
+        assert_eq!(group.package_ids(), vec!["497799835".to_string(), "904280696".to_string()]);
+        
+        let mut device: GroupConfig = toml::from_str("name = \"mas\"\n\n[apps]\nXcode = 1\n").unwrap();
+        device.packages.push("Xcode".to_string());
+        let mut merged = group.clone();
+        merged.merge(device);
+        assert_eq!(merged.package_ids()[0], "1");
+    }
+    
+    #[test]
+    fn test_mas_output() {
+        let list = "497799835  Xcode        (15.4)\n904280696  Things 3     (3.20.1)\n";
+        assert_eq!(mas::parse_list(list, "904280696"), Some("3.20.1".to_string()));
+        assert_eq!(mas::parse_list(list, "1"), None);
+        
+        assert!(mas::is_signed_out("Error: Not signed in\n"));
+        assert!(!mas::is_signed_out("someone@example.com\n"));
+        assert!(!mas::is_signed_out("Error: This command is not supported on this macOS version due to changes in macOS.\n"));
+    }
+    
+    #[test]
+    fn test_mas_dry_run() {
+        let installer = MasInstaller::new().dry_run(true);
+        assert_eq!(installer.name(), "mas");
+        assert!(installer.install(&["497799835".to_string()]).is_ok());
+        assert!(installer.uninstall(&["497799835".to_string()]).is_ok());
+        assert!(installer.install(&["Xcode".to_string()]).is_err());
+    }
+}
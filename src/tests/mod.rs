@@ -0,0 +1,105 @@
+use crate::modules::config::ConfigManager;
+use crate::modules::prompt;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+mod alias_tests;
+mod alias_usage_tests;
+mod apply_tests;
+mod auto_switch_tests;
+mod bench_tests;
+mod bin_links_tests;
+mod bootstrap_tests;
+mod changeset_tests;
+mod completions_tests;
+mod config_tests;
+mod cron_tests;
+mod daemon_tests;
+mod diagnostics_tests;
+mod disk_usage_tests;
+mod editor_tests;
+mod env_layers_tests;
+mod environment_tests;
+mod errors_tests;
+mod events_tests;
+mod external_tests;
+mod facts_tests;
+mod fingerprint_tests;
+mod flatpak_tests;
+mod fleet_tests;
+mod fonts_tests;
+mod functions_tests;
+mod gen_docs_tests;
+mod git_tests;
+mod gpg_tests;
+mod guided_tests;
+mod history_tests;
+mod hooks_tests;
+mod installer_tests;
+mod lazy_load_tests;
+mod macos_defaults_tests;
+mod mas_tests;
+mod nix_tests;
+mod oplog_tests;
+mod plugin_tests;
+mod prefix_tests;
+mod preflight_tests;
+mod profile_hooks_tests;
+mod profile_identity_tests;
+mod profile_tests;
+mod repos_tests;
+mod scripting_tests;
+mod search_tests;
+mod secrets_tests;
+mod services_tests;
+mod session_tests;
+mod shell_env_tests;
+mod sources_tests;
+mod stats_tests;
+mod suggest_tests;
+mod sync_timer_tests;
+mod template_tests;
+mod test_run_tests;
+mod theme_tests;
+mod tidy_tests;
+mod validate_tests;
+mod verify_tests;
+mod vscode_tests;
+mod windows_env_tests;
+mod windows_packages_tests;
+mod which_tests;
+mod zcompile_tests;
+mod zsh_options_tests;
+mod zsh_plugins_tests;
+
+/// A ConfigManager backed by a throwaway directory, so tests never read or
+/// write the real `~/.config/zshrcman`.
+pub fn temp_config() -> (TempDir, ConfigManager) {
+    let dir = TempDir::new().unwrap();
+    let config = open_config(dir.path().join("config.toml"), dir.path().join("dotfiles")).unwrap();
+    (dir, config)
+}
+
+/// Makes prompts take their defaults until dropped, then restores the
+/// previous setting so the flag doesn't leak into other tests.
+pub struct NonInteractive {
+    previous: bool,
+}
+
+pub fn non_interactive() -> NonInteractive {
+    let previous = prompt::is_non_interactive();
+    prompt::set_non_interactive(true);
+    NonInteractive { previous }
+}
+
+impl Drop for NonInteractive {
+    fn drop(&mut self) {
+        prompt::set_non_interactive(self.previous);
+    }
+}
+
+/// Opens the config at these paths without the `ZSHRCMAN_*` variables of
+/// the environment the tests run in.
+pub fn open_config(config_path: PathBuf, dotfiles_path: PathBuf) -> anyhow::Result<ConfigManager> {
+    ConfigManager::with_env(config_path, dotfiles_path, std::iter::empty())
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::InstallScope;
+    use crate::modules::installer::{Installer, InstallerRegistry};
+    use crate::modules::nix::{self, NixInstaller};
+    use crate::modules::state_manager::InstallationStateManager;
+    use crate::tests::temp_config;
+    use anyhow::Result;
+    use std::path::{Path, PathBuf};
+    
+    const STORE_PATH: &str = "/nix/store/0c5h8w1zr2f8m6yzzyxfdsmz3wq9gzf4-ripgrep-14.1.0";
+    
+    /// Has ripgrep in the nix store.
+    struct FakeNix;
+    
+    impl Installer for FakeNix {
+        fn name(&self) -> &str {
+            "nix"
+        }
+        
+        fn install(&self, _packages: &[String]) -> Result<()> {
+            Ok(())
+        }
+        
+        fn uninstall(&self, _packages: &[String]) -> Result<()> {
+            Ok(())
+        }
+        
+        fn version(&self, _package: &str) -> Result<Option<String>> {
+            Ok(nix::store_path_version(Path::new(STORE_PATH)))
+        }
+        
+        fn location(&self, _package: &str) -> Result<Option<PathBuf>> {
+            Ok(Some(Path::new(STORE_PATH).join("bin").join("rg")))
+        }
+    }
+    
+    #[test]
+    fn test_nix_installables() {
+        assert_eq!(nix::installable("ripgrep"), "nixpkgs#ripgrep");
+        assert_eq!(nix::installable("github:helix-editor/helix#helix"), "github:helix-editor/helix#helix");
+        assert_eq!(nix::attr_name("nixpkgs#nodePackages.pnpm"), "pnpm");
+        assert_eq!(nix::attr_name("legacyPackages.x86_64-linux.ripgrep"), "ripgrep");
+        
+        assert!(nix::has_flakes("cores = 0\nexperimental-features = flakes nix-command\n"));
+        assert!(!nix::has_flakes("experimental-features = nix-command\n"));
+        assert!(!nix::has_flakes(""));
+    }
+    
+    #[test]
+    fn test_nix_profile_list() {
+        let current = format!(
+            r#"{{"elements": {{"ripgrep": {{"active": true, "attrPath": "legacyPackages.x86_64-linux.ripgrep", "storePaths": ["{}"]}}}}, "version": 3}}"#,
+            STORE_PATH
+        );
+        let older = format!(
+            r#"{{"elements": [{{"active": true, "attrPath": "legacyPackages.x86_64-linux.ripgrep", "storePaths": ["{}"]}}], "version": 2}}"#,
+            STORE_PATH
+        );
+        for json in [current, older] {
+            assert_eq!(nix::store_path(&json, "ripgrep"), Some(PathBuf::from(STORE_PATH)));
+            assert_eq!(nix::store_path(&json, "fd"), None);
+        }
+        
+        assert_eq!(nix::store_path_version(Path::new(STORE_PATH)), Some("14.1.0".to_string()));
+        assert_eq!(
+            nix::store_path_version(Path::new("/nix/store/0c5h8w1zr2f8m6yzzyxfdsmz3wq9gzf4-python3.12-black-24.3.0")),
+            Some("24.3.0".to_string())
+        );
+        assert_eq!(nix::store_path_version(Path::new("/nix/store/0c5h8w1zr2f8m6yzzyxfdsmz3wq9gzf4-hello")), None);
+    }
+    
+    #[test]
+    fn test_nix_install_records() {
+        let installer = NixInstaller::new().dry_run(true);
+        assert_eq!(installer.name(), "nix");
+        assert!(installer.install(&["ripgrep".to_string()]).is_ok());
+        assert!(installer.uninstall(&["ripgrep".to_string()]).is_ok());
+        
+        let (_dir, config) = temp_config();
+        let mut registry = InstallerRegistry::default();
+        registry.register(FakeNix);
+        let mut state_mgr = InstallationStateManager::new(config).with_registry(registry);
+        state_mgr.create_profile("work", None).unwrap();
+        state_mgr.switch_profile("work").unwrap();
+        state_mgr.smart_install("ripgrep", InstallScope::Profile, Some("nix")).unwrap();
+        
+        let record = state_mgr.get_package_info("ripgrep").unwrap();
+        assert_eq!(record.installer_type, "nix");
+        assert_eq!(record.version.as_deref(), Some("14.1.0"));
+        assert_eq!(record.location, Some(Path::new(STORE_PATH).join("bin").join("rg")));
+    }
+}
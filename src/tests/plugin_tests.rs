@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::installer::Installer;
+    use crate::modules::plugin::{discover, plugin_name};
+    
+    #[test]
+    fn test_plugin_name() {
+        assert_eq!(plugin_name("zshrcman-installer-conda"), Some("conda"));
+        assert_eq!(plugin_name("zshrcman-installer-"), None);
+        assert_eq!(plugin_name("zshrcman-tour"), None);
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_plugin_protocol_round_trip() {
+        use std::os::unix::fs::PermissionsExt;
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join("zshrcman-installer-fake");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n\
+             read request\n\
+             case \"$request\" in\n\
+               *'\"check\"'*) echo '{\"ok\": true, \"versions\": {\"jq\": \"1.7\"}}' ;;\n\
+               *'\"plan\"'*) echo '{\"ok\": true, \"plan\": [\"fake add jq\"]}' ;;\n\
+               *) echo '{\"ok\": true}' ;;\n\
+             esac\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        
+        let plugins = discover(&[dir.path().to_path_buf()]);
+        let plugin = plugins.iter().find(|p| p.name() == "fake").unwrap();
+        
+        assert_eq!(plugin.plan(&["jq".to_string()]).unwrap(), vec!["fake add jq"]);
+        plugin.install(&["jq".to_string()]).unwrap();
+        assert_eq!(plugin.version("jq").unwrap(), Some("1.7".to_string()));
+        assert_eq!(plugin.version("fd").unwrap(), None);
+    }
+}
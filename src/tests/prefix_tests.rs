@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{InstallScope, RemovalStrategy};
+    use crate::modules::facts::Facts;
+    use crate::modules::installer::Installer;
+    use crate::modules::prefix::{self, PrefixInstaller};
+    use crate::modules::state_manager::InstallationStateManager;
+    use crate::tests::temp_config;
+    use std::fs;
+    
+    #[test]
+    fn test_prefix_installers() {
+        assert_eq!(prefix::parse_cargo_list("bat v0.24.0:\n    bat\nripgrep v14.1.0:\n    rg\n", "ripgrep"), Some("14.1.0".to_string()));
+        assert_eq!(prefix::parse_cargo_list("bat v0.24.0:\n", "rip"), None);
+        let cargo_list = "bat v0.24.0:\n    bat\nripgrep v14.1.0:\n    rg\n    rg-helper\n";
+        assert_eq!(prefix::parse_cargo_binaries(cargo_list, "ripgrep"), vec!["rg", "rg-helper"]);
+        assert_eq!(prefix::parse_pip_binaries("Files:\n  ../../../bin/http\n  ../../../bin/https\n  httpie/bin/core.py\n"), vec!["http", "https"]);
+        assert_eq!(prefix::parse_npm_binaries(r#"{"name": "typescript", "bin": {"tsc": "bin/tsc", "tsserver": "bin/tsserver"}}"#), vec!["tsc", "tsserver"]);
+        assert_eq!(prefix::parse_npm_binaries(r#"{"name": "@acme/lint", "bin": "cli.js"}"#), vec!["lint"]);
+        assert_eq!(prefix::parse_pip_show("Name: httpie\nVersion: 3.2.2\nSummary: HTTP client\n", "httpie"), Some("3.2.2".to_string()));
+        
+        let dir = tempfile::tempdir().unwrap();
+        let root = prefix::dir(dir.path(), "work", "npm");
+        assert_eq!(root, dir.path().join("profiles/work/prefix/npm"));
+        assert!(PrefixInstaller::new("brew", root.clone()).is_none());
+        
+        let mut facts = Facts { package_managers: vec!["brew".to_string(), "pip".to_string(), "npm".to_string()], ..Facts::default() };
+        assert_eq!(prefix::preferred(&facts), Some("npm"));
+        facts.package_managers.truncate(1);
+        assert_eq!(prefix::preferred(&facts), None);
+        
+        let npm = PrefixInstaller::new("npm", root.clone()).unwrap();
+        assert_eq!(npm.location("prettier@3").unwrap(), None);
+        assert_eq!(npm.version("prettier").unwrap(), None);
+        fs::create_dir_all(npm.bin_dir()).unwrap();
+        fs::write(npm.bin_dir().join("prettier"), "#!/bin/sh\n").unwrap();
+        assert_eq!(npm.location("prettier@3").unwrap(), Some(root.join("bin/prettier")));
+    }
+    
+    #[test]
+    fn test_local_installs_are_kept_per_profile() {
+        let (_dir, config) = temp_config();
+        let mut state_mgr = InstallationStateManager::new(config);
+        state_mgr.create_profile("work", None).unwrap();
+        state_mgr.create_profile("home", None).unwrap();
+        state_mgr.smart_install("prettier", InstallScope::Global, None).unwrap();
+        
+        state_mgr.switch_profile("work").unwrap();
+        state_mgr.smart_install("prettier", InstallScope::Local, None).unwrap();
+        state_mgr.switch_profile("home").unwrap();
+        state_mgr.smart_install("prettier", InstallScope::Local, None).unwrap();
+        assert!(state_mgr.is_installed("work/prettier"));
+        assert!(state_mgr.is_installed("home/prettier"));
+        assert_eq!(state_mgr.resolve("prettier"), "home/prettier");
+        assert_eq!(state_mgr.get_package_info("prettier").unwrap().scope, InstallScope::Local);
+        
+        state_mgr.handle_removal("prettier", RemovalStrategy::SmartRemove).unwrap();
+        assert!(!state_mgr.is_installed("home/prettier"));
+        assert!(state_mgr.is_installed("work/prettier"));
+        assert_eq!(state_mgr.resolve("prettier"), "prettier");
+        
+        let error = state_mgr.smart_install("jq", InstallScope::Local, Some("brew")).unwrap_err();
+        assert_eq!(error.to_string(), "brew can't install into a profile; local packages use cargo, npm, pip");
+        assert!(!prefix::dir(&state_mgr.config().data_dir(), "home", "brew").exists());
+    }
+}
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::preflight;
+    
+    #[test]
+    fn test_missing_or_relative_home_fails() {
+        let missing = preflight::check_home(None).unwrap_err().to_string();
+        assert!(missing.contains("--home"), "{}", missing);
+        let relative = preflight::check_home(Some("home/me".to_string())).unwrap_err().to_string();
+        assert!(relative.contains("not an absolute path"), "{}", relative);
+        
+        let home = if cfg!(windows) { "C:\\Users\\me" } else { "/home/me" };
+        assert_eq!(preflight::check_home(Some(home.to_string())).unwrap(), std::path::PathBuf::from(home));
+    }
+    
+    #[test]
+    fn test_missing_shell_fails() {
+        assert!(preflight::check_shell(None).unwrap_err().to_string().contains("--shell"));
+        assert_eq!(preflight::check_shell(Some("/bin/zsh".to_string())).unwrap(), "/bin/zsh");
+    }
+    
+    #[test]
+    fn test_bad_overrides_fail_before_changing_anything() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let home = std::env::var_os("HOME");
+        
+        let missing = dir.path().join("missing");
+        let error = preflight::apply(Some(missing), None, None).unwrap_err().to_string();
+        assert!(error.contains("is not an existing directory"), "{}", error);
+        
+        let error = preflight::apply(None, None, Some(dir.path().to_path_buf())).unwrap_err().to_string();
+        assert!(error.contains("is a directory"), "{}", error);
+        assert_eq!(std::env::var_os("HOME"), home);
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::EnvironmentState;
+    use crate::modules::profile_hooks::{self, Trigger};
+    use std::fs;
+    use tempfile::TempDir;
+    
+    #[test]
+    fn test_hooks_run_with_the_profile_environment_and_log_output() {
+        let dir = TempDir::new().unwrap();
+        let log = profile_hooks::log_path(dir.path());
+        let mut env_state = EnvironmentState::default();
+        env_state.variables.insert("CLOUDSDK_CONFIG".to_string(), "work".to_string());
+        let commands = vec![
+            "echo \"$ZSHRCMAN_PROFILE uses $CLOUDSDK_CONFIG\"".to_string(),
+            "echo 'vpn down' >&2; exit 3".to_string(),
+            "echo still runs".to_string(),
+            "sleep 30".to_string(),
+        ];
+        
+        let timeout = Some(std::time::Duration::from_secs(1));
+        let failures = profile_hooks::run(&commands, "work", Trigger::Activate, &env_state, &log, timeout).unwrap();
+        
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].to_string(), "`echo 'vpn down' >&2; exit 3` exited with status 3");
+        assert_eq!(failures[1].to_string(), "`sleep 30` timed out after 1s");
+        let logged = fs::read_to_string(&log).unwrap();
+        assert!(logged.contains("on_activate 'work': echo"));
+        assert!(logged.contains("work uses work\n"));
+        assert!(logged.contains("vpn down\n=== failed: exited with status 3\n"));
+        assert!(logged.contains("still runs\n"));
+    }
+    
+    #[test]
+    fn test_hook_env_adds_profile_paths() {
+        let mut env_state = EnvironmentState::default();
+        env_state.paths_prepend.push("/opt/work/bin".to_string());
+        let vars = profile_hooks::hook_env("work", &env_state);
+        
+        let path = &vars.iter().find(|(key, _)| key == "PATH").unwrap().1;
+        assert!(path.starts_with("/opt/work/bin"));
+        assert!(vars.contains(&("ZSHRCMAN_PROFILE".to_string(), "work".to_string())));
+        let log = std::path::Path::new("/nonexistent/log");
+        assert!(profile_hooks::run(&[], "work", Trigger::Deactivate, &env_state, log, None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{Profile, ProfileIdentity};
+    use crate::modules::profile_identity;
+    use std::collections::HashMap;
+    use std::fs;
+    use tempfile::TempDir;
+    
+    fn work_identity() -> ProfileIdentity {
+        ProfileIdentity {
+            name: Some("Jane Doe".to_string()),
+            email: Some("jane@corp.example".to_string()),
+            ssh_key: Some("~/.ssh/id_work".to_string()),
+            ssh_hosts: vec!["github.com".to_string()],
+            git_dirs: vec!["~/work".to_string()],
+        }
+    }
+    
+    #[test]
+    fn test_apply_and_clear_identity() {
+        let home = TempDir::new().unwrap();
+        let data = TempDir::new().unwrap();
+        let gitconfig = home.path().join(".gitconfig");
+        let ssh_config = home.path().join(".ssh").join("config");
+        fs::write(&gitconfig, "[user]\n\temail = jane@home.example\n").unwrap();
+        fs::create_dir_all(ssh_config.parent().unwrap()).unwrap();
+        fs::write(&ssh_config, "ServerAliveInterval 60\n").unwrap();
+        
+        profile_identity::apply(home.path(), data.path(), "work", Some(&work_identity())).unwrap();
+        
+        let included = profile_identity::gitconfig_path(data.path(), "work");
+        assert_eq!(fs::read_to_string(&included).unwrap(), "[user]\n\tname = \"Jane Doe\"\n\temail = \"jane@corp.example\"\n");
+        let git = fs::read_to_string(&gitconfig).unwrap();
+        assert!(git.starts_with("[user]\n\temail = jane@home.example\n# >>> zshrcman profile >>>\n"));
+        assert!(git.contains(&format!("[includeIf \"gitdir:~/work/\"]\n\tpath = \"{}\"\n", included.display())));
+        let ssh = fs::read_to_string(&ssh_config).unwrap();
+        assert!(ssh.starts_with("# >>> zshrcman profile >>>\nHost github.com\n    IdentityFile ~/.ssh/id_work\n"));
+        assert!(ssh.ends_with("Match all\n# <<< zshrcman profile <<<\nServerAliveInterval 60\n"));
+        
+        profile_identity::clear(home.path()).unwrap();
+        assert_eq!(fs::read_to_string(&gitconfig).unwrap(), "[user]\n\temail = jane@home.example\n");
+        assert_eq!(fs::read_to_string(&ssh_config).unwrap(), "ServerAliveInterval 60\n");
+        
+        // A key without hosts would be the only one SSH offers anywhere.
+        let everywhere = ProfileIdentity { ssh_hosts: vec![], ..work_identity() };
+        assert_eq!(profile_identity::ssh_lines(&everywhere), "");
+        
+        fs::write(&gitconfig, "# >>> zshrcman profile >>>\n[user]\n").unwrap();
+        assert!(profile_identity::apply(home.path(), data.path(), "work", Some(&work_identity())).is_err());
+        assert!(profile_identity::replace_block("# <<< zshrcman profile <<<\n", "", false).is_err());
+    }
+    
+    #[test]
+    fn test_identity_values_are_quoted() {
+        let identity = ProfileIdentity {
+            name: Some("Jane \"JD\" Doe".to_string()),
+            email: Some("jane@corp.example\n[core]\n\tpager = evil".to_string()),
+            ..ProfileIdentity::default()
+        };
+        assert_eq!(
+            profile_identity::gitconfig(&identity),
+            "[user]\n\tname = \"Jane \\\"JD\\\" Doe\"\n\temail = \"jane@corp.example\\n[core]\\n\\tpager = evil\"\n"
+        );
+    }
+    
+    #[test]
+    fn test_identity_is_inherited_from_parents() {
+        let profile = |name: &str, parent: &str| -> Profile {
+            toml::from_str(&format!(
+                "name = \"{}\"\n{}packages = []\nos_overrides = {{}}\n\n[environment]\npaths_prepend = []\npaths_append = []\nvariables = {{}}\naliases = {{}}\nactive = true\n",
+                name,
+                if parent.is_empty() { String::new() } else { format!("parent = \"{}\"\n", parent) }
+            ))
+            .unwrap()
+        };
+        let mut work = profile("work", "");
+        work.identity = Some(work_identity());
+        let mut profiles = HashMap::new();
+        profiles.insert("work".to_string(), work);
+        profiles.insert("client".to_string(), profile("client", "work"));
+        
+        let identity = profile_identity::for_profile(&profiles, "client").unwrap();
+        assert_eq!(identity.email.as_deref(), Some("jane@corp.example"));
+        assert!(profile_identity::for_profile(&profiles, "personal").is_none());
+        assert_eq!(profile_identity::ssh_lines(&ProfileIdentity::default()), "");
+        assert_eq!(profile_identity::include_lines(std::path::Path::new("/p"), &[]), "[include]\n\tpath = \"/p\"\n");
+    }
+}
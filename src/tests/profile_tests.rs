@@ -1,13 +1,44 @@
 #[cfg(test)]
 mod tests {
     use crate::models::*;
+    use crate::modules::installer::{Installer, InstallerRegistry};
     use crate::modules::state_manager::InstallationStateManager;
-    use crate::modules::config::ConfigManager;
-    use std::collections::{HashMap, HashSet};
+    use crate::tests::temp_config;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    
+    /// Records install/uninstall calls; everything is at version 1.0.
+    struct RecordingInstaller {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+    
+    impl Installer for RecordingInstaller {
+        fn name(&self) -> &str {
+            "recording"
+        }
+        
+        fn install(&self, packages: &[String]) -> anyhow::Result<()> {
+            self.calls.lock().unwrap().push(format!("install {}", packages.join(" ")));
+            Ok(())
+        }
+        
+        fn uninstall(&self, packages: &[String]) -> anyhow::Result<()> {
+            self.calls.lock().unwrap().push(format!("uninstall {}", packages.join(" ")));
+            Ok(())
+        }
+        
+        fn version(&self, _package: &str) -> anyhow::Result<Option<String>> {
+            Ok(Some("1.0".to_string()))
+        }
+        
+        fn location(&self, package: &str) -> anyhow::Result<Option<PathBuf>> {
+            Ok(Some(PathBuf::from("/opt/recording/bin").join(package)))
+        }
+    }
     
     #[test]
     fn test_profile_creation() {
-        let config = ConfigManager::new().unwrap();
+        let (_dir, config) = temp_config();
         let mut state_mgr = InstallationStateManager::new(config);
         
         state_mgr.create_profile("work", None).unwrap();
@@ -22,21 +53,21 @@ mod tests {
     
     #[test]
     fn test_smart_install() {
-        let config = ConfigManager::new().unwrap();
+        let (_dir, config) = temp_config();
         let mut state_mgr = InstallationStateManager::new(config);
         
         state_mgr.create_profile("test", None).unwrap();
         state_mgr.switch_profile("test").unwrap();
         
         // First install
-        state_mgr.smart_install("nodejs", InstallScope::Global).unwrap();
+        state_mgr.smart_install("nodejs", InstallScope::Global, None).unwrap();
         assert!(state_mgr.is_installed("nodejs"));
         assert!(state_mgr.is_active("nodejs"));
         
         // Second install (should just activate)
         state_mgr.create_profile("test2", None).unwrap();
         state_mgr.switch_profile("test2").unwrap();
-        state_mgr.smart_install("nodejs", InstallScope::Global).unwrap();
+        state_mgr.smart_install("nodejs", InstallScope::Global, None).unwrap();
         
         // Check both profiles have it active
         let record = state_mgr.installations.get("nodejs").unwrap();
@@ -46,12 +77,12 @@ mod tests {
     
     #[test]
     fn test_removal_strategies() {
-        let config = ConfigManager::new().unwrap();
+        let (_dir, config) = temp_config();
         let mut state_mgr = InstallationStateManager::new(config);
         
         state_mgr.create_profile("profile1", None).unwrap();
         state_mgr.switch_profile("profile1").unwrap();
-        state_mgr.smart_install("package1", InstallScope::Profile).unwrap();
+        state_mgr.smart_install("package1", InstallScope::Profile, None).unwrap();
         
         // Deactivate only
         state_mgr.handle_removal("package1", RemovalStrategy::Deactivate).unwrap();
@@ -66,6 +97,94 @@ mod tests {
         assert!(!state_mgr.is_installed("package1"));
     }
     
+    #[test]
+    fn test_list_packages_filters() {
+        let (_dir, config) = temp_config();
+        let mut state_mgr = InstallationStateManager::new(config);
+        state_mgr.create_profile("work", None).unwrap();
+        state_mgr.create_profile("home", None).unwrap();
+        state_mgr.switch_profile("work").unwrap();
+        state_mgr.smart_install("terraform", InstallScope::Global, None).unwrap();
+        state_mgr.smart_install("kubectl", InstallScope::Profile, None).unwrap();
+        state_mgr.switch_profile("home").unwrap();
+        state_mgr.smart_install("jq", InstallScope::Profile, None).unwrap();
+        state_mgr.handle_removal("jq", RemovalStrategy::Deactivate).unwrap();
+        
+        let names = |records: Vec<&InstallationRecord>| records.iter().map(|record| record.package.clone()).collect::<Vec<_>>();
+        assert_eq!(names(state_mgr.list_packages(None, None, false).unwrap()), vec!["jq", "kubectl", "terraform"]);
+        assert_eq!(names(state_mgr.list_packages(Some("work"), None, false).unwrap()), vec!["kubectl", "terraform"]);
+        assert_eq!(names(state_mgr.list_packages(Some("work"), Some(&InstallScope::Global), false).unwrap()), vec!["terraform"]);
+        assert_eq!(names(state_mgr.list_packages(None, None, true).unwrap()), vec!["jq"]);
+        
+        let error = state_mgr.list_packages(Some("wrok"), None, false).unwrap_err();
+        assert_eq!(error.to_string(), "Profile 'wrok' does not exist. Did you mean 'work'?");
+    }
+    
+    #[test]
+    fn test_smart_remove_cascades_to_dependencies() {
+        let (_dir, config) = temp_config();
+        let mut state_mgr = InstallationStateManager::new(config);
+        state_mgr.create_profile("work", None).unwrap();
+        state_mgr.switch_profile("work").unwrap();
+        state_mgr.smart_install("openssl", InstallScope::Global, None).unwrap();
+        
+        let requires = vec!["kubectl".to_string(), "helm".to_string(), "openssl".to_string()];
+        state_mgr.install_with_dependencies("k8s-tools", &requires, InstallScope::Profile, None).unwrap();
+        state_mgr.install_with_dependencies("argo", &["helm".to_string()], InstallScope::Profile, None).unwrap();
+        let helm = state_mgr.get_package_info("helm").unwrap();
+        assert_eq!(helm.installed_by.to_string(), "dependency of k8s-tools");
+        assert_eq!(helm.required_by.iter().collect::<Vec<_>>(), vec!["argo", "k8s-tools"]);
+        
+        // A dependency still required by something is only deactivated.
+        state_mgr.handle_removal("kubectl", RemovalStrategy::SmartRemove).unwrap();
+        assert!(state_mgr.is_installed("kubectl"));
+        
+        // kubectl goes with k8s-tools; helm stays for argo and openssl was
+        // installed on its own.
+        state_mgr.handle_removal("k8s-tools", RemovalStrategy::SmartRemove).unwrap();
+        assert!(!state_mgr.is_installed("kubectl"));
+        assert!(state_mgr.is_installed("helm"));
+        assert!(state_mgr.is_installed("openssl"));
+        
+        state_mgr.handle_removal("argo", RemovalStrategy::SmartRemove).unwrap();
+        assert!(!state_mgr.is_installed("helm"));
+        assert!(state_mgr.get_package_info("openssl").unwrap().required_by.is_empty());
+    }
+    
+    #[test]
+    fn test_pinned_packages_refuse_removal() {
+        let (_dir, config) = temp_config();
+        let mut state_mgr = InstallationStateManager::new(config);
+        state_mgr.create_profile("work", None).unwrap();
+        state_mgr.switch_profile("work").unwrap();
+        state_mgr.smart_install("terraform", InstallScope::Global, None).unwrap();
+        assert!(state_mgr.pin("terraform").unwrap());
+        
+        for strategy in [RemovalStrategy::SmartRemove, RemovalStrategy::ForceRemove] {
+            let error = state_mgr.handle_removal("terraform", strategy).unwrap_err();
+            assert!(error.to_string().contains("terraform is pinned"));
+        }
+        state_mgr.handle_removal("terraform", RemovalStrategy::Deactivate).unwrap();
+        assert!(state_mgr.is_installed("terraform"));
+        
+        let mut state_mgr = state_mgr.force_pinned(true);
+        state_mgr.handle_removal("terraform", RemovalStrategy::ForceRemove).unwrap();
+        assert!(!state_mgr.is_installed("terraform"));
+        assert!(!state_mgr.unpin("terraform").unwrap());
+        
+        // Pinning a package zshrcman never installed doesn't make it installed.
+        assert!(state_mgr.pin("jq").unwrap());
+        assert!(!state_mgr.is_installed("jq"));
+        assert!(state_mgr.list_packages(None, None, false).unwrap().iter().all(|record| record.package != "jq"));
+        state_mgr.smart_install("jq", InstallScope::Global, None).unwrap();
+        assert!(state_mgr.is_installed("jq"));
+        assert!(state_mgr.is_pinned("jq"));
+        
+        assert!(state_mgr.pin("fd").unwrap());
+        assert!(state_mgr.unpin("fd").unwrap());
+        assert!(state_mgr.get_package_info("fd").is_none());
+    }
+    
     #[test]
     fn test_os_detection() {
         let os = OsType::detect();
@@ -100,7 +219,7 @@ mod tests {
     fn test_profile_switching_performance() {
         use std::time::Instant;
         
-        let config = ConfigManager::new().unwrap();
+        let (_dir, config) = temp_config();
         let mut state_mgr = InstallationStateManager::new(config);
         
         // Create profiles
@@ -110,7 +229,7 @@ mod tests {
         // Add some packages
         state_mgr.switch_profile("profile1").unwrap();
         for i in 0..10 {
-            state_mgr.smart_install(&format!("package{}", i), InstallScope::Profile).unwrap();
+            state_mgr.smart_install(&format!("package{}", i), InstallScope::Profile, None).unwrap();
         }
         
         // Measure switching time
@@ -121,4 +240,89 @@ mod tests {
         // Should be very fast (< 100ms for simple state switch)
         assert!(duration.as_millis() < 100, "Profile switch took {:?}", duration);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_profile_groups_join_the_effective_group_set() {
+        let (_dir, mut config) = temp_config();
+        let groups = config.dotfiles_path().join("groups");
+        std::fs::create_dir_all(&groups).unwrap();
+        for name in ["corp-vpn", "k8s"] {
+            std::fs::write(groups.join(format!("{}.toml", name)), format!("name = \"{}\"\n", name)).unwrap();
+        }
+        config.config.groups.enabled_devices.push("laptop-only".to_string());
+        
+        let mut profiles = crate::modules::profile_service::ProfileService::new(config);
+        profiles.create("work", None).unwrap();
+        profiles.create("oncall", Some("work".to_string())).unwrap();
+        profiles.create("personal", None).unwrap();
+        assert!(profiles.enable_group("work", "corp-vpn").unwrap());
+        assert!(!profiles.enable_group("work", "corp-vpn").unwrap());
+        assert!(profiles.enable_group("oncall", "k8s").unwrap());
+        assert!(profiles.enable_group("work", "missing").is_err());
+        
+        // Switch through the state manager to leave the shell startup files alone.
+        let saved = profiles.state().config();
+        let reloaded = crate::tests::open_config(saved.config_path().to_path_buf(), saved.dotfiles_path().to_path_buf());
+        let mut state = InstallationStateManager::new(reloaded.unwrap());
+        state.switch_profile("oncall").unwrap();
+        assert_eq!(state.config().get_ordered_groups(), vec!["default", "corp-vpn", "k8s", "laptop-only"]);
+        
+        state.switch_profile("personal").unwrap();
+        assert_eq!(state.config().get_ordered_groups(), vec!["default", "laptop-only"]);
+        
+        assert!(profiles.disable_group("work", "corp-vpn").unwrap());
+        assert!(!profiles.disable_group("work", "corp-vpn").unwrap());
+    }
+    
+    #[test]
+    fn test_package_commands_use_backends() {
+        assert_eq!("global".parse::<InstallScope>().unwrap(), InstallScope::Global);
+        assert!("system".parse::<InstallScope>().is_err());
+        assert!(matches!("mark-unused".parse::<RemovalStrategy>().unwrap(), RemovalStrategy::MarkUnused));
+        assert!("purge".parse::<RemovalStrategy>().unwrap_err().contains("deactivate, smart, force or mark-unused"));
+        
+        let (_dir, config) = temp_config();
+        let calls = Arc::new(Mutex::new(vec![]));
+        let mut registry = InstallerRegistry::default();
+        registry.register(RecordingInstaller { calls: calls.clone() });
+        let mut state_mgr = InstallationStateManager::new(config).with_registry(registry);
+        state_mgr.create_profile("work", None).unwrap();
+        state_mgr.switch_profile("work").unwrap();
+        
+        let error = state_mgr.smart_install("jq", InstallScope::Profile, Some("recordng")).unwrap_err();
+        assert_eq!(error.to_string(), "Installer 'recordng' does not exist. Did you mean 'recording'?");
+        state_mgr.smart_install("jq", InstallScope::Profile, Some("recording")).unwrap();
+        let record = state_mgr.get_package_info("jq").unwrap();
+        assert_eq!(record.installer_type, "recording");
+        assert_eq!(record.version.as_deref(), Some("1.0"));
+        assert_eq!(record.location, Some(PathBuf::from("/opt/recording/bin/jq")));
+        assert_eq!(record.installed_by.to_string(), "profile work");
+        assert_eq!(record.scope.to_string(), "profile");
+        
+        state_mgr.handle_removal("jq", RemovalStrategy::SmartRemove).unwrap();
+        assert!(!state_mgr.is_installed("jq"));
+        assert_eq!(*calls.lock().unwrap(), vec!["install jq", "uninstall jq"]);
+    }
+    
+    #[test]
+    fn test_legacy_bin_dir_is_migrated() {
+        use crate::modules::profile_switcher::migrate_bin_dir;
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        let (home, data_dir) = (dir.path().join("home"), dir.path().join("data"));
+        let legacy = home.join(".local/share/zshrcman/profiles/work/bin");
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("tool"), "").unwrap();
+        
+        let bin = migrate_bin_dir(&home, &data_dir, "work").unwrap();
+        assert_eq!(bin, data_dir.join("profiles/work/bin"));
+        assert!(bin.join("tool").exists());
+        assert!(!legacy.exists());
+        
+        // Where the data dir is the old location, nothing moves.
+        let same = home.join(".local/share/zshrcman");
+        std::fs::create_dir_all(same.join("profiles/home/bin")).unwrap();
+        assert_eq!(migrate_bin_dir(&home, &same, "home").unwrap(), same.join("profiles/home/bin"));
+        assert!(same.join("profiles/home/bin").exists());
+    }
+}
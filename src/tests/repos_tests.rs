@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{ConflictStrategy, NamedRepository};
+    use crate::modules::git_mgr::{GitManager, SyncMode};
+    use crate::modules::repos;
+    use crate::modules::validate::Validator;
+    use std::fs;
+    
+    #[test]
+    fn test_sync_checks_out_device_branch_and_resolves_groups() {
+        let (dir, mut config_mgr) = crate::tests::temp_config();
+        let upstream_path = dir.path().join("work-upstream");
+        git2::Repository::init(&upstream_path).unwrap().set_head("refs/heads/main").unwrap();
+        let upstream = GitManager::open(&upstream_path).unwrap();
+        fs::create_dir_all(upstream_path.join("groups")).unwrap();
+        fs::create_dir_all(upstream_path.join("scripts")).unwrap();
+        fs::write(upstream_path.join("groups/k8s.toml"), "name = \"k8s\"\nscripts = [\"kube.zsh\"]\n").unwrap();
+        fs::write(upstream_path.join("scripts/kube.zsh"), "alias k=kubectl\n").unwrap();
+        upstream.add_all().unwrap();
+        let _ = upstream.commit_and_push("Add k8s", "main");
+        // Syncing pushes the device branch, which needs a bare remote.
+        let bare_path = dir.path().join("work.git");
+        git2::build::RepoBuilder::new()
+            .bare(true)
+            .clone(&upstream_path.display().to_string(), &bare_path)
+            .unwrap();
+        
+        let repository = NamedRepository {
+            url: bare_path.display().to_string(),
+            main_branch: "main".to_string(),
+            branch: Some("laptop".to_string()),
+            remotes: Default::default(),
+        };
+        config_mgr.config.repositories.insert("work".to_string(), repository.clone());
+        
+        let data_dir = config_mgr.data_dir();
+        repos::sync(&data_dir, "work", &repository, "ignored", ConflictStrategy::Abort, SyncMode::Both, &Default::default())
+            .unwrap();
+        let checkout = repos::checkout_dir(&data_dir, "work");
+        let clone = git2::Repository::open(&checkout).unwrap();
+        assert_eq!(clone.head().unwrap().shorthand(), Some("laptop"));
+        
+        let (root, name) = config_mgr.resolve_group("work/k8s");
+        assert_eq!((root.as_path(), name), (checkout.as_path(), "k8s"));
+        assert_eq!(config_mgr.load_group_config("work/k8s").unwrap().scripts, vec!["kube.zsh"]);
+        
+        // Scripts are looked up in the repository the group lives in.
+        let group_file = checkout.join("groups/k8s.toml");
+        let validator = || Validator::new(config_mgr.config_path(), config_mgr.dotfiles_path());
+        assert!(validator().in_repo(&checkout).group_file(&group_file).is_empty());
+        assert!(!validator().group_file(&group_file).is_empty());
+        
+        assert!(repos::open(&data_dir, "../escape", &repository).is_err());
+    }
+}
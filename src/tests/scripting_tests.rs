@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::scripting::{render_placeholders, resolve_target};
+    use std::path::Path;
+    
+    #[test]
+    fn test_resolve_target_stays_in_home() {
+        let home = Path::new("/home/me");
+        assert_eq!(resolve_target(home, "~/.config/app").unwrap(), home.join(".config/app"));
+        assert_eq!(resolve_target(home, "bin/tool").unwrap(), home.join("bin/tool"));
+        assert!(resolve_target(home, "/etc/passwd").is_err());
+        assert!(resolve_target(home, "~/../other").is_err());
+    }
+    
+    #[test]
+    fn test_render_placeholders() {
+        let vars = vec![("name".to_string(), "zsh".to_string())];
+        assert_eq!(render_placeholders("{{name}} / {{ name }} / {{other}}", &vars), "zsh / zsh / {{other}}");
+    }
+    
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn test_install_script_copies_into_home() {
+        use crate::modules::scripting::{run_install_script, ScriptContext};
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        let group_dir = dir.path().join("group");
+        let home = dir.path().join("home");
+        std::fs::create_dir_all(&group_dir).unwrap();
+        std::fs::write(group_dir.join("app.conf"), "theme = {{ theme }}\n").unwrap();
+        std::fs::write(
+            group_dir.join("install.rhai"),
+            r#"
+                if !exists("~/.app.conf") {
+                    template("app.conf", "~/.app.conf", #{ theme: "dark" });
+                }
+            "#,
+        )
+        .unwrap();
+        
+        let context = ScriptContext {
+            group: "app".to_string(),
+            group_dir: group_dir.clone(),
+            home: home.clone(),
+            dry_run: false,
+        };
+        run_install_script(&group_dir.join("install.rhai"), &context).unwrap();
+        
+        assert_eq!(std::fs::read_to_string(home.join(".app.conf")).unwrap(), "theme = dark\n");
+        
+        // A runaway script is stopped instead of hanging the install.
+        std::fs::write(group_dir.join("install.rhai"), "let s = \"x\"; loop { s += s; }").unwrap();
+        assert!(run_install_script(&group_dir.join("install.rhai"), &context).is_err());
+    }
+}
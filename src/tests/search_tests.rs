@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::search::{self, Kind};
+    use std::fs;
+    use std::path::Path;
+    
+    fn write_group(dotfiles: &Path, relative: &str, contents: &str) {
+        let path = dotfiles.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    
+    #[test]
+    fn test_search_finds_matches_across_global_and_device_groups() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let dotfiles = dir.path();
+        write_group(dotfiles, "groups/default.toml", "name = \"default\"\naliases = ['alias ll=\"ls -la\"']\n");
+        write_group(
+            dotfiles,
+            "devices/laptop/groups/k8s.toml",
+            "name = \"k8s\"\npackages = [\"kubectl\", \"kubectx\"]\naliases = ['alias kctx=\"kubectx\"']\n",
+        );
+        write_group(dotfiles, "groups/broken.toml", "name = [\n");
+        
+        let matches = search::search(dotfiles, "kubectx").unwrap();
+        let best = &matches[0];
+        assert_eq!((best.kind, best.text.as_str()), (Kind::Package, "kubectx"));
+        assert_eq!(best.device.as_deref(), Some("laptop"));
+        assert_eq!(best.location(), "devices/laptop/groups/k8s.toml:2");
+        
+        let alias = matches.iter().find(|m| m.kind == Kind::Alias).unwrap();
+        assert_eq!(alias.line, Some(3));
+        // A near-miss ranks below everything that contains the term.
+        assert_eq!(matches.last().unwrap().text, "kubectl");
+        
+        let matches = search::search(dotfiles, "LL").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].group, "default");
+        assert_eq!(matches[0].device, None);
+    }
+    
+    #[test]
+    fn test_score_prefers_exact_then_substring_then_similar() {
+        let exact = search::score("kubectx", "kubectx").unwrap();
+        let substring = search::score("kube", "kubectx").unwrap();
+        let similar = search::score("kubctx", "kubectx").unwrap();
+        assert!(exact > substring && substring > similar);
+        assert!(search::score("kubectx", "ripgrep").is_none());
+    }
+}
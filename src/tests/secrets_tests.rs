@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::EnvironmentState;
+    use crate::modules::environment::{EnvironmentManager, ShellType};
+    use crate::modules::secrets::{secret_value, Backend, SecretRef};
+    use std::path::Path;
+    
+    #[test]
+    fn test_parse_and_commands() {
+        let op = SecretRef::parse("op://Private/GitHub/token").unwrap();
+        assert_eq!(op.backend, Backend::OnePassword);
+        assert_eq!(op.command(Path::new("/repo")), ["op", "read", "op://Private/GitHub/token"]);
+        
+        let bw = SecretRef::parse("bw://npm").unwrap();
+        assert_eq!(bw.command(Path::new("/repo")), ["bw", "get", "password", "npm"]);
+        assert_eq!(SecretRef::parse("pass://work/aws").unwrap().backend, Backend::Pass);
+        
+        let age = SecretRef::parse("age://secrets/token.age").unwrap();
+        let command = age.command(Path::new("/repo"));
+        assert_eq!(command[..3], ["age", "--decrypt", "--identity"]);
+        assert_eq!(command[4], Path::new("/repo").join("secrets/token.age").display().to_string());
+        
+        assert!(SecretRef::parse("vault://kv/token").is_err());
+        assert!(SecretRef::parse("op://").is_err());
+        assert!(SecretRef::parse("plaintext").is_err());
+        
+        assert_eq!(secret_value(Backend::Pass, "hunter2\nlogin: me\n"), "hunter2");
+        assert_eq!(secret_value(Backend::OnePassword, "multi\nline\r\n"), "multi\nline");
+    }
+    
+    #[test]
+    fn test_startup_files_hold_references_only() {
+        let mut env_state = EnvironmentState::default();
+        env_state.secrets.insert("GITHUB_TOKEN".to_string(), "op://Private/GitHub/token".to_string());
+        
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).generate_shell_config(&env_state).unwrap();
+        assert!(zsh.contains(r#"export GITHUB_TOKEN="$(zshrcman env secret "op://Private/GitHub/token")""#));
+        
+        let fish = EnvironmentManager::for_shell(ShellType::Fish).generate_shell_config(&env_state).unwrap();
+        assert!(fish.contains(r#"set -gx GITHUB_TOKEN (zshrcman env secret "op://Private/GitHub/token" | string collect)"#));
+        
+        assert!(!env_state.is_empty());
+        assert!(env_state.variables.is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{GroupConfig, OsType};
+    use crate::modules::services::{self, ServiceManager};
+    use std::path::{Path, PathBuf};
+    
+    #[test]
+    fn test_services_config() {
+        let group: GroupConfig = toml::from_str(
+            "name = \"backup\"\n\n[[services]]\nsource = \"services/backup.service\"\nenable = false\n\n[[services]]\nsource = \"services/backup.timer\"\n",
+        )
+        .unwrap();
+        assert!(!group.services[0].enable);
+        assert!(group.services[1].enable);
+    }
+    
+    #[test]
+    fn test_service_targets_per_platform() {
+        let home = Path::new("/home/jane");
+        assert_eq!(ServiceManager::for_file(Path::new("backup.timer")), Some(ServiceManager::Systemd));
+        assert_eq!(ServiceManager::for_file(Path::new("com.jane.sync.plist")), Some(ServiceManager::Launchd));
+        assert_eq!(ServiceManager::for_file(Path::new("backup.sh")), None);
+        
+        assert_eq!(
+            services::target(Path::new("services/backup.timer"), home, &OsType::Linux),
+            Some(PathBuf::from("/home/jane/.config/systemd/user/backup.timer"))
+        );
+        assert_eq!(
+            services::target(Path::new("services/com.jane.sync.plist"), home, &OsType::MacOS),
+            Some(PathBuf::from("/home/jane/Library/LaunchAgents/com.jane.sync.plist"))
+        );
+        assert_eq!(services::target(Path::new("services/backup.timer"), home, &OsType::MacOS), None);
+        assert_eq!(services::target(Path::new("services/com.jane.sync.plist"), home, &OsType::Linux), None);
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::environment::ShellType;
+    use crate::modules::session;
+    use std::fs;
+    use tempfile::TempDir;
+    
+    #[test]
+    fn test_zsh_session_sources_the_user_rc_first() {
+        let dir = TempDir::new().unwrap();
+        let command = session::command(&ShellType::Zsh, dir.path(), "export CLIENT=\"x\"\n").unwrap();
+        
+        assert_eq!(command.get_program(), "zsh");
+        assert!(command.get_envs().any(|(key, value)| key == "ZDOTDIR" && value == Some(dir.path().as_os_str())));
+        let zshrc = fs::read_to_string(dir.path().join(".zshrc")).unwrap();
+        let user_rc = zshrc.find("source \"$ZDOTDIR/.zshrc\"").unwrap();
+        assert!(user_rc < zshrc.find("export CLIENT=\"x\"").unwrap());
+        assert!(fs::read_to_string(dir.path().join(".zshenv")).unwrap().contains("source \"$ZDOTDIR/.zshenv\""));
+    }
+    
+    #[test]
+    fn test_bash_and_fish_sessions() {
+        let dir = TempDir::new().unwrap();
+        let bash = session::command(&ShellType::Bash, dir.path(), "alias k='kubectl'\n").unwrap();
+        let args: Vec<_> = bash.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+        assert_eq!(args, vec!["--rcfile".to_string(), dir.path().join("bashrc").display().to_string(), "-i".to_string()]);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("bashrc")).unwrap(),
+            "[[ -r ~/.bashrc ]] && source ~/.bashrc\nalias k='kubectl'\n"
+        );
+        
+        let fish = session::command(&ShellType::Fish, dir.path(), "set -gx CLIENT \"x\"\n").unwrap();
+        assert!(fish.get_args().any(|arg| arg.to_string_lossy().starts_with("source '")));
+        assert!(session::command(&ShellType::Cmd, dir.path(), "").is_err());
+    }
+}
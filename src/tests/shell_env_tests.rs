@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::EnvironmentState;
+    use crate::modules::environment::ShellType;
+    use crate::modules::shell_env;
+    
+    fn work() -> EnvironmentState {
+        let mut env_state = EnvironmentState::default();
+        env_state.paths_prepend.push("/opt/work/bin".to_string());
+        env_state.variables.insert("AWS_PROFILE".to_string(), "work".to_string());
+        env_state.aliases.insert("k".to_string(), "kubectl".to_string());
+        env_state
+    }
+    
+    #[test]
+    fn test_script_applies_the_active_profile() {
+        let work = work();
+        let script = shell_env::script(&ShellType::Zsh, ("", None), Some(("work", &work)));
+        assert_eq!(
+            script,
+            "case \":$PATH:\" in *:\"/opt/work/bin\":*) ;; *) export PATH=\"/opt/work/bin\":\"$PATH\" ;; esac\n\
+             export AWS_PROFILE=\"work\"\n\
+             alias k='kubectl'\n\
+             _zshrcman_profile=\"work\"\n"
+        );
+        
+        let fish = shell_env::script(&ShellType::Fish, ("", None), Some(("work", &work)));
+        assert!(fish.contains("contains -- \"/opt/work/bin\" $PATH; or set -gx PATH \"/opt/work/bin\" $PATH\n"));
+        assert!(fish.contains("set -gx AWS_PROFILE \"work\"\n"));
+        assert!(fish.ends_with("set -g _zshrcman_profile \"work\"\n"));
+    }
+    
+    #[test]
+    fn test_script_only_prints_changes() {
+        let work = work();
+        assert!(shell_env::script(&ShellType::Bash, ("work", Some(&work)), Some(("work", &work))).is_empty());
+        assert!(shell_env::script(&ShellType::Bash, ("", None), None).is_empty());
+        
+        let script = shell_env::script(&ShellType::Bash, ("work", Some(&work)), None);
+        assert!(script.contains("unset AWS_PROFILE\n"));
+        assert!(script.contains("unalias k 2>/dev/null\n"));
+        assert!(script.contains("PATH=\"${PATH//:\"/opt/work/bin\":/:}\""));
+        assert!(script.ends_with("_zshrcman_profile=\"\"\n"));
+    }
+    
+    #[test]
+    fn test_hook_snippets() {
+        assert!(shell_env::hook_snippet(&ShellType::Zsh).contains("precmd_functions=(_zshrcman_env_hook $precmd_functions)"));
+        assert!(shell_env::hook_snippet(&ShellType::Bash).contains("command zshrcman --shell bash env --applied \"${_zshrcman_profile-}\""));
+        assert!(shell_env::hook_snippet(&ShellType::Fish).contains("--on-event fish_prompt"));
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::GroupSource;
+    use crate::modules::sources;
+    use crate::tests::temp_config;
+    use git2::{Repository, Signature};
+    use std::fs;
+    use std::path::Path;
+    
+    fn commit_group(repo: &Repository, description: &str) -> git2::Oid {
+        let workdir = repo.workdir().unwrap();
+        fs::create_dir_all(workdir.join("groups")).unwrap();
+        fs::write(
+            workdir.join("groups/k8s.toml"),
+            format!("name = \"k8s\"\ndescription = \"{}\"\n", description),
+        )
+        .unwrap();
+        
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("groups/k8s.toml")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@localhost").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, description, &tree, &parents)
+            .unwrap()
+    }
+    
+    #[test]
+    fn test_source_groups_are_namespaced_and_pinned() {
+        let (dir, mut config_mgr) = temp_config();
+        let upstream = Repository::init(dir.path().join("upstream")).unwrap();
+        let v1 = commit_group(&upstream, "first");
+        upstream.tag_lightweight("v1", &upstream.find_object(v1, None).unwrap(), false).unwrap();
+        commit_group(&upstream, "second");
+        
+        let url = dir.path().join("upstream").display().to_string();
+        config_mgr.config.sources.insert("work".to_string(), GroupSource::Url(url.clone()));
+        config_mgr.config.sources.insert(
+            "pinned".to_string(),
+            GroupSource::Pinned { url, reference: "v1".to_string() },
+        );
+        
+        let data_dir = config_mgr.data_dir();
+        for (name, source) in &config_mgr.config.sources {
+            sources::update(&data_dir, name, source).unwrap();
+        }
+        
+        assert_eq!(sources::groups(&data_dir, "work"), vec!["work/k8s"]);
+        assert_eq!(config_mgr.load_group_config("work/k8s").unwrap().description, "second");
+        assert_eq!(config_mgr.load_group_config("pinned/k8s").unwrap().description, "first");
+        assert!(config_mgr.load_group_config("other/k8s").is_err());
+    }
+}
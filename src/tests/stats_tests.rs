@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::oplog::Operation;
+    use crate::modules::stats::{self, GroupSummary, InstallOutcomes, RepoStats};
+    
+    fn group(name: &str, backend: &str, packages: usize) -> GroupSummary {
+        GroupSummary {
+            name: name.to_string(),
+            scope: "global".to_string(),
+            backend: backend.to_string(),
+            packages,
+            last_changed: None,
+        }
+    }
+    
+    #[test]
+    fn test_packages_per_backend() {
+        let stats = RepoStats {
+            groups: vec![group("brew", "brew", 3), group("cli", "brew", 2), group("aliases", "aliases", 0)],
+            ..Default::default()
+        };
+        
+        let counts = stats.packages_per_backend();
+        assert_eq!(counts.get("brew"), Some(&5));
+        assert!(!counts.contains_key("aliases"));
+    }
+    
+    #[test]
+    fn test_failure_rate() {
+        assert_eq!(InstallOutcomes::default().failure_rate(), 0.0);
+        assert_eq!(InstallOutcomes { succeeded: 3, failed: 1 }.failure_rate(), 0.25);
+    }
+    
+    #[test]
+    fn test_install_outcomes_from_operations() {
+        let operation = |started_at: &str, command: &str, success: bool| Operation {
+            started_at: started_at.parse().unwrap(),
+            duration_ms: 0,
+            command: command.to_string(),
+            args: command.to_string(),
+            success,
+            error: None,
+            history_id: None,
+        };
+        let operations = vec![
+            operation("2026-08-03T10:00:00Z", "install", false),
+            operation("2026-08-04T10:00:00Z", "install", true),
+            operation("2026-08-20T10:00:00Z", "pkg install", true),
+            operation("2026-08-21T10:00:00Z", "sync", false),
+            operation("2026-09-01T10:00:00Z", "install", false),
+        ];
+        
+        let outcomes = stats::install_outcomes(&operations);
+        assert_eq!(outcomes["2026-08"], InstallOutcomes { succeeded: 2, failed: 1 });
+        assert_eq!(outcomes["2026-09"], InstallOutcomes { succeeded: 0, failed: 1 });
+        assert_eq!(outcomes.len(), 2);
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::suggest;
+    use crate::tests::temp_config;
+    
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+    
+    #[test]
+    fn test_closest_names() {
+        let groups = names(&["git", "gpg", "brew", "docker", "dockerfiles"]);
+        assert_eq!(suggest::closest("brwe", &groups), vec!["brew"]);
+        assert_eq!(suggest::closest("doker", &groups), vec!["docker", "dockerfiles"]);
+        assert!(suggest::closest("zsh", &groups).is_empty());
+        assert!(suggest::closest("git", &names(&["git"])).is_empty());
+        
+        assert_eq!(
+            suggest::not_found("Profile", "wrok", &names(&["work", "home"])).to_string(),
+            "Profile 'wrok' does not exist. Did you mean 'work'?"
+        );
+        assert_eq!(
+            suggest::not_found("Group", "doker", &groups).to_string(),
+            "Group 'doker' does not exist. Did you mean one of 'docker', 'dockerfiles'?"
+        );
+        assert_eq!(suggest::not_found("Group", "zsh", &groups).to_string(), "Group 'zsh' does not exist");
+    }
+    
+    #[test]
+    fn test_unknown_groups_are_suggested() {
+        let (_dir, mut config_mgr) = temp_config();
+        config_mgr.add_global_group("brew".to_string()).unwrap();
+        
+        for result in [
+            config_mgr.enable_global_group("brwe"),
+            config_mgr.disable_global_group("brwe"),
+            config_mgr.remove_global_group("brwe"),
+        ] {
+            assert_eq!(result.unwrap_err().to_string(), "Group 'brwe' does not exist. Did you mean 'brew'?");
+        }
+        config_mgr.enable_global_group("brew").unwrap();
+        assert!(config_mgr.config.groups.enabled_global.contains(&"brew".to_string()));
+        
+        // A stale entry left after its group went away can still be cleaned up.
+        config_mgr.config.groups.enabled_global.push("gone".to_string());
+        config_mgr.disable_global_group("gone").unwrap();
+        assert!(!config_mgr.config.groups.enabled_global.contains(&"gone".to_string()));
+        config_mgr.config.groups.enabled_global.push("gone".to_string());
+        config_mgr.remove_global_group("gone").unwrap();
+        assert!(!config_mgr.config.groups.enabled_global.contains(&"gone".to_string()));
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::notifications;
+    use crate::modules::services::ServiceManager;
+    use crate::modules::sync_timer::SyncTimer;
+    use std::path::PathBuf;
+    
+    fn timer() -> SyncTimer {
+        SyncTimer {
+            binary: PathBuf::from("/usr/local/bin/zshrcman"),
+            config: PathBuf::from("/home/me/R&D/config.toml"),
+            minutes: 30,
+            log: PathBuf::from("/home/me/.local/share/zshrcman/sync-timer.log"),
+        }
+    }
+    
+    #[test]
+    fn test_sync_timer_files() {
+        let files = timer().files(ServiceManager::Systemd);
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["zshrcman-sync.service", "zshrcman-sync.timer"]);
+        assert!(files[0].1.contains(
+            "ExecStart=\"/usr/local/bin/zshrcman\" \"--config\" \"/home/me/R&D/config.toml\" \"--yes\" \"sync\" \"--notify\"\n"
+        ));
+        assert!(files[1].1.contains("OnUnitActiveSec=30min\n"));
+        // Only meaningful for OnCalendar= timers.
+        assert!(!files[1].1.contains("Persistent="));
+        assert!(files[1].1.contains("WantedBy=timers.target"));
+        
+        let files = timer().files(ServiceManager::Launchd);
+        assert_eq!(files.len(), 1);
+        let (name, plist) = &files[0];
+        assert_eq!(name, "com.zshrcman.sync.plist");
+        assert!(plist.contains("        <string>/home/me/R&amp;D/config.toml</string>\n"));
+        assert!(plist.contains("    <key>StartInterval</key>\n    <integer>1800</integer>\n"));
+        assert!(plist.ends_with("</dict>\n</plist>\n"));
+    }
+    
+    #[test]
+    fn test_sync_notifications() {
+        assert_eq!(notifications::sync_outcome("main", false, &Ok(())), None);
+        let (summary, body) = notifications::sync_outcome("main", true, &Ok(())).unwrap();
+        assert_eq!(summary, "Dotfiles updated");
+        assert!(body.contains("origin/main"));
+        let (summary, body) = notifications::sync_outcome("main", true, &Err(anyhow::anyhow!("zshrc changed on both"))).unwrap();
+        assert_eq!(summary, "zshrcman sync needs attention");
+        assert!(body.starts_with("zshrc changed on both."));
+    }
+}
@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::Config;
+    use crate::modules::facts::Facts;
+    use crate::modules::template::TemplateVars;
+    use std::path::Path;
+    
+    #[test]
+    fn test_render_device_profile_and_vars() {
+        let mut config = Config::default();
+        config.device.name = "laptop".to_string();
+        config.active_profile = Some("work".to_string());
+        config.variables.insert("email".to_string(), toml::Value::String("me@example.com".to_string()));
+        
+        let vars = TemplateVars::from_config(&config, Path::new("/home/me"), &Facts::default());
+        let rendered = vars
+            .render("gitconfig", "{{ device.name }}/{{ profile }}/{{ vars.email }}/{% if os %}ok{% endif %}")
+            .unwrap();
+        assert_eq!(rendered, "laptop/work/me@example.com/ok");
+    }
+    
+    #[test]
+    fn test_render_unknown_variable_fails() {
+        let vars = TemplateVars::from_config(&Config::default(), Path::new("/home/me"), &Facts::default());
+        let err = vars.render("broken", "{{ vars.missing }}").unwrap_err();
+        assert!(format!("{:#}", err).contains("broken"));
+    }
+}
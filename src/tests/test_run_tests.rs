@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{Config, InstallStatus};
+    use crate::modules::test_run::{self, GroupReport, Outcome, TestRun};
+    use std::path::{Path, PathBuf};
+    
+    fn status(success: bool, error: Option<&str>) -> InstallStatus {
+        InstallStatus {
+            installed: success,
+            success,
+            timestamp: None,
+            error: error.map(str::to_string),
+            attempts: 1,
+            fingerprint: None,
+        }
+    }
+    
+    #[test]
+    fn test_test_run_docker_args() {
+        let run = TestRun {
+            image: test_run::DEFAULT_IMAGE.to_string(),
+            dotfiles: PathBuf::from("/home/me/.local/share/zshrcman/dotfiles"),
+            binary: PathBuf::from("/usr/bin/zshrcman"),
+            device: "laptop".to_string(),
+            groups: vec!["default".to_string(), "brew".to_string()],
+            shell: "/bin/zsh".to_string(),
+        };
+        let args = run.docker_args();
+        assert_eq!(&args[..2], ["run", "--rm"]);
+        assert!(args.contains(&"/home/me/.local/share/zshrcman/dotfiles:/dotfiles:ro".to_string()));
+        assert!(args.contains(&"/usr/bin/zshrcman:/usr/local/bin/zshrcman:ro".to_string()));
+        assert!(args.contains(&"SHELL=/bin/zsh".to_string()));
+        assert_eq!(args[args.len() - 3..], ["laptop", "default,brew", test_run::REPORT_MARKER]);
+        
+        let image = args.iter().position(|arg| arg == test_run::DEFAULT_IMAGE).unwrap();
+        assert_eq!(args[image + 1..image + 3], ["sh", "-c"]);
+        assert!(args[image + 3].contains("zshrcman install --all"));
+    }
+    
+    #[test]
+    fn test_test_run_report() {
+        let mut config = Config::default();
+        config.groups.enabled_global = vec!["default".to_string(), "brew".to_string(), "fonts".to_string()];
+        config.status.insert("default".to_string(), status(true, None));
+        config.status.insert("brew".to_string(), status(false, Some("brew: command not found")));
+        let reports = test_run::parse_report(&toml::to_string(&config).unwrap()).unwrap();
+        
+        assert_eq!(
+            reports,
+            vec![
+                GroupReport {
+                    group: "brew".to_string(),
+                    outcome: Outcome::Failed("brew: command not found".to_string()),
+                },
+                GroupReport {
+                    group: "default".to_string(),
+                    outcome: Outcome::Installed,
+                },
+                GroupReport {
+                    group: "fonts".to_string(),
+                    outcome: Outcome::NotRun,
+                },
+            ]
+        );
+        assert!(test_run::parse_report("").is_err());
+        assert_eq!(test_run::binary(Some(Path::new("/tmp/zshrcman"))).unwrap(), PathBuf::from("/tmp/zshrcman"));
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{PromptKind, Theme};
+    use crate::modules::state_manager::InstallationStateManager;
+    use crate::modules::theme;
+    use crate::tests::temp_config;
+    use std::fs;
+    use std::path::PathBuf;
+    
+    fn starship(config: &str) -> Theme {
+        Theme {
+            prompt: PromptKind::Starship,
+            config: Some(PathBuf::from(config)),
+            url: None,
+        }
+    }
+    
+    #[test]
+    fn test_active_theme_follows_profile_chain() {
+        let (_dir, config_mgr) = temp_config();
+        let mut state_mgr = InstallationStateManager::new(config_mgr);
+        state_mgr.create_profile("work", None).unwrap();
+        state_mgr.create_profile("oncall", Some("work".to_string())).unwrap();
+        state_mgr.profiles.get_mut("work").unwrap().theme = Some("corp".to_string());
+        state_mgr.switch_profile("oncall").unwrap();
+        
+        let mut config = state_mgr.config().config.clone();
+        config.theme = Some("home".to_string());
+        assert_eq!(theme::active_name(&config), Some("corp"));
+        
+        config.active_profile = None;
+        assert_eq!(theme::active_name(&config), Some("home"));
+    }
+    
+    #[test]
+    fn test_apply_writes_and_removes_theme_block() {
+        let (dir, mut config_mgr) = temp_config();
+        let home = dir.path().join("home");
+        fs::create_dir_all(&home).unwrap();
+        config_mgr.config.themes.insert("work".to_string(), starship("themes/work.toml"));
+        config_mgr.config.theme = Some("work".to_string());
+        
+        assert_eq!(theme::apply(&config_mgr, &home).unwrap().as_deref(), Some("work"));
+        let zshrc = fs::read_to_string(home.join(".zshrc")).unwrap();
+        let expected = format!("export STARSHIP_CONFIG='{}'", config_mgr.dotfiles_path().join("themes/work.toml").display());
+        assert!(zshrc.contains(&expected));
+        assert!(zshrc.contains("eval \"$(starship init zsh)\""));
+        
+        config_mgr.config.theme = Some("missing".to_string());
+        assert!(theme::apply(&config_mgr, &home).is_err());
+        
+        config_mgr.config.theme = None;
+        assert_eq!(theme::apply(&config_mgr, &home).unwrap(), None);
+        assert!(!fs::read_to_string(home.join(".zshrc")).unwrap().contains("starship"));
+    }
+}
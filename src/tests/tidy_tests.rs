@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::tidy::{self, Action, Managed};
+    use std::path::Path;
+    
+    const ZSHRC: &str = r#"export EDITOR=vim
+alias ll="ls -la"
+alias gs='git status'
+export PATH="$HOME/.cargo/bin:$PATH"
+path=(/opt/work/bin $path)
+export PATH="/usr/local/go/bin:$PATH"
+# alias ll="ls -l"
+# >>> zshrcman rc >>>
+alias gs='git status'
+# <<< zshrcman rc <<<
+"#;
+    
+    fn managed() -> Managed {
+        let mut managed = Managed::new(Path::new("/home/me"));
+        managed.add_alias("ll", "group 'default'");
+        managed.add_alias("gs", "profile 'work'");
+        managed.add_path("~/.cargo/bin", "profile 'work'");
+        managed.add_path("/opt/work/bin/", "profile 'work'");
+        managed
+    }
+    
+    #[test]
+    fn test_analyze_finds_managed_aliases_and_paths() {
+        let findings = tidy::analyze(ZSHRC, &managed());
+        let lines: Vec<usize> = findings.iter().map(|finding| finding.line).collect();
+        // Comments, zshrcman's own block and unmanaged PATH entries stay.
+        assert_eq!(lines, vec![1, 2, 3, 4]);
+        assert_eq!(findings[0].reason, "alias 'll' is managed by group 'default'");
+        assert!(findings[2].reason.contains("$HOME/.cargo/bin"));
+    }
+    
+    #[test]
+    fn test_apply_comments_out_or_removes() {
+        let findings = tidy::analyze(ZSHRC, &managed());
+        let chosen: Vec<_> = findings.iter().take(2).collect();
+        
+        let commented = tidy::apply(ZSHRC, &chosen, Action::Comment);
+        assert!(commented.contains(&format!("{}alias ll=\"ls -la\"\n", tidy::COMMENT_PREFIX)));
+        assert_eq!(tidy::analyze(&commented, &managed()).len(), 2);
+        
+        let removed = tidy::apply(ZSHRC, &chosen, Action::Remove);
+        assert_eq!(removed.lines().count(), ZSHRC.lines().count() - 2);
+        assert!(removed.starts_with("export EDITOR=vim\nexport PATH="));
+        assert!(removed.ends_with('\n'));
+    }
+    
+    #[test]
+    fn test_alias_name() {
+        assert_eq!(tidy::alias_name("alias ll='ls -la'"), Some("ll"));
+        assert_eq!(tidy::alias_name("alias -g G='| grep'"), Some("G"));
+        assert_eq!(tidy::alias_name("alias"), None);
+        assert_eq!(tidy::alias_name("aliases=1"), None);
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::validate::{Problem, Severity, Validator};
+    use crate::tests::temp_config;
+    use std::fs;
+    use std::path::Path;
+    
+    fn find<'a>(problems: &'a [Problem], file: &Path, path: &str) -> &'a Problem {
+        problems
+            .iter()
+            .find(|problem| problem.file == file && problem.path == path)
+            .unwrap_or_else(|| panic!("no problem at {}: {}; got {:#?}", file.display(), path, problems))
+    }
+    
+    #[test]
+    fn test_validate_reports_unknown_keys_and_dangling_references() {
+        let (_dir, mut config_mgr) = temp_config();
+        let groups_dir = config_mgr.dotfiles_path().join("groups");
+        fs::create_dir_all(&groups_dir).unwrap();
+        let base = groups_dir.join("base.toml");
+        fs::write(&base, "name = \"base\"\nscripts = [\"setup.sh\"]\nextra = true\n").unwrap();
+        
+        config_mgr.config.groups.global = vec!["base".to_string(), "ghost".to_string()];
+        config_mgr.config.groups.enabled_global = vec!["base".to_string()];
+        config_mgr.save().unwrap();
+        let config_path = config_mgr.config_path().to_path_buf();
+        let contents = fs::read_to_string(&config_path).unwrap();
+        fs::write(&config_path, format!("colour = \"red\"\n{}", contents)).unwrap();
+        
+        let problems = Validator::new(&config_path, config_mgr.dotfiles_path()).run();
+        
+        assert_eq!(find(&problems, &config_path, "groups.global[1]").severity, Severity::Error);
+        assert_eq!(find(&problems, &config_path, "colour").severity, Severity::Warning);
+        assert_eq!(find(&problems, &base, "scripts[0]").severity, Severity::Error);
+        assert_eq!(find(&problems, &base, "extra").severity, Severity::Warning);
+        assert!(!problems.iter().any(|problem| problem.path == "groups.global[0]"));
+    }
+    
+    #[test]
+    fn test_validate_reports_type_errors_with_path_and_line() {
+        let (_dir, config_mgr) = temp_config();
+        let groups_dir = config_mgr.dotfiles_path().join("groups");
+        fs::create_dir_all(&groups_dir).unwrap();
+        let base = groups_dir.join("base.toml");
+        fs::write(&base, "name = \"base\"\nscripts = \"setup.sh\"\n").unwrap();
+        
+        let problems = Validator::new(config_mgr.config_path(), config_mgr.dotfiles_path()).run();
+        
+        let problem = find(&problems, &base, "scripts");
+        assert_eq!(problem.severity, Severity::Error);
+        assert!(problem.message.contains("line 2"), "{}", problem.message);
+        
+        // The error is on the table header, at the start of its line.
+        fs::write(&base, "name = \"base\"\n\n[[fonts]]\nsource = \"fonts/x.ttf\"\n").unwrap();
+        let problems = Validator::new(config_mgr.config_path(), config_mgr.dotfiles_path()).run();
+        let problem = find(&problems, &base, "fonts[0]");
+        assert!(problem.message.contains("line 3"), "{}", problem.message);
+    }
+    
+    #[test]
+    fn test_validate_reports_nested_unknown_keys_with_full_path() {
+        let (_dir, config_mgr) = temp_config();
+        let groups_dir = config_mgr.dotfiles_path().join("groups");
+        fs::create_dir_all(&groups_dir).unwrap();
+        let base = groups_dir.join("base.toml");
+        fs::write(&base, "name = \"base\"\n[when]\nos = [\"linux\"]\nfoo = 1\n[[fonts]]\nname = \"x\"\nsource = \"x.ttf\"\nbar = 2\n").unwrap();
+        
+        let problems = Validator::new(config_mgr.config_path(), config_mgr.dotfiles_path()).run();
+        
+        assert_eq!(find(&problems, &base, "when.foo").severity, Severity::Warning);
+        assert_eq!(find(&problems, &base, "fonts[0].bar").severity, Severity::Warning);
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::install::InstallManager;
+    use crate::modules::installer::Installer;
+    use crate::modules::verify::{self, Drift, DriftKind};
+    use crate::tests::temp_config;
+    use anyhow::Result;
+    use std::fs;
+    
+    /// Only `node` counts as installed.
+    struct FakeInstaller;
+    
+    impl Installer for FakeInstaller {
+        fn name(&self) -> &str {
+            "fake"
+        }
+        
+        fn install(&self, _packages: &[String]) -> Result<()> {
+            Ok(())
+        }
+        
+        fn uninstall(&self, _packages: &[String]) -> Result<()> {
+            Ok(())
+        }
+        
+        fn version(&self, package: &str) -> Result<Option<String>> {
+            Ok((package == "node").then(|| "20.1.0".to_string()))
+        }
+    }
+    
+    fn kinds(drift: &[Drift]) -> Vec<(DriftKind, String)> {
+        drift.iter().map(|item| (item.kind, item.subject.to_string())).collect()
+    }
+    
+    #[test]
+    fn test_verify_finds_drift() {
+        let (dir, mut config_mgr) = temp_config();
+        let dotfiles = config_mgr.dotfiles_path().to_path_buf();
+        fs::create_dir_all(dotfiles.join("groups")).unwrap();
+        fs::create_dir_all(dotfiles.join("files")).unwrap();
+        fs::write(
+            dotfiles.join("groups/fake.toml"),
+            "name = \"fake\"\npackages = [\"node\"]\n\n[[files]]\nsource = \"files/tool.conf\"\ntarget = \"~/.tool.conf\"\n\n[[files]]\nsource = \"files/tool.conf\"\ntarget = \".config/tool.conf\"\n",
+        )
+        .unwrap();
+        fs::write(dotfiles.join("files/tool.conf"), "color = true\n").unwrap();
+        config_mgr.add_global_group("fake".to_string()).unwrap();
+        config_mgr.enable_global_group("fake").unwrap();
+        
+        let home = dir.path().join("home");
+        let target = home.join(".tool.conf");
+        let mut manager = InstallManager::new(config_mgr).with_home(home.clone()).with_installer(FakeInstaller);
+        assert!(manager.verify().unwrap().is_empty());
+        manager.install(true).unwrap();
+        assert_eq!(manager.verify().unwrap(), vec![]);
+        // A relative target is relative to the home directory.
+        assert!(home.join(".config/tool.conf").exists());
+        
+        fs::write(&target, "color = false\n").unwrap();
+        let drift = manager.verify().unwrap();
+        assert_eq!(kinds(&drift), vec![(DriftKind::Modified, target.display().to_string())]);
+        assert_eq!(drift[0].owner, "fake");
+        
+        fs::remove_file(&target).unwrap();
+        fs::write(
+            dotfiles.join("groups/fake.toml"),
+            "name = \"fake\"\npackages = [\"node\", \"jq\"]\n\n[[files]]\nsource = \"files/tool.conf\"\ntarget = \"~/.tool.conf\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            kinds(&manager.verify().unwrap()),
+            vec![
+                (DriftKind::OutOfDate, "group fake".to_string()),
+                (DriftKind::Missing, target.display().to_string()),
+                (DriftKind::NotInstalled, "jq".to_string()),
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_verify_blocks_and_links() {
+        assert_eq!(verify::check_block("options", "", None), None);
+        assert_eq!(verify::check_block("options", "setopt auto_cd\n", Some("setopt auto_cd\n")), None);
+        assert_eq!(verify::check_block("options", "setopt auto_cd\n", None).unwrap().kind, DriftKind::Missing);
+        let edited = verify::check_block("options", "setopt auto_cd\n", Some("setopt auto_cd\nsetopt correct\n")).unwrap();
+        assert_eq!((edited.kind, edited.owner.as_str()), (DriftKind::Modified, verify::STARTUP_FILES));
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        let location = dir.path().join("rg");
+        fs::write(&location, "").unwrap();
+        let link = dir.path().join("bin-rg");
+        assert_eq!(verify::check_link("profile 'work'", &link, &location).unwrap().kind, DriftKind::BrokenLink);
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&location, &link).unwrap();
+            assert_eq!(verify::check_link("profile 'work'", &link, &location), None);
+            fs::remove_file(&location).unwrap();
+            assert_eq!(verify::check_link("profile 'work'", &link, &location).unwrap().kind, DriftKind::BrokenLink);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{GroupConfig, OsType};
+    use crate::modules::installer::{CommandInstaller, Installer};
+    use crate::modules::vscode;
+    use std::path::{Path, PathBuf};
+    
+    #[test]
+    fn test_settings_targets_follow_the_os() {
+        let home = Path::new("/home/jane");
+        let target = Path::new("$VSCODE_USER/settings.json");
+        
+        assert_eq!(
+            vscode::resolve_target(target, home, &OsType::Linux),
+            Some(PathBuf::from("/home/jane/.config/Code/User/settings.json"))
+        );
+        assert_eq!(
+            vscode::resolve_target(target, home, &OsType::MacOS),
+            Some(PathBuf::from("/home/jane/Library/Application Support/Code/User/settings.json"))
+        );
+        assert_eq!(
+            vscode::user_dir(home, &OsType::Windows),
+            PathBuf::from("/home/jane/AppData/Roaming/Code/User")
+        );
+        assert_eq!(vscode::resolve_target(Path::new("/etc/hosts"), home, &OsType::Linux), None);
+    }
+    
+    #[test]
+    fn test_vscode_group_installs_extensions() {
+        let group: GroupConfig = toml::from_str(
+            "name = \"vscode-work\"\ninstaller = \"vscode\"\npackages = [\"ms-python.python\"]\nfiles = [{ source = \"vscode/settings.json\", target = \"$VSCODE_USER/settings.json\" }]\n",
+        )
+        .unwrap();
+        assert_eq!(group.installer.as_deref(), Some("vscode"));
+        assert_eq!(group.files[0].target, PathBuf::from("$VSCODE_USER/settings.json"));
+        
+        let installer = CommandInstaller::vscode().dry_run(true);
+        assert_eq!(installer.name(), "vscode");
+        assert!(installer.install(&group.packages).is_ok());
+    }
+}
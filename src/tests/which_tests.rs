@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{Alias, AliasGroup, InstallStatus};
+    use crate::modules::installer::{Installer, InstallerRegistry};
+    use crate::modules::search::Kind;
+    use crate::modules::which;
+    use crate::tests::temp_config;
+    use anyhow::Result;
+    use std::fs;
+    
+    /// Reports only `kubectx` as installed.
+    struct FakeInstaller;
+    
+    impl Installer for FakeInstaller {
+        fn name(&self) -> &str {
+            "fake"
+        }
+        
+        fn install(&self, _packages: &[String]) -> Result<()> {
+            Ok(())
+        }
+        
+        fn uninstall(&self, _packages: &[String]) -> Result<()> {
+            Ok(())
+        }
+        
+        fn version(&self, package: &str) -> Result<Option<String>> {
+            Ok((package == "kubectx").then(|| "0.9.5".to_string()))
+        }
+    }
+    
+    fn installed() -> InstallStatus {
+        InstallStatus {
+            installed: true,
+            success: true,
+            timestamp: None,
+            error: None,
+            attempts: 1,
+            fingerprint: None,
+        }
+    }
+    
+    #[test]
+    fn test_lookup_reports_groups_backend_and_state() {
+        let (_dir, mut config_mgr) = temp_config();
+        let groups = config_mgr.dotfiles_path().join("groups");
+        fs::create_dir_all(&groups).unwrap();
+        fs::write(
+            groups.join("k8s.toml"),
+            "name = \"k8s\"\ninstaller = \"fake\"\npackages = [\"kubectx\"]\naliases = ['alias kubectx=\"kubectx -c\"']\n",
+        )
+        .unwrap();
+        fs::write(groups.join("extra.toml"), "name = \"extra\"\ninstaller = \"fake\"\npackages = [\"kubectx\"]\n").unwrap();
+        
+        let config = &mut config_mgr.config;
+        config.groups.enabled_global.push("k8s".to_string());
+        config.status.insert("k8s".to_string(), installed());
+        config.aliases.insert(
+            "k8s".to_string(),
+            AliasGroup { items: vec![Alias::new("kubectx", "kubectx -c")], active: vec![] },
+        );
+        config.aliases.insert(
+            "aliases".to_string(),
+            AliasGroup { items: vec![Alias::new("kubectx", "kctx")], active: vec![Alias::new("kubectx", "kctx")] },
+        );
+        let mut registry = InstallerRegistry::default();
+        registry.register(FakeInstaller);
+        
+        let found = which::lookup(&config_mgr, &registry, "kubectx").unwrap();
+        let summary: Vec<(Kind, &str, bool, bool, bool)> = found
+            .iter()
+            .map(|d| (d.kind, d.group.as_str(), d.enabled, d.installed, d.active))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Kind::Package, "extra", false, true, false),
+                (Kind::Package, "k8s", true, true, true),
+                // Declared, but toggled off.
+                (Kind::Alias, "k8s", true, true, false),
+                (Kind::Alias, "aliases", false, false, false),
+            ]
+        );
+        assert_eq!(found[1].backend.as_deref(), Some("fake"));
+        assert_eq!(found[1].location, "groups/k8s.toml:3");
+        assert_eq!(found[3].location, "config.toml [aliases.aliases]");
+        
+        assert!(which::lookup(&config_mgr, &registry, "kube").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::EnvironmentState;
+    use crate::modules::windows_env::{self, RegValue, UndoRecord};
+    
+    #[test]
+    fn test_parse_query() {
+        let output = "\r\nHKEY_CURRENT_USER\\Environment\r\n    Path    REG_EXPAND_SZ    %USERPROFILE%\\bin;C:\\Tools\r\n\r\n";
+        assert_eq!(
+            windows_env::parse_query(output, "PATH"),
+            Some(RegValue { kind: "REG_EXPAND_SZ".to_string(), data: "%USERPROFILE%\\bin;C:\\Tools".to_string() })
+        );
+        assert_eq!(
+            windows_env::parse_query("    EMPTY    REG_SZ    \r\n", "EMPTY"),
+            Some(RegValue { kind: "REG_SZ".to_string(), data: String::new() })
+        );
+        assert_eq!(windows_env::parse_query(output, "Pat"), None);
+        assert_eq!(RegValue::new("%APPDATA%\\npm".to_string()).kind, "REG_EXPAND_SZ");
+    }
+    
+    #[test]
+    fn test_user_path_and_undo_record() {
+        let mut env_state = EnvironmentState::default();
+        env_state.paths_prepend.extend(["~/bin".to_string(), "C:\\Work".to_string()]);
+        env_state.paths_append.push("C:\\Tools".to_string());
+        let expand = |path: &str| path.replace('~', "C:\\Users\\me");
+        
+        let (path, ours) = windows_env::user_path(Some("C:\\Tools;C:\\Python;"), &[], &env_state, expand);
+        assert_eq!(path, "C:\\Work;C:\\Users\\me/bin;C:\\Tools;C:\\Python");
+        assert_eq!(ours, ["C:\\Work", "C:\\Users\\me/bin"]);
+        assert_eq!(windows_env::user_path(None, &[], &env_state, expand).0, "C:\\Work;C:\\Users\\me/bin;C:\\Tools");
+        
+        // An installer added C:\Go while the profile was active: switching
+        // again or deactivating keeps it.
+        let current = "C:\\Work;C:\\Users\\me/bin;C:\\Tools;C:\\Python;C:\\Go";
+        assert_eq!(windows_env::user_path(Some(current), &ours, &env_state, expand), (path.clone() + ";C:\\Go", ours.clone()));
+        let cleared = windows_env::user_path(Some(current), &ours, &EnvironmentState::default(), str::to_string);
+        assert_eq!(cleared, ("C:\\Tools;C:\\Python;C:\\Go".to_string(), vec![]));
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = UndoRecord::path(dir.path());
+        let mut record = UndoRecord::load(&path).unwrap();
+        assert!(record.previous.is_empty());
+        record.remember("EDITOR", None);
+        record.remember("Path", Some(RegValue::new("C:\\Python".to_string())));
+        assert_eq!(record.remember("Path", Some(RegValue::new("C:\\Work;C:\\Python".to_string()))).unwrap().data, "C:\\Python");
+        record.path_entries.push("C:\\Work".to_string());
+        record.save(&path).unwrap();
+        assert_eq!(UndoRecord::load(&path).unwrap(), record);
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{InstallSettings, WindowsInstallSettings};
+    use crate::modules::installer::{parse_table_version, parse_version, CommandInstaller, Installer};
+    use crate::modules::windows_packages::WindowsInstaller;
+    
+    fn installer(settings: &WindowsInstallSettings, available: &[&str]) -> WindowsInstaller {
+        let managers = vec![
+            CommandInstaller::winget().dry_run(true),
+            CommandInstaller::scoop().dry_run(true),
+            CommandInstaller::choco().dry_run(true),
+        ];
+        WindowsInstaller::new(settings, managers).with_available(available)
+    }
+    
+    #[test]
+    fn test_windows_list_versions() {
+        let winget = "Name  Id       Version Source\n----------------------------\nGit   Git.Git  2.45.1  winget\n";
+        assert_eq!(parse_table_version(winget, "git.git"), Some("2.45.1".to_string()));
+        assert_eq!(parse_table_version(winget, "Microsoft.PowerToys"), None);
+        assert_eq!(parse_version("git|2.43.0\n", "git"), Some("2.43.0".to_string()));
+    }
+    
+    #[test]
+    fn test_windows_manager_choice() {
+        let mut settings = WindowsInstallSettings::default();
+        settings.packages.insert("Microsoft.PowerToys".to_string(), "winget".to_string());
+        
+        let windows = installer(&settings, &["scoop", "winget"]);
+        assert_eq!(windows.manager_for("git").unwrap().name(), "winget");
+        
+        settings.prefer = vec!["choco".to_string(), "scoop".to_string(), "winget".to_string()];
+        let windows = installer(&settings, &["scoop", "winget"]);
+        assert_eq!(windows.manager_for("git").unwrap().name(), "scoop");
+        assert_eq!(windows.manager_for("Microsoft.PowerToys").unwrap().name(), "winget");
+        
+        let windows = installer(&settings, &["scoop"]);
+        assert!(windows.manager_for("Microsoft.PowerToys").is_err());
+        assert!(installer(&settings, &[]).manager_for("git").is_err());
+    }
+    
+    #[test]
+    fn test_windows_settings_and_dry_run() {
+        let settings: InstallSettings = toml::from_str("[windows]\nprefer = [\"scoop\"]\npackages = { \"Git.Git\" = \"winget\" }\n").unwrap();
+        assert_eq!(settings.windows.prefer, vec!["scoop".to_string()]);
+        assert_eq!(settings.windows.packages["Git.Git"], "winget");
+        assert_eq!(InstallSettings::default().windows, WindowsInstallSettings::default());
+        
+        let windows = installer(&settings.windows, &["scoop", "winget"]);
+        assert_eq!(windows.name(), "windows");
+        assert!(windows.install(&["Git.Git".to_string(), "ripgrep".to_string()]).is_ok());
+        assert!(windows.uninstall(&["ripgrep".to_string()]).is_ok());
+    }
+}
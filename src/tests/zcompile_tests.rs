@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use crate::modules::environment::{EnvironmentManager, ShellType};
+    use crate::modules::zcompile;
+    use std::fs;
+    use tempfile::TempDir;
+    
+    const ZSHRC: &str = "\
+export EDITOR=vim
+# >>> zshrcman options >>>
+setopt autocd
+# <<< zshrcman options <<<
+alias ll='ls -l'
+# >>> zshrcman theme >>>
+eval \"$(starship init zsh)\"
+# <<< zshrcman theme <<<
+";
+
+    #[test]
+    fn test_bundle_and_unbundle_round_trip() {
+        let (rc, blocks) = zcompile::bundle(ZSHRC, "# >>> zshrcman bundle >>>\nsource bundle\n# <<< zshrcman bundle <<<\n").unwrap();
+        assert_eq!(rc, "export EDITOR=vim\n# >>> zshrcman bundle >>>\nsource bundle\n# <<< zshrcman bundle <<<\nalias ll='ls -l'\n");
+        assert!(blocks.starts_with("# >>> zshrcman options >>>\nsetopt autocd\n"));
+        assert!(blocks.ends_with("# <<< zshrcman theme <<<\n"));
+        
+        let restored = zcompile::unbundle(&rc, "# >>> zshrcman bundle >>>", "# <<< zshrcman bundle <<<", &blocks);
+        assert_eq!(restored, "export EDITOR=vim\n# >>> zshrcman options >>>\nsetopt autocd\n# <<< zshrcman options <<<\n# >>> zshrcman theme >>>\neval \"$(starship init zsh)\"\n# <<< zshrcman theme <<<\nalias ll='ls -l'\n");
+        assert!(zcompile::bundle("echo hi\n", "x\n").is_none());
+    }
+    
+    #[test]
+    fn test_block_writes_follow_the_bundle() {
+        let home = TempDir::new().unwrap();
+        fs::write(home.path().join(".zshrc"), ZSHRC).unwrap();
+        let zsh = EnvironmentManager::for_shell(ShellType::Zsh).with_home(home.path().to_path_buf());
+        let bundle = zcompile::bundle_path(home.path());
+        
+        assert!(zsh.set_compiled(true).unwrap());
+        assert!(!zsh.set_compiled(true).unwrap());
+        let zshrc = fs::read_to_string(home.path().join(".zshrc")).unwrap();
+        assert!(zshrc.contains(&zcompile::source_lines()));
+        assert!(!zshrc.contains("setopt autocd"));
+        
+        zsh.write_options("setopt extendedglob\n").unwrap();
+        assert!(fs::read_to_string(&bundle).unwrap().contains("setopt extendedglob"));
+        assert!(zsh.has_options_block().unwrap());
+        
+        assert!(zsh.set_compiled(false).unwrap());
+        assert!(!bundle.exists());
+        let zshrc = fs::read_to_string(home.path().join(".zshrc")).unwrap();
+        assert!(zshrc.contains("setopt extendedglob") && !zshrc.contains("zshrcman bundle"));
+    }
+}
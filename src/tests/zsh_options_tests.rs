@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::GroupConfig;
+    use crate::modules::zsh_options::ZshOptions;
+    
+    fn group(toml: &str) -> GroupConfig {
+        toml::from_str(toml).unwrap()
+    }
+    
+    #[test]
+    fn test_render_options_and_bindings() {
+        let mut options = ZshOptions::new();
+        options.add(
+            "group 'shell'",
+            &group("name = \"shell\"\nsetopt = [\"AUTO_CD\", \"extended_glob\"]\nunsetopt = [\"beep\"]\n[bindkey]\n\"^R\" = \"history-incremental-search-backward\"\n"),
+        );
+        options.add("group 'again'", &group("name = \"again\"\nsetopt = [\"autocd\"]\n"));
+        
+        assert!(options.problems.is_empty());
+        assert_eq!(
+            options.render(),
+            "# zshrcman options and key bindings\n\nsetopt autocd extendedglob\nunsetopt beep\nbindkey '^R' history-incremental-search-backward\n"
+        );
+    }
+    
+    #[test]
+    fn test_contradictions_keep_the_first_setting() {
+        let mut options = ZshOptions::new();
+        options.add("group 'a'", &group("name = \"a\"\nsetopt = [\"beep\"]\n[bindkey]\n\"^R\" = \"a-widget\"\n"));
+        options.add("group 'b'", &group("name = \"b\"\nunsetopt = [\"beep\", \"bad option\"]\n[bindkey]\n\"^R\" = \"b-widget\"\n"));
+        
+        assert_eq!(options.problems.len(), 3);
+        assert!(options.render().contains("setopt beep\nbindkey '^R' a-widget\n"));
+        assert!(ZshOptions::new().render().is_empty());
+    }
+}
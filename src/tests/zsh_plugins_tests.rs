@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::ZshPlugin;
+    use crate::modules::git_mgr::GitManager;
+    use crate::modules::zsh_plugins::{self, Lockfile};
+    use std::fs;
+    use tempfile::TempDir;
+    
+    fn plugin(name: &str, url: &str) -> ZshPlugin {
+        ZshPlugin {
+            name: name.to_string(),
+            url: url.to_string(),
+            reference: None,
+            source: None,
+            defer: false,
+        }
+    }
+    
+    #[test]
+    fn test_sync_keeps_locked_commit_until_update() {
+        let dir = TempDir::new().unwrap();
+        let upstream_path = dir.path().join("zsh-foo");
+        git2::Repository::init(&upstream_path).unwrap().set_head("refs/heads/main").unwrap();
+        let upstream = GitManager::open(&upstream_path).unwrap();
+        fs::write(upstream_path.join("zsh-foo.plugin.zsh"), "foo=1\n").unwrap();
+        upstream.add_all().unwrap();
+        let _ = upstream.commit_and_push("v1", "main");
+        let first = upstream.head_id().unwrap();
+        
+        let data_dir = dir.path().join("data");
+        let dotfiles = dir.path().join("dotfiles");
+        fs::create_dir_all(&dotfiles).unwrap();
+        let foo = plugin("zsh-foo", &upstream_path.display().to_string());
+        let mut lock = Lockfile::default();
+        assert_eq!(zsh_plugins::sync(&data_dir, &mut lock, &foo).unwrap(), first);
+        lock.save(&dotfiles).unwrap();
+        
+        fs::write(upstream_path.join("zsh-foo.plugin.zsh"), "foo=2\n").unwrap();
+        upstream.add_all().unwrap();
+        let _ = upstream.commit_and_push("v2", "main");
+        
+        let mut lock = Lockfile::load(&dotfiles).unwrap();
+        assert_eq!(zsh_plugins::sync(&data_dir, &mut lock, &foo).unwrap(), first);
+        let second = zsh_plugins::update(&data_dir, &mut lock, &foo).unwrap();
+        assert_ne!(second, first);
+        assert_eq!(lock.plugins["zsh-foo"].commit, second);
+        
+        let entry = zsh_plugins::entry_file(&data_dir, &foo).unwrap();
+        assert_eq!(fs::read_to_string(&entry).unwrap(), "foo=2\n");
+        
+        assert!(zsh_plugins::remove(&data_dir, &mut lock, "zsh-foo").unwrap());
+        assert!(!zsh_plugins::checkout_dir(&data_dir, "zsh-foo").exists());
+        assert!(lock.plugins.is_empty());
+    }
+    
+    #[test]
+    fn test_source_lines_defer_after_eager_plugins() {
+        let dir = TempDir::new().unwrap();
+        for name in ["late", "early", "missing"] {
+            let checkout = zsh_plugins::checkout_dir(dir.path(), name);
+            fs::create_dir_all(&checkout).unwrap();
+            if name != "missing" {
+                fs::write(checkout.join(format!("{}.zsh", name)), "").unwrap();
+            }
+        }
+        let mut late = plugin("late", "x");
+        late.defer = true;
+        
+        let script = zsh_plugins::source_lines(dir.path(), &[late, plugin("early", "x"), plugin("missing", "x")]);
+        let early_at = script.find("early.zsh").unwrap();
+        let late_at = script.find("late.zsh").unwrap();
+        assert!(early_at < late_at);
+        assert!(script.contains("add-zsh-hook precmd _zshrcman_deferred_plugins"));
+        assert!(!script.contains("missing"));
+        assert!(zsh_plugins::source_lines(dir.path(), &[]).is_empty());
+    }
+}